@@ -0,0 +1,104 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Statistics of the guest's heap allocator, which the guest reports to
+//! the host.
+
+use bytemuck::{Pod, Zeroable};
+
+/// The name of the built-in guest function that returns the bytes of the
+/// guest's [`GuestAllocatorStats`].
+pub const GUEST_ALLOCATOR_STATS_FUNCTION: &str = "GuestAllocatorStats";
+
+/// Statistics of the guest's heap allocator at one point in time.
+///
+/// The guest heap is a buddy allocator, which rounds every allocation up
+/// to a power of two, so `allocated_bytes` can be well above
+/// `requested_bytes`. An allocation fails when no free block is large
+/// enough for it, which can happen long before the heap is full when the
+/// free memory is fragmented, see [`fragmentation`](Self::fragmentation).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct GuestAllocatorStats {
+    /// The size of the heap.
+    pub total_bytes: u64,
+    /// The bytes taken up by live allocations, including the rounding of
+    /// each up to a power of two.
+    pub allocated_bytes: u64,
+    /// The bytes that live allocations asked for.
+    pub requested_bytes: u64,
+    /// The size of the largest free block, which is the largest allocation
+    /// that can currently succeed.
+    pub largest_free_block: u64,
+}
+
+impl GuestAllocatorStats {
+    /// The bytes of the heap that are not allocated.
+    pub fn free_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.allocated_bytes)
+    }
+
+    /// How fragmented the free memory is, from 0 when it is all in one
+    /// block to nearly 1 when it is split into many small blocks. It is 0
+    /// when the heap is full.
+    pub fn fragmentation(&self) -> f64 {
+        let free = self.free_bytes();
+        if free == 0 {
+            return 0.0;
+        }
+        1.0 - self.largest_free_block.min(free) as f64 / free as f64
+    }
+
+    /// The bytes that the guest sends the host.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+
+    /// Read the statistics from the bytes sent by the guest, returning
+    /// `None` if there are not the right number of them.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytemuck::try_pod_read_unaligned(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_fragmentation() {
+        let stats = GuestAllocatorStats {
+            total_bytes: 1024,
+            allocated_bytes: 512,
+            requested_bytes: 300,
+            largest_free_block: 128,
+        };
+        assert_eq!(
+            GuestAllocatorStats::from_bytes(stats.as_bytes()),
+            Some(stats)
+        );
+        assert_eq!(GuestAllocatorStats::from_bytes(&[0; 8]), None);
+        assert_eq!(stats.free_bytes(), 512);
+        assert_eq!(stats.fragmentation(), 0.75);
+
+        let full = GuestAllocatorStats {
+            allocated_bytes: 1024,
+            largest_free_block: 0,
+            ..stats
+        };
+        assert_eq!(full.fragmentation(), 0.0);
+    }
+}
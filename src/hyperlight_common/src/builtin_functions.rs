@@ -0,0 +1,134 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The names of the built-in functions that guests and hosts call on each
+//! other, which both sides have to agree on.
+
+/// The name of the host function that the host's `HostLink` registers to
+/// let a guest call into another sandbox. Guests reach it through
+/// `hl_call_sandbox` (C API) or `host_comm::call_sandbox` (Rust).
+pub const CALL_SANDBOX_HOST_FUNCTION: &str = "HostLinkCallSandbox";
+
+/// The name of the built-in host function that guests call to fetch the
+/// signature of another registered host function. The host answers it
+/// itself unless the embedder registers a function with the same name.
+pub const HOST_FUNCTION_SIGNATURE: &str = "HostFunctionSignature";
+
+/// The name of the built-in host function that guests call while they are
+/// initialised to fetch the guest function aliases configured on the
+/// host. The host answers it itself unless the embedder registers a
+/// function with the same name.
+pub const GUEST_FUNCTION_ALIASES: &str = "GuestFunctionAliases";
+
+/// The name of the built-in host function that guests call to invoke a
+/// host callback that is scoped to the current call. Its first parameter
+/// is the callback handle, followed by the arguments for the callback.
+/// The host answers it itself unless the embedder registers a function
+/// with the same name.
+pub const INVOKE_HOST_CALLBACK: &str = "InvokeHostCallback";
+
+/// The name of the host function guests call to add to a counter, which
+/// the host registers when it enables guest metrics.
+pub const EMIT_COUNTER: &str = "hl_emit_counter";
+
+/// The name of the host function guests call to set a gauge, which the
+/// host registers when it enables guest metrics.
+pub const EMIT_GAUGE: &str = "hl_emit_gauge";
+
+/// The name of the built-in host function that guests call to refill the
+/// shared ring from a host stream. Its parameter is the stream handle, and
+/// it returns `false` once everything the producer wrote is in the ring.
+/// The host answers it itself unless the embedder registers a function
+/// with the same name.
+pub const READ_HOST_STREAM: &str = "ReadHostStream";
+
+/// The name of the built-in host function that guests call to close a
+/// host stream before reaching its end. The host answers it itself unless
+/// the embedder registers a function with the same name.
+pub const CLOSE_HOST_STREAM: &str = "CloseHostStream";
+
+/// The name of the host function guests call to emit an event, which the
+/// host registers when it enables guest events.
+pub const EMIT_EVENT: &str = "hl_emit_event";
+
+/// The name of the built-in guest function that returns the next chunk of
+/// a guest stream, or `None` once the stream has ended.
+pub const NEXT_GUEST_STREAM_CHUNK: &str = "NextGuestStreamChunk";
+
+/// The name of the built-in guest function that closes a guest stream
+/// before its end.
+pub const CLOSE_GUEST_STREAM: &str = "CloseGuestStream";
+
+/// The name of the built-in host function that guests call to fetch the
+/// request id the host tagged the current call with, if any. The host
+/// answers it itself unless the embedder registers a function with the
+/// same name.
+pub const CURRENT_REQUEST_ID: &str = "hl_request_id";
+
+/// The name of the built-in guest function that returns the schema of
+/// another guest function: the generation of the guest's registered
+/// functions, followed by the function's signature.
+pub const GUEST_FUNCTION_SCHEMA: &str = "GuestFunctionSchema";
+
+/// The name of the built-in host function that guests call to read a
+/// variable of the environment the host set for them. The host answers it
+/// itself unless the embedder registers a function with the same name.
+pub const GUEST_GETENV: &str = "hl_getenv";
+
+/// The name of the built-in host function that guests call to enumerate
+/// the variables of the environment the host set for them. The host
+/// answers it itself unless the embedder registers a function with the
+/// same name.
+pub const GUEST_ENV_NAMES: &str = "hl_guest_env_names";
+
+/// The name of the built-in host function that guests call to read the
+/// host's monotonic clock, in nanoseconds since an arbitrary point that is
+/// the same for every sandbox in the host process. The host answers it
+/// itself unless the embedder registers a function with the same name.
+///
+/// The clock is the host's real monotonic clock, so a guest that uses it
+/// does not behave deterministically. Each reading costs a round trip to
+/// the host, which takes on the order of microseconds, so it is best
+/// suited to timing phases that take much longer than that.
+pub const MONOTONIC_NS: &str = "hl_monotonic_ns";
+
+/// The name of the built-in host function that guests call while they are
+/// initialised to report their progress. The host answers it itself
+/// unless the embedder registers a function with the same name.
+pub const INIT_PROGRESS: &str = "hl_init_progress";
+
+/// The name of the built-in host function that guests call to signal that
+/// they are ready for calls. The host answers it itself unless the
+/// embedder registers a function with the same name.
+pub const SIGNAL_READY: &str = "hl_signal_ready";
+
+/// The name of the built-in guest function that returns the signatures of
+/// all the functions the guest has registered, for diagnostics.
+pub const GUEST_FUNCTION_TABLE: &str = "GuestFunctionTable";
+
+/// The name of the built-in guest function that resumes a suspended
+/// continuation with the host's result, returning the guest function's
+/// result, or `None` if the continuation suspended again.
+pub const RESUME_CONTINUATION: &str = "ResumeContinuation";
+
+/// The name of the built-in guest function that drops a suspended
+/// continuation without resuming it.
+pub const CANCEL_CONTINUATION: &str = "CancelContinuation";
+
+/// The name of the built-in host function that guests call to read the
+/// command-line arguments the host set for them. The host answers it
+/// itself unless the embedder registers a function with the same name.
+pub const GUEST_ARGS: &str = "hl_args";
@@ -18,7 +18,7 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use anyhow::{Error, Result, bail};
-use flatbuffers::{FlatBufferBuilder, WIPOffset, size_prefixed_root};
+use flatbuffers::{FlatBufferBuilder, UnionWIPOffset, WIPOffset, size_prefixed_root};
 #[cfg(feature = "tracing")]
 use tracing::{Span, instrument};
 
@@ -27,9 +27,12 @@ use crate::flatbuffers::hyperlight::generated::{
     FunctionCall as FbFunctionCall, FunctionCallArgs as FbFunctionCallArgs,
     FunctionCallType as FbFunctionCallType, Parameter, ParameterArgs,
     ParameterValue as FbParameterValue, hlbool, hlboolArgs, hldouble, hldoubleArgs, hlfloat,
-    hlfloatArgs, hlint, hlintArgs, hllong, hllongArgs, hlstring, hlstringArgs, hluint, hluintArgs,
-    hlulong, hlulongArgs, hlvecbytes, hlvecbytesArgs,
+    hlfloatArgs, hlint, hlintArgs, hllong, hllongArgs, hlmap, hlmapArgs, hlmapentry,
+    hlmapentryArgs, hloptional, hloptionalArgs, hlstring, hlstringArgs, hluint, hluintArgs,
+    hlulong, hlulongArgs, hlvecbytes, hlvecbytesArgs, hlvecdouble, hlvecdoubleArgs, hlvecfloat,
+    hlvecfloatArgs,
 };
+use crate::func::BorrowedParameterValue;
 
 /// The type of function call.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,6 +53,11 @@ pub struct FunctionCall {
     function_call_type: FunctionCallType,
     /// The return type of the function call
     pub expected_return_type: ReturnType,
+    /// The generation of the guest's registered functions that the
+    /// parameters were validated against, or 0 if they were not. A guest
+    /// function call with the guest's current generation skips verifying
+    /// its parameters.
+    pub schema_generation: u64,
 }
 
 impl FunctionCall {
@@ -65,9 +73,18 @@ impl FunctionCall {
             parameters,
             function_call_type,
             expected_return_type,
+            schema_generation: 0,
         }
     }
 
+    /// Set the generation of the guest's registered functions that the
+    /// parameters were validated against, see
+    /// [`schema_generation`](Self::schema_generation).
+    pub fn with_schema_generation(mut self, schema_generation: u64) -> Self {
+        self.schema_generation = schema_generation;
+        self
+    }
+
     /// The type of the function call.
     pub fn function_call_type(&self) -> FunctionCallType {
         self.function_call_type.clone()
@@ -81,6 +98,44 @@ impl FunctionCall {
     /// does not reset the state of the builder. If you want to reuse the builder,
     /// you'll need to reset it first.
     pub fn encode<'a>(&self, builder: &'a mut FlatBufferBuilder) -> &'a [u8] {
+        self.encode_with(builder, &[], None)
+    }
+
+    /// Encodes the function call as [`encode`](Self::encode) does, with
+    /// `parameters` after its other parameters.
+    ///
+    /// The bytes of a [`BorrowedParameterValue::Bytes`] parameter are
+    /// encoded straight from the borrow, so a caller can pass a byte slice
+    /// without first copying it into a `Vec<u8>`.
+    pub fn encode_borrowed<'a>(
+        &self,
+        builder: &'a mut FlatBufferBuilder,
+        parameters: &[BorrowedParameterValue<'_>],
+    ) -> &'a [u8] {
+        self.encode_with(builder, parameters, None)
+    }
+
+    /// Encodes the function call as [`encode`](Self::encode) does, with an
+    /// extra `VecBytes` parameter after its other parameters whose contents
+    /// are `slices` one after the other.
+    ///
+    /// This lets a caller pass bytes held in several buffers without first
+    /// concatenating them, since they are gathered straight into the
+    /// encoded call.
+    pub fn encode_with_gathered_bytes<'a>(
+        &self,
+        builder: &'a mut FlatBufferBuilder,
+        slices: &[&[u8]],
+    ) -> &'a [u8] {
+        self.encode_with(builder, &[], Some(slices))
+    }
+
+    fn encode_with<'a>(
+        &self,
+        builder: &'a mut FlatBufferBuilder,
+        borrowed: &[BorrowedParameterValue<'_>],
+        gathered_bytes: Option<&[&[u8]]>,
+    ) -> &'a [u8] {
         let function_name = builder.create_string(&self.function_name);
 
         let function_call_type = match self.function_call_type {
@@ -90,115 +145,49 @@ impl FunctionCall {
 
         let expected_return_type = self.expected_return_type.into();
 
-        let parameters = match &self.parameters {
-            Some(p) if !p.is_empty() => {
-                let parameter_offsets: Vec<WIPOffset<Parameter>> = p
-                    .iter()
-                    .map(|param| match param {
-                        ParameterValue::Int(i) => {
-                            let hlint = hlint::create(builder, &hlintArgs { value: *i });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlint,
-                                    value: Some(hlint.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::UInt(ui) => {
-                            let hluint = hluint::create(builder, &hluintArgs { value: *ui });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hluint,
-                                    value: Some(hluint.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::Long(l) => {
-                            let hllong = hllong::create(builder, &hllongArgs { value: *l });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hllong,
-                                    value: Some(hllong.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::ULong(ul) => {
-                            let hlulong = hlulong::create(builder, &hlulongArgs { value: *ul });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlulong,
-                                    value: Some(hlulong.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::Float(f) => {
-                            let hlfloat = hlfloat::create(builder, &hlfloatArgs { value: *f });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlfloat,
-                                    value: Some(hlfloat.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::Double(d) => {
-                            let hldouble = hldouble::create(builder, &hldoubleArgs { value: *d });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hldouble,
-                                    value: Some(hldouble.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::Bool(b) => {
-                            let hlbool = hlbool::create(builder, &hlboolArgs { value: *b });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlbool,
-                                    value: Some(hlbool.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::String(s) => {
-                            let val = builder.create_string(s.as_str());
-                            let hlstring =
-                                hlstring::create(builder, &hlstringArgs { value: Some(val) });
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlstring,
-                                    value: Some(hlstring.as_union_value()),
-                                },
-                            )
-                        }
-                        ParameterValue::VecBytes(v) => {
-                            let vec_bytes = builder.create_vector(v);
-                            let hlvecbytes = hlvecbytes::create(
-                                builder,
-                                &hlvecbytesArgs {
-                                    value: Some(vec_bytes),
-                                },
-                            );
-                            Parameter::create(
-                                builder,
-                                &ParameterArgs {
-                                    value_type: FbParameterValue::hlvecbytes,
-                                    value: Some(hlvecbytes.as_union_value()),
-                                },
-                            )
-                        }
-                    })
-                    .collect();
-                Some(builder.create_vector(&parameter_offsets))
-            }
-            _ => None,
-        };
+        let mut parameter_offsets: Vec<WIPOffset<Parameter>> = self
+            .parameters
+            .iter()
+            .flatten()
+            .map(|param| {
+                let (value_type, value) = create_parameter_value(builder, param);
+                Parameter::create(
+                    builder,
+                    &ParameterArgs {
+                        value_type,
+                        value: Some(value),
+                    },
+                )
+            })
+            .collect();
+        for param in borrowed {
+            let (value_type, value) = match param {
+                BorrowedParameterValue::Value(value) => create_parameter_value(builder, value),
+                BorrowedParameterValue::Bytes(bytes) => (
+                    FbParameterValue::hlvecbytes,
+                    create_vec_bytes(builder, bytes),
+                ),
+            };
+            parameter_offsets.push(Parameter::create(
+                builder,
+                &ParameterArgs {
+                    value_type,
+                    value: Some(value),
+                },
+            ));
+        }
+        if let Some(slices) = gathered_bytes {
+            let value = create_gathered_bytes(builder, slices);
+            parameter_offsets.push(Parameter::create(
+                builder,
+                &ParameterArgs {
+                    value_type: FbParameterValue::hlvecbytes,
+                    value: Some(value),
+                },
+            ));
+        }
+        let parameters =
+            (!parameter_offsets.is_empty()).then(|| builder.create_vector(&parameter_offsets));
 
         let function_call = FbFunctionCall::create(
             builder,
@@ -207,6 +196,7 @@ impl FunctionCall {
                 parameters,
                 function_call_type,
                 expected_return_type,
+                schema_generation: self.schema_generation,
             },
         );
         builder.finish_size_prefixed(function_call, None);
@@ -214,6 +204,143 @@ impl FunctionCall {
     }
 }
 
+/// Encodes the concatenation of `slices` as an `hlvecbytes` member of the
+/// flatbuffer `ParameterValue` union, without concatenating them first.
+fn create_gathered_bytes(
+    builder: &mut FlatBufferBuilder,
+    slices: &[&[u8]],
+) -> WIPOffset<UnionWIPOffset> {
+    let len = slices.iter().map(|slice| slice.len()).sum();
+    // Flatbuffers are built back to front
+    builder.start_vector::<u8>(len);
+    for slice in slices.iter().rev() {
+        for &byte in slice.iter().rev() {
+            builder.push(byte);
+        }
+    }
+    let vec_bytes = builder.end_vector::<u8>(len);
+    hlvecbytes::create(
+        builder,
+        &hlvecbytesArgs {
+            value: Some(vec_bytes),
+        },
+    )
+    .as_union_value()
+}
+
+/// Encodes `bytes` as an `hlvecbytes` member of the flatbuffer
+/// `ParameterValue` union.
+fn create_vec_bytes(builder: &mut FlatBufferBuilder, bytes: &[u8]) -> WIPOffset<UnionWIPOffset> {
+    let vec_bytes = builder.create_vector(bytes);
+    hlvecbytes::create(
+        builder,
+        &hlvecbytesArgs {
+            value: Some(vec_bytes),
+        },
+    )
+    .as_union_value()
+}
+
+/// Encodes `value` as a member of the flatbuffer `ParameterValue` union.
+fn create_parameter_value(
+    builder: &mut FlatBufferBuilder,
+    value: &ParameterValue,
+) -> (FbParameterValue, WIPOffset<UnionWIPOffset>) {
+    match value {
+        ParameterValue::Int(i) => {
+            let hlint = hlint::create(builder, &hlintArgs { value: *i });
+            (FbParameterValue::hlint, hlint.as_union_value())
+        }
+        ParameterValue::UInt(ui) => {
+            let hluint = hluint::create(builder, &hluintArgs { value: *ui });
+            (FbParameterValue::hluint, hluint.as_union_value())
+        }
+        ParameterValue::Long(l) => {
+            let hllong = hllong::create(builder, &hllongArgs { value: *l });
+            (FbParameterValue::hllong, hllong.as_union_value())
+        }
+        ParameterValue::ULong(ul) => {
+            let hlulong = hlulong::create(builder, &hlulongArgs { value: *ul });
+            (FbParameterValue::hlulong, hlulong.as_union_value())
+        }
+        ParameterValue::Float(f) => {
+            let hlfloat = hlfloat::create(builder, &hlfloatArgs { value: *f });
+            (FbParameterValue::hlfloat, hlfloat.as_union_value())
+        }
+        ParameterValue::Double(d) => {
+            let hldouble = hldouble::create(builder, &hldoubleArgs { value: *d });
+            (FbParameterValue::hldouble, hldouble.as_union_value())
+        }
+        ParameterValue::Bool(b) => {
+            let hlbool = hlbool::create(builder, &hlboolArgs { value: *b });
+            (FbParameterValue::hlbool, hlbool.as_union_value())
+        }
+        ParameterValue::String(s) => {
+            let val = builder.create_string(s.as_str());
+            let hlstring = hlstring::create(builder, &hlstringArgs { value: Some(val) });
+            (FbParameterValue::hlstring, hlstring.as_union_value())
+        }
+        ParameterValue::VecBytes(v) => (FbParameterValue::hlvecbytes, create_vec_bytes(builder, v)),
+        ParameterValue::Optional(v) => {
+            // An absent value is encoded by leaving the union unset
+            let (value_type, value) = match v {
+                Some(v) => {
+                    let (value_type, value) = create_parameter_value(builder, v);
+                    (value_type, Some(value))
+                }
+                None => (FbParameterValue::NONE, None),
+            };
+            let hloptional = hloptional::create(builder, &hloptionalArgs { value_type, value });
+            (FbParameterValue::hloptional, hloptional.as_union_value())
+        }
+        ParameterValue::VecFloat(v) => {
+            let vec_floats = builder.create_vector(v);
+            let hlvecfloat = hlvecfloat::create(
+                builder,
+                &hlvecfloatArgs {
+                    value: Some(vec_floats),
+                },
+            );
+            (FbParameterValue::hlvecfloat, hlvecfloat.as_union_value())
+        }
+        ParameterValue::VecDouble(v) => {
+            let vec_doubles = builder.create_vector(v);
+            let hlvecdouble = hlvecdouble::create(
+                builder,
+                &hlvecdoubleArgs {
+                    value: Some(vec_doubles),
+                },
+            );
+            (FbParameterValue::hlvecdouble, hlvecdouble.as_union_value())
+        }
+        ParameterValue::Map(entries) => {
+            let entry_offsets: Vec<WIPOffset<hlmapentry>> = entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = builder.create_string(key);
+                    let (value_type, value) = create_parameter_value(builder, value);
+                    hlmapentry::create(
+                        builder,
+                        &hlmapentryArgs {
+                            key: Some(key),
+                            value_type,
+                            value: Some(value),
+                        },
+                    )
+                })
+                .collect();
+            let entries = builder.create_vector(&entry_offsets);
+            let hlmap = hlmap::create(
+                builder,
+                &hlmapArgs {
+                    value: Some(entries),
+                },
+            );
+            (FbParameterValue::hlmap, hlmap.as_union_value())
+        }
+    }
+}
+
 #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
 pub fn validate_guest_function_call_buffer(function_call_buffer: &[u8]) -> Result<()> {
     let guest_function_call_fb = size_prefixed_root::<FbFunctionCall>(function_call_buffer)
@@ -268,12 +395,14 @@ impl TryFrom<&[u8]> for FunctionCall {
             parameters,
             function_call_type,
             expected_return_type,
+            schema_generation: function_call_fb.schema_generation(),
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::boxed::Box;
     use alloc::vec;
 
     use super::*;
@@ -324,6 +453,175 @@ mod tests {
         ];
         assert!(expected_parameters == parameters);
         assert_eq!(function_call.function_call_type, FunctionCallType::Guest);
+        assert_eq!(function_call.schema_generation, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema_generation_round_trip() -> Result<()> {
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Echo".to_string(),
+            Some(vec![ParameterValue::String("hi".to_string())]),
+            FunctionCallType::Guest,
+            ReturnType::String,
+        )
+        .with_schema_generation(42)
+        .encode(&mut builder);
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(function_call.schema_generation, 42);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gathered_bytes_round_trip() -> Result<()> {
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Gather".to_string(),
+            Some(vec![ParameterValue::Int(1)]),
+            FunctionCallType::Guest,
+            ReturnType::VecBytes,
+        )
+        .encode_with_gathered_bytes(&mut builder, &[b"hello", b"", b", ", b"world"]);
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(
+            function_call.parameters,
+            Some(vec![
+                ParameterValue::Int(1),
+                ParameterValue::VecBytes(b"hello, world".to_vec()),
+            ])
+        );
+
+        // With no slices the parameter is empty
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Gather".to_string(),
+            None,
+            FunctionCallType::Guest,
+            ReturnType::VecBytes,
+        )
+        .encode_with_gathered_bytes(&mut builder, &[]);
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(
+            function_call.parameters,
+            Some(vec![ParameterValue::VecBytes(vec![])])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn borrowed_parameters_round_trip() -> Result<()> {
+        use crate::func::BorrowedParameterTuple;
+
+        let bytes = b"hello".to_vec();
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Borrow".to_string(),
+            None,
+            FunctionCallType::Guest,
+            ReturnType::VecBytes,
+        )
+        .encode_borrowed(
+            &mut builder,
+            &(bytes.as_slice(), 7u32, &b""[..]).into_borrowed_values(),
+        );
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(
+            function_call.parameters,
+            Some(vec![
+                ParameterValue::VecBytes(bytes),
+                ParameterValue::UInt(7),
+                ParameterValue::VecBytes(vec![]),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn optional_parameters_round_trip() -> Result<()> {
+        let parameters = vec![
+            ParameterValue::Optional(None),
+            ParameterValue::Optional(Some(Box::new(ParameterValue::Int(1)))),
+            ParameterValue::Optional(Some(Box::new(ParameterValue::String("2".to_string())))),
+            ParameterValue::Optional(Some(Box::new(ParameterValue::Optional(None)))),
+        ];
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Optional".to_string(),
+            Some(parameters.clone()),
+            FunctionCallType::Guest,
+            ReturnType::Void,
+        )
+        .encode(&mut builder);
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(function_call.parameters, Some(parameters));
+
+        Ok(())
+    }
+
+    #[test]
+    fn float_array_parameters_round_trip() -> Result<()> {
+        let parameters = vec![
+            ParameterValue::VecFloat(vec![]),
+            ParameterValue::VecFloat(vec![1.5, f32::INFINITY, f32::MIN]),
+            ParameterValue::VecDouble(vec![-0.25, f64::NEG_INFINITY, f64::EPSILON]),
+        ];
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "FloatArrays".to_string(),
+            Some(parameters.clone()),
+            FunctionCallType::Guest,
+            ReturnType::VecDouble,
+        )
+        .encode(&mut builder);
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(function_call.parameters, Some(parameters));
+        assert_eq!(function_call.expected_return_type, ReturnType::VecDouble);
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_parameters_round_trip() -> Result<()> {
+        let parameters = vec![
+            ParameterValue::Map(vec![]),
+            ParameterValue::Map(vec![
+                (
+                    "name".to_string(),
+                    ParameterValue::String("widget".to_string()),
+                ),
+                ("count".to_string(), ParameterValue::Int(3)),
+                ("count".to_string(), ParameterValue::ULong(4)),
+                ("missing".to_string(), ParameterValue::Optional(None)),
+                (
+                    "nested".to_string(),
+                    ParameterValue::Map(vec![(
+                        "bytes".to_string(),
+                        ParameterValue::VecBytes(vec![1, 2]),
+                    )]),
+                ),
+            ]),
+        ];
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCall::new(
+            "Maps".to_string(),
+            Some(parameters.clone()),
+            FunctionCallType::Guest,
+            ReturnType::Void,
+        )
+        .encode(&mut builder);
+
+        let function_call = FunctionCall::try_from(test_data)?;
+        assert_eq!(function_call.parameters, Some(parameters));
 
         Ok(())
     }
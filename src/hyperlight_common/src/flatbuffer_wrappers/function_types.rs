@@ -14,11 +14,13 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use anyhow::{Error, Result, anyhow, bail};
-use flatbuffers::size_prefixed_root;
+use flatbuffers::{UnionWIPOffset, WIPOffset, size_prefixed_root};
 #[cfg(feature = "tracing")]
 use tracing::{Span, instrument};
 
@@ -28,9 +30,11 @@ use crate::flatbuffers::hyperlight::generated::{
     FunctionCallResultType, Parameter, ParameterType as FbParameterType,
     ParameterValue as FbParameterValue, ReturnType as FbReturnType, ReturnValue as FbReturnValue,
     ReturnValueBox, ReturnValueBoxArgs, hlbool, hlboolArgs, hldouble, hldoubleArgs, hlfloat,
-    hlfloatArgs, hlint, hlintArgs, hllong, hllongArgs, hlsizeprefixedbuffer,
-    hlsizeprefixedbufferArgs, hlstring, hlstringArgs, hluint, hluintArgs, hlulong, hlulongArgs,
-    hlvoid, hlvoidArgs,
+    hlfloatArgs, hlint, hlintArgs, hllong, hllongArgs, hlmap, hlmultibuffer, hlmultibufferArgs,
+    hloptional, hloptionalreturn, hloptionalreturnArgs, hlsizeprefixedbuffer,
+    hlsizeprefixedbufferArgs, hlstatuswithpayload, hlstatuswithpayloadArgs, hlstring, hlstringArgs,
+    hluint, hluintArgs, hlulong, hlulongArgs, hlvecbytes, hlvecbytesArgs, hlvecdouble,
+    hlvecdoubleArgs, hlvecfloat, hlvecfloatArgs, hlvoid, hlvoidArgs,
 };
 
 pub struct FunctionCallResult(core::result::Result<ReturnValue, GuestError>);
@@ -47,61 +51,14 @@ impl FunctionCallResult {
         match &self.0 {
             Ok(rv) => {
                 // Encode ReturnValue as ReturnValueBox
-                let (value, value_type) = match rv {
-                    ReturnValue::Int(i) => {
-                        let off = hlint::create(builder, &hlintArgs { value: *i });
-                        (Some(off.as_union_value()), FbReturnValue::hlint)
-                    }
-                    ReturnValue::UInt(ui) => {
-                        let off = hluint::create(builder, &hluintArgs { value: *ui });
-                        (Some(off.as_union_value()), FbReturnValue::hluint)
-                    }
-                    ReturnValue::Long(l) => {
-                        let off = hllong::create(builder, &hllongArgs { value: *l });
-                        (Some(off.as_union_value()), FbReturnValue::hllong)
-                    }
-                    ReturnValue::ULong(ul) => {
-                        let off = hlulong::create(builder, &hlulongArgs { value: *ul });
-                        (Some(off.as_union_value()), FbReturnValue::hlulong)
-                    }
-                    ReturnValue::Float(f) => {
-                        let off = hlfloat::create(builder, &hlfloatArgs { value: *f });
-                        (Some(off.as_union_value()), FbReturnValue::hlfloat)
-                    }
-                    ReturnValue::Double(d) => {
-                        let off = hldouble::create(builder, &hldoubleArgs { value: *d });
-                        (Some(off.as_union_value()), FbReturnValue::hldouble)
-                    }
-                    ReturnValue::Bool(b) => {
-                        let off = hlbool::create(builder, &hlboolArgs { value: *b });
-                        (Some(off.as_union_value()), FbReturnValue::hlbool)
-                    }
-                    ReturnValue::String(s) => {
-                        let val = builder.create_string(s.as_str());
-                        let off = hlstring::create(builder, &hlstringArgs { value: Some(val) });
-                        (Some(off.as_union_value()), FbReturnValue::hlstring)
-                    }
-                    ReturnValue::VecBytes(v) => {
-                        let val = builder.create_vector(v);
-                        let off = hlsizeprefixedbuffer::create(
-                            builder,
-                            &hlsizeprefixedbufferArgs {
-                                value: Some(val),
-                                size: v.len() as i32,
-                            },
-                        );
-                        (
-                            Some(off.as_union_value()),
-                            FbReturnValue::hlsizeprefixedbuffer,
-                        )
-                    }
-                    ReturnValue::Void(()) => {
-                        let off = hlvoid::create(builder, &hlvoidArgs {});
-                        (Some(off.as_union_value()), FbReturnValue::hlvoid)
-                    }
-                };
-                let rv_box =
-                    ReturnValueBox::create(builder, &ReturnValueBoxArgs { value, value_type });
+                let (value_type, value) = create_return_value(builder, rv);
+                let rv_box = ReturnValueBox::create(
+                    builder,
+                    &ReturnValueBoxArgs {
+                        value: Some(value),
+                        value_type,
+                    },
+                );
                 let fcr = FbFunctionCallResult::create(
                     builder,
                     &FbFunctionCallResultArgs {
@@ -144,6 +101,179 @@ impl FunctionCallResult {
     }
 }
 
+/// Encodes `value` as a member of the flatbuffer `ReturnValue` union.
+fn create_return_value(
+    builder: &mut flatbuffers::FlatBufferBuilder,
+    value: &ReturnValue,
+) -> (FbReturnValue, WIPOffset<UnionWIPOffset>) {
+    match value {
+        ReturnValue::Int(i) => {
+            let off = hlint::create(builder, &hlintArgs { value: *i });
+            (FbReturnValue::hlint, off.as_union_value())
+        }
+        ReturnValue::UInt(ui) => {
+            let off = hluint::create(builder, &hluintArgs { value: *ui });
+            (FbReturnValue::hluint, off.as_union_value())
+        }
+        ReturnValue::Long(l) => {
+            let off = hllong::create(builder, &hllongArgs { value: *l });
+            (FbReturnValue::hllong, off.as_union_value())
+        }
+        ReturnValue::ULong(ul) => {
+            let off = hlulong::create(builder, &hlulongArgs { value: *ul });
+            (FbReturnValue::hlulong, off.as_union_value())
+        }
+        ReturnValue::Float(f) => {
+            let off = hlfloat::create(builder, &hlfloatArgs { value: *f });
+            (FbReturnValue::hlfloat, off.as_union_value())
+        }
+        ReturnValue::Double(d) => {
+            let off = hldouble::create(builder, &hldoubleArgs { value: *d });
+            (FbReturnValue::hldouble, off.as_union_value())
+        }
+        ReturnValue::Bool(b) => {
+            let off = hlbool::create(builder, &hlboolArgs { value: *b });
+            (FbReturnValue::hlbool, off.as_union_value())
+        }
+        ReturnValue::String(s) => {
+            let val = builder.create_string(s.as_str());
+            let off = hlstring::create(builder, &hlstringArgs { value: Some(val) });
+            (FbReturnValue::hlstring, off.as_union_value())
+        }
+        ReturnValue::VecBytes(v) => {
+            let val = builder.create_vector(v);
+            let off = hlsizeprefixedbuffer::create(
+                builder,
+                &hlsizeprefixedbufferArgs {
+                    value: Some(val),
+                    size: v.len() as i32,
+                },
+            );
+            (FbReturnValue::hlsizeprefixedbuffer, off.as_union_value())
+        }
+        ReturnValue::Void(()) => {
+            let off = hlvoid::create(builder, &hlvoidArgs {});
+            (FbReturnValue::hlvoid, off.as_union_value())
+        }
+        ReturnValue::Optional(v) => {
+            // An absent value is encoded by leaving the union unset
+            let (value_type, value) = match v {
+                Some(v) => {
+                    let (value_type, value) = create_return_value(builder, v);
+                    (value_type, Some(value))
+                }
+                None => (FbReturnValue::NONE, None),
+            };
+            let off =
+                hloptionalreturn::create(builder, &hloptionalreturnArgs { value_type, value });
+            (FbReturnValue::hloptionalreturn, off.as_union_value())
+        }
+        ReturnValue::VecFloat(v) => {
+            let val = builder.create_vector(v);
+            let off = hlvecfloat::create(builder, &hlvecfloatArgs { value: Some(val) });
+            (FbReturnValue::hlvecfloat, off.as_union_value())
+        }
+        ReturnValue::VecDouble(v) => {
+            let val = builder.create_vector(v);
+            let off = hlvecdouble::create(builder, &hlvecdoubleArgs { value: Some(val) });
+            (FbReturnValue::hlvecdouble, off.as_union_value())
+        }
+        ReturnValue::MultiBuffer(buffers) => {
+            let buffers: Vec<_> = buffers
+                .iter()
+                .map(|buffer| {
+                    let val = builder.create_vector(buffer);
+                    hlvecbytes::create(builder, &hlvecbytesArgs { value: Some(val) })
+                })
+                .collect();
+            let val = builder.create_vector(&buffers);
+            let off = hlmultibuffer::create(builder, &hlmultibufferArgs { value: Some(val) });
+            (FbReturnValue::hlmultibuffer, off.as_union_value())
+        }
+        ReturnValue::StatusWithPayload { code, data } => {
+            let data = builder.create_vector(data);
+            let off = hlstatuswithpayload::create(
+                builder,
+                &hlstatuswithpayloadArgs {
+                    code: *code,
+                    data: Some(data),
+                },
+            );
+            (FbReturnValue::hlstatuswithpayload, off.as_union_value())
+        }
+    }
+}
+
+impl FunctionCallResult {
+    /// Locates the value of the encoded function call result `value`, which
+    /// must be a string or a byte vector, without copying it.
+    ///
+    /// Returns the type of the value and the range of `value` that holds
+    /// its bytes, or the guest error the result holds.
+    pub fn locate_bytes(
+        value: &[u8],
+    ) -> Result<core::result::Result<(ReturnType, Range<usize>), GuestError>> {
+        let function_call_result_fb = size_prefixed_root::<FbFunctionCallResult>(value)
+            .map_err(|e| anyhow!("Failed to get FunctionCallResult from bytes: {:?}", e))?;
+
+        let boxed = match function_call_result_fb.result_type() {
+            FunctionCallResultType::ReturnValueBox => function_call_result_fb
+                .result_as_return_value_box()
+                .ok_or_else(|| anyhow!("Failed to get ReturnValueBox from function call result"))?,
+            FunctionCallResultType::GuestError => {
+                return Ok(Err(guest_error_from(function_call_result_fb)?));
+            }
+            other => {
+                bail!("Unexpected function call result type: {:?}", other)
+            }
+        };
+        let (return_type, bytes) = match boxed.value_type() {
+            FbReturnValue::hlstring => (
+                ReturnType::String,
+                boxed
+                    .value_as_hlstring()
+                    .and_then(|s| s.value())
+                    .map(str::as_bytes),
+            ),
+            FbReturnValue::hlsizeprefixedbuffer => (
+                ReturnType::VecBytes,
+                boxed
+                    .value_as_hlsizeprefixedbuffer()
+                    .and_then(|b| b.value())
+                    .map(|v| v.bytes()),
+            ),
+            other => {
+                bail!(
+                    "Expected a string or byte vector return value, got {:?}",
+                    other
+                )
+            }
+        };
+        // A missing value is empty, as when decoding a `ReturnValue`
+        let range = match bytes {
+            Some(bytes) => {
+                let start = bytes.as_ptr() as usize - value.as_ptr() as usize;
+                start..start + bytes.len()
+            }
+            None => 0..0,
+        };
+        Ok(Ok((return_type, range)))
+    }
+}
+
+/// Decodes the guest error held by a function call result.
+fn guest_error_from(function_call_result_fb: FbFunctionCallResult<'_>) -> Result<GuestError> {
+    let guest_error_table = function_call_result_fb
+        .result_as_guest_error()
+        .ok_or_else(|| anyhow!("Failed to get GuestError from function call result"))?;
+    let code = guest_error_table.code();
+    let message = guest_error_table
+        .message()
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+    Ok(GuestError::new(code.into(), message))
+}
+
 impl TryFrom<&[u8]> for FunctionCallResult {
     type Error = Error;
 
@@ -161,20 +291,9 @@ impl TryFrom<&[u8]> for FunctionCallResult {
                 let return_value = ReturnValue::try_from(boxed)?;
                 Ok(FunctionCallResult(Ok(return_value)))
             }
-            FunctionCallResultType::GuestError => {
-                let guest_error_table = function_call_result_fb
-                    .result_as_guest_error()
-                    .ok_or_else(|| anyhow!("Failed to get GuestError from function call result"))?;
-                let code = guest_error_table.code();
-                let message = guest_error_table
-                    .message()
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-                Ok(FunctionCallResult(Err(GuestError::new(
-                    code.into(),
-                    message,
-                ))))
-            }
+            FunctionCallResultType::GuestError => Ok(FunctionCallResult(Err(guest_error_from(
+                function_call_result_fb,
+            )?))),
             other => {
                 bail!("Unexpected function call result type: {:?}", other)
             }
@@ -204,6 +323,15 @@ pub enum ParameterValue {
     Bool(bool),
     /// `Vec<u8>`
     VecBytes(Vec<u8>),
+    /// `Option<T>`, where `None` is an absent value
+    Optional(Option<Box<ParameterValue>>),
+    /// `Vec<f32>`
+    VecFloat(Vec<f32>),
+    /// `Vec<f64>`
+    VecDouble(Vec<f64>),
+    /// A map from names to values, in the order the entries were given.
+    /// Names need not be unique.
+    Map(Vec<(String, ParameterValue)>),
 }
 
 /// Supported parameter types for function calling.
@@ -228,6 +356,16 @@ pub enum ParameterType {
     Bool,
     /// `Vec<u8>`
     VecBytes,
+    /// `Option<T>`. The type of the value, when present, is checked when
+    /// it is converted to `T`.
+    Optional,
+    /// `Vec<f32>`
+    VecFloat,
+    /// `Vec<f64>`
+    VecDouble,
+    /// `Vec<(String, ParameterValue)>`. The types of the values are checked
+    /// when they are read.
+    Map,
 }
 
 /// Supported return types with values from function calling.
@@ -253,6 +391,21 @@ pub enum ReturnValue {
     Void(()),
     /// `Vec<u8>`
     VecBytes(Vec<u8>),
+    /// `Option<T>`, where `None` is an absent value
+    Optional(Option<Box<ReturnValue>>),
+    /// `Vec<f32>`
+    VecFloat(Vec<f32>),
+    /// `Vec<f64>`
+    VecDouble(Vec<f64>),
+    /// `Vec<Vec<u8>>`, several independent byte buffers
+    MultiBuffer(Vec<Vec<u8>>),
+    /// `(i32, Vec<u8>)`, a status code together with a payload
+    StatusWithPayload {
+        /// The status code
+        code: i32,
+        /// The payload
+        data: Vec<u8>,
+    },
 }
 
 /// Supported return types from function calling.
@@ -281,6 +434,17 @@ pub enum ReturnType {
     Void,
     /// `Vec<u8>`
     VecBytes,
+    /// `Option<T>`. The type of the value, when present, is checked when
+    /// it is converted to `T`.
+    Optional,
+    /// `Vec<f32>`
+    VecFloat,
+    /// `Vec<f64>`
+    VecDouble,
+    /// `Vec<Vec<u8>>`, several independent byte buffers
+    MultiBuffer,
+    /// `(i32, Vec<u8>)`, a status code together with a payload
+    StatusWithPayload,
 }
 
 impl From<&ParameterValue> for ParameterType {
@@ -296,49 +460,102 @@ impl From<&ParameterValue> for ParameterType {
             ParameterValue::String(_) => ParameterType::String,
             ParameterValue::Bool(_) => ParameterType::Bool,
             ParameterValue::VecBytes(_) => ParameterType::VecBytes,
+            ParameterValue::Optional(_) => ParameterType::Optional,
+            ParameterValue::VecFloat(_) => ParameterType::VecFloat,
+            ParameterValue::VecDouble(_) => ParameterType::VecDouble,
+            ParameterValue::Map(_) => ParameterType::Map,
         }
     }
 }
 
-impl TryFrom<Parameter<'_>> for ParameterValue {
-    type Error = Error;
-
-    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
-    fn try_from(param: Parameter<'_>) -> Result<Self> {
-        let value = param.value_type();
-        let result = match value {
-            FbParameterValue::hlint => param
+/// Decodes the member of the flatbuffer `ParameterValue` union held by
+/// `$table`, which is a `Parameter`, an `hloptional` or an `hlmapentry`.
+macro_rules! parameter_value_from_union {
+    ($table:expr) => {
+        match $table.value_type() {
+            FbParameterValue::hlint => $table
                 .value_as_hlint()
                 .map(|hlint| ParameterValue::Int(hlint.value())),
-            FbParameterValue::hluint => param
+            FbParameterValue::hluint => $table
                 .value_as_hluint()
                 .map(|hluint| ParameterValue::UInt(hluint.value())),
-            FbParameterValue::hllong => param
+            FbParameterValue::hllong => $table
                 .value_as_hllong()
                 .map(|hllong| ParameterValue::Long(hllong.value())),
-            FbParameterValue::hlulong => param
+            FbParameterValue::hlulong => $table
                 .value_as_hlulong()
                 .map(|hlulong| ParameterValue::ULong(hlulong.value())),
-            FbParameterValue::hlfloat => param
+            FbParameterValue::hlfloat => $table
                 .value_as_hlfloat()
                 .map(|hlfloat| ParameterValue::Float(hlfloat.value())),
-            FbParameterValue::hldouble => param
+            FbParameterValue::hldouble => $table
                 .value_as_hldouble()
                 .map(|hldouble| ParameterValue::Double(hldouble.value())),
-            FbParameterValue::hlbool => param
+            FbParameterValue::hlbool => $table
                 .value_as_hlbool()
                 .map(|hlbool| ParameterValue::Bool(hlbool.value())),
-            FbParameterValue::hlstring => param.value_as_hlstring().map(|hlstring| {
+            FbParameterValue::hlstring => $table.value_as_hlstring().map(|hlstring| {
                 ParameterValue::String(hlstring.value().unwrap_or_default().to_string())
             }),
-            FbParameterValue::hlvecbytes => param.value_as_hlvecbytes().map(|hlvecbytes| {
+            FbParameterValue::hlvecbytes => $table.value_as_hlvecbytes().map(|hlvecbytes| {
                 ParameterValue::VecBytes(hlvecbytes.value().unwrap_or_default().bytes().to_vec())
             }),
+            FbParameterValue::hloptional => $table
+                .value_as_hloptional()
+                .map(ParameterValue::try_from)
+                .transpose()?,
+            FbParameterValue::hlvecfloat => $table.value_as_hlvecfloat().map(|hlvecfloat| {
+                ParameterValue::VecFloat(hlvecfloat.value().unwrap_or_default().iter().collect())
+            }),
+            FbParameterValue::hlvecdouble => $table.value_as_hlvecdouble().map(|hlvecdouble| {
+                ParameterValue::VecDouble(hlvecdouble.value().unwrap_or_default().iter().collect())
+            }),
+            FbParameterValue::hlmap => $table
+                .value_as_hlmap()
+                .map(ParameterValue::try_from)
+                .transpose()?,
             other => {
                 bail!("Unexpected flatbuffer parameter value type: {:?}", other);
             }
-        };
-        result.ok_or_else(|| anyhow!("Failed to get parameter value"))
+        }
+        .ok_or_else(|| anyhow!("Failed to get parameter value"))
+    };
+}
+
+impl TryFrom<Parameter<'_>> for ParameterValue {
+    type Error = Error;
+
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(param: Parameter<'_>) -> Result<Self> {
+        parameter_value_from_union!(param)
+    }
+}
+
+impl TryFrom<hloptional<'_>> for ParameterValue {
+    type Error = Error;
+
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(optional: hloptional<'_>) -> Result<Self> {
+        if optional.value_type() == FbParameterValue::NONE {
+            return Ok(ParameterValue::Optional(None));
+        }
+        let value = parameter_value_from_union!(optional)?;
+        Ok(ParameterValue::Optional(Some(Box::new(value))))
+    }
+}
+
+impl TryFrom<hlmap<'_>> for ParameterValue {
+    type Error = Error;
+
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(map: hlmap<'_>) -> Result<Self> {
+        let entries = map
+            .value()
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| Ok((entry.key().to_string(), parameter_value_from_union!(entry)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ParameterValue::Map(entries))
     }
 }
 
@@ -355,6 +572,10 @@ impl From<ParameterType> for FbParameterType {
             ParameterType::String => FbParameterType::hlstring,
             ParameterType::Bool => FbParameterType::hlbool,
             ParameterType::VecBytes => FbParameterType::hlvecbytes,
+            ParameterType::Optional => FbParameterType::hloptional,
+            ParameterType::VecFloat => FbParameterType::hlvecfloat,
+            ParameterType::VecDouble => FbParameterType::hlvecdouble,
+            ParameterType::Map => FbParameterType::hlmap,
         }
     }
 }
@@ -373,6 +594,11 @@ impl From<ReturnType> for FbReturnType {
             ReturnType::Bool => FbReturnType::hlbool,
             ReturnType::Void => FbReturnType::hlvoid,
             ReturnType::VecBytes => FbReturnType::hlsizeprefixedbuffer,
+            ReturnType::Optional => FbReturnType::hloptional,
+            ReturnType::VecFloat => FbReturnType::hlvecfloat,
+            ReturnType::VecDouble => FbReturnType::hlvecdouble,
+            ReturnType::MultiBuffer => FbReturnType::hlmultibuffer,
+            ReturnType::StatusWithPayload => FbReturnType::hlstatuswithpayload,
         }
     }
 }
@@ -391,6 +617,10 @@ impl TryFrom<FbParameterType> for ParameterType {
             FbParameterType::hlstring => Ok(ParameterType::String),
             FbParameterType::hlbool => Ok(ParameterType::Bool),
             FbParameterType::hlvecbytes => Ok(ParameterType::VecBytes),
+            FbParameterType::hloptional => Ok(ParameterType::Optional),
+            FbParameterType::hlvecfloat => Ok(ParameterType::VecFloat),
+            FbParameterType::hlvecdouble => Ok(ParameterType::VecDouble),
+            FbParameterType::hlmap => Ok(ParameterType::Map),
             _ => {
                 bail!("Unexpected flatbuffer parameter type: {:?}", value)
             }
@@ -413,6 +643,11 @@ impl TryFrom<FbReturnType> for ReturnType {
             FbReturnType::hlbool => Ok(ReturnType::Bool),
             FbReturnType::hlvoid => Ok(ReturnType::Void),
             FbReturnType::hlsizeprefixedbuffer => Ok(ReturnType::VecBytes),
+            FbReturnType::hloptional => Ok(ReturnType::Optional),
+            FbReturnType::hlvecfloat => Ok(ReturnType::VecFloat),
+            FbReturnType::hlvecdouble => Ok(ReturnType::VecDouble),
+            FbReturnType::hlmultibuffer => Ok(ReturnType::MultiBuffer),
+            FbReturnType::hlstatuswithpayload => Ok(ReturnType::StatusWithPayload),
             _ => {
                 bail!("Unexpected flatbuffer return type: {:?}", value)
             }
@@ -537,6 +772,45 @@ impl TryFrom<ParameterValue> for Vec<u8> {
     }
 }
 
+impl TryFrom<ParameterValue> for Vec<f32> {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ParameterValue) -> Result<Self> {
+        match value {
+            ParameterValue::VecFloat(v) => Ok(v),
+            _ => {
+                bail!("Unexpected parameter value type: {:?}", value)
+            }
+        }
+    }
+}
+
+impl TryFrom<ParameterValue> for Vec<f64> {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ParameterValue) -> Result<Self> {
+        match value {
+            ParameterValue::VecDouble(v) => Ok(v),
+            _ => {
+                bail!("Unexpected parameter value type: {:?}", value)
+            }
+        }
+    }
+}
+
+impl TryFrom<ParameterValue> for Vec<(String, ParameterValue)> {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ParameterValue) -> Result<Self> {
+        match value {
+            ParameterValue::Map(v) => Ok(v),
+            _ => {
+                bail!("Unexpected parameter value type: {:?}", value)
+            }
+        }
+    }
+}
+
 impl TryFrom<ReturnValue> for i32 {
     type Error = Error;
     #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
@@ -654,12 +928,12 @@ impl TryFrom<ReturnValue> for Vec<u8> {
     }
 }
 
-impl TryFrom<ReturnValue> for () {
+impl TryFrom<ReturnValue> for Vec<f32> {
     type Error = Error;
     #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
     fn try_from(value: ReturnValue) -> Result<Self> {
         match value {
-            ReturnValue::Void(()) => Ok(()),
+            ReturnValue::VecFloat(v) => Ok(v),
             _ => {
                 bail!("Unexpected return value type: {:?}", value)
             }
@@ -667,55 +941,107 @@ impl TryFrom<ReturnValue> for () {
     }
 }
 
-impl TryFrom<ReturnValueBox<'_>> for ReturnValue {
+impl TryFrom<ReturnValue> for Vec<f64> {
     type Error = Error;
     #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
-    fn try_from(return_value_box: ReturnValueBox<'_>) -> Result<Self> {
-        match return_value_box.value_type() {
+    fn try_from(value: ReturnValue) -> Result<Self> {
+        match value {
+            ReturnValue::VecDouble(v) => Ok(v),
+            _ => {
+                bail!("Unexpected return value type: {:?}", value)
+            }
+        }
+    }
+}
+
+impl TryFrom<ReturnValue> for Vec<Vec<u8>> {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ReturnValue) -> Result<Self> {
+        match value {
+            ReturnValue::MultiBuffer(v) => Ok(v),
+            _ => {
+                bail!("Unexpected return value type: {:?}", value)
+            }
+        }
+    }
+}
+
+impl TryFrom<ReturnValue> for (i32, Vec<u8>) {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ReturnValue) -> Result<Self> {
+        match value {
+            ReturnValue::StatusWithPayload { code, data } => Ok((code, data)),
+            _ => {
+                bail!("Unexpected return value type: {:?}", value)
+            }
+        }
+    }
+}
+
+impl TryFrom<ReturnValue> for () {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(value: ReturnValue) -> Result<Self> {
+        match value {
+            ReturnValue::Void(()) => Ok(()),
+            _ => {
+                bail!("Unexpected return value type: {:?}", value)
+            }
+        }
+    }
+}
+
+/// Decodes the member of the flatbuffer `ReturnValue` union held by
+/// `$table`, which is either a `ReturnValueBox` or an `hloptionalreturn`.
+macro_rules! return_value_from_union {
+    ($table:expr) => {
+        match $table.value_type() {
             FbReturnValue::hlint => {
-                let hlint = return_value_box
+                let hlint = $table
                     .value_as_hlint()
                     .ok_or_else(|| anyhow!("Failed to get hlint from return value"))?;
                 Ok(ReturnValue::Int(hlint.value()))
             }
             FbReturnValue::hluint => {
-                let hluint = return_value_box
+                let hluint = $table
                     .value_as_hluint()
                     .ok_or_else(|| anyhow!("Failed to get hluint from return value"))?;
                 Ok(ReturnValue::UInt(hluint.value()))
             }
             FbReturnValue::hllong => {
-                let hllong = return_value_box
+                let hllong = $table
                     .value_as_hllong()
                     .ok_or_else(|| anyhow!("Failed to get hllong from return value"))?;
                 Ok(ReturnValue::Long(hllong.value()))
             }
             FbReturnValue::hlulong => {
-                let hlulong = return_value_box
+                let hlulong = $table
                     .value_as_hlulong()
                     .ok_or_else(|| anyhow!("Failed to get hlulong from return value"))?;
                 Ok(ReturnValue::ULong(hlulong.value()))
             }
             FbReturnValue::hlfloat => {
-                let hlfloat = return_value_box
+                let hlfloat = $table
                     .value_as_hlfloat()
                     .ok_or_else(|| anyhow!("Failed to get hlfloat from return value"))?;
                 Ok(ReturnValue::Float(hlfloat.value()))
             }
             FbReturnValue::hldouble => {
-                let hldouble = return_value_box
+                let hldouble = $table
                     .value_as_hldouble()
                     .ok_or_else(|| anyhow!("Failed to get hldouble from return value"))?;
                 Ok(ReturnValue::Double(hldouble.value()))
             }
             FbReturnValue::hlbool => {
-                let hlbool = return_value_box
+                let hlbool = $table
                     .value_as_hlbool()
                     .ok_or_else(|| anyhow!("Failed to get hlbool from return value"))?;
                 Ok(ReturnValue::Bool(hlbool.value()))
             }
             FbReturnValue::hlstring => {
-                let hlstring = match return_value_box.value_as_hlstring() {
+                let hlstring = match $table.value_as_hlstring() {
                     Some(hlstring) => hlstring.value().map(|v| v.to_string()),
                     None => None,
                 };
@@ -723,7 +1049,7 @@ impl TryFrom<ReturnValueBox<'_>> for ReturnValue {
             }
             FbReturnValue::hlvoid => Ok(ReturnValue::Void(())),
             FbReturnValue::hlsizeprefixedbuffer => {
-                let hlvecbytes = match return_value_box.value_as_hlsizeprefixedbuffer() {
+                let hlvecbytes = match $table.value_as_hlsizeprefixedbuffer() {
                     Some(hlvecbytes) => hlvecbytes
                         .value()
                         .map(|val| val.iter().collect::<Vec<u8>>()),
@@ -731,10 +1057,79 @@ impl TryFrom<ReturnValueBox<'_>> for ReturnValue {
                 };
                 Ok(ReturnValue::VecBytes(hlvecbytes.unwrap_or(Vec::new())))
             }
+            FbReturnValue::hloptionalreturn => {
+                let hloptionalreturn = $table
+                    .value_as_hloptionalreturn()
+                    .ok_or_else(|| anyhow!("Failed to get hloptionalreturn from return value"))?;
+                ReturnValue::try_from(hloptionalreturn)
+            }
+            FbReturnValue::hlvecfloat => {
+                let hlvecfloat = $table
+                    .value_as_hlvecfloat()
+                    .ok_or_else(|| anyhow!("Failed to get hlvecfloat from return value"))?;
+                Ok(ReturnValue::VecFloat(
+                    hlvecfloat.value().unwrap_or_default().iter().collect(),
+                ))
+            }
+            FbReturnValue::hlvecdouble => {
+                let hlvecdouble = $table
+                    .value_as_hlvecdouble()
+                    .ok_or_else(|| anyhow!("Failed to get hlvecdouble from return value"))?;
+                Ok(ReturnValue::VecDouble(
+                    hlvecdouble.value().unwrap_or_default().iter().collect(),
+                ))
+            }
+            FbReturnValue::hlmultibuffer => {
+                let hlmultibuffer = $table
+                    .value_as_hlmultibuffer()
+                    .ok_or_else(|| anyhow!("Failed to get hlmultibuffer from return value"))?;
+                Ok(ReturnValue::MultiBuffer(
+                    hlmultibuffer
+                        .value()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|buffer| buffer.value().unwrap_or_default().bytes().to_vec())
+                        .collect(),
+                ))
+            }
+            FbReturnValue::hlstatuswithpayload => {
+                let hlstatuswithpayload =
+                    $table.value_as_hlstatuswithpayload().ok_or_else(|| {
+                        anyhow!("Failed to get hlstatuswithpayload from return value")
+                    })?;
+                Ok(ReturnValue::StatusWithPayload {
+                    code: hlstatuswithpayload.code(),
+                    data: hlstatuswithpayload
+                        .data()
+                        .unwrap_or_default()
+                        .bytes()
+                        .to_vec(),
+                })
+            }
             other => {
                 bail!("Unexpected flatbuffer return value type: {:?}", other)
             }
         }
+    };
+}
+
+impl TryFrom<ReturnValueBox<'_>> for ReturnValue {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(return_value_box: ReturnValueBox<'_>) -> Result<Self> {
+        return_value_from_union!(return_value_box)
+    }
+}
+
+impl TryFrom<hloptionalreturn<'_>> for ReturnValue {
+    type Error = Error;
+    #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
+    fn try_from(optional: hloptionalreturn<'_>) -> Result<Self> {
+        if optional.value_type() == FbReturnValue::NONE {
+            return Ok(ReturnValue::Optional(None));
+        }
+        let value: ReturnValue = return_value_from_union!(optional)?;
+        Ok(ReturnValue::Optional(Some(Box::new(value))))
     }
 }
 
@@ -946,6 +1341,13 @@ impl TryFrom<&ReturnValue> for Vec<u8> {
                 builder.finish_size_prefixed(fcr, None);
                 builder.finished_data().to_vec()
             }
+            ReturnValue::Optional(_)
+            | ReturnValue::VecFloat(_)
+            | ReturnValue::VecDouble(_)
+            | ReturnValue::MultiBuffer(_)
+            | ReturnValue::StatusWithPayload { .. } => FunctionCallResult::new(Ok(value.clone()))
+                .encode(&mut builder)
+                .to_vec(),
         };
 
         Ok(result_bytes)
@@ -954,6 +1356,8 @@ impl TryFrom<&ReturnValue> for Vec<u8> {
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use flatbuffers::FlatBufferBuilder;
 
     use super::super::guest_error::ErrorCode;
@@ -969,6 +1373,130 @@ mod tests {
         assert_eq!(result, ReturnValue::Int(42));
     }
 
+    #[test]
+    fn locate_bytes_result() {
+        for (value, return_type, expected) in [
+            (
+                ReturnValue::VecBytes(vec![1, 2, 3]),
+                ReturnType::VecBytes,
+                &b"\x01\x02\x03"[..],
+            ),
+            (
+                ReturnValue::String("hello".into()),
+                ReturnType::String,
+                b"hello",
+            ),
+            (ReturnValue::VecBytes(vec![]), ReturnType::VecBytes, b""),
+        ] {
+            let mut builder = FlatBufferBuilder::new();
+            let test_data = FunctionCallResult::new(Ok(value)).encode(&mut builder);
+
+            let (ty, range) = FunctionCallResult::locate_bytes(test_data)
+                .unwrap()
+                .unwrap();
+            assert_eq!(ty, return_type);
+            assert_eq!(&test_data[range], expected);
+        }
+
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCallResult::new(Ok(ReturnValue::Int(1))).encode(&mut builder);
+        assert!(FunctionCallResult::locate_bytes(test_data).is_err());
+    }
+
+    #[test]
+    fn encode_optional_result() {
+        for value in [
+            ReturnValue::Optional(None),
+            ReturnValue::Optional(Some(Box::new(ReturnValue::VecBytes(vec![1, 2])))),
+            ReturnValue::Optional(Some(Box::new(ReturnValue::Optional(None)))),
+        ] {
+            let mut builder = FlatBufferBuilder::new();
+            let test_data = FunctionCallResult::new(Ok(value.clone())).encode(&mut builder);
+
+            let function_call_result = FunctionCallResult::try_from(test_data).unwrap();
+            assert_eq!(function_call_result.into_inner().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn encode_multi_buffer_result() {
+        // Empty buffers, including trailing ones, must keep their place
+        for buffers in [
+            vec![],
+            vec![vec![]],
+            vec![b"result".to_vec(), vec![], b"log line\n".to_vec(), vec![]],
+        ] {
+            let mut builder = FlatBufferBuilder::new();
+            let test_data = FunctionCallResult::new(Ok(ReturnValue::MultiBuffer(buffers.clone())))
+                .encode(&mut builder);
+            let result = FunctionCallResult::try_from(test_data)
+                .unwrap()
+                .into_inner()
+                .unwrap();
+            assert_eq!(result, ReturnValue::MultiBuffer(buffers));
+        }
+    }
+
+    #[test]
+    fn encode_status_with_payload_result() {
+        for (code, data) in [(0, vec![]), (-22, b"invalid input".to_vec())] {
+            let value = ReturnValue::StatusWithPayload {
+                code,
+                data: data.clone(),
+            };
+            let bytes = Vec::<u8>::try_from(&value).unwrap();
+            let result = FunctionCallResult::try_from(bytes.as_slice())
+                .unwrap()
+                .into_inner()
+                .unwrap();
+            assert_eq!(result, value);
+            assert_eq!(<(i32, Vec<u8>)>::try_from(result).unwrap(), (code, data));
+        }
+    }
+
+    #[test]
+    fn encode_float_array_result() {
+        let doubles = vec![
+            0.1,
+            -0.0,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+        ];
+        let mut builder = FlatBufferBuilder::new();
+        let test_data = FunctionCallResult::new(Ok(ReturnValue::VecDouble(doubles.clone())))
+            .encode(&mut builder);
+        let ReturnValue::VecDouble(result) = FunctionCallResult::try_from(test_data)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+        else {
+            panic!("expected a VecDouble");
+        };
+        // Compare bit patterns so that NaN and the sign of zero are checked
+        assert_eq!(
+            result.iter().map(|d| d.to_bits()).collect::<Vec<_>>(),
+            doubles.iter().map(|d| d.to_bits()).collect::<Vec<_>>()
+        );
+
+        let floats = vec![1.5f32, f32::MIN_POSITIVE, f32::INFINITY, f32::NAN];
+        let mut builder = FlatBufferBuilder::new();
+        let test_data =
+            FunctionCallResult::new(Ok(ReturnValue::VecFloat(floats.clone()))).encode(&mut builder);
+        let ReturnValue::VecFloat(result) = FunctionCallResult::try_from(test_data)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+        else {
+            panic!("expected a VecFloat");
+        };
+        assert_eq!(
+            result.iter().map(|f| f.to_bits()).collect::<Vec<_>>(),
+            floats.iter().map(|f| f.to_bits()).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn encode_error_result() {
         let mut builder = FlatBufferBuilder::new();
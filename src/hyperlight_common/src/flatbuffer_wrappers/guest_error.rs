@@ -43,6 +43,11 @@ pub enum ErrorCode {
     GuestError = 15,
     ArrayLengthParamIsMissing = 16,
     HostFunctionError = 17,
+    GuestAssertionFailed = 18,
+    GuestAllocationLimitExceeded = 19,
+    DuplicateGuestFunctionName = 20,
+    DomainError = 21,
+    SelfModifyingCode = 22,
 }
 
 impl From<ErrorCode> for FbErrorCode {
@@ -68,6 +73,11 @@ impl From<ErrorCode> for FbErrorCode {
             ErrorCode::GuestError => Self::GuestError,
             ErrorCode::ArrayLengthParamIsMissing => Self::ArrayLengthParamIsMissing,
             ErrorCode::HostFunctionError => Self::HostError,
+            ErrorCode::GuestAssertionFailed => Self::GuestAssertionFailed,
+            ErrorCode::GuestAllocationLimitExceeded => Self::GuestAllocationLimitExceeded,
+            ErrorCode::DuplicateGuestFunctionName => Self::DuplicateGuestFunctionName,
+            ErrorCode::DomainError => Self::DomainError,
+            ErrorCode::SelfModifyingCode => Self::SelfModifyingCode,
         }
     }
 }
@@ -94,6 +104,11 @@ impl From<FbErrorCode> for ErrorCode {
             FbErrorCode::GuestError => Self::GuestError,
             FbErrorCode::ArrayLengthParamIsMissing => Self::ArrayLengthParamIsMissing,
             FbErrorCode::HostError => Self::HostFunctionError,
+            FbErrorCode::GuestAssertionFailed => Self::GuestAssertionFailed,
+            FbErrorCode::GuestAllocationLimitExceeded => Self::GuestAllocationLimitExceeded,
+            FbErrorCode::DuplicateGuestFunctionName => Self::DuplicateGuestFunctionName,
+            FbErrorCode::DomainError => Self::DomainError,
+            FbErrorCode::SelfModifyingCode => Self::SelfModifyingCode,
             _ => Self::UnknownError,
         }
     }
@@ -118,6 +133,11 @@ impl From<u64> for ErrorCode {
             15 => Self::GuestError,
             16 => Self::ArrayLengthParamIsMissing,
             17 => Self::HostFunctionError,
+            18 => Self::GuestAssertionFailed,
+            19 => Self::GuestAllocationLimitExceeded,
+            20 => Self::DuplicateGuestFunctionName,
+            21 => Self::DomainError,
+            22 => Self::SelfModifyingCode,
             _ => Self::UnknownError,
         }
     }
@@ -142,6 +162,11 @@ impl From<ErrorCode> for u64 {
             ErrorCode::GuestError => 15,
             ErrorCode::ArrayLengthParamIsMissing => 16,
             ErrorCode::HostFunctionError => 17,
+            ErrorCode::GuestAssertionFailed => 18,
+            ErrorCode::GuestAllocationLimitExceeded => 19,
+            ErrorCode::DuplicateGuestFunctionName => 20,
+            ErrorCode::DomainError => 21,
+            ErrorCode::SelfModifyingCode => 22,
         }
     }
 }
@@ -169,6 +194,11 @@ impl From<ErrorCode> for String {
             ErrorCode::GuestError => "GuestError".to_string(),
             ErrorCode::ArrayLengthParamIsMissing => "ArrayLengthParamIsMissing".to_string(),
             ErrorCode::HostFunctionError => "HostFunctionError".to_string(),
+            ErrorCode::GuestAssertionFailed => "GuestAssertionFailed".to_string(),
+            ErrorCode::GuestAllocationLimitExceeded => "GuestAllocationLimitExceeded".to_string(),
+            ErrorCode::DuplicateGuestFunctionName => "DuplicateGuestFunctionName".to_string(),
+            ErrorCode::DomainError => "DomainError".to_string(),
+            ErrorCode::SelfModifyingCode => "SelfModifyingCode".to_string(),
         }
     }
 }
@@ -189,6 +219,56 @@ impl GuestError {
     }
 }
 
+/// An application-defined error returned by a guest function, such as a
+/// variant of the application's own error enum.
+///
+/// It is sent to the host as a [`GuestError`] with the code
+/// [`ErrorCode::DomainError`], whose message is the discriminant in
+/// decimal, followed by a `:` and the message if there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainError {
+    /// Which error this is, for example the discriminant of an enum variant
+    pub discriminant: u32,
+    /// Details of the error, if any
+    pub message: Option<String>,
+}
+
+impl DomainError {
+    /// Create a new `DomainError`.
+    pub fn new(discriminant: u32, message: Option<String>) -> Self {
+        Self {
+            discriminant,
+            message,
+        }
+    }
+
+    /// Decode the domain error carried by `error`, or return `None` if
+    /// `error` is not a domain error.
+    pub fn from_guest_error(error: &GuestError) -> Option<Self> {
+        if error.code != ErrorCode::DomainError {
+            return None;
+        }
+        let (discriminant, message) = match error.message.split_once(':') {
+            Some((discriminant, message)) => (discriminant, Some(message.to_string())),
+            None => (error.message.as_str(), None),
+        };
+        Some(Self {
+            discriminant: discriminant.parse().ok()?,
+            message,
+        })
+    }
+}
+
+impl From<DomainError> for GuestError {
+    fn from(error: DomainError) -> Self {
+        let message = match error.message {
+            Some(message) => format!("{}:{}", error.discriminant, message),
+            None => error.discriminant.to_string(),
+        };
+        Self::new(ErrorCode::DomainError, message)
+    }
+}
+
 impl Default for GuestError {
     #[cfg_attr(feature = "tracing", instrument(parent = Span::current(), level= "Trace"))]
     fn default() -> Self {
@@ -198,3 +278,26 @@ impl Default for GuestError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domain_error_round_trip() {
+        for error in [
+            DomainError::new(7, None),
+            DomainError::new(0, Some(String::new())),
+            DomainError::new(u32::MAX, Some("key: not found".to_string())),
+        ] {
+            let guest_error = GuestError::from(error.clone());
+            assert_eq!(guest_error.code, ErrorCode::DomainError);
+            assert_eq!(DomainError::from_guest_error(&guest_error), Some(error));
+        }
+
+        let other = GuestError::new(ErrorCode::GuestError, "7".to_string());
+        assert_eq!(DomainError::from_guest_error(&other), None);
+        let malformed = GuestError::new(ErrorCode::DomainError, "seven".to_string());
+        assert_eq!(DomainError::from_guest_error(&malformed), None);
+    }
+}
@@ -142,8 +142,9 @@ impl TryFrom<&[u8]> for HostFunctionDefinition {
     type Error = Error;
     #[cfg_attr(feature = "tracing", instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace"))]
     fn try_from(value: &[u8]) -> Result<Self> {
-        let fb_host_function_definition = flatbuffers::root::<FbHostFunctionDefinition<'_>>(value)
-            .map_err(|e| anyhow!("Error while reading HostFunctionDefinition: {:?}", e))?;
+        let fb_host_function_definition =
+            flatbuffers::size_prefixed_root::<FbHostFunctionDefinition<'_>>(value)
+                .map_err(|e| anyhow!("Error while reading HostFunctionDefinition: {:?}", e))?;
         Self::try_from(&fb_host_function_definition)
     }
 }
@@ -158,3 +159,20 @@ impl TryFrom<&HostFunctionDefinition> for Vec<u8> {
         Ok(builder.finished_data().to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_function_definition_roundtrip() {
+        let hfd = HostFunctionDefinition::new(
+            "HostAdd".to_string(),
+            Some(alloc::vec![ParameterType::Int, ParameterType::String]),
+            ReturnType::Long,
+        );
+        let bytes: Vec<u8> = (&hfd).try_into().unwrap();
+        let decoded = HostFunctionDefinition::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(hfd, decoded);
+    }
+}
@@ -25,12 +25,18 @@ use crate::flatbuffers::hyperlight::generated::{
     ReturnValueBox, ReturnValueBoxArgs, hlbool as Fbhlbool, hlboolArgs as FbhlboolArgs,
     hldouble as Fbhldouble, hldoubleArgs as FbhldoubleArgs, hlfloat as Fbhlfloat,
     hlfloatArgs as FbhlfloatArgs, hlint as Fbhlint, hlintArgs as FbhlintArgs, hllong as Fbhllong,
-    hllongArgs as FbhllongArgs, hlsizeprefixedbuffer as Fbhlsizeprefixedbuffer,
-    hlsizeprefixedbufferArgs as FbhlsizeprefixedbufferArgs, hlstring as Fbhlstring,
+    hllongArgs as FbhllongArgs, hlmultibuffer as Fbhlmultibuffer,
+    hlmultibufferArgs as FbhlmultibufferArgs, hlsizeprefixedbuffer as Fbhlsizeprefixedbuffer,
+    hlsizeprefixedbufferArgs as FbhlsizeprefixedbufferArgs,
+    hlstatuswithpayload as Fbhlstatuswithpayload,
+    hlstatuswithpayloadArgs as FbhlstatuswithpayloadArgs, hlstring as Fbhlstring,
     hlstringArgs as FbhlstringArgs, hluint as Fbhluint, hluintArgs as FbhluintArgs,
-    hlulong as Fbhlulong, hlulongArgs as FbhlulongArgs, hlvoid as Fbhlvoid,
-    hlvoidArgs as FbhlvoidArgs,
+    hlulong as Fbhlulong, hlulongArgs as FbhlulongArgs, hlvecbytes as Fbhlvecbytes,
+    hlvecbytesArgs as FbhlvecbytesArgs, hlvecdouble as Fbhlvecdouble,
+    hlvecdoubleArgs as FbhlvecdoubleArgs, hlvecfloat as Fbhlvecfloat,
+    hlvecfloatArgs as FbhlvecfloatArgs, hlvoid as Fbhlvoid, hlvoidArgs as FbhlvoidArgs,
 };
+use crate::func::BorrowedParameterValue;
 
 /// Flatbuffer-encodes the given value
 pub fn get_flatbuffer_result<T: FlatbufferSerializable>(val: T) -> Vec<u8> {
@@ -113,6 +119,112 @@ impl FlatbufferSerializable for &[u8] {
     }
 }
 
+impl FlatbufferSerializable for &[&[u8]] {
+    fn serialize(&self, builder: &mut FlatBufferBuilder) -> FbFunctionCallResultArgs {
+        let buffers: Vec<_> = self
+            .iter()
+            .map(|buffer| {
+                let vec_off = builder.create_vector(buffer);
+                Fbhlvecbytes::create(
+                    builder,
+                    &FbhlvecbytesArgs {
+                        value: Some(vec_off),
+                    },
+                )
+            })
+            .collect();
+        let vec_off = builder.create_vector(&buffers);
+        let off = Fbhlmultibuffer::create(
+            builder,
+            &FbhlmultibufferArgs {
+                value: Some(vec_off),
+            },
+        );
+        let rv_box = ReturnValueBox::create(
+            builder,
+            &ReturnValueBoxArgs {
+                value_type: FbReturnValue::hlmultibuffer,
+                value: Some(off.as_union_value()),
+            },
+        );
+        FbFunctionCallResultArgs {
+            result_type: FbFunctionCallResultType::ReturnValueBox,
+            result: Some(rv_box.as_union_value()),
+        }
+    }
+}
+
+impl FlatbufferSerializable for (i32, &[u8]) {
+    fn serialize(&self, builder: &mut FlatBufferBuilder) -> FbFunctionCallResultArgs {
+        let (code, data) = *self;
+        let vec_off = builder.create_vector(data);
+        let off = Fbhlstatuswithpayload::create(
+            builder,
+            &FbhlstatuswithpayloadArgs {
+                code,
+                data: Some(vec_off),
+            },
+        );
+        let rv_box = ReturnValueBox::create(
+            builder,
+            &ReturnValueBoxArgs {
+                value_type: FbReturnValue::hlstatuswithpayload,
+                value: Some(off.as_union_value()),
+            },
+        );
+        FbFunctionCallResultArgs {
+            result_type: FbFunctionCallResultType::ReturnValueBox,
+            result: Some(rv_box.as_union_value()),
+        }
+    }
+}
+
+impl FlatbufferSerializable for &[f32] {
+    fn serialize(&self, builder: &mut FlatBufferBuilder) -> FbFunctionCallResultArgs {
+        let vec_off = builder.create_vector(self);
+        let off = Fbhlvecfloat::create(
+            builder,
+            &FbhlvecfloatArgs {
+                value: Some(vec_off),
+            },
+        );
+        let rv_box = ReturnValueBox::create(
+            builder,
+            &ReturnValueBoxArgs {
+                value_type: FbReturnValue::hlvecfloat,
+                value: Some(off.as_union_value()),
+            },
+        );
+        FbFunctionCallResultArgs {
+            result_type: FbFunctionCallResultType::ReturnValueBox,
+            result: Some(rv_box.as_union_value()),
+        }
+    }
+}
+
+impl FlatbufferSerializable for &[f64] {
+    fn serialize(&self, builder: &mut FlatBufferBuilder) -> FbFunctionCallResultArgs {
+        let vec_off = builder.create_vector(self);
+        let off = Fbhlvecdouble::create(
+            builder,
+            &FbhlvecdoubleArgs {
+                value: Some(vec_off),
+            },
+        );
+        let rv_box = ReturnValueBox::create(
+            builder,
+            &ReturnValueBoxArgs {
+                value_type: FbReturnValue::hlvecdouble,
+                value: Some(off.as_union_value()),
+            },
+        );
+        FbFunctionCallResultArgs {
+            result_type: FbFunctionCallResultType::ReturnValueBox,
+            result: Some(rv_box.as_union_value()),
+        }
+    }
+}
+
 impl FlatbufferSerializable for f32 {
     fn serialize(&self, builder: &mut FlatBufferBuilder) -> FbFunctionCallResultArgs {
         let off = Fbhlfloat::create(builder, &FbhlfloatArgs { value: *self });
@@ -246,32 +358,77 @@ impl FlatbufferSerializable for bool {
 /// on https://flatbuffers.dev/internals/ and https://github.com/dvidelabs/flatcc/blob/f064cefb2034d1e7407407ce32a6085c322212a7/doc/binary-format.md#flatbuffers-binary-format
 #[inline] // allow cross-crate inlining (for hyperlight-host calls)
 pub fn estimate_flatbuffer_capacity(function_name: &str, args: &[ParameterValue]) -> usize {
+    estimate_capacity_with(
+        function_name,
+        args.len(),
+        args.iter().map(estimate_parameter_value_capacity),
+    )
+}
+
+/// Estimates the capacity needed to encode a function call with the given
+/// borrowed parameters, as [`estimate_flatbuffer_capacity`] does.
+#[inline]
+pub fn estimate_borrowed_flatbuffer_capacity(
+    function_name: &str,
+    args: &[BorrowedParameterValue<'_>],
+) -> usize {
+    estimate_capacity_with(
+        function_name,
+        args.len(),
+        args.iter().map(|arg| match arg {
+            BorrowedParameterValue::Value(value) => estimate_parameter_value_capacity(value),
+            BorrowedParameterValue::Bytes(bytes) => bytes.len() + 20,
+        }),
+    )
+}
+
+#[inline]
+fn estimate_capacity_with(
+    function_name: &str,
+    arg_count: usize,
+    arg_capacities: impl Iterator<Item = usize>,
+) -> usize {
     let mut estimated_capacity = 20;
 
     // Function name overhead
     estimated_capacity += function_name.len() + 12;
 
     // Parameters vector overhead
-    estimated_capacity += 12 + args.len() * 6;
+    estimated_capacity += 12 + arg_count * 6;
 
     // Per-parameter overhead
-    for arg in args {
+    for arg_capacity in arg_capacities {
         estimated_capacity += 16; // Base parameter structure
-        estimated_capacity += match arg {
-            ParameterValue::String(s) => s.len() + 20,
-            ParameterValue::VecBytes(v) => v.len() + 20,
-            ParameterValue::Int(_) | ParameterValue::UInt(_) => 16,
-            ParameterValue::Long(_) | ParameterValue::ULong(_) => 20,
-            ParameterValue::Float(_) => 16,
-            ParameterValue::Double(_) => 20,
-            ParameterValue::Bool(_) => 12,
-        };
+        estimated_capacity += arg_capacity;
     }
 
     // match how vec grows
     estimated_capacity.next_power_of_two()
 }
 
+#[inline]
+fn estimate_parameter_value_capacity(arg: &ParameterValue) -> usize {
+    match arg {
+        ParameterValue::String(s) => s.len() + 20,
+        ParameterValue::VecBytes(v) => v.len() + 20,
+        ParameterValue::Int(_) | ParameterValue::UInt(_) => 16,
+        ParameterValue::Long(_) | ParameterValue::ULong(_) => 20,
+        ParameterValue::Float(_) => 16,
+        ParameterValue::Double(_) => 20,
+        ParameterValue::Bool(_) => 12,
+        ParameterValue::Optional(None) => 12,
+        ParameterValue::Optional(Some(v)) => 16 + estimate_parameter_value_capacity(v),
+        ParameterValue::VecFloat(v) => v.len() * 4 + 20,
+        ParameterValue::VecDouble(v) => v.len() * 8 + 24,
+        ParameterValue::Map(entries) => {
+            20 + entries
+                .iter()
+                .map(|(key, value)| 28 + key.len() + estimate_parameter_value_capacity(value))
+                .sum::<usize>()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::ToString;
@@ -19,13 +19,13 @@ pub const ENUM_MIN_ERROR_CODE: u64 = 0;
     since = "2.0.0",
     note = "Use associated constants instead. This will no longer be generated in 2021."
 )]
-pub const ENUM_MAX_ERROR_CODE: u64 = 17;
+pub const ENUM_MAX_ERROR_CODE: u64 = 22;
 #[deprecated(
     since = "2.0.0",
     note = "Use associated constants instead. This will no longer be generated in 2021."
 )]
 #[allow(non_camel_case_types)]
-pub const ENUM_VALUES_ERROR_CODE: [ErrorCode; 16] = [
+pub const ENUM_VALUES_ERROR_CODE: [ErrorCode; 21] = [
     ErrorCode::NoError,
     ErrorCode::UnsupportedParameterType,
     ErrorCode::GuestFunctionNameNotProvided,
@@ -42,6 +42,11 @@ pub const ENUM_VALUES_ERROR_CODE: [ErrorCode; 16] = [
     ErrorCode::GuestError,
     ErrorCode::ArrayLengthParamIsMissing,
     ErrorCode::HostError,
+    ErrorCode::GuestAssertionFailed,
+    ErrorCode::GuestAllocationLimitExceeded,
+    ErrorCode::DuplicateGuestFunctionName,
+    ErrorCode::DomainError,
+    ErrorCode::SelfModifyingCode,
 ];
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -65,9 +70,14 @@ impl ErrorCode {
     pub const GuestError: Self = Self(15);
     pub const ArrayLengthParamIsMissing: Self = Self(16);
     pub const HostError: Self = Self(17);
+    pub const GuestAssertionFailed: Self = Self(18);
+    pub const GuestAllocationLimitExceeded: Self = Self(19);
+    pub const DuplicateGuestFunctionName: Self = Self(20);
+    pub const DomainError: Self = Self(21);
+    pub const SelfModifyingCode: Self = Self(22);
 
     pub const ENUM_MIN: u64 = 0;
-    pub const ENUM_MAX: u64 = 17;
+    pub const ENUM_MAX: u64 = 22;
     pub const ENUM_VALUES: &'static [Self] = &[
         Self::NoError,
         Self::UnsupportedParameterType,
@@ -85,6 +95,11 @@ impl ErrorCode {
         Self::GuestError,
         Self::ArrayLengthParamIsMissing,
         Self::HostError,
+        Self::GuestAssertionFailed,
+        Self::GuestAllocationLimitExceeded,
+        Self::DuplicateGuestFunctionName,
+        Self::DomainError,
+        Self::SelfModifyingCode,
     ];
     /// Returns the variant's name or "" if unknown.
     pub fn variant_name(self) -> Option<&'static str> {
@@ -107,6 +122,11 @@ impl ErrorCode {
             Self::GuestError => Some("GuestError"),
             Self::ArrayLengthParamIsMissing => Some("ArrayLengthParamIsMissing"),
             Self::HostError => Some("HostError"),
+            Self::GuestAssertionFailed => Some("GuestAssertionFailed"),
+            Self::GuestAllocationLimitExceeded => Some("GuestAllocationLimitExceeded"),
+            Self::DuplicateGuestFunctionName => Some("DuplicateGuestFunctionName"),
+            Self::DomainError => Some("DomainError"),
+            Self::SelfModifyingCode => Some("SelfModifyingCode"),
             _ => None,
         }
     }
@@ -32,6 +32,7 @@ impl<'a> FunctionCall<'a> {
     pub const VT_PARAMETERS: flatbuffers::VOffsetT = 6;
     pub const VT_FUNCTION_CALL_TYPE: flatbuffers::VOffsetT = 8;
     pub const VT_EXPECTED_RETURN_TYPE: flatbuffers::VOffsetT = 10;
+    pub const VT_SCHEMA_GENERATION: flatbuffers::VOffsetT = 12;
 
     #[inline]
     pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -43,6 +44,7 @@ impl<'a> FunctionCall<'a> {
         args: &'args FunctionCallArgs<'args>,
     ) -> flatbuffers::WIPOffset<FunctionCall<'bldr>> {
         let mut builder = FunctionCallBuilder::new(_fbb);
+        builder.add_schema_generation(args.schema_generation);
         if let Some(x) = args.parameters {
             builder.add_parameters(x);
         }
@@ -116,6 +118,17 @@ impl<'a> FunctionCall<'a> {
                 .unwrap()
         }
     }
+    #[inline]
+    pub fn schema_generation(&self) -> u64 {
+        // Safety:
+        // Created from valid Table for this object
+        // which contains a valid value in this slot
+        unsafe {
+            self._tab
+                .get::<u64>(FunctionCall::VT_SCHEMA_GENERATION, Some(0))
+                .unwrap()
+        }
+    }
 }
 
 impl flatbuffers::Verifiable for FunctionCall<'_> {
@@ -144,6 +157,7 @@ impl flatbuffers::Verifiable for FunctionCall<'_> {
                 Self::VT_EXPECTED_RETURN_TYPE,
                 false,
             )?
+            .visit_field::<u64>("schema_generation", Self::VT_SCHEMA_GENERATION, false)?
             .finish();
         Ok(())
     }
@@ -157,6 +171,7 @@ pub struct FunctionCallArgs<'a> {
     >,
     pub function_call_type: FunctionCallType,
     pub expected_return_type: ReturnType,
+    pub schema_generation: u64,
 }
 impl<'a> Default for FunctionCallArgs<'a> {
     #[inline]
@@ -166,6 +181,7 @@ impl<'a> Default for FunctionCallArgs<'a> {
             parameters: None,
             function_call_type: FunctionCallType::none,
             expected_return_type: ReturnType::hlint,
+            schema_generation: 0,
         }
     }
 }
@@ -209,6 +225,11 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> FunctionCallBuilder<'a, 'b, A>
         );
     }
     #[inline]
+    pub fn add_schema_generation(&mut self, schema_generation: u64) {
+        self.fbb_
+            .push_slot::<u64>(FunctionCall::VT_SCHEMA_GENERATION, schema_generation, 0);
+    }
+    #[inline]
     pub fn new(
         _fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
     ) -> FunctionCallBuilder<'a, 'b, A> {
@@ -234,6 +255,7 @@ impl core::fmt::Debug for FunctionCall<'_> {
         ds.field("parameters", &self.parameters());
         ds.field("function_call_type", &self.function_call_type());
         ds.field("expected_return_type", &self.expected_return_type());
+        ds.field("schema_generation", &self.schema_generation());
         ds.finish()
     }
 }
@@ -0,0 +1,549 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+extern crate flatbuffers;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+use self::flatbuffers::{EndianScalar, Follow};
+use super::*;
+pub enum hloptionalOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct hloptional<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for hloptional<'a> {
+    type Inner = hloptional<'a>;
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: unsafe { flatbuffers::Table::new(buf, loc) },
+        }
+    }
+}
+
+impl<'a> hloptional<'a> {
+    pub const VT_VALUE_TYPE: flatbuffers::VOffsetT = 4;
+    pub const VT_VALUE: flatbuffers::VOffsetT = 6;
+
+    #[inline]
+    pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        hloptional { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+        args: &'args hloptionalArgs,
+    ) -> flatbuffers::WIPOffset<hloptional<'bldr>> {
+        let mut builder = hloptionalBuilder::new(_fbb);
+        if let Some(x) = args.value {
+            builder.add_value(x);
+        }
+        builder.add_value_type(args.value_type);
+        builder.finish()
+    }
+
+    #[inline]
+    pub fn value_type(&self) -> ParameterValue {
+        // Safety:
+        // Created from valid Table for this object
+        // which contains a valid value in this slot
+        unsafe {
+            self._tab
+                .get::<ParameterValue>(hloptional::VT_VALUE_TYPE, Some(ParameterValue::NONE))
+                .unwrap()
+        }
+    }
+    #[inline]
+    pub fn value(&self) -> Option<flatbuffers::Table<'a>> {
+        // Safety:
+        // Created from valid Table for this object
+        // which contains a valid value in this slot
+        unsafe {
+            self._tab
+                .get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>(
+                    hloptional::VT_VALUE,
+                    None,
+                )
+        }
+    }
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlint(&self) -> Option<hlint<'a>> {
+        if self.value_type() == ParameterValue::hlint {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlint::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hluint(&self) -> Option<hluint<'a>> {
+        if self.value_type() == ParameterValue::hluint {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hluint::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hllong(&self) -> Option<hllong<'a>> {
+        if self.value_type() == ParameterValue::hllong {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hllong::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlulong(&self) -> Option<hlulong<'a>> {
+        if self.value_type() == ParameterValue::hlulong {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlulong::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlfloat(&self) -> Option<hlfloat<'a>> {
+        if self.value_type() == ParameterValue::hlfloat {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlfloat::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hldouble(&self) -> Option<hldouble<'a>> {
+        if self.value_type() == ParameterValue::hldouble {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hldouble::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlstring(&self) -> Option<hlstring<'a>> {
+        if self.value_type() == ParameterValue::hlstring {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlstring::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlbool(&self) -> Option<hlbool<'a>> {
+        if self.value_type() == ParameterValue::hlbool {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlbool::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecbytes(&self) -> Option<hlvecbytes<'a>> {
+        if self.value_type() == ParameterValue::hlvecbytes {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlvecbytes::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hloptional(&self) -> Option<hloptional<'a>> {
+        if self.value_type() == ParameterValue::hloptional {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hloptional::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecfloat(&self) -> Option<hlvecfloat<'a>> {
+        if self.value_type() == ParameterValue::hlvecfloat {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlvecfloat::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecdouble(&self) -> Option<hlvecdouble<'a>> {
+        if self.value_type() == ParameterValue::hlvecdouble {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlvecdouble::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlmap(&self) -> Option<hlmap<'a>> {
+        if self.value_type() == ParameterValue::hlmap {
+            self.value().map(|t| {
+                // Safety:
+                // Created from a valid Table for this object
+                // Which contains a valid union in this slot
+                unsafe { hlmap::init_from_table(t) }
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl flatbuffers::Verifiable for hloptional<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_union::<ParameterValue, _>(
+                "value_type",
+                Self::VT_VALUE_TYPE,
+                "value",
+                Self::VT_VALUE,
+                false,
+                |key, v, pos| match key {
+                    ParameterValue::hlint => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlint>>(
+                            "ParameterValue::hlint",
+                            pos,
+                        ),
+                    ParameterValue::hluint => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hluint>>(
+                            "ParameterValue::hluint",
+                            pos,
+                        ),
+                    ParameterValue::hllong => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hllong>>(
+                            "ParameterValue::hllong",
+                            pos,
+                        ),
+                    ParameterValue::hlulong => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlulong>>(
+                            "ParameterValue::hlulong",
+                            pos,
+                        ),
+                    ParameterValue::hlfloat => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlfloat>>(
+                            "ParameterValue::hlfloat",
+                            pos,
+                        ),
+                    ParameterValue::hldouble => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hldouble>>(
+                            "ParameterValue::hldouble",
+                            pos,
+                        ),
+                    ParameterValue::hlstring => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlstring>>(
+                            "ParameterValue::hlstring",
+                            pos,
+                        ),
+                    ParameterValue::hlbool => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlbool>>(
+                            "ParameterValue::hlbool",
+                            pos,
+                        ),
+                    ParameterValue::hlvecbytes => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecbytes>>(
+                            "ParameterValue::hlvecbytes",
+                            pos,
+                        ),
+                    ParameterValue::hloptional => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hloptional>>(
+                            "ParameterValue::hloptional",
+                            pos,
+                        ),
+                    ParameterValue::hlvecfloat => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecfloat>>(
+                            "ParameterValue::hlvecfloat",
+                            pos,
+                        ),
+                    ParameterValue::hlvecdouble => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecdouble>>(
+                            "ParameterValue::hlvecdouble",
+                            pos,
+                        ),
+                    ParameterValue::hlmap => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlmap>>(
+                            "ParameterValue::hlmap",
+                            pos,
+                        ),
+                    _ => Ok(()),
+                },
+            )?
+            .finish();
+        Ok(())
+    }
+}
+pub struct hloptionalArgs {
+    pub value_type: ParameterValue,
+    pub value: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
+}
+impl<'a> Default for hloptionalArgs {
+    #[inline]
+    fn default() -> Self {
+        hloptionalArgs {
+            value_type: ParameterValue::NONE,
+            value: None,
+        }
+    }
+}
+
+pub struct hloptionalBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> hloptionalBuilder<'a, 'b, A> {
+    #[inline]
+    pub fn add_value_type(&mut self, value_type: ParameterValue) {
+        self.fbb_.push_slot::<ParameterValue>(
+            hloptional::VT_VALUE_TYPE,
+            value_type,
+            ParameterValue::NONE,
+        );
+    }
+    #[inline]
+    pub fn add_value(&mut self, value: flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>) {
+        self.fbb_
+            .push_slot_always::<flatbuffers::WIPOffset<_>>(hloptional::VT_VALUE, value);
+    }
+    #[inline]
+    pub fn new(
+        _fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    ) -> hloptionalBuilder<'a, 'b, A> {
+        let start = _fbb.start_table();
+        hloptionalBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<hloptional<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl core::fmt::Debug for hloptional<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut ds = f.debug_struct("hloptional");
+        ds.field("value_type", &self.value_type());
+        match self.value_type() {
+            ParameterValue::hlint => {
+                if let Some(x) = self.value_as_hlint() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hluint => {
+                if let Some(x) = self.value_as_hluint() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hllong => {
+                if let Some(x) = self.value_as_hllong() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlulong => {
+                if let Some(x) = self.value_as_hlulong() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlfloat => {
+                if let Some(x) = self.value_as_hlfloat() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hldouble => {
+                if let Some(x) = self.value_as_hldouble() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlstring => {
+                if let Some(x) = self.value_as_hlstring() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlbool => {
+                if let Some(x) = self.value_as_hlbool() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlvecbytes => {
+                if let Some(x) = self.value_as_hlvecbytes() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hloptional => {
+                if let Some(x) = self.value_as_hloptional() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlvecfloat => {
+                if let Some(x) = self.value_as_hlvecfloat() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlvecdouble => {
+                if let Some(x) = self.value_as_hlvecdouble() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlmap => {
+                if let Some(x) = self.value_as_hlmap() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            _ => {
+                let x: Option<()> = None;
+                ds.field("value", &x)
+            }
+        };
+        ds.finish()
+    }
+}
@@ -0,0 +1,148 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+extern crate flatbuffers;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+use self::flatbuffers::{EndianScalar, Follow};
+use super::*;
+pub enum hlstatuswithpayloadOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct hlstatuswithpayload<'a> {
+    pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for hlstatuswithpayload<'a> {
+    type Inner = hlstatuswithpayload<'a>;
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self {
+            _tab: unsafe { flatbuffers::Table::new(buf, loc) },
+        }
+    }
+}
+
+impl<'a> hlstatuswithpayload<'a> {
+    pub const VT_CODE: flatbuffers::VOffsetT = 4;
+    pub const VT_DATA: flatbuffers::VOffsetT = 6;
+
+    #[inline]
+    pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        hlstatuswithpayload { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+        args: &'args hlstatuswithpayloadArgs<'args>,
+    ) -> flatbuffers::WIPOffset<hlstatuswithpayload<'bldr>> {
+        let mut builder = hlstatuswithpayloadBuilder::new(_fbb);
+        if let Some(x) = args.data {
+            builder.add_data(x);
+        }
+        builder.add_code(args.code);
+        builder.finish()
+    }
+
+    #[inline]
+    pub fn code(&self) -> i32 {
+        // Safety:
+        // Created from valid Table for this object
+        // which contains a valid value in this slot
+        unsafe {
+            self._tab
+                .get::<i32>(hlstatuswithpayload::VT_CODE, Some(0))
+                .unwrap()
+        }
+    }
+    #[inline]
+    pub fn data(&self) -> Option<flatbuffers::Vector<'a, u8>> {
+        // Safety:
+        // Created from valid Table for this object
+        // which contains a valid value in this slot
+        unsafe {
+            self._tab
+                .get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(
+                    hlstatuswithpayload::VT_DATA,
+                    None,
+                )
+        }
+    }
+}
+
+impl flatbuffers::Verifiable for hlstatuswithpayload<'_> {
+    #[inline]
+    fn run_verifier(
+        v: &mut flatbuffers::Verifier,
+        pos: usize,
+    ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+        use self::flatbuffers::Verifiable;
+        v.visit_table(pos)?
+            .visit_field::<i32>("code", Self::VT_CODE, false)?
+            .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>(
+                "data",
+                Self::VT_DATA,
+                false,
+            )?
+            .finish();
+        Ok(())
+    }
+}
+pub struct hlstatuswithpayloadArgs<'a> {
+    pub code: i32,
+    pub data: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+}
+impl<'a> Default for hlstatuswithpayloadArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        hlstatuswithpayloadArgs {
+            code: 0,
+            data: None,
+        }
+    }
+}
+
+pub struct hlstatuswithpayloadBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+    fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> hlstatuswithpayloadBuilder<'a, 'b, A> {
+    #[inline]
+    pub fn add_code(&mut self, code: i32) {
+        self.fbb_
+            .push_slot::<i32>(hlstatuswithpayload::VT_CODE, code, 0);
+    }
+    #[inline]
+    pub fn add_data(&mut self, data: flatbuffers::WIPOffset<flatbuffers::Vector<'b, u8>>) {
+        self.fbb_
+            .push_slot_always::<flatbuffers::WIPOffset<_>>(hlstatuswithpayload::VT_DATA, data);
+    }
+    #[inline]
+    pub fn new(
+        _fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    ) -> hlstatuswithpayloadBuilder<'a, 'b, A> {
+        let start = _fbb.start_table();
+        hlstatuswithpayloadBuilder {
+            fbb_: _fbb,
+            start_: start,
+        }
+    }
+    #[inline]
+    pub fn finish(self) -> flatbuffers::WIPOffset<hlstatuswithpayload<'a>> {
+        let o = self.fbb_.end_table(self.start_);
+        flatbuffers::WIPOffset::new(o.value())
+    }
+}
+
+impl core::fmt::Debug for hlstatuswithpayload<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut ds = f.debug_struct("hlstatuswithpayload");
+        ds.field("code", &self.code());
+        ds.field("data", &self.data());
+        ds.finish()
+    }
+}
@@ -198,6 +198,62 @@ impl<'a> Parameter<'a> {
             None
         }
     }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hloptional(&self) -> Option<hloptional<'a>> {
+        if self.value_type() == ParameterValue::hloptional {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hloptional::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecfloat(&self) -> Option<hlvecfloat<'a>> {
+        if self.value_type() == ParameterValue::hlvecfloat {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlvecfloat::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecdouble(&self) -> Option<hlvecdouble<'a>> {
+        if self.value_type() == ParameterValue::hlvecdouble {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlvecdouble::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlmap(&self) -> Option<hlmap<'a>> {
+        if self.value_type() == ParameterValue::hlmap {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlmap::init_from_table(u) })
+        } else {
+            None
+        }
+    }
 }
 
 impl flatbuffers::Verifiable for Parameter<'_> {
@@ -260,6 +316,26 @@ impl flatbuffers::Verifiable for Parameter<'_> {
                             "ParameterValue::hlvecbytes",
                             pos,
                         ),
+                    ParameterValue::hloptional => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hloptional>>(
+                            "ParameterValue::hloptional",
+                            pos,
+                        ),
+                    ParameterValue::hlvecfloat => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecfloat>>(
+                            "ParameterValue::hlvecfloat",
+                            pos,
+                        ),
+                    ParameterValue::hlvecdouble => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecdouble>>(
+                            "ParameterValue::hlvecdouble",
+                            pos,
+                        ),
+                    ParameterValue::hlmap => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlmap>>(
+                            "ParameterValue::hlmap",
+                            pos,
+                        ),
                     _ => Ok(()),
                 },
             )?
@@ -410,6 +486,46 @@ impl core::fmt::Debug for Parameter<'_> {
                     )
                 }
             }
+            ParameterValue::hloptional => {
+                if let Some(x) = self.value_as_hloptional() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlvecfloat => {
+                if let Some(x) = self.value_as_hlvecfloat() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlvecdouble => {
+                if let Some(x) = self.value_as_hlvecdouble() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ParameterValue::hlmap => {
+                if let Some(x) = self.value_as_hlmap() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
             _ => {
                 let x: Option<()> = None;
                 ds.field("value", &x)
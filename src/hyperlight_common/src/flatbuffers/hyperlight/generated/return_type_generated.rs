@@ -19,13 +19,13 @@ pub const ENUM_MIN_RETURN_TYPE: u8 = 0;
     since = "2.0.0",
     note = "Use associated constants instead. This will no longer be generated in 2021."
 )]
-pub const ENUM_MAX_RETURN_TYPE: u8 = 9;
+pub const ENUM_MAX_RETURN_TYPE: u8 = 14;
 #[deprecated(
     since = "2.0.0",
     note = "Use associated constants instead. This will no longer be generated in 2021."
 )]
 #[allow(non_camel_case_types)]
-pub const ENUM_VALUES_RETURN_TYPE: [ReturnType; 10] = [
+pub const ENUM_VALUES_RETURN_TYPE: [ReturnType; 15] = [
     ReturnType::hlint,
     ReturnType::hluint,
     ReturnType::hllong,
@@ -36,6 +36,11 @@ pub const ENUM_VALUES_RETURN_TYPE: [ReturnType; 10] = [
     ReturnType::hlbool,
     ReturnType::hlvoid,
     ReturnType::hlsizeprefixedbuffer,
+    ReturnType::hloptional,
+    ReturnType::hlvecfloat,
+    ReturnType::hlvecdouble,
+    ReturnType::hlmultibuffer,
+    ReturnType::hlstatuswithpayload,
 ];
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -53,9 +58,14 @@ impl ReturnType {
     pub const hlbool: Self = Self(7);
     pub const hlvoid: Self = Self(8);
     pub const hlsizeprefixedbuffer: Self = Self(9);
+    pub const hloptional: Self = Self(10);
+    pub const hlvecfloat: Self = Self(11);
+    pub const hlvecdouble: Self = Self(12);
+    pub const hlmultibuffer: Self = Self(13);
+    pub const hlstatuswithpayload: Self = Self(14);
 
     pub const ENUM_MIN: u8 = 0;
-    pub const ENUM_MAX: u8 = 9;
+    pub const ENUM_MAX: u8 = 14;
     pub const ENUM_VALUES: &'static [Self] = &[
         Self::hlint,
         Self::hluint,
@@ -67,6 +77,11 @@ impl ReturnType {
         Self::hlbool,
         Self::hlvoid,
         Self::hlsizeprefixedbuffer,
+        Self::hloptional,
+        Self::hlvecfloat,
+        Self::hlvecdouble,
+        Self::hlmultibuffer,
+        Self::hlstatuswithpayload,
     ];
     /// Returns the variant's name or "" if unknown.
     pub fn variant_name(self) -> Option<&'static str> {
@@ -81,6 +96,11 @@ impl ReturnType {
             Self::hlbool => Some("hlbool"),
             Self::hlvoid => Some("hlvoid"),
             Self::hlsizeprefixedbuffer => Some("hlsizeprefixedbuffer"),
+            Self::hloptional => Some("hloptional"),
+            Self::hlvecfloat => Some("hlvecfloat"),
+            Self::hlvecdouble => Some("hlvecdouble"),
+            Self::hlmultibuffer => Some("hlmultibuffer"),
+            Self::hlstatuswithpayload => Some("hlstatuswithpayload"),
             _ => None,
         }
     }
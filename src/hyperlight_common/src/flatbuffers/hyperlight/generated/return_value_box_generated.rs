@@ -212,6 +212,76 @@ impl<'a> ReturnValueBox<'a> {
             None
         }
     }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hloptionalreturn(&self) -> Option<hloptionalreturn<'a>> {
+        if self.value_type() == ReturnValue::hloptionalreturn {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hloptionalreturn::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecfloat(&self) -> Option<hlvecfloat<'a>> {
+        if self.value_type() == ReturnValue::hlvecfloat {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlvecfloat::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlvecdouble(&self) -> Option<hlvecdouble<'a>> {
+        if self.value_type() == ReturnValue::hlvecdouble {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlvecdouble::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlmultibuffer(&self) -> Option<hlmultibuffer<'a>> {
+        if self.value_type() == ReturnValue::hlmultibuffer {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlmultibuffer::init_from_table(u) })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    #[allow(non_snake_case)]
+    pub fn value_as_hlstatuswithpayload(&self) -> Option<hlstatuswithpayload<'a>> {
+        if self.value_type() == ReturnValue::hlstatuswithpayload {
+            let u = self.value();
+            // Safety:
+            // Created from a valid Table for this object
+            // Which contains a valid union in this slot
+            Some(unsafe { hlstatuswithpayload::init_from_table(u) })
+        } else {
+            None
+        }
+    }
 }
 
 impl flatbuffers::Verifiable for ReturnValueBox<'_> {
@@ -279,6 +349,31 @@ impl flatbuffers::Verifiable for ReturnValueBox<'_> {
                             "ReturnValue::hlsizeprefixedbuffer",
                             pos,
                         ),
+                    ReturnValue::hloptionalreturn => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hloptionalreturn>>(
+                            "ReturnValue::hloptionalreturn",
+                            pos,
+                        ),
+                    ReturnValue::hlvecfloat => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecfloat>>(
+                            "ReturnValue::hlvecfloat",
+                            pos,
+                        ),
+                    ReturnValue::hlvecdouble => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlvecdouble>>(
+                            "ReturnValue::hlvecdouble",
+                            pos,
+                        ),
+                    ReturnValue::hlmultibuffer => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlmultibuffer>>(
+                            "ReturnValue::hlmultibuffer",
+                            pos,
+                        ),
+                    ReturnValue::hlstatuswithpayload => v
+                        .verify_union_variant::<flatbuffers::ForwardsUOffset<hlstatuswithpayload>>(
+                            "ReturnValue::hlstatuswithpayload",
+                            pos,
+                        ),
                     _ => Ok(()),
                 },
             )?
@@ -441,6 +536,56 @@ impl core::fmt::Debug for ReturnValueBox<'_> {
                     )
                 }
             }
+            ReturnValue::hloptionalreturn => {
+                if let Some(x) = self.value_as_hloptionalreturn() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ReturnValue::hlvecfloat => {
+                if let Some(x) = self.value_as_hlvecfloat() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ReturnValue::hlvecdouble => {
+                if let Some(x) = self.value_as_hlvecdouble() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ReturnValue::hlmultibuffer => {
+                if let Some(x) = self.value_as_hlmultibuffer() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
+            ReturnValue::hlstatuswithpayload => {
+                if let Some(x) = self.value_as_hlstatuswithpayload() {
+                    ds.field("value", &x)
+                } else {
+                    ds.field(
+                        "value",
+                        &"InvalidFlatbuffer: Union discriminant does not match value.",
+                    )
+                }
+            }
             _ => {
                 let x: Option<()> = None;
                 ds.field("value", &x)
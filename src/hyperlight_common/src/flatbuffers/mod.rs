@@ -44,6 +44,22 @@ pub mod hyperlight {
         pub use self::hlsizeprefixedbuffer_generated::*;
         mod hlvoid_generated;
         pub use self::hlvoid_generated::*;
+        mod hloptional_generated;
+        pub use self::hloptional_generated::*;
+        mod hloptionalreturn_generated;
+        pub use self::hloptionalreturn_generated::*;
+        mod hlvecfloat_generated;
+        pub use self::hlvecfloat_generated::*;
+        mod hlvecdouble_generated;
+        pub use self::hlvecdouble_generated::*;
+        mod hlmapentry_generated;
+        pub use self::hlmapentry_generated::*;
+        mod hlmap_generated;
+        pub use self::hlmap_generated::*;
+        mod hlmultibuffer_generated;
+        pub use self::hlmultibuffer_generated::*;
+        mod hlstatuswithpayload_generated;
+        pub use self::hlstatuswithpayload_generated::*;
         mod guest_error_generated;
         pub use self::guest_error_generated::*;
         mod return_value_box_generated;
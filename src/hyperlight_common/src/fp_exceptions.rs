@@ -0,0 +1,82 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The floating-point exceptions a guest can trap, see [`FpExceptions`].
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// A set of the SSE floating-point exceptions, as the flags in the low
+    /// 6 bits of the `MXCSR` register.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct FpExceptions: u32 {
+        /// An operation had no valid result, such as `0.0 / 0.0`
+        const INVALID = 1 << 0;
+        /// An operand was denormal
+        const DENORMAL = 1 << 1;
+        /// A finite non-zero number was divided by zero
+        const DIVIDE_BY_ZERO = 1 << 2;
+        /// A result was too large to represent
+        const OVERFLOW = 1 << 3;
+        /// A result was too small to represent as a normal number
+        const UNDERFLOW = 1 << 4;
+        /// A result had to be rounded
+        const PRECISION = 1 << 5;
+    }
+}
+
+impl FpExceptions {
+    /// The exceptions whose flags are set in `mxcsr`, which are those that
+    /// have occurred since the flags were last cleared.
+    pub const fn flags_of(mxcsr: u32) -> Self {
+        Self::from_bits_truncate(mxcsr)
+    }
+
+    /// The exceptions masked in `mxcsr`, which produce a default result,
+    /// such as a NaN or an infinity, instead of trapping.
+    pub const fn masked_in(mxcsr: u32) -> Self {
+        Self::from_bits_truncate(mxcsr >> 7)
+    }
+
+    /// `mxcsr` with the exceptions in `self` masked, and all others
+    /// unmasked.
+    pub const fn apply_mask(self, mxcsr: u32) -> u32 {
+        (mxcsr & !(Self::all().bits() << 7)) | (self.bits() << 7)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FpExceptions;
+
+    #[test]
+    fn mxcsr_round_trip() {
+        // The default MXCSR masks every exception
+        assert_eq!(FpExceptions::masked_in(0x1f80), FpExceptions::all());
+
+        let mask = FpExceptions::all() - FpExceptions::DIVIDE_BY_ZERO - FpExceptions::INVALID;
+        let mxcsr = mask.apply_mask(0x1f80);
+        assert_eq!(FpExceptions::masked_in(mxcsr), mask);
+        // The other fields are left as they are
+        assert_eq!(FpExceptions::all().apply_mask(mxcsr), 0x1f80);
+
+        assert_eq!(
+            FpExceptions::flags_of(0x1f80 | 0b100),
+            FpExceptions::DIVIDE_BY_ZERO
+        );
+    }
+}
@@ -0,0 +1,130 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use anyhow::{Result, anyhow};
+use flatbuffers::FlatBufferBuilder;
+
+use crate::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use crate::flatbuffer_wrappers::function_types::FunctionCallResult;
+use crate::func::{ParameterValue, ReturnType, ReturnValue};
+
+/// Serializes the arguments and results of function calls to bytes and
+/// back.
+///
+/// Calls between the host and the guest are always encoded with
+/// flatbuffers, see [`FlatbufferCodec`]. Implementing this trait lets
+/// tools and bridges to other languages exchange the same values in
+/// another encoding, such as a compact binary format or a readable one for
+/// logging.
+pub trait Codec {
+    /// Encode the arguments of a function call.
+    fn encode_parameters(&self, parameters: &[ParameterValue]) -> Result<Vec<u8>>;
+
+    /// Decode the arguments of a function call encoded by
+    /// [`encode_parameters`](Self::encode_parameters).
+    fn decode_parameters(&self, bytes: &[u8]) -> Result<Vec<ParameterValue>>;
+
+    /// Encode the result of a function call.
+    fn encode_return_value(&self, value: &ReturnValue) -> Result<Vec<u8>>;
+
+    /// Decode the result of a function call encoded by
+    /// [`encode_return_value`](Self::encode_return_value).
+    fn decode_return_value(&self, bytes: &[u8]) -> Result<ReturnValue>;
+}
+
+/// The flatbuffer encoding used for calls between the host and the guest.
+///
+/// Arguments are encoded as a `FunctionCall` with an empty function name,
+/// and results as a successful `FunctionCallResult`, so a result encoded
+/// by this codec is the same as the one a guest function returns.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FlatbufferCodec;
+
+impl Codec for FlatbufferCodec {
+    fn encode_parameters(&self, parameters: &[ParameterValue]) -> Result<Vec<u8>> {
+        let call = FunctionCall::new(
+            String::new(),
+            Some(parameters.to_vec()),
+            FunctionCallType::Guest,
+            ReturnType::Void,
+        );
+        let mut builder = FlatBufferBuilder::new();
+        Ok(call.encode(&mut builder).to_vec())
+    }
+
+    fn decode_parameters(&self, bytes: &[u8]) -> Result<Vec<ParameterValue>> {
+        let call = FunctionCall::try_from(bytes)?;
+        Ok(call.parameters.unwrap_or_default())
+    }
+
+    fn encode_return_value(&self, value: &ReturnValue) -> Result<Vec<u8>> {
+        let result = FunctionCallResult::new(Ok(value.clone()));
+        let mut builder = FlatBufferBuilder::new();
+        Ok(result.encode(&mut builder).to_vec())
+    }
+
+    fn decode_return_value(&self, bytes: &[u8]) -> Result<ReturnValue> {
+        FunctionCallResult::try_from(bytes)?
+            .into_inner()
+            .map_err(|e| anyhow!("Encoded result is an error {:?}: {}", e.code, e.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn flatbuffer_codec_round_trip() {
+        let parameters = vec![
+            ParameterValue::Int(1),
+            ParameterValue::ULong(2),
+            ParameterValue::Double(0.5),
+            ParameterValue::String("three".to_string()),
+            ParameterValue::VecBytes(vec![4, 5]),
+        ];
+        let bytes = FlatbufferCodec.encode_parameters(&parameters).unwrap();
+        assert_eq!(
+            FlatbufferCodec.decode_parameters(&bytes).unwrap(),
+            parameters
+        );
+
+        let bytes = FlatbufferCodec.encode_parameters(&[]).unwrap();
+        assert!(
+            FlatbufferCodec
+                .decode_parameters(&bytes)
+                .unwrap()
+                .is_empty()
+        );
+
+        for value in [
+            ReturnValue::Void(()),
+            ReturnValue::Bool(true),
+            ReturnValue::String("result".to_string()),
+        ] {
+            let bytes = FlatbufferCodec.encode_return_value(&value).unwrap();
+            assert_eq!(FlatbufferCodec.decode_return_value(&bytes).unwrap(), value);
+        }
+
+        assert!(FlatbufferCodec.decode_return_value(&[1, 2, 3]).is_err());
+    }
+}
@@ -14,6 +14,8 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+/// Pluggable serialization of function arguments and results
+pub(crate) mod codec;
 /// Error types related to function support
 pub(crate) mod error;
 /// Definitions and functionality to enable guest-to-host function calling,
@@ -33,10 +35,16 @@ pub(crate) mod param_type;
 /// Definitions and functionality for supported return types
 pub(crate) mod ret_type;
 
+pub use codec::{Codec, FlatbufferCodec};
 pub use error::Error;
 /// Re-export for `HostFunction` trait
 pub use functions::Function;
-pub use param_type::{ParameterTuple, SupportedParameterType};
+#[doc(hidden)]
+pub use param_type::params_from_array;
+pub use param_type::{
+    BorrowedParameter, BorrowedParameterTuple, BorrowedParameterValue, IntoParameterValue,
+    ParameterTuple, SupportedParameterType,
+};
 pub use ret_type::{ResultType, SupportedReturnType};
 
 /// Re-export for `ParameterValue` enum
@@ -14,6 +14,7 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -50,6 +51,9 @@ macro_rules! for_each_param_type {
         $macro!(f64, Double);
         $macro!(bool, Bool);
         $macro!(Vec<u8>, VecBytes);
+        $macro!(Vec<f32>, VecFloat);
+        $macro!(Vec<f64>, VecDouble);
+        $macro!(Vec<(String, ParameterValue)>, Map);
     };
 }
 
@@ -77,6 +81,21 @@ macro_rules! impl_supported_param_type {
 
 for_each_param_type!(impl_supported_param_type);
 
+impl<T: SupportedParameterType> SupportedParameterType for Option<T> {
+    const TYPE: ParameterType = ParameterType::Optional;
+
+    fn into_value(self) -> ParameterValue {
+        ParameterValue::Optional(self.map(|value| Box::new(value.into_value())))
+    }
+
+    fn from_value(value: ParameterValue) -> Result<Self, Error> {
+        match value {
+            ParameterValue::Optional(value) => value.map(|value| T::from_value(*value)).transpose(),
+            other => Err(Error::ParameterValueConversionFailure(other, "Option")),
+        }
+    }
+}
+
 /// A trait to describe the tuple of parameters that a host function can take.
 pub trait ParameterTuple: Sized + Clone + Send + Sync + 'static {
     /// The number of parameters in the tuple
@@ -135,3 +154,160 @@ macro_rules! impl_param_tuple {
 }
 
 for_each_tuple!(impl_param_tuple);
+
+/// A parameter value that may borrow its bytes rather than own them, see
+/// [`BorrowedParameterTuple`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedParameterValue<'a> {
+    /// An owned parameter value
+    Value(ParameterValue),
+    /// A `VecBytes` parameter value whose bytes are borrowed
+    Bytes(&'a [u8]),
+}
+
+/// A parameter of a guest call that may be borrowed for the duration of
+/// the call: any [`SupportedParameterType`], or a `&[u8]`, which is
+/// passed as a `Vec<u8>` without being copied into one first.
+pub trait BorrowedParameter<'a> {
+    /// The underlying Hyperlight parameter type representing this parameter
+    const TYPE: ParameterType;
+
+    /// Get the parameter value representing this parameter
+    fn into_borrowed_value(self) -> BorrowedParameterValue<'a>;
+}
+
+impl<'a, T: SupportedParameterType> BorrowedParameter<'a> for T {
+    const TYPE: ParameterType = T::TYPE;
+
+    fn into_borrowed_value(self) -> BorrowedParameterValue<'a> {
+        BorrowedParameterValue::Value(self.into_value())
+    }
+}
+
+impl<'a> BorrowedParameter<'a> for &'a [u8] {
+    const TYPE: ParameterType = ParameterType::VecBytes;
+
+    fn into_borrowed_value(self) -> BorrowedParameterValue<'a> {
+        BorrowedParameterValue::Bytes(self)
+    }
+}
+
+/// A tuple of parameters of a guest call, like a [`ParameterTuple`], that
+/// may also contain `&[u8]`s, which are encoded straight from the borrow.
+///
+/// A [`ParameterTuple`] must own its values, since host functions are
+/// called with them, so it cannot hold a `&[u8]`. The arguments of a guest
+/// call, on the other hand, are only read while the call is encoded,
+/// which completes before the call returns.
+pub trait BorrowedParameterTuple<'a> {
+    /// The number of parameters in the tuple
+    const SIZE: usize;
+
+    /// The underlying Hyperlight parameter types representing this tuple
+    const TYPE: &[ParameterType];
+
+    /// Get the parameter values representing this tuple
+    fn into_borrowed_values(self) -> Vec<BorrowedParameterValue<'a>>;
+}
+
+impl<'a, T: BorrowedParameter<'a>> BorrowedParameterTuple<'a> for T {
+    const SIZE: usize = 1;
+
+    const TYPE: &[ParameterType] = &[T::TYPE];
+
+    fn into_borrowed_values(self) -> Vec<BorrowedParameterValue<'a>> {
+        vec![self.into_borrowed_value()]
+    }
+}
+
+macro_rules! impl_borrowed_param_tuple {
+    ([$N:expr] ($($name:ident: $param:ident),*)) => {
+        impl<'a, $($param: BorrowedParameter<'a>),*> BorrowedParameterTuple<'a> for ($($param,)*) {
+            const SIZE: usize = $N;
+
+            const TYPE: &[ParameterType] = &[
+                $($param::TYPE),*
+            ];
+
+            fn into_borrowed_values(self) -> Vec<BorrowedParameterValue<'a>> {
+                let ($($name,)*) = self;
+                vec![$($name.into_borrowed_value()),*]
+            }
+        }
+    };
+}
+
+for_each_tuple!(impl_borrowed_param_tuple);
+
+/// A value that can be passed to [`params!`](crate::params): any
+/// [`SupportedParameterType`], or a borrowed string or byte slice, which
+/// is copied.
+pub trait IntoParameterValue {
+    /// Get the Hyperlight parameter value representing this value
+    fn into_parameter_value(self) -> ParameterValue;
+}
+
+impl<T: SupportedParameterType> IntoParameterValue for T {
+    fn into_parameter_value(self) -> ParameterValue {
+        self.into_value()
+    }
+}
+
+impl IntoParameterValue for &str {
+    fn into_parameter_value(self) -> ParameterValue {
+        ParameterValue::String(self.into())
+    }
+}
+
+impl IntoParameterValue for &[u8] {
+    fn into_parameter_value(self) -> ParameterValue {
+        ParameterValue::VecBytes(self.to_vec())
+    }
+}
+
+impl<const N: usize> IntoParameterValue for [u8; N] {
+    fn into_parameter_value(self) -> ParameterValue {
+        ParameterValue::VecBytes(self.to_vec())
+    }
+}
+
+impl<const N: usize> IntoParameterValue for &[u8; N] {
+    fn into_parameter_value(self) -> ParameterValue {
+        ParameterValue::VecBytes(self.to_vec())
+    }
+}
+
+#[doc(hidden)]
+pub fn params_from_array<const N: usize>(values: [ParameterValue; N]) -> Vec<ParameterValue> {
+    values.into()
+}
+
+/// Build a `Vec<ParameterValue>` from a list of values, selecting the
+/// [`ParameterValue`] variant from the type of each value.
+///
+/// This is a shorthand for building the parameters of dynamic calls, which
+/// take a list of [`ParameterValue`]s rather than a typed tuple.
+///
+/// ```
+/// use hyperlight_common::flatbuffer_wrappers::function_types::ParameterValue;
+/// use hyperlight_common::params;
+///
+/// let params = params![1i32, "hello", vec![0u8; 4], true];
+/// assert_eq!(
+///     params,
+///     vec![
+///         ParameterValue::Int(1),
+///         ParameterValue::String("hello".to_string()),
+///         ParameterValue::VecBytes(vec![0; 4]),
+///         ParameterValue::Bool(true),
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! params {
+    ($($value:expr),* $(,)?) => {
+        $crate::func::params_from_array([
+            $($crate::func::IntoParameterValue::into_parameter_value($value)),*
+        ])
+    };
+}
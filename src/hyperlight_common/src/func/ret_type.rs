@@ -14,6 +14,7 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 
@@ -46,6 +47,9 @@ macro_rules! for_each_return_type {
         $macro!(f64, Double);
         $macro!(bool, Bool);
         $macro!(Vec<u8>, VecBytes);
+        $macro!(Vec<f32>, VecFloat);
+        $macro!(Vec<f64>, VecDouble);
+        $macro!(Vec<Vec<u8>>, MultiBuffer);
     };
 }
 
@@ -105,3 +109,34 @@ where
 }
 
 for_each_return_type!(impl_supported_return_type);
+
+impl SupportedReturnType for (i32, Vec<u8>) {
+    const TYPE: ReturnType = ReturnType::StatusWithPayload;
+
+    fn into_value(self) -> ReturnValue {
+        let (code, data) = self;
+        ReturnValue::StatusWithPayload { code, data }
+    }
+
+    fn from_value(value: ReturnValue) -> Result<Self, Error> {
+        match value {
+            ReturnValue::StatusWithPayload { code, data } => Ok((code, data)),
+            other => Err(Error::ReturnValueConversionFailure(other, "(i32, Vec<u8>)")),
+        }
+    }
+}
+
+impl<T: SupportedReturnType> SupportedReturnType for Option<T> {
+    const TYPE: ReturnType = ReturnType::Optional;
+
+    fn into_value(self) -> ReturnValue {
+        ReturnValue::Optional(self.map(|value| Box::new(value.into_value())))
+    }
+
+    fn from_value(value: ReturnValue) -> Result<Self, Error> {
+        match value {
+            ReturnValue::Optional(value) => value.map(|value| T::from_value(*value)).transpose(),
+            other => Err(Error::ReturnValueConversionFailure(other, "Option")),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The compile-time features a guest was built with, which the guest
+//! reports to the host.
+
+use bitflags::bitflags;
+
+/// The name of the built-in guest function that returns the bits of the
+/// guest's [`GuestFeatures`].
+pub const GUEST_FEATURES_FUNCTION: &str = "GuestFeatures";
+
+bitflags! {
+    /// The compile-time features a guest was built with.
+    ///
+    /// The low 32 bits are the features of `hyperlight-guest-bin`. The high
+    /// 32 bits are for the guest's own features, see
+    /// [`GuestFeatures::application`].
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct GuestFeatures: u64 {
+        /// The guest was built with debug assertions.
+        const DEBUG_ASSERTIONS = 1 << 0;
+        /// The guest was built with the `trace_guest` feature.
+        const TRACE_GUEST = 1 << 1;
+        /// The guest was built with the `mem_profile` feature.
+        const MEM_PROFILE = 1 << 2;
+        /// The guest was built with the `libc` feature.
+        const LIBC = 1 << 3;
+        /// The guest was built with the `macros` feature.
+        const MACROS = 1 << 4;
+        /// The guest was built with the `allocator_stats` feature.
+        const ALLOCATOR_STATS = 1 << 5;
+
+        // The guest's own features are not known here, but must be kept
+        const _ = !0;
+    }
+}
+
+impl GuestFeatures {
+    /// The guest's own feature number `n`, which must be less than 32.
+    pub const fn application(n: u32) -> Self {
+        assert!(n < 32, "a guest has at most 32 application features");
+        Self::from_bits_retain(1 << (32 + n))
+    }
+}
@@ -45,6 +45,18 @@ pub mod resource;
 /// cbindgen:ignore
 pub mod func;
 
+/// cbindgen:ignore
+pub mod guest_features;
+
+/// cbindgen:ignore
+pub mod fp_exceptions;
+
+/// cbindgen:ignore
+pub mod allocator_stats;
+
+/// cbindgen:ignore
+pub mod builtin_functions;
+
 // cbindgen:ignore
 pub mod vmem;
 
@@ -53,3 +65,6 @@ pub mod version_note;
 
 /// cbindgen:ignore
 pub mod virtq;
+
+/// cbindgen:ignore
+pub mod shared_ring;
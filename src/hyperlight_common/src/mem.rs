@@ -65,6 +65,35 @@ impl Default for FileMappingInfo {
     }
 }
 
+/// How the guest allocates memory from its heap, see
+/// [`HyperlightPEB::alloc_mode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(u64)]
+pub enum AllocMode {
+    /// A general-purpose allocator, which reuses the memory that is freed.
+    #[default]
+    General = 0,
+    /// A bump allocator, which allocates each block after the previous
+    /// one and only reclaims memory when the guest's memory is restored.
+    ///
+    /// Allocating is a pointer increment, so this suits guests that only
+    /// allocate transient buffers in each call and are reset between
+    /// calls. Memory a call allocates must not be kept for later calls,
+    /// since the reset reclaims it.
+    Bump = 1,
+}
+
+impl AllocMode {
+    /// The mode with the value `value` of [`HyperlightPEB::alloc_mode`],
+    /// where unknown values are [`AllocMode::General`].
+    pub const fn from_u64(value: u64) -> Self {
+        match value {
+            1 => Self::Bump,
+            _ => Self::General,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct HyperlightPEB {
@@ -72,6 +101,28 @@ pub struct HyperlightPEB {
     pub output_stack: GuestMemoryRegion,
     pub init_data: GuestMemoryRegion,
     pub guest_heap: GuestMemoryRegion,
+    /// The maximum size of the main stack, or 0 if it may grow until
+    /// scratch memory is exhausted.
+    pub guest_stack_size: u64,
+    /// The shared ring region, see [`crate::shared_ring`]. `size` is 0 if
+    /// the sandbox has no shared ring.
+    pub shared_ring: GuestMemoryRegion,
+    /// The maximum number of bytes a single guest function call may
+    /// allocate from the guest heap, or 0 if allocations are only bounded
+    /// by the heap size.
+    pub max_alloc_per_call: u64,
+    /// How the guest allocates from its heap, an [`AllocMode`] as a `u64`.
+    pub alloc_mode: u64,
+    /// Whether the guest should fail when it executes a page it has
+    /// written to, 1 if so and 0 otherwise.
+    pub detect_self_modifying_code: u64,
+    /// The floating-point exceptions that trap during guest function
+    /// calls, the bits of a [`crate::fp_exceptions::FpExceptions`]. The
+    /// others are masked, as they are by default.
+    pub fp_exceptions_trapped: u64,
+    /// Whether the host has guest function aliases for the guest to fetch
+    /// while it is initialised, 1 if so and 0 otherwise.
+    pub guest_function_aliases: u64,
     /// File mappings array descriptor.
     /// **Note:** `size` holds the **entry count** (number of valid
     /// [`FileMappingInfo`] entries), NOT a byte size. `ptr` holds the
@@ -104,10 +155,20 @@ mod tests {
                 size: 0x7777,
                 ptr: 0x8888,
             },
+            guest_stack_size: 0x9999,
+            shared_ring: GuestMemoryRegion {
+                size: 0xaaaa,
+                ptr: 0xbbbb,
+            },
+            max_alloc_per_call: 0xcafe,
+            alloc_mode: AllocMode::Bump as u64,
+            detect_self_modifying_code: 1,
+            fp_exceptions_trapped: 0b100,
+            guest_function_aliases: 1,
             #[cfg(feature = "nanvix-unstable")]
             file_mappings: GuestMemoryRegion {
-                size: 0x9999,
-                ptr: 0xaaaa,
+                size: 0xcccc,
+                ptr: 0xdddd,
             },
         };
         let bytes = bytemuck::bytes_of(&peb);
@@ -85,6 +85,28 @@ impl TryFrom<u8> for Exception {
     }
 }
 
+/// The version of the ABI between the host and the guest: the layout of
+/// the PEB and the encoding of function calls, parameters and return
+/// values. It must be bumped whenever any of these change.
+///
+/// The guest reports the version it was built with through
+/// [`OutBAction::AbiVersion`] when it is initialised, and the host refuses
+/// to run a guest whose version differs from its own or that does not
+/// report one.
+///
+/// Versions:
+/// - 1: the initial version
+/// - 2: map parameters
+/// - 3: multi-buffer return values
+/// - 4: status-with-payload return values
+/// - 5: `HyperlightPEB::detect_self_modifying_code`
+/// - 6: `HyperlightPEB::fp_exceptions_trapped`
+/// - 7: `HyperlightPEB::alloc_mode`
+/// - 8: the schema generation of function calls
+/// - 9: `ErrorCode::DomainError`
+/// - 10: `HyperlightPEB::guest_function_aliases`
+pub const ABI_VERSION: u32 = 10;
+
 /// Supported actions when issuing an OUTB actions by Hyperlight.
 /// These are handled by the sandbox-level outb dispatcher.
 /// - Log: for logging,
@@ -94,6 +116,7 @@ impl TryFrom<u8> for Exception {
 /// - TraceBatch: reports a batch of spans and events from the guest
 /// - TraceMemoryAlloc: records memory allocation events
 /// - TraceMemoryFree: records memory deallocation events
+/// - AbiVersion: reports the [`ABI_VERSION`] the guest was built with
 pub enum OutBAction {
     Log = 99,
     CallFunction = 101,
@@ -105,6 +128,7 @@ pub enum OutBAction {
     TraceMemoryAlloc = 105,
     #[cfg(feature = "mem_profile")]
     TraceMemoryFree = 106,
+    AbiVersion = 109,
 }
 
 /// IO-port actions intercepted at the hypervisor level (in `run_vcpu`)
@@ -137,6 +161,7 @@ impl TryFrom<u16> for OutBAction {
             105 => Ok(OutBAction::TraceMemoryAlloc),
             #[cfg(feature = "mem_profile")]
             106 => Ok(OutBAction::TraceMemoryFree),
+            109 => Ok(OutBAction::AbiVersion),
             _ => Err(anyhow::anyhow!("Invalid OutBAction value: {}", val)),
         }
     }
@@ -0,0 +1,387 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Shared Ring - Host/Guest Message Rings
+//!
+//! A [`SharedRing`] is a pair of lock-free single-producer single-consumer
+//! byte rings living in memory that is mapped into both the host and the
+//! guest. The first half of the region carries messages from the host to
+//! the guest, the second half carries messages from the guest to the host,
+//! so each side only ever produces into one ring and consumes from the other.
+//!
+//! Each ring starts with an 8 byte header followed by its data area:
+//!
+//! | Offset | Field      | Written by                      |
+//! |--------|------------|---------------------------------|
+//! | 0      | `head: u16`| consumer (release)              |
+//! | 2      | `tail: u16`| producer (release)              |
+//! | 4      | `cap: u32` | host, once, on [`SharedRing::init`] |
+//!
+//! `head` and `tail` are free-running positions, so the data area size must
+//! be a power of two no larger than [`SHARED_RING_MAX_CAPACITY`]. Messages
+//! are stored as a little-endian `u32` length followed by the payload, and
+//! may wrap around the end of the data area.
+//!
+//! Memory is accessed through [`MemOps`], so the same code is used by the
+//! host (through its view of shared memory) and by the guest (through
+//! plain pointers).
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use crate::virtq::MemOps;
+
+/// Size of the header at the start of each ring.
+pub const SHARED_RING_HEADER_SIZE: usize = 8;
+
+/// The largest data area a single ring may have.
+pub const SHARED_RING_MAX_CAPACITY: usize = 0x8000;
+
+const HEAD_OFFSET: u64 = 0;
+const TAIL_OFFSET: u64 = 2;
+const CAPACITY_OFFSET: u64 = 4;
+const LEN_PREFIX_SIZE: usize = size_of::<u32>();
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SharedRingError {
+    #[error("Shared ring is full")]
+    Full,
+    #[error("Message of {0} bytes can never fit in the shared ring")]
+    MessageTooLarge(usize),
+    #[error("Shared ring state is corrupt")]
+    Corrupt,
+    #[error("Invalid shared ring layout")]
+    InvalidLayout,
+    #[error("Backend memory error accessing shared ring at address 0x{0:x}")]
+    MemError(u64),
+}
+
+/// The size of the data area of each ring in a shared ring region of
+/// `size` bytes, or `None` if the region is too small to hold a ring.
+pub fn ring_capacity(size: usize) -> Option<usize> {
+    let per_ring = (size / 2).checked_sub(SHARED_RING_HEADER_SIZE)?;
+    if per_ring == 0 {
+        return None;
+    }
+    // Round down to a power of two
+    let capacity = 1usize << (usize::BITS - 1 - per_ring.leading_zeros());
+    Some(capacity.min(SHARED_RING_MAX_CAPACITY))
+}
+
+/// One direction of a [`SharedRing`].
+struct SpscRing<M> {
+    mem: M,
+    base: u64,
+    capacity: u16,
+}
+
+impl<M: MemOps> SpscRing<M> {
+    fn new(mem: M, base: u64) -> Result<Self, SharedRingError> {
+        let capacity: u32 = mem
+            .read_val(base + CAPACITY_OFFSET)
+            .map_err(|_| SharedRingError::MemError(base + CAPACITY_OFFSET))?;
+        let capacity = capacity as usize;
+        if !capacity.is_power_of_two() || capacity > SHARED_RING_MAX_CAPACITY {
+            return Err(SharedRingError::InvalidLayout);
+        }
+        Ok(Self {
+            mem,
+            base,
+            capacity: capacity as u16,
+        })
+    }
+
+    fn data_addr(&self, pos: u16) -> u64 {
+        self.base + SHARED_RING_HEADER_SIZE as u64 + (pos & (self.capacity - 1)) as u64
+    }
+
+    fn load(&self, offset: u64) -> Result<u16, SharedRingError> {
+        self.mem
+            .load_acquire(self.base + offset)
+            .map_err(|_| SharedRingError::MemError(self.base + offset))
+    }
+
+    fn store(&self, offset: u64, val: u16) -> Result<(), SharedRingError> {
+        self.mem
+            .store_release(self.base + offset, val)
+            .map_err(|_| SharedRingError::MemError(self.base + offset))
+    }
+
+    /// Number of bytes currently in the ring, validated against its capacity.
+    fn used(&self, head: u16, tail: u16) -> Result<usize, SharedRingError> {
+        let used = tail.wrapping_sub(head) as usize;
+        if used > self.capacity as usize {
+            return Err(SharedRingError::Corrupt);
+        }
+        Ok(used)
+    }
+
+    fn copy_in(&self, pos: u16, src: &[u8]) -> Result<(), SharedRingError> {
+        let offset = (pos & (self.capacity - 1)) as usize;
+        let first = src.len().min(self.capacity as usize - offset);
+        for (at, chunk) in [
+            (pos, &src[..first]),
+            (pos.wrapping_add(first as u16), &src[first..]),
+        ] {
+            if !chunk.is_empty() {
+                let addr = self.data_addr(at);
+                self.mem
+                    .write(addr, chunk)
+                    .map_err(|_| SharedRingError::MemError(addr))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_out(&self, pos: u16, dst: &mut [u8]) -> Result<(), SharedRingError> {
+        let offset = (pos & (self.capacity - 1)) as usize;
+        let first = dst.len().min(self.capacity as usize - offset);
+        let (a, b) = dst.split_at_mut(first);
+        for (at, chunk) in [(pos, a), (pos.wrapping_add(first as u16), b)] {
+            if !chunk.is_empty() {
+                let addr = self.data_addr(at);
+                self.mem
+                    .read(addr, chunk)
+                    .map_err(|_| SharedRingError::MemError(addr))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&self, msg: &[u8]) -> Result<(), SharedRingError> {
+        let needed = LEN_PREFIX_SIZE + msg.len();
+        if needed > self.capacity as usize {
+            return Err(SharedRingError::MessageTooLarge(msg.len()));
+        }
+        // Only the producer writes `tail`, so it can be read without
+        // synchronisation; `head` is acquired so that the consumer has
+        // finished reading the space we are about to reuse.
+        let tail = self.load(TAIL_OFFSET)?;
+        let head = self.load(HEAD_OFFSET)?;
+        if self.capacity as usize - self.used(head, tail)? < needed {
+            return Err(SharedRingError::Full);
+        }
+        self.copy_in(tail, &(msg.len() as u32).to_le_bytes())?;
+        self.copy_in(tail.wrapping_add(LEN_PREFIX_SIZE as u16), msg)?;
+        // Publish the message
+        self.store(TAIL_OFFSET, tail.wrapping_add(needed as u16))
+    }
+
+    fn pop(&self) -> Result<Option<Vec<u8>>, SharedRingError> {
+        let head = self.load(HEAD_OFFSET)?;
+        let tail = self.load(TAIL_OFFSET)?;
+        let used = self.used(head, tail)?;
+        if used == 0 {
+            return Ok(None);
+        }
+        if used < LEN_PREFIX_SIZE {
+            return Err(SharedRingError::Corrupt);
+        }
+        let mut len = [0u8; LEN_PREFIX_SIZE];
+        self.copy_out(head, &mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > used - LEN_PREFIX_SIZE {
+            return Err(SharedRingError::Corrupt);
+        }
+        let mut msg = vec![0u8; len];
+        self.copy_out(head.wrapping_add(LEN_PREFIX_SIZE as u16), &mut msg)?;
+        // Hand the space back to the producer
+        self.store(
+            HEAD_OFFSET,
+            head.wrapping_add((LEN_PREFIX_SIZE + len) as u16),
+        )?;
+        Ok(Some(msg))
+    }
+}
+
+/// A bidirectional message channel between the host and the guest.
+///
+/// Use [`host_handle`](Self::host_handle) on the host and
+/// [`guest_handle`](Self::guest_handle) in the guest to get the view for
+/// each side. Each view must only be used by one thread at a time.
+pub struct SharedRing<M> {
+    tx: SpscRing<M>,
+    rx: SpscRing<M>,
+}
+
+impl<M: MemOps + Clone> SharedRing<M> {
+    /// Reset the region of `size` bytes at `base` to two empty rings.
+    ///
+    /// This is done by the host before the guest first runs.
+    pub fn init(mem: &M, base: u64, size: usize) -> Result<(), SharedRingError> {
+        let capacity = ring_capacity(size).ok_or(SharedRingError::InvalidLayout)?;
+        for ring_base in [base, base + (size / 2) as u64] {
+            let header = [0u16, 0u16];
+            mem.write(ring_base, bytemuck::cast_slice(&header))
+                .map_err(|_| SharedRingError::MemError(ring_base))?;
+            mem.write_val(ring_base + CAPACITY_OFFSET, capacity as u32)
+                .map_err(|_| SharedRingError::MemError(ring_base + CAPACITY_OFFSET))?;
+        }
+        Ok(())
+    }
+
+    /// The host's view of the region of `size` bytes at `base`: it pushes
+    /// into the first ring and pops from the second.
+    pub fn host_handle(mem: M, base: u64, size: usize) -> Result<Self, SharedRingError> {
+        let second = base + (size / 2) as u64;
+        Ok(Self {
+            tx: SpscRing::new(mem.clone(), base)?,
+            rx: SpscRing::new(mem, second)?,
+        })
+    }
+
+    /// The guest's view of the region of `size` bytes at `base`: it pushes
+    /// into the second ring and pops from the first.
+    pub fn guest_handle(mem: M, base: u64, size: usize) -> Result<Self, SharedRingError> {
+        let second = base + (size / 2) as u64;
+        Ok(Self {
+            tx: SpscRing::new(mem.clone(), second)?,
+            rx: SpscRing::new(mem, base)?,
+        })
+    }
+
+    /// Push `msg` to the other side.
+    ///
+    /// Returns [`SharedRingError::Full`] if there is not currently enough
+    /// room; the caller may retry once the other side has popped messages.
+    pub fn push(&self, msg: &[u8]) -> Result<(), SharedRingError> {
+        self.tx.push(msg)
+    }
+
+    /// Pop the oldest message sent by the other side, if any.
+    pub fn pop(&self) -> Result<Option<Vec<u8>>, SharedRingError> {
+        self.rx.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use core::cell::UnsafeCell;
+    use core::sync::atomic::{AtomicU16, Ordering};
+
+    use super::*;
+
+    /// Test MemOps implementation treating addresses as offsets into a
+    /// shared buffer.
+    #[derive(Clone)]
+    struct TestMem(Arc<UnsafeCell<Vec<u64>>>);
+
+    impl TestMem {
+        fn new(size: usize) -> Self {
+            Self(Arc::new(UnsafeCell::new(vec![0u64; size.div_ceil(8)])))
+        }
+
+        fn ptr(&self, addr: u64) -> *mut u8 {
+            unsafe { (*self.0.get()).as_mut_ptr().cast::<u8>().add(addr as usize) }
+        }
+    }
+
+    // SAFETY: addresses are offsets into the owned buffer, and unit tests
+    // have no real concurrency.
+    unsafe impl MemOps for TestMem {
+        type Error = core::convert::Infallible;
+
+        fn read(&self, addr: u64, dst: &mut [u8]) -> Result<(), Self::Error> {
+            unsafe { core::ptr::copy_nonoverlapping(self.ptr(addr), dst.as_mut_ptr(), dst.len()) };
+            Ok(())
+        }
+
+        fn write(&self, addr: u64, src: &[u8]) -> Result<(), Self::Error> {
+            unsafe { core::ptr::copy_nonoverlapping(src.as_ptr(), self.ptr(addr), src.len()) };
+            Ok(())
+        }
+
+        fn load_acquire(&self, addr: u64) -> Result<u16, Self::Error> {
+            Ok(unsafe { (*self.ptr(addr).cast::<AtomicU16>()).load(Ordering::Acquire) })
+        }
+
+        fn store_release(&self, addr: u64, val: u16) -> Result<(), Self::Error> {
+            unsafe { (*self.ptr(addr).cast::<AtomicU16>()).store(val, Ordering::Release) };
+            Ok(())
+        }
+
+        unsafe fn as_slice(&self, addr: u64, len: usize) -> Result<&[u8], Self::Error> {
+            Ok(unsafe { core::slice::from_raw_parts(self.ptr(addr), len) })
+        }
+
+        unsafe fn as_mut_slice(&self, addr: u64, len: usize) -> Result<&mut [u8], Self::Error> {
+            Ok(unsafe { core::slice::from_raw_parts_mut(self.ptr(addr), len) })
+        }
+    }
+
+    const REGION_SIZE: usize = 2 * (SHARED_RING_HEADER_SIZE + 64);
+
+    fn new_ring() -> (SharedRing<TestMem>, SharedRing<TestMem>) {
+        let mem = TestMem::new(REGION_SIZE);
+        SharedRing::init(&mem, 0, REGION_SIZE).unwrap();
+        (
+            SharedRing::host_handle(mem.clone(), 0, REGION_SIZE).unwrap(),
+            SharedRing::guest_handle(mem, 0, REGION_SIZE).unwrap(),
+        )
+    }
+
+    #[test]
+    fn push_pop_both_directions() {
+        let (host, guest) = new_ring();
+        assert_eq!(guest.pop().unwrap(), None);
+
+        host.push(b"to guest").unwrap();
+        guest.push(b"to host").unwrap();
+
+        assert_eq!(guest.pop().unwrap().as_deref(), Some(&b"to guest"[..]));
+        assert_eq!(host.pop().unwrap().as_deref(), Some(&b"to host"[..]));
+        assert_eq!(guest.pop().unwrap(), None);
+        assert_eq!(host.pop().unwrap(), None);
+    }
+
+    #[test]
+    fn full_and_wraparound() {
+        let (host, guest) = new_ring();
+        assert_eq!(ring_capacity(REGION_SIZE), Some(64));
+
+        // 4 byte prefix + 20 byte payload, so only two fit at a time
+        let msg = [0xabu8; 20];
+        for i in 0..100u8 {
+            let mut msg = msg;
+            msg[0] = i;
+            host.push(&msg).unwrap();
+            if i % 2 == 1 {
+                assert_eq!(host.push(&msg), Err(SharedRingError::Full));
+                assert_eq!(guest.pop().unwrap().unwrap()[0], i - 1);
+                assert_eq!(guest.pop().unwrap().unwrap()[0], i);
+            }
+        }
+
+        assert_eq!(
+            host.push(&[0u8; 61]),
+            Err(SharedRingError::MessageTooLarge(61))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_layout() {
+        let mem = TestMem::new(REGION_SIZE);
+        // Never initialised, so the capacity is zero
+        assert!(matches!(
+            SharedRing::host_handle(mem, 0, REGION_SIZE),
+            Err(SharedRingError::InvalidLayout)
+        ));
+        assert_eq!(ring_capacity(16), None);
+    }
+}
@@ -18,6 +18,7 @@ use alloc::format;
 use alloc::string::{String, ToString as _};
 
 pub use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::flatbuffer_wrappers::guest_error::{DomainError, GuestError};
 use hyperlight_common::func::Error as FuncError;
 use {anyhow, serde_json};
 
@@ -35,6 +36,15 @@ impl HyperlightGuestError {
     }
 }
 
+/// Lets a guest function return an application-defined error with `?`,
+/// which the host receives with `MultiUseSandbox::call_or_domain_error`.
+impl From<DomainError> for HyperlightGuestError {
+    fn from(error: DomainError) -> Self {
+        let GuestError { code, message } = error.into();
+        Self::new(code, message)
+    }
+}
+
 impl From<anyhow::Error> for HyperlightGuestError {
     fn from(error: anyhow::Error) -> Self {
         Self {
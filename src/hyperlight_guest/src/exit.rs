@@ -16,7 +16,8 @@ limitations under the License.
 
 use core::ffi::{CStr, c_char};
 
-use hyperlight_common::outb::OutBAction;
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::outb::{ABI_VERSION, OutBAction};
 
 #[cfg_attr(target_arch = "x86_64", path = "arch/amd64/exit.rs")]
 #[cfg_attr(target_arch = "x86", path = "arch/amd64/exit.rs")]
@@ -66,6 +67,43 @@ pub unsafe fn abort_with_code_and_message(code: &[u8], message_ptr: *const c_cha
     }
 }
 
+/// Aborts the program with an [`ErrorCode::GuestAssertionFailed`] error.
+///
+/// The expression text and source location are sent to the host as
+/// `expression\0file\0line` (with the line in decimal) so they can be
+/// reported as a structured error rather than a free-form message.
+pub fn abort_with_assertion_failure(expression: &[u8], file: &[u8], line: u32) -> ! {
+    // End any ongoing trace before aborting
+    #[cfg(all(feature = "trace_guest", target_arch = "x86_64"))]
+    hyperlight_guest_tracing::end_trace();
+
+    // Format the line number without allocating, the heap may be in an
+    // unknown state if an assertion has failed.
+    let mut digits = [0u8; 10];
+    let mut start = digits.len();
+    let mut n = line;
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+
+    outb(
+        OutBAction::Abort as u16,
+        &[ErrorCode::GuestAssertionFailed as u8],
+    );
+    outb(OutBAction::Abort as u16, expression);
+    outb(OutBAction::Abort as u16, &[0]);
+    outb(OutBAction::Abort as u16, file);
+    outb(OutBAction::Abort as u16, &[0]);
+    outb(OutBAction::Abort as u16, &digits[start..]);
+    outb(OutBAction::Abort as u16, &[0xFF]);
+    unreachable!()
+}
+
 /// This function exists to give the guest more manual control
 /// over the abort sequence. For example, in `hyperlight_guest_bin`'s panic handler,
 /// we have a message of unknown length that we want to stream
@@ -92,6 +130,17 @@ pub(crate) fn outb(port: u16, data: &[u8]) {
     }
 }
 
+/// Reports the [`ABI_VERSION`] this guest was built with to the host, which
+/// fails initialisation if it does not match its own or is never reported.
+///
+/// This must be called before the guest reads anything the host has
+/// written, such as the PEB.
+pub fn report_abi_version() {
+    unsafe {
+        out32(OutBAction::AbiVersion as u16, ABI_VERSION);
+    }
+}
+
 /// Prints a message using `OutBAction::DebugPrint`. It transmits bytes of a message
 /// through several VMExists and, with such, it is slower than
 /// `print_output_with_host_print`.
@@ -23,7 +23,7 @@ use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, Functi
 use hyperlight_common::flatbuffer_wrappers::function_types::{
     FunctionCallResult, ParameterValue, ReturnType, ReturnValue,
 };
-use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
 use hyperlight_common::flatbuffer_wrappers::guest_log_data::GuestLogData;
 use hyperlight_common::flatbuffer_wrappers::guest_log_level::LogLevel;
 use hyperlight_common::flatbuffer_wrappers::util::estimate_flatbuffer_capacity;
@@ -34,6 +34,13 @@ use super::handle::GuestHandle;
 use crate::error::{HyperlightGuestError, Result};
 use crate::exit::out32;
 
+/// The result of a host function call: either the value the host function
+/// returned, or the error code and message it failed with.
+///
+/// This is decoded from the `FunctionCallResult` the host writes back after
+/// every host function call.
+pub type HostResult = core::result::Result<ReturnValue, GuestError>;
+
 impl GuestHandle {
     /// Get user memory region as bytes.
     #[instrument(skip_all, level = "Trace")]
@@ -152,6 +159,25 @@ impl GuestHandle {
         self.get_host_return_value::<T>()
     }
 
+    /// Call a host function and return its [`HostResult`], so that a
+    /// failing host function can be handled by the guest rather than
+    /// surfacing as a guest error.
+    ///
+    /// The outer `Result` only fails if the call could not be made or its
+    /// result could not be decoded.
+    #[instrument(skip_all, level = "Info")]
+    pub fn call_host_function_with_result(
+        &self,
+        function_name: &str,
+        parameters: Option<Vec<ParameterValue>>,
+        return_type: ReturnType,
+    ) -> Result<HostResult> {
+        self.call_host_function_without_returning_result(function_name, parameters, return_type)?;
+        Ok(self
+            .try_pop_shared_input_data_into::<FunctionCallResult>()?
+            .into_inner())
+    }
+
     /// Log a message with the specified log level, source, caller, source file, and line number.
     pub fn log_message(
         &self,
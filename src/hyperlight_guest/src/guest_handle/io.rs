@@ -147,4 +147,52 @@ impl GuestHandle {
 
         Ok(())
     }
+
+    /// Pushes the given data onto the shared output data buffer as
+    /// [`push_shared_output_data`](Self::push_shared_output_data) does,
+    /// first popping the element on top of the stack if it is the one
+    /// written at `replace`.
+    ///
+    /// Returns the offset the data was written at, which can be passed as
+    /// `replace` to replace it in turn.
+    pub fn replace_shared_output_data(&self, replace: Option<u64>, data: &[u8]) -> Result<u64> {
+        let peb_ptr = self.peb().unwrap();
+        let output_stack_size = unsafe { (*peb_ptr).output_stack.size as usize };
+        let output_stack_ptr = unsafe { (*peb_ptr).output_stack.ptr as *mut u8 };
+
+        let odb = unsafe { from_raw_parts_mut(output_stack_ptr, output_stack_size) };
+
+        if odb.len() < 8 {
+            return Err(HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                "Got a 0-size buffer in replace_shared_output_data".to_string(),
+            ));
+        }
+
+        let mut stack_ptr_rel: u64 =
+            u64::from_le_bytes(odb[..8].try_into().expect("Shared output buffer too small"));
+
+        // The element on top of the stack is the one at `replace` if the
+        // offset stored just below the stack pointer points at it
+        if let Some(offset) = replace
+            && stack_ptr_rel as usize <= output_stack_size
+            && stack_ptr_rel >= offset + 8
+            && offset >= 8
+        {
+            let top = stack_ptr_rel as usize;
+            let last_element_offset_rel = u64::from_le_bytes(
+                odb[top - 8..top]
+                    .try_into()
+                    .expect("Invalid stack pointer in replace_shared_output_data"),
+            );
+            if last_element_offset_rel == offset {
+                odb[offset as usize..top].fill(0);
+                odb[..8].copy_from_slice(&offset.to_le_bytes());
+                stack_ptr_rel = offset;
+            }
+        }
+
+        self.push_shared_output_data(data)?;
+        Ok(stack_ptr_rel)
+    }
 }
@@ -0,0 +1,152 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Enforcement of the per-call allocation limit configured by the host.
+//!
+//! Each guest function call may hold at most `max_alloc_per_call` bytes
+//! (from the PEB) of heap memory that it allocated itself. A call that
+//! exceeds the limit aborts with [`ErrorCode::GuestAllocationLimitExceeded`],
+//! rather than running the heap dry.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt::Write;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_guest::exit::write_abort;
+
+use crate::{GUEST_HANDLE, HyperlightAbortWriter};
+
+/// Whether a guest function call is in progress, as opposed to the guest
+/// being initialised.
+static IN_CALL: AtomicBool = AtomicBool::new(false);
+/// The bytes allocated and not yet freed by the current call.
+static CALL_ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+/// The budget of the call that was interrupted by a nested call, see
+/// [`begin_call`].
+pub(crate) struct OuterCall {
+    in_call: bool,
+    allocated: u64,
+}
+
+/// Start charging allocations to a new guest function call. The returned
+/// state must be passed to [`end_call`] when the call returns, so that a
+/// call nested inside another (a reentrant call from the host) does not
+/// consume or reset the budget of the call it interrupted.
+pub(crate) fn begin_call() -> OuterCall {
+    OuterCall {
+        in_call: IN_CALL.swap(true, Ordering::Relaxed),
+        allocated: CALL_ALLOCATED.swap(0, Ordering::Relaxed),
+    }
+}
+
+/// Finish the current call, resuming the budget of the call it interrupted.
+pub(crate) fn end_call(outer: OuterCall) {
+    IN_CALL.store(outer.in_call, Ordering::Relaxed);
+    CALL_ALLOCATED.store(outer.allocated, Ordering::Relaxed);
+}
+
+fn limit() -> u64 {
+    unsafe { GUEST_HANDLE }
+        .peb()
+        .map(|peb| unsafe { (*peb).max_alloc_per_call })
+        .unwrap_or(0)
+}
+
+/// Charge `size` bytes to the current call, aborting the call if that
+/// exceeds its limit.
+fn charge(size: usize) {
+    if !IN_CALL.load(Ordering::Relaxed) {
+        return;
+    }
+    let size = size as u64;
+    let allocated = CALL_ALLOCATED.load(Ordering::Relaxed).saturating_add(size);
+    let limit = limit();
+    if limit != 0 && allocated > limit {
+        abort_limit_exceeded(size, limit);
+    }
+    CALL_ALLOCATED.store(allocated, Ordering::Relaxed);
+}
+
+/// Credit `size` freed bytes back to the current call. Memory allocated
+/// before the call started was never charged to it, so the credit cannot
+/// make the call's balance negative.
+fn refund(size: usize) {
+    if !IN_CALL.load(Ordering::Relaxed) {
+        return;
+    }
+    let allocated = CALL_ALLOCATED.load(Ordering::Relaxed);
+    CALL_ALLOCATED.store(allocated.saturating_sub(size as u64), Ordering::Relaxed);
+}
+
+/// Abort the call, sending the host `requested\0limit` in decimal. This
+/// must not allocate, since it is called from inside the allocator.
+fn abort_limit_exceeded(requested: u64, limit: u64) -> ! {
+    // End any ongoing trace before aborting
+    #[cfg(all(feature = "trace_guest", target_arch = "x86_64"))]
+    hyperlight_guest_tracing::end_trace();
+
+    write_abort(&[ErrorCode::GuestAllocationLimitExceeded as u8]);
+    let _ = write!(HyperlightAbortWriter, "{requested}\0{limit}");
+    write_abort(&[0xFF]);
+    unreachable!()
+}
+
+/// Wraps the guest heap so that every allocation is charged to the current
+/// guest function call.
+pub(crate) struct BudgetedHeap<H>(pub(crate) H);
+
+unsafe impl<H: GlobalAlloc> GlobalAlloc for BudgetedHeap<H> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        charge(layout.size());
+        let ptr = unsafe { self.0.alloc(layout) };
+        if ptr.is_null() {
+            refund(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.0.dealloc(ptr, layout) };
+        refund(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        charge(layout.size());
+        let ptr = unsafe { self.0.alloc_zeroed(layout) };
+        if ptr.is_null() {
+            refund(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let old_size = layout.size();
+        if new_size > old_size {
+            charge(new_size - old_size);
+        }
+        let new_ptr = unsafe { self.0.realloc(ptr, layout, new_size) };
+        if new_ptr.is_null() {
+            if new_size > old_size {
+                refund(new_size - old_size);
+            }
+        } else if new_size < old_size {
+            refund(old_size - new_size);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,74 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Reporting the statistics of the heap allocator to the host, which
+//! reads them with `MultiUseSandbox::guest_allocator_stats`.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+
+use buddy_system_allocator::LockedHeap;
+use hyperlight_common::allocator_stats::{GUEST_ALLOCATOR_STATS_FUNCTION, GuestAllocatorStats};
+
+use crate::HEAP_ALLOCATOR;
+use crate::guest_function::register::register_fn;
+
+/// The heap that [`HEAP_ALLOCATOR`] allocates from, which is empty in bump
+/// mode.
+fn heap() -> &'static LockedHeap<32> {
+    #[cfg(not(all(feature = "mem_profile", target_arch = "x86_64")))]
+    let heap = &HEAP_ALLOCATOR.0.0;
+    #[cfg(all(feature = "mem_profile", target_arch = "x86_64"))]
+    let heap = &HEAP_ALLOCATOR.0.0.0;
+    heap
+}
+
+fn allocator_stats() -> GuestAllocatorStats {
+    let mut heap = heap().lock();
+    let mut stats = GuestAllocatorStats {
+        total_bytes: heap.stats_total_bytes() as u64,
+        allocated_bytes: heap.stats_alloc_actual() as u64,
+        requested_bytes: heap.stats_alloc_user() as u64,
+        largest_free_block: 0,
+    };
+    // The free lists are private to the allocator, but a block of a given
+    // size can be allocated exactly when there is a free block at least
+    // that large, so try each size from the largest down. Freeing the
+    // block merges it back, leaving the heap as it was.
+    for order in (0..32).rev() {
+        let Ok(layout) = Layout::from_size_align(1 << order, 1) else {
+            continue;
+        };
+        if let Ok(block) = heap.alloc(layout) {
+            heap.dealloc(block, layout);
+            stats.largest_free_block = 1 << order;
+            break;
+        }
+    }
+    stats
+}
+
+fn guest_allocator_stats() -> Vec<u8> {
+    // The heap is not locked here, so this can allocate
+    allocator_stats().as_bytes().to_vec()
+}
+
+pub(crate) fn register_allocator_stats_function() {
+    register_fn(
+        GUEST_ALLOCATOR_STATS_FUNCTION,
+        guest_allocator_stats as fn() -> Vec<u8>,
+    );
+}
@@ -60,6 +60,59 @@ pub type ExceptionHandler = fn(
     page_fault_address: u64,
 ) -> bool;
 
+/// The lowest address the main stack may grow down to, honouring the
+/// stack size configured by the host, if any.
+fn main_stack_limit_gva() -> u64 {
+    let stack_size = unsafe { crate::GUEST_HANDLE }
+        .peb()
+        .map(|peb| unsafe { (*peb).guest_stack_size })
+        .unwrap_or(0);
+    if stack_size == 0 {
+        return MAIN_STACK_LIMIT_GVA;
+    }
+    MAIN_STACK_TOP_GVA
+        .saturating_sub(stack_size)
+        .max(MAIN_STACK_LIMIT_GVA)
+}
+
+/// Whether the host asked for the guest to fail when it executes a page
+/// it has written to.
+fn detect_self_modifying_code() -> bool {
+    unsafe { crate::GUEST_HANDLE }
+        .peb()
+        .is_some_and(|peb| unsafe { (*peb).detect_self_modifying_code } != 0)
+}
+
+/// Abort the call, sending the host the executed address in decimal.
+fn abort_self_modifying_code(gva: u64) -> ! {
+    // End any ongoing trace before aborting
+    #[cfg(feature = "trace_guest")]
+    hyperlight_guest_tracing::end_trace();
+
+    write_abort(&[ErrorCode::SelfModifyingCode as u8]);
+    let _ = write!(HyperlightAbortWriter, "{gva}");
+    write_abort(&[0xFF]);
+    unreachable!()
+}
+
+/// Abort the call after a SIMD floating-point exception, sending the host
+/// `mxcsr\0rip` in decimal, so that it can tell which exceptions trapped.
+fn abort_fp_exception(rip: u64) -> ! {
+    let mxcsr = super::super::fp::read_mxcsr();
+
+    // End any ongoing trace before aborting
+    #[cfg(feature = "trace_guest")]
+    hyperlight_guest_tracing::end_trace();
+
+    write_abort(&[
+        ErrorCode::GuestError as u8,
+        Exception::SIMDFloatingPointException as u8,
+    ]);
+    let _ = write!(HyperlightAbortWriter, "{mxcsr}\0{rip}");
+    write_abort(&[0xFF]);
+    unreachable!()
+}
+
 fn handle_stack_pagefault(gva: u64) {
     // TODO: perhaps we should have a sanity check that the
     // stack grows only one page at a time, which should be
@@ -116,7 +169,10 @@ fn handle_cow_pagefault(_phys: PhysAddr, virt: VirtAddr, perms: CowMapping) {
                 // CoW, so the copied mapping should always be
                 // writable
                 writable: true,
-                executable: perms.executable,
+                // When detecting self-modifying code, the copy is not
+                // executable, so that executing it faults after the
+                // write that caused it
+                executable: perms.executable && !detect_self_modifying_code(),
             }),
         );
         // This is updating an entry that was already valid, changing
@@ -135,7 +191,7 @@ fn try_handle_internal_pagefault(
     if !present {
         // If the fault was caused by a not-present page, check if we
         // should populate it with a stack page
-        if (MAIN_STACK_LIMIT_GVA..MAIN_STACK_TOP_GVA).contains(&gva) {
+        if (main_stack_limit_gva()..MAIN_STACK_TOP_GVA).contains(&gva) {
             handle_stack_pagefault(gva);
             return true;
         }
@@ -165,6 +221,15 @@ fn try_handle_internal_pagefault(
 
         return false;
     };
+    if access_was_insn && detect_self_modifying_code() {
+        // The guest executed a page it can write to, which is only
+        // mapped that way once the guest has written to it
+        if let Some(mapping) = orig_mappings.next()
+            && let MappingKind::Basic(BasicMapping { writable: true, .. }) = mapping.kind
+        {
+            abort_self_modifying_code(gva);
+        }
+    }
     false
 }
 
@@ -205,6 +270,9 @@ pub(crate) extern "C" fn hl_exception_handler(
 
     // Otherwise, abort due to unexpected exception
     let saved_rip = unsafe { (&raw const (*exn_info).rip).read_volatile() };
+    if matches!(exception, Exception::SIMDFloatingPointException) {
+        abort_fp_exception(saved_rip);
+    }
     let error_code = unsafe { (&raw const (*exn_info).error_code).read_volatile() };
     let bytes_at_rip = unsafe { (saved_rip as *const [u8; 8]).read_volatile() };
 
@@ -0,0 +1,51 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Trapping the floating-point exceptions the host asked for, see
+//! `SandboxConfiguration::set_fp_exception_mask`.
+
+use hyperlight_common::fp_exceptions::FpExceptions;
+
+/// The guest's current MXCSR register.
+pub(crate) fn read_mxcsr() -> u32 {
+    let mut mxcsr: u32 = 0;
+    unsafe {
+        core::arch::asm!("stmxcsr [{}]", in(reg) &mut mxcsr, options(nostack, preserves_flags));
+    }
+    mxcsr
+}
+
+/// Unmask the floating-point exceptions the host asked to trap, so that
+/// they raise a SIMD floating-point exception during the guest function
+/// call that is about to run. Restoring the sandbox resets MXCSR, so this
+/// is done at the start of every call.
+pub(crate) fn trap_fp_exceptions() {
+    let trapped = unsafe { crate::GUEST_HANDLE }
+        .peb()
+        .map(|peb| unsafe { (*peb).fp_exceptions_trapped })
+        .unwrap_or(0);
+    if trapped == 0 {
+        return;
+    }
+    let trapped = FpExceptions::from_bits_truncate(trapped as u32);
+    // Clear the flags of exceptions that occurred before the call, so that
+    // they are not blamed on it
+    let mxcsr =
+        (FpExceptions::all() - trapped).apply_mask(read_mxcsr()) & !FpExceptions::all().bits();
+    unsafe {
+        core::arch::asm!("ldmxcsr [{}]", in(reg) &mxcsr, options(nostack, preserves_flags, readonly));
+    }
+}
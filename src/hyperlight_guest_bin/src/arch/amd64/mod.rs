@@ -17,6 +17,7 @@ limitations under the License.
 pub(crate) mod context;
 pub(crate) mod dispatch;
 pub mod exception;
+pub(crate) mod fp;
 mod init;
 mod layout;
 pub(crate) mod machine;
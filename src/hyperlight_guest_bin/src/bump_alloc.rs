@@ -0,0 +1,133 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The bump allocation mode of the guest heap, selected by the host with
+//! `AllocMode::Bump` (from the PEB).
+//!
+//! In bump mode each allocation is placed after the previous one, and
+//! freeing memory only reclaims it if it was the last allocation. The
+//! bump pointer lives in guest memory, so when the host restores the
+//! sandbox's memory, for example to reset it to its baseline after a
+//! call, the pointer is reset with it and everything allocated since is
+//! reclaimed.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Whether the heap is in bump mode.
+static BUMP: AtomicBool = AtomicBool::new(false);
+/// The address of the first free byte of the heap.
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+/// The address just past the end of the heap.
+static END: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocate from the heap at `start`, of `size` bytes, in bump mode
+/// instead of with the general allocator.
+pub(crate) fn init(start: usize, size: usize) {
+    NEXT.store(start, Ordering::Relaxed);
+    END.store(start + size, Ordering::Relaxed);
+    BUMP.store(true, Ordering::Relaxed);
+}
+
+fn is_bump() -> bool {
+    BUMP.load(Ordering::Relaxed)
+}
+
+fn bump(layout: Layout) -> *mut u8 {
+    let next = NEXT.load(Ordering::Relaxed);
+    // The alignment of a layout is a power of two
+    let Some(start) = next
+        .checked_add(layout.align() - 1)
+        .map(|addr| addr & !(layout.align() - 1))
+    else {
+        return ptr::null_mut();
+    };
+    match start.checked_add(layout.size()) {
+        Some(end) if end <= END.load(Ordering::Relaxed) => {
+            NEXT.store(end, Ordering::Relaxed);
+            start as *mut u8
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Whether the block at `ptr` of `size` bytes is the last one allocated.
+fn is_last(ptr: *mut u8, size: usize) -> bool {
+    ptr as usize + size == NEXT.load(Ordering::Relaxed)
+}
+
+/// Wraps the guest heap so that, in bump mode, allocations bypass it and
+/// are bumped from the heap memory instead.
+pub(crate) struct ModalHeap<H>(pub(crate) H);
+
+unsafe impl<H: GlobalAlloc> GlobalAlloc for ModalHeap<H> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if is_bump() {
+            bump(layout)
+        } else {
+            unsafe { self.0.alloc(layout) }
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if !is_bump() {
+            unsafe { self.0.dealloc(ptr, layout) }
+        } else if is_last(ptr, layout.size()) {
+            NEXT.store(ptr as usize, Ordering::Relaxed);
+        }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !is_bump() {
+            return unsafe { self.0.alloc_zeroed(layout) };
+        }
+        // Memory that was freed, or restored from the baseline, may not
+        // be zero
+        let ptr = bump(layout);
+        if !ptr.is_null() {
+            unsafe { ptr.write_bytes(0, layout.size()) };
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if !is_bump() {
+            return unsafe { self.0.realloc(ptr, layout, new_size) };
+        }
+        // The last block can grow or shrink in place
+        if is_last(ptr, layout.size()) {
+            match (ptr as usize).checked_add(new_size) {
+                Some(end) if end <= END.load(Ordering::Relaxed) => {
+                    NEXT.store(end, Ordering::Relaxed);
+                    return ptr;
+                }
+                _ => return ptr::null_mut(),
+            }
+        }
+        if new_size <= layout.size() {
+            return ptr;
+        }
+        let Ok(new_layout) = Layout::from_size_align(new_size, layout.align()) else {
+            return ptr::null_mut();
+        };
+        let new_ptr = bump(new_layout);
+        if !new_ptr.is_null() {
+            unsafe { ptr::copy_nonoverlapping(ptr, new_ptr, layout.size()) };
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,118 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Guest functions that suspend while they wait for a result from the
+//! host, which the host fulfils and passes back with
+//! `MultiUseSandbox::resume`.
+//!
+//! A suspending guest function registers the rest of its work as a
+//! continuation with [`suspend`] and returns the token it gets back. The
+//! host then does whatever the guest was waiting for, and resumes the
+//! continuation with its result in a guest call of its own. A continuation
+//! can suspend again by returning [`Poll::Pending`] with the next
+//! continuation, which is resumed with the same token.
+//!
+//! Everything a continuation needs across a suspension has to be moved
+//! into it: the guest's stack unwinds when the suspending function
+//! returns, so only what is captured by the continuation or kept in
+//! statics survives until it is resumed.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use hyperlight_common::builtin_functions::{CANCEL_CONTINUATION, RESUME_CONTINUATION};
+use hyperlight_guest::bail;
+use hyperlight_guest::error::Result;
+
+use crate::guest_function::register::register_fn;
+
+/// The rest of a suspended guest function's work, which is run with the
+/// host's result when the host resumes it.
+pub type Continuation = Box<dyn FnOnce(Vec<u8>) -> Result<Poll>>;
+
+/// What a [`Continuation`] did with the host's result.
+pub enum Poll {
+    /// The guest function finished with this result.
+    Ready(Vec<u8>),
+    /// The guest function is waiting for another result from the host,
+    /// with which this continuation is resumed.
+    Pending(Continuation),
+}
+
+struct Continuations {
+    /// The token of the last continuation suspended
+    last_token: u64,
+    suspended: BTreeMap<u64, Continuation>,
+}
+
+static mut CONTINUATIONS: Continuations = Continuations {
+    last_token: 0,
+    suspended: BTreeMap::new(),
+};
+
+fn continuations() -> &'static mut Continuations {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    unsafe {
+        &mut CONTINUATIONS
+    }
+}
+
+/// Suspend a guest function until the host resumes it, with `continuation`
+/// doing the rest of its work, and return the token that a suspending
+/// guest function returns to the host.
+///
+/// The continuation stays suspended until it returns [`Poll::Ready`] or
+/// fails, or the host cancels it.
+pub fn suspend(continuation: impl FnOnce(Vec<u8>) -> Result<Poll> + 'static) -> u64 {
+    let continuations = continuations();
+    continuations.last_token += 1;
+    let token = continuations.last_token;
+    continuations
+        .suspended
+        .insert(token, Box::new(continuation));
+    token
+}
+
+fn resume_continuation(token: u64, result: Vec<u8>) -> Result<Option<Vec<u8>>> {
+    // The continuation is taken out while it runs, so that it can suspend
+    // other guest functions
+    let Some(continuation) = continuations().suspended.remove(&token) else {
+        bail!("No suspended continuation with token {token}");
+    };
+    match continuation(result)? {
+        Poll::Ready(result) => Ok(Some(result)),
+        Poll::Pending(next) => {
+            continuations().suspended.insert(token, next);
+            Ok(None)
+        }
+    }
+}
+
+fn cancel_continuation(token: u64) {
+    continuations().suspended.remove(&token);
+}
+
+/// Register the built-in guest functions that resume continuations.
+pub(crate) fn register_continuation_functions() {
+    register_fn(
+        RESUME_CONTINUATION,
+        resume_continuation as fn(u64, Vec<u8>) -> Result<Option<Vec<u8>>>,
+    );
+    register_fn(CANCEL_CONTINUATION, cancel_continuation as fn(u64));
+}
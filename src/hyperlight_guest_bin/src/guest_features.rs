@@ -0,0 +1,78 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Reporting the features this guest was built with to the host, which
+//! reads them with `MultiUseSandbox::guest_features`.
+//!
+//! The features of this crate are reported automatically. A guest adds
+//! its own with [`declare_application_features`], usually in
+//! `hyperlight_main`.
+
+use hyperlight_common::guest_features::GUEST_FEATURES_FUNCTION;
+pub use hyperlight_common::guest_features::GuestFeatures;
+
+use crate::guest_function::register::register_fn;
+
+static mut APPLICATION_FEATURES: GuestFeatures = GuestFeatures::empty();
+
+/// Declare features of the guest itself, made with
+/// [`GuestFeatures::application`], to be reported to the host along with
+/// the features of this crate. Features declared in earlier calls are
+/// kept.
+pub fn declare_application_features(features: GuestFeatures) {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    unsafe {
+        APPLICATION_FEATURES |= features;
+    }
+}
+
+/// The features of this crate that the guest was built with.
+const fn built_in_features() -> GuestFeatures {
+    let mut features = GuestFeatures::empty();
+    if cfg!(debug_assertions) {
+        features = features.union(GuestFeatures::DEBUG_ASSERTIONS);
+    }
+    if cfg!(feature = "trace_guest") {
+        features = features.union(GuestFeatures::TRACE_GUEST);
+    }
+    if cfg!(feature = "mem_profile") {
+        features = features.union(GuestFeatures::MEM_PROFILE);
+    }
+    if cfg!(feature = "libc") {
+        features = features.union(GuestFeatures::LIBC);
+    }
+    if cfg!(feature = "macros") {
+        features = features.union(GuestFeatures::MACROS);
+    }
+    if cfg!(feature = "allocator_stats") {
+        features = features.union(GuestFeatures::ALLOCATOR_STATS);
+    }
+    features
+}
+
+fn guest_features() -> u64 {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let application = unsafe { APPLICATION_FEATURES };
+    built_in_features().union(application).bits()
+}
+
+pub(crate) fn register_guest_features_function() {
+    register_fn(GUEST_FEATURES_FUNCTION, guest_features as fn() -> u64);
+}
@@ -14,18 +14,180 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use alloc::collections::BTreeMap;
 use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 
 use flatbuffers::FlatBufferBuilder;
+use hyperlight_common::builtin_functions::{
+    GUEST_FUNCTION_ALIASES, GUEST_FUNCTION_SCHEMA, GUEST_FUNCTION_TABLE,
+};
 use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
 use hyperlight_common::flatbuffer_wrappers::function_types::{FunctionCallResult, ParameterType};
 use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
+use hyperlight_common::flatbuffer_wrappers::host_function_definition::HostFunctionDefinition;
+use hyperlight_common::flatbuffer_wrappers::host_function_details::HostFunctionDetails;
 use hyperlight_guest::bail;
 use hyperlight_guest::error::{HyperlightGuestError, Result};
 use tracing::instrument;
 
-use crate::{GUEST_HANDLE, REGISTERED_GUEST_FUNCTIONS};
+use super::register::register_fn;
+use crate::host_comm::call_host;
+use crate::{GUEST_HANDLE, REGISTERED_GUEST_FUNCTIONS, alloc_budget};
+
+/// Guest function aliases configured on the host, mapping the name a caller
+/// uses to the name of the function to dispatch to.
+static mut GUEST_FUNCTION_ALIAS_TABLE: BTreeMap<String, String> = BTreeMap::new();
+
+/// Fetch the guest function aliases configured on the host.
+///
+/// This is called once while the guest is initialised, so that the aliases
+/// are part of the initialised guest's state. The host marks in the PEB
+/// whether it has any, so a guest without aliases does not call it. A table
+/// that is not consecutive NUL-terminated alias and target names is an
+/// error.
+pub(crate) fn load_guest_function_aliases() -> Result<()> {
+    let has_aliases = unsafe { GUEST_HANDLE }
+        .peb()
+        .is_some_and(|peb| unsafe { (*peb).guest_function_aliases } != 0);
+    if !has_aliases {
+        return Ok(());
+    }
+
+    let bytes = call_host::<Vec<u8>>(GUEST_FUNCTION_ALIASES, ())?;
+    if bytes.last().is_some_and(|&b| b != 0) {
+        bail!(ErrorCode::GuestError => "Guest function aliases are not NUL-terminated");
+    }
+    let names = bytes
+        .split_terminator(|&b| b == 0)
+        .map(|name| String::from_utf8(name.to_vec()))
+        .collect::<core::result::Result<Vec<_>, _>>()
+        .map_err(|e| {
+            HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                format!("Guest function alias is not valid UTF-8: {e}"),
+            )
+        })?;
+    if names.len() % 2 != 0 {
+        bail!(ErrorCode::GuestError => "Guest function alias {:?} has no target", names[names.len() - 1]);
+    }
+
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let aliases = unsafe { &mut GUEST_FUNCTION_ALIAS_TABLE };
+    let mut names = names.into_iter();
+    while let (Some(alias), Some(target)) = (names.next(), names.next()) {
+        aliases.insert(alias, target);
+    }
+    Ok(())
+}
+
+/// Rewrite the name of `function_call` if the host aliased it to another
+/// guest function.
+fn resolve_guest_function_alias(function_call: &mut FunctionCall) {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let aliases = unsafe { &GUEST_FUNCTION_ALIAS_TABLE };
+    if let Some(target) = aliases.get(&function_call.function_name) {
+        tracing::info!(
+            alias = %function_call.function_name,
+            function_name = %target,
+            "resolved guest function alias"
+        );
+        function_call.function_name = target.clone();
+    }
+}
+
+/// Register the built-in guest function that lets the host fetch the
+/// schema of a guest function, to validate calls to it once rather than on
+/// every call.
+pub(crate) fn register_guest_function_schema() {
+    register_fn(
+        GUEST_FUNCTION_SCHEMA,
+        guest_function_schema as fn(String) -> Result<Vec<u8>>,
+    );
+}
+
+/// The schema of the registered guest function `function_name`: the
+/// generation of the registered functions as 8 little-endian bytes,
+/// followed by the function's signature, encoded as a
+/// `HostFunctionDefinition`.
+///
+/// A call that carries this generation skips verifying its parameters for
+/// as long as no function is registered in the meantime.
+fn guest_function_schema(function_name: String) -> Result<Vec<u8>> {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let (aliases, registered_functions) =
+        unsafe { (&GUEST_FUNCTION_ALIAS_TABLE, &REGISTERED_GUEST_FUNCTIONS) };
+    let name = aliases.get(&function_name).unwrap_or(&function_name);
+    let Some(definition) = registered_functions.get(name) else {
+        bail!(ErrorCode::GuestFunctionNotFound => "No registered guest function: {name:#?}");
+    };
+
+    let signature = HostFunctionDefinition::new(
+        definition.function_name.clone(),
+        Some(definition.parameter_types.clone()),
+        definition.return_type,
+    );
+    let encoded: Vec<u8> = (&signature).try_into().map_err(|e| {
+        HyperlightGuestError::new(
+            ErrorCode::GuestError,
+            format!("Failed to encode the schema of guest function {name}: {e}"),
+        )
+    })?;
+
+    let mut schema = registered_functions.generation().to_le_bytes().to_vec();
+    schema.extend_from_slice(&encoded);
+    Ok(schema)
+}
+
+/// Register the built-in guest function that lets the host list the
+/// registered guest functions, for diagnostics.
+pub(crate) fn register_guest_function_table() {
+    register_fn(
+        GUEST_FUNCTION_TABLE,
+        guest_function_table as fn() -> Result<Vec<u8>>,
+    );
+}
+
+/// The signatures of the registered guest functions, including those
+/// registered after the guest was initialised: the number of functions as
+/// 4 little-endian bytes, then the number of parameters with a default
+/// value of each function in the same way, followed by the functions'
+/// signatures, encoded as a `HostFunctionDetails`.
+fn guest_function_table() -> Result<Vec<u8>> {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let registered_functions = unsafe { &REGISTERED_GUEST_FUNCTIONS };
+
+    let mut table = (registered_functions.len() as u32).to_le_bytes().to_vec();
+    let mut signatures = Vec::with_capacity(registered_functions.len());
+    for definition in registered_functions.iter() {
+        table.extend_from_slice(&(definition.default_values().len() as u32).to_le_bytes());
+        signatures.push(HostFunctionDefinition::new(
+            definition.function_name.clone(),
+            Some(definition.parameter_types.clone()),
+            definition.return_type,
+        ));
+    }
+    let details = HostFunctionDetails {
+        host_functions: Some(signatures),
+    };
+    let encoded: Vec<u8> = (&details).try_into().map_err(|e| {
+        HyperlightGuestError::new(
+            ErrorCode::GuestError,
+            format!("Failed to encode the guest function table: {e}"),
+        )
+    })?;
+    table.extend_from_slice(&encoded);
+    Ok(table)
+}
 
 core::arch::global_asm!(
     ".weak guest_dispatch_function",
@@ -40,7 +202,7 @@ fn guest_dispatch_function_default(function_call: FunctionCall) -> Result<Vec<u8
 }
 
 #[instrument(skip_all, level = "Info")]
-pub(crate) fn call_guest_function(function_call: FunctionCall) -> Result<Vec<u8>> {
+pub(crate) fn call_guest_function(mut function_call: FunctionCall) -> Result<Vec<u8>> {
     // Validate this is a Guest Function Call
     if function_call.function_call_type() != FunctionCallType::Guest {
         return Err(HyperlightGuestError::new(
@@ -52,22 +214,35 @@ pub(crate) fn call_guest_function(function_call: FunctionCall) -> Result<Vec<u8>
         ));
     }
 
+    // Resolve aliases first, so that both registered functions and
+    // guest_dispatch_function see the name of the function to call.
+    resolve_guest_function_alias(&mut function_call);
+
     // Find the function definition for the function call.
     // Use &raw const to get an immutable reference to the static HashMap
     // this is to avoid the clippy warning "shared reference to mutable static"
     #[allow(clippy::deref_addrof)]
+    let registered_functions = unsafe { &*(&raw const REGISTERED_GUEST_FUNCTIONS) };
     if let Some(registered_function_definition) =
-        unsafe { (*(&raw const REGISTERED_GUEST_FUNCTIONS)).get(&function_call.function_name) }
+        registered_functions.get(&function_call.function_name)
     {
-        let function_call_parameter_types: Vec<ParameterType> = function_call
-            .parameters
-            .iter()
-            .flatten()
-            .map(|p| p.into())
-            .collect();
+        registered_function_definition.apply_defaults(&mut function_call);
 
-        // Verify that the function call has the correct parameter types and length.
-        registered_function_definition.verify_parameters(&function_call_parameter_types)?;
+        // The host already validated the parameters of a call that carries
+        // the current generation, see `guest_function_schema`. Any other
+        // call, including one validated before a function was registered
+        // again, is verified here.
+        if function_call.schema_generation != registered_functions.generation() {
+            let function_call_parameter_types: Vec<ParameterType> = function_call
+                .parameters
+                .iter()
+                .flatten()
+                .map(|p| p.into())
+                .collect();
+
+            // Verify that the function call has the correct parameter types and length.
+            registered_function_definition.verify_parameters(&function_call_parameter_types)?;
+        }
 
         (registered_function_definition.function_pointer)(function_call)
     } else {
@@ -104,7 +279,11 @@ pub(crate) fn internal_dispatch_function() {
         .try_pop_shared_input_data_into::<FunctionCall>()
         .expect("Function call deserialization failed");
 
+    let outer_call = alloc_budget::begin_call();
+    #[cfg(target_arch = "x86_64")]
+    crate::arch::fp::trap_fp_exceptions();
     let res = call_guest_function(function_call);
+    alloc_budget::end_call(outer_call);
 
     match res {
         Ok(bytes) => {
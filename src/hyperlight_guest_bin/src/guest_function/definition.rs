@@ -18,8 +18,11 @@ use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 
+use flatbuffers::FlatBufferBuilder;
 use hyperlight_common::flatbuffer_wrappers::function_call::FunctionCall;
-use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ReturnType};
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    FunctionCallResult, ParameterType, ParameterValue, ReturnType,
+};
 use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
 use hyperlight_common::flatbuffer_wrappers::util::get_flatbuffer_result;
 use hyperlight_common::for_each_tuple;
@@ -45,6 +48,11 @@ pub struct GuestFunctionDefinition<F: Copy> {
     pub return_type: ReturnType,
     /// The function pointer to the guest function.
     pub function_pointer: F,
+    /// The default values of the trailing parameters, which the host may
+    /// omit when calling the function. Only set by
+    /// [`with_defaults`](Self::with_defaults), which checks them against
+    /// `parameter_types`.
+    default_values: Vec<ParameterValue>,
 }
 
 /// Trait for functions that can be converted to a `fn(FunctionCall) -> Result<Vec<u8>>`
@@ -90,6 +98,18 @@ fn into_flatbuffer_result(value: ReturnValue) -> Vec<u8> {
         ReturnValue::Bool(b) => get_flatbuffer_result(b),
         ReturnValue::String(s) => get_flatbuffer_result(s.as_str()),
         ReturnValue::VecBytes(v) => get_flatbuffer_result(v.as_slice()),
+        ReturnValue::VecFloat(v) => get_flatbuffer_result(v.as_slice()),
+        ReturnValue::VecDouble(v) => get_flatbuffer_result(v.as_slice()),
+        ReturnValue::MultiBuffer(v) => {
+            let buffers: Vec<&[u8]> = v.iter().map(Vec::as_slice).collect();
+            get_flatbuffer_result(buffers.as_slice())
+        }
+        ReturnValue::StatusWithPayload { code, data } => {
+            get_flatbuffer_result((code, data.as_slice()))
+        }
+        ReturnValue::Optional(_) => FunctionCallResult::new(Ok(value))
+            .encode(&mut FlatBufferBuilder::new())
+            .to_vec(),
     }
 }
 
@@ -169,6 +189,7 @@ where
             parameter_types,
             return_type,
             function_pointer,
+            default_values: Vec::new(),
         }
     }
 }
@@ -188,7 +209,73 @@ impl<F: Copy> GuestFunctionDefinition<F> {
             parameter_types,
             return_type,
             function_pointer,
+            default_values: Vec::new(),
+        }
+    }
+
+    /// Set the default values of the last `default_values.len()`
+    /// parameters, which are passed to the function when a call omits them.
+    ///
+    /// Fails if there are more default values than parameters, or if a
+    /// default value does not have the type of its parameter.
+    pub fn with_defaults(mut self, default_values: Vec<ParameterValue>) -> Result<Self> {
+        let Some(first_default) = self.parameter_types.len().checked_sub(default_values.len())
+        else {
+            return Err(HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                format!(
+                    "Function {} has {} default values but only {} parameters.",
+                    self.function_name,
+                    default_values.len(),
+                    self.parameter_types.len()
+                ),
+            ));
+        };
+        for (i, value) in default_values.iter().enumerate() {
+            let index = first_default + i;
+            let value_type = ParameterType::from(value);
+            if value_type != self.parameter_types[index] {
+                return Err(HyperlightGuestError::new(
+                    ErrorCode::GuestFunctionParameterTypeMismatch,
+                    format!(
+                        "Default value for parameter index {} of function {} has type {:?} but the parameter has type {:?}.",
+                        index, self.function_name, value_type, self.parameter_types[index]
+                    ),
+                ));
+            }
+        }
+        self.default_values = default_values;
+        Ok(self)
+    }
+
+    /// The default values of the trailing parameters, see
+    /// [`with_defaults`](Self::with_defaults).
+    pub fn default_values(&self) -> &[ParameterValue] {
+        &self.default_values
+    }
+
+    /// The number of parameters that a call must pass, which are those
+    /// before the ones with default values.
+    fn required_parameters(&self) -> usize {
+        // `parameter_types` is public, so it may have been shortened since
+        // the default values were checked against it
+        self.parameter_types
+            .len()
+            .saturating_sub(self.default_values.len())
+    }
+
+    /// Append the default values of the trailing parameters that
+    /// `function_call` omits, if it omits only parameters that have one.
+    pub fn apply_defaults(&self, function_call: &mut FunctionCall) {
+        let given = function_call.parameters.as_ref().map_or(0, Vec::len);
+        let first_default = self.required_parameters();
+        if given < first_default || given >= self.parameter_types.len() {
+            return;
         }
+        function_call
+            .parameters
+            .get_or_insert_default()
+            .extend_from_slice(&self.default_values[given - first_default..]);
     }
 
     /// Create a new `GuestFunctionDefinition<GuestFunc>` from a function that
@@ -221,13 +308,19 @@ impl<F: Copy> GuestFunctionDefinition<F> {
         }
 
         if self.parameter_types.len() != parameter_types.len() {
+            let required = self.required_parameters();
+            let takes = if required == self.parameter_types.len() {
+                format!("{}", required)
+            } else {
+                format!("{} to {}", required, self.parameter_types.len())
+            };
             return Err(HyperlightGuestError::new(
                 ErrorCode::GuestFunctionIncorrecNoOfParameters,
                 format!(
                     "Called function {} with {} parameters but it takes {}.",
                     self.function_name,
                     parameter_types.len(),
-                    self.parameter_types.len()
+                    takes
                 ),
             ));
         }
@@ -28,13 +28,13 @@ use crate::guest_function::definition::AsGuestFunctionDefinition;
 pub struct GuestFunctionRegister<F: Copy> {
     /// Currently registered guest functions
     guest_functions: BTreeMap<String, GuestFunctionDefinition<F>>,
+    /// The number of registrations so far, see [`generation`](Self::generation)
+    generation: u64,
 }
 
 impl<F: Copy> Default for GuestFunctionRegister<F> {
     fn default() -> Self {
-        Self {
-            guest_functions: BTreeMap::new(),
-        }
+        Self::new()
     }
 }
 
@@ -43,6 +43,7 @@ impl<F: Copy> GuestFunctionRegister<F> {
     pub const fn new() -> Self {
         Self {
             guest_functions: BTreeMap::new(),
+            generation: 0,
         }
     }
 
@@ -54,14 +55,45 @@ impl<F: Copy> GuestFunctionRegister<F> {
         &mut self,
         guest_function: GuestFunctionDefinition<F>,
     ) -> Option<GuestFunctionDefinition<F>> {
+        self.generation += 1;
         self.guest_functions
             .insert(guest_function.function_name.clone(), guest_function)
     }
 
+    /// The generation of the registered functions, which changes every time
+    /// a function is registered. It is 0 until the first registration.
+    ///
+    /// A caller that validated a call against the signature of a function
+    /// can skip validating it again for as long as the generation is the
+    /// same.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Gets a `GuestFunctionDefinition` by its `name` field.
     pub fn get(&self, function_name: &str) -> Option<&GuestFunctionDefinition<F>> {
         self.guest_functions.get(function_name)
     }
+
+    /// Whether a function named `function_name` is registered.
+    pub fn contains(&self, function_name: &str) -> bool {
+        self.guest_functions.contains_key(function_name)
+    }
+
+    /// The number of registered functions.
+    pub fn len(&self) -> usize {
+        self.guest_functions.len()
+    }
+
+    /// Whether no functions are registered.
+    pub fn is_empty(&self) -> bool {
+        self.guest_functions.is_empty()
+    }
+
+    /// The registered functions, in order of their names.
+    pub fn iter(&self) -> impl Iterator<Item = &GuestFunctionDefinition<F>> {
+        self.guest_functions.values()
+    }
 }
 
 impl GuestFunctionRegister<GuestFunc> {
@@ -0,0 +1,93 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Guest functions that return their result as a stream of chunks, which
+//! the host pulls one at a time with `MultiUseSandbox::next_chunk`.
+//!
+//! A streaming guest function opens a stream with [`open_stream`] and
+//! returns its handle. Each time the host asks for the next chunk, the
+//! guest advances the stream's iterator, so chunks are produced lazily, in
+//! as many guest calls as there are chunks.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use hyperlight_common::builtin_functions::{CLOSE_GUEST_STREAM, NEXT_GUEST_STREAM_CHUNK};
+use hyperlight_guest::bail;
+use hyperlight_guest::error::Result;
+
+use crate::guest_function::register::register_fn;
+
+type Chunks = Box<dyn Iterator<Item = Vec<u8>>>;
+
+struct GuestStreams {
+    /// The handle of the last stream opened
+    last_handle: u64,
+    open: BTreeMap<u64, Chunks>,
+}
+
+static mut GUEST_STREAMS: GuestStreams = GuestStreams {
+    last_handle: 0,
+    open: BTreeMap::new(),
+};
+
+fn guest_streams() -> &'static mut GuestStreams {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    unsafe {
+        &mut GUEST_STREAMS
+    }
+}
+
+/// Open a stream that yields `chunks`, and return the handle that a
+/// streaming guest function returns to the host.
+///
+/// The stream stays open until the host has pulled its last chunk or
+/// closed it.
+pub fn open_stream(chunks: impl Iterator<Item = Vec<u8>> + 'static) -> u64 {
+    let streams = guest_streams();
+    streams.last_handle += 1;
+    let handle = streams.last_handle;
+    streams.open.insert(handle, Box::new(chunks));
+    handle
+}
+
+fn next_guest_stream_chunk(handle: u64) -> Result<Option<Vec<u8>>> {
+    let streams = guest_streams();
+    let Some(chunks) = streams.open.get_mut(&handle) else {
+        bail!("No open guest stream with handle {handle}");
+    };
+    let chunk = chunks.next();
+    if chunk.is_none() {
+        streams.open.remove(&handle);
+    }
+    Ok(chunk)
+}
+
+fn close_guest_stream(handle: u64) {
+    guest_streams().open.remove(&handle);
+}
+
+/// Register the built-in guest functions that serve streams to the host.
+pub(crate) fn register_guest_stream_functions() {
+    register_fn(
+        NEXT_GUEST_STREAM_CHUNK,
+        next_guest_stream_chunk as fn(u64) -> Result<Option<Vec<u8>>>,
+    );
+    register_fn(CLOSE_GUEST_STREAM, close_guest_stream as fn(u64));
+}
@@ -14,17 +14,27 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use alloc::string::ToString;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
+use flatbuffers::FlatBufferBuilder;
+use hyperlight_common::builtin_functions::{
+    CALL_SANDBOX_HOST_FUNCTION, CURRENT_REQUEST_ID, EMIT_COUNTER, EMIT_EVENT, EMIT_GAUGE,
+    GUEST_ARGS, GUEST_ENV_NAMES, GUEST_GETENV, HOST_FUNCTION_SIGNATURE, INIT_PROGRESS,
+    INVOKE_HOST_CALLBACK, MONOTONIC_NS, SIGNAL_READY,
+};
 use hyperlight_common::flatbuffer_wrappers::function_call::FunctionCall;
 use hyperlight_common::flatbuffer_wrappers::function_types::{
-    ParameterValue, ReturnType, ReturnValue,
+    FunctionCallResult, ParameterType, ParameterValue, ReturnType, ReturnValue,
 };
 use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::flatbuffer_wrappers::host_function_definition::HostFunctionDefinition;
 use hyperlight_common::flatbuffer_wrappers::util::get_flatbuffer_result;
 use hyperlight_common::func::{ParameterTuple, SupportedReturnType};
 use hyperlight_guest::error::{HyperlightGuestError, Result};
+pub use hyperlight_guest::guest_handle::host_comm::HostResult;
 
 use crate::GUEST_HANDLE;
 
@@ -47,6 +57,22 @@ where
     call_host_function::<T>(function_name.as_ref(), Some(args.into_value()), T::TYPE)
 }
 
+/// Invoke the host callback `handle` with `args`.
+///
+/// Handles are passed to guest functions called with the host's
+/// `MultiUseSandbox::call_with_callback`, and are only valid until that
+/// guest function returns.
+pub fn call_host_callback<T>(handle: u64, args: impl ParameterTuple) -> Result<T>
+where
+    T: SupportedReturnType + TryFrom<ReturnValue>,
+{
+    let args = args.into_value();
+    let mut parameters = Vec::with_capacity(args.len() + 1);
+    parameters.push(ParameterValue::ULong(handle));
+    parameters.extend(args);
+    call_host_function::<T>(INVOKE_HOST_CALLBACK, Some(parameters), T::TYPE)
+}
+
 pub fn call_host_function_without_returning_result(
     function_name: &str,
     parameters: Option<Vec<ParameterValue>>,
@@ -56,6 +82,17 @@ pub fn call_host_function_without_returning_result(
     handle.call_host_function_without_returning_result(function_name, parameters, return_type)
 }
 
+/// Call a host function and return its [`HostResult`], leaving the
+/// guest to decide how to handle a failing host function.
+pub fn call_host_function_with_result(
+    function_name: &str,
+    parameters: Option<Vec<ParameterValue>>,
+    return_type: ReturnType,
+) -> Result<HostResult> {
+    let handle = unsafe { GUEST_HANDLE };
+    handle.call_host_function_with_result(function_name, parameters, return_type)
+}
+
 pub fn get_host_return_value_raw() -> Result<ReturnValue> {
     let handle = unsafe { GUEST_HANDLE };
     handle.get_host_return_raw()
@@ -66,6 +103,248 @@ pub fn get_host_return_value<T: TryFrom<ReturnValue>>() -> Result<T> {
     handle.get_host_return_value::<T>()
 }
 
+/// Host function signatures already fetched from the host, so that each
+/// signature is only requested once.
+static mut HOST_FUNCTION_SIGNATURES: BTreeMap<String, HostFunctionDefinition> = BTreeMap::new();
+
+/// Get the signature the host declared for the host function `function_name`.
+///
+/// The signature is fetched from the host on first use and cached afterwards.
+pub fn host_function_signature(function_name: &str) -> Result<HostFunctionDefinition> {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    let signatures = unsafe { &mut HOST_FUNCTION_SIGNATURES };
+    if let Some(definition) = signatures.get(function_name) {
+        return Ok(definition.clone());
+    }
+
+    let bytes = call_host::<Vec<u8>>(HOST_FUNCTION_SIGNATURE, function_name.to_string())?;
+    let definition = HostFunctionDefinition::try_from(bytes.as_slice()).map_err(|e| {
+        HyperlightGuestError::new(
+            ErrorCode::GuestError,
+            format!("Failed to decode signature of host function {function_name}: {e}"),
+        )
+    })?;
+    signatures.insert(function_name.to_string(), definition.clone());
+    Ok(definition)
+}
+
+/// Verify that calling the host function `function_name` with `parameters`
+/// and expecting `return_type` matches the signature declared by the host.
+///
+/// This catches mismatches in the guest, with an error naming the offending
+/// parameter, instead of waiting for the host to reject the call.
+pub fn verify_host_function_call(
+    function_name: &str,
+    parameters: &[ParameterValue],
+    return_type: ReturnType,
+) -> Result<()> {
+    let definition = host_function_signature(function_name)?;
+    let expected = definition.parameter_types.unwrap_or_default();
+
+    if expected.len() != parameters.len() {
+        return Err(HyperlightGuestError::new(
+            ErrorCode::GuestFunctionIncorrecNoOfParameters,
+            format!(
+                "Host function {} takes {} parameters but {} were supplied.",
+                function_name,
+                expected.len(),
+                parameters.len()
+            ),
+        ));
+    }
+
+    for (i, (expected, parameter)) in expected.iter().zip(parameters).enumerate() {
+        let actual: ParameterType = parameter.into();
+        if *expected != actual {
+            return Err(HyperlightGuestError::new(
+                ErrorCode::GuestFunctionParameterTypeMismatch,
+                format!(
+                    "Expected parameter type {:?} for parameter index {} of host function {} but got {:?}.",
+                    expected, i, function_name, actual
+                ),
+            ));
+        }
+    }
+
+    if definition.return_type != return_type {
+        return Err(HyperlightGuestError::new(
+            ErrorCode::GuestFunctionParameterTypeMismatch,
+            format!(
+                "Host function {} returns {:?} but {:?} was expected.",
+                function_name, definition.return_type, return_type
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Forward `function_call` to the sandbox registered with the host under
+/// `sandbox_name`, returning its encoded `FunctionCallResult`.
+///
+/// The returned bytes are in the same format a guest function returns,
+/// so they can be handed straight back to the host by the calling guest.
+pub fn call_sandbox_raw(sandbox_name: &str, function_call: &FunctionCall) -> Result<Vec<u8>> {
+    let mut builder = FlatBufferBuilder::new();
+    let call = function_call.encode(&mut builder).to_vec();
+    call_host::<Vec<u8>>(CALL_SANDBOX_HOST_FUNCTION, (sandbox_name.to_string(), call))
+}
+
+/// Call a guest function in the sandbox registered with the host under
+/// `sandbox_name` and return its result.
+pub fn call_sandbox(sandbox_name: &str, function_call: &FunctionCall) -> Result<ReturnValue> {
+    let bytes = call_sandbox_raw(sandbox_name, function_call)?;
+    FunctionCallResult::try_from(bytes.as_slice())
+        .map_err(|e| {
+            HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                format!("Failed to decode linked sandbox result: {e}"),
+            )
+        })?
+        .into_inner()
+        .map_err(|e| HyperlightGuestError::new(e.code, e.message))
+}
+
+/// Where in the output buffer the last partial result was published, so
+/// that the next one can replace it.
+static mut PARTIAL_RESULT_OFFSET: Option<u64> = None;
+
+/// Publish `value` as the partial result of the guest function currently
+/// being called.
+///
+/// If the host cancels the call through `MultiUseSandbox::call_with_timeout_partial`,
+/// the most recently published value is handed back to the host caller
+/// inside its timeout error. Partial results are discarded once the guest
+/// function returns. Each value replaces the one published before it in
+/// the output buffer, so a function can publish as often as it likes.
+pub fn publish_partial_result<T: SupportedReturnType>(value: T) -> Result<()> {
+    let handle = unsafe { GUEST_HANDLE };
+    let mut builder = FlatBufferBuilder::new();
+    let data = FunctionCallResult::new(Ok(value.into_value())).encode(&mut builder);
+    // This is currently safe, because we are single threaded
+    let offset = unsafe { &mut *(&raw mut PARTIAL_RESULT_OFFSET) };
+    *offset = Some(handle.replace_shared_output_data(*offset, data)?);
+    Ok(())
+}
+
+/// Add `value` to the counter `name`.
+///
+/// The host must have enabled guest metrics with
+/// `MultiUseSandbox::guest_metrics`, which also decides the prefix added to
+/// `name` on the host.
+pub fn emit_counter(name: &str, value: u64) -> Result<()> {
+    call_host::<()>(EMIT_COUNTER, (name.to_string(), value))
+}
+
+/// Set the gauge `name` to `value`.
+///
+/// The host must have enabled guest metrics with
+/// `MultiUseSandbox::guest_metrics`, which also decides the prefix added to
+/// `name` on the host.
+pub fn emit_gauge(name: &str, value: f64) -> Result<()> {
+    call_host::<()>(EMIT_GAUGE, (name.to_string(), value))
+}
+
+/// Emit an event of kind `kind` with `payload`, returning whether the host
+/// queued it or dropped it because its queue was full.
+///
+/// The host must have enabled guest events with
+/// `MultiUseSandbox::guest_events`.
+pub fn emit_event(kind: &str, payload: &[u8]) -> Result<bool> {
+    call_host::<bool>(EMIT_EVENT, (kind.to_string(), payload.to_vec()))
+}
+
+/// The request id the host tagged the current call with, using
+/// `MultiUseSandbox::call_with_request_id`, or `None` if it did not.
+pub fn current_request_id() -> Result<Option<String>> {
+    call_host::<Option<String>>(CURRENT_REQUEST_ID, ())
+}
+
+/// The value of the variable `name` in the environment the host set with
+/// `UninitializedSandbox::set_guest_env`, or `None` if it is not set.
+pub fn getenv(name: &str) -> Result<Option<String>> {
+    call_host::<Option<String>>(GUEST_GETENV, (name.to_string(),))
+}
+
+/// The names of the variables in the environment the host set with
+/// `UninitializedSandbox::set_guest_env`, in order.
+pub fn guest_env_names() -> Result<Vec<String>> {
+    let encoded = call_host::<Vec<u8>>(GUEST_ENV_NAMES, ())?;
+    Ok(encoded
+        .split_terminator(|&b| b == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
+
+/// The command-line arguments the host set with
+/// `UninitializedSandbox::set_guest_args`, in order, as argv would hold
+/// them in a POSIX program, so that the number of them is argc.
+///
+/// Each argument is the bytes the host set it as, which need not be UTF-8;
+/// use [`String::from_utf8_lossy`] where text is needed.
+pub fn guest_args() -> Result<Vec<Vec<u8>>> {
+    let encoded = call_host::<Vec<u8>>(GUEST_ARGS, ())?;
+    decode_guest_args(&encoded).ok_or_else(|| {
+        HyperlightGuestError::new(
+            ErrorCode::GuestError,
+            "Malformed command-line arguments from the host".to_string(),
+        )
+    })
+}
+
+/// Decode the arguments returned by [`GUEST_ARGS`]: their number as a
+/// little-endian `u32`, then each argument as its length as a
+/// little-endian `u32` followed by its bytes.
+fn decode_guest_args(encoded: &[u8]) -> Option<Vec<Vec<u8>>> {
+    fn take_u32(bytes: &mut &[u8]) -> Option<usize> {
+        let (len, rest) = bytes.split_first_chunk::<4>()?;
+        *bytes = rest;
+        Some(u32::from_le_bytes(*len) as usize)
+    }
+
+    let mut rest = encoded;
+    let argc = take_u32(&mut rest)?;
+    let mut args = Vec::new();
+    for _ in 0..argc {
+        let len = take_u32(&mut rest)?;
+        let (arg, tail) = rest.split_at_checked(len)?;
+        args.push(arg.to_vec());
+        rest = tail;
+    }
+    rest.is_empty().then_some(args)
+}
+
+/// The host's monotonic clock, in nanoseconds since an arbitrary point.
+///
+/// Each reading is a call to the host, so the clock is only useful for
+/// timing work that takes much longer than that call, which is on the
+/// order of microseconds. The clock is real time rather than guest
+/// execution time, so a guest that depends on its value does not behave
+/// deterministically.
+pub fn monotonic_ns() -> Result<u64> {
+    call_host::<u64>(MONOTONIC_NS, ())
+}
+
+/// Report the progress of the guest's initialisation to the host, which
+/// passes it to the callback of `evolve_with_progress`.
+///
+/// `completed` is the units of work done so far out of `total`, or 0 if
+/// the total is not known, and `stage` describes what the guest is doing.
+/// Reports made when the host is not tracking progress are ignored.
+pub fn report_init_progress(completed: u64, total: u64, stage: &str) -> Result<()> {
+    call_host::<()>(INIT_PROGRESS, (completed, total, stage.to_string()))
+}
+
+/// Signal to the host that the guest is ready for calls, once it has
+/// finished the setup it does after it was initialised. The host waits
+/// for this with `MultiUseSandbox::wait_until_ready`. Signalling more than
+/// once has no further effect.
+pub fn signal_ready() -> Result<()> {
+    call_host::<()>(SIGNAL_READY, ())
+}
+
 pub fn read_n_bytes_from_user_memory(num: u64) -> Result<Vec<u8>> {
     let handle = unsafe { GUEST_HANDLE };
     handle.read_n_bytes_from_user_memory(num)
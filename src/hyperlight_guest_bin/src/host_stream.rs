@@ -0,0 +1,105 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Reading the result of a streaming host function, which the host
+//! registered with `UninitializedSandbox::register_streaming_host_function`.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use hyperlight_common::builtin_functions::{CLOSE_HOST_STREAM, READ_HOST_STREAM};
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::func::ParameterTuple;
+use hyperlight_common::shared_ring::SharedRingError;
+use hyperlight_guest::error::{HyperlightGuestError, Result};
+
+use crate::host_comm::call_host;
+use crate::shared_ring::{GuestSharedRing, shared_ring};
+
+/// The result of a streaming host function, which the host writes to the
+/// shared ring as it produces it.
+///
+/// While a stream is open it is the only user of the messages the host
+/// pushes to the shared ring. Dropping a stream before its end discards
+/// what is left of it.
+pub struct HostStream {
+    handle: u64,
+    ring: GuestSharedRing,
+    /// Set once everything the host wrote is in the ring
+    eof: bool,
+}
+
+impl HostStream {
+    /// Call the streaming host function `function_name` with `args`,
+    /// closing any stream that is already open.
+    pub fn open(function_name: &str, args: impl ParameterTuple) -> Result<Self> {
+        let ring = shared_ring().ok_or_else(|| {
+            HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                "Host streams need a shared ring".to_string(),
+            )
+        })?;
+        let handle = call_host::<u64>(function_name, args)?;
+        Ok(Self {
+            handle,
+            ring,
+            eof: false,
+        })
+    }
+
+    /// The next part of the stream, or `None` once the stream has ended.
+    ///
+    /// The parts need not match how the host wrote the stream. Returns the
+    /// host function's error if it failed, once everything it wrote before
+    /// failing has been read.
+    pub fn read(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            if let Some(data) = self.ring.pop().map_err(ring_error)? {
+                return Ok(Some(data));
+            }
+            if self.eof {
+                return Ok(None);
+            }
+            // The host closes the stream if reading it fails, so it is
+            // only still open if the host says more is to come.
+            self.eof = true;
+            self.eof = !call_host::<bool>(READ_HOST_STREAM, (self.handle,))?;
+        }
+    }
+
+    /// Read the rest of the stream.
+    pub fn read_to_end(&mut self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        while let Some(part) = self.read()? {
+            data.extend_from_slice(&part);
+        }
+        Ok(data)
+    }
+}
+
+impl Drop for HostStream {
+    fn drop(&mut self) {
+        // Leave the ring empty for whatever uses it next
+        while let Ok(Some(_)) = self.ring.pop() {}
+        if !self.eof {
+            let _ = call_host::<()>(CLOSE_HOST_STREAM, (self.handle,));
+        }
+    }
+}
+
+fn ring_error(e: SharedRingError) -> HyperlightGuestError {
+    HyperlightGuestError::new(ErrorCode::GuestError, e.to_string())
+}
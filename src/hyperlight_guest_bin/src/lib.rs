@@ -20,22 +20,28 @@ extern crate alloc;
 
 use core::fmt::Write;
 
+use alloc_budget::BudgetedHeap;
 use arch::dispatch::dispatch_function;
 use buddy_system_allocator::LockedHeap;
+use bump_alloc::ModalHeap;
 use guest_function::register::GuestFunctionRegister;
 use guest_logger::init_logger;
 use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
 use hyperlight_common::log_level::GuestLogFilter;
-use hyperlight_common::mem::HyperlightPEB;
+use hyperlight_common::mem::{AllocMode, HyperlightPEB};
 #[cfg(feature = "mem_profile")]
 use hyperlight_common::outb::OutBAction;
 use hyperlight_guest::exit::write_abort;
 use hyperlight_guest::guest_handle::handle::GuestHandle;
 
 // === Modules ===
+mod alloc_budget;
+#[cfg(feature = "allocator_stats")]
+mod allocator_stats;
 #[cfg_attr(target_arch = "x86_64", path = "arch/amd64/mod.rs")]
 #[cfg_attr(target_arch = "aarch64", path = "arch/aarch64/mod.rs")]
 mod arch;
+mod bump_alloc;
 // temporarily expose the architecture-specific exception interface;
 // this should be replaced with something a bit more abstract in the
 // near future.
@@ -47,12 +53,17 @@ pub mod guest_function {
     pub mod register;
 }
 
+pub mod continuation;
 pub mod error;
+pub mod guest_features;
 pub mod guest_logger;
+pub mod guest_stream;
 pub mod host_comm;
+pub mod host_stream;
 pub mod memory;
 #[cfg(target_arch = "x86_64")]
 pub mod paging;
+pub mod shared_ring;
 
 /// Bridge between picolibc's POSIX expectations and the Hyperlight host.
 /// cbindgen:ignore
@@ -121,11 +132,12 @@ unsafe impl<const ORDER: usize> alloc::alloc::GlobalAlloc for ProfiledLockedHeap
 // === Globals ===
 #[cfg(not(all(feature = "mem_profile", target_arch = "x86_64")))]
 #[global_allocator]
-pub(crate) static HEAP_ALLOCATOR: LockedHeap<32> = LockedHeap::<32>::empty();
+pub(crate) static HEAP_ALLOCATOR: BudgetedHeap<ModalHeap<LockedHeap<32>>> =
+    BudgetedHeap(ModalHeap(LockedHeap::<32>::empty()));
 #[cfg(all(feature = "mem_profile", target_arch = "x86_64"))]
 #[global_allocator]
-pub(crate) static HEAP_ALLOCATOR: ProfiledLockedHeap<32> =
-    ProfiledLockedHeap(LockedHeap::<32>::empty());
+pub(crate) static HEAP_ALLOCATOR: BudgetedHeap<ModalHeap<ProfiledLockedHeap<32>>> =
+    BudgetedHeap(ModalHeap(ProfiledLockedHeap(LockedHeap::<32>::empty())));
 
 pub static mut GUEST_HANDLE: GuestHandle = GuestHandle::new();
 pub(crate) static mut REGISTERED_GUEST_FUNCTIONS: GuestFunctionRegister<GuestFunc> =
@@ -169,6 +181,29 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
     _panic_handler(info)
 }
 
+/// A hook run when the guest panics, which returns the error code to abort
+/// the guest with, see [`set_panic_hook`].
+pub type PanicHook = fn(&core::panic::PanicInfo) -> u8;
+
+static mut PANIC_HOOK: Option<PanicHook> = None;
+
+/// Install `hook` to run when the guest panics, and return the previous
+/// hook. `None` removes the hook.
+///
+/// The guest still aborts once the hook has returned, with the error code
+/// the hook returned and the panic message, so the host receives a
+/// `GuestAborted` error as for any other panic. Without a hook, the code is
+/// `ErrorCode::UnknownError`. The hook only runs for the first panic, so a
+/// hook that panics itself does not recurse.
+pub fn set_panic_hook(hook: Option<PanicHook>) -> Option<PanicHook> {
+    // This is currently safe, because we are single threaded, but we
+    // should find a better way to do this, see issue #808
+    #[allow(static_mut_refs)]
+    unsafe {
+        core::mem::replace(&mut PANIC_HOOK, hook)
+    }
+}
+
 /// A writer that sends all output to the hyperlight host
 /// using output ports. This allows us to not impose a
 /// buffering limit on error message size on the guest end,
@@ -185,8 +220,19 @@ impl core::fmt::Write for HyperlightAbortWriter {
 fn _panic_handler(info: &core::panic::PanicInfo) -> ! {
     let mut w = HyperlightAbortWriter;
 
+    // The hook is taken rather than called in place, so that a panic in
+    // the hook aborts without running it again
+    let code = match set_panic_hook(None) {
+        Some(hook) => match hook(info) {
+            // The terminator would end the abort before the message
+            0xFF => ErrorCode::UnknownError as u8,
+            code => code,
+        },
+        None => ErrorCode::UnknownError as u8,
+    };
+
     // begin abort sequence by writing the error code
-    write_abort(&[ErrorCode::UnknownError as u8]);
+    write_abort(&[code]);
 
     let write_res = write!(w, "{}", info);
     if write_res.is_err() {
@@ -228,6 +274,9 @@ pub(crate) extern "C" fn generic_init(
     ops: u64,
     max_log_level: u64,
 ) -> u64 {
+    // Check that the host agrees on the layout of the PEB before reading it.
+    hyperlight_guest::exit::report_abi_version();
+
     unsafe {
         GUEST_HANDLE = GuestHandle::init(peb_address as *mut HyperlightPEB);
         #[allow(static_mut_refs)]
@@ -235,14 +284,19 @@ pub(crate) extern "C" fn generic_init(
 
         let heap_start = (*peb_ptr).guest_heap.ptr as usize;
         let heap_size = (*peb_ptr).guest_heap.size as usize;
-        #[cfg(not(all(feature = "mem_profile", target_arch = "x86_64")))]
-        let heap_allocator = &HEAP_ALLOCATOR;
-        #[cfg(all(feature = "mem_profile", target_arch = "x86_64"))]
-        let heap_allocator = &HEAP_ALLOCATOR.0;
-        heap_allocator
-            .try_lock()
-            .expect("Failed to access HEAP_ALLOCATOR")
-            .init(heap_start, heap_size);
+        match AllocMode::from_u64((*peb_ptr).alloc_mode) {
+            AllocMode::General => {
+                #[cfg(not(all(feature = "mem_profile", target_arch = "x86_64")))]
+                let heap_allocator = &HEAP_ALLOCATOR.0.0;
+                #[cfg(all(feature = "mem_profile", target_arch = "x86_64"))]
+                let heap_allocator = &HEAP_ALLOCATOR.0.0.0;
+                heap_allocator
+                    .try_lock()
+                    .expect("Failed to access HEAP_ALLOCATOR")
+                    .init(heap_start, heap_size);
+            }
+            AllocMode::Bump => bump_alloc::init(heap_start, heap_size),
+        }
         peb_ptr
     };
 
@@ -280,6 +334,16 @@ pub(crate) extern "C" fn generic_init(
     #[cfg(all(feature = "trace_guest", target_arch = "x86_64"))]
     let _entered = tracing::span!(tracing::Level::INFO, "generic_init").entered();
 
+    guest_function::call::load_guest_function_aliases()
+        .expect("Failed to load the guest function aliases");
+    guest_function::call::register_guest_function_schema();
+    guest_function::call::register_guest_function_table();
+    guest_stream::register_guest_stream_functions();
+    continuation::register_continuation_functions();
+    guest_features::register_guest_features_function();
+    #[cfg(feature = "allocator_stats")]
+    allocator_stats::register_allocator_stats_function();
+
     #[cfg(feature = "macros")]
     for registration in __private::GUEST_FUNCTION_INIT {
         registration();
@@ -0,0 +1,79 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use core::convert::Infallible;
+use core::ptr;
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use hyperlight_common::shared_ring::SharedRing;
+use hyperlight_common::virtq::MemOps;
+
+use crate::GUEST_HANDLE;
+
+/// The guest's view of the shared ring, see [`shared_ring`].
+pub type GuestSharedRing = SharedRing<GuestMemOps>;
+
+/// [`MemOps`] for the guest, where addresses are guest virtual addresses.
+#[derive(Clone, Copy, Debug)]
+pub struct GuestMemOps;
+
+// SAFETY: the shared ring only accesses addresses within the region the
+// host described in the PEB, which is mapped for the lifetime of the guest.
+unsafe impl MemOps for GuestMemOps {
+    type Error = Infallible;
+
+    fn read(&self, addr: u64, dst: &mut [u8]) -> Result<(), Self::Error> {
+        unsafe { ptr::copy_nonoverlapping(addr as *const u8, dst.as_mut_ptr(), dst.len()) };
+        Ok(())
+    }
+
+    fn write(&self, addr: u64, src: &[u8]) -> Result<(), Self::Error> {
+        unsafe { ptr::copy_nonoverlapping(src.as_ptr(), addr as *mut u8, src.len()) };
+        Ok(())
+    }
+
+    fn load_acquire(&self, addr: u64) -> Result<u16, Self::Error> {
+        Ok(unsafe { AtomicU16::from_ptr(addr as *mut u16) }.load(Ordering::Acquire))
+    }
+
+    fn store_release(&self, addr: u64, val: u16) -> Result<(), Self::Error> {
+        unsafe { AtomicU16::from_ptr(addr as *mut u16) }.store(val, Ordering::Release);
+        Ok(())
+    }
+
+    unsafe fn as_slice(&self, addr: u64, len: usize) -> Result<&[u8], Self::Error> {
+        Ok(unsafe { core::slice::from_raw_parts(addr as *const u8, len) })
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn as_mut_slice(&self, addr: u64, len: usize) -> Result<&mut [u8], Self::Error> {
+        Ok(unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, len) })
+    }
+}
+
+/// Returns the guest's handle to the shared ring, or `None` if the host
+/// did not configure one.
+///
+/// The handle pops messages pushed by the host and pushes messages for the
+/// host to pop. Only one guest handle should be used at a time.
+pub fn shared_ring() -> Option<GuestSharedRing> {
+    let peb = unsafe { GUEST_HANDLE }.peb()?;
+    let region = unsafe { (*peb).shared_ring };
+    if region.size == 0 {
+        return None;
+    }
+    SharedRing::guest_handle(GuestMemOps, region.ptr, region.size as usize).ok()
+}
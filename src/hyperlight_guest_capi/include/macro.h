@@ -275,4 +275,6 @@ hl_ParameterType _##function##_parameter_types[] = { hl_ParameterType_##arg1, \
 // Note that the function must first have been defined using the HYPERLIGHT_WRAP_FUNCTION macro
 #define HYPERLIGHT_REGISTER_FUNCTION(name, function)   hl_register_function_definition( name, &_call_##function, _##function##_parameter_count, _##function##_parameter_types, _##function##_return_type )
 
+#define HYPERLIGHT_REGISTER_FUNCTION_WITH_DEFAULTS(name, function, default_count, defaults)   hl_register_function_definition_with_defaults( name, &_call_##function, _##function##_parameter_count, _##function##_parameter_types, _##function##_return_type, default_count, defaults )
+
 #endif  /* HYPERLIGHT_GUEST_MACRO_H */
@@ -19,18 +19,45 @@ use alloc::slice;
 use alloc::vec::Vec;
 use core::ffi::{CStr, c_char};
 
-use hyperlight_common::flatbuffer_wrappers::function_call::FunctionCall;
-use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ReturnType};
-use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use flatbuffers::FlatBufferBuilder;
+use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    FunctionCallResult, ParameterType, ReturnType,
+};
+use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
 use hyperlight_guest::error::{HyperlightGuestError, Result};
 use hyperlight_guest_bin::guest_function::definition::GuestFunctionDefinition;
 use hyperlight_guest_bin::guest_function::register::GuestFunctionRegister;
-use hyperlight_guest_bin::host_comm::call_host_function_without_returning_result;
+use hyperlight_guest_bin::host_comm::{
+    call_host_function_with_result, call_host_function_without_returning_result, call_sandbox_raw,
+    signal_ready,
+};
 
-use crate::types::{FfiFunctionCall, FfiVec};
+use crate::types::{
+    FfiFunctionCall, FfiHostFunctionSignature, FfiHostResult, FfiParameter, FfiVec,
+};
 static mut REGISTERED_C_GUEST_FUNCTIONS: GuestFunctionRegister<CGuestFunc> =
     GuestFunctionRegister::new();
 
+/// Adds `func_def` to `REGISTERED_C_GUEST_FUNCTIONS`, unless a function with
+/// the same name is already registered.
+///
+/// C guests may register functions from constructors in several translation
+/// units, whose relative order is unspecified, so a duplicate name is
+/// rejected rather than letting whichever unit runs last win. Registered
+/// functions are looked up by name, so the order in which they were
+/// registered does not matter otherwise.
+fn register_c_function(func_def: GuestFunctionDefinition<CGuestFunc>) -> ErrorCode {
+    // Use &raw mut to get a mutable raw pointer, then dereference it
+    // this is to avoid the clippy warning "shared reference to mutable static"
+    let registered = unsafe { &mut *(&raw mut REGISTERED_C_GUEST_FUNCTIONS) };
+    if registered.contains(&func_def.function_name) {
+        return ErrorCode::DuplicateGuestFunctionName;
+    }
+    registered.register(func_def);
+    ErrorCode::NoError
+}
+
 type CGuestFunc = extern "C" fn(&FfiFunctionCall) -> Box<FfiVec>;
 
 unsafe extern "C" {
@@ -41,11 +68,23 @@ unsafe extern "C" {
 
 #[unsafe(no_mangle)]
 pub fn guest_dispatch_function(function_call: FunctionCall) -> Result<Vec<u8>> {
+    #[cfg(debug_assertions)]
+    let function_name = function_call.function_name.clone();
+    let res = dispatch_c_function(function_call);
+    // By now the result and the parameters have been freed, so anything
+    // else the guest function allocated has leaked
+    #[cfg(debug_assertions)]
+    crate::types::leak_tracking::report_leaks(&function_name);
+    res
+}
+
+fn dispatch_c_function(mut function_call: FunctionCall) -> Result<Vec<u8>> {
     // Use &raw const to get an immutable reference to the static HashMap
     // this is to avoid the clippy warning "shared reference to mutable static"
     if let Some(registered_func) =
         unsafe { (*(&raw const REGISTERED_C_GUEST_FUNCTIONS)).get(&function_call.function_name) }
     {
+        registered_func.apply_defaults(&mut function_call);
         let function_call_parameter_types: Vec<ParameterType> = function_call
             .parameters
             .iter()
@@ -79,6 +118,11 @@ pub fn guest_dispatch_function(function_call: FunctionCall) -> Result<Vec<u8>> {
     }
 }
 
+/// Register a guest function.
+///
+/// Returns `DuplicateGuestFunctionName`, without registering the function,
+/// if a function with the same name is already registered, and `NoError`
+/// otherwise.
 #[unsafe(no_mangle)]
 pub extern "C" fn hl_register_function_definition(
     function_name: *const c_char,
@@ -86,16 +130,67 @@ pub extern "C" fn hl_register_function_definition(
     param_no: usize,
     params_type: *const ParameterType,
     return_type: ReturnType,
-) {
+) -> ErrorCode {
     let func_name = unsafe { CStr::from_ptr(function_name).to_string_lossy().into_owned() };
 
     let func_params = unsafe { slice::from_raw_parts(params_type, param_no).to_vec() };
 
     let func_def = GuestFunctionDefinition::new(func_name, func_params, return_type, func_ptr);
 
-    // Use &raw mut to get a mutable raw pointer, then dereference it
+    register_c_function(func_def)
+}
+
+/// Register a guest function whose last `default_no` parameters have the
+/// default values in `defaults`, which are passed to the function when the
+/// host omits them.
+///
+/// Returns false, without registering the function, if there are more
+/// defaults than parameters, a default does not have the type of its
+/// parameter, or a function with the same name is already registered. The
+/// defaults are copied, so the caller keeps ownership of
+/// any strings or buffers they point to.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_register_function_definition_with_defaults(
+    function_name: *const c_char,
+    func_ptr: CGuestFunc,
+    param_no: usize,
+    params_type: *const ParameterType,
+    return_type: ReturnType,
+    default_no: usize,
+    defaults: *const FfiParameter,
+) -> bool {
+    let func_name = unsafe { CStr::from_ptr(function_name).to_string_lossy().into_owned() };
+
+    let func_params = unsafe { slice::from_raw_parts(params_type, param_no).to_vec() };
+
+    let defaults = if default_no == 0 {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(defaults, default_no) }
+            .iter()
+            .map(|default| unsafe { default.copy_to_parameter_value() })
+            .collect()
+    };
+
+    let Ok(func_def) = GuestFunctionDefinition::new(func_name, func_params, return_type, func_ptr)
+        .with_defaults(defaults)
+    else {
+        return false;
+    };
+
+    register_c_function(func_def) == ErrorCode::NoError
+}
+
+/// The number of guest functions registered with `hl_register_function_definition`
+/// and `hl_register_function_definition_with_defaults`.
+///
+/// A guest can compare this with the number of functions it registers to check
+/// that none were rejected.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_registration_count() -> usize {
+    // Use &raw const to get an immutable reference to the static
     // this is to avoid the clippy warning "shared reference to mutable static"
-    unsafe { (&mut *(&raw mut REGISTERED_C_GUEST_FUNCTIONS)).register(func_def) };
+    unsafe { (*(&raw const REGISTERED_C_GUEST_FUNCTIONS)).len() }
 }
 
 /// The caller is responsible for freeing the memory associated with given `FfiFunctionCall`.
@@ -110,3 +205,95 @@ pub extern "C" fn hl_call_host_function(function_call: &FfiFunctionCall) {
     let _ = call_host_function_without_returning_result(&func_name, Some(parameters), return_type)
         .expect("Failed to call host function");
 }
+
+/// Call a host function and return its result, which holds either the value
+/// returned by the host function or the error it failed with.
+///
+/// Unlike `hl_call_host_function`, a failing host function does not abort the
+/// guest. Check the result with `hl_host_result_is_err` before reading its value
+/// with the `hl_host_result_as_*` functions. The caller must release the result
+/// with `hl_free_host_result`, and is responsible for freeing the memory
+/// associated with given `FfiFunctionCall`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_call_host_function_with_result(
+    function_call: &FfiFunctionCall,
+) -> Box<FfiHostResult> {
+    let parameters = unsafe { function_call.copy_parameters() };
+    let func_name = unsafe { function_call.copy_function_name() };
+    let return_type = unsafe { function_call.copy_return_type() };
+
+    let result = call_host_function_with_result(&func_name, Some(parameters), return_type)
+        .unwrap_or_else(|e| Err(GuestError::new(e.kind, e.message)));
+    Box::new(FfiHostResult::from_host_result(result))
+}
+
+/// Free a result returned by `hl_call_host_function_with_result`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_free_host_result(result: Option<Box<FfiHostResult>>) {
+    drop(result);
+}
+
+/// Get the signature the host declared for the host function `function_name`.
+///
+/// Returns NULL if the host has no function with that name. The signature is fetched
+/// from the host on first use and cached. The caller must release the result with
+/// `hl_free_host_function_signature`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_function_signature(
+    function_name: *const c_char,
+) -> Option<Box<FfiHostFunctionSignature>> {
+    let function_name = unsafe { CStr::from_ptr(function_name).to_string_lossy() };
+    host_function_signature(&function_name)
+        .ok()
+        .map(|definition| {
+            Box::new(FfiHostFunctionSignature::from_host_function_definition(
+                definition,
+            ))
+        })
+}
+
+/// Free a signature returned by `hl_host_function_signature`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_free_host_function_signature(
+    signature: Option<Box<FfiHostFunctionSignature>>,
+) {
+    drop(signature);
+}
+
+/// Call a guest function in the sandbox registered with the host under `sandbox_name`.
+///
+/// The result is returned in the same encoding as the `hl_flatbuffer_result_from_*`
+/// functions, so a guest function can return it directly to forward the linked
+/// sandbox's result (or error) to its own caller.
+/// The caller is responsible for freeing the memory associated with given `FfiFunctionCall`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_call_sandbox(
+    sandbox_name: *const c_char,
+    function_call: &FfiFunctionCall,
+) -> Box<FfiVec> {
+    let sandbox_name = unsafe { CStr::from_ptr(sandbox_name).to_string_lossy() };
+    let call = unsafe {
+        FunctionCall::new(
+            function_call.copy_function_name(),
+            Some(function_call.copy_parameters()),
+            FunctionCallType::Guest,
+            function_call.copy_return_type(),
+        )
+    };
+
+    let result = call_sandbox_raw(&sandbox_name, &call).unwrap_or_else(|e| {
+        let fcr = FunctionCallResult::new(Err(GuestError::new(e.kind, e.message)));
+        let mut builder = FlatBufferBuilder::new();
+        fcr.encode(&mut builder).to_vec()
+    });
+
+    Box::new(unsafe { FfiVec::from_vec(result) })
+}
+
+/// Signal to the host that the guest is ready for calls, once it has finished the
+/// setup it does after it was initialised. Returns false if the host failed to
+/// record the signal.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_signal_ready() -> bool {
+    signal_ready().is_ok()
+}
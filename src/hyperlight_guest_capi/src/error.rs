@@ -15,13 +15,18 @@ limitations under the License.
 */
 
 use core::ffi::{CStr, c_char};
+use core::fmt::Write;
+use core::panic::PanicInfo;
 
 use flatbuffers::FlatBufferBuilder;
 use hyperlight_common::flatbuffer_wrappers::function_types::FunctionCallResult;
-use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
-use hyperlight_guest_bin::GUEST_HANDLE;
+use hyperlight_common::flatbuffer_wrappers::guest_error::{DomainError, ErrorCode, GuestError};
+use hyperlight_common::flatbuffer_wrappers::util::get_flatbuffer_result;
+use hyperlight_guest_bin::{GUEST_HANDLE, PanicHook, set_panic_hook};
 
 use crate::alloc::borrow::ToOwned;
+use crate::alloc::boxed::Box;
+use crate::types::FfiVec;
 
 #[unsafe(no_mangle)]
 pub extern "C" fn hl_set_error(err: ErrorCode, message: *const c_char) {
@@ -43,6 +48,41 @@ pub extern "C" fn hl_set_error(err: ErrorCode, message: *const c_char) {
     }
 }
 
+/// Returns `code` as the status of a guest function that has no other
+/// result, e.g. `return hl_return_status(-1);`. The function must be
+/// registered with `hl_ReturnType_Int`.
+///
+/// The host reads the status with `MultiUseSandbox::call_status`. Unlike an
+/// error set with `hl_set_error`, the host receives a status as a
+/// successful result, whatever its value, so the two cannot be confused.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_return_status(code: i32) -> Box<FfiVec> {
+    let vec = get_flatbuffer_result(code);
+
+    Box::new(unsafe { FfiVec::from_vec(vec) })
+}
+
+/// Returns an application-defined error from a guest function, e.g.
+/// `return hl_return_domain_error(MY_ERROR_NOT_FOUND, "no such key");`.
+/// `message` may be NULL if the error has no details.
+///
+/// The host reads the error with `MultiUseSandbox::call_or_domain_error`,
+/// which receives it as the `Err` of a successful call rather than as a
+/// failure of the call.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_return_domain_error(discriminant: u32, message: *const c_char) -> Box<FfiVec> {
+    let message = (!message.is_null()).then(|| {
+        let cstr = unsafe { CStr::from_ptr(message) };
+        cstr.to_string_lossy().into_owned()
+    });
+    let guest_error = Err(DomainError::new(discriminant, message).into());
+    let fcr = FunctionCallResult::new(guest_error);
+    let mut builder = FlatBufferBuilder::new();
+    let data = fcr.encode(&mut builder).to_vec();
+
+    Box::new(unsafe { FfiVec::from_vec(data) })
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn hl_abort_with_code(err: i32) {
     hyperlight_guest::exit::abort_with_code(&[err as u8]);
@@ -52,3 +92,68 @@ pub extern "C" fn hl_abort_with_code(err: i32) {
 pub extern "C" fn hl_abort_with_code_and_message(err: i32, message: *const c_char) {
     unsafe { hyperlight_guest::exit::abort_with_code_and_message(&[err as u8], message) };
 }
+
+/// Aborts the guest because `expr` evaluated to false at `file:line`.
+///
+/// The host receives this as a `GuestAssertionFailed` error carrying the
+/// expression and its location. C guests normally call this through the
+/// `HL_ASSERT` macro rather than directly.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_assert_failed(expr: *const c_char, file: *const c_char, line: u32) {
+    let expr = unsafe { CStr::from_ptr(expr) };
+    let file = unsafe { CStr::from_ptr(file) };
+    hyperlight_guest::exit::abort_with_assertion_failure(expr.to_bytes(), file.to_bytes(), line);
+}
+
+/// A function run when the guest panics, which receives the panic message
+/// and returns the error code to abort the guest with.
+pub type PanicHandler = extern "C" fn(message: *const c_char) -> u8;
+
+static mut PANIC_HANDLER: Option<PanicHandler> = None;
+
+/// Installs `handler` to run when the guest panics, for example in a Rust
+/// library linked into the guest, or removes it if `handler` is NULL.
+///
+/// The handler receives the panic message and returns the error code the
+/// guest aborts with, so the host receives a `GuestAborted` error with that
+/// code and the panic message, rather than one with
+/// `hl_ErrorCode_UnknownError`. The handler can also record the failure,
+/// but must not return to the code that panicked, which cannot continue.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_set_panic_handler(handler: Option<PanicHandler>) {
+    unsafe { PANIC_HANDLER = handler };
+    set_panic_hook(handler.map(|_| call_panic_handler as PanicHook));
+}
+
+/// The longest panic message passed to a panic handler, including the NUL
+/// terminator. Longer messages are truncated.
+const MAX_PANIC_MESSAGE_LEN: usize = 256;
+
+/// A panic message, formatted on the stack since the panic may have come
+/// from the allocator.
+struct PanicMessage {
+    buffer: [u8; MAX_PANIC_MESSAGE_LEN],
+    len: usize,
+}
+
+impl Write for PanicMessage {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // Keep the last byte for the NUL terminator
+        let n = s.len().min(MAX_PANIC_MESSAGE_LEN - 1 - self.len);
+        self.buffer[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+fn call_panic_handler(info: &PanicInfo) -> u8 {
+    let Some(handler) = (unsafe { PANIC_HANDLER }) else {
+        return ErrorCode::UnknownError as u8;
+    };
+    let mut message = PanicMessage {
+        buffer: [0; MAX_PANIC_MESSAGE_LEN],
+        len: 0,
+    };
+    let _ = write!(message, "{}", info.message());
+    handler(message.buffer.as_ptr() as *const c_char)
+}
@@ -20,10 +20,11 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use core::ffi::{CStr, c_char};
 
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
 use hyperlight_common::flatbuffer_wrappers::util::get_flatbuffer_result;
 use hyperlight_guest_bin::host_comm::get_host_return_value;
 
-use crate::types::FfiVec;
+use crate::types::{FfiHostResult, FfiVec};
 
 // The reason for the capitalized type in the function names below
 // is to match the names of the variants in hl_ReturnType,
@@ -95,6 +96,93 @@ pub extern "C" fn hl_flatbuffer_result_from_Bytes(data: *const u8, len: usize) -
     Box::new(unsafe { FfiVec::from_vec(vec) })
 }
 
+/// Returns the result of a function that returns `len` 32-bit floats
+/// starting at `data`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_flatbuffer_result_from_f32_array(data: *const f32, len: usize) -> Box<FfiVec> {
+    let slice = if len == 0 {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(data, len) }
+    };
+
+    let vec = get_flatbuffer_result(slice);
+
+    Box::new(unsafe { FfiVec::from_vec(vec) })
+}
+
+/// Returns the result of a function that returns `len` 64-bit floats
+/// starting at `data`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_flatbuffer_result_from_f64_array(data: *const f64, len: usize) -> Box<FfiVec> {
+    let slice = if len == 0 {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(data, len) }
+    };
+
+    let vec = get_flatbuffer_result(slice);
+
+    Box::new(unsafe { FfiVec::from_vec(vec) })
+}
+
+/// Returns the result of a function that returns `count` independent byte
+/// buffers, where buffer `i` is the `lens[i]` bytes starting at
+/// `buffers[i]`. The buffers may be empty, and are returned in order.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_flatbuffer_result_from_buffers(
+    buffers: *const *const u8,
+    lens: *const usize,
+    count: usize,
+) -> Box<FfiVec> {
+    let (buffers, lens) = if count == 0 {
+        (&[][..], &[][..])
+    } else {
+        unsafe {
+            (
+                core::slice::from_raw_parts(buffers, count),
+                core::slice::from_raw_parts(lens, count),
+            )
+        }
+    };
+    let slices: Vec<&[u8]> = buffers
+        .iter()
+        .zip(lens)
+        .map(|(&data, &len)| {
+            if len == 0 {
+                &[][..]
+            } else {
+                unsafe { core::slice::from_raw_parts(data, len) }
+            }
+        })
+        .collect();
+
+    let vec = get_flatbuffer_result(slices.as_slice());
+
+    Box::new(unsafe { FfiVec::from_vec(vec) })
+}
+
+/// Returns the result of a function that returns the status `code`
+/// together with the `len` bytes starting at `data`, which the host reads
+/// with `MultiUseSandbox::call_status_payload`. The function must be
+/// registered with `hl_ReturnType_StatusWithPayload`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_flatbuffer_result_from_status_payload(
+    code: i32,
+    data: *const u8,
+    len: usize,
+) -> Box<FfiVec> {
+    let slice = if len == 0 {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(data, len) }
+    };
+
+    let vec = get_flatbuffer_result((code, slice));
+
+    Box::new(unsafe { FfiVec::from_vec(vec) })
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn hl_flatbuffer_result_from_Bool(value: bool) -> Box<FfiVec> {
     let vec = get_flatbuffer_result(value);
@@ -156,3 +244,73 @@ pub extern "C" fn hl_get_host_return_value_as_VecBytes() -> Box<FfiVec> {
 
     Box::new(unsafe { FfiVec::from_vec(vec_value) })
 }
+
+//--- Functions for inspecting results returned by hl_call_host_function_with_result
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_is_err(result: &FfiHostResult) -> bool {
+    result.is_err()
+}
+
+/// Returns `NoError` if the host function succeeded.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_error_code(result: &FfiHostResult) -> ErrorCode {
+    result.error_code()
+}
+
+/// Returns the error message the host function failed with, or NULL if it succeeded.
+/// The message is freed along with `result`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_error(result: &FfiHostResult) -> *const c_char {
+    result.error_message()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_Int(result: &FfiHostResult) -> i32 {
+    result.value().expect("Host result is not an int")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_UInt(result: &FfiHostResult) -> u32 {
+    result.value().expect("Host result is not a uint")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_Long(result: &FfiHostResult) -> i64 {
+    result.value().expect("Host result is not a long")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_ULong(result: &FfiHostResult) -> u64 {
+    result.value().expect("Host result is not a ulong")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_Bool(result: &FfiHostResult) -> bool {
+    result.value().expect("Host result is not a bool")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_Float(result: &FfiHostResult) -> f32 {
+    result.value().expect("Host result is not a f32")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_Double(result: &FfiHostResult) -> f64 {
+    result.value().expect("Host result is not a f64")
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_String(result: &FfiHostResult) -> *const c_char {
+    let string_value: String = result.value().expect("Host result is not a string");
+
+    let c_string = CString::new(string_value).expect("Failed to create CString");
+    c_string.into_raw()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_host_result_as_VecBytes(result: &FfiHostResult) -> Box<FfiVec> {
+    let vec_value: Vec<u8> = result.value().expect("Host result is not vec bytes");
+
+    Box::new(unsafe { FfiVec::from_vec(vec_value) })
+}
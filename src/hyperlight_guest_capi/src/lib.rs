@@ -23,4 +23,6 @@ pub mod dispatch;
 pub mod error;
 pub mod flatbuffer;
 pub mod logging;
+pub mod param;
+pub mod shared_ring;
 pub mod types;
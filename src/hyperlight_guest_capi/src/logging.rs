@@ -37,3 +37,32 @@ pub extern "C" fn hl_log(
         );
     }
 }
+
+/// Logs `message` at `level` under the given log `target`.
+///
+/// The host re-emits the record through `tracing` with the target in the
+/// `guest_target` field, inside the span of the guest call that produced it.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_log_target(
+    level: log::Level,
+    target: *const c_char,
+    message: *const c_char,
+    line: i32,
+    file: *const c_char,
+) {
+    if log::log_enabled!(level) {
+        let target = unsafe { core::ffi::CStr::from_ptr(target).to_string_lossy() };
+        let message = unsafe { core::ffi::CStr::from_ptr(message).to_string_lossy() };
+        let file = unsafe { core::ffi::CStr::from_ptr(file).to_string_lossy() };
+
+        log::logger().log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(level)
+                .target(&target)
+                .line(Some(line as u32))
+                .file(Some(&file))
+                .build(),
+        );
+    }
+}
@@ -0,0 +1,126 @@
+/*
+Copyright 2025 The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use core::ffi::{CStr, c_char};
+use core::{ptr, slice};
+
+use crate::types::{FfiFunctionCall, FfiParameter};
+
+/// Returned by `hl_param_bytes_copy` when the function call has no
+/// parameter at the given index.
+pub const PARAM_ERROR_INDEX_OUT_OF_RANGE: isize = -1;
+
+/// Returned by `hl_param_bytes_copy` when the parameter is not a byte buffer.
+pub const PARAM_ERROR_NOT_BYTES: isize = -2;
+
+/// Returned by `hl_param_bytes_copy` when the destination buffer is too
+/// small to hold the parameter.
+pub const PARAM_ERROR_BUFFER_TOO_SMALL: isize = -3;
+
+/// Returned by `hl_param_map_len` when the parameter is not a map.
+pub const PARAM_ERROR_NOT_MAP: isize = -4;
+
+/// Returns whether `call` has a parameter at `index` and, if it is an
+/// optional parameter, whether its value is present.
+///
+/// A present optional value is read with the getter for its type, as if
+/// the parameter were not optional.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_is_present(call: &FfiFunctionCall, index: usize) -> bool {
+    unsafe { call.parameter(index) }.is_some_and(|parameter| parameter.is_present())
+}
+
+/// Copy the byte buffer parameter at `index` of `call` into `dst`, which
+/// must point to at least `dst_len` writable bytes.
+///
+/// Returns the number of bytes copied, or a negative `PARAM_ERROR_*` value
+/// if the parameter does not exist, is not a byte buffer, or does not fit
+/// in `dst_len` bytes. Nothing is copied on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_bytes_copy(
+    call: &FfiFunctionCall,
+    index: usize,
+    dst: *mut u8,
+    dst_len: usize,
+) -> isize {
+    let Some(parameter) = (unsafe { call.parameter(index) }) else {
+        return PARAM_ERROR_INDEX_OUT_OF_RANGE;
+    };
+    let Some(bytes) = (unsafe { parameter.as_bytes() }) else {
+        return PARAM_ERROR_NOT_BYTES;
+    };
+    if bytes.len() > dst_len {
+        return PARAM_ERROR_BUFFER_TOO_SMALL;
+    }
+    if !bytes.is_empty() {
+        unsafe { slice::from_raw_parts_mut(dst, bytes.len()) }.copy_from_slice(bytes);
+    }
+    bytes.len() as isize
+}
+
+/// Returns the number of entries in the map `parameter`, or
+/// `PARAM_ERROR_NOT_MAP` if it is not a map.
+///
+/// `parameter` is a parameter of a function call, such as
+/// `&call->parameters[0]`, or a value in a map.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_map_len(parameter: &FfiParameter) -> isize {
+    match unsafe { parameter.map_entries() } {
+        Some(entries) => entries.len() as isize,
+        None => PARAM_ERROR_NOT_MAP,
+    }
+}
+
+/// Returns the key of the entry at `index` of the map `parameter`, or
+/// NULL if it is not a map or has no such entry.
+///
+/// The key is owned by the function call and valid for as long as it is.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_map_key(parameter: &FfiParameter, index: usize) -> *const c_char {
+    unsafe { parameter.map_entries() }
+        .and_then(|entries| entries.get(index))
+        .map_or(ptr::null(), |entry| entry.key().as_ptr())
+}
+
+/// Returns the value of the entry at `index` of the map `parameter`, or
+/// NULL if it is not a map or has no such entry.
+///
+/// The value is owned by the function call and valid for as long as it is.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_map_value(
+    parameter: &FfiParameter,
+    index: usize,
+) -> Option<&FfiParameter> {
+    unsafe { parameter.map_entries() }
+        .and_then(|entries| entries.get(index))
+        .map(|entry| entry.value())
+}
+
+/// Returns the value of the first entry of the map `parameter` whose key
+/// is `key`, or NULL if it is not a map or has no such entry.
+///
+/// The value is owned by the function call and valid for as long as it is.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_param_map_get(
+    parameter: &FfiParameter,
+    key: *const c_char,
+) -> Option<&FfiParameter> {
+    let key = unsafe { CStr::from_ptr(key) };
+    unsafe { parameter.map_entries() }?
+        .iter()
+        .find(|entry| entry.key() == key)
+        .map(|entry| entry.value())
+}
@@ -0,0 +1,57 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use alloc::boxed::Box;
+use core::slice;
+
+use hyperlight_guest_bin::shared_ring::shared_ring;
+
+use crate::types::FfiVec;
+
+/// Push `len` bytes starting at `data` onto the shared ring for the host to pop.
+///
+/// Returns false if the host did not configure a shared ring, the ring is full,
+/// or the message does not fit in the ring.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_shared_ring_push(data: *const u8, len: usize) -> bool {
+    let Some(ring) = shared_ring() else {
+        return false;
+    };
+    let data = if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(data, len) }
+    };
+    ring.push(data).is_ok()
+}
+
+/// Pop the oldest message pushed by the host onto the shared ring.
+///
+/// Returns NULL if the host did not configure a shared ring or no message is
+/// available. The caller must release the result with `hl_free_shared_ring_message`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_shared_ring_pop() -> Option<Box<FfiVec>> {
+    let message = shared_ring()?.pop().ok()??;
+    Some(Box::new(unsafe { FfiVec::from_vec(message) }))
+}
+
+/// Free a message returned by `hl_shared_ring_pop`.
+#[unsafe(no_mangle)]
+pub extern "C" fn hl_free_shared_ring_message(message: Option<Box<FfiVec>>) {
+    if let Some(message) = message {
+        drop(unsafe { message.into_vec() });
+    }
+}
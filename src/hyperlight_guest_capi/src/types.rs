@@ -17,6 +17,12 @@ limitations under the License.
 mod function_call;
 pub use function_call::*;
 
+mod host_function_signature;
+pub use host_function_signature::*;
+
+mod host_result;
+pub use host_result::*;
+
 mod parameter;
 pub use parameter::*;
 
@@ -76,6 +76,17 @@ impl FfiFunctionCall {
             .collect()
     }
 
+    /// Borrows the parameter of `self` at `index`, or returns `None` if there
+    /// is no such parameter.
+    /// # Safety
+    /// `self` must be an unmodified version of what `from_function_call` returned.
+    pub unsafe fn parameter(&self, index: usize) -> Option<&FfiParameter> {
+        if index >= self.parameters_len {
+            return None;
+        }
+        Some(unsafe { &*self.parameters.add(index) })
+    }
+
     /// Copies the function name of `self into a new `String`.
     /// # Safety
     /// `self` must be an unmodified version of what `from_function_call` returned.
@@ -0,0 +1,55 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use alloc::boxed::Box;
+use alloc::slice;
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ReturnType};
+use hyperlight_common::flatbuffer_wrappers::host_function_definition::HostFunctionDefinition;
+
+/// An FFI version of the signature of a host function, as declared by the host.
+#[repr(C)]
+pub struct FfiHostFunctionSignature {
+    parameter_types: *const ParameterType,
+    parameters_len: usize,
+    return_type: ReturnType,
+}
+
+impl FfiHostFunctionSignature {
+    /// Create a new `FfiHostFunctionSignature` from a `HostFunctionDefinition`.
+    pub fn from_host_function_definition(value: HostFunctionDefinition) -> Self {
+        let boxed = value.parameter_types.unwrap_or_default().into_boxed_slice();
+        let parameters_len = boxed.len();
+        let leaked = Box::into_raw(boxed);
+
+        Self {
+            parameter_types: leaked as *const ParameterType,
+            parameters_len,
+            return_type: value.return_type,
+        }
+    }
+}
+
+impl Drop for FfiHostFunctionSignature {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(slice::from_raw_parts_mut(
+                self.parameter_types as *mut ParameterType,
+                self.parameters_len,
+            )));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use alloc::ffi::CString;
+use core::ffi::c_char;
+use core::ptr;
+
+use hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue;
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_guest_bin::host_comm::HostResult;
+
+/// An FFI handle to the result of a host function call, which is either
+/// the value returned by the host function or the error it failed with.
+/// The contents are only accessible through the `hl_host_result_*` functions.
+pub struct FfiHostResult {
+    result: HostResult,
+    /// The error message of `result` as a C string, if it is an error.
+    error_message: Option<CString>,
+}
+
+impl FfiHostResult {
+    /// Create a new `FfiHostResult` from a `HostResult`.
+    pub fn from_host_result(result: HostResult) -> Self {
+        let error_message = result.as_ref().err().map(|e| {
+            // Interior NULs would truncate the message, so drop them
+            CString::new(e.message.replace('\0', "")).unwrap_or_default()
+        });
+        Self {
+            result,
+            error_message,
+        }
+    }
+
+    /// Returns true if the host function failed.
+    pub fn is_err(&self) -> bool {
+        self.result.is_err()
+    }
+
+    /// The error code the host function failed with, or `NoError`.
+    pub fn error_code(&self) -> ErrorCode {
+        match &self.result {
+            Ok(_) => ErrorCode::NoError,
+            Err(e) => e.code,
+        }
+    }
+
+    /// The error message the host function failed with, or NULL.
+    /// The pointer is valid for as long as `self`.
+    pub fn error_message(&self) -> *const c_char {
+        self.error_message
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    }
+
+    /// The value returned by the host function converted to `T`, or `None`
+    /// if the host function failed or returned a different type.
+    pub fn value<T: TryFrom<ReturnValue>>(&self) -> Option<T> {
+        self.result.clone().ok().and_then(|v| T::try_from(v).ok())
+    }
+}
@@ -14,8 +14,11 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use alloc::boxed::Box;
 use alloc::ffi::CString;
+use alloc::vec::Vec;
 use core::ffi::{CStr, c_char};
+use core::slice;
 
 use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ParameterValue};
 use hyperlight_guest::error::Result;
@@ -38,6 +41,40 @@ pub union FfiParameterValue {
     pub Bool: bool,
     pub String: *mut c_char,
     pub VecBytes: FfiVec,
+    pub Map: FfiMap,
+}
+
+/// The entries of a map parameter, used for FFI.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct FfiMap {
+    entries: *mut FfiMapEntry,
+    len: usize,
+}
+
+/// An owned FFI version of an entry of a `ParameterValue::Map`
+#[repr(C)]
+pub struct FfiMapEntry {
+    key: *mut c_char,
+    value: FfiParameter,
+}
+
+impl FfiMapEntry {
+    /// The key of this entry.
+    pub fn key(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.key) }
+    }
+
+    /// The value of this entry.
+    pub fn value(&self) -> &FfiParameter {
+        &self.value
+    }
+}
+
+impl Drop for FfiMapEntry {
+    fn drop(&mut self) {
+        drop(unsafe { CString::from_raw(self.key) });
+    }
 }
 
 /// An owned FFI version Of `ParameterValue`
@@ -71,10 +108,88 @@ impl FfiParameter {
                     FfiParameterValue { VecBytes: leaked },
                 )
             }
+            // A present optional value is passed as the value itself, so
+            // only absent values have the `Optional` tag.
+            ParameterValue::Optional(Some(v)) => return Self::from_parameter_value(*v),
+            ParameterValue::Optional(None) => {
+                (ParameterType::Optional, FfiParameterValue { ULong: 0 })
+            }
+            // Float arrays are passed as their bytes, in the guest's byte
+            // order, so they can be read through `VecBytes`.
+            ParameterValue::VecFloat(v) => {
+                let bytes = v.iter().flat_map(|f| f.to_ne_bytes()).collect();
+                let leaked = unsafe { FfiVec::from_vec(bytes) };
+                (
+                    ParameterType::VecFloat,
+                    FfiParameterValue { VecBytes: leaked },
+                )
+            }
+            ParameterValue::VecDouble(v) => {
+                let bytes = v.iter().flat_map(|d| d.to_ne_bytes()).collect();
+                let leaked = unsafe { FfiVec::from_vec(bytes) };
+                (
+                    ParameterType::VecDouble,
+                    FfiParameterValue { VecBytes: leaked },
+                )
+            }
+            ParameterValue::Map(entries) => {
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Ok(FfiMapEntry {
+                            key: CString::new(key)
+                                .expect("Unable to make CString from String")
+                                .into_raw(),
+                            value: Self::from_parameter_value(value)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .into_boxed_slice();
+                let len = entries.len();
+                let entries = Box::into_raw(entries) as *mut FfiMapEntry;
+                (
+                    ParameterType::Map,
+                    FfiParameterValue {
+                        Map: FfiMap { entries, len },
+                    },
+                )
+            }
         };
         Ok(FfiParameter { tag, value: union })
     }
 
+    /// Returns `false` if this is an absent optional parameter.
+    pub fn is_present(&self) -> bool {
+        self.tag != ParameterType::Optional
+    }
+
+    /// Borrows the bytes of a `VecBytes`, `VecFloat` or `VecDouble`
+    /// parameter, or returns `None` for parameters of any other type.
+    /// # Safety
+    /// `self` must be an unmodified version of what `from_parameter_value` returned.
+    pub unsafe fn as_bytes(&self) -> Option<&[u8]> {
+        match self.tag {
+            ParameterType::VecBytes | ParameterType::VecFloat | ParameterType::VecDouble => {
+                Some(unsafe { self.value.VecBytes.as_slice() })
+            }
+            _ => None,
+        }
+    }
+
+    /// Borrows the entries of a `Map` parameter, or returns `None` for
+    /// parameters of any other type.
+    /// # Safety
+    /// `self` must be an unmodified version of what `from_parameter_value` returned.
+    pub unsafe fn map_entries(&self) -> Option<&[FfiMapEntry]> {
+        match self.tag {
+            ParameterType::Map => {
+                let map = unsafe { self.value.Map };
+                Some(unsafe { slice::from_raw_parts(map.entries, map.len) })
+            }
+            _ => None,
+        }
+    }
+
     /// Copies self into a new `ParameterValue`.
     /// # Safety
     /// `self` must be an unmodified version of what `from_parameter_value` returned.
@@ -95,6 +210,36 @@ impl FfiParameter {
             ParameterType::VecBytes => {
                 ParameterValue::VecBytes(unsafe { self.value.VecBytes.copy_to_vec() })
             }
+            ParameterType::Optional => ParameterValue::Optional(None),
+            ParameterType::VecFloat => {
+                let bytes = unsafe { self.value.VecBytes.as_slice() };
+                ParameterValue::VecFloat(
+                    bytes
+                        .chunks_exact(4)
+                        .map(|f| f32::from_ne_bytes(f.try_into().unwrap_or_default()))
+                        .collect::<Vec<_>>(),
+                )
+            }
+            ParameterType::VecDouble => {
+                let bytes = unsafe { self.value.VecBytes.as_slice() };
+                ParameterValue::VecDouble(
+                    bytes
+                        .chunks_exact(8)
+                        .map(|d| f64::from_ne_bytes(d.try_into().unwrap_or_default()))
+                        .collect::<Vec<_>>(),
+                )
+            }
+            ParameterType::Map => ParameterValue::Map(
+                unsafe { self.map_entries() }
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| {
+                        (entry.key().to_string_lossy().into_owned(), unsafe {
+                            entry.value.copy_to_parameter_value()
+                        })
+                    })
+                    .collect(),
+            ),
         }
     }
 }
@@ -105,9 +250,16 @@ impl Drop for FfiParameter {
             ParameterType::String => unsafe {
                 drop(CString::from_raw(self.value.String));
             },
-            ParameterType::VecBytes => unsafe {
+            ParameterType::VecBytes | ParameterType::VecFloat | ParameterType::VecDouble => unsafe {
                 drop(self.value.VecBytes.into_vec());
             },
+            ParameterType::Map => unsafe {
+                let map = self.value.Map;
+                drop(Box::from_raw(slice::from_raw_parts_mut(
+                    map.entries,
+                    map.len,
+                )));
+            },
             _ => {}
         }
     }
@@ -33,19 +33,25 @@ impl FfiVec {
     /// # Safety
     /// The caller must later reclaim memory by calling `into_vec`, otherwise memory will be leaked.
     /// The caller must not modify the returned `FfiVec`.
+    #[cfg_attr(debug_assertions, track_caller)]
     pub unsafe fn from_vec(v: Vec<u8>) -> Self {
         let boxed = v.into_boxed_slice();
         let leaked = Box::into_raw(boxed);
-        FfiVec {
+        let res = FfiVec {
             data: leaked as *mut u8,
             len: leaked.len(),
-        }
+        };
+        #[cfg(debug_assertions)]
+        leak_tracking::track(&res, core::panic::Location::caller());
+        res
     }
 
     /// Consumes `self` and returns the original Vec<u8> without copying memory.
     /// # Safety
     /// Self must have been obtained using `from_vec`, and must be in its original state (i.e. not modified).
     pub unsafe fn into_vec(mut self) -> Vec<u8> {
+        #[cfg(debug_assertions)]
+        leak_tracking::untrack(&self);
         let slice = unsafe { slice::from_raw_parts_mut(self.data, self.len) };
         let boxed: Box<[u8]> = unsafe { Box::from_raw(slice) };
 
@@ -55,6 +61,16 @@ impl FfiVec {
         res
     }
 
+    /// Borrows the contents of `self` without copying them.
+    /// # Safety
+    /// Self must have been obtained using `from_vec`, and must be in its original state (i.e. not modified).
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+
     /// Copies the contents of `self` to a new independent Vec<u8>.
     /// # Safety
     /// Self must have been obtained using `from_vec`, and must be in its original state (i.e. not modified).
@@ -73,3 +89,57 @@ impl FfiVec {
         clone
     }
 }
+
+/// Tracking of the live `FfiVec`s in debug builds, so that those a guest
+/// function leaks, typically by building a result it does not return, can
+/// be reported once it has returned. Release builds do not track them.
+#[cfg(debug_assertions)]
+pub(crate) mod leak_tracking {
+    use alloc::collections::BTreeMap;
+    use core::panic::Location;
+
+    use super::FfiVec;
+
+    struct Allocation {
+        len: usize,
+        /// The function that created the `FfiVec`, such as the
+        /// `hl_flatbuffer_result_from_*` function a guest called
+        site: &'static Location<'static>,
+    }
+
+    /// The live `FfiVec`s, keyed by the address of their contents. Empty
+    /// ones do not allocate, so they are not tracked.
+    static mut LIVE: BTreeMap<usize, Allocation> = BTreeMap::new();
+
+    pub(super) fn track(vec: &FfiVec, site: &'static Location<'static>) {
+        if vec.len == 0 {
+            return;
+        }
+        // This is currently safe, because we are single threaded
+        let live = unsafe { &mut *(&raw mut LIVE) };
+        live.insert(vec.data as usize, Allocation { len: vec.len, site });
+    }
+
+    pub(super) fn untrack(vec: &FfiVec) {
+        // This is currently safe, because we are single threaded
+        let live = unsafe { &mut *(&raw mut LIVE) };
+        live.remove(&(vec.data as usize));
+    }
+
+    /// Log a warning for every `FfiVec` that is still live after the guest
+    /// function `function_name` returned, and stop tracking them so that
+    /// each leak is reported once.
+    pub(crate) fn report_leaks(function_name: &str) {
+        // This is currently safe, because we are single threaded
+        let live = core::mem::take(unsafe { &mut *(&raw mut LIVE) });
+        for (address, allocation) in live {
+            log::warn!(
+                "Guest function {} leaked an FfiVec of {} bytes at {:#x}, created by {}",
+                function_name,
+                allocation.len,
+                address,
+                allocation.site
+            );
+        }
+    }
+}
@@ -165,6 +165,43 @@ fn bench_guest_call(b: &mut criterion::Bencher, size: SandboxSize) {
     b.iter(|| sbox.call::<String>("Echo", "hello\n".to_string()).unwrap());
 }
 
+fn bench_guest_call_vec_result(b: &mut criterion::Bencher, reuse_buffer: bool) {
+    let mut sbox = create_multiuse_sandbox_with_size(SandboxSize::Default);
+    let input = vec![0xAB; 4096];
+    let mut buf = Vec::new();
+
+    if reuse_buffer {
+        b.iter(|| {
+            sbox.call_into::<Vec<u8>>("GetSizePrefixedBuffer", input.clone(), &mut buf)
+                .unwrap()
+        });
+    } else {
+        b.iter(|| {
+            sbox.call::<Vec<u8>>("GetSizePrefixedBuffer", input.clone())
+                .unwrap()
+        });
+    }
+}
+
+fn bench_guest_call_loop(b: &mut criterion::Bencher, use_session: bool) {
+    let mut sbox = create_multiuse_sandbox_with_size(SandboxSize::Default);
+
+    if use_session {
+        let mut session = sbox.call_session();
+        b.iter(|| {
+            for _ in 0..100 {
+                session.call::<i32>("AddToStatic", 1i32).unwrap();
+            }
+        });
+    } else {
+        b.iter(|| {
+            for _ in 0..100 {
+                sbox.call::<i32>("AddToStatic", 1i32).unwrap();
+            }
+        });
+    }
+}
+
 fn bench_guest_call_with_restore(b: &mut criterion::Bencher, size: SandboxSize) {
     let mut sbox = create_multiuse_sandbox_with_size(size);
     let snapshot = sbox.snapshot().unwrap();
@@ -290,6 +327,18 @@ fn guest_calls_benchmark(c: &mut Criterion) {
         });
     }
 
+    // Allocating a `Vec` for each result vs decoding into a reused buffer
+    group.bench_function("call_vec_result/allocate", |b| {
+        bench_guest_call_vec_result(b, false)
+    });
+    group.bench_function("call_vec_result/reuse_buffer", |b| {
+        bench_guest_call_vec_result(b, true)
+    });
+
+    // 100 small calls, each encoded into a fresh buffer vs a `CallSession`'s
+    group.bench_function("call_loop/call", |b| bench_guest_call_loop(b, false));
+    group.bench_function("call_loop/call_session", |b| bench_guest_call_loop(b, true));
+
     group.bench_function("different_thread".to_string(), |b| {
         bench_guest_call_different_thread(b, SandboxSize::Default)
     });
@@ -347,6 +396,31 @@ fn bench_snapshot_restore(b: &mut criterion::Bencher, size: SandboxSize) {
     });
 }
 
+fn bench_reset_to_baseline(b: &mut criterion::Bencher, pages: u64) {
+    b.iter_custom(|iters| {
+        let mut cfg = SandboxConfiguration::default();
+        cfg.set_capture_baseline(true);
+        let path = simple_guest_as_string().unwrap();
+        let mut sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), Some(cfg))
+            .unwrap()
+            .evolve()
+            .unwrap();
+        let mut total_duration = Duration::ZERO;
+
+        for _ in 0..iters {
+            // Touch `pages` pages of guest memory
+            sbox.call::<u64>("TouchPages", pages).unwrap();
+
+            // Measure only the reset time
+            let start = Instant::now();
+            sbox.reset_to_baseline().unwrap();
+            total_duration += start.elapsed();
+        }
+
+        total_duration
+    });
+}
+
 fn snapshots_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("snapshots");
 
@@ -362,6 +436,15 @@ fn snapshots_benchmark(c: &mut Criterion) {
         });
     }
 
+    // The reset cost depends on how much of its memory the guest wrote to
+    for pages in [1, 16, 256] {
+        group.bench_with_input(
+            BenchmarkId::new("reset_to_baseline_after_touching_pages", pages),
+            &pages,
+            |b, &pages| bench_reset_to_baseline(b, pages),
+        );
+    }
+
     group.finish();
 }
 
@@ -0,0 +1,195 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::time::{Duration, Instant};
+
+use tracing::{Span, instrument};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// The most calls made to warm up before measuring, see [`benchmark_call`].
+const MAX_WARMUP_ITERATIONS: u32 = 100;
+
+/// The results of [`benchmark_call`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchStats {
+    /// The number of calls measured
+    pub iterations: u32,
+    /// The number of calls made to warm up, which are not measured
+    pub warmup_iterations: u32,
+    /// The total duration of the measured calls, not counting the resets
+    /// between them
+    pub total: Duration,
+    /// The fastest call
+    pub min: Duration,
+    /// The slowest call
+    pub max: Duration,
+    /// The mean duration of a call
+    pub mean: Duration,
+    /// The median duration of a call
+    pub p50: Duration,
+    /// The duration that 90% of the calls took at most
+    pub p90: Duration,
+    /// The duration that 99% of the calls took at most
+    pub p99: Duration,
+    /// The number of calls per second, based on [`total`](Self::total)
+    pub throughput: f64,
+}
+
+/// Call the guest function `func_name` with `args` `iterations` times on
+/// `sandbox`, and report how long the calls took.
+///
+/// The measured calls are preceded by a tenth as many warmup calls, but at
+/// least 1 and at most 100, so that caches and lazily initialised guest
+/// state do not skew the first measurements. Every call, warmup included,
+/// starts from the sandbox's baseline: the sandbox is reset with
+/// [`MultiUseSandbox::reset_to_baseline`] before the first call and after
+/// each one, so each call measures the same work. The resets are not
+/// counted in the durations. A sandbox that has no baseline uses the state
+/// it is in when the benchmark starts.
+///
+/// Percentiles are taken from the duration of every measured call, so they
+/// are exact. The benchmark stops at the first call that fails, and returns
+/// its error.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+/// # use hyperlight_host::bench::benchmark_call;
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+///     GuestBinary::FilePath("guest.bin".into()),
+///     None
+/// )?.evolve()?;
+///
+/// let stats = benchmark_call::<String>(&mut sandbox, "Echo", "hello".to_string(), 1000)?;
+/// println!("p50: {:?}, p99: {:?}", stats.p50, stats.p99);
+/// println!("{:.0} calls/s", stats.throughput);
+/// # Ok(())
+/// # }
+/// ```
+#[instrument(err(Debug), skip(sandbox, args), parent = Span::current())]
+pub fn benchmark_call<Output: SupportedReturnType>(
+    sandbox: &mut MultiUseSandbox,
+    func_name: &str,
+    args: impl ParameterTuple,
+    iterations: u32,
+) -> Result<BenchStats> {
+    if iterations == 0 {
+        return Err(new_error!("A benchmark needs at least 1 iteration"));
+    }
+    let warmup_iterations = (iterations / 10).clamp(1, MAX_WARMUP_ITERATIONS);
+
+    sandbox.ensure_baseline()?;
+    sandbox.reset_to_baseline()?;
+    for _ in 0..warmup_iterations {
+        let res = sandbox.call::<Output>(func_name, args.clone());
+        sandbox.reset_to_baseline()?;
+        res?;
+    }
+
+    let mut durations = Vec::with_capacity(iterations as usize);
+    for _ in 0..iterations {
+        let args = args.clone();
+        let start = Instant::now();
+        let res = sandbox.call::<Output>(func_name, args);
+        durations.push(start.elapsed());
+        sandbox.reset_to_baseline()?;
+        res?;
+    }
+
+    Ok(BenchStats::from_durations(durations, warmup_iterations))
+}
+
+impl BenchStats {
+    fn from_durations(mut durations: Vec<Duration>, warmup_iterations: u32) -> Self {
+        durations.sort_unstable();
+        let iterations = durations.len() as u32;
+        let total: Duration = durations.iter().sum();
+        // The smallest duration that at least `percentile`% of the calls
+        // took at most
+        let percentile = |percentile: usize| {
+            let rank = (durations.len() * percentile).div_ceil(100).max(1);
+            durations[rank - 1]
+        };
+        Self {
+            iterations,
+            warmup_iterations,
+            total,
+            min: durations[0],
+            max: durations[durations.len() - 1],
+            mean: total / iterations,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            throughput: iterations as f64 / total.as_secs_f64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hyperlight_testing::simple_guest_as_string;
+
+    use super::{BenchStats, benchmark_call};
+    use crate::{GuestBinary, UninitializedSandbox};
+
+    #[test]
+    fn stats_from_durations() {
+        let durations = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = BenchStats::from_durations(durations, 10);
+        assert_eq!(stats.iterations, 100);
+        assert_eq!(stats.warmup_iterations, 10);
+        assert_eq!(stats.total, Duration::from_millis(5050));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50500));
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert!((stats.throughput - 100.0 / 5.05).abs() < 1e-9);
+
+        let stats = BenchStats::from_durations(vec![Duration::from_millis(7)], 1);
+        assert_eq!(stats.p50, Duration::from_millis(7));
+        assert_eq!(stats.p99, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn benchmark() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None)
+            .unwrap()
+            .evolve()
+            .unwrap();
+
+        let stats = benchmark_call::<i32>(&mut sandbox, "AddToStatic", 5i32, 50).unwrap();
+        assert_eq!(stats.iterations, 50);
+        assert_eq!(stats.warmup_iterations, 5);
+        assert!(stats.min <= stats.p50 && stats.p50 <= stats.p99 && stats.p99 <= stats.max);
+        assert!(stats.throughput > 0.0);
+
+        // Each call started from the baseline, and so does the next one
+        let count: i32 = sandbox.call("AddToStatic", 5i32).unwrap();
+        assert_eq!(count, 5);
+
+        assert!(benchmark_call::<i32>(&mut sandbox, "AddToStatic", 5i32, 0).is_err());
+        assert!(benchmark_call::<i32>(&mut sandbox, "NoSuchFunction", (), 10).is_err());
+    }
+}
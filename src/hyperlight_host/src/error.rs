@@ -21,16 +21,18 @@ use std::error::Error;
 use std::num::TryFromIntError;
 use std::string::FromUtf8Error;
 use std::sync::{MutexGuard, PoisonError};
-use std::time::SystemTimeError;
+use std::time::{Duration, SystemTimeError};
 
 #[cfg(target_os = "windows")]
 use crossbeam_channel::{RecvError, SendError};
 use flatbuffers::InvalidFlatbuffer;
 use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnValue};
 use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::fp_exceptions::FpExceptions;
 use thiserror::Error;
 
-use crate::hypervisor::hyperlight_vm::HyperlightVmError;
+use crate::hypervisor::HypervisorBackend;
+use crate::hypervisor::hyperlight_vm::{HyperlightVmError, ProtectRegionError};
 #[cfg(target_os = "windows")]
 use crate::hypervisor::wrappers::HandleWrapper;
 use crate::mem::memory_region::MemoryRegionFlags;
@@ -39,6 +41,25 @@ use crate::mem::ptr::RawPtr;
 /// The error type for Hyperlight operations
 #[derive(Error, Debug)]
 pub enum HyperlightError {
+    /// The guest was built against a different version of the host-guest
+    /// ABI than the host, see [`hyperlight_common::outb::ABI_VERSION`].
+    #[error("Guest was built for ABI version {guest}, but the host uses version {host}")]
+    AbiMismatch {
+        /// The ABI version of the host
+        host: u32,
+        /// The ABI version the guest reported
+        guest: u32,
+    },
+
+    /// The guest finished initialising without reporting the version of
+    /// the host-guest ABI it was built against, so it is either not a
+    /// Hyperlight guest or predates versioning.
+    #[error("Guest did not report its ABI version, the host uses version {host}")]
+    AbiVersionMissing {
+        /// The ABI version of the host
+        host: u32,
+    },
+
     /// Anyhow error
     #[error("Anyhow Error was returned: {0}")]
     AnyhowError(#[from] anyhow::Error),
@@ -88,6 +109,39 @@ pub enum HyperlightError {
     #[error("Guest aborted: {0} {1}")]
     GuestAborted(u8, String),
 
+    /// A guest function call allocated more than the limit set with
+    /// [`SandboxConfiguration::set_max_alloc_per_call`](crate::sandbox::SandboxConfiguration::set_max_alloc_per_call)
+    #[error("Guest allocation of {requested} bytes exceeded the per-call limit of {limit} bytes")]
+    GuestAllocationLimitExceeded {
+        /// The size of the allocation that exceeded the limit
+        requested: u64,
+        /// The configured per-call allocation limit
+        limit: u64,
+    },
+
+    /// A guest assertion failed
+    #[error("Guest assertion failed: `{expression}` at {file}:{line}")]
+    GuestAssertionFailed {
+        /// The source text of the expression that evaluated to false
+        expression: String,
+        /// The guest source file containing the assertion
+        file: String,
+        /// The line number of the assertion within `file`
+        line: u32,
+    },
+
+    /// A guest call made with a timeout did not complete before the timeout expired
+    #[error("Guest call timed out after {timeout:?}")]
+    GuestCallTimedOut {
+        /// The timeout that expired
+        timeout: Duration,
+        /// The last partial result published by the guest before it was
+        /// cancelled. This is only collected by
+        /// [`crate::MultiUseSandbox::call_with_timeout_partial`], and is
+        /// incomplete by definition.
+        partial_result: Option<ReturnValue>,
+    },
+
     /// Guest call resulted in error in guest
     #[error("Guest error occurred {0:?}: {1}")]
     GuestError(ErrorCode, String),
@@ -96,6 +150,21 @@ pub enum HyperlightError {
     #[error("Guest execution hung on the execution of a host function call")]
     GuestExecutionHungOnHostFunctionCall(),
 
+    /// A floating-point exception that was not masked with
+    /// [`SandboxConfiguration::set_fp_exception_mask`](crate::sandbox::SandboxConfiguration::set_fp_exception_mask)
+    /// trapped in the guest
+    #[error("Guest floating-point exception {exceptions:?} at {rip:#x} (MXCSR {mxcsr:#x})")]
+    GuestFpException {
+        /// The exceptions that trapped: those that occurred and were not
+        /// masked
+        exceptions: FpExceptions,
+        /// The guest's floating-point status and control register when the
+        /// exception trapped
+        mxcsr: u32,
+        /// The guest address of the instruction that raised the exception
+        rip: u64,
+    },
+
     /// Guest call already in progress
     #[error("Guest call is already in progress")]
     GuestFunctionCallAlreadyInProgress(),
@@ -104,6 +173,11 @@ pub enum HyperlightError {
     #[error("Unsupported type: {0}")]
     GuestInterfaceUnsupportedType(String),
 
+    /// The guest binary loaded into an uninitialized sandbox failed
+    /// [`crate::UninitializedSandbox::validate`]
+    #[error("Guest binary failed validation: {0}")]
+    GuestBinaryValidationFailed(String),
+
     /// The guest binary was built with a different hyperlight-guest-bin version than the host expects.
     /// Hyperlight currently provides no backwards compatibility guarantees for guest binaries,
     /// so the guest and host versions must match exactly. This might change in the future.
@@ -118,10 +192,34 @@ pub enum HyperlightError {
         host_version: String,
     },
 
+    /// A guest function call made more host function calls than the limit set with
+    /// [`SandboxConfiguration::set_max_host_calls_per_call`](crate::sandbox::SandboxConfiguration::set_max_host_calls_per_call)
+    #[error("Guest function call exceeded the limit of {limit} host function calls")]
+    HostCallLimitExceeded {
+        /// The configured per-call host call limit
+        limit: u64,
+    },
+
+    /// The guest invoked a host callback handle that does not belong to a
+    /// call in progress, or whose callback is already running.
+    #[error("Host callback {0} is not available")]
+    HostCallbackUnavailable(u64),
+
+    /// A host function failed, with the context it added to the error as
+    /// it was propagated, see [`crate::func::Context`].
+    #[error("{0}")]
+    HostFunctionFailed(crate::func::HostFunctionError),
+
     /// A Host function was called by the guest but it was not registered.
     #[error("HostFunction {0} was not found")]
     HostFunctionNotFound(String),
 
+    /// The contents of a region mapped read-only with
+    /// [`MultiUseSandbox::map_host_region`](crate::MultiUseSandbox::map_host_region)
+    /// at the given guest address changed while it was mapped.
+    #[error("Read-only host region mapped at {0:#x} was modified")]
+    HostRegionModified(u64),
+
     /// Hyperlight VM error.
     ///
     /// **Note:** This error variant is considered internal and its structure is not stable.
@@ -142,6 +240,11 @@ pub enum HyperlightError {
     #[error("The flatbuffer is invalid")]
     InvalidFlatBuffer(#[from] InvalidFlatbuffer),
 
+    /// The interrupt vector cannot be raised in the guest, see
+    /// [`MultiUseSandbox::raise_guest_interrupt`](crate::MultiUseSandbox::raise_guest_interrupt).
+    #[error("Interrupt vector {0:#x} cannot be raised in the guest")]
+    InvalidInterruptVector(u8),
+
     /// Conversion of str to Json failed
     #[error("Conversion of str data to json failed")]
     JsonConversionFailure(#[from] serde_json::Error),
@@ -224,10 +327,29 @@ pub enum HyperlightError {
     #[error("The sandbox was poisoned")]
     PoisonedSandbox,
 
+    /// A guest call exceeded the rate limit set with
+    /// [`crate::MultiUseSandbox::with_call_rate_limit`], and was rejected
+    /// without running the guest
+    #[error("Guest call exceeds the sandbox's call rate limit, retry after {retry_after:?}")]
+    RateLimited {
+        /// How long until the sandbox allows another call
+        retry_after: Duration,
+    },
+
     /// Raw pointer is less than base address
     #[error("Raw pointer ({0:?}) was less than the base address ({1})")]
     RawPointerLessThanBaseAddress(RawPtr, u64),
 
+    /// A reentrant guest call was made while the maximum number of nested
+    /// calls were already in progress, see [`crate::sandbox::reentrant`].
+    #[error("Reentrant guest call exceeds the maximum nesting depth of {0}")]
+    ReentrantCallDepthExceeded(usize),
+
+    /// A reentrant guest call was made outside of a host function called by
+    /// a guest, see [`crate::sandbox::reentrant`].
+    #[error("Reentrant guest calls can only be made from a host function called by a guest")]
+    ReentrantCallUnavailable,
+
     /// RefCell borrow failed
     #[error("RefCell borrow failed")]
     RefCellBorrowFailed(#[from] BorrowError),
@@ -236,10 +358,41 @@ pub enum HyperlightError {
     #[error("RefCell mut borrow failed")]
     RefCellMutBorrowFailed(#[from] BorrowMutError),
 
+    /// The result of a guest function call was larger than the limit set
+    /// with
+    /// [`SandboxConfiguration::set_max_result_bytes`](crate::sandbox::SandboxConfiguration::set_max_result_bytes)
+    #[error("Guest function result of {size} bytes exceeds the limit of {limit} bytes")]
+    ResultTooLarge {
+        /// The size of the encoded result
+        size: usize,
+        /// The configured result size limit
+        limit: usize,
+    },
+
     /// Failed to get value from return value
     #[error("Failed To Convert Return Value {0:?} to {1:?}")]
     ReturnValueConversionFailure(ReturnValue, &'static str),
 
+    /// A sandbox being created in the background was not ready before the
+    /// timeout passed to [`crate::sandbox::SandboxHandle::ready`] expired,
+    /// or a guest did not signal that it was ready before the timeout
+    /// passed to [`crate::MultiUseSandbox::wait_until_ready`] expired
+    #[error("Sandbox was not ready after {0:?}")]
+    SandboxNotReady(Duration),
+
+    /// The sandbox is in a [`crate::sandbox::SandboxRegistry`] that has
+    /// been shut down
+    #[error("The sandbox's registry has been shut down")]
+    SandboxRegistryShutDown,
+
+    /// The guest executed a page it had written to, with detection enabled
+    /// by [`SandboxConfiguration::set_self_modifying_code_detection`](crate::sandbox::SandboxConfiguration::set_self_modifying_code_detection)
+    #[error("Guest executed the page at {address:#x} after writing to it")]
+    SelfModifyingCode {
+        /// The guest address that was executed
+        address: u64,
+    },
+
     /// Tried to restore a snapshot into a sandbox whose memory
     /// layout is not compatible with the snapshot's.
     #[error("Snapshot memory layout is not compatible with this sandbox")]
@@ -280,6 +433,18 @@ pub enum HyperlightError {
     #[error("The return value type is unexpected got {0:?} expected {1:?}")]
     UnexpectedReturnValueType(ReturnValue, String),
 
+    /// A feature was requested that the hypervisor backend does not
+    /// support, see [`HypervisorBackend::capabilities`], or that no
+    /// backend supports yet, such as reprotecting the sandbox's own
+    /// memory.
+    #[error("{feature} is not supported on the {backend} hypervisor backend")]
+    Unsupported {
+        /// The feature that was requested.
+        feature: String,
+        /// The backend the sandbox runs on.
+        backend: HypervisorBackend,
+    },
+
     /// Slice conversion to UTF8 failed
     #[error("String Conversion of UTF8 data to str failed")]
     UTF8StringConversionFailure(#[from] FromUtf8Error),
@@ -343,6 +508,12 @@ impl HyperlightError {
             // These errors poison the sandbox because they can leave it in an inconsistent state due
             // to the guest not running to completion.
             HyperlightError::GuestAborted(_, _)
+            | HyperlightError::GuestAllocationLimitExceeded { .. }
+            | HyperlightError::GuestAssertionFailed { .. }
+            | HyperlightError::GuestCallTimedOut { .. }
+            | HyperlightError::GuestFpException { .. }
+            | HyperlightError::HostCallLimitExceeded { .. }
+            | HyperlightError::SelfModifyingCode { .. }
             | HyperlightError::ExecutionCanceledByHost()
             | HyperlightError::PoisonedSandbox
             | HyperlightError::ExecutionAccessViolation(_)
@@ -358,32 +529,55 @@ impl HyperlightError {
             HyperlightError::HyperlightVmError(HyperlightVmError::UpdateRegion(_))
             | HyperlightError::HyperlightVmError(HyperlightVmError::AccessPageTable(_)) => true,
 
+            // Failing to remap a region whose protection was being changed
+            // leaves part of it unmapped
+            HyperlightError::HyperlightVmError(HyperlightVmError::ProtectRegion(
+                ProtectRegionError::UpdateRegion(_),
+            )) => true,
+
+            // The interrupt is either requested or not, so the VM state
+            // is unchanged on failure
+            #[cfg(feature = "hw-interrupts")]
+            HyperlightError::HyperlightVmError(HyperlightVmError::RaiseInterrupt(_)) => false,
+
             // HyperlightVmError::DispatchGuestCall may poison the sandbox
             HyperlightError::HyperlightVmError(HyperlightVmError::DispatchGuestCall(e)) => {
                 e.is_poison_error()
             }
 
             // All other errors do not poison the sandbox.
-            HyperlightError::AnyhowError(_)
+            HyperlightError::AbiMismatch { .. }
+            | HyperlightError::AbiVersionMissing { .. }
+            | HyperlightError::AnyhowError(_)
             | HyperlightError::BoundsCheckFailed(_, _)
             | HyperlightError::CheckedAddOverflow(_, _)
             | HyperlightError::CStringConversionError(_)
             | HyperlightError::Error(_)
             | HyperlightError::FailedToGetValueFromParameter()
             | HyperlightError::FieldIsMissingInGuestLogData(_)
+            | HyperlightError::GuestBinaryValidationFailed(_)
             | HyperlightError::GuestBinVersionMismatch { .. }
             | HyperlightError::GuestError(_, _)
             | HyperlightError::GuestExecutionHungOnHostFunctionCall()
             | HyperlightError::GuestFunctionCallAlreadyInProgress()
             | HyperlightError::GuestInterfaceUnsupportedType(_)
+            | HyperlightError::HostCallbackUnavailable(_)
+            | HyperlightError::HostFunctionFailed(_)
             | HyperlightError::HostFunctionNotFound(_)
+            | HyperlightError::HostRegionModified(_)
             | HyperlightError::HyperlightVmError(HyperlightVmError::Create(_))
             | HyperlightError::HyperlightVmError(HyperlightVmError::Initialize(_))
             | HyperlightError::HyperlightVmError(HyperlightVmError::MapRegion(_))
+            | HyperlightError::HyperlightVmError(HyperlightVmError::ProtectRegion(
+                ProtectRegionError::NotPageAligned { .. }
+                | ProtectRegionError::NotMapped { .. }
+                | ProtectRegionError::NotWritable { .. },
+            ))
             | HyperlightError::HyperlightVmError(HyperlightVmError::UnmapRegion(_))
             | HyperlightError::IOError(_)
             | HyperlightError::IntConversionFailure(_)
             | HyperlightError::InvalidFlatBuffer(_)
+            | HyperlightError::InvalidInterruptVector(_)
             | HyperlightError::JsonConversionFailure(_)
             | HyperlightError::LockAttemptFailed(_)
             | HyperlightError::MemoryAllocationFailed(_)
@@ -397,10 +591,16 @@ impl HyperlightError {
             | HyperlightError::NoMemorySnapshot
             | HyperlightError::ParameterValueConversionFailure(_, _)
             | HyperlightError::PEFileProcessingFailure(_)
+            | HyperlightError::RateLimited { .. }
             | HyperlightError::RawPointerLessThanBaseAddress(_, _)
+            | HyperlightError::ReentrantCallDepthExceeded(_)
+            | HyperlightError::ReentrantCallUnavailable
             | HyperlightError::RefCellBorrowFailed(_)
             | HyperlightError::RefCellMutBorrowFailed(_)
+            | HyperlightError::ResultTooLarge { .. }
             | HyperlightError::ReturnValueConversionFailure(_, _)
+            | HyperlightError::SandboxNotReady(_)
+            | HyperlightError::SandboxRegistryShutDown
             | HyperlightError::SnapshotLayoutMismatch
             | HyperlightError::SnapshotHostFunctionMismatch { .. }
             | HyperlightError::SystemTimeError(_)
@@ -408,6 +608,7 @@ impl HyperlightError {
             | HyperlightError::UnexpectedNoOfArguments(_, _)
             | HyperlightError::UnexpectedParameterValueType(_, _)
             | HyperlightError::UnexpectedReturnValueType(_, _)
+            | HyperlightError::Unsupported { .. }
             | HyperlightError::UTF8StringConversionFailure(_)
             | HyperlightError::VectorCapacityIncorrect(_, _, _) => false,
 
@@ -421,6 +622,76 @@ impl HyperlightError {
             HyperlightError::VmmSysError(_) => false,
         }
     }
+
+    /// Wraps `error` with a description of the operation that failed, keeping
+    /// its [`ErrorKind`](std::io::ErrorKind) so that converting the result back
+    /// to an [`std::io::Error`] reports the same kind.
+    ///
+    /// ```
+    /// # use hyperlight_host::HyperlightError;
+    /// let e = std::fs::read("/does/not/exist")
+    ///     .map_err(|e| HyperlightError::from_io("reading guest binary", e))
+    ///     .unwrap_err();
+    /// assert_eq!(std::io::Error::from(e).kind(), std::io::ErrorKind::NotFound);
+    /// ```
+    pub fn from_io(context: impl std::fmt::Display, error: std::io::Error) -> Self {
+        HyperlightError::IOError(std::io::Error::new(
+            error.kind(),
+            format!("{context}: {error}"),
+        ))
+    }
+
+    /// The [`ErrorKind`](std::io::ErrorKind) this error is reported as when it
+    /// is converted to an [`std::io::Error`].
+    pub fn io_error_kind(&self) -> std::io::ErrorKind {
+        use std::io::ErrorKind;
+
+        match self {
+            HyperlightError::IOError(e) => e.kind(),
+            HyperlightError::HostFunctionFailed(e) => e.root_cause().io_error_kind(),
+            HyperlightError::ExecutionCanceledByHost() => ErrorKind::Interrupted,
+            HyperlightError::GuestCallTimedOut { .. } | HyperlightError::SandboxNotReady(_) => {
+                ErrorKind::TimedOut
+            }
+            HyperlightError::GuestFunctionCallAlreadyInProgress() => ErrorKind::ResourceBusy,
+            HyperlightError::RateLimited { .. } => ErrorKind::WouldBlock,
+            HyperlightError::HostFunctionNotFound(_)
+            | HyperlightError::MetricNotFound(_)
+            | HyperlightError::NoHypervisorFound() => ErrorKind::NotFound,
+            HyperlightError::GuestInterfaceUnsupportedType(_)
+            | HyperlightError::Unsupported { .. } => ErrorKind::Unsupported,
+            HyperlightError::MemoryAllocationFailed(_)
+            | HyperlightError::MemoryRequestTooBig(_, _) => ErrorKind::OutOfMemory,
+            HyperlightError::ParameterValueConversionFailure(_, _)
+            | HyperlightError::UnexpectedNoOfArguments(_, _)
+            | HyperlightError::UnexpectedParameterValueType(_, _)
+            | HyperlightError::MemoryRequestTooSmall(_, _)
+            | HyperlightError::InvalidInterruptVector(_)
+            | HyperlightError::CStringConversionError(_) => ErrorKind::InvalidInput,
+            HyperlightError::AbiMismatch { .. }
+            | HyperlightError::AbiVersionMissing { .. }
+            | HyperlightError::InvalidFlatBuffer(_)
+            | HyperlightError::JsonConversionFailure(_)
+            | HyperlightError::ReturnValueConversionFailure(_, _)
+            | HyperlightError::UnexpectedReturnValueType(_, _)
+            | HyperlightError::UTF8StringConversionFailure(_)
+            | HyperlightError::PEFileProcessingFailure(_)
+            | HyperlightError::GuestBinaryValidationFailed(_) => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Converts a [`HyperlightError`] to an [`std::io::Error`] whose kind is
+/// [`HyperlightError::io_error_kind`] and whose message is the error's
+/// message. An [`HyperlightError::IOError`] is unwrapped as is.
+impl From<HyperlightError> for std::io::Error {
+    fn from(e: HyperlightError) -> Self {
+        match e {
+            HyperlightError::IOError(e) => e,
+            e => std::io::Error::new(e.io_error_kind(), e.to_string()),
+        }
+    }
 }
 
 /// Creates a `HyperlightError::Error` from a string literal or format string
@@ -443,11 +714,31 @@ macro_rules! new_error {
 #[cfg(test)]
 mod tests {
     use super::*;
+
     use crate::hypervisor::hyperlight_vm::{
-        DispatchGuestCallError, HandleIoError, HyperlightVmError, RunVmError,
+        DispatchGuestCallError, HandleIoError, HyperlightVmError, InitializeError, RunVmError,
     };
     use crate::sandbox::outb::HandleOutbError;
 
+    /// Test that HyperlightErrors convert to io::Errors of the matching kind
+    #[test]
+    fn converts_to_io_error() {
+        let e = std::io::Error::from(HyperlightError::HostFunctionNotFound("Add".to_string()));
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(e.to_string(), "HostFunction Add was not found");
+
+        let e = std::io::Error::from(HyperlightError::ExecutionCanceledByHost());
+        assert_eq!(e.kind(), std::io::ErrorKind::Interrupted);
+
+        // A wrapped io::Error keeps its kind and gains the context
+        let source = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let e = HyperlightError::from_io("mapping file", source);
+        assert_eq!(e.io_error_kind(), std::io::ErrorKind::PermissionDenied);
+        let e = std::io::Error::from(e);
+        assert_eq!(e.kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(e.to_string(), "mapping file: denied");
+    }
+
     /// Test that ExecutionCancelledByHost promotes to HyperlightError::ExecutionCanceledByHost
     #[test]
     fn test_promote_execution_cancelled_by_host() {
@@ -486,6 +777,166 @@ mod tests {
         }
     }
 
+    /// Test that GuestAssertionFailed promotes to HyperlightError::GuestAssertionFailed with its location
+    #[test]
+    fn test_promote_guest_assertion_failed() {
+        let err = DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::GuestAssertionFailed {
+                expression: "x == 42".to_string(),
+                file: "main.c".to_string(),
+                line: 7,
+            },
+        )));
+        let (promoted, should_poison) = err.promote();
+
+        assert!(
+            should_poison,
+            "GuestAssertionFailed should poison the sandbox"
+        );
+        match promoted {
+            HyperlightError::GuestAssertionFailed {
+                expression,
+                file,
+                line,
+            } => {
+                assert_eq!(expression, "x == 42");
+                assert_eq!(file, "main.c");
+                assert_eq!(line, 7);
+            }
+            _ => panic!(
+                "Expected HyperlightError::GuestAssertionFailed, got {:?}",
+                promoted
+            ),
+        }
+    }
+
+    /// Test that GuestAllocationLimitExceeded promotes to HyperlightError::GuestAllocationLimitExceeded
+    #[test]
+    fn test_promote_guest_allocation_limit_exceeded() {
+        let err = DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::GuestAllocationLimitExceeded {
+                requested: 4096,
+                limit: 1024,
+            },
+        )));
+        let (promoted, should_poison) = err.promote();
+
+        assert!(
+            should_poison,
+            "GuestAllocationLimitExceeded should poison the sandbox"
+        );
+        assert!(
+            matches!(
+                promoted,
+                HyperlightError::GuestAllocationLimitExceeded {
+                    requested: 4096,
+                    limit: 1024
+                }
+            ),
+            "Expected HyperlightError::GuestAllocationLimitExceeded, got {:?}",
+            promoted
+        );
+    }
+
+    /// Test that an ABI mismatch during initialisation promotes to HyperlightError::AbiMismatch
+    #[test]
+    fn test_promote_abi_mismatch() {
+        let err = InitializeError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::AbiMismatch { host: 2, guest: 1 },
+        )));
+        let promoted = err.promote();
+
+        assert!(!promoted.is_poison_error());
+        assert!(
+            matches!(promoted, HyperlightError::AbiMismatch { host: 2, guest: 1 }),
+            "Expected HyperlightError::AbiMismatch, got {:?}",
+            promoted
+        );
+
+        let err = InitializeError::InvalidStackPointer(0x1001);
+        assert!(matches!(
+            err.promote(),
+            HyperlightError::HyperlightVmError(HyperlightVmError::Initialize(_))
+        ));
+    }
+
+    /// Test that a guest which never reports its ABI version promotes to
+    /// HyperlightError::AbiVersionMissing
+    #[test]
+    fn test_promote_abi_version_missing() {
+        let err = InitializeError::AbiVersionMissing { host: 2 };
+        let promoted = err.promote();
+
+        assert!(!promoted.is_poison_error());
+        assert!(
+            matches!(promoted, HyperlightError::AbiVersionMissing { host: 2 }),
+            "Expected HyperlightError::AbiVersionMissing, got {:?}",
+            promoted
+        );
+    }
+
+    /// Test that an ABI mismatch reported during a call promotes to
+    /// HyperlightError::AbiMismatch and poisons the sandbox
+    #[test]
+    fn test_promote_abi_mismatch_during_call() {
+        let err = DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::AbiMismatch { host: 2, guest: 3 },
+        )));
+        let (promoted, should_poison) = err.promote();
+
+        assert!(should_poison);
+        assert!(
+            matches!(promoted, HyperlightError::AbiMismatch { host: 2, guest: 3 }),
+            "Expected HyperlightError::AbiMismatch, got {:?}",
+            promoted
+        );
+    }
+
+    /// Test that GuestFpException promotes to HyperlightError::GuestFpException
+    #[test]
+    fn test_promote_guest_fp_exception() {
+        let err = DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::GuestFpException {
+                mxcsr: 0x1d84,
+                rip: 0x1234,
+            },
+        )));
+        let (promoted, should_poison) = err.promote();
+
+        assert!(should_poison, "GuestFpException should poison the sandbox");
+        assert!(
+            matches!(
+                promoted,
+                HyperlightError::GuestFpException {
+                    exceptions: FpExceptions::DIVIDE_BY_ZERO,
+                    mxcsr: 0x1d84,
+                    rip: 0x1234,
+                }
+            ),
+            "Expected HyperlightError::GuestFpException, got {:?}",
+            promoted
+        );
+    }
+
+    /// Test that SelfModifyingCode promotes to HyperlightError::SelfModifyingCode
+    #[test]
+    fn test_promote_self_modifying_code() {
+        let err = DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+            HandleOutbError::SelfModifyingCode { address: 0x1234 },
+        )));
+        let (promoted, should_poison) = err.promote();
+
+        assert!(should_poison, "SelfModifyingCode should poison the sandbox");
+        assert!(
+            matches!(
+                promoted,
+                HyperlightError::SelfModifyingCode { address: 0x1234 }
+            ),
+            "Expected HyperlightError::SelfModifyingCode, got {:?}",
+            promoted
+        );
+    }
+
     /// Test that MemoryAccessViolation promotes to HyperlightError::MemoryAccessViolation
     #[test]
     fn test_promote_memory_access_violation() {
@@ -0,0 +1,203 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+/// Declares a set of host functions, generating both their registration
+/// and their definitions from a single definition of each function.
+///
+/// The macro takes the name of a unit struct to generate, followed by the
+/// functions. Each function becomes an associated function of the struct,
+/// and is registered under its own name, or under the name given after
+/// `as`. The struct gets a `register` function, which registers every
+/// function on a [`Registerable`](crate::func::Registerable) such as an
+/// [`UninitializedSandbox`](crate::UninitializedSandbox), and a
+/// `definitions` function, which returns the
+/// [`HostFunctionDefinition`](crate::func::HostFunctionDefinition) of each
+/// function, in order, for describing them to guests.
+///
+/// The parameter and return types come from the functions' signatures, so
+/// they cannot drift from the implementations. A function must declare its
+/// return type, which may be a [`Result`](crate::Result).
+///
+/// ```
+/// # use hyperlight_host::{Result, UninitializedSandbox, GuestBinary, host_functions};
+/// host_functions! {
+///     /// The host functions the guest uses to do arithmetic
+///     pub struct MathFunctions;
+///
+///     fn add(a: i32, b: i32) -> i32 as "HostAdd" {
+///         a + b
+///     }
+///
+///     /// Divides `a` by `b`, failing if `b` is 0
+///     fn divide(a: i64, b: i64) -> Result<i64> {
+///         a.checked_div(b)
+///             .ok_or_else(|| hyperlight_host::new_error!("{} / {} is undefined", a, b))
+///     }
+/// }
+///
+/// assert_eq!(MathFunctions::add(1, 2), 3);
+/// let names: Vec<_> = MathFunctions::definitions()
+///     .into_iter()
+///     .map(|definition| definition.function_name)
+///     .collect();
+/// assert_eq!(names, ["HostAdd", "divide"]);
+///
+/// # fn example() -> Result<()> {
+/// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+/// MathFunctions::register(&mut sandbox)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A function whose parameters or return value cannot be passed between
+/// the host and the guest does not compile:
+///
+/// ```compile_fail
+/// # use hyperlight_host::host_functions;
+/// host_functions! {
+///     struct FileFunctions;
+///
+///     fn exists(path: std::path::PathBuf) -> bool {
+///         path.exists()
+///     }
+/// }
+/// ```
+///
+/// Nor does a function whose implementation does not match its signature:
+///
+/// ```compile_fail
+/// # use hyperlight_host::host_functions;
+/// host_functions! {
+///     struct MathFunctions;
+///
+///     fn double(a: i32) -> i32 {
+///         i64::from(a) * 2
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! host_functions {
+    (@name $name:ident) => {
+        stringify!($name)
+    };
+    (@name $name:ident $host_name:literal) => {
+        $host_name
+    };
+    (
+        $(#[$set_meta:meta])*
+        $vis:vis struct $set:ident;
+
+        $(
+            $(#[$meta:meta])*
+            fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $(as $host_name:literal)? $body:block
+        )*
+    ) => {
+        $(#[$set_meta])*
+        $vis struct $set;
+
+        impl $set {
+            $(
+                $(#[$meta])*
+                $vis fn $name($($arg: $ty),*) -> $ret $body
+            )*
+
+            /// Register each of these host functions on `sandbox`.
+            #[allow(dead_code)]
+            $vis fn register(sandbox: &mut impl $crate::func::Registerable) -> $crate::Result<()> {
+                $(
+                    sandbox.register_host_function(
+                        $crate::host_functions!(@name $name $($host_name)?),
+                        Self::$name,
+                    )?;
+                )*
+                Ok(())
+            }
+
+            /// The definitions of these host functions, in the order they
+            /// were declared.
+            #[allow(dead_code)]
+            $vis fn definitions() -> ::std::vec::Vec<$crate::func::HostFunctionDefinition> {
+                ::std::vec![$(
+                    $crate::func::HostFunctionDefinition {
+                        function_name: ::std::string::String::from(
+                            $crate::host_functions!(@name $name $($host_name)?),
+                        ),
+                        parameter_types: ::std::option::Option::Some(
+                            <($($ty,)*) as $crate::func::ParameterTuple>::TYPE.to_vec(),
+                        ),
+                        return_type: <<$ret as $crate::func::ResultType<$crate::HyperlightError>>::ReturnType
+                            as $crate::func::SupportedReturnType>::TYPE,
+                    },
+                )*]
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_testing::simple_guest_as_string;
+
+    use crate::func::{ParameterType, ReturnType};
+    use crate::{GuestBinary, Result, UninitializedSandbox, new_error};
+
+    host_functions! {
+        struct TestFunctions;
+
+        fn add(a: i32, b: i32) -> i32 as "HostAdd" {
+            a + b
+        }
+
+        fn checked_divide(a: i64, b: i64) -> Result<i64> {
+            a.checked_div(b)
+                .ok_or_else(|| new_error!("{} / {} is undefined", a, b))
+        }
+
+        fn version() -> String {
+            "1.0".to_string()
+        }
+    }
+
+    #[test]
+    fn definitions() {
+        let definitions = TestFunctions::definitions();
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(definitions[0].function_name, "HostAdd");
+        assert_eq!(
+            definitions[0].parameter_types,
+            Some(vec![ParameterType::Int, ParameterType::Int])
+        );
+        assert_eq!(definitions[0].return_type, ReturnType::Int);
+        assert_eq!(definitions[1].function_name, "checked_divide");
+        assert_eq!(definitions[1].return_type, ReturnType::Long);
+        assert_eq!(definitions[2].function_name, "version");
+        assert_eq!(definitions[2].parameter_types, Some(vec![]));
+        assert_eq!(definitions[2].return_type, ReturnType::String);
+    }
+
+    #[test]
+    fn register() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        TestFunctions::register(&mut sandbox).unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        // The guest's Add calls HostAdd
+        assert_eq!(sandbox.call::<i32>("Add", (1i32, 2i32)).unwrap(), 3);
+        assert_eq!(TestFunctions::checked_divide(7, 2).unwrap(), 3);
+        assert!(TestFunctions::checked_divide(7, 0).is_err());
+    }
+}
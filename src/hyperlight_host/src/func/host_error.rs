@@ -0,0 +1,179 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::fmt;
+
+use crate::{HyperlightError, Result};
+
+/// A host function failure, with the context added to it by the host
+/// function as it was propagated, see [`Context`].
+///
+/// It is displayed as its context, outermost first, followed by the error
+/// it was created from, each separated by `": "`. That is also the message
+/// the guest receives when a host function fails with it, so the whole
+/// chain is kept in the
+/// [`HyperlightError::GuestError`] with `ErrorCode::HostFunctionError`
+/// that a guest call fails with when the guest propagates the failure.
+#[derive(Debug)]
+pub struct HostFunctionError {
+    /// The context added to the error, outermost first
+    context: Vec<String>,
+    source: Box<HyperlightError>,
+}
+
+impl HostFunctionError {
+    /// The context added to the error, outermost first.
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
+    /// The error the chain was created from.
+    pub fn root_cause(&self) -> &HyperlightError {
+        &self.source
+    }
+
+    /// The messages of the chain, from the outermost context to the root
+    /// cause.
+    pub fn chain(&self) -> impl Iterator<Item = String> + '_ {
+        self.context
+            .iter()
+            .cloned()
+            .chain(std::iter::once(self.source.to_string()))
+    }
+
+    /// Adds `context` to `error`, making it a
+    /// [`HyperlightError::HostFunctionFailed`] if it is not one already.
+    fn wrap(error: HyperlightError, context: String) -> HyperlightError {
+        match error {
+            HyperlightError::HostFunctionFailed(mut e) => {
+                e.context.insert(0, context);
+                HyperlightError::HostFunctionFailed(e)
+            }
+            error => HyperlightError::HostFunctionFailed(HostFunctionError {
+                context: vec![context],
+                source: Box::new(error),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for HostFunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for context in &self.context {
+            write!(f, "{context}: ")?;
+        }
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::error::Error for HostFunctionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Adds context to the errors of host functions, in the style of
+/// `anyhow::Context`.
+///
+/// ```
+/// # use hyperlight_host::Result;
+/// # use hyperlight_host::func::Context;
+/// fn load_config(path: &str) -> Result<String> {
+///     std::fs::read_to_string(path).with_context(|| format!("loading config from {path}"))
+/// }
+///
+/// let e = load_config("/does/not/exist")
+///     .context("handling GetConfig")
+///     .unwrap_err();
+/// assert!(e
+///     .to_string()
+///     .starts_with("handling GetConfig: loading config from /does/not/exist: "));
+/// ```
+pub trait Context<T> {
+    /// Wrap the error, if any, with `context`.
+    fn context(self, context: impl fmt::Display) -> Result<T>;
+
+    /// Wrap the error, if any, with the context returned by `f`, which is
+    /// only called if there is an error.
+    fn with_context<C: fmt::Display>(self, f: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T, E: Into<HyperlightError>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: impl fmt::Display) -> Result<T> {
+        self.map_err(|e| HostFunctionError::wrap(e.into(), context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display>(self, f: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|e| HostFunctionError::wrap(e.into(), f().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+    use hyperlight_testing::simple_guest_as_string;
+
+    use super::Context;
+    use crate::{GuestBinary, HyperlightError, Result, UninitializedSandbox, new_error};
+
+    fn open() -> Result<()> {
+        Err(new_error!("no such file"))
+    }
+
+    #[test]
+    fn context_chains() {
+        let e = open()
+            .context("opening data.bin")
+            .with_context(|| format!("loading record {}", 3))
+            .unwrap_err();
+        let HyperlightError::HostFunctionFailed(chain) = &e else {
+            panic!("{e:?}");
+        };
+        assert_eq!(chain.context(), ["loading record 3", "opening data.bin"]);
+        assert!(matches!(chain.root_cause(), HyperlightError::Error(m) if m == "no such file"));
+        assert_eq!(
+            chain.chain().collect::<Vec<_>>(),
+            ["loading record 3", "opening data.bin", "no such file"]
+        );
+        assert_eq!(
+            e.to_string(),
+            "loading record 3: opening data.bin: no such file"
+        );
+        assert!(Ok::<_, HyperlightError>(1).context("unused").is_ok());
+    }
+
+    #[test]
+    fn chain_reaches_guest_caller() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        sandbox
+            .register("HostError", || -> Result<i64> {
+                open().context("reading the answer")
+            })
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        let e = sandbox
+            .call::<i64>(
+                "CallGivenParamlessHostFuncThatReturnsI64",
+                "HostError".to_string(),
+            )
+            .unwrap_err();
+        assert!(
+            matches!(&e, HyperlightError::GuestError(ErrorCode::HostFunctionError, m) if m == "reading the answer: no such file"),
+            "{e:?}"
+        );
+    }
+}
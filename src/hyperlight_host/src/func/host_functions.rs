@@ -156,8 +156,11 @@ where
     func: Arc<dyn Function<Output, Args, HyperlightError> + Send + Sync + 'static>,
 }
 
+// Arc so that a host function can be cloned out of the registry and called
+// without holding the registry lock, see `outb::call_host_function`.
+#[derive(Clone)]
 pub(crate) struct TypeErasedHostFunction {
-    func: Box<dyn Fn(Vec<ParameterValue>) -> Result<ReturnValue> + Send + Sync + 'static>,
+    func: Arc<dyn Fn(Vec<ParameterValue>) -> Result<ReturnValue> + Send + Sync + 'static>,
 }
 
 impl<Args, Output> HostFunction<Output, Args>
@@ -169,9 +172,42 @@ where
     pub fn call(&self, args: Args) -> Result<Output> {
         self.func.call(args)
     }
+
+    /// Create a host function from a closure that takes all of its
+    /// arguments as one tuple, which lets it be generic over `Args`.
+    pub(crate) fn from_tuple_fn(
+        func: impl Fn(Args) -> Result<Output> + Send + Sync + 'static,
+    ) -> Self {
+        HostFunction {
+            func: Arc::new(TupleFn(func)),
+        }
+    }
+}
+
+/// A closure that takes all of a host function's arguments as one tuple,
+/// see [`HostFunction::from_tuple_fn`].
+struct TupleFn<F>(F);
+
+impl<F, Args, Output> Function<Output, Args, HyperlightError> for TupleFn<F>
+where
+    F: Fn(Args) -> Result<Output>,
+    Args: ParameterTuple,
+    Output: SupportedReturnType,
+{
+    fn call(&self, args: Args) -> Result<Output> {
+        (self.0)(args)
+    }
 }
 
 impl TypeErasedHostFunction {
+    pub(crate) fn new(
+        func: impl Fn(Vec<ParameterValue>) -> Result<ReturnValue> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            func: Arc::new(func),
+        }
+    }
+
     pub(crate) fn call(&self, args: Vec<ParameterValue>) -> Result<ReturnValue> {
         (self.func)(args)
     }
@@ -206,7 +242,7 @@ where
 {
     fn from(func: HostFunction<Output, Args>) -> TypeErasedHostFunction {
         TypeErasedHostFunction {
-            func: Box::new(move |args: Vec<ParameterValue>| {
+            func: Arc::new(move |args: Vec<ParameterValue>| {
                 let args = Args::from_value(args)?;
                 Ok(func.call(args)?.into_value())
             }),
@@ -14,6 +14,11 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+/// The `host_functions!` macro, which declares host functions and their
+/// registration together
+mod declare;
+/// Errors of host functions with context chains
+pub(crate) mod host_error;
 /// Definitions and functionality to enable guest-to-host function calling,
 /// also called "host functions"
 ///
@@ -26,7 +31,14 @@ limitations under the License.
 /// - Dynamically dispatching a call from the guest to the appropriate
 ///   host function
 pub(crate) mod host_functions;
+/// Recording host and guest function calls, and replaying them to test
+/// guests without the host functions or check that they are deterministic
+pub(crate) mod replay;
+/// Return types that guest results can be decoded into without allocating
+pub(crate) mod reusable;
 
+/// Re-export for the host function error types
+pub use host_error::{Context, HostFunctionError};
 /// Re-export for `HostFunction` trait
 pub use host_functions::{HostFunction, Registerable};
 /// Re-export for `ParameterType` enum
@@ -42,5 +54,14 @@ pub use hyperlight_common::flatbuffer_wrappers::host_function_definition::HostFu
 /// Re-export for `HostFunctionDetails`
 pub use hyperlight_common::flatbuffer_wrappers::host_function_details::HostFunctionDetails;
 pub use hyperlight_common::func::{
+    BorrowedParameter, BorrowedParameterTuple, Codec, FlatbufferCodec, IntoParameterValue,
     ParameterTuple, ResultType, SupportedParameterType, SupportedReturnType,
 };
+/// Re-export for the `params!` macro, which builds a `Vec<ParameterValue>`
+pub use hyperlight_common::params;
+/// Re-export for the call recording and replay types
+pub use replay::{
+    GuestCallRecorder, HostCallRecorder, HostCallReplayer, RecordedGuestCall, RecordedHostCall,
+};
+/// Re-export for the `ReusableReturnType` trait
+pub use reusable::ReusableReturnType;
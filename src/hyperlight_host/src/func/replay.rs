@@ -0,0 +1,489 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+use tracing::{Span, instrument};
+
+use super::{
+    Codec, FlatbufferCodec, HostFunction, ParameterTuple, Registerable, SupportedReturnType,
+};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// A host function call made by a guest, as recorded by
+/// [`HostCallRecorder`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedHostCall {
+    /// The name of the host function
+    pub name: String,
+    /// The arguments the guest passed
+    pub args: Vec<ParameterValue>,
+    /// The value the host function returned, or the message of the error
+    /// it failed with
+    pub response: std::result::Result<ReturnValue, String>,
+}
+
+/// Records the calls a guest makes to host functions and their responses,
+/// so that they can be replayed with [`HostCallReplayer`].
+///
+/// Register the host functions to record with
+/// [`register`](Self::register) rather than on the sandbox directly.
+/// Clones of a recorder share its recording.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use hyperlight_host::{GuestBinary, UninitializedSandbox};
+/// # use hyperlight_host::func::{HostCallRecorder, HostCallReplayer};
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let recorder = HostCallRecorder::new();
+/// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+/// recorder.register(&mut sandbox, "ProcessId", || std::process::id())?;
+/// let mut sandbox = sandbox.evolve()?;
+/// let first: String = sandbox.call("Process", "input".to_string())?;
+///
+/// // Run the same call again, with the host functions answered from the
+/// // recording
+/// let replayer = HostCallReplayer::new(recorder.calls()?);
+/// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+/// replayer.register::<(), u32>(&mut sandbox, "ProcessId")?;
+/// let mut sandbox = sandbox.evolve()?;
+/// let second: String = sandbox.call("Process", "input".to_string())?;
+/// assert_eq!(first, second);
+/// replayer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HostCallRecorder {
+    calls: Arc<Mutex<Vec<RecordedHostCall>>>,
+}
+
+impl HostCallRecorder {
+    /// Create a recorder that has not recorded any calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `hf` as the host function `name` on `sandbox`, recording
+    /// every call the guest makes to it.
+    pub fn register<Args: ParameterTuple, Output: SupportedReturnType>(
+        &self,
+        sandbox: &mut impl Registerable,
+        name: &str,
+        hf: impl Into<HostFunction<Output, Args>>,
+    ) -> Result<()> {
+        let hf = hf.into();
+        let calls = self.calls.clone();
+        let function_name = name.to_string();
+        let recording = HostFunction::from_tuple_fn(move |args: Args| {
+            let arg_values = args.clone().into_value();
+            let res = hf.call(args);
+            let response = match &res {
+                Ok(output) => Ok(output.clone().into_value()),
+                Err(e) => Err(e.to_string()),
+            };
+            calls
+                .lock()
+                .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                .push(RecordedHostCall {
+                    name: function_name.clone(),
+                    args: arg_values,
+                    response,
+                });
+            res
+        });
+        sandbox.register_host_function(name, recording)
+    }
+
+    /// The calls recorded so far, in the order they were made.
+    pub fn calls(&self) -> Result<Vec<RecordedHostCall>> {
+        Ok(self
+            .calls
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .clone())
+    }
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    calls: VecDeque<RecordedHostCall>,
+    /// The number of calls replayed so far
+    replayed: usize,
+}
+
+/// Answers a guest's host function calls with the responses recorded by a
+/// [`HostCallRecorder`], without calling the host functions, so that guest
+/// behaviour can be tested without their side effects.
+///
+/// The calls must be made in the order they were recorded, with the same
+/// arguments. A call that does not match the next recorded call fails,
+/// naming both calls, and is not consumed. Register the host functions to
+/// replay with [`register`](Self::register), and check that every recorded
+/// call was replayed with [`finish`](Self::finish). Clones of a replayer
+/// share the recording.
+#[derive(Clone, Debug)]
+pub struct HostCallReplayer {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl HostCallReplayer {
+    /// Create a replayer that answers calls with `calls`, in order.
+    pub fn new(calls: Vec<RecordedHostCall>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                calls: calls.into(),
+                replayed: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, ReplayState>> {
+        self.state
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))
+    }
+
+    /// Register the host function `name` on `sandbox`, answering the
+    /// guest's calls to it from the recording.
+    pub fn register<Args: ParameterTuple, Output: SupportedReturnType>(
+        &self,
+        sandbox: &mut impl Registerable,
+        name: &str,
+    ) -> Result<()> {
+        let replayer = self.clone();
+        let function_name = name.to_string();
+        let replay = HostFunction::from_tuple_fn(move |args: Args| {
+            let response = replayer.replay(&function_name, args.into_value())?;
+            Ok(Output::from_value(response)?)
+        });
+        sandbox.register_host_function(name, replay)
+    }
+
+    /// The response to the call of `name` with `args`, which must be the
+    /// next recorded call.
+    fn replay(&self, name: &str, args: Vec<ParameterValue>) -> Result<ReturnValue> {
+        let mut state = self.lock()?;
+        let index = state.replayed;
+        let Some(call) = state.calls.front() else {
+            return Err(new_error!(
+                "Host call {} to {}({:?}) was not recorded, all {} recorded calls have been replayed",
+                index,
+                name,
+                args,
+                index
+            ));
+        };
+        if call.name != name || call.args != args {
+            return Err(new_error!(
+                "Host call {} to {}({:?}) does not match the recorded call to {}({:?})",
+                index,
+                name,
+                args,
+                call.name,
+                call.args
+            ));
+        }
+        let call = state.calls.pop_front().unwrap();
+        state.replayed += 1;
+        call.response.map_err(|msg| new_error!("{}", msg))
+    }
+
+    /// The number of recorded calls that have not been replayed.
+    pub fn remaining(&self) -> Result<usize> {
+        Ok(self.lock()?.calls.len())
+    }
+
+    /// Check that every recorded call was replayed, failing with the first
+    /// one that was not.
+    pub fn finish(&self) -> Result<()> {
+        let state = self.lock()?;
+        match state.calls.front() {
+            None => Ok(()),
+            Some(call) => Err(new_error!(
+                "{} recorded host calls were not replayed, starting with call {} to {}({:?})",
+                state.calls.len(),
+                state.replayed,
+                call.name,
+                call.args
+            )),
+        }
+    }
+}
+
+/// A guest function call made by the host, as recorded by
+/// [`GuestCallRecorder`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedGuestCall {
+    /// The name of the guest function
+    pub name: String,
+    /// The arguments the host passed
+    pub args: Vec<ParameterValue>,
+    /// The type the guest function was expected to return
+    pub return_type: ReturnType,
+    /// The value the guest function returned, or the message of the error
+    /// the call failed with
+    pub result: std::result::Result<ReturnValue, String>,
+}
+
+/// Records the guest function calls made through it and their results, so
+/// that they can be checked against a fresh sandbox with
+/// [`MultiUseSandbox::verify_replay`].
+///
+/// Clones of a recorder share its recording.
+#[derive(Clone, Debug, Default)]
+pub struct GuestCallRecorder {
+    calls: Arc<Mutex<Vec<RecordedGuestCall>>>,
+}
+
+impl GuestCallRecorder {
+    /// Create a recorder that has not recorded any calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call the guest function `func_name` on `sandbox` as
+    /// [`MultiUseSandbox::call`] does, recording the call and its result.
+    pub fn call<Output: SupportedReturnType>(
+        &self,
+        sandbox: &mut MultiUseSandbox,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<Output> {
+        let args = args.into_value();
+        let res = sandbox.call_dynamic(func_name, Output::TYPE, args.clone());
+        self.calls
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .push(RecordedGuestCall {
+                name: func_name.to_string(),
+                args,
+                return_type: Output::TYPE,
+                result: res.as_ref().cloned().map_err(ToString::to_string),
+            });
+        Ok(Output::from_value(res?)?)
+    }
+
+    /// The calls recorded so far, in the order they were made.
+    pub fn calls(&self) -> Result<Vec<RecordedGuestCall>> {
+        Ok(self
+            .calls
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .clone())
+    }
+}
+
+impl MultiUseSandbox {
+    /// Makes the guest function calls recorded by a [`GuestCallRecorder`]
+    /// again, in order, checking that each returns exactly what it
+    /// returned when it was recorded.
+    ///
+    /// This is a testing tool for checking that a guest is deterministic,
+    /// so that recordings of it can be trusted. Run it on a fresh sandbox
+    /// created from the same guest, with the guest's host functions
+    /// answered by a [`HostCallReplayer`] so that they return what they
+    /// returned when the calls were recorded. A guest that still produces
+    /// a different result, for example because it reads an unseeded
+    /// random number generator or the real time, diverges from the
+    /// recording.
+    ///
+    /// Results are compared in their flatbuffer encoding, so they must
+    /// match byte for byte, and a call that failed must fail with the same
+    /// message. This fails at the first call that diverges, naming it and
+    /// both results, without making the calls after it.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{GuestBinary, UninitializedSandbox};
+    /// # use hyperlight_host::func::{GuestCallRecorder, HostCallRecorder, HostCallReplayer};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let host_calls = HostCallRecorder::new();
+    /// let guest_calls = GuestCallRecorder::new();
+    /// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+    /// host_calls.register(&mut sandbox, "ProcessId", || std::process::id())?;
+    /// let mut sandbox = sandbox.evolve()?;
+    /// guest_calls.call::<String>(&mut sandbox, "Process", "input".to_string())?;
+    ///
+    /// let replayer = HostCallReplayer::new(host_calls.calls()?);
+    /// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+    /// replayer.register::<(), u32>(&mut sandbox, "ProcessId")?;
+    /// let mut sandbox = sandbox.evolve()?;
+    /// sandbox.verify_replay(&guest_calls.calls()?)?;
+    /// replayer.finish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn verify_replay(&mut self, calls: &[RecordedGuestCall]) -> Result<()> {
+        for (index, call) in calls.iter().enumerate() {
+            let replayed = self
+                .call_dynamic(&call.name, call.return_type, call.args.clone())
+                .map_err(|e| e.to_string());
+            let matches = match (&call.result, &replayed) {
+                (Ok(recorded), Ok(replayed)) => {
+                    FlatbufferCodec.encode_return_value(recorded)?
+                        == FlatbufferCodec.encode_return_value(replayed)?
+                }
+                (Err(recorded), Err(replayed)) => recorded == replayed,
+                _ => false,
+            };
+            if !matches {
+                return Err(new_error!(
+                    "Replayed guest call {} to {}({:?}) returned {:?}, but the recorded call returned {:?}",
+                    index,
+                    call.name,
+                    call.args,
+                    replayed,
+                    call.result
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnValue};
+    use hyperlight_testing::simple_guest_as_string;
+
+    use super::{GuestCallRecorder, HostCallRecorder, HostCallReplayer, RecordedHostCall};
+    use crate::{GuestBinary, UninitializedSandbox};
+
+    fn new_uninit_sandbox() -> UninitializedSandbox {
+        let path = simple_guest_as_string().unwrap();
+        UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap()
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let recorder = HostCallRecorder::new();
+        let host_calls = Arc::new(AtomicI32::new(0));
+        let mut sandbox = new_uninit_sandbox();
+        let counter = host_calls.clone();
+        recorder
+            .register(&mut sandbox, "HostAdd", move |a: i32, b: i32| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                a + b
+            })
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        assert_eq!(sandbox.call::<i32>("Add", (1i32, 2i32)).unwrap(), 3);
+        assert_eq!(sandbox.call::<i32>("Add", (3i32, 4i32)).unwrap(), 7);
+
+        let calls = recorder.calls().unwrap();
+        assert_eq!(
+            calls[0],
+            RecordedHostCall {
+                name: "HostAdd".to_string(),
+                args: vec![ParameterValue::Int(1), ParameterValue::Int(2)],
+                response: Ok(ReturnValue::Int(3)),
+            }
+        );
+        assert_eq!(calls.len(), 2);
+        assert_eq!(host_calls.load(Ordering::Relaxed), 2);
+
+        let replayer = HostCallReplayer::new(calls);
+        let mut sandbox = new_uninit_sandbox();
+        replayer
+            .register::<(i32, i32), i32>(&mut sandbox, "HostAdd")
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        assert_eq!(sandbox.call::<i32>("Add", (1i32, 2i32)).unwrap(), 3);
+        assert_eq!(replayer.remaining().unwrap(), 1);
+        assert!(replayer.finish().is_err());
+
+        // A call that does not match the recording fails, and is not
+        // consumed
+        assert!(sandbox.call::<i32>("Add", (5i32, 6i32)).is_err());
+        assert_eq!(replayer.remaining().unwrap(), 1);
+
+        let mut sandbox = new_uninit_sandbox();
+        replayer
+            .register::<(i32, i32), i32>(&mut sandbox, "HostAdd")
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        assert_eq!(sandbox.call::<i32>("Add", (3i32, 4i32)).unwrap(), 7);
+        replayer.finish().unwrap();
+        // The host function was never called while replaying
+        assert_eq!(host_calls.load(Ordering::Relaxed), 2);
+
+        // Nor are calls beyond the recording
+        assert!(sandbox.call::<i32>("Add", (3i32, 4i32)).is_err());
+    }
+
+    #[test]
+    fn verify_replay() {
+        let host_calls = HostCallRecorder::new();
+        let guest_calls = GuestCallRecorder::new();
+        let mut sandbox = new_uninit_sandbox();
+        host_calls
+            .register(&mut sandbox, "HostAdd", |a: i32, b: i32| a + b)
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        let sum: i32 = guest_calls.call(&mut sandbox, "Add", (1i32, 2i32)).unwrap();
+        assert_eq!(sum, 3);
+        let echoed: String = guest_calls
+            .call(&mut sandbox, "Echo", "hello".to_string())
+            .unwrap();
+        assert_eq!(echoed, "hello");
+        assert!(
+            guest_calls
+                .call::<i32>(&mut sandbox, "NoSuchFunction", ())
+                .is_err()
+        );
+        let calls = guest_calls.calls().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].result, Ok(ReturnValue::Int(3)));
+        assert!(calls[2].result.is_err());
+
+        // A fresh sandbox whose host calls are replayed reproduces every
+        // result
+        let replayer = HostCallReplayer::new(host_calls.calls().unwrap());
+        let mut sandbox = new_uninit_sandbox();
+        replayer
+            .register::<(i32, i32), i32>(&mut sandbox, "HostAdd")
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        sandbox.verify_replay(&calls).unwrap();
+        replayer.finish().unwrap();
+
+        // A host function that answers differently makes the first call
+        // diverge
+        let mut sandbox = new_uninit_sandbox();
+        sandbox
+            .register("HostAdd", |a: i32, b: i32| a + b + 1)
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        let err = sandbox.verify_replay(&calls).unwrap_err().to_string();
+        assert!(err.contains("call 0 to Add"), "{err}");
+    }
+}
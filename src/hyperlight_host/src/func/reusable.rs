@@ -0,0 +1,51 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use super::SupportedReturnType;
+use crate::{Result, new_error};
+
+/// A return type backed by a byte buffer, which
+/// [`MultiUseSandbox::call_into`](crate::MultiUseSandbox::call_into) and
+/// [`MultiUseSandbox::call_reuse`](crate::MultiUseSandbox::call_reuse) can
+/// decode guest results into, reusing its allocation when it is large
+/// enough to hold the result.
+pub trait ReusableReturnType: SupportedReturnType {
+    /// Take the buffer backing this value, leaving the value empty.
+    fn take_buffer(&mut self) -> Vec<u8>;
+
+    /// Create a value from a buffer holding its bytes.
+    fn from_buffer(buf: Vec<u8>) -> Result<Self>;
+}
+
+impl ReusableReturnType for Vec<u8> {
+    fn take_buffer(&mut self) -> Vec<u8> {
+        std::mem::take(self)
+    }
+
+    fn from_buffer(buf: Vec<u8>) -> Result<Self> {
+        Ok(buf)
+    }
+}
+
+impl ReusableReturnType for String {
+    fn take_buffer(&mut self) -> Vec<u8> {
+        std::mem::take(self).into_bytes()
+    }
+
+    fn from_buffer(buf: Vec<u8>) -> Result<Self> {
+        String::from_utf8(buf).map_err(|e| new_error!("Guest returned an invalid string: {}", e))
+    }
+}
@@ -79,6 +79,17 @@ impl HyperlightVm {
         unimplemented!("dispatch_call_from_host")
     }
 
+    pub(crate) fn dispatch_nested_call_from_host(
+        &mut self,
+        _mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        _host_funcs: &Arc<std::sync::Mutex<FunctionRegistry>>,
+        #[cfg(gdb)] _dbg_mem_access_fn: Arc<
+            std::sync::Mutex<SandboxMemoryManager<HostSharedMemory>>,
+        >,
+    ) -> Result<(), DispatchGuestCallError> {
+        unimplemented!("dispatch_nested_call_from_host")
+    }
+
     pub(crate) fn get_root_pt(&self) -> Result<u64, AccessPageTableError> {
         unimplemented!("get_root_pt")
     }
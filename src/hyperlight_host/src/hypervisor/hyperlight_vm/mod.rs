@@ -23,10 +23,19 @@ mod aarch64;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 #[cfg(target_arch = "aarch64")]
 pub(crate) use aarch64::*;
+use flatbuffers::FlatBufferBuilder;
+use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+use hyperlight_common::flatbuffer_wrappers::util::estimate_flatbuffer_capacity;
+use hyperlight_common::fp_exceptions::FpExceptions;
 use hyperlight_common::log_level::GuestLogFilter;
+use hyperlight_common::outb::OutBAction;
 use tracing_core::LevelFilter;
 
 use crate::HyperlightError;
@@ -40,18 +49,28 @@ use crate::hypervisor::gdb::{
 };
 #[cfg(gdb)]
 use crate::hypervisor::hyperlight_vm::x86_64::debug::ProcessDebugRequestError;
+#[cfg(feature = "hw-interrupts")]
+use crate::hypervisor::virtual_machine::RaiseInterruptError;
 #[cfg(not(gdb))]
 use crate::hypervisor::virtual_machine::VirtualMachine;
 use crate::hypervisor::virtual_machine::{
-    MapMemoryError, RegisterError, RunVcpuError, UnmapMemoryError, VmError, VmExit,
+    HypervisorBackend, MapMemoryError, RegisterError, RunVcpuError, UnmapMemoryError, VmError,
+    VmExit,
 };
 use crate::hypervisor::{InterruptHandle, InterruptHandleImpl};
-use crate::mem::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
+use crate::mem::memory_region::{
+    HostGuestMemoryRegion, MemoryRegion, MemoryRegionFlags, MemoryRegionKind, MemoryRegionType,
+};
 use crate::mem::mgr::{SandboxMemoryManager, SnapshotSharedMemory};
 use crate::mem::shared_mem::{GuestSharedMemory, HostSharedMemory, SharedMemory};
 use crate::metrics::{METRIC_ERRONEOUS_VCPU_KICKS, METRIC_GUEST_CANCELLATION};
+use crate::sandbox::exit_stats::{ExitReason, ExitStats};
 use crate::sandbox::host_funcs::FunctionRegistry;
-use crate::sandbox::outb::{HandleOutbError, handle_outb};
+use crate::sandbox::host_stream;
+use crate::sandbox::outb::{
+    HandleOutbError, call_host_function, handle_host_function_call, handle_outb,
+};
+use crate::sandbox::reentrant::{self, ReentrantCall};
 use crate::sandbox::snapshot::NextAction;
 #[cfg(feature = "mem_profile")]
 use crate::sandbox::trace::MemTraceInfo;
@@ -159,6 +178,42 @@ impl DispatchGuestCallError {
                 HandleOutbError::GuestAborted { code, message },
             ))) => HyperlightError::GuestAborted(code, message),
 
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::AbiMismatch { host, guest },
+            ))) => HyperlightError::AbiMismatch { host, guest },
+
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::GuestAllocationLimitExceeded { requested, limit },
+            ))) => HyperlightError::GuestAllocationLimitExceeded { requested, limit },
+
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::HostCallLimitExceeded { limit },
+            ))) => HyperlightError::HostCallLimitExceeded { limit },
+
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::GuestFpException { mxcsr, rip },
+            ))) => HyperlightError::GuestFpException {
+                exceptions: FpExceptions::flags_of(mxcsr) - FpExceptions::masked_in(mxcsr),
+                mxcsr,
+                rip,
+            },
+
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::SelfModifyingCode { address },
+            ))) => HyperlightError::SelfModifyingCode { address },
+
+            DispatchGuestCallError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::GuestAssertionFailed {
+                    expression,
+                    file,
+                    line,
+                },
+            ))) => HyperlightError::GuestAssertionFailed {
+                expression,
+                file,
+                line,
+            },
+
             DispatchGuestCallError::Run(RunVmError::MemoryAccessViolation {
                 addr,
                 access_type,
@@ -183,6 +238,28 @@ pub enum InitializeError {
     SetupRegs(#[from] RegisterError),
     #[error("Guest initialised stack pointer to architecturally invalid value: {0}")]
     InvalidStackPointer(u64),
+    #[error("Guest did not report its ABI version, the host uses version {host}")]
+    AbiVersionMissing { host: u32 },
+}
+
+impl InitializeError {
+    /// Converts an `InitializeError` to a `HyperlightError`, surfacing an
+    /// ABI mismatch reported by the guest and a cancellation by the host as
+    /// their own errors.
+    pub(crate) fn promote(self) -> HyperlightError {
+        match self {
+            InitializeError::Run(RunVmError::ExecutionCancelledByHost) => {
+                HyperlightError::ExecutionCanceledByHost()
+            }
+            InitializeError::Run(RunVmError::HandleIo(HandleIoError::Outb(
+                HandleOutbError::AbiMismatch { host, guest },
+            ))) => HyperlightError::AbiMismatch { host, guest },
+            InitializeError::AbiVersionMissing { host } => {
+                HyperlightError::AbiVersionMissing { host }
+            }
+            other => HyperlightVmError::Initialize(other).into(),
+        }
+    }
 }
 
 /// Errors that can occur during VM execution in the run loop
@@ -234,6 +311,8 @@ pub enum HandleIoError {
     NoData,
     #[error("{0}")]
     Outb(#[from] HandleOutbError),
+    #[error("Reentrant guest call did not complete: {0}")]
+    ReentrantCallFailed(String),
 }
 
 /// Errors that can occur when mapping a memory region
@@ -263,6 +342,23 @@ pub enum UnmapRegionError {
     UnmapMemory(#[from] UnmapMemoryError),
 }
 
+/// Errors that can occur when changing the protection of a mapped region
+#[derive(Debug, thiserror::Error)]
+pub enum ProtectRegionError {
+    #[error("Range [{start:#x}..{end:#x}) is not page-aligned (page size: {page_size:#x})")]
+    NotPageAligned {
+        start: usize,
+        end: usize,
+        page_size: usize,
+    },
+    #[error("Range [{start:#x}..{end:#x}) is not within a single region mapped into the sandbox")]
+    NotMapped { start: usize, end: usize },
+    #[error("Range [{start:#x}..{end:#x}) is not backed by writable memory")]
+    NotWritable { start: usize, end: usize },
+    #[error("Update region error: {0}")]
+    UpdateRegion(#[from] UpdateRegionError),
+}
+
 /// Errors that can occur when updating the scratch mapping
 #[derive(Debug, thiserror::Error)]
 pub enum UpdateRegionError {
@@ -352,12 +448,17 @@ pub enum HyperlightVmError {
     Initialize(#[from] InitializeError),
     #[error("Map region error: {0}")]
     MapRegion(#[from] MapRegionError),
+    #[cfg(feature = "hw-interrupts")]
+    #[error("Raise interrupt error: {0}")]
+    RaiseInterrupt(#[from] RaiseInterruptError),
     #[error("Restore VM (vcpu) error: {0}")]
     Restore(#[from] RegisterError),
     #[error("Unmap region error: {0}")]
     UnmapRegion(#[from] UnmapRegionError),
     #[error("Update region error: {0}")]
     UpdateRegion(#[from] UpdateRegionError),
+    #[error("Protect region error: {0}")]
+    ProtectRegion(#[from] ProtectRegionError),
     #[error("Access page table error: {0}")]
     AccessPageTable(#[from] AccessPageTableError),
 }
@@ -373,6 +474,7 @@ pub(crate) struct HyperlightVm {
     pub(super) vm: Box<dyn DebuggableVm>,
     #[cfg(not(gdb))]
     pub(super) vm: Box<dyn VirtualMachine>,
+    pub(super) backend: HypervisorBackend,
     pub(super) page_size: usize,
     pub(super) entrypoint: NextAction, // only present if this vm has not yet been initialised
     pub(super) rsp_gva: u64,
@@ -394,6 +496,18 @@ pub(crate) struct HyperlightVm {
 
     pub(super) pending_tlb_flush: bool,
 
+    /// The maximum number of host function calls a guest function call
+    /// may make, or 0 for no limit.
+    pub(super) max_host_calls_per_call: u64,
+    /// The number of host function calls made by the current guest
+    /// function call.
+    pub(super) host_calls: u64,
+    /// The exits of the current guest function call
+    pub(super) exit_stats: ExitStats,
+    /// Whether the guest has reported its ABI version, which it must do
+    /// before it finishes initialising.
+    pub(super) abi_version_reported: bool,
+
     #[cfg(gdb)]
     pub(super) gdb_conn: Option<DebugCommChannel<DebugResponse, DebugMsg>>,
     #[cfg(gdb)]
@@ -493,7 +607,7 @@ impl HyperlightVm {
         };
 
         // Safety: slots are unique. It's up to caller to ensure that the region is valid
-        unsafe { self.vm.map_memory((slot, region))? };
+        unsafe { self.map_slot(slot, region)? };
         self.mmap_regions.push((slot, region.clone()));
         Ok(())
     }
@@ -511,7 +625,121 @@ impl HyperlightVm {
 
         let (slot, _) = self.mmap_regions.remove(pos);
         self.freed_slots.push(slot);
-        self.vm.unmap_memory((slot, region))?;
+        self.unmap_slot(slot, region)?;
+        Ok(())
+    }
+
+    /// Map `region` into `slot` of the hypervisor. A region with no
+    /// access rights is left unmapped so that any access to it exits
+    /// and is reported as a memory access violation, as not every
+    /// hypervisor can remove read access from a mapping.
+    ///
+    /// # Safety
+    /// As for [`VirtualMachine::map_memory`].
+    unsafe fn map_slot(
+        &mut self,
+        slot: u32,
+        region: &MemoryRegion,
+    ) -> std::result::Result<(), MapMemoryError> {
+        if region.flags.is_empty() {
+            return Ok(());
+        }
+        unsafe { self.vm.map_memory((slot, region)) }
+    }
+
+    /// Unmap `region` from `slot` of the hypervisor, see [`Self::map_slot`].
+    fn unmap_slot(
+        &mut self,
+        slot: u32,
+        region: &MemoryRegion,
+    ) -> std::result::Result<(), UnmapMemoryError> {
+        if region.flags.is_empty() {
+            return Ok(());
+        }
+        self.vm.unmap_memory((slot, region))
+    }
+
+    /// Change the protection of the guest physical range `[gpa, gpa + len)`,
+    /// which must lie within a single dynamically mapped region.
+    ///
+    /// The containing region is split so that only the requested range
+    /// takes the new `flags`. Write access can only be kept, not granted,
+    /// since the host memory behind a read-only region may itself be
+    /// read-only. A range with no access rights is unmapped from the
+    /// hypervisor, see [`Self::map_slot`].
+    pub(crate) fn protect_region(
+        &mut self,
+        gpa: usize,
+        len: usize,
+        flags: MemoryRegionFlags,
+    ) -> std::result::Result<(), ProtectRegionError> {
+        let start = gpa;
+        let end = gpa.checked_add(len).ok_or(ProtectRegionError::NotMapped {
+            start,
+            end: usize::MAX,
+        })?;
+        if len == 0 || start % self.page_size != 0 || end % self.page_size != 0 {
+            return Err(ProtectRegionError::NotPageAligned {
+                start,
+                end,
+                page_size: self.page_size,
+            });
+        }
+
+        let pos = self
+            .mmap_regions
+            .iter()
+            .position(|(_, r)| r.guest_region.start <= start && end <= r.guest_region.end)
+            .ok_or(ProtectRegionError::NotMapped { start, end })?;
+        let region = &self.mmap_regions[pos].1;
+        if flags.contains(MemoryRegionFlags::WRITE)
+            && !region.flags.contains(MemoryRegionFlags::WRITE)
+        {
+            return Err(ProtectRegionError::NotWritable { start, end });
+        }
+        if region.flags == flags {
+            return Ok(());
+        }
+
+        let (slot, region) = self.mmap_regions.remove(pos);
+        self.unmap_slot(slot, &region)
+            .map_err(UpdateRegionError::from)?;
+
+        let sub_region = |from: usize, to: usize, flags: MemoryRegionFlags| MemoryRegion {
+            guest_region: from..to,
+            host_region: HostGuestMemoryRegion::add(
+                region.host_region.start,
+                from - region.guest_region.start,
+            )
+                ..HostGuestMemoryRegion::add(
+                    region.host_region.start,
+                    to - region.guest_region.start,
+                ),
+            flags,
+            region_type: region.region_type,
+        };
+        let pieces = [
+            sub_region(region.guest_region.start, start, region.flags),
+            sub_region(start, end, flags),
+            sub_region(end, region.guest_region.end, region.flags),
+        ];
+
+        // The first piece reuses the slot of the region being split
+        let mut slot = Some(slot);
+        for piece in pieces.into_iter().filter(|p| !p.guest_region.is_empty()) {
+            let piece_slot = slot.take().unwrap_or_else(|| {
+                self.freed_slots.pop().unwrap_or_else(|| {
+                    self.next_slot += 1;
+                    self.next_slot - 1
+                })
+            });
+            // Safety: the piece is a subrange of a region that was
+            // already mapped, so the caller of `map_region` has
+            // guaranteed that its host memory stays valid
+            unsafe { self.map_slot(piece_slot, &piece) }.map_err(UpdateRegionError::from)?;
+            self.mmap_regions.push((piece_slot, piece));
+        }
+
         Ok(())
     }
 
@@ -575,6 +803,21 @@ impl HyperlightVm {
         self.entrypoint = entrypoint
     }
 
+    /// The hypervisor backend this VM runs on
+    pub(crate) fn backend(&self) -> HypervisorBackend {
+        self.backend
+    }
+
+    /// Raise interrupt `vector` in the guest, which is delivered once
+    /// the guest next runs with interrupts enabled.
+    #[cfg(feature = "hw-interrupts")]
+    pub(crate) fn raise_interrupt(
+        &mut self,
+        vector: u8,
+    ) -> std::result::Result<(), RaiseInterruptError> {
+        self.vm.raise_interrupt(vector)
+    }
+
     pub(crate) fn interrupt_handle(&self) -> Arc<dyn InterruptHandle> {
         self.interrupt_handle.clone()
     }
@@ -612,10 +855,12 @@ impl HyperlightVm {
                 // ==== KILL() TIMING POINT 3: Before calling run() ====
                 // If kill() is called and ran to completion BEFORE this line executes:
                 //    - Will still do a VM entry, but signals will be sent until VM exits
+                let entered = Instant::now();
                 let result = self.vm.run_vcpu(
                     #[cfg(feature = "trace_guest")]
                     &mut tc,
                 );
+                self.exit_stats.add_guest_time(entered.elapsed());
 
                 // End current host trace by closing the current span that captures traces
                 // happening when a guest exits and re-enters.
@@ -654,6 +899,9 @@ impl HyperlightVm {
             let cancel_requested = self.interrupt_handle.is_cancelled();
             let debug_interrupted = self.interrupt_handle.is_debug_interrupted();
 
+            let exit = classify_exit(&exit_reason);
+            self.exit_stats.count_exit(exit);
+
             // ===== KILL() TIMING POINT 6: Before checking exit_reason =====
             // If kill() is called and ran to completion BEFORE this line executes:
             //    - CANCEL_BIT will be set. Cancellation is deferred to the next iteration.
@@ -692,7 +940,17 @@ impl HyperlightVm {
                     break Ok(());
                 }
                 Ok(VmExit::IoOut(port, data)) => {
-                    self.handle_io(mem_mgr, host_funcs, port, data)?;
+                    let handling = Instant::now();
+                    let handled = self.handle_io(
+                        mem_mgr,
+                        host_funcs,
+                        port,
+                        data,
+                        #[cfg(gdb)]
+                        &dbg_mem_access_fn,
+                    );
+                    self.exit_stats.add_exit_time(exit, handling.elapsed());
+                    handled?;
                 }
                 Ok(VmExit::MmioRead(addr)) => {
                     let all_regions = self.get_mapped_regions();
@@ -799,11 +1057,21 @@ impl HyperlightVm {
         host_funcs: &Arc<Mutex<FunctionRegistry>>,
         port: u16,
         data: Vec<u8>,
+        #[cfg(gdb)] dbg_mem_access_fn: &Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
     ) -> std::result::Result<(), HandleIoError> {
         if data.is_empty() {
             return Err(HandleIoError::NoData);
         }
 
+        if port == OutBAction::CallFunction as u16 {
+            return self.handle_host_function_call(
+                mem_mgr,
+                host_funcs,
+                #[cfg(gdb)]
+                dbg_mem_access_fn,
+            );
+        }
+
         #[allow(clippy::get_first)]
         let val = u32::from_le_bytes([
             data.get(0).copied().unwrap_or(0),
@@ -823,16 +1091,165 @@ impl HyperlightVm {
             handle_outb(mem_mgr, host_funcs, port, val)?;
         }
 
+        if port == OutBAction::AbiVersion as u16 {
+            self.abi_version_reported = true;
+        }
+
         Ok(())
     }
 }
 
+impl HyperlightVm {
+    /// The number of host function calls made by the last guest function
+    /// call dispatched from the host, including those made by reentrant
+    /// calls it triggered.
+    pub(crate) fn host_calls(&self) -> u64 {
+        self.host_calls
+    }
+
+    /// The exits of the last guest function call dispatched from the host,
+    /// including those of reentrant calls it triggered.
+    pub(crate) fn exit_stats(&self) -> &ExitStats {
+        &self.exit_stats
+    }
+
+    /// Handle a host function call from the guest, allowing the host
+    /// function to make reentrant guest calls on this VM.
+    fn handle_host_function_call(
+        &mut self,
+        mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        host_funcs: &Arc<Mutex<FunctionRegistry>>,
+        #[cfg(gdb)] dbg_mem_access_fn: &Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
+    ) -> std::result::Result<(), HandleIoError> {
+        self.host_calls += 1;
+        if self.max_host_calls_per_call != 0
+            && self.host_calls > self.max_host_calls_per_call
+            && matches!(self.entrypoint, NextAction::Call(_))
+        {
+            return Err(HandleOutbError::HostCallLimitExceeded {
+                limit: self.max_host_calls_per_call,
+            }
+            .into());
+        }
+
+        let mut failure = None;
+        handle_host_function_call(mem_mgr, |mem_mgr, name, args, return_type| {
+            if let Some(res) = host_stream::call_builtin(host_funcs, mem_mgr, name, &args)? {
+                return Ok(res);
+            }
+            let mut scope = NestedCallScope {
+                vm: self,
+                mem_mgr,
+                host_funcs,
+                #[cfg(gdb)]
+                dbg_mem_access_fn,
+                failure: &mut failure,
+            };
+            reentrant::enter(&mut scope, || {
+                call_host_function(host_funcs, name, args, return_type)
+            })
+        })?;
+
+        // A nested call that did not complete leaves the guest in an
+        // inconsistent state, so the outer call cannot continue either.
+        match failure {
+            Some(e) => Err(HandleIoError::ReentrantCallFailed(e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Makes reentrant guest calls on behalf of a host function called by the
+/// guest, see [`crate::sandbox::reentrant`].
+struct NestedCallScope<'a> {
+    vm: &'a mut HyperlightVm,
+    mem_mgr: &'a mut SandboxMemoryManager<HostSharedMemory>,
+    host_funcs: &'a Arc<Mutex<FunctionRegistry>>,
+    #[cfg(gdb)]
+    dbg_mem_access_fn: &'a Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
+    /// Set if a nested call failed without running to completion
+    failure: &'a mut Option<String>,
+}
+
+impl ReentrantCall for NestedCallScope<'_> {
+    fn call(
+        &mut self,
+        function_name: &str,
+        return_type: ReturnType,
+        args: Vec<ParameterValue>,
+    ) -> crate::Result<ReturnValue> {
+        if !matches!(self.vm.entrypoint, NextAction::Call(_)) {
+            return Err(HyperlightVmError::DispatchGuestCall(
+                DispatchGuestCallError::Uninitialized,
+            )
+            .into());
+        }
+
+        let estimated_capacity = estimate_flatbuffer_capacity(function_name, &args);
+        let fc = FunctionCall::new(
+            function_name.to_string(),
+            Some(args),
+            FunctionCallType::Guest,
+            return_type,
+        );
+        let mut builder = FlatBufferBuilder::with_capacity(estimated_capacity);
+        self.mem_mgr
+            .write_guest_function_call(fc.encode(&mut builder))?;
+
+        if let Err(e) = self.vm.dispatch_nested_call_from_host(
+            self.mem_mgr,
+            self.host_funcs,
+            #[cfg(gdb)]
+            self.dbg_mem_access_fn.clone(),
+        ) {
+            // A cancellation is still pending, so it is reported by the
+            // outer call's run loop instead.
+            let cancelled = matches!(
+                e,
+                DispatchGuestCallError::Run(RunVmError::ExecutionCancelledByHost)
+            );
+            if e.is_poison_error() && !cancelled {
+                *self.failure = Some(e.to_string());
+            }
+            return Err(e.promote().0);
+        }
+
+        self.mem_mgr
+            .get_guest_function_call_result()?
+            .into_inner()
+            .map_err(|e| HyperlightError::GuestError(e.code, e.message))
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    fn write_guest_memory(&mut self, gva: u64, data: &[u8]) -> crate::Result<()> {
+        let root_pt = self
+            .vm
+            .get_root_pt()
+            .map_err(|e| HyperlightError::HyperlightVmError(e.into()))?;
+        self.mem_mgr.write_guest_memory_by_gva(gva, data, root_pt)
+    }
+}
+
 impl Drop for HyperlightVm {
     fn drop(&mut self) {
         self.interrupt_handle.set_dropped();
     }
 }
 
+/// The reason counted in [`ExitStats`] for `exit`.
+fn classify_exit(exit: &std::result::Result<VmExit, RunVcpuError>) -> ExitReason {
+    match exit {
+        Ok(VmExit::IoOut(port, _)) if *port == OutBAction::CallFunction as u16 => {
+            ExitReason::HostCall
+        }
+        Ok(VmExit::IoOut(..)) => ExitReason::Io,
+        Ok(VmExit::MmioRead(_) | VmExit::MmioWrite(_)) => ExitReason::Mmio,
+        Ok(VmExit::Halt()) => ExitReason::Halt,
+        Ok(VmExit::Cancelled()) => ExitReason::Cancelled,
+        _ => ExitReason::Other,
+    }
+}
+
 /// The vCPU tried to access the given addr
 enum MemoryAccess {
     /// The accessed region has the given flags
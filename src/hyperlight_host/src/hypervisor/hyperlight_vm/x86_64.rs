@@ -25,6 +25,7 @@ use std::sync::atomic::AtomicU8;
 use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex};
 
+use hyperlight_common::outb::ABI_VERSION;
 use tracing::{Span, instrument};
 use tracing_core::LevelFilter;
 
@@ -62,6 +63,7 @@ use crate::mem::mgr::SandboxMemoryManager;
 use crate::mem::ptr::RawPtr;
 use crate::mem::shared_mem::{GuestSharedMemory, HostSharedMemory};
 use crate::sandbox::SandboxConfiguration;
+use crate::sandbox::exit_stats::ExitStats;
 use crate::sandbox::host_funcs::FunctionRegistry;
 use crate::sandbox::snapshot::NextAction;
 #[cfg(feature = "mem_profile")]
@@ -80,7 +82,7 @@ impl HyperlightVm {
         entrypoint: NextAction,
         rsp_gva: u64,
         page_size: usize,
-        #[cfg_attr(target_os = "windows", allow(unused_variables))] config: &SandboxConfiguration,
+        config: &SandboxConfiguration,
         #[cfg(gdb)] gdb_conn: Option<DebugCommChannel<DebugResponse, DebugMsg>>,
         #[cfg(crashdump)] rt_cfg: SandboxRuntimeConfig,
         #[cfg(feature = "mem_profile")] trace_info: MemTraceInfo,
@@ -90,14 +92,16 @@ impl HyperlightVm {
         #[cfg(not(gdb))]
         type VmType = Box<dyn VirtualMachine>;
 
-        let vm: VmType = match get_available_hypervisor() {
+        let Some(hypervisor_type) = *get_available_hypervisor() else {
+            return Err(CreateHyperlightVmError::NoHypervisorFound);
+        };
+        let vm: VmType = match hypervisor_type {
             #[cfg(kvm)]
-            Some(HypervisorType::Kvm) => Box::new(KvmVm::new().map_err(VmError::CreateVm)?),
+            HypervisorType::Kvm => Box::new(KvmVm::new().map_err(VmError::CreateVm)?),
             #[cfg(mshv3)]
-            Some(HypervisorType::Mshv) => Box::new(MshvVm::new().map_err(VmError::CreateVm)?),
+            HypervisorType::Mshv => Box::new(MshvVm::new().map_err(VmError::CreateVm)?),
             #[cfg(target_os = "windows")]
-            Some(HypervisorType::Whp) => Box::new(WhpVm::new().map_err(VmError::CreateVm)?),
-            None => return Err(CreateHyperlightVmError::NoHypervisorFound),
+            HypervisorType::Whp => Box::new(WhpVm::new().map_err(VmError::CreateVm)?),
         };
 
         #[cfg(not(feature = "i686-guest"))]
@@ -147,6 +151,7 @@ impl HyperlightVm {
         #[cfg_attr(not(gdb), allow(unused_mut))]
         let mut ret = Self {
             vm,
+            backend: hypervisor_type.into(),
             entrypoint,
             rsp_gva,
             interrupt_handle,
@@ -164,6 +169,11 @@ impl HyperlightVm {
 
             pending_tlb_flush: false,
 
+            max_host_calls_per_call: config.get_max_host_calls_per_call(),
+            host_calls: 0,
+            exit_stats: ExitStats::default(),
+            abi_version_reported: false,
+
             #[cfg(gdb)]
             gdb_conn,
             #[cfg(gdb)]
@@ -252,6 +262,12 @@ impl HyperlightVm {
         )
         .map_err(InitializeError::Run)?;
 
+        // A mismatched version fails the run above, but a guest that never
+        // reports one would otherwise be accepted.
+        if !self.abi_version_reported {
+            return Err(InitializeError::AbiVersionMissing { host: ABI_VERSION });
+        }
+
         let regs = self.vm.regs()?;
         // todo(portability): this is architecture-specific
         if !regs.rsp.is_multiple_of(16) {
@@ -263,6 +279,11 @@ impl HyperlightVm {
         Ok(())
     }
 
+    /// Get the vCPU's general purpose registers, as it last stopped.
+    pub(crate) fn regs(&self) -> Result<CommonRegisters, RegisterError> {
+        self.vm.regs()
+    }
+
     /// Get the current base page table physical address from CR3.
     pub(crate) fn get_root_pt(&self) -> Result<u64, AccessPageTableError> {
         let sregs = self.vm.sregs()?;
@@ -323,6 +344,8 @@ impl HyperlightVm {
             .set_fpu(&CommonFpu::default())
             .map_err(DispatchGuestCallError::SetupRegs)?;
 
+        self.host_calls = 0;
+        self.exit_stats = ExitStats::default();
         let result = self
             .run(
                 mem_mgr,
@@ -339,6 +362,105 @@ impl HyperlightVm {
         result
     }
 
+    /// Dispatch a guest function call while the guest is blocked in a
+    /// host function call, see [`crate::sandbox::reentrant`].
+    ///
+    /// The nested call runs on the guest stack below the interrupted
+    /// frame, and the interrupted registers are restored afterwards so
+    /// that the guest resumes after its host function call.
+    pub(crate) fn dispatch_nested_call_from_host(
+        &mut self,
+        mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        host_funcs: &Arc<Mutex<FunctionRegistry>>,
+        #[cfg(gdb)] dbg_mem_access_fn: Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
+    ) -> std::result::Result<(), DispatchGuestCallError> {
+        // The System V red zone below the stack pointer of the interrupted frame
+        const RED_ZONE_SIZE: u64 = 128;
+
+        let NextAction::Call(dispatch_func_addr) = self.entrypoint else {
+            return Err(DispatchGuestCallError::Uninitialized);
+        };
+        let saved_regs = self
+            .vm
+            .regs_after_io_exit()
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+        let saved_fpu = self.vm.fpu().map_err(DispatchGuestCallError::SetupRegs)?;
+
+        let regs = CommonRegisters {
+            rip: dispatch_func_addr,
+            // 16-byte aligned as for dispatch_call_from_host
+            rsp: (saved_regs.rsp - RED_ZONE_SIZE) & !0xf,
+            // ZF clear: the outer call has already flushed the TLB
+            rflags: 1 << 1,
+            ..Default::default()
+        };
+        self.vm
+            .set_regs(&regs)
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+        self.vm
+            .set_fpu(&CommonFpu::default())
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+
+        let result = self.run(
+            mem_mgr,
+            host_funcs,
+            #[cfg(gdb)]
+            dbg_mem_access_fn,
+        );
+
+        // Restore the interrupted call even if the nested call failed, so
+        // that a pending cancellation is reported by the outer call.
+        self.vm
+            .set_regs(&saved_regs)
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+        self.vm
+            .set_fpu(&saved_fpu)
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+
+        result.map_err(DispatchGuestCallError::Run)
+    }
+
+    /// Run the guest from `regs.rip` with `regs` until it halts, and return
+    /// the registers it halted with, see
+    /// [`crate::MultiUseSandbox::run_at`]. A stack pointer of 0 is replaced
+    /// by the top of the guest's stack.
+    pub(crate) fn run_at(
+        &mut self,
+        regs: &CommonRegisters,
+        mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        host_funcs: &Arc<Mutex<FunctionRegistry>>,
+        #[cfg(gdb)] dbg_mem_access_fn: Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
+    ) -> std::result::Result<CommonRegisters, DispatchGuestCallError> {
+        let regs = CommonRegisters {
+            rsp: if regs.rsp == 0 {
+                self.rsp_gva
+            } else {
+                regs.rsp
+            },
+            rflags: regs.rflags | 1 << 1, // RFLAGS.1 is RES1
+            ..*regs
+        };
+        self.vm
+            .set_regs(&regs)
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+        self.vm
+            .set_fpu(&CommonFpu::default())
+            .map_err(DispatchGuestCallError::SetupRegs)?;
+
+        // The TLB flush that may be pending is left to the next call made
+        // through the dispatch function, which is what performs it
+        self.host_calls = 0;
+        self.run(
+            mem_mgr,
+            host_funcs,
+            #[cfg(gdb)]
+            dbg_mem_access_fn,
+        )
+        .map_err(DispatchGuestCallError::Run)?;
+
+        self.vm.regs().map_err(DispatchGuestCallError::SetupRegs)
+    }
+
     /// Resets the following vCPU state:
     /// - General purpose registers
     /// - Debug registers
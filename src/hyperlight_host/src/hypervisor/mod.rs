@@ -22,6 +22,7 @@ pub(crate) mod gdb;
 pub(crate) mod regs;
 
 pub(crate) mod virtual_machine;
+pub use virtual_machine::{HypervisorBackend, HypervisorCapabilities};
 
 #[cfg(target_os = "windows")]
 /// Hyperlight Surrogate Process
@@ -476,6 +477,7 @@ pub(crate) mod tests {
             return Ok(());
         }
 
+        use crate::hypervisor::hyperlight_vm::InitializeError;
         use crate::mem::ptr::RawPtr;
         use crate::sandbox::host_funcs::FunctionRegistry;
 
@@ -510,8 +512,9 @@ pub(crate) mod tests {
         #[cfg(gdb)]
         let dbg_mem_access_fn = Arc::new(Mutex::new(mem_mgr.clone()));
 
-        // Test the initialise method
-        vm.initialise(
+        // Test the initialise method. The dummy guest runs to completion
+        // but never reports its ABI version, so it is rejected.
+        let res = vm.initialise(
             peb_addr,
             seed,
             page_size,
@@ -520,8 +523,12 @@ pub(crate) mod tests {
             guest_max_log_level,
             #[cfg(gdb)]
             dbg_mem_access_fn,
-        )
-        .unwrap();
+        );
+        assert!(
+            matches!(res, Err(InitializeError::AbiVersionMissing { .. })),
+            "{:?}",
+            res
+        );
 
         Ok(())
     }
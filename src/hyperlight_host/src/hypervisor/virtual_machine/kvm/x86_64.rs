@@ -36,6 +36,8 @@ use crate::hypervisor::regs::{
     CommonDebugRegs, CommonFpu, CommonRegisters, CommonSpecialRegisters, FP_CONTROL_WORD_DEFAULT,
     MXCSR_DEFAULT,
 };
+#[cfg(feature = "hw-interrupts")]
+use crate::hypervisor::virtual_machine::RaiseInterruptError;
 #[cfg(all(test, not(feature = "i686-guest")))]
 use crate::hypervisor::virtual_machine::XSAVE_BUFFER_SIZE;
 #[cfg(feature = "hw-interrupts")]
@@ -343,6 +345,21 @@ impl VirtualMachine for KvmVm {
         Ok((&kvm_regs).into())
     }
 
+    fn regs_after_io_exit(&mut self) -> std::result::Result<CommonRegisters, RegisterError> {
+        // KVM only steps over an IO instruction on the next KVM_RUN. With
+        // immediate_exit set, KVM_RUN completes it and returns EINTR
+        // without entering the guest.
+        self.vcpu_fd.set_kvm_immediate_exit(1);
+        let result = self.vcpu_fd.run().map(|_| ());
+        self.vcpu_fd.set_kvm_immediate_exit(0);
+        if let Err(e) = result
+            && e.errno() != libc::EINTR
+        {
+            return Err(RegisterError::GetRegs(e.into()));
+        }
+        self.regs()
+    }
+
     fn set_regs(&self, regs: &CommonRegisters) -> std::result::Result<(), RegisterError> {
         let kvm_regs: kvm_regs = regs.into();
         self.vcpu_fd
@@ -468,6 +485,46 @@ impl VirtualMachine for KvmVm {
 
         Ok(())
     }
+
+    #[cfg(feature = "hw-interrupts")]
+    fn raise_interrupt(&mut self, vector: u8) -> std::result::Result<(), RaiseInterruptError> {
+        use super::super::x86_64::hw_interrupts::{
+            RAISABLE_VECTORS, clear_in_service, read_lapic_u32, write_lapic_u32,
+        };
+
+        let mut lapic = self
+            .vcpu_fd
+            .get_lapic()
+            .map_err(|e| RaiseInterruptError::AccessLapic(e.into()))?;
+        // Safety: c_char (i8) and u8 have the same size and alignment;
+        // LAPIC register values are treated as raw bytes.
+        let regs = unsafe {
+            &mut *(&mut lapic.regs as *mut [::std::os::raw::c_char; 1024] as *mut [u8; 1024])
+        };
+        // The guest acknowledges interrupts through the PIC, which is
+        // in-kernel and never forwards the EOI to the LAPIC. The vCPU is
+        // not running, so no handler for a raised vector is in progress.
+        clear_in_service(regs, RAISABLE_VECTORS);
+        // Fixed interrupts are dropped while the LAPIC is software
+        // disabled, which it is after reset (SVR bit 8 clear).
+        let svr = read_lapic_u32(regs, 0xF0);
+        write_lapic_u32(regs, 0xF0, svr | 0x100);
+        self.vcpu_fd
+            .set_lapic(&lapic)
+            .map_err(|e| RaiseInterruptError::AccessLapic(e.into()))?;
+
+        // An MSI to the default LAPIC address delivers a fixed,
+        // edge-triggered interrupt to APIC ID 0.
+        let msi = kvm_bindings::kvm_msi {
+            address_lo: 0xFEE0_0000,
+            data: vector.into(),
+            ..Default::default()
+        };
+        self.vm_fd
+            .signal_msi(msi)
+            .map_err(|e| RaiseInterruptError::RequestInterrupt(e.into()))?;
+        Ok(())
+    }
 }
 
 #[cfg(gdb)]
@@ -104,6 +104,118 @@ pub(crate) enum HypervisorType {
     Whp,
 }
 
+impl From<HypervisorType> for HypervisorBackend {
+    fn from(hypervisor_type: HypervisorType) -> Self {
+        match hypervisor_type {
+            #[cfg(kvm)]
+            HypervisorType::Kvm => HypervisorBackend::Kvm,
+            #[cfg(mshv3)]
+            HypervisorType::Mshv => HypervisorBackend::Mshv,
+            #[cfg(target_os = "windows")]
+            HypervisorType::Whp => HypervisorBackend::Whp,
+        }
+    }
+}
+
+/// The hypervisor backend a sandbox runs on, see
+/// [`MultiUseSandbox::hypervisor_backend`](crate::MultiUseSandbox::hypervisor_backend).
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum HypervisorBackend {
+    /// KVM (Kernel-based Virtual Machine) on Linux
+    Kvm,
+    /// MSHV (Microsoft Hypervisor) on Linux
+    Mshv,
+    /// WHP (Windows Hypervisor Platform) on Windows
+    Whp,
+}
+
+impl HypervisorBackend {
+    /// The optional features that sandboxes running on this backend support
+    /// in this build of Hyperlight.
+    pub fn capabilities(self) -> HypervisorCapabilities {
+        let features = HypervisorCapabilities {
+            dirty_page_tracking: false,
+            execute_protection: true,
+            guest_debugging: cfg!(gdb),
+            crash_dumps: cfg!(crashdump),
+            hardware_interrupts: cfg!(feature = "hw-interrupts"),
+        };
+        match self {
+            // KVM memory slots are always executable
+            HypervisorBackend::Kvm => HypervisorCapabilities {
+                execute_protection: false,
+                ..features
+            },
+            HypervisorBackend::Mshv | HypervisorBackend::Whp => features,
+        }
+    }
+
+    /// Returns [`HyperlightError::Unsupported`](crate::HyperlightError::Unsupported)
+    /// for `feature` unless `supported` holds for this backend's capabilities.
+    pub(crate) fn require(
+        self,
+        feature: &str,
+        supported: impl FnOnce(&HypervisorCapabilities) -> bool,
+    ) -> crate::Result<()> {
+        if supported(&self.capabilities()) {
+            Ok(())
+        } else {
+            Err(crate::HyperlightError::Unsupported {
+                feature: feature.to_string(),
+                backend: self,
+            })
+        }
+    }
+}
+
+impl std::fmt::Display for HypervisorBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HypervisorBackend::Kvm => write!(f, "KVM"),
+            HypervisorBackend::Mshv => write!(f, "MSHV"),
+            HypervisorBackend::Whp => write!(f, "WHP"),
+        }
+    }
+}
+
+/// The optional features supported by a [`HypervisorBackend`].
+///
+/// APIs that rely on one of these features return
+/// [`HyperlightError::Unsupported`](crate::HyperlightError::Unsupported)
+/// when the backend does not support it, rather than silently doing
+/// nothing, so callers that need to stay portable should check the
+/// capabilities first. Features that depend on cargo features are only
+/// reported as supported when Hyperlight was built with them.
+///
+/// | Capability            | KVM | MSHV | WHP |
+/// |-----------------------|-----|------|-----|
+/// | `dirty_page_tracking` | no  | no   | no  |
+/// | `execute_protection`  | no  | yes  | yes |
+/// | `guest_debugging`     | `gdb` feature | `gdb` feature | `gdb` feature |
+/// | `crash_dumps`         | `crashdump` feature | `crashdump` feature | `crashdump` feature |
+/// | `hardware_interrupts` | `hw-interrupts` feature | `hw-interrupts` feature | `hw-interrupts` feature |
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct HypervisorCapabilities {
+    /// Whether snapshots can be taken by copying only the guest pages
+    /// written since the last snapshot. No backend supports this yet.
+    pub dirty_page_tracking: bool,
+    /// Whether mapped memory can be made non-executable while staying
+    /// readable, see [`MultiUseSandbox::protect_region`](crate::MultiUseSandbox::protect_region).
+    pub execute_protection: bool,
+    /// Whether guests can be debugged with gdb, which needs the `gdb`
+    /// feature and a debug build.
+    pub guest_debugging: bool,
+    /// Whether the VM state is dumped to a file when a guest crashes, which
+    /// needs the `crashdump` feature.
+    pub crash_dumps: bool,
+    /// Whether guests can receive hardware timer interrupts and interrupts
+    /// raised with [`MultiUseSandbox::raise_guest_interrupt`](crate::MultiUseSandbox::raise_guest_interrupt),
+    /// which needs the `hw-interrupts` feature.
+    pub hardware_interrupts: bool,
+}
+
 /// Minimum XSAVE buffer size: 512 bytes legacy region + 64 bytes header.
 /// Only used by MSHV and WHP which use compacted XSAVE format and need to
 /// validate buffer size before accessing XCOMP_BV.
@@ -278,6 +390,16 @@ pub enum UnmapMemoryError {
     Hypervisor(HypervisorError),
 }
 
+/// Raise interrupt error
+#[cfg(feature = "hw-interrupts")]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum RaiseInterruptError {
+    #[error("Failed to access the local APIC: {0}")]
+    AccessLapic(HypervisorError),
+    #[error("Failed to request the interrupt: {0}")]
+    RequestInterrupt(HypervisorError),
+}
+
 /// Implementation-specific Hypervisor error
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum HypervisorError {
@@ -325,6 +447,14 @@ pub(crate) trait VirtualMachine: Debug + Send {
     /// Get regs
     #[allow(dead_code)]
     fn regs(&self) -> std::result::Result<CommonRegisters, RegisterError>;
+    /// Get regs as they will be once the instruction that caused the last
+    /// [`VmExit::IoOut`] has completed.
+    ///
+    /// Some hypervisors only complete the instruction when the vCPU is next
+    /// run, in which case [`regs`](Self::regs) still points at it.
+    fn regs_after_io_exit(&mut self) -> std::result::Result<CommonRegisters, RegisterError> {
+        self.regs()
+    }
     /// Set regs
     fn set_regs(&self, regs: &CommonRegisters) -> std::result::Result<(), RegisterError>;
     /// Get fpu regs
@@ -353,6 +483,12 @@ pub(crate) trait VirtualMachine: Debug + Send {
     #[cfg(not(feature = "i686-guest"))]
     fn set_xsave(&self, xsave: &[u32]) -> std::result::Result<(), RegisterError>;
 
+    /// Request that `vector` is delivered to the vCPU through its local
+    /// APIC. Must not be called while the vCPU is running. The interrupt
+    /// stays pending until the guest runs with interrupts enabled.
+    #[cfg(feature = "hw-interrupts")]
+    fn raise_interrupt(&mut self, vector: u8) -> std::result::Result<(), RaiseInterruptError>;
+
     /// Get partition handle
     #[cfg(target_os = "windows")]
     fn partition_handle(&self) -> windows::Win32::System::Hypervisor::WHV_PARTITION_HANDLE;
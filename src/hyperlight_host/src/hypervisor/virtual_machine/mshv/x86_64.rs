@@ -52,6 +52,8 @@ use crate::hypervisor::regs::{
     CommonDebugRegs, CommonFpu, CommonRegisters, CommonSpecialRegisters, FP_CONTROL_WORD_DEFAULT,
     MXCSR_DEFAULT,
 };
+#[cfg(feature = "hw-interrupts")]
+use crate::hypervisor::virtual_machine::RaiseInterruptError;
 #[cfg(all(test, not(feature = "i686-guest")))]
 use crate::hypervisor::virtual_machine::XSAVE_BUFFER_SIZE;
 #[cfg(feature = "hw-interrupts")]
@@ -90,6 +92,10 @@ pub(crate) struct MshvVm {
     /// Handle to the background timer (if started).
     #[cfg(feature = "hw-interrupts")]
     timer: Option<TimerThread>,
+    /// Whether the host has raised an interrupt, whose EOI the guest
+    /// sends to the PIC like the timer's.
+    #[cfg(feature = "hw-interrupts")]
+    interrupt_raised: bool,
 }
 
 static MSHV: LazyLock<std::result::Result<Mshv, CreateVmError>> =
@@ -149,6 +155,8 @@ impl MshvVm {
             vcpu_fd,
             #[cfg(feature = "hw-interrupts")]
             timer: None,
+            #[cfg(feature = "hw-interrupts")]
+            interrupt_raised: false,
         })
     }
 }
@@ -500,6 +508,22 @@ impl VirtualMachine for MshvVm {
             .map_err(|e| RegisterError::SetXsave(e.into()))?;
         Ok(())
     }
+
+    #[cfg(feature = "hw-interrupts")]
+    fn raise_interrupt(&mut self, vector: u8) -> std::result::Result<(), RaiseInterruptError> {
+        self.vm_fd
+            .request_virtual_interrupt(&InterruptRequest {
+                interrupt_type: hv_interrupt_type_HV_X64_INTERRUPT_TYPE_FIXED,
+                apic_id: 0,
+                vector: vector.into(),
+                level_triggered: false,
+                logical_destination_mode: false,
+                long_mode: false,
+            })
+            .map_err(|e| RaiseInterruptError::RequestInterrupt(e.into()))?;
+        self.interrupt_raised = true;
+        Ok(())
+    }
 }
 
 #[cfg(gdb)]
@@ -722,7 +746,8 @@ impl MshvVm {
             );
             return true;
         }
-        let timer_active = self.timer.as_ref().is_some_and(|t| t.is_active());
+        let timer_active =
+            self.timer.as_ref().is_some_and(|t| t.is_active()) || self.interrupt_raised;
         super::super::x86_64::hw_interrupts::handle_common_io_out(port, data, timer_active, || {
             self.do_lapic_eoi()
         })
@@ -39,6 +39,8 @@ use crate::hypervisor::regs::{
 use crate::hypervisor::surrogate_process::SurrogateProcess;
 use crate::hypervisor::surrogate_process_manager::get_surrogate_process_manager;
 #[cfg(feature = "hw-interrupts")]
+use crate::hypervisor::virtual_machine::RaiseInterruptError;
+#[cfg(feature = "hw-interrupts")]
 use crate::hypervisor::virtual_machine::x86_64::hw_interrupts::TimerThread;
 use crate::hypervisor::virtual_machine::{
     CreateVmError, HypervisorError, MapMemoryError, RegisterError, RunVcpuError, UnmapMemoryError,
@@ -99,6 +101,10 @@ pub(crate) struct WhpVm {
     /// Handle to the background timer (if started).
     #[cfg(feature = "hw-interrupts")]
     timer: Option<TimerThread>,
+    /// Whether the host has raised an interrupt, whose EOI the guest
+    /// sends to the PIC like the timer's.
+    #[cfg(feature = "hw-interrupts")]
+    interrupt_raised: bool,
 }
 
 // Safety: `WhpVm` is !Send because it holds `SurrogateProcess` which contains a raw pointer
@@ -155,6 +161,8 @@ impl WhpVm {
             file_mappings: Vec::new(),
             #[cfg(feature = "hw-interrupts")]
             timer: None,
+            #[cfg(feature = "hw-interrupts")]
+            interrupt_raised: false,
         })
     }
 
@@ -799,6 +807,25 @@ impl VirtualMachine for WhpVm {
         Ok(())
     }
 
+    #[cfg(feature = "hw-interrupts")]
+    fn raise_interrupt(&mut self, vector: u8) -> Result<(), RaiseInterruptError> {
+        let interrupt = WHV_INTERRUPT_CONTROL {
+            _bitfield: 0, // Type=Fixed, DestMode=Physical, Trigger=Edge
+            Destination: 0,
+            Vector: vector.into(),
+        };
+        unsafe {
+            WHvRequestInterrupt(
+                self.partition,
+                &interrupt,
+                std::mem::size_of::<WHV_INTERRUPT_CONTROL>() as u32,
+            )
+            .map_err(|e| RaiseInterruptError::RequestInterrupt(e.into()))?;
+        }
+        self.interrupt_raised = true;
+        Ok(())
+    }
+
     /// Get the partition handle for this VM
     fn partition_handle(&self) -> WHV_PARTITION_HANDLE {
         self.partition
@@ -1096,7 +1123,8 @@ impl WhpVm {
             );
             return true;
         }
-        let timer_active = self.timer.as_ref().is_some_and(|t| t.is_active());
+        let timer_active =
+            self.timer.as_ref().is_some_and(|t| t.is_active()) || self.interrupt_raised;
         super::x86_64::hw_interrupts::handle_common_io_out(port, data, timer_active, || {
             self.do_lapic_eoi()
         })
@@ -23,6 +23,13 @@ limitations under the License.
 /// — no PIC state machine needed.
 pub(crate) const TIMER_VECTOR: u32 = 0x20;
 
+/// The vectors the host can raise in the guest, see
+/// [`MultiUseSandbox::raise_guest_interrupt`](crate::MultiUseSandbox::raise_guest_interrupt).
+/// Vectors below 0x20 are CPU exceptions, 0x20-0x2F are where the guest
+/// remaps the legacy PIC (so the timer uses 0x20), and 0xFF is the
+/// LAPIC spurious vector.
+pub(crate) const RAISABLE_VECTORS: std::ops::RangeInclusive<u8> = 0x30..=0xFE;
+
 /// Minimum allowed timer period in microseconds (100 µs).
 /// Prevents runaway interrupt injection from a malicious or buggy guest.
 pub(crate) const MIN_TIMER_PERIOD_US: u64 = 100;
@@ -154,6 +161,18 @@ pub(crate) fn lapic_eoi(state: &mut [u8]) {
     }
 }
 
+/// Clear the in-service bits of every vector in `vectors`.
+///
+/// The ISR is at LAPIC offset 0x100, organized as 8 × 32-bit words
+/// (one per 16 bytes), with vector `v` at bit `v % 32` of word `v / 32`.
+pub(crate) fn clear_in_service(state: &mut [u8], vectors: std::ops::RangeInclusive<u8>) {
+    for vector in vectors {
+        let offset = 0x100 + (vector as usize / 32) * 0x10;
+        let isr_val = read_lapic_u32(state, offset);
+        write_lapic_u32(state, offset, isr_val & !(1u32 << (vector % 32)));
+    }
+}
+
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::JoinHandle;
@@ -298,6 +317,17 @@ mod tests {
         assert_eq!(read_lapic_u32(&state, 0x100), 0b11);
     }
 
+    #[test]
+    fn clear_in_service_only_clears_given_vectors() {
+        let mut state = vec![0u8; 1024];
+        // Vector 0x20 (timer) is in ISR[1], 0x31 in ISR[1], 0xFE in ISR[7]
+        write_lapic_u32(&mut state, 0x110, (1 << 0) | (1 << 17));
+        write_lapic_u32(&mut state, 0x170, 1 << 30);
+        clear_in_service(&mut state, RAISABLE_VECTORS);
+        assert_eq!(read_lapic_u32(&state, 0x110), 1 << 0);
+        assert_eq!(read_lapic_u32(&state, 0x170), 0);
+    }
+
     #[test]
     fn init_lapic_registers_sets_svr() {
         let mut state = vec![0u8; 1024];
@@ -49,6 +49,8 @@ use std::sync::Once;
 pub(crate) mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
+/// Measuring the latency and throughput of guest function calls
+pub mod bench;
 /// Dealing with errors, including errors across VM boundaries
 pub mod error;
 /// Wrappers for host and guest functions.
@@ -71,6 +73,9 @@ pub mod hypervisor;
 pub mod mem;
 /// Metric definitions and helpers
 pub mod metrics;
+/// Exporting guest function calls as OpenTelemetry spans
+#[cfg(feature = "otel")]
+pub mod otel;
 /// The main sandbox implementations. Do not use this module directly in code
 /// outside this file. Types from this module needed for public consumption are
 /// re-exported below.
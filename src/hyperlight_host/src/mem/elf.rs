@@ -28,6 +28,7 @@ use goblin::elf32::program_header::PT_LOAD;
 use goblin::elf64::program_header::PT_LOAD;
 
 use super::exe::LoadInfo;
+use super::mapped_file::MappedFile;
 use crate::{Result, log_then_return, new_error};
 
 #[cfg(feature = "mem_profile")]
@@ -38,8 +39,26 @@ struct ResolvedSectionHeader {
     size: u64,
 }
 
+/// The contents of an ELF file, either copied into memory or mapped from
+/// disk.
+enum ElfPayload {
+    Owned(Vec<u8>),
+    Mapped(MappedFile),
+}
+
+impl std::ops::Deref for ElfPayload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ElfPayload::Owned(bytes) => bytes,
+            ElfPayload::Mapped(file) => file,
+        }
+    }
+}
+
 pub(crate) struct ElfInfo {
-    payload: Vec<u8>,
+    payload: ElfPayload,
     phdrs: ProgramHeaders,
     #[cfg(feature = "mem_profile")]
     shdrs: Vec<ResolvedSectionHeader>,
@@ -114,6 +133,19 @@ impl framehop::ModuleSectionInfo<Vec<u8>> for &UnwindInfo {
 
 impl ElfInfo {
     pub(crate) fn new(bytes: &[u8]) -> Result<Self> {
+        Self::from_payload(ElfPayload::Owned(bytes.to_vec()))
+    }
+
+    /// Parses an ELF file mapped from disk without reading all of it into
+    /// memory. Only the parts the loader needs, such as its headers, symbol
+    /// tables and `PT_LOAD` segments, are read, so sections like debug
+    /// information never are.
+    pub(crate) fn from_mapped(file: MappedFile) -> Result<Self> {
+        Self::from_payload(ElfPayload::Mapped(file))
+    }
+
+    fn from_payload(payload: ElfPayload) -> Result<Self> {
+        let bytes = &*payload;
         let elf = Elf::parse(bytes)?;
         let relocs = elf.dynrels.iter().chain(elf.dynrelas.iter()).collect();
         if !elf
@@ -128,23 +160,28 @@ impl ElfInfo {
         // hyperlight-guest-bin.
         let guest_bin_version = Self::read_version_note(&elf, bytes);
 
+        let phdrs = elf.program_headers;
+        #[cfg(feature = "mem_profile")]
+        let shdrs = elf
+            .section_headers
+            .iter()
+            .filter_map(|sh| {
+                Some(ResolvedSectionHeader {
+                    name: elf.shdr_strtab.get_at(sh.sh_name)?.to_string(),
+                    addr: sh.sh_addr,
+                    offset: sh.sh_offset,
+                    size: sh.sh_size,
+                })
+            })
+            .collect();
+        let entry = elf.entry;
+
         Ok(ElfInfo {
-            payload: bytes.to_vec(),
-            phdrs: elf.program_headers,
+            payload,
+            phdrs,
             #[cfg(feature = "mem_profile")]
-            shdrs: elf
-                .section_headers
-                .iter()
-                .filter_map(|sh| {
-                    Some(ResolvedSectionHeader {
-                        name: elf.shdr_strtab.get_at(sh.sh_name)?.to_string(),
-                        addr: sh.sh_addr,
-                        offset: sh.sh_offset,
-                        size: sh.sh_size,
-                    })
-                })
-                .collect(),
-            entry: elf.entry,
+            shdrs,
+            entry,
             relocs,
             guest_bin_version,
         })
@@ -198,6 +235,9 @@ impl ElfInfo {
     }
     pub(crate) fn load_at(self, load_addr: usize, target: &mut [u8]) -> Result<LoadInfo> {
         let base_va = self.get_base_va();
+        // Segments are copied out of the payload before relocations are
+        // applied, so a mapped file is never written to, even for writable
+        // segments.
         for phdr in self.phdrs.iter().filter(|phdr| phdr.p_type == PT_LOAD) {
             let start_va = (phdr.p_vaddr - base_va) as usize;
             let payload_offset = phdr.p_offset as usize;
@@ -242,7 +282,10 @@ impl ElfInfo {
                 let base_svma = self.get_base_va();
                 Ok(LoadInfo {
                     info: Arc::new(UnwindInfo {
-                        payload: self.payload,
+                        payload: match self.payload {
+                            ElfPayload::Owned(bytes) => bytes,
+                            ElfPayload::Mapped(file) => file.to_vec(),
+                        },
                         load_addr: load_addr as u64,
                         va_size,
                         base_svma,
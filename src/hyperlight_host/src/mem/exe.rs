@@ -16,11 +16,13 @@ limitations under the License.
 
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 #[cfg(feature = "mem_profile")]
 use std::sync::Arc;
 use std::vec::Vec;
 
 use super::elf::ElfInfo;
+use super::mapped_file::MappedFile;
 use super::ptr_offset::Offset;
 use crate::Result;
 
@@ -80,6 +82,12 @@ impl ExeInfo {
         file.read_to_end(&mut contents)?;
         Self::from_buf(&contents)
     }
+    /// Load the executable at `path` by mapping it, rather than reading
+    /// it into memory, see [`ElfInfo::from_mapped`].
+    pub fn from_mapped_file(path: &str) -> Result<Self> {
+        let file = MappedFile::open(Path::new(path))?;
+        ElfInfo::from_mapped(file).map(ExeInfo::Elf)
+    }
     pub fn from_buf(buf: &[u8]) -> Result<Self> {
         ElfInfo::new(buf).map(ExeInfo::Elf)
     }
@@ -169,6 +177,22 @@ mod tests {
         assert_eq!(version, env!("CARGO_PKG_VERSION"));
     }
 
+    #[test]
+    fn mapped_file_loads_like_buffer() {
+        let path = simple_guest_as_string().expect("failed to locate simpleguest");
+        let mapped = ExeInfo::from_mapped_file(&path).expect("failed to map ELF");
+        let read = ExeInfo::from_file(&path).expect("failed to load ELF");
+        assert_eq!(mapped.loaded_size(), read.loaded_size());
+        assert_eq!(mapped.base_va(), read.base_va());
+        assert_eq!(mapped.guest_bin_version(), read.guest_bin_version());
+
+        let mut mapped_target = vec![0; mapped.loaded_size()];
+        let mut read_target = vec![0; read.loaded_size()];
+        mapped.load(0x1000, &mut mapped_target).unwrap();
+        read.load(0x1000, &mut read_target).unwrap();
+        assert!(mapped_target == read_target);
+    }
+
     #[test]
     fn dummyguest_has_no_version_section() {
         let path = dummy_guest_as_string().expect("failed to locate dummyguest");
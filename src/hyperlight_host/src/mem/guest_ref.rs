@@ -0,0 +1,111 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+use hyperlight_common::vmem::PAGE_SIZE;
+
+use super::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
+use super::shared_mem::{ExclusiveSharedMemory, GuestSharedMemory, HostSharedMemory, SharedMemory};
+use crate::{Result, new_error};
+
+/// A value mapped into a sandbox with
+/// [`MultiUseSandbox::map_value`](crate::MultiUseSandbox::map_value), which
+/// both the host and the guest can read and write.
+///
+/// The value lives in page-aligned memory shared with the guest, which
+/// stays alive for as long as the `GuestRef` does, so it can still be read
+/// after the sandbox has unmapped it or been dropped.
+pub struct GuestRef<T: Pod> {
+    memory: HostSharedMemory,
+    guest_base: u64,
+    _value: PhantomData<T>,
+}
+
+impl<T: Pod> GuestRef<T> {
+    /// Copy `value` into new memory, and return it along with the memory
+    /// the sandbox maps and must keep alive.
+    pub(crate) fn new(value: T, guest_base: u64) -> Result<(Self, GuestSharedMemory)> {
+        let len = size_of::<T>();
+        if len == 0 {
+            return Err(new_error!("Cannot map a zero-sized value"));
+        }
+        let mut memory = ExclusiveSharedMemory::new(len.next_multiple_of(PAGE_SIZE))?;
+        memory.copy_from_slice(bytemuck::bytes_of(&value), 0)?;
+        let (memory, guest_memory) = memory.build();
+        Ok((
+            Self {
+                memory,
+                guest_base,
+                _value: PhantomData,
+            },
+            guest_memory,
+        ))
+    }
+
+    /// The read-write [`MemoryRegion`] that maps `memory` at `guest_base`.
+    pub(crate) fn memory_region(
+        memory: &GuestSharedMemory,
+        guest_base: u64,
+    ) -> Result<MemoryRegion> {
+        let guest_base = usize::try_from(guest_base)?;
+        if guest_base % PAGE_SIZE != 0 {
+            return Err(new_error!(
+                "Guest address {:#x} is not page-aligned",
+                guest_base
+            ));
+        }
+        let guest_end = guest_base
+            .checked_add(memory.mem_size())
+            .ok_or_else(|| new_error!("Guest address {:#x} is too large", guest_base))?;
+        Ok(MemoryRegion {
+            host_region: memory.host_region_base()..memory.host_region_end(),
+            guest_region: guest_base..guest_end,
+            flags: MemoryRegionFlags::READ | MemoryRegionFlags::WRITE,
+            region_type: MemoryRegionType::Heap,
+        })
+    }
+
+    /// The guest address the value is mapped at.
+    pub fn guest_address(&self) -> u64 {
+        self.guest_base
+    }
+
+    /// Read the value, including any changes the guest made to it.
+    ///
+    /// Reading while the guest is running, from another thread, may see a
+    /// value the guest is halfway through writing.
+    pub fn get(&self) -> Result<T> {
+        let mut value = T::zeroed();
+        self.memory
+            .copy_to_slice(bytemuck::bytes_of_mut(&mut value), 0)?;
+        Ok(value)
+    }
+
+    /// Replace the value, for the guest to see the next time it reads it.
+    pub fn set(&self, value: T) -> Result<()> {
+        self.memory.copy_from_slice(bytemuck::bytes_of(&value), 0)
+    }
+}
+
+impl<T: Pod> std::fmt::Debug for GuestRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuestRef")
+            .field("guest_base", &self.guest_base)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,257 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::sync::Arc;
+
+use hyperlight_common::vmem::PAGE_SIZE;
+
+use super::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
+use super::shared_mem::{ExclusiveSharedMemory, GuestSharedMemory, SharedMemory};
+use crate::{HyperlightError, Result, new_error};
+
+/// Host memory that can be mapped into a sandbox, read-only, with
+/// [`MultiUseSandbox::map_host_region`](crate::MultiUseSandbox::map_host_region).
+///
+/// The contents are copied into page-aligned memory owned by the region,
+/// which cannot be modified afterwards. The memory is freed once the region
+/// and every sandbox it is mapped into have been dropped.
+///
+/// A hash of the contents is taken when the region is created, so that
+/// [`is_intact`](Self::is_intact) can detect writes that the hypervisor
+/// failed to prevent.
+#[derive(Clone, Debug)]
+pub struct HostRegion {
+    memory: Arc<GuestSharedMemory>,
+    len: usize,
+    hash: blake3::Hash,
+}
+
+impl HostRegion {
+    /// Create a region holding a copy of `data`, which must not be empty.
+    pub fn from_slice(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Err(new_error!("Cannot create an empty host region"));
+        }
+        let size = data
+            .len()
+            .checked_next_multiple_of(PAGE_SIZE)
+            .ok_or_else(|| new_error!("Host region of {} bytes is too large", data.len()))?;
+        let mut memory = ExclusiveSharedMemory::new(size)?;
+        memory.copy_from_slice(data, 0)?;
+        let hash = blake3::hash(memory.as_slice());
+        let (_, memory) = memory.build();
+        Ok(Self {
+            memory: Arc::new(memory),
+            len: data.len(),
+            hash,
+        })
+    }
+
+    /// Create a region holding the contents of `data`, which must not be
+    /// empty.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        Self::from_slice(&data)
+    }
+
+    /// The number of bytes of data in the region.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the region holds no data, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bytes the region takes up when mapped, which is its
+    /// length rounded up to a whole number of pages. The bytes after the
+    /// data are zero.
+    pub fn mapped_size(&self) -> usize {
+        self.memory.mem_size()
+    }
+
+    /// Whether the region still holds what it was created with, including
+    /// the zeroes after the data.
+    ///
+    /// The region is mapped read-only, so this only fails if a guest wrote
+    /// to it on a hypervisor that does not enforce the protection, or if
+    /// its memory was written by other means. This is a best-effort check:
+    /// it reads the memory without synchronising with guests that have it
+    /// mapped, so it is only reliable when none of them is running.
+    pub fn is_intact(&self) -> bool {
+        // Safety: the memory is mapped for as long as `self.memory` is
+        // alive, and is never written to by the host. A guest may write to
+        // it concurrently only if the protection is not enforced, which is
+        // what this check is meant to detect.
+        let contents =
+            unsafe { std::slice::from_raw_parts(self.memory.base_ptr(), self.mapped_size()) };
+        blake3::hash(contents) == self.hash
+    }
+
+    /// The host address of the region's memory, for tests to simulate
+    /// writes that the hypervisor did not prevent.
+    #[cfg(test)]
+    pub(crate) fn base_ptr(&self) -> *mut u8 {
+        self.memory.base_ptr()
+    }
+
+    /// The read-only [`MemoryRegion`] that maps this region at `guest_base`,
+    /// which must be page-aligned.
+    ///
+    /// The returned region does not keep the memory alive, so it must only
+    /// be passed to [`MultiUseSandbox::map_region`](crate::MultiUseSandbox::map_region)
+    /// while `self` is kept alive for the lifetime of the sandbox.
+    /// [`MultiUseSandbox::map_host_region`](crate::MultiUseSandbox::map_host_region)
+    /// takes care of this.
+    pub fn memory_region(&self, guest_base: u64) -> Result<MemoryRegion> {
+        let guest_base = usize::try_from(guest_base)?;
+        if guest_base % PAGE_SIZE != 0 {
+            return Err(new_error!(
+                "Guest address {:#x} is not page-aligned",
+                guest_base
+            ));
+        }
+        let guest_end = guest_base
+            .checked_add(self.mapped_size())
+            .ok_or_else(|| new_error!("Guest address {:#x} is too large", guest_base))?;
+        Ok(MemoryRegion {
+            host_region: self.memory.host_region_base()..self.memory.host_region_end(),
+            guest_region: guest_base..guest_end,
+            flags: MemoryRegionFlags::READ,
+            region_type: MemoryRegionType::Heap,
+        })
+    }
+}
+
+/// The [`HostRegion`]s mapped into a sandbox and their guest addresses,
+/// kept alive for as long as the VM may access them.
+///
+/// Every region is checked with [`HostRegion::is_intact`] when it is
+/// released, whether by [`release`](Self::release) or when this is
+/// dropped. A modified region is logged, and the first one found is kept
+/// for [`check`](Self::check) to report.
+#[derive(Debug, Default)]
+pub(crate) struct MappedHostRegions {
+    regions: Vec<(u64, HostRegion)>,
+    /// The guest address of the first released region found modified
+    /// since [`check`](Self::check) last reported one.
+    modified: Option<u64>,
+}
+
+impl MappedHostRegions {
+    /// Keeps `region`, mapped at `guest_base`, alive until it is released.
+    pub(crate) fn push(&mut self, guest_base: u64, region: HostRegion) {
+        self.regions.push((guest_base, region));
+    }
+
+    /// Releases every region, once they are no longer mapped, checking
+    /// that the guest did not manage to write to them.
+    pub(crate) fn release(&mut self) {
+        for (guest_base, region) in self.regions.drain(..) {
+            if !region.is_intact() {
+                tracing::error!(
+                    "Read-only host region mapped at {:#x} was modified",
+                    guest_base
+                );
+                self.modified.get_or_insert(guest_base);
+            }
+        }
+    }
+
+    /// Returns [`HyperlightError::HostRegionModified`] for the first
+    /// released region that was found modified since this was last
+    /// called, or else for the first mapped region that is modified.
+    pub(crate) fn check(&mut self) -> Result<()> {
+        let modified = self.modified.take().or_else(|| {
+            self.regions
+                .iter()
+                .find(|(_, region)| !region.is_intact())
+                .map(|(guest_base, _)| *guest_base)
+        });
+        match modified {
+            Some(guest_base) => Err(HyperlightError::HostRegionModified(guest_base)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for MappedHostRegions {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_slice() {
+        let region = HostRegion::from_slice(b"hello").unwrap();
+        assert_eq!(region.len(), 5);
+        assert_eq!(region.mapped_size(), PAGE_SIZE);
+
+        let region = HostRegion::from_vec(vec![1; PAGE_SIZE + 1]).unwrap();
+        assert_eq!(region.mapped_size(), 2 * PAGE_SIZE);
+        let rgn = region.memory_region(0x1_0000_0000).unwrap();
+        assert_eq!(
+            rgn.guest_region,
+            0x1_0000_0000..0x1_0000_0000 + 2 * PAGE_SIZE
+        );
+        assert_eq!(rgn.flags, MemoryRegionFlags::READ);
+
+        assert!(region.memory_region(0x1_0000_0001).is_err());
+        assert!(HostRegion::from_slice(&[]).is_err());
+    }
+
+    #[test]
+    fn is_intact() {
+        let region = HostRegion::from_slice(b"hello").unwrap();
+        assert!(region.is_intact());
+
+        // Simulate a write that the hypervisor did not prevent, past the
+        // data
+        unsafe { region.base_ptr().add(PAGE_SIZE - 1).write(1) };
+        assert!(!region.is_intact());
+    }
+
+    #[test]
+    fn mapped_host_regions() {
+        let mut regions = MappedHostRegions::default();
+        let intact = HostRegion::from_slice(b"hello").unwrap();
+        let modified = HostRegion::from_slice(b"world").unwrap();
+        regions.push(0x1000, intact);
+        regions.push(0x2000, modified.clone());
+        assert!(regions.check().is_ok());
+
+        // A mapped region is reported for as long as it is modified
+        unsafe { modified.base_ptr().write(b'W') };
+        for _ in 0..2 {
+            assert!(matches!(
+                regions.check(),
+                Err(HyperlightError::HostRegionModified(0x2000))
+            ));
+        }
+
+        // A released region is reported once
+        regions.release();
+        assert!(matches!(
+            regions.check(),
+            Err(HyperlightError::HostRegionModified(0x2000))
+        ));
+        assert!(regions.check().is_ok());
+    }
+}
@@ -65,7 +65,8 @@ use std::fmt::Debug;
 use std::mem::offset_of;
 use std::mem::size_of;
 
-use hyperlight_common::mem::{HyperlightPEB, PAGE_SIZE_USIZE};
+use hyperlight_common::fp_exceptions::FpExceptions;
+use hyperlight_common::mem::{AllocMode, HyperlightPEB, PAGE_SIZE_USIZE};
 use tracing::{Span, instrument};
 
 use super::memory_region::MemoryRegionType::{Code, Heap, InitData, Peb};
@@ -215,6 +216,46 @@ impl<Sn: ReadableSharedMemory, Sc: ReadableSharedMemory> ResolvedGpa<Sn, Sc> {
     }
 }
 
+/// Where a sandbox's guest binary is loaded in guest memory, see
+/// [`MultiUseSandbox::guest_image_info`](crate::MultiUseSandbox::guest_image_info).
+///
+/// The binary is relocated as a whole when it is loaded, so an address in
+/// the loaded image is translated to the address the binary was linked at,
+/// as used by its symbols and debug information, by subtracting
+/// [`slide`](Self::slide).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestImageInfo {
+    /// The guest address the binary was loaded at.
+    pub load_base: u64,
+    /// The size in bytes of the loaded image.
+    pub size: u64,
+    /// The guest address of the binary's entry point.
+    pub entry_point: u64,
+    /// The address the binary was linked to be loaded at, which is the
+    /// lowest address of its loadable segments.
+    pub link_base: u64,
+}
+
+impl GuestImageInfo {
+    /// The difference between the address the binary was loaded at and the
+    /// address it was linked at.
+    pub fn slide(&self) -> i64 {
+        self.load_base.wrapping_sub(self.link_base) as i64
+    }
+
+    /// Whether `guest_address` is within the loaded image.
+    pub fn contains(&self, guest_address: u64) -> bool {
+        guest_address >= self.load_base && guest_address - self.load_base < self.size
+    }
+
+    /// Translates an address in the loaded image to the address it has in
+    /// the binary, or returns `None` if it is outside the image.
+    pub fn to_link_address(&self, guest_address: u64) -> Option<u64> {
+        self.contains(guest_address)
+            .then(|| guest_address - self.load_base + self.link_base)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub(crate) struct SandboxMemoryLayout {
     /// Input data buffer size (from SandboxConfiguration).
@@ -223,8 +264,16 @@ pub(crate) struct SandboxMemoryLayout {
     pub(crate) output_data_size: usize,
     /// The heap size of this sandbox.
     pub(crate) heap_size: usize,
+    /// The maximum size of the guest's main stack, or 0 if it is only
+    /// bounded by available scratch memory.
+    pub(crate) stack_size: usize,
     /// The size of the guest code section.
     pub(crate) code_size: usize,
+    /// The virtual address the guest binary was linked to be loaded at.
+    pub(crate) code_link_base: u64,
+    /// The offset of the guest binary's entry point from the start of the
+    /// guest code section.
+    pub(crate) code_entrypoint_offset: u64,
     /// The size of the init data section (guest blob).
     pub(crate) init_data_size: usize,
     /// Permission flags for the init data region.
@@ -232,6 +281,21 @@ pub(crate) struct SandboxMemoryLayout {
     pub(crate) init_data_permissions: Option<MemoryRegionFlags>,
     /// The size of the scratch region in physical memory.
     pub(crate) scratch_size: usize,
+    /// The size of the shared ring region within scratch memory, or 0
+    /// if the sandbox has no shared ring.
+    pub(crate) shared_ring_size: usize,
+    /// The maximum number of bytes a single guest function call may
+    /// allocate, or 0 for no limit.
+    pub(crate) max_alloc_per_call: u64,
+    /// How the guest allocates from its heap.
+    pub(crate) alloc_mode: AllocMode,
+    /// Whether the scratch region should be backed with huge pages.
+    pub(crate) huge_pages: bool,
+    /// Whether the guest fails when it executes a page it has written to.
+    pub(crate) detect_self_modifying_code: bool,
+    /// The floating-point exceptions that are masked during guest
+    /// function calls.
+    pub(crate) fp_exception_mask: FpExceptions,
     /// Size of the primary guest memory region at `BASE_ADDRESS`
     /// (code, PEB, heap, init data). For a snapshot-backed layout
     /// this is also the guest-visible prefix of the host snapshot
@@ -252,7 +316,16 @@ impl Debug for SandboxMemoryLayout {
             &format_args!("{:#x}", self.get_memory_size().unwrap_or(0)),
         )
         .field("Code Size", &format_args!("{:#x}", self.code_size))
+        .field(
+            "Code Link Base",
+            &format_args!("{:#x}", self.code_link_base),
+        )
+        .field(
+            "Code Entrypoint Offset",
+            &format_args!("{:#x}", self.code_entrypoint_offset),
+        )
         .field("Heap Size", &format_args!("{:#x}", self.heap_size))
+        .field("Stack Size", &format_args!("{:#x}", self.stack_size))
         .field(
             "Init Data Size",
             &format_args!("{:#x}", self.init_data_size),
@@ -266,6 +339,21 @@ impl Debug for SandboxMemoryLayout {
             &format_args!("{:#x}", self.output_data_size),
         )
         .field("Scratch Size", &format_args!("{:#x}", self.scratch_size))
+        .field(
+            "Shared Ring Size",
+            &format_args!("{:#x}", self.shared_ring_size),
+        )
+        .field(
+            "Max Alloc Per Call",
+            &format_args!("{:#x}", self.max_alloc_per_call),
+        )
+        .field("Alloc Mode", &self.alloc_mode)
+        .field("Huge Pages", &self.huge_pages)
+        .field(
+            "Detect Self-Modifying Code",
+            &self.detect_self_modifying_code,
+        )
+        .field("FP Exception Mask", &self.fp_exception_mask)
         .field("Snapshot Size", &format_args!("{:#x}", self.snapshot_size))
         .field("PT Size", &format_args!("{:#x}", self.pt_size.unwrap_or(0)))
         .field(
@@ -314,20 +402,39 @@ impl SandboxMemoryLayout {
             input_data_size,
             output_data_size,
             heap_size,
+            stack_size,
             code_size,
+            code_link_base,
+            code_entrypoint_offset,
             init_data_size,
             init_data_permissions,
             scratch_size,
+            shared_ring_size,
+            max_alloc_per_call,
+            alloc_mode,
+            detect_self_modifying_code,
+            fp_exception_mask,
+            // Only changes how the scratch region is backed on the host,
+            // which the guest cannot tell apart
+            huge_pages: _,
             snapshot_size: _,
             pt_size: _,
         } = self;
         *input_data_size == other.input_data_size
             && *output_data_size == other.output_data_size
             && *heap_size == other.heap_size
+            && *stack_size == other.stack_size
             && *code_size == other.code_size
+            && *code_link_base == other.code_link_base
+            && *code_entrypoint_offset == other.code_entrypoint_offset
             && *init_data_size == other.init_data_size
             && *init_data_permissions == other.init_data_permissions
             && *scratch_size == other.scratch_size
+            && *shared_ring_size == other.shared_ring_size
+            && *max_alloc_per_call == other.max_alloc_per_call
+            && *alloc_mode == other.alloc_mode
+            && *detect_self_modifying_code == other.detect_self_modifying_code
+            && *fp_exception_mask == other.fp_exception_mask
     }
 
     /// The maximum amount of memory a single sandbox will be allowed.
@@ -359,32 +466,65 @@ impl SandboxMemoryLayout {
         }
         let input_data_size = cfg.get_input_data_size();
         let output_data_size = cfg.get_output_data_size();
+        let shared_ring_size = cfg.get_shared_ring_size().next_multiple_of(PAGE_SIZE_USIZE);
         let min_scratch_size =
-            hyperlight_common::layout::min_scratch_size(input_data_size, output_data_size);
+            Self::min_fixed_scratch_size(input_data_size, output_data_size, shared_ring_size);
         if scratch_size < min_scratch_size {
             return Err(MemoryRequestTooSmall(scratch_size, min_scratch_size));
         }
+        // Stack pages are allocated on demand from whatever scratch
+        // memory is not needed for anything else.
+        let stack_size = usize::try_from(cfg.get_stack_size())?.next_multiple_of(PAGE_SIZE_USIZE);
+        let max_stack_size = scratch_size - min_scratch_size;
+        if stack_size > max_stack_size {
+            return Err(MemoryRequestTooBig(stack_size, max_stack_size));
+        }
 
         let mut ret = Self {
             input_data_size,
             output_data_size,
             heap_size,
+            stack_size,
             code_size,
+            code_link_base: 0,
+            code_entrypoint_offset: 0,
             init_data_size,
             init_data_permissions,
             pt_size: None,
             scratch_size,
+            shared_ring_size,
+            max_alloc_per_call: cfg.get_max_alloc_per_call(),
+            alloc_mode: cfg.get_guest_allocator(),
+            huge_pages: cfg.get_huge_pages(),
+            detect_self_modifying_code: cfg.get_self_modifying_code_detection(),
+            fp_exception_mask: cfg.get_fp_exception_mask(),
             snapshot_size: 0,
         };
         ret.set_snapshot_size(ret.get_memory_size()?);
         Ok(ret)
     }
 
+    /// The scratch memory needed for everything other than page tables
+    /// and memory the guest allocates for itself.
+    fn min_fixed_scratch_size(
+        input_data_size: usize,
+        output_data_size: usize,
+        shared_ring_size: usize,
+    ) -> usize {
+        hyperlight_common::layout::min_scratch_size(input_data_size, output_data_size)
+            + shared_ring_size
+    }
+
     /// Offset of the PEB struct within the snapshot region.
     pub(crate) fn peb_offset(&self) -> usize {
         self.code_size.next_multiple_of(PAGE_SIZE_USIZE)
     }
 
+    /// Offset of the PEB guest_function_aliases field.
+    pub(crate) fn get_guest_function_aliases_offset(&self) -> usize {
+        self.peb_offset() + offset_of!(HyperlightPEB, guest_function_aliases)
+    }
+
     /// Offset of the PEB file_mappings field.
     #[cfg(feature = "nanvix-unstable")]
     fn peb_file_mappings_offset(&self) -> usize {
@@ -453,12 +593,26 @@ impl SandboxMemoryLayout {
         0
     }
 
+    /// Get the offset into the host scratch buffer of the start of
+    /// the shared ring
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_shared_ring_scratch_host_offset(&self) -> usize {
+        (self.input_data_size + self.output_data_size)
+            .next_multiple_of(hyperlight_common::vmem::PAGE_SIZE)
+    }
+
+    /// Get the guest virtual address of the start of the shared ring
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    fn get_shared_ring_gva(&self) -> u64 {
+        hyperlight_common::layout::scratch_base_gva(self.scratch_size)
+            + self.get_shared_ring_scratch_host_offset() as u64
+    }
+
     /// Get the offset from the beginning of the scratch region to the
     /// location where page tables will be eagerly copied on restore
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn get_pt_base_scratch_offset(&self) -> usize {
-        (self.input_data_size + self.output_data_size)
-            .next_multiple_of(hyperlight_common::vmem::PAGE_SIZE)
+        self.get_shared_ring_scratch_host_offset() + self.shared_ring_size
     }
 
     /// Get the base GPA to which the page tables will be eagerly
@@ -516,6 +670,24 @@ impl SandboxMemoryLayout {
         Self::BASE_ADDRESS + self.guest_code_offset()
     }
 
+    /// Record where the guest binary was linked to be loaded, and the
+    /// offset of its entry point from the start of the code section.
+    pub(crate) fn set_code_image(&mut self, link_base: u64, entrypoint_offset: u64) {
+        self.code_link_base = link_base;
+        self.code_entrypoint_offset = entrypoint_offset;
+    }
+
+    /// Where the guest binary is loaded in the sandbox.
+    pub(crate) fn guest_image_info(&self) -> GuestImageInfo {
+        let load_base = self.get_guest_code_address() as u64;
+        GuestImageInfo {
+            load_base,
+            size: self.code_size as u64,
+            entry_point: load_base + self.code_entrypoint_offset,
+            link_base: self.code_link_base,
+        }
+    }
+
     /// Get the total size of guest memory in `self`'s memory
     /// layout aligned to page size boundaries.
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
@@ -545,9 +717,10 @@ impl SandboxMemoryLayout {
     /// independent field and must be set separately.
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn set_pt_size(&mut self, size: usize) -> Result<()> {
-        let min_fixed_scratch = hyperlight_common::layout::min_scratch_size(
+        let min_fixed_scratch = Self::min_fixed_scratch_size(
             self.input_data_size,
             self.output_data_size,
+            self.shared_ring_size,
         );
         let min_scratch = min_fixed_scratch + size;
         if self.scratch_size < min_scratch {
@@ -704,6 +877,22 @@ impl SandboxMemoryLayout {
                 size: self.heap_size as u64,
                 ptr: guest_base + self.guest_heap_buffer_offset() as u64,
             },
+            guest_stack_size: self.stack_size as u64,
+            shared_ring: GuestMemoryRegion {
+                size: self.shared_ring_size as u64,
+                ptr: if self.shared_ring_size > 0 {
+                    self.get_shared_ring_gva()
+                } else {
+                    0
+                },
+            },
+            max_alloc_per_call: self.max_alloc_per_call,
+            alloc_mode: self.alloc_mode as u64,
+            detect_self_modifying_code: self.detect_self_modifying_code as u64,
+            fp_exceptions_trapped: (FpExceptions::all() - self.fp_exception_mask).bits() as u64,
+            // The aliases are only known once the sandbox is evolved, see
+            // `SandboxMemoryManager::set_guest_function_aliases`
+            guest_function_aliases: 0,
             // Set up the file_mappings descriptor in the PEB.
             // - The `size` field holds the number of valid FileMappingInfo
             //   entries currently written (initially 0 — entries are added
@@ -817,6 +1006,40 @@ mod tests {
         assert!(matches!(layout.unwrap_err(), MemoryRequestTooBig(..)));
     }
 
+    #[test]
+    fn test_stack_size() {
+        let mut cfg = SandboxConfiguration::default();
+        cfg.set_stack_size(0x1001);
+        let layout = SandboxMemoryLayout::new(cfg, 4096, 0, None).unwrap();
+        assert_eq!(layout.stack_size, 0x2000);
+
+        // The stack must fit in the scratch region
+        cfg.set_stack_size(cfg.get_scratch_size() as u64);
+        let layout = SandboxMemoryLayout::new(cfg, 4096, 0, None);
+        assert!(matches!(layout.unwrap_err(), MemoryRequestTooBig(..)));
+    }
+
+    #[test]
+    fn test_shared_ring_size() {
+        let mut cfg = SandboxConfiguration::default();
+        let without_ring = SandboxMemoryLayout::new(cfg, 4096, 0, None).unwrap();
+        assert_eq!(without_ring.shared_ring_size, 0);
+
+        cfg.set_shared_ring_size(0x1001);
+        let layout = SandboxMemoryLayout::new(cfg, 4096, 0, None).unwrap();
+        assert_eq!(layout.shared_ring_size, 0x2000);
+        // The page tables are moved past the ring
+        assert_eq!(
+            layout.get_pt_base_scratch_offset(),
+            without_ring.get_pt_base_scratch_offset() + 0x2000
+        );
+
+        // The ring must fit in the scratch region
+        cfg.set_shared_ring_size(cfg.get_scratch_size());
+        let layout = SandboxMemoryLayout::new(cfg, 4096, 0, None);
+        assert!(matches!(layout.unwrap_err(), MemoryRequestTooSmall(..)));
+    }
+
     #[test]
     fn is_compatible_with_identical_layouts() {
         let cfg = SandboxConfiguration::default();
@@ -850,9 +1073,17 @@ mod tests {
             |l| l.input_data_size += PAGE_SIZE_USIZE,
             |l| l.output_data_size += PAGE_SIZE_USIZE,
             |l| l.heap_size += PAGE_SIZE_USIZE,
+            |l| l.stack_size += PAGE_SIZE_USIZE,
             |l| l.code_size += PAGE_SIZE_USIZE,
+            |l| l.code_link_base += PAGE_SIZE_USIZE as u64,
+            |l| l.code_entrypoint_offset += 1,
             |l| l.init_data_size += PAGE_SIZE_USIZE,
             |l| l.scratch_size += PAGE_SIZE_USIZE,
+            |l| l.shared_ring_size += PAGE_SIZE_USIZE,
+            |l| l.max_alloc_per_call += 1,
+            |l| l.alloc_mode = AllocMode::Bump,
+            |l| l.detect_self_modifying_code = true,
+            |l| l.fp_exception_mask = FpExceptions::empty(),
             |l| {
                 l.init_data_permissions = Some(MemoryRegionFlags::READ);
             },
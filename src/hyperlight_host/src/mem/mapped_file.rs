@@ -0,0 +1,161 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::ffi::c_void;
+use std::ops::Deref;
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+use crate::hypervisor::wrappers::HandleWrapper;
+use crate::{HyperlightError, Result, log_then_return};
+
+/// A read-only, private mapping of a whole file into the host process.
+///
+/// Pages of the file are only read from disk when they are first
+/// accessed. The mapping can never be written through: anything that
+/// needs to modify the contents, such as applying relocations or zeroing
+/// `.bss`, must copy them out first.
+pub(crate) struct MappedFile {
+    base: *mut c_void,
+    len: usize,
+    #[cfg(target_os = "windows")]
+    mapping_handle: HandleWrapper,
+}
+
+impl MappedFile {
+    /// Map the file at `path`, which must not be empty.
+    ///
+    /// The file must not be truncated while it is mapped. Doing so makes
+    /// accesses to the pages past its new end fail.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| HyperlightError::from_io(format!("opening {path:?}"), e))?;
+        let file_size = file.metadata()?.len();
+        if file_size == 0 {
+            log_then_return!("cannot map an empty file: {:?}", path);
+        }
+        let len = usize::try_from(file_size).map_err(|_| {
+            HyperlightError::Error(format!(
+                "File size {file_size} exceeds addressable range on this platform"
+            ))
+        })?;
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::io::AsRawHandle;
+
+            use windows::Win32::Foundation::HANDLE;
+            use windows::Win32::System::Memory::{
+                CreateFileMappingW, FILE_MAP_READ, MapViewOfFile, PAGE_READONLY,
+            };
+
+            let file_handle = HANDLE(file.as_raw_handle());
+            let mapping_handle =
+                unsafe { CreateFileMappingW(file_handle, None, PAGE_READONLY, 0, 0, None) }
+                    .map_err(|e| {
+                        HyperlightError::Error(format!("CreateFileMappingW failed: {e}"))
+                    })?;
+            let view = unsafe { MapViewOfFile(mapping_handle, FILE_MAP_READ, 0, 0, 0) };
+            if view.Value.is_null() {
+                unsafe {
+                    let _ = windows::Win32::Foundation::CloseHandle(mapping_handle);
+                }
+                log_then_return!(
+                    "MapViewOfFile failed: {:?}",
+                    std::io::Error::last_os_error()
+                );
+            }
+            Ok(Self {
+                base: view.Value,
+                len,
+                mapping_handle: HandleWrapper::from(mapping_handle),
+            })
+        }
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+
+            let base = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if base == libc::MAP_FAILED {
+                log_then_return!("mmap error: {:?}", std::io::Error::last_os_error());
+            }
+            Ok(Self { base, len })
+        }
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `base` points to a readable mapping of `len` bytes that
+        // lives as long as `self`, and is never written to.
+        unsafe { std::slice::from_raw_parts(self.base as *const u8, self.len) }
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use windows::Win32::Foundation::CloseHandle;
+            use windows::Win32::System::Memory::{MEMORY_MAPPED_VIEW_ADDRESS, UnmapViewOfFile};
+            if let Err(e) = UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS { Value: self.base }) {
+                tracing::error!("MappedFile::drop: UnmapViewOfFile failed: {:?}", e);
+            }
+            if let Err(e) = CloseHandle(self.mapping_handle.into()) {
+                tracing::error!("MappedFile::drop: CloseHandle failed: {:?}", e);
+            }
+        }
+        #[cfg(unix)]
+        unsafe {
+            if libc::munmap(self.base, self.len) != 0 {
+                tracing::error!(
+                    "MappedFile::drop: munmap failed: {:?}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+    }
+}
+
+// SAFETY: The mapping is owned by `MappedFile`, is only ever read, and is
+// released exactly once, when it is dropped.
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_testing::simple_guest_as_string;
+
+    use super::MappedFile;
+
+    #[test]
+    fn maps_whole_file() {
+        let path = simple_guest_as_string().unwrap();
+        let mapped = MappedFile::open(path.as_ref()).unwrap();
+        assert_eq!(&*mapped, std::fs::read(&path).unwrap().as_slice());
+    }
+}
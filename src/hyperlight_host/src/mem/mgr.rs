@@ -20,24 +20,32 @@ use flatbuffers::FlatBufferBuilder;
 use hyperlight_common::flatbuffer_wrappers::function_call::{
     FunctionCall, validate_guest_function_call_buffer,
 };
-use hyperlight_common::flatbuffer_wrappers::function_types::FunctionCallResult;
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    FunctionCallResult, ReturnType, ReturnValue,
+};
+use hyperlight_common::flatbuffer_wrappers::guest_error::GuestError;
 use hyperlight_common::flatbuffer_wrappers::guest_log_data::GuestLogData;
 use hyperlight_common::flatbuffer_wrappers::host_function_details::HostFunctionDetails;
+use hyperlight_common::mem::PAGE_SIZE_USIZE;
+use hyperlight_common::shared_ring::SharedRing;
 use hyperlight_common::vmem::{self, PAGE_TABLE_SIZE};
 #[cfg(all(feature = "crashdump", not(feature = "i686-guest")))]
 use hyperlight_common::vmem::{BasicMapping, MappingKind};
 use tracing::{Span, instrument};
 
 use super::layout::SandboxMemoryLayout;
+#[cfg(target_os = "linux")]
+use super::shared_mem::HUGE_PAGE_SIZE;
 use super::shared_mem::{
     ExclusiveSharedMemory, GuestSharedMemory, HostSharedMemory, ReadonlySharedMemory, SharedMemory,
 };
+use super::shared_ring::{HostSharedRing, SharedRingMemory};
 use crate::hypervisor::regs::CommonSpecialRegisters;
 use crate::mem::memory_region::MemoryRegion;
 #[cfg(crashdump)]
 use crate::mem::memory_region::{CrashDumpRegion, MemoryRegionFlags, MemoryRegionType};
 use crate::sandbox::snapshot::{NextAction, Snapshot};
-use crate::{Result, new_error};
+use crate::{HyperlightError, Result, new_error};
 
 #[cfg(all(feature = "crashdump", not(feature = "i686-guest")))]
 fn mapping_kind_to_flags(kind: &MappingKind) -> (MemoryRegionFlags, MemoryRegionType) {
@@ -153,6 +161,15 @@ pub(crate) struct SandboxMemoryManager<S: SharedMemory> {
     /// restored snapshot's own generation number so the guest-visible
     /// counter tracks which snapshot the sandbox is a clone of.
     pub(crate) snapshot_count: u64,
+    /// The size of the pages backing `scratch_mem` on the host
+    pub(crate) scratch_page_size: usize,
+    /// The largest guest function call result the host reads, see
+    /// [`SandboxConfiguration::set_max_result_bytes`](crate::sandbox::SandboxConfiguration::set_max_result_bytes)
+    pub(crate) max_result_bytes: Option<usize>,
+    /// The encoded guest function call result that
+    /// [`Self::get_guest_function_call_result_into`] last read, kept so
+    /// that its allocation is reused across calls
+    pub(crate) result_buffer: Vec<u8>,
 }
 
 /// Buffer for building guest page tables during snapshot creation.
@@ -287,7 +304,49 @@ where
             entrypoint,
             abort_buffer: Vec::new(),
             snapshot_count: 0,
+            scratch_page_size: PAGE_SIZE_USIZE,
+            max_result_bytes: None,
+            result_buffer: Vec::new(),
+        }
+    }
+
+    /// Check that the memory layout fits within the allocated shared
+    /// memory and scratch regions, and that a pending initialisation
+    /// entrypoint lies within the loaded guest code. Neither region is
+    /// read or written.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let memory_size = self.layout.get_memory_size()?;
+        if self.shared_mem.mem_size() < memory_size {
+            return Err(HyperlightError::GuestBinaryValidationFailed(format!(
+                "shared memory size {:#x} is smaller than the memory layout size {:#x}",
+                self.shared_mem.mem_size(),
+                memory_size
+            )));
+        }
+
+        let scratch_size = self.layout.get_scratch_size();
+        if self.scratch_mem.mem_size() < scratch_size {
+            return Err(HyperlightError::GuestBinaryValidationFailed(format!(
+                "scratch memory size {:#x} is smaller than the configured scratch size {:#x}",
+                self.scratch_mem.mem_size(),
+                scratch_size
+            )));
         }
+
+        // A `Call` entrypoint comes from a snapshot of an initialised
+        // guest, whose dispatch function was reported by the guest itself.
+        if let NextAction::Initialise(entrypoint) = self.entrypoint {
+            let code_start = self.layout.get_guest_code_address() as u64;
+            let code_end = code_start + self.layout.code_size as u64;
+            if !(code_start..code_end).contains(&entrypoint) {
+                return Err(HyperlightError::GuestBinaryValidationFailed(format!(
+                    "entrypoint {:#x} is outside the guest code region [{:#x}..{:#x})",
+                    entrypoint, code_start, code_end
+                )));
+            }
+        }
+
+        Ok(())
     }
 
     /// Get mutable access to the abort buffer
@@ -323,13 +382,34 @@ where
     }
 }
 
+/// Allocate the scratch memory for `layout`, and return it along with the
+/// size of the pages requested for it.
+fn new_scratch_mem(layout: &SandboxMemoryLayout) -> Result<(ExclusiveSharedMemory, usize)> {
+    // Huge pages can only back memory aligned to them
+    #[cfg(target_os = "linux")]
+    let scratch_mem = if layout.huge_pages {
+        ExclusiveSharedMemory::new_aligned(layout.get_scratch_size(), HUGE_PAGE_SIZE)?
+    } else {
+        ExclusiveSharedMemory::new(layout.get_scratch_size())?
+    };
+    #[cfg(not(target_os = "linux"))]
+    let scratch_mem = ExclusiveSharedMemory::new(layout.get_scratch_size())?;
+    let page_size = if layout.huge_pages {
+        scratch_mem.advise_huge_pages()
+    } else {
+        PAGE_SIZE_USIZE
+    };
+    Ok((scratch_mem, page_size))
+}
+
 impl SandboxMemoryManager<ExclusiveSharedMemory> {
     pub(crate) fn from_snapshot(s: &Snapshot) -> Result<Self> {
         let layout = *s.layout();
         let shared_mem = s.memory().to_mgr_snapshot_mem()?;
-        let scratch_mem = ExclusiveSharedMemory::new(s.layout().get_scratch_size())?;
+        let (scratch_mem, scratch_page_size) = new_scratch_mem(&layout)?;
         let entrypoint = s.entrypoint();
         let mut mgr = Self::new(layout, shared_mem, scratch_mem, entrypoint);
+        mgr.scratch_page_size = scratch_page_size;
         // Inherit the snapshot's generation number for the same
         // reason `restore_snapshot` does: the guest-visible counter
         // reflects "which snapshot is the sandbox currently a clone
@@ -363,6 +443,9 @@ impl SandboxMemoryManager<ExclusiveSharedMemory> {
             entrypoint: self.entrypoint,
             abort_buffer: self.abort_buffer,
             snapshot_count: self.snapshot_count,
+            scratch_page_size: self.scratch_page_size,
+            max_result_bytes: self.max_result_bytes,
+            result_buffer: self.result_buffer,
         };
         let guest_mgr = SandboxMemoryManager {
             shared_mem: gshm,
@@ -371,6 +454,9 @@ impl SandboxMemoryManager<ExclusiveSharedMemory> {
             entrypoint: self.entrypoint,
             abort_buffer: Vec::new(), // Guest doesn't need abort buffer
             snapshot_count: self.snapshot_count,
+            scratch_page_size: self.scratch_page_size,
+            max_result_bytes: self.max_result_bytes,
+            result_buffer: Vec::new(),
         };
         host_mgr.update_scratch_bookkeeping()?;
         Ok((host_mgr, guest_mgr))
@@ -378,6 +464,16 @@ impl SandboxMemoryManager<ExclusiveSharedMemory> {
 }
 
 impl SandboxMemoryManager<HostSharedMemory> {
+    /// Tell the guest in the PEB whether it should fetch its function
+    /// aliases from the host while it is initialised, so that a guest
+    /// without any does not make the call.
+    pub(crate) fn set_guest_function_aliases(&mut self, has_aliases: bool) -> Result<()> {
+        self.shared_mem.write::<u64>(
+            self.layout.get_guest_function_aliases_offset(),
+            has_aliases.into(),
+        )
+    }
+
     /// Write a [`FileMappingInfo`] entry into the PEB's preallocated array.
     ///
     /// Reads the current entry count from the PEB, validates that the
@@ -486,12 +582,61 @@ impl SandboxMemoryManager<HostSharedMemory> {
     /// A function call result can be either an error or a successful return value.
     #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn get_guest_function_call_result(&mut self) -> Result<FunctionCallResult> {
-        self.scratch_mem.try_pop_buffer_into::<FunctionCallResult>(
+        self.get_guest_function_call_result_with(&mut Vec::new())
+    }
+
+    /// Reads a function call result from memory as
+    /// [`get_guest_function_call_result`](Self::get_guest_function_call_result)
+    /// does, copying the encoded result out through `scratch` so that its
+    /// allocation can be reused across calls.
+    #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_guest_function_call_result_with(
+        &mut self,
+        scratch: &mut Vec<u8>,
+    ) -> Result<FunctionCallResult> {
+        self.scratch_mem.try_pop_buffer_with(
             self.layout.get_output_data_buffer_scratch_host_offset(),
             self.layout.output_data_size,
+            self.max_result_bytes,
+            scratch,
+            |data| {
+                FunctionCallResult::try_from(data)
+                    .map_err(|e| new_error!("Failed to decode guest function call result: {}", e))
+            },
         )
     }
 
+    /// Reads the result of a guest function call whose value is a string or
+    /// a byte vector into `buf`, reusing its allocation, and returns the
+    /// type of the value.
+    ///
+    /// Only the value is copied into `buf`, the encoded result around it
+    /// is read through [`Self::result_buffer`]. `buf` is left empty if the
+    /// result is a guest error or cannot be read.
+    #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_guest_function_call_result_into(
+        &mut self,
+        buf: &mut Vec<u8>,
+    ) -> Result<std::result::Result<ReturnType, GuestError>> {
+        buf.clear();
+        let mut encoded = std::mem::take(&mut self.result_buffer);
+        let located = self.scratch_mem.try_pop_buffer_with(
+            self.layout.get_output_data_buffer_scratch_host_offset(),
+            self.layout.output_data_size,
+            self.max_result_bytes,
+            &mut encoded,
+            |data| {
+                FunctionCallResult::locate_bytes(data)
+                    .map_err(|e| new_error!("Failed to decode guest function call result: {}", e))
+            },
+        );
+        if let Ok(Ok((_, range))) = &located {
+            buf.extend_from_slice(&encoded[range.clone()]);
+        }
+        self.result_buffer = encoded;
+        Ok(located?.map(|(return_type, _)| return_type))
+    }
+
     /// Read guest log data from the `SharedMemory` contained within `self`
     #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn read_guest_log_data(&mut self) -> Result<GuestLogData> {
@@ -501,16 +646,47 @@ impl SandboxMemoryManager<HostSharedMemory> {
         )
     }
 
-    pub(crate) fn clear_io_buffers(&mut self) {
-        // Clear the output data buffer
-        loop {
-            let Ok(_) = self.scratch_mem.try_pop_buffer_into::<Vec<u8>>(
-                self.layout.get_output_data_buffer_scratch_host_offset(),
-                self.layout.output_data_size,
-            ) else {
+    /// Pop the most recent partial result published by the guest, if the
+    /// top of the output data buffer holds one.
+    pub(crate) fn take_partial_result(&mut self) -> Option<ReturnValue> {
+        self.get_guest_function_call_result()
+            .ok()
+            .and_then(|result| result.into_inner().ok())
+    }
+
+    /// Discard everything left in the output data buffer, such as partial
+    /// results the guest published before producing its final result.
+    pub(crate) fn clear_output_buffer(&mut self) {
+        let offset = self.layout.get_output_data_buffer_scratch_host_offset();
+        // An empty buffer's stack pointer is 8, check it first so that the
+        // common case of an already empty buffer stays cheap.
+        while self.scratch_mem.read::<u64>(offset).is_ok_and(|sp| sp > 8) {
+            let Ok(_) = self
+                .scratch_mem
+                .try_pop_buffer_into::<Vec<u8>>(offset, self.layout.output_data_size)
+            else {
                 break;
             };
         }
+    }
+
+    /// Get the host's view of the shared ring, if the sandbox has one.
+    pub(crate) fn shared_ring(&self) -> Result<Option<HostSharedRing>> {
+        if self.layout.shared_ring_size == 0 {
+            return Ok(None);
+        }
+        SharedRing::host_handle(
+            SharedRingMemory::new(self.scratch_mem.clone()),
+            self.layout.get_shared_ring_scratch_host_offset() as u64,
+            self.layout.shared_ring_size,
+        )
+        .map(Some)
+        .map_err(|e| new_error!("Failed to open shared ring: {}", e))
+    }
+
+    pub(crate) fn clear_io_buffers(&mut self) {
+        // Clear the output data buffer
+        self.clear_output_buffer();
         // Clear the input data buffer
         loop {
             let Ok(_) = self.scratch_mem.try_pop_buffer_into::<Vec<u8>>(
@@ -546,11 +722,14 @@ impl SandboxMemoryManager<HostSharedMemory> {
             Some(gsnapshot)
         };
         let new_scratch_size = snapshot.layout().get_scratch_size();
-        let gscratch = if new_scratch_size == self.scratch_mem.mem_size() {
+        let gscratch = if new_scratch_size == self.scratch_mem.mem_size()
+            && snapshot.layout().huge_pages == self.layout.huge_pages
+        {
             self.scratch_mem.zero()?;
             None
         } else {
-            let new_scratch_mem = ExclusiveSharedMemory::new(new_scratch_size)?;
+            let (new_scratch_mem, scratch_page_size) = new_scratch_mem(snapshot.layout())?;
+            self.scratch_page_size = scratch_page_size;
             let (hscratch, gscratch) = new_scratch_mem.build();
             // Even though this destroys the reference to the host
             // side of the old scratch mapping, the VM should still
@@ -614,6 +793,14 @@ impl SandboxMemoryManager<HostSharedMemory> {
             self.layout.get_output_data_buffer_scratch_host_offset(),
             SandboxMemoryLayout::STACK_POINTER_SIZE_BYTES,
         )?;
+        if self.layout.shared_ring_size > 0 {
+            SharedRing::init(
+                &SharedRingMemory::new(self.scratch_mem.clone()),
+                self.layout.get_shared_ring_scratch_host_offset() as u64,
+                self.layout.shared_ring_size,
+            )
+            .map_err(|e| new_error!("Failed to initialise shared ring: {}", e))?;
+        }
 
         // Copy page tables from `shared_mem` into scratch. PT bytes
         // are appended to the snapshot blob at build time and live
@@ -764,7 +951,7 @@ impl SandboxMemoryManager<HostSharedMemory> {
     /// * `gva` - The Guest Virtual Address to read from
     /// * `len` - The number of bytes to read
     /// * `root_pt` - The root page table physical address (CR3)
-    #[cfg(feature = "trace_guest")]
+    #[cfg(any(feature = "trace_guest", target_arch = "x86_64"))]
     pub(crate) fn read_guest_memory_by_gva(
         &mut self,
         gva: u64,
@@ -859,6 +1046,98 @@ impl SandboxMemoryManager<HostSharedMemory> {
             })
         })??
     }
+
+    /// Write `data` to guest memory at a Guest Virtual Address (GVA),
+    /// translating it through the guest's page tables like
+    /// [`read_guest_memory_by_gva`](Self::read_guest_memory_by_gva).
+    ///
+    /// Every page written must be mapped writable by the guest, which
+    /// means it is backed by scratch memory. Pages the guest has not yet
+    /// written are still copy-on-write mappings of the snapshot, which is
+    /// shared with other sandboxes, so writing to them fails without
+    /// writing anything.
+    ///
+    /// # Arguments
+    /// * `gva` - The Guest Virtual Address to write to
+    /// * `data` - The bytes to write
+    /// * `root_pt` - The root page table physical address (CR3)
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) fn write_guest_memory_by_gva(
+        &mut self,
+        gva: u64,
+        data: &[u8],
+        root_pt: u64,
+    ) -> Result<()> {
+        use hyperlight_common::vmem::PAGE_SIZE;
+
+        use crate::mem::layout::BaseGpaRegion;
+        use crate::sandbox::snapshot::SharedMemoryPageTableBuffer;
+
+        // Resolve every page before writing any, so that a write that is
+        // not allowed does not partly happen
+        let writes = self.shared_mem.with_contents(|snap| {
+            self.scratch_mem.with_contents(|scratch| {
+                let pt_buf = SharedMemoryPageTableBuffer::new(snap, scratch, self.layout, root_pt);
+                let mappings = unsafe { vmem::virt_to_phys(&pt_buf, gva, data.len() as u64) };
+
+                let mut writes = Vec::new();
+                let mut written = 0;
+                for mapping in mappings {
+                    let current_gva = gva + written as u64;
+                    if mapping.virt_base > current_gva {
+                        break;
+                    }
+                    if !matches!(
+                        mapping.kind,
+                        MappingKind::Basic(vmem::BasicMapping { writable: true, .. })
+                    ) {
+                        return Err(new_error!(
+                            "GVA {:#x} is not mapped writable by the guest",
+                            current_gva
+                        ));
+                    }
+
+                    let page_offset = (current_gva - mapping.virt_base) as usize;
+                    let len = (data.len() - written).min(PAGE_SIZE - page_offset);
+                    let gpa = mapping.phys_base + page_offset as u64;
+                    match self.layout.resolve_gpa(gpa, &[]) {
+                        Some(resolved)
+                            if matches!(resolved.base, BaseGpaRegion::Scratch(()))
+                                && resolved.offset + len <= scratch.len() =>
+                        {
+                            writes.push((resolved.offset, written..written + len));
+                        }
+                        _ => {
+                            return Err(new_error!(
+                                "GVA {:#x} is not backed by the guest's scratch memory (GPA {:#x})",
+                                current_gva,
+                                gpa
+                            ));
+                        }
+                    }
+                    written += len;
+                    if written == data.len() {
+                        break;
+                    }
+                }
+
+                if written != data.len() {
+                    return Err(new_error!(
+                        "Could not write full GVA range: {} of {} bytes at {:#x} are mapped",
+                        written,
+                        data.len(),
+                        gva
+                    ));
+                }
+                Ok(writes)
+            })
+        })???;
+
+        for (offset, range) in writes {
+            self.scratch_mem.copy_from_slice(&data[range], offset)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
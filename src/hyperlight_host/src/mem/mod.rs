@@ -18,8 +18,14 @@ limitations under the License.
 pub(crate) mod elf;
 /// A generic wrapper for executable files (PE, ELF, etc)
 pub(crate) mod exe;
+/// Typed values shared between the host and a sandbox
+pub mod guest_ref;
+/// Host memory that can be safely mapped into a sandbox
+pub mod host_region;
 /// Functionality to establish a sandbox's memory layout.
 pub mod layout;
+/// Read-only mappings of files into the host process
+pub(crate) mod mapped_file;
 /// memory regions to be mapped inside a vm
 pub mod memory_region;
 /// Functionality that wraps a `SandboxMemoryLayout` and a
@@ -38,3 +44,7 @@ pub mod shared_mem;
 /// Utilities for writing shared memory tests
 #[cfg(all(test, not(miri)))] // uses proptest which isn't miri-compatible
 pub(crate) mod shared_mem_tests;
+/// Host memory that can be mapped read-write into several sandboxes
+pub mod shared_mut_region;
+/// Host side access to the shared ring between a sandbox and its guest
+pub mod shared_ring;
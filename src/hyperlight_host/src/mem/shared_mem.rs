@@ -46,6 +46,14 @@ use super::memory_region::{
 use crate::HyperlightError::WindowsAPIError;
 use crate::{HyperlightError, Result, log_then_return, new_error};
 
+/// The size of a transparent huge page, see
+/// [`ExclusiveSharedMemory::advise_huge_pages`].
+pub(crate) const HUGE_PAGE_SIZE: usize = 0x200000;
+
+/// Whether the kernel uses transparent huge pages, and when.
+#[cfg(all(target_os = "linux", not(miri)))]
+const TRANSPARENT_HUGE_PAGES_ENABLED: &str = "/sys/kernel/mm/transparent_hugepage/enabled";
+
 /// Makes sure that the given `offset` and `size` are within the bounds of the memory with size `mem_size`.
 macro_rules! bounds_check {
     ($offset:expr, $size:expr, $mem_size:expr) => {
@@ -558,6 +566,18 @@ impl ExclusiveSharedMemory {
     #[cfg(target_os = "linux")]
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
     pub fn new(min_size_bytes: usize) -> Result<Self> {
+        Self::new_aligned(min_size_bytes, PAGE_SIZE_USIZE)
+    }
+
+    /// Create a new region of shared memory as [`Self::new`] does, whose
+    /// usable memory starts on an `align` boundary. `align` must be a
+    /// power of two and a multiple of the page size.
+    ///
+    /// This is used to back memory with [`HUGE_PAGE_SIZE`] pages, which
+    /// the kernel can only use for aligned parts of a mapping.
+    #[cfg(target_os = "linux")]
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn new_aligned(min_size_bytes: usize, align: usize) -> Result<Self> {
         use libc::{
             MAP_ANONYMOUS, MAP_FAILED, MAP_PRIVATE, PROT_READ, PROT_WRITE, c_int, mmap, off_t,
             size_t,
@@ -580,11 +600,25 @@ impl ExclusiveSharedMemory {
             ));
         }
 
+        if !align.is_power_of_two() || align < PAGE_SIZE_USIZE {
+            return Err(new_error!(
+                "shared memory alignment {} is not a power of two multiple of {}",
+                align,
+                PAGE_SIZE_USIZE
+            ));
+        }
+        // Map enough extra memory that the usable memory can be moved up
+        // to an `align` boundary, the excess is unmapped below.
+        let extra = align - PAGE_SIZE_USIZE;
+        let mapped_size = total_size
+            .checked_add(extra)
+            .ok_or_else(|| new_error!("Memory required for sandbox exceeded usize::MAX"))?;
+
         // usize and isize are guaranteed to be the same size, and
         // isize::MAX should be positive, so this cast should be safe.
-        if total_size > isize::MAX as usize {
+        if mapped_size > isize::MAX as usize {
             return Err(HyperlightError::MemoryRequestTooBig(
-                total_size,
+                mapped_size,
                 isize::MAX as usize,
             ));
         }
@@ -598,7 +632,7 @@ impl ExclusiveSharedMemory {
         let addr = unsafe {
             mmap(
                 null_mut(),
-                total_size as size_t,
+                mapped_size as size_t,
                 PROT_READ | PROT_WRITE,
                 flags,
                 -1 as c_int,
@@ -610,11 +644,41 @@ impl ExclusiveSharedMemory {
                 Error::last_os_error().raw_os_error()
             ));
         }
-        let mmap = Mmap {
+        let mut mmap = Mmap {
             base: addr,
-            len: total_size,
+            len: mapped_size,
         };
 
+        // unmap the excess before and after the aligned memory
+        if extra > 0 {
+            let head = (align - (addr as usize + PAGE_SIZE_USIZE) % align) % align;
+            let tail = extra - head;
+            // SAFETY: `head` and `tail` are multiples of the page size, and
+            // the ranges they cover lie within the mapping created above,
+            // which `mmap` is updated to no longer include.
+            unsafe {
+                if head > 0 {
+                    if libc::munmap(mmap.base, head) != 0 {
+                        return Err(HyperlightError::MmapFailed(
+                            Error::last_os_error().raw_os_error(),
+                        ));
+                    }
+                    mmap.base = (mmap.base as *mut u8).add(head) as *mut c_void;
+                    mmap.len -= head;
+                }
+                if tail > 0 {
+                    if libc::munmap((mmap.base as *mut u8).add(total_size) as *mut c_void, tail)
+                        != 0
+                    {
+                        return Err(HyperlightError::MmapFailed(
+                            Error::last_os_error().raw_os_error(),
+                        ));
+                    }
+                    mmap.len = total_size;
+                }
+            }
+        }
+
         // protect the guard pages
         #[cfg(not(miri))]
         {
@@ -773,6 +837,56 @@ impl ExclusiveSharedMemory {
         })
     }
 
+    /// Ask the host to back this memory with transparent huge pages, and
+    /// return the size of the pages requested: [`HUGE_PAGE_SIZE`] if the
+    /// host agreed, or [`PAGE_SIZE_USIZE`] if it does not support huge
+    /// pages, in which case a warning is logged.
+    ///
+    /// Huge pages only back the parts of the memory that are aligned to
+    /// them, see [`Self::new_aligned`], and only once they are touched, so
+    /// the kernel may still use small pages for some of it.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn advise_huge_pages(&self) -> usize {
+        #[cfg(all(target_os = "linux", not(miri)))]
+        {
+            // madvise succeeds even when transparent huge pages are
+            // disabled, so check that first
+            let enabled = std::fs::read_to_string(TRANSPARENT_HUGE_PAGES_ENABLED)
+                .is_ok_and(|mode| !mode.contains("[never]"));
+            if !enabled {
+                tracing::warn!(
+                    "Transparent huge pages are not enabled on this host, using {} byte pages",
+                    PAGE_SIZE_USIZE
+                );
+                return PAGE_SIZE_USIZE;
+            }
+            let ret = unsafe {
+                libc::madvise(
+                    self.base_ptr() as *mut c_void,
+                    self.mem_size(),
+                    libc::MADV_HUGEPAGE,
+                )
+            };
+            if ret != 0 {
+                tracing::warn!(
+                    "Failed to back sandbox memory with huge pages, using {} byte pages: {}",
+                    PAGE_SIZE_USIZE,
+                    Error::last_os_error()
+                );
+                return PAGE_SIZE_USIZE;
+            }
+            HUGE_PAGE_SIZE
+        }
+        #[cfg(not(all(target_os = "linux", not(miri))))]
+        {
+            tracing::warn!(
+                "Huge pages are not supported on this platform, using {} byte pages",
+                PAGE_SIZE_USIZE
+            );
+            PAGE_SIZE_USIZE
+        }
+    }
+
     /// Internal helper method to get the backing memory as a mutable slice.
     ///
     /// # Safety
@@ -1297,6 +1411,47 @@ impl HostSharedMemory {
         Ok(())
     }
 
+    /// Atomically load a `u16` at `offset` with acquire ordering.
+    ///
+    /// Unlike [`read`](Self::read) this cannot tear, so it is suitable
+    /// for values the guest may be updating concurrently.
+    pub(crate) fn load_acquire_u16(&self, offset: usize) -> Result<u16> {
+        bounds_check!(offset, size_of::<u16>(), self.mem_size());
+        let ptr = self.base_ptr().wrapping_add(offset) as *mut u16;
+        if !ptr.is_aligned() {
+            return Err(new_error!("Unaligned atomic load at offset {}", offset));
+        }
+        let guard = self
+            .lock
+            .try_read()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        // SAFETY: the pointer is in bounds and aligned, and atomic
+        // accesses are sound even if the guest accesses the same memory
+        // concurrently.
+        let value = unsafe { std::sync::atomic::AtomicU16::from_ptr(ptr) }
+            .load(std::sync::atomic::Ordering::Acquire);
+        drop(guard);
+        Ok(value)
+    }
+
+    /// Atomically store a `u16` at `offset` with release ordering.
+    pub(crate) fn store_release_u16(&self, offset: usize, value: u16) -> Result<()> {
+        bounds_check!(offset, size_of::<u16>(), self.mem_size());
+        let ptr = self.base_ptr().wrapping_add(offset) as *mut u16;
+        if !ptr.is_aligned() {
+            return Err(new_error!("Unaligned atomic store at offset {}", offset));
+        }
+        let guard = self
+            .lock
+            .try_read()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        // SAFETY: see `load_acquire_u16`
+        unsafe { std::sync::atomic::AtomicU16::from_ptr(ptr) }
+            .store(value, std::sync::atomic::Ordering::Release);
+        drop(guard);
+        Ok(())
+    }
+
     /// Fill the memory in the range `[offset, offset + len)` with `value`
     #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
     pub fn fill(&mut self, value: u8, offset: usize, len: usize) -> Result<()> {
@@ -1405,6 +1560,38 @@ impl HostSharedMemory {
     where
         T: for<'b> TryFrom<&'b [u8]>,
     {
+        self.try_pop_buffer_with(
+            buffer_start_offset,
+            buffer_size,
+            None,
+            &mut Vec::new(),
+            |data| {
+                T::try_from(data).map_err(|_e| {
+                    new_error!(
+                        "pop_buffer_into: failed to convert buffer to {}",
+                        type_name::<T>()
+                    )
+                })
+            },
+        )
+    }
+
+    /// Pops the given buffer by copying the element on top of it into
+    /// `buf`, which is reused rather than reallocated when it is large
+    /// enough, and decoding it with `decode`.
+    ///
+    /// The element is only popped if `decode` succeeds. If it is larger
+    /// than `result_limit`, it is neither copied nor popped, and this fails
+    /// with [`HyperlightError::ResultTooLarge`]. The same requirements as
+    /// for [`Self::try_pop_buffer_into`] apply.
+    pub fn try_pop_buffer_with<R>(
+        &mut self,
+        buffer_start_offset: usize,
+        buffer_size: usize,
+        result_limit: Option<usize>,
+        buf: &mut Vec<u8>,
+        decode: impl FnOnce(&[u8]) -> Result<R>,
+    ) -> Result<R> {
         // get the stackpointer
         let stack_pointer_rel = self.read::<u64>(buffer_start_offset)? as usize;
 
@@ -1463,16 +1650,17 @@ impl HostSharedMemory {
                 max_element_size
             ));
         }
+        if let Some(limit) = result_limit.filter(|&limit| fb_buffer_size > limit) {
+            return Err(HyperlightError::ResultTooLarge {
+                size: fb_buffer_size,
+                limit,
+            });
+        }
 
-        let mut result_buffer = vec![0; fb_buffer_size];
-
-        self.copy_to_slice(&mut result_buffer, last_element_offset_abs)?;
-        let to_return = T::try_from(result_buffer.as_slice()).map_err(|_e| {
-            new_error!(
-                "pop_buffer_into: failed to convert buffer to {}",
-                type_name::<T>()
-            )
-        })?;
+        buf.clear();
+        buf.resize(fb_buffer_size, 0);
+        self.copy_to_slice(buf, last_element_offset_abs)?;
+        let to_return = decode(buf)?;
 
         // update the stack pointer to point to the element we just popped off since that is now free
         self.write::<u64>(buffer_start_offset, last_element_offset_rel as u64)?;
@@ -1568,7 +1756,7 @@ impl ReadonlySharedMemory {
     /// The file's length must be a non-zero multiple of `PAGE_SIZE`.
     /// `guest_mapped_size` must be a non-zero multiple of `PAGE_SIZE`
     /// no greater than the file's length.
-    #[cfg_attr(not(test), expect(dead_code))]
+    #[cfg_attr(not(any(test, target_os = "linux")), expect(dead_code))]
     pub(crate) fn from_file(file: &std::fs::File, guest_mapped_size: usize) -> Result<Self> {
         let len: usize = file
             .metadata()
@@ -1608,6 +1796,33 @@ impl ReadonlySharedMemory {
         })
     }
 
+    /// Copy this memory into `file` if the file is empty, and map it from
+    /// there, so that the processes that map the same file share its
+    /// pages. A file that is not empty must already hold exactly this
+    /// memory.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn backed_by_file(&self, file: &std::fs::File) -> Result<Self> {
+        use std::os::unix::fs::FileExt;
+
+        let contents = self.as_slice();
+        let len = file
+            .metadata()
+            .map_err(|e| new_error!("Failed to read file metadata: {}", e))?
+            .len();
+        if len == 0 {
+            file.write_all_at(contents, 0)
+                .map_err(|e| new_error!("Failed to write the shared memory backing: {}", e))?;
+        }
+
+        let backed = Self::from_file(file, self.guest_mapped_size)?;
+        if backed.as_slice() != contents {
+            return Err(new_error!(
+                "The shared memory backing holds different memory than the snapshot"
+            ));
+        }
+        Ok(backed)
+    }
+
     /// Linux: reserve `[guard][blob][guard]` as one anonymous
     /// `PROT_NONE` mapping, then `MAP_FIXED` the file over the
     /// middle slot.
@@ -1990,6 +2205,24 @@ mod tests {
         assert!(gm.is_err());
     }
 
+    /// Test that memory allocated for huge pages starts on a huge page
+    /// boundary and keeps its guard pages.
+    #[test]
+    #[cfg(all(target_os = "linux", not(miri)))]
+    fn new_aligned() {
+        use super::HUGE_PAGE_SIZE;
+
+        let eshm = ExclusiveSharedMemory::new_aligned(2 * HUGE_PAGE_SIZE, HUGE_PAGE_SIZE).unwrap();
+        assert_eq!(eshm.base_addr() % HUGE_PAGE_SIZE, 0);
+        assert_eq!(eshm.mem_size(), 2 * HUGE_PAGE_SIZE);
+        assert_eq!(
+            eshm.raw_mem_size(),
+            2 * HUGE_PAGE_SIZE + 2 * PAGE_SIZE_USIZE
+        );
+
+        assert!(ExclusiveSharedMemory::new_aligned(PAGE_SIZE_USIZE, 3 * PAGE_SIZE_USIZE).is_err());
+    }
+
     #[test]
     fn clone() {
         let eshm = ExclusiveSharedMemory::new(PAGE_SIZE_USIZE).unwrap();
@@ -0,0 +1,159 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+use hyperlight_common::vmem::PAGE_SIZE;
+
+use super::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
+use super::shared_mem::{ExclusiveSharedMemory, GuestSharedMemory, HostSharedMemory, SharedMemory};
+use crate::{Result, new_error};
+
+/// Host memory that can be mapped read-write into several sandboxes at
+/// once with
+/// [`MultiUseSandbox::map_shared_mut`](crate::MultiUseSandbox::map_shared_mut),
+/// as a scratchpad shared by cooperating guests and the host.
+///
+/// Clones share the same memory, which stays alive for as long as any
+/// clone, or any sandbox it is mapped into, does. Nothing synchronises the
+/// accesses to it: the guests and the host must do that themselves, for
+/// example with atomic operations such as those of
+/// [`atomic_u64`](Self::atomic_u64).
+#[derive(Clone, Debug)]
+pub struct SharedMutRegion {
+    memory: Arc<GuestSharedMemory>,
+    host: HostSharedMemory,
+}
+
+impl SharedMutRegion {
+    /// Create a zeroed region of at least `size` bytes, which must not be
+    /// zero. The size is rounded up to a whole number of pages.
+    pub fn new(size: usize) -> Result<Self> {
+        if size == 0 {
+            return Err(new_error!("Cannot create an empty shared region"));
+        }
+        let size = size
+            .checked_next_multiple_of(PAGE_SIZE)
+            .ok_or_else(|| new_error!("Shared region of {} bytes is too large", size))?;
+        let (host, memory) = ExclusiveSharedMemory::new(size)?.build();
+        Ok(Self {
+            memory: Arc::new(memory),
+            host,
+        })
+    }
+
+    /// The size of the region in bytes, a whole number of pages.
+    pub fn size(&self) -> usize {
+        self.memory.mem_size()
+    }
+
+    /// Copy `data` into the region at `offset`.
+    ///
+    /// The copy is not atomic, so a guest reading the same bytes at the
+    /// same time may see part of it.
+    pub fn write(&self, offset: usize, data: &[u8]) -> Result<()> {
+        self.host.copy_from_slice(data, offset)
+    }
+
+    /// Copy the bytes of the region at `offset` into `data`.
+    ///
+    /// The copy is not atomic, so it may see part of a write that a guest
+    /// makes at the same time.
+    pub fn read(&self, offset: usize, data: &mut [u8]) -> Result<()> {
+        self.host.copy_to_slice(data, offset)
+    }
+
+    /// The 8 bytes of the region at `offset`, which must be 8-byte
+    /// aligned, as an [`AtomicU64`] that the host can use to synchronise
+    /// with guests that access the same bytes atomically.
+    pub fn atomic_u64(&self, offset: usize) -> Result<&AtomicU64> {
+        if offset % align_of::<AtomicU64>() != 0 {
+            return Err(new_error!(
+                "Offset {:#x} is not aligned for an AtomicU64",
+                offset
+            ));
+        }
+        if offset
+            .checked_add(size_of::<AtomicU64>())
+            .is_none_or(|end| end > self.size())
+        {
+            return Err(new_error!(
+                "Offset {:#x} is out of bounds of a shared region of {:#x} bytes",
+                offset,
+                self.size()
+            ));
+        }
+        // Safety: the pointer is aligned and in bounds, the memory stays
+        // alive for as long as `self` does, and an `AtomicU64` tolerates
+        // concurrent accesses, atomic ones in particular, by guests.
+        Ok(unsafe { AtomicU64::from_ptr(self.memory.base_ptr().add(offset).cast()) })
+    }
+
+    /// The read-write [`MemoryRegion`] that maps this region at
+    /// `guest_base`, which must be page-aligned.
+    pub(crate) fn memory_region(&self, guest_base: u64) -> Result<MemoryRegion> {
+        let guest_base = usize::try_from(guest_base)?;
+        if guest_base % PAGE_SIZE != 0 {
+            return Err(new_error!(
+                "Guest address {:#x} is not page-aligned",
+                guest_base
+            ));
+        }
+        let guest_end = guest_base
+            .checked_add(self.size())
+            .ok_or_else(|| new_error!("Guest address {:#x} is too large", guest_base))?;
+        Ok(MemoryRegion {
+            host_region: self.memory.host_region_base()..self.memory.host_region_end(),
+            guest_region: guest_base..guest_end,
+            flags: MemoryRegionFlags::READ | MemoryRegionFlags::WRITE,
+            region_type: MemoryRegionType::Heap,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    #[test]
+    fn read_write_and_atomics() {
+        let region = SharedMutRegion::new(10).unwrap();
+        assert_eq!(region.size(), PAGE_SIZE);
+        assert!(SharedMutRegion::new(0).is_err());
+
+        let clone = region.clone();
+        region.write(8, b"hello").unwrap();
+        let mut data = [0; 5];
+        clone.read(8, &mut data).unwrap();
+        assert_eq!(&data, b"hello");
+
+        clone.atomic_u64(16).unwrap().fetch_add(3, Ordering::SeqCst);
+        assert_eq!(region.atomic_u64(16).unwrap().load(Ordering::SeqCst), 3);
+        assert!(region.atomic_u64(4).is_err());
+        assert!(region.atomic_u64(PAGE_SIZE - 4).is_err());
+        assert!(region.atomic_u64(PAGE_SIZE).is_err());
+
+        let rgn = region.memory_region(0x1_0000_0000).unwrap();
+        assert_eq!(
+            rgn.flags,
+            MemoryRegionFlags::READ | MemoryRegionFlags::WRITE
+        );
+        assert!(region.memory_region(0x1_0000_0001).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use hyperlight_common::shared_ring::SharedRing;
+use hyperlight_common::virtq::MemOps;
+
+use super::shared_mem::HostSharedMemory;
+use crate::{HyperlightError, Result, new_error};
+
+/// The host's view of a sandbox's shared ring, see
+/// [`MultiUseSandbox::shared_ring`](crate::MultiUseSandbox::shared_ring).
+pub type HostSharedRing = SharedRing<SharedRingMemory>;
+
+/// [`MemOps`] over the host mapping of a sandbox's scratch memory, where
+/// addresses are offsets into the scratch region.
+#[derive(Clone, Debug)]
+pub struct SharedRingMemory {
+    scratch: HostSharedMemory,
+}
+
+impl SharedRingMemory {
+    pub(crate) fn new(scratch: HostSharedMemory) -> Self {
+        Self { scratch }
+    }
+}
+
+// SAFETY: all accesses go through the bounds-checked `HostSharedMemory`
+// accessors, and the u16 accessors are atomic.
+unsafe impl MemOps for SharedRingMemory {
+    type Error = HyperlightError;
+
+    fn read(&self, addr: u64, dst: &mut [u8]) -> Result<()> {
+        self.scratch.copy_to_slice(dst, addr as usize)
+    }
+
+    fn write(&self, addr: u64, src: &[u8]) -> Result<()> {
+        self.scratch.copy_from_slice(src, addr as usize)
+    }
+
+    fn load_acquire(&self, addr: u64) -> Result<u16> {
+        self.scratch.load_acquire_u16(addr as usize)
+    }
+
+    fn store_release(&self, addr: u64, val: u16) -> Result<()> {
+        self.scratch.store_release_u16(addr as usize, val)
+    }
+
+    // The guest may write to scratch memory at any time, so the host never
+    // hands out references into it.
+    unsafe fn as_slice(&self, _addr: u64, _len: usize) -> Result<&[u8]> {
+        Err(new_error!(
+            "Shared ring memory cannot be borrowed by the host"
+        ))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn as_mut_slice(&self, _addr: u64, _len: usize) -> Result<&mut [u8]> {
+        Err(new_error!(
+            "Shared ring memory cannot be borrowed by the host"
+        ))
+    }
+}
@@ -35,6 +35,10 @@ pub(crate) static METRIC_GUEST_FUNC_DURATION: &str = "guest_call_duration_second
 #[cfg(feature = "function_call_metrics")]
 pub(crate) static METRIC_HOST_FUNC_DURATION: &str = "host_call_duration_seconds";
 
+// Histogram metric that measures the number of host function calls made by each guest function call
+#[cfg(feature = "function_call_metrics")]
+pub(crate) static METRIC_GUEST_CALL_HOST_CALLS: &str = "guest_call_host_calls";
+
 /// If the the `function_call_metrics` feature is enabled, this function measures
 /// the time it takes to execute the given closure, and will then emit a guest call metric
 /// with the given function name.
@@ -89,6 +93,20 @@ pub(crate) fn maybe_time_and_emit_host_call<T, F: FnOnce() -> T>(
     }
 }
 
+/// If the the `function_call_metrics` feature is enabled, this function emits the
+/// number of host function calls made by a call to the guest function with the given name.
+pub(crate) fn maybe_emit_guest_call_host_calls(
+    #[allow(unused_variables)] name: &str,
+    #[allow(unused_variables)] host_calls: u64,
+) {
+    #[cfg(feature = "function_call_metrics")]
+    {
+        static LABEL_GUEST_FUNC_NAME: &str = "function_name";
+        metrics::histogram!(METRIC_GUEST_CALL_HOST_CALLS, LABEL_GUEST_FUNC_NAME => name.to_string())
+            .record(host_calls as f64);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::thread;
@@ -138,7 +156,7 @@ mod tests {
             if #[cfg(feature = "function_call_metrics")] {
                 use metrics::Label;
 
-                let expected_num_metrics = 4;
+                let expected_num_metrics = 6;
 
                 // Verify that the histogram metrics are recorded correctly
                 assert_eq!(snapshot.len(), expected_num_metrics);
@@ -186,6 +204,24 @@ mod tests {
                     ),
                     "Histogram metric does not match expected value"
                 );
+
+                // 4. Host calls made by the guest call
+                let histogram_key = CompositeKey::new(
+                    metrics_util::MetricKind::Histogram,
+                    Key::from_parts(
+                        METRIC_GUEST_CALL_HOST_CALLS,
+                        vec![Label::new("function_name", "PrintOutput")],
+                    ),
+                );
+                let histogram_value = &snapshot.get(&histogram_key).unwrap().2;
+                assert!(
+                    matches!(
+                        histogram_value,
+                        metrics_util::debugging::DebugValue::Histogram(histogram)
+                            if histogram.len() == 1 && histogram[0].into_inner() == 1.0
+                    ),
+                    "Histogram metric does not match expected value"
+                );
             } else {
                 // Verify that the counter metrics are recorded correctly
                 assert_eq!(snapshot.len(), 1);
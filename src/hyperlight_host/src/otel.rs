@@ -0,0 +1,172 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Exporting guest function calls as OpenTelemetry spans.
+//!
+//! Each call to a guest function made through [`MultiUseSandbox`](crate::MultiUseSandbox)
+//! runs in a `tracing` span. With the layer returned by [`init_otel_layer`]
+//! installed, these spans are exported to OpenTelemetry with the following
+//! attributes:
+//!
+//! - `hyperlight.function_name`: the name of the guest function
+//! - `hyperlight.duration_us`: how long the call took, in microseconds
+//! - `hyperlight.result_size`: the size in bytes of the value the function
+//!   returned
+//! - `hyperlight.error_category`: what kind of error the call failed with,
+//!   only set if it failed
+
+use std::time::Duration;
+
+use hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue;
+use opentelemetry::trace::Tracer;
+use tracing::{Span, Subscriber};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt, PreSampledTracer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{HyperlightError, Result};
+
+/// Returns a `tracing` layer that exports spans, including those of guest
+/// function calls, to OpenTelemetry through `tracer`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use opentelemetry::trace::TracerProvider;
+/// use tracing_subscriber::layer::SubscriberExt;
+/// use tracing_subscriber::util::SubscriberInitExt;
+///
+/// let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder().build();
+/// tracing_subscriber::registry()
+///     .with(hyperlight_host::otel::init_otel_layer(provider.tracer("my-host")))
+///     .init();
+/// ```
+pub fn init_otel_layer<S, T>(tracer: T) -> OpenTelemetryLayer<S, T>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    T: Tracer + PreSampledTracer + 'static,
+{
+    OpenTelemetryLayer::new(tracer)
+}
+
+/// Adds the attributes describing a guest function call, and its outcome,
+/// to the current span.
+pub(crate) fn record_guest_call(
+    function_name: &str,
+    duration: Duration,
+    result: &Result<ReturnValue>,
+) {
+    let span = Span::current();
+    span.set_attribute("hyperlight.function_name", function_name.to_string());
+    span.set_attribute(
+        "hyperlight.duration_us",
+        i64::try_from(duration.as_micros()).unwrap_or(i64::MAX),
+    );
+    match result {
+        Ok(value) => span.set_attribute("hyperlight.result_size", result_size(value) as i64),
+        Err(e) => span.set_attribute("hyperlight.error_category", error_category(e)),
+    }
+}
+
+/// The size in bytes of the data in `value`.
+fn result_size(value: &ReturnValue) -> usize {
+    match value {
+        ReturnValue::Int(_) | ReturnValue::UInt(_) | ReturnValue::Float(_) => 4,
+        ReturnValue::Long(_) | ReturnValue::ULong(_) | ReturnValue::Double(_) => 8,
+        ReturnValue::Bool(_) => 1,
+        ReturnValue::Void(()) | ReturnValue::Optional(None) => 0,
+        ReturnValue::String(s) => s.len(),
+        ReturnValue::VecBytes(v) => v.len(),
+        ReturnValue::VecFloat(v) => v.len() * 4,
+        ReturnValue::VecDouble(v) => v.len() * 8,
+        ReturnValue::MultiBuffer(v) => v.iter().map(Vec::len).sum(),
+        ReturnValue::StatusWithPayload { data, .. } => 4 + data.len(),
+        ReturnValue::Optional(Some(value)) => result_size(value),
+    }
+}
+
+/// A short, stable name for the kind of error a guest function call failed
+/// with, suitable for grouping failed calls.
+fn error_category(error: &HyperlightError) -> &'static str {
+    match error {
+        HyperlightError::GuestError(..)
+        | HyperlightError::GuestAborted(..)
+        | HyperlightError::GuestAssertionFailed { .. }
+        | HyperlightError::GuestFpException { .. } => "guest_error",
+        HyperlightError::ExecutionCanceledByHost() => "cancelled",
+        HyperlightError::GuestCallTimedOut { .. } => "timeout",
+        HyperlightError::MemoryAccessViolation(..)
+        | HyperlightError::ExecutionAccessViolation(..)
+        | HyperlightError::SelfModifyingCode { .. } => "memory_access_violation",
+        HyperlightError::GuestAllocationLimitExceeded { .. }
+        | HyperlightError::HostCallLimitExceeded { .. }
+        | HyperlightError::RateLimited { .. }
+        | HyperlightError::ReentrantCallDepthExceeded(..)
+        | HyperlightError::ResultTooLarge { .. } => "limit_exceeded",
+        HyperlightError::HostFunctionFailed(..)
+        | HyperlightError::HostFunctionNotFound(..)
+        | HyperlightError::HostCallbackUnavailable(..) => "host_function",
+        HyperlightError::PoisonedSandbox => "poisoned",
+        _ => "internal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+
+    use super::*;
+
+    #[test]
+    fn result_sizes() {
+        assert_eq!(result_size(&ReturnValue::Void(())), 0);
+        assert_eq!(result_size(&ReturnValue::Int(1)), 4);
+        assert_eq!(result_size(&ReturnValue::Double(1.0)), 8);
+        assert_eq!(result_size(&ReturnValue::String("hello".to_string())), 5);
+        assert_eq!(result_size(&ReturnValue::VecBytes(vec![0; 3])), 3);
+        assert_eq!(
+            result_size(&ReturnValue::Optional(Some(Box::new(ReturnValue::Long(1))))),
+            8
+        );
+        assert_eq!(result_size(&ReturnValue::Optional(None)), 0);
+    }
+
+    #[test]
+    fn error_categories() {
+        assert_eq!(
+            error_category(&HyperlightError::GuestError(
+                ErrorCode::GuestError,
+                "oops".to_string()
+            )),
+            "guest_error"
+        );
+        assert_eq!(
+            error_category(&HyperlightError::ExecutionCanceledByHost()),
+            "cancelled"
+        );
+        assert_eq!(
+            error_category(&HyperlightError::HostCallLimitExceeded { limit: 1 }),
+            "limit_exceeded"
+        );
+        assert_eq!(
+            error_category(&HyperlightError::PoisonedSandbox),
+            "poisoned"
+        );
+        assert_eq!(
+            error_category(&HyperlightError::Error("oops".to_string())),
+            "internal"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Statistics of the guest's heap allocator, see
+//! [`MultiUseSandbox::guest_allocator_stats`].
+
+use hyperlight_common::allocator_stats::{GUEST_ALLOCATOR_STATS_FUNCTION, GuestAllocatorStats};
+use tracing::{Span, instrument};
+
+use crate::{MultiUseSandbox, Result, new_error};
+
+impl MultiUseSandbox {
+    /// Statistics of the guest's heap allocator, including the largest
+    /// free block, to tell whether a guest that ran out of memory did so
+    /// because its heap was full or because its free memory was too
+    /// fragmented for the allocation that failed.
+    ///
+    /// The statistics are reported by a built-in guest function that is
+    /// only included in guests built with the `allocator_stats` feature of
+    /// `hyperlight-guest-bin`, to keep it out of minimal guests. For other
+    /// guests this fails with a [`crate::HyperlightError::GuestError`] with
+    /// `ErrorCode::GuestFunctionNotFound`. A guest whose heap is in
+    /// [`AllocMode::Bump`](crate::sandbox::AllocMode::Bump) does not use the
+    /// general allocator, which reports an empty heap.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let stats = sandbox.guest_allocator_stats()?;
+    /// println!(
+    ///     "{} of {} bytes free, largest free block {} bytes",
+    ///     stats.free_bytes(),
+    ///     stats.total_bytes,
+    ///     stats.largest_free_block
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_allocator_stats(&mut self) -> Result<GuestAllocatorStats> {
+        let bytes: Vec<u8> = self
+            .without_call_rate_limit(|sandbox| sandbox.call(GUEST_ALLOCATOR_STATS_FUNCTION, ()))?;
+        GuestAllocatorStats::from_bytes(&bytes).ok_or_else(|| {
+            new_error!(
+                "The guest returned {} bytes of allocator statistics",
+                bytes.len()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn guest_allocator_stats() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let stats = sandbox.guest_allocator_stats().unwrap();
+
+        assert!(stats.total_bytes > 0, "{stats:?}");
+        assert!(stats.allocated_bytes >= stats.requested_bytes, "{stats:?}");
+        assert!(stats.largest_free_block > 0, "{stats:?}");
+        assert!(stats.largest_free_block <= stats.free_bytes(), "{stats:?}");
+        assert!(stats.largest_free_block.is_power_of_two(), "{stats:?}");
+        assert!((0.0..1.0).contains(&stats.fragmentation()), "{stats:?}");
+    }
+}
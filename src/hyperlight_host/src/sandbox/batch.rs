@@ -0,0 +1,167 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Running a sequence of guest function calls as a batch, optionally as an
+//! all-or-nothing transaction, see [`MultiUseSandbox::call_batch`].
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+use tracing::{Span, instrument};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{HyperlightError, MultiUseSandbox, Result};
+
+/// A guest function call in a batch.
+#[derive(Clone, Debug)]
+pub struct BatchCall {
+    function_name: String,
+    return_type: ReturnType,
+    args: Vec<ParameterValue>,
+}
+
+impl BatchCall {
+    /// A call to the guest function `function_name` with `args`, which
+    /// returns an `Output`.
+    pub fn new<Output: SupportedReturnType>(
+        function_name: impl Into<String>,
+        args: impl ParameterTuple,
+    ) -> Self {
+        Self {
+            function_name: function_name.into(),
+            return_type: Output::TYPE,
+            args: args.into_value(),
+        }
+    }
+}
+
+/// How a batch handles a call that fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Stop at the first call that fails, keeping the effects of the calls
+    /// that completed before it.
+    #[default]
+    StopOnError,
+    /// Run the batch as a transaction: stop at the first call that fails,
+    /// including calls that are cancelled or interrupted, and restore the
+    /// sandbox to its state before the batch, so that either every call in
+    /// the batch takes effect or none do.
+    Atomic,
+}
+
+/// The outcome of [`MultiUseSandbox::call_batch`].
+#[derive(Debug)]
+pub struct BatchResult {
+    /// The results of the calls that completed, in batch order.
+    pub results: Vec<ReturnValue>,
+    /// The error of the call that stopped the batch, if it did not run to
+    /// completion.
+    pub error: Option<HyperlightError>,
+    /// Whether the sandbox was restored to its state before the batch, in
+    /// which case none of the completed calls took effect.
+    pub rolled_back: bool,
+}
+
+impl BatchResult {
+    /// The number of calls that completed before the batch stopped.
+    pub fn completed(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Whether every call in the batch completed.
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl MultiUseSandbox {
+    /// Calls each guest function in `calls` in order, stopping at the first
+    /// call that fails.
+    ///
+    /// With [`BatchMode::Atomic`] a snapshot is taken before the first call,
+    /// and if any call fails, including because it was cancelled through
+    /// [`InterruptHandle::kill`](crate::hypervisor::InterruptHandle::kill),
+    /// the sandbox is restored from it, which also recovers the sandbox if
+    /// the failed call poisoned it.
+    ///
+    /// Errors from the calls themselves are reported in the returned
+    /// [`BatchResult`]; this only returns an error if the batch could not be
+    /// started, or could not be rolled back.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::{BatchCall, BatchMode};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let batch = sandbox.call_batch(
+    ///     [
+    ///         BatchCall::new::<i32>("Debit", ("alice".to_string(), 10)),
+    ///         BatchCall::new::<i32>("Credit", ("bob".to_string(), 10)),
+    ///     ],
+    ///     BatchMode::Atomic,
+    /// )?;
+    /// if let Some(e) = batch.error {
+    ///     println!("transfer rolled back after {} calls: {e}", batch.completed());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, calls), parent = Span::current())]
+    pub fn call_batch(
+        &mut self,
+        calls: impl IntoIterator<Item = BatchCall>,
+        mode: BatchMode,
+    ) -> Result<BatchResult> {
+        if self.poisoned() {
+            return Err(HyperlightError::PoisonedSandbox);
+        }
+        let snapshot = match mode {
+            BatchMode::StopOnError => None,
+            BatchMode::Atomic => Some(self.snapshot()?),
+        };
+
+        let mut results = Vec::new();
+        let mut error = None;
+        for call in calls {
+            match self.call_dynamic(&call.function_name, call.return_type, call.args) {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let rolled_back = match (snapshot, &error) {
+            (Some(snapshot), Some(_)) => {
+                self.restore(snapshot)?;
+                true
+            }
+            _ => false,
+        };
+        Ok(BatchResult {
+            results,
+            error,
+            rolled_back,
+        })
+    }
+}
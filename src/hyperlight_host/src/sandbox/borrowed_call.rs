@@ -0,0 +1,126 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Passing guest functions borrowed byte slices, see
+//! [`MultiUseSandbox::call_borrowed`].
+
+use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use hyperlight_common::flatbuffer_wrappers::util::estimate_borrowed_flatbuffer_capacity;
+use tracing::{Span, instrument};
+
+use super::initialized_multi_use::read_return_value;
+use crate::func::{BorrowedParameterTuple, SupportedReturnType};
+use crate::metrics::maybe_time_and_emit_guest_call;
+use crate::{MultiUseSandbox, Result};
+
+impl MultiUseSandbox {
+    /// Calls a guest function as [`call`](Self::call) does, with arguments
+    /// that may include `&[u8]`s for its `Vec<u8>` parameters.
+    ///
+    /// The bytes of a `&[u8]` argument are encoded straight into the
+    /// guest's input buffer from the borrow, so the host does not need to
+    /// copy them into an owned `Vec<u8>` first. The arguments are only
+    /// borrowed until the call has been encoded, which happens before this
+    /// returns, so any slice that outlives the call can be passed.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let input = std::fs::read("input.bin")?;
+    /// let reversed: Vec<u8> = sandbox.call_borrowed("ReverseBytes", input.as_slice())?;
+    /// let position: Option<u32> = sandbox.call_borrowed("FindByte", (&input[..16], 0u32))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_borrowed<'a, Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl BorrowedParameterTuple<'a>,
+    ) -> Result<Output> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+
+        let args = args.into_borrowed_values();
+        let estimated_capacity = estimate_borrowed_flatbuffer_capacity(func_name, &args);
+        let fc = FunctionCall::new(
+            func_name.to_string(),
+            None,
+            FunctionCallType::Guest,
+            Output::TYPE,
+        )
+        .with_schema_generation(self.schema_generation);
+
+        maybe_time_and_emit_guest_call(func_name, || {
+            let ret = self.call_guest_function_encoded_inner(
+                func_name,
+                estimated_capacity,
+                |builder| {
+                    fc.encode_borrowed(builder, &args);
+                },
+                None,
+                read_return_value,
+            );
+            Ok(Output::from_value(ret?)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn call_borrowed() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        let data: Vec<u8> = (0..=255).collect();
+        let res: Vec<u8> = sandbox
+            .call_borrowed("ReverseBytes", data.as_slice())
+            .unwrap();
+        assert_eq!(res, data.iter().rev().copied().collect::<Vec<_>>());
+
+        // Borrowed and owned arguments can be mixed
+        let res: Option<u32> = sandbox
+            .call_borrowed("FindByte", (&data[16..], 20u32))
+            .unwrap();
+        assert_eq!(res, Some(4));
+        let res: Option<u32> = sandbox
+            .call_borrowed("FindByte", (&data[..0], 20u32))
+            .unwrap();
+        assert_eq!(res, None);
+
+        // Owned arguments alone work as with `call`
+        let res: Vec<u8> = sandbox.call_borrowed("ReverseBytes", vec![1u8, 2]).unwrap();
+        assert_eq!(res, vec![2, 1]);
+    }
+}
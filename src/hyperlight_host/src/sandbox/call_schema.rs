@@ -0,0 +1,231 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Validating calls to a guest function once rather than on every call,
+//! see [`MultiUseSandbox::call_schema`].
+
+use std::marker::PhantomData;
+
+use hyperlight_common::builtin_functions::GUEST_FUNCTION_SCHEMA;
+use hyperlight_common::flatbuffer_wrappers::host_function_definition::HostFunctionDefinition;
+use tracing::{Span, instrument};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// The validated signature of a guest function, which lets
+/// [`MultiUseSandbox::call_validated`] call it without the guest verifying
+/// the parameters of every call.
+///
+/// A schema is tied to the generation of the guest's registered functions
+/// it was created for. If the guest registers a function after that, calls
+/// made with the schema are verified again, as [`MultiUseSandbox::call`]
+/// does, so a stale schema is never trusted. Create a new schema to skip
+/// the verification again.
+pub struct CallSchema<Output, Args> {
+    function_name: String,
+    generation: u64,
+    _signature: PhantomData<fn(Args) -> Output>,
+}
+
+impl<Output, Args> CallSchema<Output, Args> {
+    /// The name of the guest function this schema is for.
+    pub fn function_name(&self) -> &str {
+        &self.function_name
+    }
+}
+
+impl<Output, Args> Clone for CallSchema<Output, Args> {
+    fn clone(&self) -> Self {
+        Self {
+            function_name: self.function_name.clone(),
+            generation: self.generation,
+            _signature: PhantomData,
+        }
+    }
+}
+
+impl<Output, Args> std::fmt::Debug for CallSchema<Output, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallSchema")
+            .field("function_name", &self.function_name)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl MultiUseSandbox {
+    /// Validate that the guest function `func_name` takes `Args` and
+    /// returns `Output`, and return a schema to call it with
+    /// [`call_validated`](Self::call_validated).
+    ///
+    /// Only functions registered with `hyperlight_guest_bin` have a schema.
+    /// A schema does not cover the default values of a function's trailing
+    /// parameters, so `Args` must include all of its parameters.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let schema = sandbox.call_schema::<String, String>("HandleRequest")?;
+    /// for request in ["a", "b", "c"] {
+    ///     let reply = sandbox.call_validated(&schema, request.to_string())?;
+    ///     println!("{reply}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn call_schema<Output: SupportedReturnType, Args: ParameterTuple>(
+        &mut self,
+        func_name: &str,
+    ) -> Result<CallSchema<Output, Args>> {
+        let schema: Vec<u8> = self.without_call_rate_limit(|sandbox| {
+            sandbox.call(GUEST_FUNCTION_SCHEMA, func_name.to_string())
+        })?;
+        let (generation, signature) = schema
+            .split_first_chunk::<8>()
+            .ok_or_else(|| new_error!("Invalid schema of guest function {}", func_name))?;
+        let signature = HostFunctionDefinition::try_from(signature)?;
+
+        let parameter_types = signature.parameter_types.unwrap_or_default();
+        if parameter_types != Args::TYPE {
+            return Err(new_error!(
+                "Guest function {} takes {:?}, not {:?}",
+                func_name,
+                parameter_types,
+                Args::TYPE
+            ));
+        }
+        if signature.return_type != Output::TYPE {
+            return Err(new_error!(
+                "Guest function {} returns {:?}, not {:?}",
+                func_name,
+                signature.return_type,
+                Output::TYPE
+            ));
+        }
+
+        Ok(CallSchema {
+            function_name: func_name.to_string(),
+            generation: u64::from_le_bytes(*generation),
+            _signature: PhantomData,
+        })
+    }
+
+    /// Calls a guest function as [`call`](Self::call) does, with arguments
+    /// that `schema` has already validated, so that the guest does not
+    /// verify them again.
+    ///
+    /// This saves work for a host that makes many calls to the same
+    /// functions. If the guest has registered a function since `schema` was
+    /// created, the call is verified as usual.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is already poisoned before the call. Use [`restore()`](Self::restore) to recover from
+    /// a poisoned state.
+    pub fn call_validated<Output: SupportedReturnType, Args: ParameterTuple>(
+        &mut self,
+        schema: &CallSchema<Output, Args>,
+        args: Args,
+    ) -> Result<Output> {
+        self.schema_generation = schema.generation;
+        let res = self.call(&schema.function_name, args);
+        self.schema_generation = 0;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use super::CallSchema;
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn call_validated() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let schema = sandbox.call_schema::<String, String>("Echo").unwrap();
+        assert_eq!(schema.function_name(), "Echo");
+        for message in ["hello", "world"] {
+            let echoed = sandbox
+                .call_validated(&schema, message.to_string())
+                .unwrap();
+            assert_eq!(echoed, message);
+        }
+
+        // Calls without a schema are still verified
+        let res = sandbox.call::<String>("Echo", 1i32);
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(_, msg)) if msg.contains("Expected parameter type")),
+            "{res:?}"
+        );
+    }
+
+    #[test]
+    fn signature_mismatch() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        assert!(sandbox.call_schema::<String, i32>("Echo").is_err());
+        assert!(sandbox.call_schema::<i32, String>("Echo").is_err());
+        assert!(sandbox.call_schema::<(), ()>("NoSuchFunction").is_err());
+    }
+
+    #[test]
+    fn stale_schema_is_verified() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let schema = sandbox.call_schema::<String, String>("Echo").unwrap();
+
+        // A schema for the current generation skips the verification, so
+        // wrong arguments are only caught converting them
+        let forged = CallSchema::<String, i32> {
+            function_name: "Echo".to_string(),
+            generation: schema.generation,
+            _signature: PhantomData,
+        };
+        let res = sandbox.call_validated(&forged, 1);
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(_, msg)) if !msg.contains("Expected parameter type")),
+            "{res:?}"
+        );
+
+        // One for another generation, as after the guest registered a
+        // function, is verified
+        let stale = CallSchema::<String, i32> {
+            generation: schema.generation - 1,
+            ..forged
+        };
+        let res = sandbox.call_validated(&stale, 1);
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(_, msg)) if msg.contains("Expected parameter type")),
+            "{res:?}"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Making many guest calls that reuse the same serialization buffers, see
+//! [`MultiUseSandbox::call_session`].
+
+use flatbuffers::FlatBufferBuilder;
+use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use tracing::{Span, instrument};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::metrics::maybe_time_and_emit_guest_call;
+use crate::{MultiUseSandbox, Result};
+
+/// Makes guest calls on a [`MultiUseSandbox`] that reuse one buffer to
+/// encode each call and one to read back each result.
+///
+/// [`MultiUseSandbox::call`] allocates a fresh buffer for every call it
+/// encodes and every result it reads, which can show up in the profile of a
+/// hot loop of small calls. A `CallSession` keeps both buffers between
+/// calls, so once they have grown to fit the largest call and result, no
+/// further buffer allocations are needed.
+///
+/// A session borrows the sandbox mutably, and is created with
+/// [`MultiUseSandbox::call_session`].
+pub struct CallSession<'a> {
+    sandbox: &'a mut MultiUseSandbox,
+    builder: FlatBufferBuilder<'static>,
+    scratch: Vec<u8>,
+}
+
+impl MultiUseSandbox {
+    /// Starts a [`CallSession`] for making many guest calls that reuse the
+    /// same serialization buffers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let mut session = sandbox.call_session();
+    /// for i in 0..1000 {
+    ///     let _: i32 = session.call("AddToStatic", i)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_session(&mut self) -> CallSession<'_> {
+        CallSession {
+            sandbox: self,
+            builder: FlatBufferBuilder::new(),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl CallSession<'_> {
+    /// Calls a guest function as [`MultiUseSandbox::call`] does, encoding
+    /// the call and reading its result through the session's buffers.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`]
+    /// if the sandbox is currently poisoned. A call that poisons the sandbox
+    /// leaves the session usable once the sandbox has been restored.
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<Output> {
+        let sandbox = &mut *self.sandbox;
+        if sandbox.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        sandbox.snapshot = None;
+
+        let fc = FunctionCall::new(
+            func_name.to_string(),
+            Some(args.into_value()),
+            FunctionCallType::Guest,
+            Output::TYPE,
+        )
+        .with_schema_generation(sandbox.schema_generation);
+        let builder = &mut self.builder;
+        let scratch = &mut self.scratch;
+
+        maybe_time_and_emit_guest_call(func_name, || {
+            let ret = sandbox.call_guest_function_with_builder_inner(
+                func_name,
+                builder,
+                |builder| {
+                    fc.encode(builder);
+                },
+                None,
+                |mem_mgr| {
+                    Ok(mem_mgr
+                        .get_guest_function_call_result_with(scratch)?
+                        .into_inner())
+                },
+            );
+            Ok(Output::from_value(ret?)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn call_session() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        let mut session = sandbox.call_session();
+        for i in 1..=100 {
+            let res: i32 = session.call("AddToStatic", 1i32).unwrap();
+            assert_eq!(res, i);
+        }
+        // Calls of other shapes go through the same buffers
+        let res: String = session.call("Echo", "x".repeat(4096)).unwrap();
+        assert_eq!(res, "x".repeat(4096));
+        let res: i32 = session.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 100);
+
+        // Errors are reported as they are by `call`
+        let err = session.call::<i32>("NoSuchFunction", ()).unwrap_err();
+        assert!(matches!(err, HyperlightError::GuestError(..)));
+        let res: i32 = session.call("AddToStatic", 1i32).unwrap();
+        assert_eq!(res, 101);
+
+        // The sandbox is usable directly once the session is dropped
+        let res: i32 = sandbox.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 101);
+    }
+}
@@ -0,0 +1,174 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Host callbacks that are scoped to a single guest call, see
+//! [`MultiUseSandbox::call_with_callback`](crate::MultiUseSandbox::call_with_callback).
+//!
+//! While the call is in progress its callback is installed on the calling
+//! thread under a handle that is never reused, so a guest that keeps a
+//! handle after the call returns cannot reach a later call's callback.
+
+use std::cell::{Cell, RefCell};
+use std::ptr::NonNull;
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnValue};
+
+use crate::{HyperlightError, Result};
+
+type ErasedCallback<'a> = dyn FnMut(Vec<ParameterValue>) -> Result<ReturnValue> + 'a;
+
+thread_local! {
+    /// The callbacks of the calls in progress on this thread, by handle. A
+    /// callback is `None` while it is running.
+    static CALLBACKS: RefCell<Vec<(u64, Option<NonNull<ErasedCallback<'static>>>)>> =
+        const { RefCell::new(Vec::new()) };
+    /// The handle of the next callback installed on this thread.
+    static NEXT_HANDLE: Cell<u64> = const { Cell::new(1) };
+}
+
+/// Puts a callback back in [`CALLBACKS`] when dropped, or removes it if
+/// `callback` is `None`, so that this happens even if the callback or the
+/// call panics.
+struct Restore {
+    handle: u64,
+    callback: Option<NonNull<ErasedCallback<'static>>>,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        CALLBACKS.with_borrow_mut(|callbacks| {
+            let Some(index) = callbacks.iter().position(|(h, _)| *h == self.handle) else {
+                return;
+            };
+            match self.callback {
+                Some(callback) => callbacks[index].1 = Some(callback),
+                None => {
+                    callbacks.remove(index);
+                }
+            }
+        });
+    }
+}
+
+/// Runs the call `f`, passing it the handle under which the guest can
+/// invoke `callback` until `f` returns.
+pub(crate) fn with_callback<R>(callback: &mut ErasedCallback<'_>, f: impl FnOnce(u64) -> R) -> R {
+    // SAFETY: this only erases the lifetime of `callback`. The pointer is
+    // removed from `CALLBACKS` before this function returns, and `callback`
+    // is exclusively borrowed until then.
+    let callback = unsafe {
+        std::mem::transmute::<NonNull<ErasedCallback<'_>>, NonNull<ErasedCallback<'static>>>(
+            NonNull::from(callback),
+        )
+    };
+    let handle = NEXT_HANDLE.get();
+    NEXT_HANDLE.set(handle + 1);
+    CALLBACKS.with_borrow_mut(|callbacks| callbacks.push((handle, Some(callback))));
+    let _remove = Restore {
+        handle,
+        callback: None,
+    };
+    f(handle)
+}
+
+/// Invokes the callback whose handle is the first of `args`, passing it
+/// the remaining arguments.
+pub(crate) fn invoke(mut args: Vec<ParameterValue>) -> Result<ReturnValue> {
+    if args.is_empty() {
+        return Err(HyperlightError::UnexpectedNoOfArguments(0, 1));
+    }
+    let handle = match args.remove(0) {
+        ParameterValue::ULong(handle) => handle,
+        other => {
+            return Err(HyperlightError::UnexpectedParameterValueType(
+                other,
+                "ULong".to_string(),
+            ));
+        }
+    };
+
+    // Take the callback so that it cannot be reentered while it runs
+    let callback = CALLBACKS.with_borrow_mut(|callbacks| {
+        callbacks
+            .iter_mut()
+            .find(|(h, _)| *h == handle)
+            .and_then(|(_, callback)| callback.take())
+    });
+    let Some(mut callback) = callback else {
+        return Err(HyperlightError::HostCallbackUnavailable(handle));
+    };
+    let _restore = Restore {
+        handle,
+        callback: Some(callback),
+    };
+
+    // SAFETY: `callback` was installed by `with_callback`, which has not
+    // returned since its call is what invoked the callback, and it was
+    // taken out of `CALLBACKS` above so this is the only reference.
+    unsafe { callback.as_mut() }
+    (args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invokes_callback_for_handle() {
+        let mut calls = 0;
+        let mut callback = |args: Vec<ParameterValue>| {
+            calls += 1;
+            match args.as_slice() {
+                [ParameterValue::Int(i)] => Ok(ReturnValue::Int(i * 2)),
+                _ => Err(crate::new_error!("unexpected arguments")),
+            }
+        };
+        let handle = with_callback(&mut callback, |handle| {
+            let ret = invoke(vec![ParameterValue::ULong(handle), ParameterValue::Int(21)]);
+            assert!(matches!(ret, Ok(ReturnValue::Int(42))));
+            handle
+        });
+        assert_eq!(calls, 1);
+
+        // The callback is removed once the call returns
+        let ret = invoke(vec![ParameterValue::ULong(handle), ParameterValue::Int(21)]);
+        assert!(matches!(
+            ret,
+            Err(HyperlightError::HostCallbackUnavailable(h)) if h == handle
+        ));
+    }
+
+    #[test]
+    fn callback_cannot_reenter_itself() {
+        let mut callback = |mut args: Vec<ParameterValue>| match args.pop() {
+            Some(ParameterValue::ULong(handle)) => invoke(vec![
+                ParameterValue::ULong(handle),
+                ParameterValue::ULong(handle),
+            ]),
+            _ => Err(crate::new_error!("unexpected arguments")),
+        };
+        let ret = with_callback(&mut callback, |handle| {
+            invoke(vec![
+                ParameterValue::ULong(handle),
+                ParameterValue::ULong(handle),
+            ])
+        });
+        assert!(matches!(
+            ret,
+            Err(HyperlightError::HostCallbackUnavailable(_))
+        ));
+    }
+}
@@ -17,6 +17,8 @@ limitations under the License.
 use std::cmp::max;
 use std::time::Duration;
 
+use hyperlight_common::fp_exceptions::FpExceptions;
+use hyperlight_common::mem::AllocMode;
 #[cfg(target_os = "linux")]
 use libc::c_int;
 use tracing::{Span, instrument};
@@ -57,6 +59,12 @@ pub struct SandboxConfiguration {
     /// field should be represented as an `Option`, that type is not
     /// FFI-safe, so it cannot be.
     heap_size_override: u64,
+    /// The maximum size of the guest's main stack. If set to 0, the
+    /// stack may grow until scratch memory is exhausted.
+    ///
+    /// Note: as with `heap_size_override`, this is not an `Option`
+    /// because that type is not FFI-safe.
+    stack_size_override: u64,
     /// Delay between interrupt retries. This duration specifies how long to wait
     /// between attempts to send signals to the thread running the sandbox's VCPU.
     /// Multiple retries may be necessary because signals only interrupt the VCPU
@@ -74,6 +82,41 @@ pub struct SandboxConfiguration {
     interrupt_vcpu_sigrtmin_offset: u8,
     /// How much writable memory to offer the guest
     scratch_size: usize,
+    /// The size of the shared ring region carved out of scratch memory,
+    /// or 0 if the sandbox has no shared ring.
+    shared_ring_size: usize,
+    /// The maximum number of bytes a single guest function call may
+    /// allocate from the guest heap, or 0 for no limit.
+    ///
+    /// Note: as with `heap_size_override`, this is not an `Option`
+    /// because that type is not FFI-safe.
+    max_alloc_per_call: u64,
+    /// How the guest allocates from its heap.
+    alloc_mode: AllocMode,
+    /// The maximum number of host function calls a single guest function
+    /// call may make, or 0 for no limit.
+    max_host_calls_per_call: u64,
+    /// The maximum size in bytes of the result of a guest function call,
+    /// or 0 for no limit.
+    max_result_bytes: u64,
+    /// Whether to back the guest's writable (scratch) memory with huge
+    /// pages where the host supports them.
+    huge_pages: bool,
+    /// Whether the guest fails when it executes a page it has written to.
+    detect_self_modifying_code: bool,
+    /// The floating-point exceptions that are masked rather than trapping
+    /// during guest function calls.
+    fp_exception_mask: FpExceptions,
+    /// Whether to snapshot the guest right after it is initialised, for
+    /// `MultiUseSandbox::reset_to_baseline`.
+    capture_baseline: bool,
+    /// The file descriptor of the file that backs the memory of a sandbox
+    /// created from a snapshot, or -1 to back it with anonymous memory.
+    ///
+    /// Note: as with `heap_size_override`, this is not an `Option`
+    /// because that type is not FFI-safe.
+    #[cfg(target_os = "linux")]
+    shared_memory_backing: c_int,
 }
 
 impl SandboxConfiguration {
@@ -111,7 +154,19 @@ impl SandboxConfiguration {
             input_data_size: max(input_data_size, Self::MIN_INPUT_SIZE),
             output_data_size: max(output_data_size, Self::MIN_OUTPUT_SIZE),
             heap_size_override: heap_size_override.unwrap_or(0),
+            stack_size_override: 0,
             scratch_size,
+            shared_ring_size: 0,
+            max_alloc_per_call: 0,
+            alloc_mode: AllocMode::General,
+            max_host_calls_per_call: 0,
+            max_result_bytes: 0,
+            huge_pages: false,
+            detect_self_modifying_code: false,
+            fp_exception_mask: FpExceptions::all(),
+            capture_baseline: false,
+            #[cfg(target_os = "linux")]
+            shared_memory_backing: -1,
             interrupt_retry_delay,
             interrupt_vcpu_sigrtmin_offset,
             #[cfg(gdb)]
@@ -141,6 +196,16 @@ impl SandboxConfiguration {
         self.heap_size_override = heap_size;
     }
 
+    /// Set the maximum size of the guest's main stack, independently of the heap size.
+    ///
+    /// Stack pages are allocated on demand from the scratch region, so the stack must fit
+    /// in scratch memory alongside the input and output buffers; this is checked when the
+    /// sandbox is created. If set to 0, the stack is only bounded by available scratch memory.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_stack_size(&mut self, stack_size: u64) {
+        self.stack_size_override = stack_size;
+    }
+
     /// Sets the interrupt retry delay
     #[cfg(target_os = "linux")]
     pub fn set_interrupt_retry_delay(&mut self, delay: Duration) {
@@ -215,6 +280,255 @@ impl SandboxConfiguration {
         self.scratch_size = scratch_size;
     }
 
+    /// Set the size of the shared ring used to stream messages between
+    /// the host and the guest. The ring is carved out of scratch memory, so
+    /// the scratch size must leave room for it. If set to 0 (the default),
+    /// the sandbox has no shared ring.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_shared_ring_size(&mut self, shared_ring_size: usize) {
+        self.shared_ring_size = shared_ring_size;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_shared_ring_size(&self) -> usize {
+        self.shared_ring_size
+    }
+
+    /// Limit each guest function call to allocating at most `bytes` from
+    /// the guest heap. A call that exceeds the limit is stopped and fails
+    /// with [`GuestAllocationLimitExceeded`](crate::HyperlightError::GuestAllocationLimitExceeded),
+    /// instead of exhausting the heap. Memory freed during the call is
+    /// credited back, so only the memory a call holds at once counts
+    /// against the limit. If set to 0 (the default), allocations are only
+    /// bounded by the heap size.
+    ///
+    /// The limit is enforced by the guest's allocator, so it applies to
+    /// guests built with `hyperlight_guest_bin`, and does not apply while
+    /// the guest is initialised.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_max_alloc_per_call(&mut self, bytes: u64) {
+        self.max_alloc_per_call = bytes;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_max_alloc_per_call(&self) -> u64 {
+        self.max_alloc_per_call
+    }
+
+    /// Select how the guest allocates from its heap. The default,
+    /// [`AllocMode::General`], reuses the memory that is freed.
+    ///
+    /// With [`AllocMode::Bump`], each allocation is placed after the
+    /// previous one, so allocating costs little more than a pointer
+    /// increment, but freed memory is only reclaimed when the sandbox's
+    /// memory is restored, for example by
+    /// [`MultiUseSandbox::reset_to_baseline`](crate::MultiUseSandbox::reset_to_baseline)
+    /// or [`FastResetCallable`](crate::sandbox::FastResetCallable), which
+    /// resets the bump pointer along with the rest of the heap. This suits
+    /// guests that only allocate transient buffers in each call and are
+    /// reset between calls. A call must not keep anything it allocates for
+    /// later calls, since the reset reclaims it, and a guest that is not
+    /// reset runs out of heap once its calls have allocated it all.
+    ///
+    /// Memory allocated while the guest is initialised is part of the
+    /// baseline, so it is kept. The mode is applied by the guest's
+    /// allocator, so it applies to guests built with
+    /// `hyperlight_guest_bin`.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_guest_allocator(&mut self, mode: AllocMode) {
+        self.alloc_mode = mode;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_guest_allocator(&self) -> AllocMode {
+        self.alloc_mode
+    }
+
+    /// Limit each guest function call to making at most `calls` host
+    /// function calls, including those made by reentrant guest calls it
+    /// triggers. A call that exceeds the limit is stopped and fails with
+    /// [`HostCallLimitExceeded`](crate::HyperlightError::HostCallLimitExceeded),
+    /// which protects the host from guests that amplify a single call into
+    /// a flood of host work. If set to 0 (the default), the number of host
+    /// calls is not limited.
+    ///
+    /// The limit does not apply while the guest is initialised.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_max_host_calls_per_call(&mut self, calls: u64) {
+        self.max_host_calls_per_call = calls;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_max_host_calls_per_call(&self) -> u64 {
+        self.max_host_calls_per_call
+    }
+
+    /// Limit the result of each guest function call to `bytes` bytes, as
+    /// encoded in the output data buffer. A call whose result is larger
+    /// fails with
+    /// [`ResultTooLarge`](crate::HyperlightError::ResultTooLarge) before
+    /// the host copies the result out of guest memory, which protects the
+    /// host from guests that amplify a small call into a huge result. If
+    /// set to 0 (the default), results are only limited by the size of
+    /// the output data buffer.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_max_result_bytes(&mut self, bytes: u64) {
+        self.max_result_bytes = bytes;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_max_result_bytes(&self) -> Option<usize> {
+        (self.max_result_bytes != 0).then_some(self.max_result_bytes as usize)
+    }
+
+    /// Back the guest's writable (scratch) memory with huge pages, which
+    /// can make guests that touch a lot of memory faster by reducing TLB
+    /// misses. This is off by default.
+    ///
+    /// On Linux this uses transparent huge pages. Where huge pages are not
+    /// available, such as on Windows or on a host with transparent huge
+    /// pages disabled, a warning is logged and the memory is backed with
+    /// regular pages. Either way,
+    /// [`MultiUseSandbox::memory_layout`](crate::MultiUseSandbox::memory_layout)
+    /// reports the page size that was requested from the host. Only the
+    /// scratch memory is affected, the snapshot memory holding the guest's
+    /// code, heap and init data always uses regular pages.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_huge_pages(&mut self, enable: bool) {
+        self.huge_pages = enable;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_huge_pages(&self) -> bool {
+        self.huge_pages
+    }
+
+    /// Detect guests that execute code they have written, such as code
+    /// injected into the guest or a guest that modifies its own code. A
+    /// guest function call that executes a page the guest has written to
+    /// since the sandbox's memory was last restored fails with
+    /// [`SelfModifyingCode`](crate::HyperlightError::SelfModifyingCode),
+    /// carrying the address it executed. This is off by default.
+    ///
+    /// The guest's code pages start out shared with the snapshot it was
+    /// created from, and are copied into its own memory the first time it
+    /// writes to them. With detection enabled, such a copy is mapped
+    /// non-executable, so that executing it faults. Detection is per page,
+    /// so a guest that writes to data that shares a page with code and then
+    /// runs that code also fails, as does a guest that executes any other
+    /// page it can write to, such as its stack.
+    ///
+    /// Detection is done by the guest's page fault handler, so it applies
+    /// to guests built with `hyperlight_guest_bin`.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_self_modifying_code_detection(&mut self, enable: bool) {
+        self.detect_self_modifying_code = enable;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_self_modifying_code_detection(&self) -> bool {
+        self.detect_self_modifying_code
+    }
+
+    /// Select which floating-point exceptions are masked during guest
+    /// function calls. A masked exception produces the IEEE 754 default
+    /// result, such as a NaN for an invalid operation or an infinity for a
+    /// division by zero, while an exception that is not masked traps, so
+    /// that the call fails with
+    /// [`GuestFpException`](crate::HyperlightError::GuestFpException),
+    /// carrying the floating-point status. The default masks all of them.
+    ///
+    /// The mask applies to the SSE floating-point instructions that guest
+    /// code compiled for x86-64 uses for `f32` and `f64` arithmetic. It is
+    /// set by the guest at the start of each guest function call, so it
+    /// applies to guests built with `hyperlight_guest_bin`, and not while
+    /// the guest is initialised. A guest can still change the mask itself
+    /// during a call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hyperlight_host::sandbox::{FpExceptions, SandboxConfiguration};
+    /// // Trap on division by zero and invalid operations
+    /// let mut cfg = SandboxConfiguration::default();
+    /// cfg.set_fp_exception_mask(
+    ///     FpExceptions::all() - FpExceptions::DIVIDE_BY_ZERO - FpExceptions::INVALID,
+    /// );
+    /// ```
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_fp_exception_mask(&mut self, mask: FpExceptions) {
+        self.fp_exception_mask = mask;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_fp_exception_mask(&self) -> FpExceptions {
+        self.fp_exception_mask
+    }
+
+    /// Snapshot the sandbox right after the guest is initialised, so that
+    /// [`MultiUseSandbox::reset_to_baseline`](crate::MultiUseSandbox::reset_to_baseline)
+    /// can return to that state. This is off by default, because the
+    /// snapshot copies the memory the guest wrote while it was initialised,
+    /// which adds to the time it takes to create every sandbox.
+    ///
+    /// A sandbox created with
+    /// [`MultiUseSandbox::from_snapshot`](crate::MultiUseSandbox::from_snapshot)
+    /// from a snapshot of an initialised guest always uses that snapshot as
+    /// its baseline, since that costs nothing.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub fn set_capture_baseline(&mut self, enable: bool) {
+        self.capture_baseline = enable;
+    }
+
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_capture_baseline(&self) -> bool {
+        self.capture_baseline
+    }
+
+    /// Back the guest memory of a sandbox created with
+    /// [`MultiUseSandbox::from_snapshot`](crate::MultiUseSandbox::from_snapshot)
+    /// with the file open as `fd`, such as a file in `/dev/shm` or a
+    /// `memfd`, rather than with anonymous memory.
+    ///
+    /// If the file is empty, the snapshot's memory is written to it, and
+    /// otherwise it must already hold exactly that memory, for example
+    /// because another process wrote it. Every process that creates a
+    /// sandbox from the same snapshot with the same file then maps the same
+    /// pages, so a fleet of sandboxes started from a common initialised
+    /// guest shares one copy of it. The guest never writes to these pages:
+    /// its writes go to its own writable memory, so they stay private to
+    /// each sandbox.
+    ///
+    /// The file descriptor is not taken over, and only has to stay open
+    /// until the sandbox is created. This has no effect on sandboxes
+    /// created with [`UninitializedSandbox::evolve`](crate::UninitializedSandbox::evolve).
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor, and must stay open and refer
+    /// to the same file until every sandbox created from this
+    /// configuration, or a copy of it, with `from_snapshot` has been
+    /// created.
+    #[cfg(target_os = "linux")]
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub unsafe fn set_shared_memory_backing(&mut self, fd: c_int) {
+        self.shared_memory_backing = fd;
+    }
+
+    /// Back sandboxes created from this configuration with anonymous
+    /// memory, undoing [`Self::set_shared_memory_backing`].
+    #[cfg(target_os = "linux")]
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn clear_shared_memory_backing(&mut self) {
+        self.shared_memory_backing = -1;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_shared_memory_backing(&self) -> Option<c_int> {
+        (self.shared_memory_backing >= 0).then_some(self.shared_memory_backing)
+    }
+
     #[cfg(crashdump)]
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn get_guest_core_dump(&self) -> bool {
@@ -232,6 +546,13 @@ impl SandboxConfiguration {
         (self.heap_size_override > 0).then_some(self.heap_size_override)
     }
 
+    /// The maximum size of the guest's main stack, or 0 if it is only
+    /// bounded by available scratch memory.
+    #[instrument(skip_all, parent = Span::current(), level= "Trace")]
+    pub(crate) fn get_stack_size(&self) -> u64 {
+        self.stack_size_override
+    }
+
     /// If self.heap_size_override is non-zero, return it. Otherwise,
     /// return exe_info.heap_reserve()
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
@@ -287,6 +608,10 @@ mod tests {
         assert_eq!(HEAP_SIZE_OVERRIDE, heap_size);
         assert_eq!(SCRATCH_SIZE_OVERRIDE, scratch_size);
 
+        assert_eq!(0, cfg.get_stack_size());
+        cfg.set_stack_size(0x8000);
+        assert_eq!(0x8000, cfg.get_stack_size());
+
         cfg.heap_size_override = 2048;
         cfg.scratch_size = 0x40000;
         assert_eq!(2048, cfg.heap_size_override);
@@ -0,0 +1,294 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Guest functions that suspend while they wait for a result from the
+//! host, see [`MultiUseSandbox::call_suspending`].
+
+use std::time::Duration;
+
+use hyperlight_common::builtin_functions::{CANCEL_CONTINUATION, RESUME_CONTINUATION};
+use tracing::{Span, instrument};
+
+use crate::func::ParameterTuple;
+use crate::{MultiUseSandbox, Result};
+
+/// A guest function suspended while it waits for a result from the host,
+/// returned by [`MultiUseSandbox::call_suspending`].
+///
+/// The continuation lives in guest memory, so its token is only valid in
+/// the sandbox that suspended it. Dropping a token does not cancel the
+/// continuation in the guest, see [`MultiUseSandbox::cancel`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContinuationToken {
+    token: u64,
+}
+
+impl ContinuationToken {
+    /// The token the guest gave the continuation.
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+}
+
+/// What a suspended guest function did when it was
+/// [`resume`](MultiUseSandbox::resume)d.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resumed {
+    /// The guest function finished with this result.
+    Ready(Vec<u8>),
+    /// The guest function suspended again, waiting for another result
+    /// from the host.
+    Suspended(ContinuationToken),
+}
+
+impl Resumed {
+    /// What a guest function that was resumed with the continuation of
+    /// `token` did, given what the guest returned.
+    fn new(token: ContinuationToken, resumed: Option<Vec<u8>>) -> Self {
+        match resumed {
+            Some(result) => Resumed::Ready(result),
+            None => Resumed::Suspended(token),
+        }
+    }
+}
+
+impl MultiUseSandbox {
+    /// Call the suspending guest function `func_name` with `args`, and
+    /// return the token of the continuation it suspended, which the host
+    /// fulfils and passes back with [`resume`](Self::resume).
+    ///
+    /// This lets a guest written in an asynchronous style wait for work
+    /// the host does on its behalf, such as I/O, without holding the vCPU
+    /// while it waits and without guest threads. A suspending guest
+    /// function registers the rest of its work with
+    /// `hyperlight_guest_bin::continuation::suspend` and returns the
+    /// token. The guest has to cooperate: it can only suspend by
+    /// returning, so everything it needs afterwards must be captured by the
+    /// continuation or kept in guest statics.
+    ///
+    /// Between suspending and being resumed, the sandbox is idle, and the
+    /// continuation is ordinary guest state:
+    ///
+    /// - Other guest functions can be called, and other continuations
+    ///   suspended and resumed, in any order. Each continuation sees the
+    ///   guest's statics as the calls in between left them.
+    /// - [`restore()`](Self::restore) and
+    ///   [`reset_to_baseline()`](Self::reset_to_baseline) roll the
+    ///   continuation back with the rest of the guest's state: to where it
+    ///   was when the snapshot was taken, or to not existing at all if it
+    ///   was suspended after, in which case resuming it fails.
+    /// - A call that poisons the sandbox, while a continuation is
+    ///   suspended or while resuming it, leaves the continuation unusable
+    ///   until the sandbox is restored.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::Resumed;
+    /// # fn fetch(url: &[u8]) -> Vec<u8> { url.to_vec() }
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// // The guest suspends each time it needs a page fetched
+    /// let mut token = sandbox.call_suspending("Crawl", "https://example.com".to_string())?;
+    /// let mut page = fetch(b"https://example.com");
+    /// let links = loop {
+    ///     match sandbox.resume(token, page)? {
+    ///         Resumed::Ready(links) => break links,
+    ///         Resumed::Suspended(next) => {
+    ///             token = next;
+    ///             page = fetch(b"...");
+    ///         }
+    ///     }
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_suspending(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<ContinuationToken> {
+        let token = self.call::<u64>(func_name, args)?;
+        Ok(ContinuationToken { token })
+    }
+
+    /// Resume the continuation of `token` with `result`, returning the
+    /// guest function's result if it finished, or the token to resume it
+    /// with next if it suspended again.
+    ///
+    /// A continuation that finishes or fails is dropped by the guest, so
+    /// the token is taken by value. Fails if the guest does not know the
+    /// continuation, for example because the sandbox was restored to
+    /// before it was suspended.
+    ///
+    /// Resuming runs the rest of the guest function, so it takes a token
+    /// from the sandbox's
+    /// [call rate limit](Self::with_call_rate_limit) like any other call.
+    /// A resume rejected with [`crate::HyperlightError::RateLimited`]
+    /// does not run the guest, but the token is gone, so with a
+    /// [`RateLimitMode::Reject`](crate::sandbox::rate_limit::RateLimitMode::Reject)
+    /// limit check [`call_rate_limit_tokens`](Self::call_rate_limit_tokens)
+    /// before resuming.
+    #[instrument(err(Debug), skip(self, result), parent = Span::current())]
+    pub fn resume(&mut self, token: ContinuationToken, result: Vec<u8>) -> Result<Resumed> {
+        let resumed: Option<Vec<u8>> = self.call(RESUME_CONTINUATION, (token.token, result))?;
+        Ok(Resumed::new(token, resumed))
+    }
+
+    /// Like [`call_suspending`](Self::call_suspending), but cancels the
+    /// call if it runs for longer than `timeout`, as
+    /// [`call_with_timeout`](Self::call_with_timeout) does.
+    pub(crate) fn call_suspending_with_timeout(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        timeout: Duration,
+    ) -> Result<ContinuationToken> {
+        let token = self.call_with_timeout::<u64>(func_name, args, timeout)?;
+        Ok(ContinuationToken { token })
+    }
+
+    /// Like [`resume`](Self::resume), but cancels the call if it runs for
+    /// longer than `timeout`, as
+    /// [`call_with_timeout`](Self::call_with_timeout) does.
+    pub(crate) fn resume_with_timeout(
+        &mut self,
+        token: ContinuationToken,
+        result: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Resumed> {
+        let resumed: Option<Vec<u8>> =
+            self.call_with_timeout(RESUME_CONTINUATION, (token.token, result), timeout)?;
+        Ok(Resumed::new(token, resumed))
+    }
+
+    /// Drop the continuation of `token` in the guest without resuming it.
+    /// Cancelling a continuation the guest does not know is not an error.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn cancel(&mut self, token: ContinuationToken) -> Result<()> {
+        self.call(CANCEL_CONTINUATION, token.token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{ContinuationToken, Resumed};
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    fn suspended(resumed: Resumed) -> ContinuationToken {
+        match resumed {
+            Resumed::Suspended(token) => token,
+            Resumed::Ready(result) => panic!("finished with {result:?}"),
+        }
+    }
+
+    #[test]
+    fn suspend_and_resume() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let first = sandbox.call_suspending("AwaitConcat", 3i32).unwrap();
+        let second = sandbox.call_suspending("AwaitConcat", 1i32).unwrap();
+        assert_ne!(first, second);
+
+        // Continuations are resumed independently of each other, with
+        // other calls in between
+        let first = suspended(sandbox.resume(first, b"ab".to_vec()).unwrap());
+        sandbox.call::<i32>("AddToStatic", 1i32).unwrap();
+        assert_eq!(
+            sandbox.resume(second, b"x".to_vec()).unwrap(),
+            Resumed::Ready(b"x".to_vec())
+        );
+        let first = suspended(sandbox.resume(first, b"c".to_vec()).unwrap());
+        let token = first.token();
+        assert_eq!(
+            sandbox.resume(first, b"d".to_vec()).unwrap(),
+            Resumed::Ready(b"abcd".to_vec())
+        );
+
+        // The guest dropped the continuation when it finished
+        assert!(
+            sandbox
+                .resume(ContinuationToken { token }, b"e".to_vec())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn failed_and_cancelled_continuations_are_dropped() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let token = sandbox.call_suspending("AwaitConcat", 2i32).unwrap();
+        let token = suspended(sandbox.resume(token, b"a".to_vec()).unwrap());
+        let raw = token.token();
+        assert!(sandbox.resume(token, Vec::new()).is_err());
+        assert!(
+            sandbox
+                .resume(ContinuationToken { token: raw }, b"b".to_vec())
+                .is_err()
+        );
+
+        let token = sandbox.call_suspending("AwaitConcat", 2i32).unwrap();
+        let raw = token.token();
+        sandbox.cancel(token).unwrap();
+        assert!(
+            sandbox
+                .resume(ContinuationToken { token: raw }, b"b".to_vec())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn restore_rolls_back_continuations() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let before = sandbox.snapshot().unwrap();
+        let token = sandbox.call_suspending("AwaitConcat", 2i32).unwrap();
+        let raw = token.token();
+        let during = sandbox.snapshot().unwrap();
+
+        let token = suspended(sandbox.resume(token, b"a".to_vec()).unwrap());
+        assert_eq!(
+            sandbox.resume(token, b"b".to_vec()).unwrap(),
+            Resumed::Ready(b"ab".to_vec())
+        );
+        sandbox.restore(during).unwrap();
+        let token = suspended(
+            sandbox
+                .resume(ContinuationToken { token: raw }, b"c".to_vec())
+                .unwrap(),
+        );
+        assert_eq!(
+            sandbox.resume(token, b"d".to_vec()).unwrap(),
+            Resumed::Ready(b"cd".to_vec())
+        );
+
+        sandbox.restore(before).unwrap();
+        assert!(
+            sandbox
+                .resume(ContinuationToken { token: raw }, b"e".to_vec())
+                .is_err()
+        );
+    }
+}
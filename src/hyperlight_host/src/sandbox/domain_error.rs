@@ -0,0 +1,186 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Application-defined errors returned by guest functions, see
+//! [`MultiUseSandbox::call_or_domain_error`].
+
+pub use hyperlight_common::flatbuffer_wrappers::guest_error::DomainError;
+use tracing::{Span, instrument};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::metrics::maybe_time_and_emit_guest_call;
+use crate::{MultiUseSandbox, Result};
+
+/// An application's error type that guest functions can return, such as an
+/// enum whose variants are identified by [`DomainError::discriminant`].
+///
+/// ```
+/// # use hyperlight_host::{Result, new_error};
+/// # use hyperlight_host::sandbox::domain_error::{DomainError, FromDomainError};
+/// #[derive(Debug)]
+/// enum DivideError {
+///     DivisionByZero(String),
+///     Overflow,
+/// }
+///
+/// impl FromDomainError for DivideError {
+///     fn from_domain_error(error: DomainError) -> Result<Self> {
+///         match error.discriminant {
+///             0 => Ok(Self::DivisionByZero(error.message.unwrap_or_default())),
+///             1 => Ok(Self::Overflow),
+///             other => Err(new_error!("Unknown DivideError {}", other)),
+///         }
+///     }
+/// }
+/// ```
+pub trait FromDomainError: Sized {
+    /// Convert the error returned by the guest, or fail if it is not one
+    /// of this type's errors.
+    fn from_domain_error(error: DomainError) -> Result<Self>;
+}
+
+impl FromDomainError for DomainError {
+    fn from_domain_error(error: DomainError) -> Result<Self> {
+        Ok(error)
+    }
+}
+
+impl MultiUseSandbox {
+    /// Calls a guest function as [`call`](Self::call) does, returning any
+    /// application-defined error it fails with as `Ok(Err(_))`.
+    ///
+    /// A Rust guest returns such an error by converting a
+    /// [`DomainError`] into its `HyperlightGuestError`, and a C guest with
+    /// `hl_return_domain_error`. The error is converted to `Err` with
+    /// [`FromDomainError`], and the conversion failing fails the call.
+    /// Any other failure, such as the function not existing or the guest
+    /// aborting, is returned as the outer `Err`, as by
+    /// [`call`](Self::call). A domain error is an expected outcome of the
+    /// function rather than a failure of the call, so it is not counted as
+    /// a guest error in the metrics.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::domain_error::DomainError;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// match sandbox.call_or_domain_error::<i32, DomainError>("CheckedDivide", (1, 0))? {
+    ///     Ok(quotient) => println!("{quotient}"),
+    ///     Err(e) => println!("error {}: {:?}", e.discriminant, e.message),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_or_domain_error<Output: SupportedReturnType, Error: FromDomainError>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<std::result::Result<Output, Error>> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+        maybe_time_and_emit_guest_call(func_name, || {
+            let ret = self.call_guest_function_by_name_inner(
+                func_name,
+                Output::TYPE,
+                args.into_value(),
+                None,
+                |mem_mgr| {
+                    let result = mem_mgr.get_guest_function_call_result()?.into_inner();
+                    Ok(match result {
+                        Ok(value) => Ok(Ok(value)),
+                        Err(guest_error) => match DomainError::from_guest_error(&guest_error) {
+                            Some(domain_error) => Ok(Err(domain_error)),
+                            None => Err(guest_error),
+                        },
+                    })
+                },
+            )?;
+            match ret {
+                Ok(value) => Ok(Ok(Output::from_value(value)?)),
+                Err(domain_error) => Ok(Err(Error::from_domain_error(domain_error)?)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{DomainError, FromDomainError};
+    use crate::sandbox::new_simple_guest_sandbox;
+    use crate::{HyperlightError, Result, new_error};
+
+    #[derive(Debug, PartialEq)]
+    enum DivideError {
+        DivisionByZero(String),
+        Overflow,
+    }
+
+    impl FromDomainError for DivideError {
+        fn from_domain_error(error: DomainError) -> Result<Self> {
+            match error.discriminant {
+                0 => Ok(Self::DivisionByZero(error.message.unwrap_or_default())),
+                1 => Ok(Self::Overflow),
+                other => Err(new_error!("Unknown DivideError {}", other)),
+            }
+        }
+    }
+
+    #[test]
+    fn call_or_domain_error() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        let res = sandbox.call_or_domain_error::<i32, DivideError>("CheckedDivide", (7, 2));
+        assert_eq!(res.unwrap(), Ok(3));
+        let res = sandbox.call_or_domain_error::<i32, DivideError>("CheckedDivide", (7, 0));
+        assert_eq!(
+            res.unwrap(),
+            Err(DivideError::DivisionByZero("division by zero".to_string()))
+        );
+        let res = sandbox.call_or_domain_error::<i32, DomainError>("CheckedDivide", (i32::MIN, -1));
+        assert_eq!(res.unwrap(), Err(DomainError::new(1, None)));
+        assert!(!sandbox.poisoned());
+
+        // Other failures are errors of the call
+        let res = sandbox.call_or_domain_error::<i32, DivideError>("NoSuchFunction", ());
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(..))),
+            "{res:?}"
+        );
+
+        // As are domain errors to `call`
+        let res = sandbox.call::<i32>("CheckedDivide", (7, 0));
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(..))),
+            "{res:?}"
+        );
+    }
+}
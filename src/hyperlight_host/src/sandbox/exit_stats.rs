@@ -0,0 +1,198 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Counting the exits from the guest to the hypervisor during a guest
+//! function call, see [`MultiUseSandbox::exit_stats`].
+
+use std::time::Duration;
+
+use crate::MultiUseSandbox;
+
+/// Why the guest exited to the host, as counted by [`ExitStats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExitReason {
+    /// The guest wrote to an I/O port for something other than a host
+    /// function call, such as logging or debug output
+    Io,
+    /// The guest called a host function
+    HostCall,
+    /// The guest accessed memory that is not mapped, or not mapped for
+    /// that access
+    Mmio,
+    /// The guest halted, which ends a guest function call
+    Halt,
+    /// The vCPU was interrupted, e.g. by
+    /// [`InterruptHandle::kill`](crate::hypervisor::InterruptHandle::kill)
+    /// or a late signal meant for an earlier call
+    Cancelled,
+    /// Any other exit, such as a debug exit or one the hypervisor asked to
+    /// retry
+    Other,
+}
+
+impl ExitReason {
+    const ALL: [ExitReason; 6] = [
+        ExitReason::Io,
+        ExitReason::HostCall,
+        ExitReason::Mmio,
+        ExitReason::Halt,
+        ExitReason::Cancelled,
+        ExitReason::Other,
+    ];
+}
+
+/// The number of exits for one [`ExitReason`] and the time the host spent
+/// handling them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExitCounter {
+    /// The number of exits
+    pub count: u64,
+    /// The time between the guest exiting and the host being ready to
+    /// resume it, which for host calls includes the host function itself
+    pub time: Duration,
+}
+
+/// Counts and durations of the guest's exits to the host during the last
+/// guest function call, see [`MultiUseSandbox::exit_stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExitStats {
+    counters: [ExitCounter; ExitReason::ALL.len()],
+    guest_time: Duration,
+}
+
+impl ExitStats {
+    /// Count an exit for `reason`.
+    pub(crate) fn count_exit(&mut self, reason: ExitReason) {
+        self.counters[reason as usize].count += 1;
+    }
+
+    /// Add `time` spent handling an exit for `reason`.
+    pub(crate) fn add_exit_time(&mut self, reason: ExitReason, time: Duration) {
+        let counter = &mut self.counters[reason as usize];
+        counter.time = counter.time.saturating_add(time);
+    }
+
+    /// Add `time` spent running the guest.
+    pub(crate) fn add_guest_time(&mut self, time: Duration) {
+        self.guest_time = self.guest_time.saturating_add(time);
+    }
+
+    /// The exits for `reason`.
+    pub fn get(&self, reason: ExitReason) -> ExitCounter {
+        self.counters[reason as usize]
+    }
+
+    /// The exits for each reason, including those that did not happen.
+    pub fn iter(&self) -> impl Iterator<Item = (ExitReason, ExitCounter)> + '_ {
+        ExitReason::ALL
+            .into_iter()
+            .map(|reason| (reason, self.get(reason)))
+    }
+
+    /// The number of exits for all reasons.
+    pub fn exits(&self) -> u64 {
+        self.counters.iter().map(|counter| counter.count).sum()
+    }
+
+    /// The time the guest ran for, between entering it and its exits.
+    pub fn guest_time(&self) -> Duration {
+        self.guest_time
+    }
+
+    /// The time spent handling exits for all reasons.
+    pub fn exit_time(&self) -> Duration {
+        self.counters.iter().map(|counter| counter.time).sum()
+    }
+
+    /// The time spent running the guest and handling its exits, which is
+    /// most of the duration of a call.
+    pub fn total_time(&self) -> Duration {
+        self.guest_time.saturating_add(self.exit_time())
+    }
+}
+
+impl MultiUseSandbox {
+    /// Counts and durations of the guest's exits to the host, by
+    /// [`ExitReason`], during the last guest function call, to tell
+    /// whether a call spends its time in guest code or in crossing between
+    /// the guest and the host. Use it together with
+    /// [`latency_histogram`](Self::latency_histogram), which measures the
+    /// calls as a whole.
+    ///
+    /// The exits of reentrant calls made by host functions are included,
+    /// and so is the time of those calls, in the time of the host call
+    /// that made them. Exits are classified the same way on every
+    /// hypervisor, but the exits the hypervisor handles itself without
+    /// returning to Hyperlight, such as most interrupts, are not seen and
+    /// their time counts as guest time. The stats are empty before the
+    /// first call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::ExitReason;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// sandbox.call::<i32>("PrintOutput", "Hello".to_string())?;
+    /// let stats = sandbox.exit_stats();
+    /// println!(
+    ///     "{:?} in the guest, {} host calls taking {:?}",
+    ///     stats.guest_time(),
+    ///     stats.get(ExitReason::HostCall).count,
+    ///     stats.get(ExitReason::HostCall).time
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn exit_stats(&self) -> ExitStats {
+        self.vm.exit_stats().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ExitReason;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn exit_stats() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        sandbox
+            .call::<i32>("PrintOutput", "Hello".to_string())
+            .unwrap();
+        let stats = sandbox.exit_stats();
+        assert_eq!(stats.get(ExitReason::Halt).count, 1, "{stats:?}");
+        assert!(stats.get(ExitReason::HostCall).count >= 1, "{stats:?}");
+        assert_eq!(stats.get(ExitReason::Mmio).count, 0, "{stats:?}");
+        assert_eq!(
+            stats.exits(),
+            stats.iter().map(|(_, counter)| counter.count).sum::<u64>()
+        );
+        assert!(stats.total_time() >= stats.guest_time());
+
+        // The stats are those of the last call only
+        sandbox.call::<i32>("GetStatic", ()).unwrap();
+        let stats = sandbox.exit_stats();
+        assert_eq!(stats.get(ExitReason::Halt).count, 1, "{stats:?}");
+    }
+}
@@ -0,0 +1,116 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use super::Callable;
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result};
+
+/// Wraps a [`MultiUseSandbox`] so that every call starts from the state the
+/// guest was in right after it was initialised, as if each call were made
+/// on a new sandbox.
+///
+/// That state is the sandbox's baseline, see
+/// [`MultiUseSandbox::reset_to_baseline`]. A sandbox that has none uses the
+/// state it is in when it is wrapped.
+///
+/// After each call, successful or not, the sandbox is reset with
+/// [`MultiUseSandbox::reset_to_baseline`], which is the cheapest way to
+/// discard a call's changes: the baseline snapshot's memory is already
+/// mapped, so only the memory the guest can write to and its registers are
+/// reset. Because the reset also recovers a poisoned sandbox, a call that
+/// poisons the sandbox does not affect the calls after it.
+///
+/// ```no_run
+/// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+/// # use hyperlight_host::sandbox::{Callable, FastResetCallable};
+/// # fn example() -> Result<()> {
+/// let sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?;
+/// let mut sandbox = FastResetCallable::new(sandbox.evolve()?)?;
+///
+/// for request in ["first", "second"] {
+///     // Each request is handled by a freshly initialised guest
+///     let _response: String = sandbox.call("HandleRequest", request.to_string())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FastResetCallable {
+    sandbox: MultiUseSandbox,
+}
+
+impl FastResetCallable {
+    /// Wrap `sandbox`, whose baseline is the state each call starts from.
+    /// If it has no baseline, its current state becomes its baseline.
+    pub fn new(mut sandbox: MultiUseSandbox) -> Result<Self> {
+        sandbox.ensure_baseline()?;
+        Ok(Self { sandbox })
+    }
+
+    /// The wrapped sandbox.
+    pub fn sandbox(&self) -> &MultiUseSandbox {
+        &self.sandbox
+    }
+
+    /// Unwrap the sandbox.
+    pub fn into_inner(self) -> MultiUseSandbox {
+        self.sandbox
+    }
+}
+
+impl Callable for FastResetCallable {
+    fn call<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<Output> {
+        let res = Callable::call(&mut self.sandbox, func_name, args);
+        // A failure to reset is reported even if the call succeeded, since
+        // the next call would otherwise see this call's changes
+        self.sandbox.reset_to_baseline()?;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FastResetCallable;
+    use crate::sandbox::Callable;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn resets_after_each_call() {
+        let sandbox = new_simple_guest_sandbox(None);
+        let mut sandbox = FastResetCallable::new(sandbox).unwrap();
+
+        // The static counter never sees the previous call's increment
+        for _ in 0..3 {
+            let count: i32 = sandbox.call("AddToStatic", 5i32).unwrap();
+            assert_eq!(count, 5);
+        }
+
+        // A call that poisons the sandbox does not affect the next one
+        assert!(
+            sandbox
+                .call::<()>("guest_panic", "boom".to_string())
+                .is_err()
+        );
+        assert!(!sandbox.sandbox().poisoned());
+        let count: i32 = sandbox.call("AddToStatic", 5i32).unwrap();
+        assert_eq!(count, 5);
+    }
+}
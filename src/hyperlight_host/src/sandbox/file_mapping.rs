@@ -303,7 +303,10 @@ pub(crate) fn prepare_file_cow(
         };
         use windows::Win32::System::SystemServices::SECURITY_DESCRIPTOR_REVISION1;
 
-        let file = std::fs::File::options().read(true).open(file_path)?;
+        let file = std::fs::File::options()
+            .read(true)
+            .open(file_path)
+            .map_err(|e| HyperlightError::from_io(format!("opening {file_path:?}"), e))?;
         let file_size = file.metadata()?.len();
         if file_size == 0 {
             log_then_return!("map_file_cow: cannot map an empty file: {:?}", file_path);
@@ -380,7 +383,10 @@ pub(crate) fn prepare_file_cow(
     {
         use std::os::fd::AsRawFd;
 
-        let file = std::fs::File::options().read(true).open(file_path)?;
+        let file = std::fs::File::options()
+            .read(true)
+            .open(file_path)
+            .map_err(|e| crate::HyperlightError::from_io(format!("opening {file_path:?}"), e))?;
         let file_size = file.metadata()?.len();
         if file_size == 0 {
             log_then_return!("map_file_cow: cannot map an empty file: {:?}", file_path);
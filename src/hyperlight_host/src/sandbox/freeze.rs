@@ -0,0 +1,210 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Releasing the hypervisor resources of idle sandboxes, see
+//! [`MultiUseSandbox::freeze`].
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::{Span, instrument};
+
+use super::host_funcs::FunctionRegistry;
+use super::initialized_multi_use::PtRootFinder;
+use super::latency::Histogram;
+use super::rate_limit::CallRateLimiter;
+use super::snapshot::Snapshot;
+use crate::sandbox::SandboxConfiguration;
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// A sandbox whose VM has been released by [`MultiUseSandbox::freeze`],
+/// holding only its memory until it is [`thaw`](Self::thaw)ed.
+pub struct FrozenSandbox {
+    /// The state of the sandbox when it was frozen
+    snapshot: Arc<Snapshot>,
+    host_funcs: Arc<Mutex<FunctionRegistry>>,
+    config: SandboxConfiguration,
+    baseline: Option<Arc<Snapshot>>,
+    latency: HashMap<String, Histogram>,
+    pt_root_finder: Option<PtRootFinder>,
+    user_data: Option<Box<dyn Any + Send>>,
+    clear_user_data_on_reset: bool,
+    has_quiesce_handler: bool,
+    init_duration: Duration,
+    rate_limiter: Option<CallRateLimiter>,
+    state_ignored_ranges: Vec<Range<u64>>,
+    #[cfg(target_arch = "x86_64")]
+    guest_thread_names: Vec<Option<String>>,
+}
+
+impl MultiUseSandbox {
+    /// Snapshots the sandbox and releases its VM, including its vCPU and
+    /// the hypervisor's file descriptors or handles, so that an idle
+    /// sandbox holds nothing but its memory.
+    ///
+    /// The returned [`FrozenSandbox`] can be [`thaw`](FrozenSandbox::thaw)ed
+    /// into a sandbox in the same state, with a new VM, when it is needed
+    /// again. The host functions, user data, latency histograms, rate
+    /// limit, ignored state ranges, guest thread names and baseline are
+    /// kept. A sandbox in a
+    /// [`SandboxRegistry`](crate::sandbox::SandboxRegistry) leaves it when
+    /// it is frozen, since its VM can no longer be interrupted.
+    ///
+    /// Regions mapped into the sandbox are not part of its snapshots, so a
+    /// sandbox with any mapped cannot be frozen. Restore a snapshot taken
+    /// before they were mapped to unmap them first.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// sandbox.call::<i32>("AddToStatic", 5)?;
+    ///
+    /// // Keep the sandbox around without a VM while it is idle
+    /// let frozen = sandbox.freeze()?;
+    ///
+    /// let mut sandbox = frozen.thaw()?;
+    /// assert_eq!(sandbox.call::<i32>("GetStatic", ())?, 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn freeze(mut self) -> Result<FrozenSandbox> {
+        if self.has_mapped_regions() {
+            return Err(new_error!(
+                "A sandbox with regions mapped into it cannot be frozen"
+            ));
+        }
+        let snapshot = self.snapshot()?;
+        Ok(FrozenSandbox {
+            snapshot,
+            host_funcs: self.host_funcs,
+            config: self.config,
+            baseline: self.baseline,
+            latency: self.latency,
+            pt_root_finder: self.pt_root_finder,
+            user_data: self.user_data,
+            clear_user_data_on_reset: self.clear_user_data_on_reset,
+            has_quiesce_handler: self.has_quiesce_handler,
+            init_duration: self.init_duration,
+            rate_limiter: self.rate_limiter,
+            state_ignored_ranges: self.state_ignored_ranges,
+            #[cfg(target_arch = "x86_64")]
+            guest_thread_names: self.guest_thread_names,
+        })
+        // The VM and the sandbox's memory are released here
+    }
+}
+
+impl FrozenSandbox {
+    /// Creates a new VM for the sandbox and restores it to the state it
+    /// was frozen in, so that it can be called again.
+    ///
+    /// The VM is created with the configuration the sandbox was created
+    /// with, except that its memory is not backed by the file set with
+    /// `SandboxConfiguration::set_shared_memory_backing`.
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn thaw(self) -> Result<MultiUseSandbox> {
+        #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+        let mut config = self.config;
+        #[cfg(target_os = "linux")]
+        config.clear_shared_memory_backing();
+        let mut sandbox = MultiUseSandbox::from_snapshot_with_registry(
+            self.snapshot.clone(),
+            self.host_funcs,
+            config,
+        )?;
+        // The sandbox is in the state it was frozen in
+        sandbox.snapshot = Some(self.snapshot);
+        sandbox.baseline = self.baseline;
+        sandbox.latency = self.latency;
+        sandbox.pt_root_finder = self.pt_root_finder;
+        sandbox.user_data = self.user_data;
+        sandbox.clear_user_data_on_reset = self.clear_user_data_on_reset;
+        sandbox.has_quiesce_handler = self.has_quiesce_handler;
+        sandbox.init_duration = self.init_duration;
+        sandbox.rate_limiter = self.rate_limiter;
+        sandbox.state_ignored_ranges = self.state_ignored_ranges;
+        #[cfg(target_arch = "x86_64")]
+        {
+            sandbox.guest_thread_names = self.guest_thread_names;
+        }
+        Ok(sandbox)
+    }
+
+    /// The snapshot of the sandbox's state when it was frozen, which holds
+    /// its memory.
+    pub fn snapshot(&self) -> &Arc<Snapshot> {
+        &self.snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::HyperlightError;
+    use crate::sandbox::{SandboxConfiguration, new_simple_guest_sandbox};
+
+    #[test]
+    fn freeze_and_thaw() {
+        let mut config = SandboxConfiguration::default();
+        config.set_capture_baseline(true);
+        let mut sandbox = new_simple_guest_sandbox(Some(config));
+        sandbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        sandbox.set_user_data(42u32);
+        sandbox.set_ignored_state_ranges([0x1000..0x2000]);
+
+        let frozen = sandbox.freeze().unwrap();
+        let mut sandbox = frozen.thaw().unwrap();
+        assert_eq!(sandbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+        assert_eq!(sandbox.user_data::<u32>(), Some(&42));
+        assert_eq!(sandbox.state_ignored_ranges, [0x1000..0x2000]);
+        assert_eq!(sandbox.latency_histogram("AddToStatic").count(), 1);
+
+        // The baseline is still the state after initialisation
+        sandbox.reset_to_baseline().unwrap();
+        assert_eq!(sandbox.call::<i32>("GetStatic", ()).unwrap(), 0);
+
+        // A sandbox can be frozen again after it is thawed
+        sandbox.call::<i32>("AddToStatic", 2i32).unwrap();
+        let mut sandbox = sandbox.freeze().unwrap().thaw().unwrap();
+        assert_eq!(sandbox.call::<i32>("GetStatic", ()).unwrap(), 2);
+    }
+
+    #[test]
+    fn poisoned_sandbox_cannot_be_frozen() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let _ = sandbox.call::<()>("guest_panic", "boom".to_string());
+        assert!(sandbox.poisoned());
+        assert!(matches!(
+            sandbox.freeze(),
+            Err(HyperlightError::PoisonedSandbox)
+        ));
+    }
+}
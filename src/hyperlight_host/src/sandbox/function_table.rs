@@ -0,0 +1,190 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Listing the functions a guest has registered, see
+//! [`MultiUseSandbox::dump_guest_function_table`].
+
+use std::fmt;
+
+use hyperlight_common::builtin_functions::GUEST_FUNCTION_TABLE;
+use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ReturnType};
+use hyperlight_common::flatbuffer_wrappers::host_function_details::HostFunctionDetails;
+use tracing::{Span, instrument};
+
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// The signature of a function registered by the guest, as listed by
+/// [`MultiUseSandbox::dump_guest_function_table`].
+///
+/// It is displayed as, for example, `Echo(String) -> String`, with the
+/// parameters that have a default value marked with `= ..`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionSignature {
+    /// The name the function is called by
+    pub name: String,
+    /// The types of the function's parameters
+    pub parameter_types: Vec<ParameterType>,
+    /// The type of the value the function returns
+    pub return_type: ReturnType,
+    /// The number of trailing parameters that have a default value, which
+    /// callers may omit
+    pub default_count: usize,
+}
+
+impl FunctionSignature {
+    /// Whether some of the function's parameters have a default value.
+    pub fn has_defaults(&self) -> bool {
+        self.default_count > 0
+    }
+
+    /// The fewest arguments the function can be called with.
+    pub fn required_parameters(&self) -> usize {
+        self.parameter_types
+            .len()
+            .saturating_sub(self.default_count)
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, parameter_type) in self.parameter_types.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{parameter_type:?}")?;
+            if i >= self.required_parameters() {
+                write!(f, " = ..")?;
+            }
+        }
+        write!(f, ") -> {:?}", self.return_type)
+    }
+}
+
+/// Decode the table sent by the guest: the number of functions and the
+/// number of defaults of each, as 4 little-endian bytes each, followed by
+/// their signatures, encoded as a `HostFunctionDetails`.
+fn decode_function_table(table: &[u8]) -> Result<Vec<FunctionSignature>> {
+    let invalid = || new_error!("Invalid guest function table");
+    let (count, mut rest) = table.split_first_chunk::<4>().ok_or_else(invalid)?;
+    let mut default_counts = Vec::new();
+    for _ in 0..u32::from_le_bytes(*count) {
+        let (default_count, tail) = rest.split_first_chunk::<4>().ok_or_else(invalid)?;
+        default_counts.push(u32::from_le_bytes(*default_count) as usize);
+        rest = tail;
+    }
+    let signatures = HostFunctionDetails::try_from(rest)?
+        .host_functions
+        .unwrap_or_default();
+    if signatures.len() != default_counts.len() {
+        return Err(invalid());
+    }
+    Ok(signatures
+        .into_iter()
+        .zip(default_counts)
+        .map(|(signature, default_count)| FunctionSignature {
+            name: signature.function_name,
+            parameter_types: signature.parameter_types.unwrap_or_default(),
+            return_type: signature.return_type,
+            default_count,
+        })
+        .collect())
+}
+
+impl MultiUseSandbox {
+    /// The signatures of all the functions the guest has registered, in
+    /// order of their names, for diagnostics and tooling.
+    ///
+    /// This includes the functions registered while the guest was
+    /// initialised, those it has registered since, and the built-in guest
+    /// functions of `hyperlight-guest-bin`. It lists the functions of
+    /// guests built with `hyperlight-guest-bin`; the functions a C guest
+    /// registers with `hl_register_function_definition` are kept apart
+    /// and are not listed. Each signature can be displayed for people to
+    /// read, and [`call_schema`](Self::call_schema) checks a single
+    /// function's signature against Rust types.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// for signature in sandbox.dump_guest_function_table()? {
+    ///     println!("{signature}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn dump_guest_function_table(&mut self) -> Result<Vec<FunctionSignature>> {
+        let table: Vec<u8> =
+            self.without_call_rate_limit(|sandbox| sandbox.call(GUEST_FUNCTION_TABLE, ()))?;
+        decode_function_table(&table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterType, ReturnType};
+
+    use super::{FunctionSignature, GUEST_FUNCTION_TABLE};
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn dump_guest_function_table() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let table = sandbox.dump_guest_function_table().unwrap();
+
+        let echo = table.iter().find(|s| s.name == "Echo").unwrap();
+        assert_eq!(
+            *echo,
+            FunctionSignature {
+                name: "Echo".to_string(),
+                parameter_types: vec![ParameterType::String],
+                return_type: ReturnType::String,
+                default_count: 0,
+            }
+        );
+        assert_eq!(echo.to_string(), "Echo(String) -> String");
+
+        // The built-in functions are listed too
+        assert!(table.iter().any(|s| s.name == GUEST_FUNCTION_TABLE));
+        assert!(table.windows(2).all(|w| w[0].name < w[1].name));
+    }
+
+    #[test]
+    fn display_defaults() {
+        let signature = FunctionSignature {
+            name: "Greet".to_string(),
+            parameter_types: vec![ParameterType::String, ParameterType::Int],
+            return_type: ReturnType::Void,
+            default_count: 1,
+        };
+        assert!(signature.has_defaults());
+        assert_eq!(signature.required_parameters(), 1);
+        assert_eq!(signature.to_string(), "Greet(String, Int = ..) -> Void");
+    }
+}
@@ -0,0 +1,135 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Walking the guest's call stack, see [`MultiUseSandbox::guest_backtrace`].
+
+use tracing::{Span, instrument};
+
+use crate::{HyperlightError, MultiUseSandbox, Result, new_error};
+
+/// The most frames [`MultiUseSandbox::guest_backtrace`] returns, so that a
+/// corrupt frame chain cannot make it loop for long.
+const MAX_FRAMES: usize = 256;
+
+impl MultiUseSandbox {
+    /// The guest's call stack where it last stopped, innermost first: the
+    /// instruction pointer, followed by the return address of each frame.
+    ///
+    /// This is meant for finding out where a guest call was stuck after it
+    /// was cancelled, for example by
+    /// [`call_with_timeout`](Self::call_with_timeout), so it works on a
+    /// poisoned sandbox. Call it before restoring the sandbox, which
+    /// resets the stack. After a call that completed, it returns where the
+    /// guest halted to return to the host.
+    ///
+    /// The stack is walked by following the chain of saved frame pointers,
+    /// so frames of code built without frame pointers are missing, and the
+    /// walk stops at the first frame that does not look valid. The
+    /// addresses are guest addresses. Translate them with
+    /// [`GuestImageInfo::to_link_address`](crate::mem::layout::GuestImageInfo::to_link_address)
+    /// to look them up in the guest binary's symbols.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// if sandbox.call_with_timeout::<()>("Process", (), Duration::from_secs(1)).is_err() {
+    ///     let image = sandbox.guest_image_info();
+    ///     for address in sandbox.guest_backtrace()? {
+    ///         match image.to_link_address(address) {
+    ///             Some(link_address) => eprintln!("  {link_address:#x}"),
+    ///             None => eprintln!("  {address:#x} (outside the guest binary)"),
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_backtrace(&mut self) -> Result<Vec<u64>> {
+        let regs = self
+            .vm
+            .regs()
+            .map_err(|e| new_error!("Failed to read the guest's registers: {}", e))?;
+        let root_pt = self
+            .vm
+            .get_root_pt()
+            .map_err(|e| HyperlightError::HyperlightVmError(e.into()))?;
+
+        let mut frames = vec![regs.rip];
+        let mut frame_pointer = regs.rbp;
+        // The outermost frame has a null frame pointer
+        while frame_pointer != 0 && frame_pointer.is_multiple_of(8) && frames.len() < MAX_FRAMES {
+            // A frame starts with the caller's frame pointer, followed by
+            // the return address
+            let Ok(frame) = self
+                .mem_mgr
+                .read_guest_memory_by_gva(frame_pointer, 16, root_pt)
+            else {
+                break;
+            };
+            let caller_frame_pointer = u64::from_le_bytes(frame[..8].try_into().unwrap());
+            let return_address = u64::from_le_bytes(frame[8..].try_into().unwrap());
+            if return_address == 0 {
+                break;
+            }
+            frames.push(return_address);
+            // The stack grows down, so a caller's frame is always above its
+            // callee's
+            if caller_frame_pointer <= frame_pointer {
+                break;
+            }
+            frame_pointer = caller_frame_pointer;
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn backtrace_of_cancelled_call() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let snapshot = sandbox.snapshot().unwrap();
+
+        let res = sandbox.call_with_timeout::<()>("Spin", (), Duration::from_millis(100));
+        assert!(
+            matches!(res, Err(HyperlightError::GuestCallTimedOut { .. })),
+            "{res:?}"
+        );
+        let frames = sandbox.guest_backtrace().unwrap();
+        let image = sandbox.guest_image_info();
+        // The guest was stopped in its own code
+        assert!(image.contains(frames[0]), "{frames:x?}");
+        assert!(frames.len() <= super::MAX_FRAMES);
+
+        sandbox.restore(snapshot).unwrap();
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+    }
+}
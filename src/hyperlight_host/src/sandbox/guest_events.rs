@@ -0,0 +1,209 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Events emitted by the guest, see [`MultiUseSandbox::guest_events`].
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+pub use hyperlight_common::builtin_functions::EMIT_EVENT;
+use tracing::{Span, instrument};
+
+use super::host_funcs::FunctionEntry;
+use crate::func::host_functions::HostFunction;
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// An event emitted by the guest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestEvent {
+    /// The position of this event among all the events the guest has
+    /// emitted, counting from 0 and including the ones that were dropped
+    pub sequence: u64,
+    /// What kind of event this is, chosen by the guest, e.g. `"heartbeat"`
+    pub kind: String,
+    /// The event's data, in whatever format the guest uses for `kind`
+    pub payload: Vec<u8>,
+}
+
+/// The result of [`GuestEvents::poll_events`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PolledEvents {
+    /// The events queued since the last poll, oldest first
+    pub events: Vec<GuestEvent>,
+    /// The number of events dropped since the last poll because the queue
+    /// was full
+    pub dropped: u64,
+    /// The most events the queue held at once since the last poll
+    pub high_water_mark: usize,
+}
+
+impl PolledEvents {
+    /// Whether the queue filled up and events were dropped since the last
+    /// poll.
+    pub fn overflowed(&self) -> bool {
+        self.dropped > 0
+    }
+}
+
+struct EventQueue {
+    capacity: usize,
+    events: VecDeque<GuestEvent>,
+    next_sequence: u64,
+    dropped: u64,
+    high_water_mark: usize,
+}
+
+impl EventQueue {
+    /// Queue an event, or drop it if the queue is full. Returns whether
+    /// the event was queued.
+    fn push(&mut self, kind: String, payload: Vec<u8>) -> bool {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        if self.events.len() == self.capacity {
+            self.dropped += 1;
+            return false;
+        }
+        self.events.push_back(GuestEvent {
+            sequence,
+            kind,
+            payload,
+        });
+        self.high_water_mark = self.high_water_mark.max(self.events.len());
+        true
+    }
+
+    fn poll(&mut self) -> PolledEvents {
+        PolledEvents {
+            events: self.events.drain(..).collect(),
+            dropped: std::mem::take(&mut self.dropped),
+            high_water_mark: std::mem::take(&mut self.high_water_mark),
+        }
+    }
+}
+
+/// The queue of events a guest has emitted since
+/// [`MultiUseSandbox::guest_events`] was called.
+///
+/// Events are delivered in the order the guest emitted them, and none are
+/// lost unless the queue is full. When it is, the new event is dropped
+/// rather than an older one, so what is delivered between two polls is
+/// always the oldest events emitted in that time, with no gaps. Which
+/// events are dropped therefore only depends on what the guest emits and
+/// when the queue is polled, which keeps replays and tests deterministic.
+#[derive(Clone)]
+pub struct GuestEvents {
+    queue: Arc<Mutex<EventQueue>>,
+}
+
+impl GuestEvents {
+    /// Take the queued events, along with the number dropped and the
+    /// high-water mark of the queue since the last poll, and reset both.
+    pub fn poll_events(&self) -> Result<PolledEvents> {
+        Ok(self.lock()?.poll())
+    }
+
+    /// The most events the queue holds before it drops new ones.
+    pub fn capacity(&self) -> Result<usize> {
+        Ok(self.lock()?.capacity)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, EventQueue>> {
+        self.queue
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))
+    }
+}
+
+impl std::fmt::Debug for GuestEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuestEvents").finish_non_exhaustive()
+    }
+}
+
+impl MultiUseSandbox {
+    /// Lets the guest emit events, such as heartbeats or progress, and
+    /// returns the queue they are delivered to.
+    ///
+    /// This registers the host function [`EMIT_EVENT`], which takes the
+    /// kind of the event and its payload as bytes, and returns whether the
+    /// event was queued. The queue holds up to `capacity` events, see
+    /// [`GuestEvents`] for what happens when it is full.
+    ///
+    /// Calling this again replaces the previous registration, and the
+    /// [`GuestEvents`] it returned no longer receives events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let events = sandbox.guest_events(1024)?;
+    /// sandbox.call::<()>("Process", "input".to_string())?;
+    /// let polled = events.poll_events()?;
+    /// if polled.overflowed() {
+    ///     eprintln!("missed {} events", polled.dropped);
+    /// }
+    /// for event in polled.events {
+    ///     println!("{}: {:?}", event.kind, event.payload);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_events(&mut self, capacity: usize) -> Result<GuestEvents> {
+        if capacity == 0 {
+            return Err(new_error!(
+                "The guest event queue needs a capacity of at least 1"
+            ));
+        }
+        let queue = Arc::new(Mutex::new(EventQueue {
+            capacity,
+            events: VecDeque::new(),
+            next_sequence: 0,
+            dropped: 0,
+            high_water_mark: 0,
+        }));
+
+        let events = queue.clone();
+        let emit: HostFunction<bool, (String, Vec<u8>)> =
+            (move |kind: String, payload: Vec<u8>| -> Result<bool> {
+                Ok(events
+                    .lock()
+                    .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                    .push(kind, payload))
+            })
+            .into();
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .register_host_function(
+                EMIT_EVENT.to_string(),
+                FunctionEntry {
+                    function: emit.into(),
+                    parameter_types: <(String, Vec<u8>)>::TYPE,
+                    return_type: <bool as SupportedReturnType>::TYPE,
+                },
+            );
+
+        Ok(GuestEvents { queue })
+    }
+}
@@ -0,0 +1,97 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The compile-time features a guest was built with, see
+//! [`MultiUseSandbox::guest_features`].
+
+use hyperlight_common::guest_features::{GUEST_FEATURES_FUNCTION, GuestFeatures};
+use tracing::{Span, instrument};
+
+use crate::{MultiUseSandbox, Result};
+
+impl MultiUseSandbox {
+    /// The compile-time features the guest was built with, so that the
+    /// host can match its behaviour to the guest's build, or warn about
+    /// capabilities the guest lacks.
+    ///
+    /// The features of `hyperlight-guest-bin` are reported by every Rust
+    /// guest, and a guest adds its own
+    /// [`GuestFeatures::application`] features with
+    /// `hyperlight_guest_bin::guest_features::declare_application_features`.
+    /// This calls a built-in guest function, so it fails with a
+    /// [`crate::HyperlightError::GuestError`] with
+    /// `ErrorCode::GuestFunctionNotFound` for guests that do not report
+    /// their features, such as those built with an older
+    /// `hyperlight-guest-bin`.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::GuestFeatures;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let features = sandbox.guest_features()?;
+    /// if features.contains(GuestFeatures::DEBUG_ASSERTIONS) {
+    ///     println!("the guest is a debug build");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_features(&mut self) -> Result<GuestFeatures> {
+        let bits: u64 =
+            self.without_call_rate_limit(|sandbox| sandbox.call(GUEST_FEATURES_FUNCTION, ()))?;
+        Ok(GuestFeatures::from_bits_retain(bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::GuestFeatures;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn guest_features() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let features = sandbox.guest_features().unwrap();
+
+        // simpleguest is built with the macros and allocator_stats features
+        // but not libc, and declares its own feature 0
+        assert!(features.contains(GuestFeatures::MACROS), "{features:?}");
+        assert!(
+            features.contains(GuestFeatures::ALLOCATOR_STATS),
+            "{features:?}"
+        );
+        assert!(!features.contains(GuestFeatures::LIBC), "{features:?}");
+        assert!(features.contains(GuestFeatures::application(0)));
+        assert!(!features.contains(GuestFeatures::application(1)));
+        assert_eq!(
+            features.contains(GuestFeatures::DEBUG_ASSERTIONS),
+            cfg!(debug_assertions)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Delivering guest output one line at a time, see
+//! [`MultiUseSandbox::on_guest_line`].
+
+use std::sync::{Arc, Mutex};
+
+use hyperlight_common::flatbuffer_wrappers::function_types::ParameterValue;
+use tracing::{Span, instrument};
+
+use super::host_funcs::FunctionEntry;
+use crate::func::host_functions::HostFunction;
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// Splits guest output into lines and passes each complete line to a
+/// callback.
+struct LineBuffer {
+    /// Output after the last newline, which is not yet a complete line.
+    partial: String,
+    callback: Box<dyn FnMut(&str) + Send>,
+}
+
+impl LineBuffer {
+    fn push(&mut self, output: &str) {
+        let mut rest = output;
+        while let Some(end) = rest.find('\n') {
+            self.partial.push_str(&rest[..end]);
+            rest = &rest[end + 1..];
+            self.emit();
+        }
+        self.partial.push_str(rest);
+    }
+
+    fn flush(&mut self) {
+        if !self.partial.is_empty() {
+            self.emit();
+        }
+    }
+
+    fn emit(&mut self) {
+        let line = self.partial.strip_suffix('\r').unwrap_or(&self.partial);
+        (self.callback)(line);
+        self.partial.clear();
+    }
+}
+
+/// The line capture installed by [`MultiUseSandbox::on_guest_line`].
+///
+/// Guest output that does not end with a newline is kept until a later call
+/// completes the line, or until [`finish`](Self::finish) is called.
+#[derive(Clone)]
+pub struct GuestLines {
+    buffer: Arc<Mutex<LineBuffer>>,
+}
+
+impl GuestLines {
+    /// Passes any output the guest printed after its last newline to the
+    /// callback as a line of its own.
+    pub fn finish(&self) -> Result<()> {
+        self.buffer
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .flush();
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for GuestLines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuestLines").finish_non_exhaustive()
+    }
+}
+
+impl MultiUseSandbox {
+    /// Calls `callback` with each complete line the guest prints, without
+    /// its line terminator.
+    ///
+    /// Output is still written to the `HostPrint` function the sandbox was
+    /// created with. A line is passed to `callback` during the guest call
+    /// that completes it, inside that call's tracing span, so the line is
+    /// attributed to the call when it is logged. A line that is started by
+    /// one call and completed by a later one is attributed to the later
+    /// call, and a line that is never completed is only passed to `callback`
+    /// by [`GuestLines::finish`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let lines = sandbox.on_guest_line(|line| tracing::info!(guest = line))?;
+    /// sandbox.call::<i32>("PrintOutput", "Hello, ".to_string())?;
+    /// sandbox.call::<i32>("PrintOutput", "World!".to_string())?;
+    /// // Logs "Hello, World!"
+    /// lines.finish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn on_guest_line(
+        &mut self,
+        callback: impl FnMut(&str) + Send + 'static,
+    ) -> Result<GuestLines> {
+        let buffer = Arc::new(Mutex::new(LineBuffer {
+            partial: String::new(),
+            callback: Box::new(callback),
+        }));
+
+        let mut registry = self
+            .host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        let print = registry.host_function("HostPrint");
+        let lines = buffer.clone();
+        let hf: HostFunction<i32, (String,)> = (move |output: String| -> Result<i32> {
+            let written = match &print {
+                Some(print) => print
+                    .call(vec![ParameterValue::String(output.clone())])?
+                    .try_into()?,
+                None => output.len() as i32,
+            };
+            lines
+                .lock()
+                .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                .push(&output);
+            Ok(written)
+        })
+        .into();
+        registry.register_host_function(
+            "HostPrint".to_string(),
+            FunctionEntry {
+                function: hf.into(),
+                parameter_types: <(String,)>::TYPE,
+                return_type: <i32 as SupportedReturnType>::TYPE,
+            },
+        );
+
+        Ok(GuestLines { buffer })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_buffer() -> (LineBuffer, Arc<Mutex<Vec<String>>>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let captured = lines.clone();
+        let buffer = LineBuffer {
+            partial: String::new(),
+            callback: Box::new(move |line| captured.lock().unwrap().push(line.to_string())),
+        };
+        (buffer, lines)
+    }
+
+    #[test]
+    fn splits_output_into_lines() {
+        let (mut buffer, lines) = line_buffer();
+        buffer.push("one\ntw");
+        assert_eq!(*lines.lock().unwrap(), ["one"]);
+        buffer.push("o\r\n\nthree");
+        assert_eq!(*lines.lock().unwrap(), ["one", "two", ""]);
+        buffer.flush();
+        assert_eq!(*lines.lock().unwrap(), ["one", "two", "", "three"]);
+        // Nothing is left to flush
+        buffer.flush();
+        assert_eq!(lines.lock().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,142 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Host functions that write their results straight into guest memory,
+//! see [`HostFunction::with_guest_memory`].
+
+use std::marker::PhantomData;
+
+use super::reentrant;
+use crate::Result;
+use crate::func::{HostFunction, ParameterTuple, SupportedReturnType};
+
+/// Writes to the memory of the guest that called a host function, for the
+/// duration of that call. See [`HostFunction::with_guest_memory`].
+///
+/// An accessor is only lent to the host function while it runs, so it
+/// cannot be used after the host function returns to the guest.
+pub struct GuestMemoryAccessor<'a> {
+    _call: PhantomData<&'a mut ()>,
+}
+
+impl GuestMemoryAccessor<'_> {
+    /// Write `data` to guest memory at `gva`, a guest virtual address,
+    /// such as the address of a buffer that the guest passed to the host
+    /// function.
+    ///
+    /// The whole range must be mapped writable in the guest and backed by
+    /// memory the guest has already written, since pages it has not are
+    /// still shared with the snapshot the sandbox was created from. A
+    /// guest buffer that was zeroed when it was allocated is. Otherwise
+    /// nothing is written and this fails, as it does while the host
+    /// function is making a [`reentrant`] call.
+    pub fn write(&mut self, gva: u64, data: &[u8]) -> Result<()> {
+        reentrant::write_guest_memory(gva, data)
+    }
+}
+
+impl<Args, Output> HostFunction<Output, Args>
+where
+    Args: ParameterTuple,
+    Output: SupportedReturnType,
+{
+    /// Create a host function that can write to the memory of the guest
+    /// that calls it, through the [`GuestMemoryAccessor`] it is given.
+    ///
+    /// This lets a host function fill a buffer the guest passed by
+    /// address, instead of returning a large result that the guest then
+    /// copies. The arguments are passed as one tuple.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+    /// # use hyperlight_host::func::{HostFunction, Registerable};
+    /// # fn example() -> Result<()> {
+    /// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?;
+    /// sandbox.register_host_function(
+    ///     "ReadFile",
+    ///     HostFunction::with_guest_memory(|mem, (buffer, len): (u64, u64)| {
+    ///         let contents = std::fs::read("data.bin").unwrap_or_default();
+    ///         let n = contents.len().min(len as usize);
+    ///         mem.write(buffer, &contents[..n])?;
+    ///         Ok(n as u64)
+    ///     }),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_guest_memory(
+        func: impl Fn(&mut GuestMemoryAccessor<'_>, Args) -> Result<Output> + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_tuple_fn(move |args| {
+            let mut accessor = GuestMemoryAccessor { _call: PhantomData };
+            func(&mut accessor, args)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_testing::simple_guest_as_string;
+
+    use crate::func::{HostFunction, Registerable};
+    use crate::{GuestBinary, UninitializedSandbox};
+
+    #[test]
+    fn host_function_writes_guest_buffer() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        sandbox
+            .register_host_function(
+                "FillBuffer",
+                HostFunction::with_guest_memory(|mem, (buffer, len): (u64, i32)| {
+                    let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+                    mem.write(buffer, &data)
+                }),
+            )
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        // Spans several pages
+        let len = 3 * 4096 + 17;
+        let filled: Vec<u8> = sandbox.call("FillFromHost", len).unwrap();
+        assert_eq!(filled, (0..len).map(|i| i as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn writes_outside_guest_memory_fail() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        sandbox
+            .register_host_function(
+                "FillBuffer",
+                HostFunction::with_guest_memory(|mem, (_, len): (u64, i32)| {
+                    mem.write(0, &vec![0xff; len as usize])
+                }),
+            )
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        assert!(sandbox.call::<Vec<u8>>("FillFromHost", 16i32).is_err());
+        assert!(!sandbox.poisoned());
+    }
+
+    #[test]
+    fn accessor_unavailable_outside_guest_call() {
+        let f = HostFunction::with_guest_memory(|mem, (buffer,): (u64,)| mem.write(buffer, b"x"));
+        assert!(f.call((0x1000,)).is_err());
+    }
+}
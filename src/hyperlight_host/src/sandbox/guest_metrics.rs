@@ -0,0 +1,152 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Metrics emitted by the guest, see [`MultiUseSandbox::guest_metrics`].
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+pub use hyperlight_common::builtin_functions::{EMIT_COUNTER, EMIT_GAUGE};
+use tracing::{Span, instrument};
+
+use super::host_funcs::FunctionEntry;
+use crate::func::host_functions::HostFunction;
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+#[derive(Default)]
+struct MetricValues {
+    counters: BTreeMap<String, u64>,
+    gauges: BTreeMap<String, f64>,
+}
+
+/// The metrics a guest has emitted since [`MultiUseSandbox::guest_metrics`]
+/// was called, keyed by their name including the host's prefix.
+#[derive(Clone)]
+pub struct GuestMetrics {
+    values: Arc<Mutex<MetricValues>>,
+}
+
+impl GuestMetrics {
+    /// The total the guest has added to each counter.
+    pub fn counters(&self) -> Result<BTreeMap<String, u64>> {
+        Ok(self.lock()?.counters.clone())
+    }
+
+    /// The value the guest last set each gauge to.
+    pub fn gauges(&self) -> Result<BTreeMap<String, f64>> {
+        Ok(self.lock()?.gauges.clone())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, MetricValues>> {
+        self.values
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))
+    }
+}
+
+impl std::fmt::Debug for GuestMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuestMetrics").finish_non_exhaustive()
+    }
+}
+
+impl MultiUseSandbox {
+    /// Lets the guest emit its own metrics, and returns the values it
+    /// emits.
+    ///
+    /// This registers the host functions [`EMIT_COUNTER`], which takes a
+    /// name and a `u64` to add to that counter, and [`EMIT_GAUGE`], which
+    /// takes a name and an `f64` to set that gauge to. Each name is prefixed
+    /// with `prefix`, so the guest's metrics cannot collide with the host's
+    /// own. As well as being collected in the returned [`GuestMetrics`],
+    /// each value is recorded with the [`metrics`] crate under its prefixed
+    /// name.
+    ///
+    /// Calling this again replaces the previous registration, and the
+    /// [`GuestMetrics`] it returned no longer receives values.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let metrics = sandbox.guest_metrics("my_guest_")?;
+    /// sandbox.call::<()>("Lookup", "key".to_string())?;
+    /// let hits = metrics.counters()?.get("my_guest_cache_hits").copied();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_metrics(&mut self, prefix: &str) -> Result<GuestMetrics> {
+        let values = Arc::new(Mutex::new(MetricValues::default()));
+        let mut registry = self
+            .host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+
+        let (counters, counter_prefix) = (values.clone(), prefix.to_string());
+        let counter: HostFunction<(), (String, u64)> =
+            (move |name: String, value: u64| -> Result<()> {
+                let name = format!("{counter_prefix}{name}");
+                metrics::counter!(name.clone()).increment(value);
+                let mut counters = counters
+                    .lock()
+                    .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+                let total = counters.counters.entry(name).or_default();
+                *total = total.saturating_add(value);
+                Ok(())
+            })
+            .into();
+        registry.register_host_function(
+            EMIT_COUNTER.to_string(),
+            FunctionEntry {
+                function: counter.into(),
+                parameter_types: <(String, u64)>::TYPE,
+                return_type: <() as SupportedReturnType>::TYPE,
+            },
+        );
+
+        let (gauges, gauge_prefix) = (values.clone(), prefix.to_string());
+        let gauge: HostFunction<(), (String, f64)> =
+            (move |name: String, value: f64| -> Result<()> {
+                let name = format!("{gauge_prefix}{name}");
+                metrics::gauge!(name.clone()).set(value);
+                gauges
+                    .lock()
+                    .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                    .gauges
+                    .insert(name, value);
+                Ok(())
+            })
+            .into();
+        registry.register_host_function(
+            EMIT_GAUGE.to_string(),
+            FunctionEntry {
+                function: gauge.into(),
+                parameter_types: <(String, f64)>::TYPE,
+                return_type: <() as SupportedReturnType>::TYPE,
+            },
+        );
+
+        Ok(GuestMetrics { values })
+    }
+}
@@ -0,0 +1,171 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Guest functions that return their result as a stream of chunks, see
+//! [`MultiUseSandbox::call_stream`].
+
+use hyperlight_common::builtin_functions::{CLOSE_GUEST_STREAM, NEXT_GUEST_STREAM_CHUNK};
+use tracing::{Span, instrument};
+
+use crate::func::ParameterTuple;
+use crate::{MultiUseSandbox, Result};
+
+/// A stream of chunks produced by the guest, returned by
+/// [`MultiUseSandbox::call_stream`].
+///
+/// The stream's state lives in guest memory, so it is only valid in the
+/// sandbox that opened it. Dropping a stream does not close it in the
+/// guest, see [`MultiUseSandbox::close_stream`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct GuestStream {
+    handle: u64,
+}
+
+impl GuestStream {
+    /// The handle the guest gave the stream.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+}
+
+impl MultiUseSandbox {
+    /// Call the streaming guest function `func_name` with `args`, and
+    /// return the stream it opened, whose chunks are then pulled with
+    /// [`next_chunk`](Self::next_chunk).
+    ///
+    /// A streaming guest function opens a stream with
+    /// `hyperlight_guest_bin::guest_stream::open_stream` and returns its
+    /// handle. The guest produces each chunk when it is pulled, in a guest
+    /// call of its own, so the guest's state carries over between pulls
+    /// like between any other calls. In particular:
+    ///
+    /// - [`restore()`](Self::restore) and
+    ///   [`reset_to_baseline()`](Self::reset_to_baseline) roll the stream
+    ///   back with the rest of the guest's state: to where it was when the
+    ///   snapshot was taken, or to not existing at all if it was opened
+    ///   after, in which case pulling from it fails.
+    /// - A call that poisons the sandbox, while a stream is open or while
+    ///   pulling from it, leaves the stream unusable until the sandbox is
+    ///   restored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let stream = sandbox.call_stream("ListRecords", ())?;
+    /// while let Some(record) = sandbox.next_chunk(&stream)? {
+    ///     println!("{} bytes", record.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_stream(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<GuestStream> {
+        let handle = self.call::<u64>(func_name, args)?;
+        Ok(GuestStream { handle })
+    }
+
+    /// Pull the next chunk of `stream` from the guest, or return `None`
+    /// once the stream has ended, after which the guest has closed it.
+    ///
+    /// Fails if the guest does not know the stream, for example because it
+    /// has ended or the sandbox was restored to before it was opened.
+    ///
+    /// Pulling a chunk runs the guest's stream, so it takes a token from
+    /// the sandbox's [call rate limit](Self::with_call_rate_limit) like
+    /// any other call.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn next_chunk(&mut self, stream: &GuestStream) -> Result<Option<Vec<u8>>> {
+        self.call(NEXT_GUEST_STREAM_CHUNK, stream.handle)
+    }
+
+    /// Close `stream` in the guest before its end, dropping whatever the
+    /// guest holds for it. Closing a stream the guest does not know is not
+    /// an error.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn close_stream(&mut self, stream: GuestStream) -> Result<()> {
+        self.call(CLOSE_GUEST_STREAM, stream.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::MultiUseSandbox;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    fn pull_all(sandbox: &mut MultiUseSandbox, stream: &super::GuestStream) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        while let Some(chunk) = sandbox.next_chunk(stream).unwrap() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    #[test]
+    fn stream_chunks() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let stream = sandbox
+            .call_stream("StreamChunks", (b"hello world".to_vec(), 4i32))
+            .unwrap();
+        assert_eq!(
+            pull_all(&mut sandbox, &stream),
+            [b"hell".to_vec(), b"o wo".to_vec(), b"rld".to_vec()]
+        );
+        // The guest closed the stream when it ended
+        assert!(sandbox.next_chunk(&stream).is_err());
+
+        let stream = sandbox
+            .call_stream("StreamChunks", (b"abc".to_vec(), 1i32))
+            .unwrap();
+        assert_eq!(sandbox.next_chunk(&stream).unwrap(), Some(b"a".to_vec()));
+        sandbox.close_stream(stream).unwrap();
+    }
+
+    #[test]
+    fn restore_rolls_back_streams() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let before = sandbox.snapshot().unwrap();
+        let stream = sandbox
+            .call_stream("StreamChunks", (b"abc".to_vec(), 1i32))
+            .unwrap();
+        assert_eq!(sandbox.next_chunk(&stream).unwrap(), Some(b"a".to_vec()));
+
+        let during = sandbox.snapshot().unwrap();
+        assert_eq!(
+            pull_all(&mut sandbox, &stream),
+            [b"b".to_vec(), b"c".to_vec()]
+        );
+        sandbox.restore(during).unwrap();
+        assert_eq!(
+            pull_all(&mut sandbox, &stream),
+            [b"b".to_vec(), b"c".to_vec()]
+        );
+
+        sandbox.restore(before).unwrap();
+        assert!(sandbox.next_chunk(&stream).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Listing the guest's threads of execution, see
+//! [`MultiUseSandbox::guest_threads`].
+
+use tracing::{Span, instrument};
+
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// The number of vCPUs a sandbox runs the guest on.
+pub(crate) const VCPU_COUNT: usize = 1;
+
+/// What a guest thread was doing when the host last got control back, see
+/// [`GuestThreadInfo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuestThreadState {
+    /// The thread halted to hand control back to the host, such as at the
+    /// end of a guest function call
+    Halted,
+    /// The thread was stopped before it halted, because the guest function
+    /// call it was running failed, for example by being cancelled or
+    /// faulting. The sandbox is poisoned until it is restored.
+    Interrupted,
+}
+
+/// A guest thread of execution, which is a vCPU of the sandbox, see
+/// [`MultiUseSandbox::guest_threads`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuestThreadInfo {
+    /// The index of the thread's vCPU
+    pub id: usize,
+    /// What the thread was doing when the host last got control back
+    pub state: GuestThreadState,
+    /// The guest address of the instruction the thread stopped at
+    pub rip: u64,
+    /// The name given to the thread with
+    /// [`MultiUseSandbox::set_guest_thread_name`], if any
+    pub name: Option<String>,
+}
+
+impl MultiUseSandbox {
+    /// Lists the guest's threads of execution, one for each vCPU of the
+    /// sandbox, with where each of them stopped. This is meant for
+    /// debugging guests, for example to find out where a guest function
+    /// call was stuck after it was cancelled.
+    ///
+    /// Sandboxes run the guest on a single vCPU, so the list has one entry,
+    /// with the id 0. Since the guest does not run while the sandbox is
+    /// borrowed, each thread is either halted or was interrupted.
+    ///
+    /// This works on a poisoned sandbox, so that a failed call can be
+    /// inspected before the sandbox is restored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// sandbox.set_guest_thread_name(0, "worker")?;
+    ///
+    /// if sandbox.call_with_timeout::<()>("Process", (), Duration::from_secs(1)).is_err() {
+    ///     for thread in sandbox.guest_threads()? {
+    ///         eprintln!("{:?} {:?} at {:#x}", thread.name, thread.state, thread.rip);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn guest_threads(&self) -> Result<Vec<GuestThreadInfo>> {
+        let regs = self
+            .vm
+            .regs()
+            .map_err(|e| new_error!("Failed to read the guest's registers: {}", e))?;
+        let state = if self.poisoned {
+            GuestThreadState::Interrupted
+        } else {
+            GuestThreadState::Halted
+        };
+        Ok(vec![GuestThreadInfo {
+            id: 0,
+            state,
+            rip: regs.rip,
+            name: self.guest_thread_names[0].clone(),
+        }])
+    }
+
+    /// Names the guest thread with the id `id`, as reported by
+    /// [`guest_threads`](Self::guest_threads), to tell the threads apart
+    /// when debugging. The name is kept when the sandbox is restored.
+    ///
+    /// Returns an error if the sandbox has no thread with the id `id`.
+    #[instrument(err(Debug), skip(self, name), parent = Span::current())]
+    pub fn set_guest_thread_name(&mut self, id: usize, name: impl Into<String>) -> Result<()> {
+        let slot = self.guest_thread_names.get_mut(id).ok_or_else(|| {
+            new_error!(
+                "The sandbox has no guest thread {} (it has {})",
+                id,
+                VCPU_COUNT
+            )
+        })?;
+        *slot = Some(name.into());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::GuestThreadState;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn guest_threads() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let snapshot = sandbox.snapshot().unwrap();
+        let image = sandbox.guest_image_info();
+
+        let threads = sandbox.guest_threads().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, 0);
+        assert_eq!(threads[0].state, GuestThreadState::Halted);
+        assert_eq!(threads[0].name, None);
+
+        sandbox.set_guest_thread_name(0, "main").unwrap();
+        assert!(sandbox.set_guest_thread_name(1, "other").is_err());
+
+        sandbox
+            .call_with_timeout::<()>("Spin", (), Duration::from_millis(100))
+            .unwrap_err();
+        let threads = sandbox.guest_threads().unwrap();
+        assert_eq!(threads[0].state, GuestThreadState::Interrupted);
+        // The guest was stopped in its own code
+        assert!(image.contains(threads[0].rip), "{:#x}", threads[0].rip);
+        assert_eq!(threads[0].name.as_deref(), Some("main"));
+
+        sandbox.restore(snapshot).unwrap();
+        let threads = sandbox.guest_threads().unwrap();
+        assert_eq!(threads[0].name.as_deref(), Some("main"));
+    }
+}
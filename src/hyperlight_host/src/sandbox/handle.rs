@@ -0,0 +1,170 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Sandboxes created on a background thread, see [`SandboxHandle`].
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use tracing::{Span, instrument};
+
+use crate::{HyperlightError, MultiUseSandbox, Result, UninitializedSandbox, new_error};
+
+/// A sandbox that is being created on a background thread, so that
+/// expensive initialisation can happen off the hot path, for example to
+/// pre-warm a pool of sandboxes.
+///
+/// Dropping the handle does not stop the background thread. If the sandbox
+/// has not been taken with [`SandboxHandle::ready`] by then, it is dropped
+/// on that thread once it has been created, or as soon as creation fails.
+#[derive(Debug)]
+pub struct SandboxHandle {
+    receiver: Option<Receiver<Result<MultiUseSandbox>>>,
+}
+
+impl SandboxHandle {
+    /// Run `create` on a new thread, and return a handle to the sandbox
+    /// it creates.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use hyperlight_host::{GuestBinary, UninitializedSandbox};
+    /// # use hyperlight_host::sandbox::SandboxHandle;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut handle = SandboxHandle::spawn(|| {
+    ///     let mut u_sbox =
+    ///         UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?;
+    ///     u_sbox.register("Add", |a: i32, b: i32| Ok(a + b))?;
+    ///     u_sbox.evolve()
+    /// })?;
+    ///
+    /// // ... do other work ...
+    ///
+    /// let mut sandbox = handle.ready(Duration::from_secs(5))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn spawn<F>(create: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<MultiUseSandbox> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        thread::Builder::new()
+            .name("hyperlight-sandbox-init".to_string())
+            .spawn(move || {
+                // If the handle has been dropped the sandbox is dropped
+                // here, along with the error returned by `send`.
+                let _ = sender.send(create());
+            })?;
+        Ok(Self {
+            receiver: Some(receiver),
+        })
+    }
+
+    /// Wait up to `timeout` for the sandbox to be created, and take it.
+    ///
+    /// Returns [`HyperlightError::SandboxNotReady`] if the sandbox is still
+    /// being created when `timeout` expires, in which case creation carries
+    /// on and `ready` can be called again. Otherwise returns the result of
+    /// creating the sandbox, after which every call to `ready` fails.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn ready(&mut self, timeout: Duration) -> Result<MultiUseSandbox> {
+        let receiver = self
+            .receiver
+            .as_ref()
+            .ok_or_else(|| new_error!("The sandbox has already been taken from this handle"))?;
+        let res = match receiver.recv_timeout(timeout) {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(HyperlightError::SandboxNotReady(timeout));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(new_error!("The thread creating the sandbox panicked"))
+            }
+        };
+        self.receiver = None;
+        res
+    }
+}
+
+impl UninitializedSandbox {
+    /// Evolve this sandbox on a background thread, see
+    /// [`SandboxHandle::spawn`].
+    pub fn evolve_in_background(self) -> Result<SandboxHandle> {
+        SandboxHandle::spawn(move || self.evolve())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hyperlight_testing::simple_guest_as_string;
+
+    use super::SandboxHandle;
+    use crate::{GuestBinary, HyperlightError, UninitializedSandbox, new_error};
+
+    #[test]
+    fn ready() {
+        let path = simple_guest_as_string().unwrap();
+        let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        let mut handle = u_sbox.evolve_in_background().unwrap();
+
+        let mut sandbox = handle.ready(Duration::from_secs(60)).unwrap();
+        let res: String = sandbox.call("Echo", "ready".to_string()).unwrap();
+        assert_eq!(res, "ready");
+
+        assert!(handle.ready(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn ready_times_out() {
+        let (sender, receiver) = std::sync::mpsc::channel::<()>();
+        let mut handle = SandboxHandle::spawn(move || {
+            let _ = receiver.recv();
+            let path = simple_guest_as_string().unwrap();
+            UninitializedSandbox::new(GuestBinary::FilePath(path), None)?.evolve()
+        })
+        .unwrap();
+
+        let err = handle.ready(Duration::from_millis(10)).unwrap_err();
+        assert!(matches!(err, HyperlightError::SandboxNotReady(_)));
+
+        // Creation carries on after a timeout, and can still be waited for
+        sender.send(()).unwrap();
+        assert!(handle.ready(Duration::from_secs(60)).is_ok());
+
+        // Dropping a handle whose sandbox is still being created is fine
+        let handle = SandboxHandle::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            let path = simple_guest_as_string().unwrap();
+            UninitializedSandbox::new(GuestBinary::FilePath(path), None)?.evolve()
+        })
+        .unwrap();
+        drop(handle);
+    }
+
+    #[test]
+    fn ready_returns_creation_error() {
+        let mut handle = SandboxHandle::spawn(|| Err(new_error!("failed"))).unwrap();
+        let err = handle.ready(Duration::from_secs(60)).unwrap_err();
+        assert!(err.to_string().contains("failed"));
+    }
+}
@@ -14,9 +14,16 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
 use std::io::{IsTerminal, Write};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Instant;
 
+use hyperlight_common::builtin_functions::{
+    CURRENT_REQUEST_ID, GUEST_ARGS, GUEST_ENV_NAMES, GUEST_FUNCTION_ALIASES, GUEST_GETENV,
+    HOST_FUNCTION_SIGNATURE, INIT_PROGRESS, MONOTONIC_NS, SIGNAL_READY,
+};
 use hyperlight_common::flatbuffer_wrappers::function_types::{
     ParameterType, ParameterValue, ReturnType, ReturnValue,
 };
@@ -25,14 +32,107 @@ use hyperlight_common::flatbuffer_wrappers::host_function_details::HostFunctionD
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use tracing::{Span, instrument};
 
-use crate::HyperlightError::HostFunctionNotFound;
+use crate::HyperlightError::{HostFunctionNotFound, UnexpectedNoOfArguments};
 use crate::Result;
 use crate::func::host_functions::TypeErasedHostFunction;
+use crate::sandbox::host_stream::{HostStreams, StreamProducer};
+use crate::sandbox::init_progress::InitProgressReporter;
+use crate::sandbox::readiness::Readiness;
+
+/// The point the clock read with [`MONOTONIC_NS`] counts from. Its
+/// resolution is that of [`Instant`].
+static MONOTONIC_START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// The built-in host functions that the registry answers itself.
+const BUILT_IN_HOST_FUNCTIONS: [&str; 9] = [
+    HOST_FUNCTION_SIGNATURE,
+    GUEST_FUNCTION_ALIASES,
+    CURRENT_REQUEST_ID,
+    GUEST_GETENV,
+    GUEST_ENV_NAMES,
+    GUEST_ARGS,
+    MONOTONIC_NS,
+    INIT_PROGRESS,
+    SIGNAL_READY,
+];
+
+/// What a sandbox does when its guest calls a host function that is not
+/// registered, see
+/// [`crate::UninitializedSandbox::set_unknown_host_call_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownHostCallPolicy {
+    /// Return a [`HostFunctionNotFound`](crate::HyperlightError::HostFunctionNotFound)
+    /// error to the guest.
+    #[default]
+    Error,
+    /// Log a warning and return the zero value of the type the guest
+    /// expects, such as `0`, an empty string or `None`, as if the function
+    /// existed and did nothing.
+    ReturnDefault,
+}
+
+/// Answers the guest's calls to host functions that are not registered,
+/// given the name of the function, its arguments and the type of the value
+/// the guest expects back.
+pub(crate) type UnknownHostCallHandler =
+    Arc<dyn Fn(&str, Vec<ParameterValue>, ReturnType) -> Result<ReturnValue> + Send + Sync>;
+
+/// How to answer a guest's call to a host function that is not registered,
+/// other than with an error.
+pub(crate) enum UnknownHostCall {
+    /// Return [`default_return_value`] of the type the guest expects
+    ReturnDefault,
+    /// Call the handler set with
+    /// [`crate::UninitializedSandbox::set_unknown_host_call_handler`]
+    Handler(UnknownHostCallHandler),
+}
+
+/// The zero value of `return_type`, see [`UnknownHostCallPolicy::ReturnDefault`].
+pub(crate) fn default_return_value(return_type: ReturnType) -> ReturnValue {
+    match return_type {
+        ReturnType::Int => ReturnValue::Int(0),
+        ReturnType::UInt => ReturnValue::UInt(0),
+        ReturnType::Long => ReturnValue::Long(0),
+        ReturnType::ULong => ReturnValue::ULong(0),
+        ReturnType::Float => ReturnValue::Float(0.0),
+        ReturnType::Double => ReturnValue::Double(0.0),
+        ReturnType::String => ReturnValue::String(String::new()),
+        ReturnType::Bool => ReturnValue::Bool(false),
+        ReturnType::Void => ReturnValue::Void(()),
+        ReturnType::VecBytes => ReturnValue::VecBytes(Vec::new()),
+        ReturnType::Optional => ReturnValue::Optional(None),
+        ReturnType::VecFloat => ReturnValue::VecFloat(Vec::new()),
+        ReturnType::VecDouble => ReturnValue::VecDouble(Vec::new()),
+        ReturnType::MultiBuffer => ReturnValue::MultiBuffer(Vec::new()),
+        ReturnType::StatusWithPayload => ReturnValue::StatusWithPayload {
+            code: 0,
+            data: Vec::new(),
+        },
+    }
+}
 
 #[derive(Default)]
 /// A Wrapper around details of functions exposed by the Host
 pub struct FunctionRegistry {
     functions_map: HashMap<String, FunctionEntry>,
+    /// The producers of the streaming host functions in `functions_map`
+    stream_producers: HashMap<String, StreamProducer>,
+    guest_function_aliases: BTreeMap<String, String>,
+    guest_env: BTreeMap<String, String>,
+    guest_args: Vec<OsString>,
+    /// The streams opened by `stream_producers`, which are this
+    /// registry's own, see [`fork`](Self::fork)
+    host_streams: Arc<Mutex<HostStreams>>,
+    /// The request id of the guest call in progress, if it has one
+    pub(crate) request_id: Option<String>,
+    /// Passed the guest's progress reports while it is initialised by
+    /// [`crate::UninitializedSandbox::evolve_with_progress`]
+    pub(crate) init_progress: Option<InitProgressReporter>,
+    /// Set when the guest calls [`SIGNAL_READY`]
+    pub(crate) readiness: Readiness,
+    unknown_host_call_policy: UnknownHostCallPolicy,
+    /// Takes precedence over `unknown_host_call_policy` when set
+    unknown_host_call_handler: Option<UnknownHostCallHandler>,
 }
 
 /// A collection of host functions that can be supplied to a sandbox
@@ -125,6 +225,7 @@ impl From<&FunctionRegistry> for HostFunctionDetails {
     }
 }
 
+#[derive(Clone)]
 pub struct FunctionEntry {
     pub function: TypeErasedHostFunction,
     pub parameter_types: &'static [ParameterType],
@@ -135,9 +236,56 @@ impl FunctionRegistry {
     /// Register a host function with the sandbox.
     #[instrument(skip_all, parent = Span::current(), level = "Trace")]
     pub(crate) fn register_host_function(&mut self, name: String, func: FunctionEntry) {
+        self.stream_producers.remove(&name);
         self.functions_map.insert(name, func);
     }
 
+    /// Register a streaming host function, whose calls are answered by
+    /// opening a stream with `producer`, see
+    /// [`crate::sandbox::host_stream`].
+    pub(crate) fn register_streaming_host_function(
+        &mut self,
+        name: String,
+        func: FunctionEntry,
+        producer: StreamProducer,
+    ) {
+        self.functions_map.insert(name.clone(), func);
+        self.stream_producers.insert(name, producer);
+    }
+
+    /// The producer of the streaming host function `name`, if it is one.
+    pub(crate) fn stream_producer(&self, name: &str) -> Option<StreamProducer> {
+        self.stream_producers.get(name).cloned()
+    }
+
+    /// A registry for another sandbox running the same guest, such as a
+    /// fork made by [`crate::MultiUseSandbox::speculate`], with the same
+    /// host functions, aliases, guest environment and arguments and
+    /// unknown host call policy as this one.
+    ///
+    /// The state of the calls the guest makes is not shared: the new
+    /// registry has no request id, progress reporter or open host stream,
+    /// and a readiness of its own, which starts out ready if this one is.
+    pub(crate) fn fork(&self) -> Result<Self> {
+        let readiness = Readiness::default();
+        if self.readiness.is_ready() {
+            readiness.signal()?;
+        }
+        Ok(Self {
+            functions_map: self.functions_map.clone(),
+            stream_producers: self.stream_producers.clone(),
+            guest_function_aliases: self.guest_function_aliases.clone(),
+            guest_env: self.guest_env.clone(),
+            guest_args: self.guest_args.clone(),
+            host_streams: Arc::default(),
+            request_id: None,
+            init_progress: None,
+            readiness,
+            unknown_host_call_policy: self.unknown_host_call_policy,
+            unknown_host_call_handler: self.unknown_host_call_handler.clone(),
+        })
+    }
+
     /// Return the registered signature for `name`.
     pub(crate) fn function_signature(
         &self,
@@ -148,6 +296,72 @@ impl FunctionRegistry {
             .map(|entry| (entry.parameter_types, entry.return_type))
     }
 
+    /// Make guest calls to `alias` call the guest function `target`.
+    pub(crate) fn alias_guest_function(&mut self, alias: String, target: String) {
+        self.guest_function_aliases.insert(alias, target);
+    }
+
+    /// Whether the guest has to fetch its function aliases while it is
+    /// initialised: if any are configured, or the embedder answers the
+    /// call itself.
+    pub(crate) fn has_guest_function_aliases(&self) -> bool {
+        !self.guest_function_aliases.is_empty()
+            || self.functions_map.contains_key(GUEST_FUNCTION_ALIASES)
+    }
+
+    /// Replace the environment the guest reads with `hl_getenv`.
+    pub(crate) fn set_guest_env(&mut self, env: HashMap<String, String>) {
+        self.guest_env = env.into_iter().collect();
+    }
+
+    /// Replace the command-line arguments the guest reads with `hl_args`.
+    pub(crate) fn set_guest_args(&mut self, args: Vec<OsString>) {
+        self.guest_args = args;
+    }
+
+    /// Set what happens when the guest calls a host function that is not
+    /// registered, replacing any handler set before.
+    pub(crate) fn set_unknown_host_call_policy(&mut self, policy: UnknownHostCallPolicy) {
+        self.unknown_host_call_policy = policy;
+        self.unknown_host_call_handler = None;
+    }
+
+    /// Answer the guest's calls to host functions that are not registered
+    /// with `handler`.
+    pub(crate) fn set_unknown_host_call_handler(&mut self, handler: UnknownHostCallHandler) {
+        self.unknown_host_call_handler = Some(handler);
+    }
+
+    /// How to answer a guest's call to `name`, which is not registered,
+    /// or `None` if it is a built-in function or should fail with
+    /// [`HostFunctionNotFound`].
+    pub(crate) fn unknown_host_call(&self, name: &str) -> Option<UnknownHostCall> {
+        if BUILT_IN_HOST_FUNCTIONS.contains(&name) {
+            return None;
+        }
+        if let Some(handler) = &self.unknown_host_call_handler {
+            return Some(UnknownHostCall::Handler(handler.clone()));
+        }
+        match self.unknown_host_call_policy {
+            UnknownHostCallPolicy::Error => None,
+            UnknownHostCallPolicy::ReturnDefault => Some(UnknownHostCall::ReturnDefault),
+        }
+    }
+
+    /// The host stream the guest is reading, see
+    /// [`crate::sandbox::host_stream`].
+    pub(crate) fn host_streams(&self) -> Arc<Mutex<HostStreams>> {
+        self.host_streams.clone()
+    }
+
+    /// Return a handle to the host function registered as `name`, which
+    /// can be called after the registry has been released.
+    pub(crate) fn host_function(&self, name: &str) -> Option<TypeErasedHostFunction> {
+        self.functions_map
+            .get(name)
+            .map(|entry| entry.function.clone())
+    }
+
     /// Create a `FunctionRegistry` pre-populated with the default
     /// `HostPrint` function (writes to stdout with green text).
     pub(crate) fn with_default_host_print() -> Self {
@@ -190,9 +404,124 @@ impl FunctionRegistry {
         name: &str,
         args: Vec<ParameterValue>,
     ) -> Result<ReturnValue> {
+        if name == HOST_FUNCTION_SIGNATURE && !self.functions_map.contains_key(name) {
+            return self.encoded_function_signature(args);
+        }
+        if name == GUEST_FUNCTION_ALIASES && !self.functions_map.contains_key(name) {
+            return Ok(ReturnValue::VecBytes(self.encoded_guest_function_aliases()));
+        }
+        if name == CURRENT_REQUEST_ID && !self.functions_map.contains_key(name) {
+            return Ok(ReturnValue::Optional(
+                self.request_id
+                    .clone()
+                    .map(|id| Box::new(ReturnValue::String(id))),
+            ));
+        }
+        if name == MONOTONIC_NS && !self.functions_map.contains_key(name) {
+            let elapsed = MONOTONIC_START.elapsed().as_nanos();
+            return Ok(ReturnValue::ULong(elapsed.try_into().unwrap_or(u64::MAX)));
+        }
+        if name == INIT_PROGRESS && !self.functions_map.contains_key(name) {
+            return match &self.init_progress {
+                Some(reporter) => reporter.report(args),
+                None => Ok(ReturnValue::Void(())),
+            };
+        }
+        if name == SIGNAL_READY && !self.functions_map.contains_key(name) {
+            self.readiness.signal()?;
+            return Ok(ReturnValue::Void(()));
+        }
+        if name == GUEST_GETENV && !self.functions_map.contains_key(name) {
+            return self.guest_env_var(args);
+        }
+        if name == GUEST_ENV_NAMES && !self.functions_map.contains_key(name) {
+            return Ok(ReturnValue::VecBytes(self.encoded_guest_env_names()));
+        }
+        if name == GUEST_ARGS && !self.functions_map.contains_key(name) {
+            return Ok(ReturnValue::VecBytes(self.encoded_guest_args()));
+        }
         self.call_host_func_impl(name, args)
     }
 
+    /// Answer a guest's [`GUEST_GETENV`] call with the value of the
+    /// variable named in `args`, if it is set.
+    fn guest_env_var(&self, args: Vec<ParameterValue>) -> Result<ReturnValue> {
+        let name = match args.as_slice() {
+            [ParameterValue::String(name)] => name,
+            _ => {
+                return Err(UnexpectedNoOfArguments(args.len(), 1));
+            }
+        };
+        Ok(ReturnValue::Optional(
+            self.guest_env
+                .get(name)
+                .map(|value| Box::new(ReturnValue::String(value.clone()))),
+        ))
+    }
+
+    /// Answer a guest's [`GUEST_ENV_NAMES`] call with the names of the
+    /// variables in the guest's environment, in order, each NUL-terminated.
+    fn encoded_guest_env_names(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for name in self.guest_env.keys() {
+            encoded.extend_from_slice(name.as_bytes());
+            encoded.push(0);
+        }
+        encoded
+    }
+
+    /// Answer a guest's [`GUEST_ARGS`] call with the guest's command-line
+    /// arguments: their number as a little-endian `u32`, then each argument
+    /// as its length in bytes as a little-endian `u32` followed by its
+    /// bytes.
+    ///
+    /// The bytes of an argument are those of the [`OsString`] it was set
+    /// as, which on Unix are its raw bytes, whether or not they are UTF-8,
+    /// and on Windows are UTF-8 for arguments that are valid Unicode. They
+    /// are length-prefixed rather than NUL-terminated so that any bytes
+    /// survive the round trip.
+    fn encoded_guest_args(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encoded.extend_from_slice(&(self.guest_args.len() as u32).to_le_bytes());
+        for arg in &self.guest_args {
+            let bytes = arg.as_encoded_bytes();
+            encoded.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            encoded.extend_from_slice(bytes);
+        }
+        encoded
+    }
+
+    /// Answer a guest's [`HOST_FUNCTION_SIGNATURE`] call with the encoded
+    /// `HostFunctionDefinition` of the host function named in `args`.
+    fn encoded_function_signature(&self, args: Vec<ParameterValue>) -> Result<ReturnValue> {
+        let name = match args.as_slice() {
+            [ParameterValue::String(name)] => name,
+            _ => {
+                return Err(UnexpectedNoOfArguments(args.len(), 1));
+            }
+        };
+        let (parameter_types, return_type) = self
+            .function_signature(name)
+            .ok_or_else(|| HostFunctionNotFound(name.clone()))?;
+        let definition =
+            HostFunctionDefinition::new(name.clone(), Some(parameter_types.to_vec()), return_type);
+        let encoded: Vec<u8> = (&definition).try_into()?;
+        Ok(ReturnValue::VecBytes(encoded))
+    }
+
+    /// Answer a guest's [`GUEST_FUNCTION_ALIASES`] call with the alias table,
+    /// encoded as consecutive NUL-terminated alias and target names.
+    fn encoded_guest_function_aliases(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for (alias, target) in &self.guest_function_aliases {
+            for name in [alias, target] {
+                encoded.extend_from_slice(name.as_bytes());
+                encoded.push(0);
+            }
+        }
+        encoded
+    }
+
     #[instrument(err(Debug), skip_all, parent = Span::current(), level = "Trace")]
     fn call_host_func_impl(&self, name: &str, args: Vec<ParameterValue>) -> Result<ReturnValue> {
         let FunctionEntry {
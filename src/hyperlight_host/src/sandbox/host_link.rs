@@ -0,0 +1,202 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use flatbuffers::FlatBufferBuilder;
+pub use hyperlight_common::builtin_functions::CALL_SANDBOX_HOST_FUNCTION;
+use hyperlight_common::flatbuffer_wrappers::function_call::FunctionCall;
+use hyperlight_common::flatbuffer_wrappers::function_types::FunctionCallResult;
+use hyperlight_common::flatbuffer_wrappers::guest_error::GuestError;
+
+use crate::func::Registerable;
+use crate::{HyperlightError, MultiUseSandbox, Result, new_error};
+
+type LinkedSandboxes = HashMap<String, Arc<Mutex<MultiUseSandbox>>>;
+
+/// Links sandboxes together so that a guest running in one sandbox can
+/// call guest functions in another, with the host mediating each call.
+///
+/// Target sandboxes are registered by name with
+/// [`register_sandbox`](Self::register_sandbox), then the calling
+/// sandbox is given access via [`register_with`](Self::register_with),
+/// which installs the [`CALL_SANDBOX_HOST_FUNCTION`] host function.
+///
+/// A linked sandbox can only service one call at a time. A call that
+/// would re-enter a sandbox which is already executing (for example
+/// `A -> B -> A`) fails instead of deadlocking.
+///
+/// ```no_run
+/// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+/// # use hyperlight_host::sandbox::HostLink;
+/// # fn example() -> Result<()> {
+/// let link = HostLink::new();
+/// let parser = UninitializedSandbox::new(GuestBinary::FilePath("parser".into()), None)?.evolve()?;
+/// link.register_sandbox("parser", parser)?;
+///
+/// let mut frontend = UninitializedSandbox::new(GuestBinary::FilePath("frontend".into()), None)?;
+/// link.register_with(&mut frontend)?;
+/// let mut frontend = frontend.evolve()?;
+/// let out: String = frontend.call("Process", "input".to_string())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct HostLink {
+    sandboxes: Arc<Mutex<LinkedSandboxes>>,
+}
+
+impl HostLink {
+    /// Create a new `HostLink` with no sandboxes registered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `sandbox` under `name` so that linked guests can call into it.
+    ///
+    /// Returns the sandbox previously registered under `name`, if any and
+    /// if it is not currently servicing a call.
+    pub fn register_sandbox(
+        &self,
+        name: impl Into<String>,
+        sandbox: MultiUseSandbox,
+    ) -> Result<Option<MultiUseSandbox>> {
+        let previous = self
+            .sandboxes
+            .lock()?
+            .insert(name.into(), Arc::new(Mutex::new(sandbox)));
+        Ok(previous.and_then(Self::into_sandbox))
+    }
+
+    /// Remove the sandbox registered under `name` and hand it back to the caller.
+    ///
+    /// Returns `None` if no sandbox was registered under `name`, or if it is
+    /// currently servicing a call.
+    pub fn take_sandbox(&self, name: &str) -> Result<Option<MultiUseSandbox>> {
+        let removed = self.sandboxes.lock()?.remove(name);
+        Ok(removed.and_then(Self::into_sandbox))
+    }
+
+    /// Install the [`CALL_SANDBOX_HOST_FUNCTION`] host function on `registerable`,
+    /// allowing its guest to call into the sandboxes registered with this link.
+    pub fn register_with(&self, registerable: &mut impl Registerable) -> Result<()> {
+        let sandboxes = self.sandboxes.clone();
+        registerable.register_host_function(
+            CALL_SANDBOX_HOST_FUNCTION,
+            move |sandbox_name: String, call: Vec<u8>| {
+                Self::forward_call(&sandboxes, &sandbox_name, &call)
+            },
+        )
+    }
+
+    /// Decode `call`, run it on the sandbox named `sandbox_name` and
+    /// return the encoded `FunctionCallResult`. Errors raised by the
+    /// target guest are encoded into the result so the calling guest
+    /// sees them as guest errors; any other failure is returned as a
+    /// host function error.
+    fn forward_call(
+        sandboxes: &Mutex<LinkedSandboxes>,
+        sandbox_name: &str,
+        call: &[u8],
+    ) -> Result<Vec<u8>> {
+        let call = FunctionCall::try_from(call)
+            .map_err(|e| new_error!("Failed to decode linked sandbox call: {}", e))?;
+
+        // Release the registry lock before running the guest so that the
+        // target guest can itself make linked calls.
+        let target = sandboxes
+            .lock()?
+            .get(sandbox_name)
+            .cloned()
+            .ok_or_else(|| new_error!("No sandbox registered with name {}", sandbox_name))?;
+        let mut target = target.try_lock().map_err(|_| {
+            new_error!(
+                "Sandbox {} is already executing a linked call",
+                sandbox_name
+            )
+        })?;
+
+        let result = match target.call_dynamic(
+            &call.function_name,
+            call.expected_return_type,
+            call.parameters.unwrap_or_default(),
+        ) {
+            Ok(value) => Ok(value),
+            Err(HyperlightError::GuestError(code, message)) => Err(GuestError::new(code, message)),
+            Err(e) => return Err(e),
+        };
+
+        let mut builder = FlatBufferBuilder::new();
+        Ok(FunctionCallResult::new(result)
+            .encode(&mut builder)
+            .to_vec())
+    }
+
+    fn into_sandbox(sandbox: Arc<Mutex<MultiUseSandbox>>) -> Option<MultiUseSandbox> {
+        Arc::into_inner(sandbox).and_then(|m| m.into_inner().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    fn encode_call(name: &str, message: &str) -> Vec<u8> {
+        use hyperlight_common::flatbuffer_wrappers::function_call::FunctionCallType;
+        use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnType};
+
+        let call = FunctionCall::new(
+            name.to_string(),
+            Some(vec![ParameterValue::String(message.to_string())]),
+            FunctionCallType::Guest,
+            ReturnType::String,
+        );
+        let mut builder = FlatBufferBuilder::new();
+        call.encode(&mut builder).to_vec()
+    }
+
+    #[test]
+    fn forward_call_to_registered_sandbox() {
+        use hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue;
+
+        let link = HostLink::new();
+        assert!(
+            link.register_sandbox("echo", new_simple_guest_sandbox(None))
+                .unwrap()
+                .is_none()
+        );
+
+        let bytes =
+            HostLink::forward_call(&link.sandboxes, "echo", &encode_call("Echo", "hi")).unwrap();
+        let result = FunctionCallResult::try_from(bytes.as_slice())
+            .unwrap()
+            .into_inner();
+        assert!(matches!(result, Ok(ReturnValue::String(s)) if s == "hi"));
+
+        assert!(link.take_sandbox("echo").unwrap().is_some());
+        assert!(link.take_sandbox("echo").unwrap().is_none());
+    }
+
+    #[test]
+    fn forward_call_to_unknown_sandbox() {
+        let link = HostLink::new();
+        let res = HostLink::forward_call(&link.sandboxes, "missing", &encode_call("Echo", "hi"));
+        assert!(res.is_err());
+    }
+}
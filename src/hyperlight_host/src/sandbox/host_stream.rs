@@ -0,0 +1,426 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Host functions that stream their result to the guest through the
+//! shared ring, see
+//! [`UninitializedSandbox::register_streaming_host_function`].
+//!
+//! Calling a streaming host function starts its producer on a new thread
+//! and returns a stream handle to the guest. The guest pops the result
+//! from the host-to-guest ring, and each time the ring is empty calls
+//! [`READ_HOST_STREAM`], which waits for the producer and moves as much of
+//! what it has written into the ring as fits. At most
+//! [`MAX_QUEUED_CHUNKS`] writes wait between the producer and the ring, so
+//! the producer never gets far ahead of the guest.
+
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use hyperlight_common::builtin_functions::{CLOSE_HOST_STREAM, READ_HOST_STREAM};
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+use hyperlight_common::shared_ring::{SharedRingError, ring_capacity};
+
+use super::host_funcs::{FunctionEntry, FunctionRegistry};
+use super::outb::HandleOutbError;
+use crate::func::ParameterTuple;
+use crate::func::host_functions::TypeErasedHostFunction;
+use crate::mem::mgr::SandboxMemoryManager;
+use crate::mem::shared_mem::HostSharedMemory;
+use crate::mem::shared_ring::HostSharedRing;
+use crate::{HyperlightError, Result, UninitializedSandbox, new_error};
+
+/// The number of writes that may be waiting to be moved into the ring
+/// before [`RingWriter::write`] blocks.
+const MAX_QUEUED_CHUNKS: usize = 4;
+
+/// Writes the result of a streaming host function to the guest, see
+/// [`UninitializedSandbox::register_streaming_host_function`].
+#[derive(Debug)]
+pub struct RingWriter {
+    sender: SyncSender<Result<Vec<u8>>>,
+}
+
+impl RingWriter {
+    /// Write `data` to the stream, waiting while the guest catches up
+    /// with what has already been written.
+    ///
+    /// Fails once the guest has closed the stream, after which the
+    /// producer should return.
+    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send(Ok(data.to_vec()))
+            .map_err(|_| new_error!("The guest closed the host stream"))
+    }
+}
+
+impl std::io::Write for RingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        RingWriter::write(self, buf).map_err(std::io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An open host stream.
+struct Stream {
+    handle: u64,
+    receiver: Receiver<Result<Vec<u8>>>,
+    /// A write that did not fit in the ring, and the offset of the first
+    /// byte that has not been pushed yet
+    pending: Option<(Vec<u8>, usize)>,
+    /// The producer's error, which the guest gets once it has read
+    /// everything written before it
+    error: Option<HyperlightError>,
+}
+
+/// The host stream a guest is reading, if any.
+#[derive(Default)]
+pub(crate) struct HostStreams {
+    /// The handle of the last stream opened
+    last_handle: u64,
+    open: Option<Stream>,
+}
+
+impl HostStreams {
+    /// Run `produce` on a new thread, and make the stream it writes the
+    /// open stream, closing any stream the guest left open.
+    fn open(
+        &mut self,
+        produce: impl FnOnce(&mut RingWriter) -> Result<()> + Send + 'static,
+    ) -> Result<u64> {
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_CHUNKS);
+        thread::Builder::new()
+            .name("hyperlight-host-stream".to_string())
+            .spawn(move || {
+                let mut writer = RingWriter { sender };
+                if let Err(e) = produce(&mut writer) {
+                    // If the stream has been closed nobody is left to
+                    // report the error to.
+                    let _ = writer.sender.send(Err(e));
+                }
+            })?;
+        self.last_handle += 1;
+        self.open = Some(Stream {
+            handle: self.last_handle,
+            receiver,
+            pending: None,
+            error: None,
+        });
+        Ok(self.last_handle)
+    }
+
+    /// The open stream, if its handle is `handle`.
+    fn get(&mut self, handle: u64) -> Result<&mut Stream> {
+        self.open
+            .as_mut()
+            .filter(|stream| stream.handle == handle)
+            .ok_or_else(|| new_error!("Host stream {} is not open", handle))
+    }
+
+    /// Answer a guest's [`READ_HOST_STREAM`] call.
+    fn read(&mut self, handle: u64, ring: &HostSharedRing, max_message: usize) -> Result<bool> {
+        let res = self.get(handle)?.fill(ring, max_message);
+        // The stream is done once the guest has been told about its end
+        // or its error.
+        if !matches!(res, Ok(true)) {
+            self.open = None;
+        }
+        res
+    }
+
+    /// Answer a guest's [`CLOSE_HOST_STREAM`] call. Dropping the stream
+    /// makes the producer's next write fail.
+    fn close(&mut self, handle: u64) -> Result<()> {
+        self.get(handle)?;
+        self.open = None;
+        Ok(())
+    }
+}
+
+impl Stream {
+    /// Push what the producer has written into `ring`, waiting for the
+    /// first write if there is nothing to push yet.
+    ///
+    /// Returns whether more is to come.
+    fn fill(&mut self, ring: &HostSharedRing, max_message: usize) -> Result<bool> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let mut pushed = false;
+        loop {
+            let (data, mut offset) = match self.pending.take() {
+                Some(pending) => pending,
+                None if pushed => match self.receiver.try_recv() {
+                    Ok(Ok(data)) => (data, 0),
+                    Ok(Err(e)) => {
+                        self.error = Some(e);
+                        return Ok(true);
+                    }
+                    Err(TryRecvError::Empty) => return Ok(true),
+                    Err(TryRecvError::Disconnected) => return Ok(false),
+                },
+                None => match self.receiver.recv() {
+                    Ok(data) => (data?, 0),
+                    Err(_) => return Ok(false),
+                },
+            };
+            while offset < data.len() {
+                let end = data.len().min(offset + max_message);
+                match ring.push(&data[offset..end]) {
+                    Ok(()) => {
+                        offset = end;
+                        pushed = true;
+                    }
+                    Err(SharedRingError::Full) if pushed => {
+                        self.pending = Some((data, offset));
+                        return Ok(true);
+                    }
+                    Err(e) => {
+                        return Err(new_error!("Failed to push to the shared ring: {}", e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The work a streaming host function's producer does for one call from
+/// the guest, which is run on the stream's own thread.
+pub(crate) type StreamJob = Box<dyn FnOnce(&mut RingWriter) -> Result<()> + Send>;
+
+/// Turns the arguments of a guest's call to a streaming host function into
+/// the [`StreamJob`] that produces its result.
+pub(crate) type StreamProducer =
+    Arc<dyn Fn(Vec<ParameterValue>) -> Result<StreamJob> + Send + Sync>;
+
+/// Wrap `producer` in the entry that a streaming host function is
+/// registered with, which describes its signature, and the
+/// [`StreamProducer`] that starts its streams.
+///
+/// The producer is not tied to a sandbox: [`call_builtin`] opens each
+/// stream in the host streams of the sandbox whose guest called the
+/// function, so sandboxes sharing the producer do not share streams.
+pub(crate) fn streaming_host_function<Args: ParameterTuple>(
+    producer: impl Fn(Args, &mut RingWriter) -> Result<()> + Send + Sync + 'static,
+) -> (FunctionEntry, StreamProducer) {
+    let producer = Arc::new(producer);
+    let stream_producer: StreamProducer = Arc::new(move |args| {
+        let args = Args::from_value(args)?;
+        let producer = producer.clone();
+        Ok(Box::new(move |writer: &mut RingWriter| producer(args, writer)) as StreamJob)
+    });
+    let entry = FunctionEntry {
+        function: TypeErasedHostFunction::new(|_| {
+            Err(new_error!(
+                "A streaming host function can only be called by a guest"
+            ))
+        }),
+        parameter_types: Args::TYPE,
+        return_type: ReturnType::ULong,
+    };
+    (entry, stream_producer)
+}
+
+/// Answer the guest's calls to streaming host functions, which open a
+/// stream and return its handle, and its [`READ_HOST_STREAM`] and
+/// [`CLOSE_HOST_STREAM`] calls, or return `None` for any other host
+/// function.
+pub(crate) fn call_builtin(
+    host_funcs: &Arc<Mutex<FunctionRegistry>>,
+    mem_mgr: &SandboxMemoryManager<HostSharedMemory>,
+    name: &str,
+    args: &[ParameterValue],
+) -> std::result::Result<Option<Result<ReturnValue>>, HandleOutbError> {
+    let (streams, producer) = {
+        let registry = host_funcs
+            .try_lock()
+            .map_err(|e| HandleOutbError::LockFailed(file!(), line!(), e.to_string()))?;
+        let producer = registry.stream_producer(name);
+        let builtin = (name == READ_HOST_STREAM || name == CLOSE_HOST_STREAM)
+            && registry.function_signature(name).is_none();
+        if producer.is_none() && !builtin {
+            return Ok(None);
+        }
+        (registry.host_streams(), producer)
+    };
+    if let Some(producer) = producer {
+        return Ok(Some(crate::metrics::maybe_time_and_emit_host_call(
+            name,
+            || {
+                let job = producer(args.to_vec())?;
+                let handle = streams
+                    .try_lock()
+                    .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                    .open(job)?;
+                Ok(ReturnValue::ULong(handle))
+            },
+        )));
+    }
+    let mut streams = streams
+        .try_lock()
+        .map_err(|e| HandleOutbError::LockFailed(file!(), line!(), e.to_string()))?;
+
+    let handle = match args {
+        [ParameterValue::ULong(handle)] => *handle,
+        _ => {
+            return Ok(Some(Err(HyperlightError::UnexpectedNoOfArguments(
+                args.len(),
+                1,
+            ))));
+        }
+    };
+    let res = if name == CLOSE_HOST_STREAM {
+        streams.close(handle).map(|()| ReturnValue::Void(()))
+    } else {
+        read(&mut streams, mem_mgr, handle).map(ReturnValue::Bool)
+    };
+    Ok(Some(res))
+}
+
+fn read(
+    streams: &mut HostStreams,
+    mem_mgr: &SandboxMemoryManager<HostSharedMemory>,
+    handle: u64,
+) -> Result<bool> {
+    let ring = mem_mgr
+        .shared_ring()?
+        .ok_or_else(|| new_error!("Host streams need a shared ring"))?;
+    let max_message = ring_capacity(mem_mgr.layout.shared_ring_size)
+        .and_then(|capacity| capacity.checked_sub(size_of::<u32>()))
+        .filter(|&max| max > 0)
+        .ok_or_else(|| new_error!("The shared ring is too small for host streams"))?;
+    streams.read(handle, &ring, max_message)
+}
+
+impl UninitializedSandbox {
+    /// Register `producer` as the host function `name`, whose result is
+    /// streamed to the guest through the shared ring rather than returned
+    /// in one piece, so that it can be larger than the guest's input
+    /// buffer and the guest can start on it before it is complete.
+    ///
+    /// Each call from the guest runs `producer` on a new thread, which
+    /// writes the result to the [`RingWriter`] it is given. The guest
+    /// reads the result with `hyperlight_guest_bin::host_stream::HostStream`
+    /// until it reaches the end, which is when `producer` returns. If
+    /// `producer` fails, the guest gets its error once it has read what was
+    /// written before it. The guest may see the result split up
+    /// differently from how it was written.
+    ///
+    /// Streams need a shared ring, see
+    /// [`SandboxConfiguration::set_shared_ring_size`](crate::sandbox::SandboxConfiguration::set_shared_ring_size),
+    /// and while one is open it is the only user of the host-to-guest
+    /// direction of the ring. The guest can read one stream at a time:
+    /// calling a streaming host function closes the stream it was reading.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+    /// # fn example() -> Result<()> {
+    /// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?;
+    /// sandbox.register_streaming_host_function("ReadLog", |(path,): (String,), writer| {
+    ///     for line in std::fs::read_to_string(path)?.lines() {
+    ///         writer.write(line.as_bytes())?;
+    ///     }
+    ///     Ok(())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_streaming_host_function<Args: ParameterTuple>(
+        &mut self,
+        name: &str,
+        producer: impl Fn(Args, &mut RingWriter) -> Result<()> + Send + Sync + 'static,
+    ) -> Result<()> {
+        let mut hfs = self
+            .host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        let (entry, producer) = streaming_host_function(producer);
+        hfs.register_streaming_host_function(name.to_string(), entry, producer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_testing::simple_guest_as_string;
+
+    use crate::sandbox::SandboxConfiguration;
+    use crate::{GuestBinary, UninitializedSandbox, new_error};
+
+    fn pattern(len: u64) -> impl Iterator<Item = u8> {
+        (0..len).map(|i| (i % 251) as u8)
+    }
+
+    fn hash(data: impl Iterator<Item = u8>) -> u64 {
+        data.fold(0u64, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u64))
+    }
+
+    #[test]
+    fn stream_larger_than_ring() {
+        let mut cfg = SandboxConfiguration::default();
+        cfg.set_shared_ring_size(0x1000);
+        let path = simple_guest_as_string().unwrap();
+        let mut u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), Some(cfg)).unwrap();
+        u_sbox
+            .register_streaming_host_function("Pattern", |(len,): (u64,), writer| {
+                let data: Vec<u8> = pattern(len).collect();
+                for chunk in data.chunks(3000) {
+                    writer.write(chunk)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        u_sbox
+            .register_streaming_host_function("Failing", |(len,): (u64,), writer| {
+                writer.write(&pattern(len).collect::<Vec<_>>())?;
+                Err(new_error!("producer failed"))
+            })
+            .unwrap();
+        let mut sbox = u_sbox.evolve().unwrap();
+
+        let len = 100_000u64;
+        let res: u64 = sbox
+            .call("HashHostStream", ("Pattern".to_string(), len, 0u32))
+            .unwrap();
+        assert_eq!(res, hash(pattern(len)));
+
+        // A stream the guest stops reading early is closed, and does not
+        // affect the next one
+        let _: u64 = sbox
+            .call("HashHostStream", ("Pattern".to_string(), len, 1u32))
+            .unwrap();
+        let res: u64 = sbox
+            .call("HashHostStream", ("Pattern".to_string(), len, 0u32))
+            .unwrap();
+        assert_eq!(res, hash(pattern(len)));
+
+        let err = sbox
+            .call::<u64>("HashHostStream", ("Failing".to_string(), 10u64, 0u32))
+            .unwrap_err();
+        assert!(err.to_string().contains("producer failed"), "{err:?}");
+    }
+}
@@ -0,0 +1,219 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Reporting the progress of a guest's initialisation, see
+//! [`UninitializedSandbox::evolve_with_progress`].
+
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnValue};
+use tracing::{Span, instrument};
+
+use super::uninitialized_evolve::evolve_impl_multi_use;
+use crate::func::ParameterTuple;
+use crate::hypervisor::InterruptHandle;
+use crate::{MultiUseSandbox, Result, UninitializedSandbox, new_error};
+
+/// A report of a guest's initialisation progress, see
+/// [`UninitializedSandbox::evolve_with_progress`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitProgress {
+    /// The units of work the guest has completed
+    pub completed: u64,
+    /// The units of work the guest expects to do in all, or 0 if it does
+    /// not know
+    pub total: u64,
+    /// What the guest is doing, as described by the guest
+    pub stage: String,
+    /// The time since the sandbox started evolving
+    pub elapsed: Duration,
+}
+
+/// The callback of the [`UninitializedSandbox::evolve_with_progress`] in
+/// progress, which the guest's reports are passed to.
+pub(crate) struct InitProgressReporter {
+    started: Instant,
+    callback: Mutex<Box<dyn FnMut(InitProgress) -> ControlFlow<()> + Send>>,
+    /// The handle of the VM being initialised, which is killed when the
+    /// callback asks to stop
+    interrupt_handle: Option<Arc<dyn InterruptHandle>>,
+}
+
+impl InitProgressReporter {
+    /// Let the callback cancel the initialisation of the VM that
+    /// `interrupt_handle` belongs to.
+    pub(crate) fn set_interrupt_handle(&mut self, interrupt_handle: Arc<dyn InterruptHandle>) {
+        self.interrupt_handle = Some(interrupt_handle);
+    }
+
+    /// Answer a guest's
+    /// [`INIT_PROGRESS`](hyperlight_common::builtin_functions::INIT_PROGRESS) call with the
+    /// work completed, the total work and the stage in `args`.
+    pub(crate) fn report(&self, args: Vec<ParameterValue>) -> Result<ReturnValue> {
+        let (completed, total, stage) = <(u64, u64, String)>::from_value(args)?;
+        let progress = InitProgress {
+            completed,
+            total,
+            stage,
+            elapsed: self.started.elapsed(),
+        };
+        let mut callback = self
+            .callback
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        if callback(progress).is_break()
+            && let Some(interrupt_handle) = &self.interrupt_handle
+        {
+            // The guest is waiting for this call, so the VM is cancelled
+            // as soon as it resumes
+            interrupt_handle.kill();
+        }
+        Ok(ReturnValue::Void(()))
+    }
+}
+
+impl UninitializedSandbox {
+    /// Evolves this sandbox as [`evolve`](Self::evolve) does, passing
+    /// `progress` each report the guest makes of its progress while it is
+    /// initialised.
+    ///
+    /// A guest opts in by reporting its progress, which a Rust guest does
+    /// with `hyperlight_guest_bin::host_comm::report_init_progress`. A
+    /// guest that does not report any progress is evolved as by
+    /// [`evolve`](Self::evolve), and reports made after the guest was
+    /// initialised are ignored. `progress` is called on the thread that
+    /// is evolving the sandbox, while the guest waits, so it should
+    /// return quickly.
+    ///
+    /// `progress` returns [`ControlFlow::Break`] to stop the guest's
+    /// initialisation, in which case this returns
+    /// [`ExecutionCanceledByHost`](crate::HyperlightError::ExecutionCanceledByHost).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::ops::ControlFlow;
+    /// # use std::time::Duration;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve_with_progress(|progress| {
+    ///     println!(
+    ///         "{}: {}/{} after {:?}",
+    ///         progress.stage, progress.completed, progress.total, progress.elapsed
+    ///     );
+    ///     if progress.elapsed > Duration::from_secs(10) {
+    ///         ControlFlow::Break(())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current(), level = "Trace")]
+    pub fn evolve_with_progress(
+        self,
+        progress: impl FnMut(InitProgress) -> ControlFlow<()> + Send + 'static,
+    ) -> Result<MultiUseSandbox> {
+        let host_funcs = self.host_funcs.clone();
+        host_funcs
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .init_progress = Some(InitProgressReporter {
+            started: Instant::now(),
+            callback: Mutex::new(Box::new(progress)),
+            interrupt_handle: None,
+        });
+        let res = evolve_impl_multi_use(self);
+        host_funcs
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .init_progress = None;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+    use std::sync::{Arc, Mutex};
+
+    use hyperlight_testing::simple_guest_as_string;
+
+    use crate::{GuestBinary, HyperlightError, UninitializedSandbox};
+
+    #[test]
+    fn evolve_with_progress() {
+        let path = simple_guest_as_string().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None)
+            .unwrap()
+            .evolve_with_progress(move |progress| {
+                recorded.lock().unwrap().push(progress);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        let reports = reports.lock().unwrap().clone();
+        let stages: Vec<_> = reports
+            .iter()
+            .map(|progress| (progress.completed, progress.total))
+            .collect();
+        assert_eq!(stages, [(0, 2), (1, 2), (2, 2)]);
+        assert!(reports.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+    }
+
+    #[test]
+    fn evolve_with_progress_stops_when_asked() {
+        let path = simple_guest_as_string().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let res = UninitializedSandbox::new(GuestBinary::FilePath(path), None)
+            .unwrap()
+            .evolve_with_progress(move |progress| {
+                let completed = progress.completed;
+                recorded.lock().unwrap().push(progress);
+                if completed == 1 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+        assert!(
+            matches!(res, Err(HyperlightError::ExecutionCanceledByHost())),
+            "unexpected result: {:?}",
+            res.err()
+        );
+
+        // The guest was not resumed after the report that stopped it
+        let stages: Vec<_> = reports
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|progress| (progress.completed, progress.total))
+            .collect();
+        assert_eq!(stages, [(0, 2), (1, 2)]);
+    }
+}
@@ -14,37 +14,69 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use std::any::Any;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use flatbuffers::FlatBufferBuilder;
 use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
 use hyperlight_common::flatbuffer_wrappers::function_types::{
     ParameterValue, ReturnType, ReturnValue,
 };
+use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
 use hyperlight_common::flatbuffer_wrappers::util::estimate_flatbuffer_capacity;
 use tracing::{Span, instrument};
 
 use super::Callable;
+use super::callback;
 use super::file_mapping::prepare_file_cow;
 use super::host_funcs::FunctionRegistry;
-use super::snapshot::Snapshot;
-use crate::func::{ParameterTuple, SupportedReturnType};
-use crate::hypervisor::InterruptHandle;
+use super::latency::Histogram;
+use super::rate_limit::CallRateLimiter;
+use super::readiness::Readiness;
+use super::registry::Registration;
+use super::snapshot::{PartialSnapshot, Snapshot};
+use crate::func::{Codec, ParameterTuple, ResultType, ReusableReturnType, SupportedReturnType};
 use crate::hypervisor::hyperlight_vm::{HyperlightVm, HyperlightVmError};
+#[cfg(target_arch = "x86_64")]
+use crate::hypervisor::regs::CommonRegisters;
+use crate::hypervisor::{HypervisorBackend, HypervisorCapabilities, InterruptHandle};
+use crate::mem::guest_ref::GuestRef;
+use crate::mem::host_region::{HostRegion, MappedHostRegions};
+use crate::mem::layout::GuestImageInfo;
 use crate::mem::memory_region::{MemoryRegion, MemoryRegionFlags};
 use crate::mem::mgr::SandboxMemoryManager;
-use crate::mem::shared_mem::{HostSharedMemory, SharedMemory as _};
+use crate::mem::shared_mem::{GuestSharedMemory, HostSharedMemory, SharedMemory as _};
+use crate::mem::shared_mut_region::SharedMutRegion;
+use crate::mem::shared_ring::HostSharedRing;
 use crate::metrics::{
-    METRIC_GUEST_ERROR, METRIC_GUEST_ERROR_LABEL_CODE, maybe_time_and_emit_guest_call,
+    METRIC_GUEST_ERROR, METRIC_GUEST_ERROR_LABEL_CODE, maybe_emit_guest_call_host_calls,
+    maybe_time_and_emit_guest_call,
 };
-use crate::{HyperlightError, Result, log_then_return};
+use crate::{HyperlightError, Result, log_then_return, new_error};
+
+/// The name of the guest function that [`MultiUseSandbox::quiesce`] calls.
+pub const QUIESCE_FUNCTION: &str = "__hyperlight_quiesce";
 
 /// A fully initialized sandbox that can execute guest functions multiple times.
 ///
 /// Guest functions can be called repeatedly while maintaining state between calls.
 /// The sandbox supports creating snapshots and restoring to previous states.
 ///
+/// ## Moving Between Threads
+///
+/// A sandbox is [`Send`], so an idle sandbox can be moved to another
+/// thread, e.g. by a work-stealing scheduler, and called from there. Guest
+/// calls borrow the sandbox mutably for as long as they run, so the type
+/// system ensures that it is never moved while a call is in flight. It is
+/// not [`Sync`]: to interrupt a call from another thread, use the
+/// [`InterruptHandle`] returned by [`interrupt_handle()`](Self::interrupt_handle).
+///
 /// ## Sandbox Poisoning
 ///
 /// The sandbox becomes **poisoned** when the guest is not run to completion, leaving it in
@@ -80,7 +112,7 @@ use crate::{HyperlightError, Result, log_then_return};
 /// eliminating any inconsistencies. See [`restore()`](Self::restore) for details.
 pub struct MultiUseSandbox {
     /// Whether this sandbox is poisoned
-    poisoned: bool,
+    pub(crate) poisoned: bool,
     pub(crate) host_funcs: Arc<Mutex<FunctionRegistry>>,
     pub(crate) mem_mgr: SandboxMemoryManager<HostSharedMemory>,
     vm: HyperlightVm,
@@ -92,7 +124,60 @@ pub struct MultiUseSandbox {
     /// Optional callback to discover page table roots from guest memory.
     /// Given (snapshot_mem, scratch_mem, cr3), returns a list of root GPAs.
     /// If not set, only CR3 is used as the single root.
-    pt_root_finder: Option<PtRootFinder>,
+    pub(crate) pt_root_finder: Option<PtRootFinder>,
+    /// Durations of the guest function calls made on this sandbox, keyed by
+    /// function name.
+    pub(crate) latency: HashMap<String, Histogram>,
+    /// The state of the sandbox right after the guest was initialised, see
+    /// [`reset_to_baseline`](Self::reset_to_baseline).
+    pub(crate) baseline: Option<Arc<Snapshot>>,
+    /// The regions mapped with [`map_host_region`](Self::map_host_region)
+    /// and their guest addresses, kept alive for as long as the VM may
+    /// access them.
+    host_regions: MappedHostRegions,
+    /// The memory behind the values mapped with
+    /// [`map_value`](Self::map_value), kept alive for as long as the VM
+    /// may access it.
+    mapped_values: Vec<GuestSharedMemory>,
+    /// The regions mapped with [`map_shared_mut`](Self::map_shared_mut),
+    /// kept alive for as long as the VM may access them.
+    shared_mut_regions: Vec<SharedMutRegion>,
+    /// The sandbox's membership of a registry, see
+    /// [`join_registry`](Self::join_registry).
+    pub(crate) registration: Option<Registration>,
+    /// The value attached with [`set_user_data`](Self::set_user_data).
+    pub(crate) user_data: Option<Box<dyn Any + Send>>,
+    /// Whether [`reset_to_baseline`](Self::reset_to_baseline) drops
+    /// `user_data`.
+    pub(crate) clear_user_data_on_reset: bool,
+    /// Cleared once the guest turns out not to implement
+    /// [`QUIESCE_FUNCTION`], so that [`quiesce`](Self::quiesce) stops
+    /// calling it.
+    pub(crate) has_quiesce_handler: bool,
+    /// The generation of the guest's registered functions that the
+    /// arguments of the next call were validated against, set by
+    /// [`call_validated`](Self::call_validated), or 0.
+    pub(crate) schema_generation: u64,
+    /// How long the guest took to initialise, see
+    /// [`init_duration`](Self::init_duration).
+    pub(crate) init_duration: Duration,
+    /// The limit set with
+    /// [`with_call_rate_limit`](Self::with_call_rate_limit), if any.
+    pub(crate) rate_limiter: Option<CallRateLimiter>,
+    /// Whether the guest has signalled that it is ready for calls, shared
+    /// with the registry that answers its signal, see
+    /// [`wait_until_ready`](Self::wait_until_ready).
+    pub(crate) readiness: Readiness,
+    /// The ranges of guest memory that
+    /// [`state_divergence`](Self::state_divergence) ignores.
+    pub(crate) state_ignored_ranges: Vec<Range<u64>>,
+    /// The configuration the sandbox was created with, which its VM is
+    /// recreated with when it is thawed, see [`freeze`](Self::freeze).
+    pub(crate) config: crate::sandbox::SandboxConfiguration,
+    /// The names given to the guest's threads with
+    /// [`set_guest_thread_name`](Self::set_guest_thread_name), by vCPU.
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) guest_thread_names: Vec<Option<String>>,
 }
 
 /// Callback for discovering page table roots from guest memory.
@@ -107,6 +192,124 @@ pub struct MultiUseSandbox {
 /// empty, only `root_pt_gpa` is used.
 pub type PtRootFinder = Box<dyn Fn(&[u8], &[u8], u64) -> Vec<u64> + Send>;
 
+/// The general purpose registers of the guest's vCPU, see
+/// [`MultiUseSandbox::run_at`].
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct GuestRegisters {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rsp: u64,
+    pub rbp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rip: u64,
+    pub rflags: u64,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<GuestRegisters> for CommonRegisters {
+    fn from(regs: GuestRegisters) -> Self {
+        let GuestRegisters {
+            rax,
+            rbx,
+            rcx,
+            rdx,
+            rsi,
+            rdi,
+            rsp,
+            rbp,
+            r8,
+            r9,
+            r10,
+            r11,
+            r12,
+            r13,
+            r14,
+            r15,
+            rip,
+            rflags,
+        } = regs;
+        CommonRegisters {
+            rax,
+            rbx,
+            rcx,
+            rdx,
+            rsi,
+            rdi,
+            rsp,
+            rbp,
+            r8,
+            r9,
+            r10,
+            r11,
+            r12,
+            r13,
+            r14,
+            r15,
+            rip,
+            rflags,
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl From<CommonRegisters> for GuestRegisters {
+    fn from(regs: CommonRegisters) -> Self {
+        let CommonRegisters {
+            rax,
+            rbx,
+            rcx,
+            rdx,
+            rsi,
+            rdi,
+            rsp,
+            rbp,
+            r8,
+            r9,
+            r10,
+            r11,
+            r12,
+            r13,
+            r14,
+            r15,
+            rip,
+            rflags,
+        } = regs;
+        GuestRegisters {
+            rax,
+            rbx,
+            rcx,
+            rdx,
+            rsi,
+            rdi,
+            rsp,
+            rbp,
+            r8,
+            r9,
+            r10,
+            r11,
+            r12,
+            r13,
+            r14,
+            r15,
+            rip,
+            rflags,
+        }
+    }
+}
+
 impl MultiUseSandbox {
     /// Move an `UninitializedSandbox` into a new `MultiUseSandbox` instance.
     ///
@@ -118,8 +321,15 @@ impl MultiUseSandbox {
         host_funcs: Arc<Mutex<FunctionRegistry>>,
         mgr: SandboxMemoryManager<HostSharedMemory>,
         vm: HyperlightVm,
+        config: crate::sandbox::SandboxConfiguration,
+        init_duration: Duration,
         #[cfg(gdb)] dbg_mem_access_fn: Arc<Mutex<SandboxMemoryManager<HostSharedMemory>>>,
     ) -> MultiUseSandbox {
+        let readiness = host_funcs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .readiness
+            .clone();
         Self {
             poisoned: false,
             host_funcs,
@@ -129,7 +339,50 @@ impl MultiUseSandbox {
             dbg_mem_access_fn,
             snapshot: None,
             pt_root_finder: None,
+            latency: HashMap::new(),
+            baseline: None,
+            host_regions: MappedHostRegions::default(),
+            mapped_values: Vec::new(),
+            shared_mut_regions: Vec::new(),
+            registration: None,
+            user_data: None,
+            clear_user_data_on_reset: false,
+            has_quiesce_handler: true,
+            schema_generation: 0,
+            init_duration,
+            rate_limiter: None,
+            readiness,
+            state_ignored_ranges: Vec::new(),
+            config,
+            #[cfg(target_arch = "x86_64")]
+            guest_thread_names: vec![None; super::guest_threads::VCPU_COUNT],
+        }
+    }
+
+    /// Record the state to return to with
+    /// [`reset_to_baseline`](Self::reset_to_baseline): `snapshot` if the
+    /// sandbox was created from a snapshot of an initialised guest, and
+    /// otherwise a new snapshot of the sandbox's current state, if the
+    /// configuration asks for one.
+    pub(super) fn capture_baseline(&mut self, snapshot: Option<Arc<Snapshot>>) -> Result<()> {
+        // The guest has only just been initialised, so there is nothing for
+        // it to quiesce
+        let baseline = match snapshot {
+            Some(snapshot) => snapshot,
+            None if self.config.get_capture_baseline() => self.take_snapshot()?,
+            None => return Ok(()),
+        };
+        self.baseline = Some(baseline);
+        Ok(())
+    }
+
+    /// Make the sandbox's current state its baseline if it does not have
+    /// one yet, for callers that reset to it.
+    pub(crate) fn ensure_baseline(&mut self) -> Result<()> {
+        if self.baseline.is_none() {
+            self.baseline = Some(self.snapshot()?);
         }
+        Ok(())
     }
 
     /// Set a callback that discovers page table roots from guest memory.
@@ -157,7 +410,9 @@ impl MultiUseSandbox {
     /// An optional [`SandboxConfiguration`](crate::sandbox::SandboxConfiguration)
     /// can be supplied to override runtime settings such as timeouts and
     /// interrupt behavior. Memory layout fields
-    /// (`input_data_size`, `output_data_size`, `heap_size`, `scratch_size`)
+    /// (`input_data_size`, `output_data_size`, `heap_size`, `stack_size`, `scratch_size`,
+    /// `shared_ring_size`, `max_alloc_per_call`, `alloc_mode`, `huge_pages`,
+    /// `detect_self_modifying_code`, `fp_exception_mask`)
     /// are always taken from the snapshot. Any values supplied in
     /// `config` for those fields are ignored.
     ///
@@ -190,18 +445,11 @@ impl MultiUseSandbox {
         host_funcs: crate::HostFunctions,
         config: Option<crate::sandbox::SandboxConfiguration>,
     ) -> Result<Self> {
-        use rand::RngExt;
-
-        use crate::mem::ptr::RawPtr;
-        use crate::sandbox::uninitialized_evolve::set_up_hypervisor_partition;
-
         // Validate that the provided host functions are a superset of
         // those required by the snapshot.
         snapshot.validate_host_functions(host_funcs.inner())?;
 
         let host_funcs = Arc::new(Mutex::new(host_funcs.into_inner()));
-
-        let stack_top_gva = snapshot.stack_top_gva();
         // Start from the caller's config (if any) so runtime fields
         // such as timeouts and interrupt knobs are honored, then
         // overwrite the layout fields from the snapshot. The on-disk
@@ -209,19 +457,60 @@ impl MultiUseSandbox {
         // caller are silently ignored. Warn if the caller passed a
         // config whose layout fields disagree with the snapshot, so
         // the override is at least visible.
-        let caller_supplied_config = config.is_some();
-        let mut config = config.unwrap_or_default();
-        if caller_supplied_config {
-            warn_on_layout_override(&config, snapshot.layout());
+        if let Some(config) = &config {
+            warn_on_layout_override(config, snapshot.layout());
         }
+        Self::from_snapshot_with_registry(snapshot, host_funcs, config.unwrap_or_default())
+    }
+
+    /// Create a `MultiUseSandbox` from a [`Snapshot`] as
+    /// [`from_snapshot`](Self::from_snapshot) does, with a registry of
+    /// host functions that has already been validated against it. The
+    /// layout fields of `config` are taken from the snapshot.
+    pub(super) fn from_snapshot_with_registry(
+        snapshot: Arc<Snapshot>,
+        host_funcs: Arc<Mutex<FunctionRegistry>>,
+        mut config: crate::sandbox::SandboxConfiguration,
+    ) -> Result<Self> {
+        use rand::RngExt;
+
+        use crate::mem::ptr::RawPtr;
+        use crate::sandbox::uninitialized_evolve::set_up_hypervisor_partition;
+
+        let stack_top_gva = snapshot.stack_top_gva();
         config.set_input_data_size(snapshot.layout().input_data_size);
         config.set_output_data_size(snapshot.layout().output_data_size);
         config.set_heap_size(snapshot.layout().heap_size as u64);
+        config.set_stack_size(snapshot.layout().stack_size as u64);
+        config.set_shared_ring_size(snapshot.layout().shared_ring_size);
         config.set_scratch_size(snapshot.layout().get_scratch_size());
+        config.set_max_alloc_per_call(snapshot.layout().max_alloc_per_call);
+        config.set_guest_allocator(snapshot.layout().alloc_mode);
+        config.set_huge_pages(snapshot.layout().huge_pages);
+        config.set_self_modifying_code_detection(snapshot.layout().detect_self_modifying_code);
+        config.set_fp_exception_mask(snapshot.layout().fp_exception_mask);
+
+        #[cfg(target_os = "linux")]
+        let snapshot = match config.get_shared_memory_backing() {
+            Some(fd) => {
+                use std::os::fd::BorrowedFd;
+
+                // SAFETY: the caller of `set_shared_memory_backing`
+                // promised that `fd` is open until this sandbox has been
+                // created, and keeps ownership of it, so it is only
+                // borrowed here to duplicate it.
+                let file = unsafe { BorrowedFd::borrow_raw(fd) }
+                    .try_clone_to_owned()
+                    .map_err(|e| new_error!("Invalid shared memory backing: {}", e))?;
+                Arc::new(snapshot.backed_by_file(&file.into())?)
+            }
+            None => snapshot,
+        };
         let load_info = snapshot.load_info();
 
         let mgr = crate::mem::mgr::SandboxMemoryManager::from_snapshot(&snapshot)?;
         let (mut hshm, gshm) = mgr.build()?;
+        hshm.max_result_bytes = config.get_max_result_bytes();
 
         let page_size = u32::try_from(page_size::get())? as usize;
 
@@ -265,6 +554,7 @@ impl MultiUseSandbox {
         let dbg_mem_access_hdl = Arc::new(Mutex::new(hshm.clone()));
 
         // noop for NextAction::Call
+        let init_start = Instant::now();
         vm.initialise(
             peb_addr,
             seed,
@@ -275,7 +565,8 @@ impl MultiUseSandbox {
             #[cfg(gdb)]
             dbg_mem_access_hdl,
         )
-        .map_err(crate::hypervisor::hyperlight_vm::HyperlightVmError::Initialize)?;
+        .map_err(crate::hypervisor::hyperlight_vm::InitializeError::promote)?;
+        let init_duration = init_start.elapsed();
 
         // If the snapshot was taken from an already-initialized guest
         // (NextAction::Call), apply the captured special registers so
@@ -296,13 +587,19 @@ impl MultiUseSandbox {
         #[cfg(gdb)]
         let dbg_mem_wrapper = Arc::new(Mutex::new(hshm.clone()));
 
-        let sbox = MultiUseSandbox::from_uninit(
+        let mut sbox = MultiUseSandbox::from_uninit(
             host_funcs,
             hshm,
             vm,
+            config,
+            init_duration,
             #[cfg(gdb)]
             dbg_mem_wrapper,
         );
+        // A snapshot of an initialised guest is already the baseline,
+        // otherwise the guest has just been initialised from it.
+        let initialised = matches!(snapshot.entrypoint(), super::snapshot::NextAction::Call(_));
+        sbox.capture_baseline(initialised.then_some(snapshot))?;
         Ok(sbox)
     }
 
@@ -316,6 +613,11 @@ impl MultiUseSandbox {
     /// [`MultiUseSandbox::from_snapshot`] for the exact compatibility
     /// rules and the error variants returned on mismatch.
     ///
+    /// Unless the sandbox has not changed since its last snapshot, the
+    /// guest is first brought to a safe point with
+    /// [`quiesce()`](Self::quiesce), and the snapshot captures its state
+    /// after that.
+    ///
     /// ## Poisoned Sandbox
     ///
     /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
@@ -348,6 +650,68 @@ impl MultiUseSandbox {
         if let Some(snapshot) = &self.snapshot {
             return Ok(snapshot.clone());
         }
+        self.quiesce()?;
+        self.take_snapshot()
+    }
+
+    /// Asks the guest to reach a safe point for a snapshot, for example
+    /// by finishing or parking work it has in flight, and returns whether
+    /// it did.
+    ///
+    /// Guests opt in by implementing a guest function named
+    /// [`QUIESCE_FUNCTION`] that takes no arguments and returns nothing,
+    /// which this calls. If the guest has no such function, this does
+    /// nothing and returns `false`, and the sandbox does not call it again.
+    /// [`snapshot()`](Self::snapshot) calls this itself, so it only needs
+    /// to be called directly to bring the guest to a safe point for some
+    /// other reason.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned, and poisons it if the handler does not run to
+    /// completion, like any other guest call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// sandbox.call::<()>("StartWork", ())?;
+    /// if !sandbox.quiesce()? {
+    ///     println!("the guest does not support quiescing");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn quiesce(&mut self) -> Result<bool> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        if !self.has_quiesce_handler {
+            return Ok(false);
+        }
+        self.snapshot = None;
+        match self.without_call_rate_limit(|sandbox| {
+            sandbox.call_guest_function_by_name_no_reset(QUIESCE_FUNCTION, ReturnType::Void, vec![])
+        }) {
+            Ok(_) => Ok(true),
+            Err(HyperlightError::GuestError(ErrorCode::GuestFunctionNotFound, _)) => {
+                self.has_quiesce_handler = false;
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Captures the sandbox's current state, without quiescing the guest.
+    pub(super) fn take_snapshot(&mut self) -> Result<Arc<Snapshot>> {
         let mapped_regions_iter = self.vm.get_mapped_regions();
         let mapped_regions_vec: Vec<MemoryRegion> = mapped_regions_iter.cloned().collect();
         // Get CR3 from the vCPU
@@ -404,6 +768,14 @@ impl MultiUseSandbox {
     /// [`SnapshotHostFunctionMismatch`](crate::HyperlightError::SnapshotHostFunctionMismatch)
     /// carrying the missing names and signature differences.
     ///
+    /// Restoring unmaps every region and value mapped since the sandbox was
+    /// created.
+    /// The regions mapped with [`map_host_region`](Self::map_host_region)
+    /// are checked to still hold what they were created with, see
+    /// [`HostRegion::is_intact`]. A modified region does not fail the
+    /// restore, it is logged and reported by
+    /// [`check_host_regions`](Self::check_host_regions).
+    ///
     /// ## Poison State Recovery
     ///
     /// This method automatically clears any poison state when successful. This is safe because:
@@ -539,6 +911,12 @@ impl MultiUseSandbox {
                 .unmap_region(region)
                 .map_err(HyperlightVmError::UnmapRegion)?;
         }
+        self.mapped_values.clear();
+        self.shared_mut_regions.clear();
+        // The host regions are no longer mapped, so they need not be kept
+        // alive. Releasing them checks that the guest did not manage to
+        // write to them, see `check_host_regions`.
+        self.host_regions.release();
 
         // The restored snapshot is now our most current snapshot
         self.snapshot = Some(snapshot.clone());
@@ -557,6 +935,199 @@ impl MultiUseSandbox {
         Ok(())
     }
 
+    /// Restores the sandbox to its state right after the guest was
+    /// initialised, by [`evolve()`](crate::UninitializedSandbox::evolve) or
+    /// [`from_snapshot()`](Self::from_snapshot), discarding everything every
+    /// guest call since has done.
+    ///
+    /// This is equivalent to creating a new sandbox from the same guest, but
+    /// much cheaper, so it suits servers that want each request to start
+    /// from a fresh sandbox. Like [`restore()`](Self::restore), it also
+    /// recovers a poisoned sandbox.
+    ///
+    /// The baseline is a snapshot taken when the sandbox is created, if
+    /// [`SandboxConfiguration::set_capture_baseline`](crate::sandbox::SandboxConfiguration::set_capture_baseline)
+    /// asks for it, or the snapshot of an initialised guest the sandbox was
+    /// created from. Without one, this returns
+    /// [`NoMemorySnapshot`](crate::HyperlightError::NoMemorySnapshot), and
+    /// [`compact_snapshots()`](Self::compact_snapshots) can make the
+    /// sandbox's current state its baseline instead. Host function registrations and mapped regions are
+    /// handled as by [`restore()`](Self::restore). The value attached with
+    /// [`set_user_data()`](Self::set_user_data) is kept, unless
+    /// [`clear_user_data_on_reset()`](Self::clear_user_data_on_reset) says
+    /// otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::SandboxConfiguration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut config = SandboxConfiguration::default();
+    /// config.set_capture_baseline(true);
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     Some(config)
+    /// )?.evolve()?;
+    ///
+    /// for request in ["first", "second"] {
+    ///     let _ = sandbox.call::<String>("HandleRequest", request.to_string());
+    ///     sandbox.reset_to_baseline()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn reset_to_baseline(&mut self) -> Result<()> {
+        let baseline = self
+            .baseline
+            .clone()
+            .ok_or(HyperlightError::NoMemorySnapshot)?;
+        self.restore(baseline)?;
+        if self.clear_user_data_on_reset {
+            self.user_data = None;
+        }
+        Ok(())
+    }
+
+    /// Makes the sandbox's current state its baseline, dropping the
+    /// snapshot of its earlier baseline, and returns the number of bytes of
+    /// snapshot memory that freed.
+    ///
+    /// A long-lived sandbox with a baseline holds on to the snapshot of its
+    /// state right after initialisation for
+    /// [`reset_to_baseline()`](Self::reset_to_baseline),
+    /// as well as the snapshot of its current state once one has been
+    /// taken. This collapses the two into one, for callers that no longer
+    /// need to go back past the current state: afterwards
+    /// `reset_to_baseline()` returns to the state the sandbox is in now.
+    /// The guest is first brought to a safe point as by
+    /// [`snapshot()`](Self::snapshot).
+    ///
+    /// Snapshots are reference counted, so the earlier baseline is only
+    /// freed, and its size only counted, if nothing else holds it, such as
+    /// a snapshot returned by `snapshot()` or another sandbox created from
+    /// it.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// sandbox.call::<()>("LoadModel", "model.bin".to_string())?;
+    /// // Requests now start from the loaded model
+    /// let reclaimed = sandbox.compact_snapshots()?;
+    /// println!("freed {reclaimed} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn compact_snapshots(&mut self) -> Result<usize> {
+        let current = self.snapshot()?;
+        let Some(previous) = self.baseline.replace(current.clone()) else {
+            return Ok(0);
+        };
+        if Arc::ptr_eq(&previous, &current) {
+            return Ok(0);
+        }
+        // The previous baseline is freed when it is dropped below if this
+        // was the last reference to it
+        Ok(match Arc::into_inner(previous) {
+            Some(previous) => previous.size(),
+            None => 0,
+        })
+    }
+
+    /// Copies the contents of the given ranges of guest virtual memory, so
+    /// that they can later be put back with
+    /// [`restore_partial`](Self::restore_partial).
+    ///
+    /// This is much cheaper than a full [`snapshot`](Self::snapshot) when a
+    /// computation is known to only modify a small part of guest memory,
+    /// such as a scratch buffer. Each range must be non-empty and mapped to
+    /// the sandbox's own memory; overlapping and adjacent ranges are merged.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let buffer: u64 = sandbox.call("GetBufferAddress", ())?;
+    /// let clean = sandbox.snapshot_regions(&[buffer..buffer + 4096])?;
+    /// for input in ["a", "b", "c"] {
+    ///     sandbox.call::<()>("Process", input.to_string())?;
+    ///     sandbox.restore_partial(&clean)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn snapshot_regions(&mut self, ranges: &[Range<u64>]) -> Result<PartialSnapshot> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        let root_pt = self
+            .vm
+            .get_root_pt()
+            .map_err(|e| HyperlightError::HyperlightVmError(e.into()))?;
+        PartialSnapshot::capture(&mut self.mem_mgr, root_pt, ranges)
+    }
+
+    /// Writes the memory captured by
+    /// [`snapshot_regions`](Self::snapshot_regions) back to the guest.
+    ///
+    /// Only the captured ranges are restored: unlike [`restore`](Self::restore),
+    /// anything else the guest changed since, including its registers, is
+    /// left as is. A warning is logged if the guest wrote to memory outside
+    /// the captured ranges since they were captured, although writes to
+    /// pages the guest had already written to before then, and to the
+    /// scratch region that holds its stack, are not detected.
+    ///
+    /// Nothing is restored if a range is no longer mapped, or can no longer
+    /// be written by the guest, which is the case for memory the guest has
+    /// not written to since the sandbox was last restored from a full
+    /// snapshot.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Since it does not reset the guest's execution
+    /// state, a partial restore cannot recover a poisoned sandbox; use
+    /// [`restore()`](Self::restore) for that.
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn restore_partial(&mut self, snapshot: &PartialSnapshot) -> Result<()> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        let root_pt = self
+            .vm
+            .get_root_pt()
+            .map_err(|e| HyperlightError::HyperlightVmError(e.into()))?;
+        snapshot.restore_to(&mut self.mem_mgr, root_pt)?;
+        self.snapshot = None;
+        Ok(())
+    }
+
     /// Calls a guest function by name with the specified arguments.
     ///
     /// Changes made to the sandbox during execution are *not* persisted.
@@ -713,351 +1284,511 @@ impl MultiUseSandbox {
         })
     }
 
-    /// Maps a region of host memory into the sandbox address space.
+    /// Calls a guest function as [`call`](Self::call) does, tagging the
+    /// call with `request_id` to correlate it with the request it serves
+    /// across the host/guest boundary.
     ///
-    /// The base address and length must meet platform alignment requirements
-    /// (typically page-aligned). The `region_type` field is ignored as guest
-    /// page table entries are not created.
+    /// The call runs in a span with a `request_id` field, which every span
+    /// and event recorded during the call, including the guest's log lines
+    /// and spans, is nested in. The guest can also fetch the id itself, for
+    /// its own logging, with
+    /// `hyperlight_guest_bin::host_comm::current_request_id()`, which
+    /// returns `None` outside of such a call.
     ///
     /// ## Poisoned Sandbox
     ///
     /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
-    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    /// is already poisoned before the call. Use [`restore()`](Self::restore) to recover from
+    /// a poisoned state.
     ///
-    /// # Safety
+    /// # Examples
     ///
-    /// The caller must ensure the host memory region remains valid and unmodified
-    /// for the lifetime of `self`.
-    #[instrument(err(Debug), skip(self, rgn), parent = Span::current())]
-    pub unsafe fn map_region(&mut self, rgn: &MemoryRegion) -> Result<()> {
-        if self.poisoned {
-            return Err(crate::HyperlightError::PoisonedSandbox);
-        }
-        if rgn.flags.contains(MemoryRegionFlags::WRITE) {
-            // TODO: Implement support for writable mappings, which
-            // need to be registered with the memory manager so that
-            // writes can be rolled back when necessary.
-            log_then_return!("TODO: Writable mappings not yet supported");
-        }
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let reply: String = sandbox.call_with_request_id(
+    ///     "HandleRequest",
+    ///     "input".to_string(),
+    ///     "7f3a9c2e",
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_with_request_id<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        request_id: &str,
+    ) -> Result<Output> {
+        let span = tracing::info_span!("call_with_request_id", request_id, func_name);
+        let _entered = span.enter();
 
-        // Map first so overlaps are rejected before resetting the snapshot
-        unsafe { self.vm.map_region(rgn) }.map_err(HyperlightVmError::MapRegion)?;
-        self.snapshot = None;
+        self.set_request_id(Some(request_id.to_string()))?;
+        let res = self.call(func_name, args);
+        // Reset the id whether or not the call succeeded, and report the
+        // call's own error over a failure to reset it
+        let reset = self.set_request_id(None);
+        let output = res?;
+        reset?;
+        Ok(output)
+    }
+
+    fn set_request_id(&mut self, request_id: Option<String>) -> Result<()> {
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .request_id = request_id;
         Ok(())
     }
 
-    /// Map the contents of a file into the guest at a particular address
-    ///
-    /// An optional `label` identifies this mapping in the PEB's
-    /// `FileMappingInfo` array (max 63 bytes, defaults to the file name).
+    /// Calls a guest function whose only result is a status code, such as a
+    /// C guest function that returns `hl_return_status(code)`.
     ///
-    /// Returns the length of the mapping in bytes.
+    /// The status is returned as `Ok` whatever its value, and what it means
+    /// is up to the guest. A guest function that fails instead, for example
+    /// by setting an error with `hl_set_error` or by aborting, returns `Err`
+    /// as with [`call`](Self::call), so a failure is never mistaken for a
+    /// status.
     ///
     /// ## Poisoned Sandbox
     ///
     /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
     /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
-    #[instrument(err(Debug), skip(self, file_path, guest_base, label), parent = Span::current())]
-    pub fn map_file_cow(
-        &mut self,
-        file_path: &Path,
-        guest_base: u64,
-        label: Option<&str>,
-    ) -> Result<u64> {
-        if self.poisoned {
-            return Err(crate::HyperlightError::PoisonedSandbox);
-        }
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// match sandbox.call_status("Validate", "input".to_string())? {
+    ///     0 => println!("valid"),
+    ///     status => println!("invalid: {status}"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_status(&mut self, func_name: &str, args: impl ParameterTuple) -> Result<i32> {
+        self.call::<i32>(func_name, args)
+    }
 
-        // Pre-check the file mapping limit before doing any expensive
-        // OS or VM work. The PEB count is the source of truth.
-        #[cfg(feature = "nanvix-unstable")]
-        let current_count = self
-            .mem_mgr
-            .shared_mem
-            .read::<u64>(self.mem_mgr.layout.get_file_mappings_size_offset())?
-            as usize;
-        #[cfg(feature = "nanvix-unstable")]
-        if current_count >= hyperlight_common::mem::MAX_FILE_MAPPINGS {
-            return Err(crate::HyperlightError::Error(format!(
-                "map_file_cow: file mapping limit reached ({} of {})",
-                current_count,
-                hyperlight_common::mem::MAX_FILE_MAPPINGS,
-            )));
-        }
-
-        // Phase 1: host-side OS work (open file, create mapping)
-        let mut prepared = prepare_file_cow(file_path, guest_base, label)?;
-
-        // Validate that the full mapped range doesn't overlap the
-        // sandbox's primary shared memory region.
-        let shared_size = self.mem_mgr.shared_mem.mem_size() as u64;
-        let base_addr = crate::mem::layout::SandboxMemoryLayout::BASE_ADDRESS as u64;
-        let shared_end = base_addr.checked_add(shared_size).ok_or_else(|| {
-            crate::HyperlightError::Error("shared memory end overflow".to_string())
-        })?;
-        let mapping_end = guest_base
-            .checked_add(prepared.size as u64)
-            .ok_or_else(|| {
-                crate::HyperlightError::Error(format!(
-                    "map_file_cow: guest address overflow: {:#x} + {:#x}",
-                    guest_base, prepared.size
-                ))
-            })?;
-        if guest_base < shared_end && mapping_end > base_addr {
-            return Err(crate::HyperlightError::Error(format!(
-                "map_file_cow: mapping [{:#x}..{:#x}) overlaps sandbox shared memory [{:#x}..{:#x})",
-                guest_base, mapping_end, base_addr, shared_end,
-            )));
-        }
-
-        // Phase 2: VM-side work (map into guest address space)
-        let region = prepared.to_memory_region()?;
-
-        unsafe { self.vm.map_region(&region) }
-            .map_err(HyperlightVmError::MapRegion)
-            .map_err(crate::HyperlightError::HyperlightVmError)?;
-
-        self.snapshot = None;
-
-        let size = prepared.size as u64;
-
-        // Mark consumed immediately after map_region succeeds.
-        // On Windows, WhpVm::map_memory copies the file mapping handle
-        // into its own `file_mappings` vec for cleanup on drop. If we
-        // deferred mark_consumed(), both PreparedFileMapping::drop and
-        // WhpVm::drop would release the same handle — a double-close.
-        // On Linux the hypervisor holds a reference to the host mmap;
-        // freeing it here would leave a dangling backing.
-        prepared.mark_consumed();
-
-        // Record the mapping metadata in the PEB. If this fails the VM
-        // still holds a valid mapping but the PEB won't list it — the
-        // limit was already pre-checked above so this should not fail
-        // in practice.
-        #[cfg(feature = "nanvix-unstable")]
-        self.mem_mgr
-            .write_file_mapping_entry(prepared.guest_base, size, &prepared.label)?;
-
-        Ok(size)
+    /// Calls a guest function that returns a status code together with a
+    /// payload, such as a Rust guest function returning `(i32, Vec<u8>)`
+    /// or a C guest function that returns
+    /// `hl_flatbuffer_result_from_status_payload(code, data, len)`.
+    ///
+    /// As with [`call_status`](Self::call_status), the status is returned
+    /// as `Ok` whatever its value, and a guest function that fails returns
+    /// `Err`.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let (status, payload) = sandbox.call_status_payload("Lookup", "key".to_string())?;
+    /// if status == 0 {
+    ///     println!("found {} bytes", payload.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_status_payload(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<(i32, Vec<u8>)> {
+        self.call::<(i32, Vec<u8>)>(func_name, args)
     }
 
-    /// Calls a guest function with type-erased parameters and return values.
+    /// Calls a guest function that returns a byte vector or a string, as
+    /// chosen by `Output`, and decodes its result into `buf` rather than a
+    /// newly allocated value.
     ///
-    /// This function is used for fuzz testing parameter and return type handling.
+    /// `buf` is cleared first, and its allocation is reused whenever it is
+    /// large enough to hold the value, so calling this in a loop with the
+    /// same buffer avoids allocating for each result. For a string the bytes are not checked
+    /// to be UTF-8, see [`call_reuse`](Self::call_reuse) for that. Otherwise
+    /// the call is made as by [`call`](Self::call).
     ///
     /// ## Poisoned Sandbox
     ///
     /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
     /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
-    #[cfg(feature = "fuzzing")]
-    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
-    pub fn call_type_erased_guest_function_by_name(
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let mut buf = Vec::new();
+    /// for i in 0..1000 {
+    ///     sandbox.call_into::<Vec<u8>>("Render", i, &mut buf)?;
+    ///     // ... use buf ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, args, buf), parent = Span::current())]
+    pub fn call_into<Output: ReusableReturnType>(
         &mut self,
         func_name: &str,
-        ret_type: ReturnType,
-        args: Vec<ParameterValue>,
-    ) -> Result<ReturnValue> {
+        args: impl ParameterTuple,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
         if self.poisoned {
             return Err(crate::HyperlightError::PoisonedSandbox);
         }
         // Reset snapshot since we are mutating the sandbox state
         self.snapshot = None;
         maybe_time_and_emit_guest_call(func_name, || {
-            self.call_guest_function_by_name_no_reset(func_name, ret_type, args)
+            let return_type = self.call_guest_function_by_name_inner(
+                func_name,
+                Output::TYPE,
+                args.into_value(),
+                None,
+                |mem_mgr| mem_mgr.get_guest_function_call_result_into(buf),
+            )?;
+            if return_type != Output::TYPE {
+                buf.clear();
+                return Err(crate::new_error!(
+                    "Guest function {} returned {:?} but {:?} was expected",
+                    func_name,
+                    return_type,
+                    Output::TYPE
+                ));
+            }
+            Ok(())
         })
     }
 
-    fn call_guest_function_by_name_no_reset(
+    /// Calls a guest function as by [`call_into`](Self::call_into), reusing
+    /// the allocation of `output` for its result.
+    ///
+    /// On success `output` is replaced with the result. On failure it is
+    /// left empty.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[instrument(err(Debug), skip(self, args, output), parent = Span::current())]
+    pub fn call_reuse<Output: ReusableReturnType>(
         &mut self,
-        function_name: &str,
-        return_type: ReturnType,
-        args: Vec<ParameterValue>,
-    ) -> Result<ReturnValue> {
-        if self.poisoned {
-            return Err(crate::HyperlightError::PoisonedSandbox);
-        }
-        // ===== KILL() TIMING POINT 1 =====
-        // Clear any stale cancellation from a previous guest function call or if kill() was called too early.
-        // Any kill() that completed (even partially) BEFORE this line has NO effect on this call.
-        self.vm.clear_cancel();
-
-        let res = (|| {
-            let estimated_capacity = estimate_flatbuffer_capacity(function_name, &args);
-
-            let fc = FunctionCall::new(
-                function_name.to_string(),
-                Some(args),
-                FunctionCallType::Guest,
-                return_type,
-            );
-
-            let mut builder = FlatBufferBuilder::with_capacity(estimated_capacity);
-            let buffer = fc.encode(&mut builder);
-
-            self.mem_mgr.write_guest_function_call(buffer)?;
-
-            let dispatch_res = self.vm.dispatch_call_from_host(
-                &mut self.mem_mgr,
-                &self.host_funcs,
-                #[cfg(gdb)]
-                self.dbg_mem_access_fn.clone(),
-            );
-
-            // Convert dispatch errors to HyperlightErrors to maintain backwards compatibility
-            // but first determine if sandbox should be poisoned
-            if let Err(e) = dispatch_res {
-                let (error, should_poison) = e.promote();
-                self.poisoned |= should_poison;
-                return Err(error);
-            }
-
-            let guest_result = self.mem_mgr.get_guest_function_call_result()?.into_inner();
-
-            match guest_result {
-                Ok(val) => Ok(val),
-                Err(guest_error) => {
-                    metrics::counter!(
-                        METRIC_GUEST_ERROR,
-                        METRIC_GUEST_ERROR_LABEL_CODE => (guest_error.code as u64).to_string()
-                    )
-                    .increment(1);
-
-                    Err(HyperlightError::GuestError(
-                        guest_error.code,
-                        guest_error.message,
-                    ))
-                }
-            }
-        })();
-
-        // Clear partial abort bytes so they don't leak across calls.
-        self.mem_mgr.abort_buffer.clear();
-
-        // In the happy path we do not need to clear io-buffers from the host because:
-        // - the serialized guest function call is zeroed out by the guest during deserialization, see call to `try_pop_shared_input_data_into::<FunctionCall>()`
-        // - the serialized guest function result is zeroed out by us (the host) during deserialization, see `get_guest_function_call_result`
-        // - any serialized host function call are zeroed out by us (the host) during deserialization, see `get_host_function_call`
-        // - any serialized host function result is zeroed out by the guest during deserialization, see `get_host_return_value`
-        if let Err(e) = &res {
-            self.mem_mgr.clear_io_buffers();
-
-            // Determine if we should poison the sandbox.
-            self.poisoned |= e.is_poison_error();
-        }
-
-        // Note: clear_call_active() is automatically called when _guard is dropped here
-
-        res
+        func_name: &str,
+        args: impl ParameterTuple,
+        output: &mut Output,
+    ) -> Result<()> {
+        let mut buf = output.take_buffer();
+        self.call_into::<Output>(func_name, args, &mut buf)?;
+        *output = Output::from_buffer(buf)?;
+        Ok(())
     }
 
-    /// Returns a handle for interrupting guest execution.
+    /// Calls a guest function by name, passing it a handle to `callback` as
+    /// an additional last parameter of type `u64`.
+    ///
+    /// The guest invokes the callback through the handle with
+    /// `hyperlight_guest_bin::host_comm::call_host_callback`, which calls
+    /// `callback` on this thread with the arguments the guest passed. This
+    /// saves registering a named host function for a callback that is only
+    /// needed by one call. The handle is only valid until this call returns,
+    /// and a callback cannot be invoked again while it is running.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
-    /// # use std::thread;
-    /// # use std::time::Duration;
     /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
     ///     GuestBinary::FilePath("guest.bin".into()),
     ///     None
     /// )?.evolve()?;
     ///
-    /// // Get interrupt handle before starting long-running operation
-    /// let interrupt_handle = sandbox.interrupt_handle();
-    ///
-    /// // Spawn thread to interrupt after timeout
-    /// let handle_clone = interrupt_handle.clone();
-    /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(5));
-    ///     handle_clone.kill();
-    /// });
-    ///
-    /// // This call may be interrupted by the spawned thread
-    /// let result = sandbox.call_guest_function_by_name::<i32>("LongRunningFunction", ());
+    /// // The guest calls the callback once for each item it visits
+    /// let mut visited = Vec::new();
+    /// let count: i32 = sandbox.call_with_callback("VisitItems", (), |item: String| {
+    ///     visited.push(item);
+    /// })?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn interrupt_handle(&self) -> Arc<dyn InterruptHandle> {
-        self.vm.interrupt_handle()
+    #[instrument(err(Debug), skip(self, args, callback), parent = Span::current())]
+    pub fn call_with_callback<Output, Args, Ret>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        mut callback: impl FnMut(Args) -> Ret,
+    ) -> Result<Output>
+    where
+        Output: SupportedReturnType,
+        Args: ParameterTuple,
+        Ret: ResultType<HyperlightError>,
+    {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+        let mut callback = |args: Vec<ParameterValue>| -> Result<ReturnValue> {
+            let ret = callback(Args::from_value(args)?).into_result()?;
+            Ok(ret.into_value())
+        };
+        callback::with_callback(&mut callback, |handle| {
+            let mut args = args.into_value();
+            args.push(ParameterValue::ULong(handle));
+            maybe_time_and_emit_guest_call(func_name, || {
+                let ret = self.call_guest_function_by_name_no_reset(func_name, Output::TYPE, args);
+                Ok(Output::from_value(ret?)?)
+            })
+        })
     }
 
-    /// Generate a crash dump of the current state of the VM underlying this sandbox.
-    ///
-    /// Creates an ELF core dump file that can be used for debugging. The dump
-    /// captures the current state of the sandbox including registers, memory regions,
-    /// and other execution context.
-    ///
-    /// The location of the core dump file is determined by the `HYPERLIGHT_CORE_DUMP_DIR`
-    /// environment variable. If not set, it defaults to the system's temporary directory.
-    ///
-    /// This is only available when the `crashdump` feature is enabled and then only if the sandbox
-    /// is also configured to allow core dumps (which is the default behavior).
+    /// Calls a guest function by name, cancelling it if it has not completed
+    /// within `timeout`.
     ///
-    /// This can be useful for generating a crash dump from gdb when trying to debug issues in the
-    /// guest that dont cause crashes (e.g. a guest function that does not return)
+    /// If the timeout expires the guest is interrupted as if
+    /// [`InterruptHandle::kill`] had been called, the sandbox is poisoned and
+    /// the call fails with
+    /// [`GuestCallTimedOut`](crate::HyperlightError::GuestCallTimedOut).
     ///
     /// # Examples
     ///
-    /// Attach to your running process with gdb and call this function:
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use std::time::Duration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
     ///
-    /// ```shell
-    /// sudo gdb -p <pid_of_your_process>
-    /// (gdb) info threads
-    /// # find the thread that is running the guest function you want to debug
-    /// (gdb) thread <thread_number>
-    /// # switch to the frame where you have access to your MultiUseSandbox instance
-    /// (gdb) backtrace
-    /// (gdb) frame <frame_number>
-    /// # get the pointer to your MultiUseSandbox instance
-    /// # Get the sandbox pointer
-    /// (gdb) print sandbox
-    /// # Call the crashdump function
-    /// call sandbox.generate_crashdump()
+    /// let result: i32 = sandbox.call_with_timeout("Add", (1i32, 2i32), Duration::from_secs(1))?;
+    /// # Ok(())
+    /// # }
     /// ```
-    /// The crashdump should be available in crash dump directory (see `HYPERLIGHT_CORE_DUMP_DIR` env var).
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_with_timeout<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        timeout: Duration,
+    ) -> Result<Output> {
+        self.call_with_deadline(func_name, args, timeout, false)
+    }
+
+    /// Calls a guest function by name, cancelling it if it has not completed
+    /// within `timeout`, and salvaging any partial result.
     ///
-    #[cfg(crashdump)]
-    #[instrument(err(Debug), skip_all, parent = Span::current())]
-    pub fn generate_crashdump(&mut self) -> Result<()> {
-        crate::hypervisor::crashdump::generate_crashdump(&self.vm, &mut self.mem_mgr, None)
+    /// This behaves like [`call_with_timeout`](Self::call_with_timeout), except
+    /// that when the timeout expires the
+    /// [`GuestCallTimedOut`](crate::HyperlightError::GuestCallTimedOut) error
+    /// carries the last partial result the guest published (see
+    /// `hyperlight_guest_bin::host_comm::publish_partial_result`) before it
+    /// was cancelled, so the caller can keep the work done so far.
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_with_timeout_partial<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        timeout: Duration,
+    ) -> Result<Output> {
+        self.call_with_deadline(func_name, args, timeout, true)
     }
 
-    /// Generate a crash dump of the current state of the VM, writing to `dir`.
+    fn call_with_deadline<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+        timeout: Duration,
+        collect_partial_result: bool,
+    ) -> Result<Output> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+
+        // Nothing is ever sent on this channel, it is disconnected when the
+        // call completes which wakes the watchdog before the timeout.
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let interrupt_handle = self.interrupt_handle();
+        let watchdog = thread::spawn(move || match done_rx.recv_timeout(timeout) {
+            Err(RecvTimeoutError::Timeout) => {
+                interrupt_handle.kill();
+                true
+            }
+            _ => false,
+        });
+
+        let mut partial_result = None;
+        let res = maybe_time_and_emit_guest_call(func_name, || {
+            self.call_guest_function_by_name_inner(
+                func_name,
+                Output::TYPE,
+                args.into_value(),
+                collect_partial_result.then_some(&mut partial_result),
+                read_return_value,
+            )
+        });
+
+        drop(done_tx);
+        let timed_out = watchdog
+            .join()
+            .map_err(|_| crate::new_error!("Guest call timeout watchdog thread panicked"))?;
+
+        match res {
+            // If the call completed before the cancellation took effect the
+            // result is still returned to the caller.
+            Err(HyperlightError::ExecutionCanceledByHost()) if timed_out => {
+                Err(HyperlightError::GuestCallTimedOut {
+                    timeout,
+                    partial_result,
+                })
+            }
+            res => Ok(Output::from_value(res?)?),
+        }
+    }
+
+    /// Maps a region of host memory into the sandbox address space.
     ///
-    /// Like [`generate_crashdump`](Self::generate_crashdump), but the core dump
-    /// file is placed in `dir` instead of consulting the `HYPERLIGHT_CORE_DUMP_DIR`
-    /// environment variable.  This avoids the need for callers to use
-    /// `unsafe { std::env::set_var(...) }`.
-    #[cfg(crashdump)]
-    #[instrument(err(Debug), skip_all, parent = Span::current())]
-    pub fn generate_crashdump_to_dir(&mut self, dir: impl Into<String>) -> Result<()> {
-        crate::hypervisor::crashdump::generate_crashdump(
-            &self.vm,
-            &mut self.mem_mgr,
-            Some(dir.into()),
-        )
+    /// The base address and length must meet platform alignment requirements
+    /// (typically page-aligned). The `region_type` field is ignored as guest
+    /// page table entries are not created.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the host memory region remains valid and unmodified
+    /// for the lifetime of `self`.
+    #[instrument(err(Debug), skip(self, rgn), parent = Span::current())]
+    pub unsafe fn map_region(&mut self, rgn: &MemoryRegion) -> Result<()> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        if rgn.flags.contains(MemoryRegionFlags::WRITE) {
+            // TODO: Implement support for writable mappings, which
+            // need to be registered with the memory manager so that
+            // writes can be rolled back when necessary.
+            log_then_return!("TODO: Writable mappings not yet supported");
+        }
+
+        // Map first so overlaps are rejected before resetting the snapshot
+        unsafe { self.vm.map_region(rgn) }.map_err(HyperlightVmError::MapRegion)?;
+        self.snapshot = None;
+        Ok(())
     }
 
-    /// Returns whether the sandbox is currently poisoned.
+    /// Whether any regions are mapped into the sandbox besides its own
+    /// memory, which snapshots do not include.
+    pub(crate) fn has_mapped_regions(&self) -> bool {
+        self.vm.get_mapped_regions().next().is_some()
+    }
+
+    /// Maps `region` read-only into the sandbox address space at
+    /// `guest_base`, which must be page-aligned.
     ///
-    /// A poisoned sandbox is in an inconsistent state due to the guest not running to completion.
-    /// All operations will be rejected until the sandbox is restored from a non-poisoned snapshot.
+    /// Unlike [`map_region`](Self::map_region) this is safe, since the
+    /// sandbox keeps the memory behind `region` alive until it is dropped.
     ///
-    /// ## Causes of Poisoning
+    /// ## Poisoned Sandbox
     ///
-    /// The sandbox becomes poisoned when guest execution is interrupted:
-    /// - **Panics/Aborts** - Guest code panics or calls `abort()`
-    /// - **Invalid Memory Access** - Read/write/execute violations  
-    /// - **Stack Overflow** - Guest exhausts stack space
-    /// - **Heap Exhaustion** - Guest runs out of heap memory
-    /// - **Forced Termination** - [`InterruptHandle::kill()`] called during execution
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
     ///
-    /// ## Recovery
+    /// # Examples
     ///
-    /// To clear the poison state, use [`restore()`](Self::restore) with a snapshot
-    /// that was taken before the sandbox became poisoned.
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::mem::host_region::HostRegion;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let region = HostRegion::from_vec(std::fs::read("model.bin")?)?;
+    /// sandbox.map_host_region(&region, 0x1_0000_0000)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, region), parent = Span::current())]
+    pub fn map_host_region(&mut self, region: &HostRegion, guest_base: u64) -> Result<()> {
+        let rgn = region.memory_region(guest_base)?;
+        // Safety: the memory is never written to after the region is
+        // created, and is kept alive by `host_regions` until it is
+        // unmapped by `restore` or the VM has been dropped.
+        unsafe { self.map_region(&rgn) }?;
+        self.host_regions.push(guest_base, region.clone());
+        Ok(())
+    }
+
+    /// Checks that the regions mapped with
+    /// [`map_host_region`](Self::map_host_region) and
+    /// [`map_code_region`](Self::map_code_region) still hold what they
+    /// were created with, see [`HostRegion::is_intact`].
+    ///
+    /// Regions are also checked when they are released by
+    /// [`restore()`](Self::restore) or
+    /// [`reset_to_baseline()`](Self::reset_to_baseline), which do not fail
+    /// because of them. This returns
+    /// [`HostRegionModified`](crate::HyperlightError::HostRegionModified)
+    /// for the first released region found modified since it was last
+    /// called, or else for the first mapped region that is modified.
+    /// Regions released when the sandbox is dropped are only logged.
+    ///
+    /// Like `is_intact`, this is a best-effort check that should only be
+    /// relied on while no guest call is running.
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn check_host_regions(&mut self) -> Result<()> {
+        self.host_regions.check()
+    }
+
+    /// Maps a copy of `code` into the sandbox address space at `guest_va`,
+    /// which must be page-aligned, so that the guest can execute it, e.g.
+    /// code that the host has just-in-time compiled for the guest.
+    ///
+    /// The code is mapped readable and executable but never writable, so
+    /// the guest cannot modify it after the host has produced it. A guest
+    /// write to it fails with [`crate::HyperlightError::MemoryAccessViolation`].
+    /// As with [`map_host_region`](Self::map_host_region), the guest maps
+    /// `guest_va` into its own page tables before it jumps to the code, and
+    /// the mapping lasts until the sandbox is restored to a snapshot.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
     ///
     /// # Examples
     ///
@@ -1069,265 +1800,1994 @@ impl MultiUseSandbox {
     ///     None
     /// )?.evolve()?;
     ///
-    /// // Check if sandbox is poisoned
-    /// if sandbox.poisoned() {
-    ///     println!("Sandbox is poisoned and needs attention");
-    /// }
+    /// // nop; ret
+    /// sandbox.map_code_region(&[0x90, 0xC3], 0x1_0000_0000)?;
+    /// sandbox.call::<bool>("ExecMappedBuffer", (0x1_0000_0000u64, 2u64))?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn poisoned(&self) -> bool {
-        self.poisoned
+    #[instrument(err(Debug), skip(self, code), parent = Span::current())]
+    pub fn map_code_region(&mut self, code: &[u8], guest_va: u64) -> Result<()> {
+        let region = HostRegion::from_slice(code)?;
+        let mut rgn = region.memory_region(guest_va)?;
+        rgn.flags |= MemoryRegionFlags::EXECUTE;
+        // Safety: as in `map_host_region`, the memory is never written to
+        // and is kept alive by `host_regions`.
+        unsafe { self.map_region(&rgn) }?;
+        self.host_regions.push(guest_va, region);
+        Ok(())
     }
-}
 
-impl Callable for MultiUseSandbox {
-    fn call<Output: SupportedReturnType>(
+    /// Maps a copy of `value` read-write into the sandbox address space at
+    /// `guest_base`, which must be page-aligned, and returns a
+    /// [`GuestRef`] through which the host reads and writes it, e.g. to
+    /// share a control block with the guest.
+    ///
+    /// `T` must be [`Pod`](bytemuck::Pod), which is usually derived for a
+    /// `#[repr(C)]` struct, so that whatever bytes the guest writes are a
+    /// valid `T`. The value takes up whole pages, the rest of which are
+    /// zero.
+    ///
+    /// The value is shared rather than part of the sandbox's memory, so
+    /// the guest's writes to it are not rolled back: a snapshot taken while
+    /// it is mapped captures its current contents, and
+    /// [`restore()`](Self::restore) unmaps it, after which the
+    /// [`GuestRef`] keeps the last value the guest wrote.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// #[repr(C)]
+    /// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    /// struct Control {
+    ///     requests: u32,
+    ///     responses: u32,
+    /// }
+    ///
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let control = sandbox.map_value(Control { requests: 1, responses: 0 }, 0x1_0000_0000)?;
+    /// sandbox.call::<()>("Serve", control.guest_address())?;
+    /// println!("{} responses", control.get()?.responses);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, value), parent = Span::current())]
+    pub fn map_value<T: bytemuck::Pod>(
         &mut self,
-        func_name: &str,
-        args: impl ParameterTuple,
-    ) -> Result<Output> {
+        value: T,
+        guest_base: u64,
+    ) -> Result<GuestRef<T>> {
         if self.poisoned {
             return Err(crate::HyperlightError::PoisonedSandbox);
         }
-        self.call(func_name, args)
+        let (value, memory) = GuestRef::new(value, guest_base)?;
+        let rgn = GuestRef::<T>::memory_region(&memory, guest_base)?;
+        // Safety: the memory is kept alive by `mapped_values` until it is
+        // unmapped by `restore` or the VM has been dropped, and the host
+        // only accesses it through `HostSharedMemory`, which tolerates
+        // concurrent writes by the guest.
+        unsafe { self.vm.map_region(&rgn) }.map_err(HyperlightVmError::MapRegion)?;
+        self.snapshot = None;
+        self.mapped_values.push(memory);
+        Ok(value)
     }
-}
 
-impl std::fmt::Debug for MultiUseSandbox {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("MultiUseSandbox").finish()
+    /// Maps `region` read-write into the sandbox address space at
+    /// `guest_base`, which must be page-aligned, so that this sandbox
+    /// shares it with every other sandbox it is mapped into, and with the
+    /// host.
+    ///
+    /// This is for trusted pipelines of cooperating sandboxes that need
+    /// shared mutable state, such as a work queue or counters. The region
+    /// is shared rather than part of the sandbox's memory, so the guest's
+    /// writes to it are not rolled back: [`restore()`](Self::restore)
+    /// unmaps it, and the region keeps whatever was last written to it.
+    ///
+    /// # Isolation
+    ///
+    /// This deliberately breaks the isolation between the sandboxes the
+    /// region is mapped into. Each of them can read everything the others
+    /// write to it, and corrupt anything they keep in it, so only map a
+    /// region into sandboxes that trust each other with its contents.
+    ///
+    /// Nothing synchronises the accesses to the region. The sandboxes may
+    /// run on different threads at the same time, so guests, and the host
+    /// through [`SharedMutRegion`], must synchronise their accesses
+    /// themselves, for example with atomic operations or a lock kept in the
+    /// region. The host's accesses do not race with the guests' in a way
+    /// that is unsafe for the host, but unsynchronised accesses can see
+    /// partial writes.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::atomic::Ordering;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::mem::shared_mut_region::SharedMutRegion;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut producer: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("producer.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// let mut consumer: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("consumer.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let queue = SharedMutRegion::new(64 * 1024)?;
+    /// producer.map_shared_mut(&queue, 0x1_0000_0000)?;
+    /// consumer.map_shared_mut(&queue, 0x1_0000_0000)?;
+    ///
+    /// producer.call::<()>("Produce", 0x1_0000_0000u64)?;
+    /// consumer.call::<()>("Consume", 0x1_0000_0000u64)?;
+    /// println!("{} items left", queue.atomic_u64(0)?.load(Ordering::Acquire));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, region), parent = Span::current())]
+    pub fn map_shared_mut(&mut self, region: &SharedMutRegion, guest_base: u64) -> Result<()> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        let rgn = region.memory_region(guest_base)?;
+        // Safety: the memory is kept alive by `shared_mut_regions` until it
+        // is unmapped by `restore` or the VM has been dropped, and the host
+        // only accesses it through `HostSharedMemory` and atomics, which
+        // tolerate concurrent writes by guests.
+        unsafe { self.vm.map_region(&rgn) }.map_err(HyperlightVmError::MapRegion)?;
+        self.snapshot = None;
+        self.shared_mut_regions.push(region.clone());
+        Ok(())
     }
-}
 
-/// Emit a warning for each memory-layout field in `caller` that
-/// disagrees with `snapshot`. Used by [`MultiUseSandbox::from_snapshot`]
-/// to surface ignored caller-supplied layout values, since those
-/// fields are always taken from the snapshot.
-fn warn_on_layout_override(
-    caller: &crate::sandbox::SandboxConfiguration,
-    snapshot: &crate::mem::layout::SandboxMemoryLayout,
-) {
-    let mismatches: &[(&str, u64, u64)] = &[
-        (
-            "input_data_size",
-            caller.get_input_data_size() as u64,
-            snapshot.input_data_size as u64,
-        ),
-        (
-            "output_data_size",
-            caller.get_output_data_size() as u64,
-            snapshot.output_data_size as u64,
-        ),
-        (
-            "heap_size",
-            caller.get_heap_size(),
-            snapshot.heap_size as u64,
-        ),
-        (
-            "scratch_size",
-            caller.get_scratch_size() as u64,
-            snapshot.get_scratch_size() as u64,
-        ),
-    ];
-    for (name, supplied, snap) in mismatches {
-        if supplied != snap {
-            tracing::warn!(
-                "from_snapshot ignoring caller-supplied {} ({}); using snapshot value ({})",
-                name,
-                supplied,
-                snap
-            );
+    /// Changes the protection of the guest physical range `[gpa, gpa + len)`
+    /// to `prot`, so that any guest access the new protection does not allow
+    /// fails with [`crate::HyperlightError::MemoryAccessViolation`].
+    ///
+    /// The range must be page-aligned and lie entirely within a single region
+    /// previously mapped with [`map_region`](Self::map_region) or
+    /// [`map_file_cow`](Self::map_file_cow). Write access cannot be granted to a range that was not
+    /// mapped writable. Protecting a range with [`MemoryRegionFlags::NONE`] makes
+    /// it inaccessible on every hypervisor, whereas removing only execute access
+    /// from an executable mapping fails with
+    /// [`Unsupported`](crate::HyperlightError::Unsupported) on backends without
+    /// [`execute_protection`](crate::hypervisor::HypervisorCapabilities::execute_protection).
+    ///
+    /// Like other mapping changes, the new protection is discarded by
+    /// [`restore()`](Self::restore).
+    ///
+    /// ## Guest Memory
+    ///
+    /// Only mapped regions can be reprotected. The sandbox's own memory,
+    /// which holds the guest's code, heap and stacks and its scratch
+    /// region, is mapped into the VM as a whole, and the guest controls
+    /// the permissions within it through its own page tables, which the
+    /// host does not edit. A range that overlaps it fails with
+    /// [`Unsupported`](crate::HyperlightError::Unsupported) on every
+    /// backend, so a guest that wants part of its own memory read-only or
+    /// non-executable has to change its page tables itself.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn protect_region(&mut self, gpa: u64, len: u64, prot: MemoryRegionFlags) -> Result<()> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+
+        let end = gpa.saturating_add(len);
+        let base_addr = crate::mem::layout::SandboxMemoryLayout::BASE_ADDRESS as u64;
+        let shared_end = base_addr.saturating_add(self.mem_mgr.shared_mem.mem_size() as u64);
+        let scratch_base =
+            hyperlight_common::layout::scratch_base_gpa(self.mem_mgr.scratch_mem.mem_size());
+        if (gpa < shared_end && end > base_addr) || end > scratch_base {
+            return Err(HyperlightError::Unsupported {
+                feature: "protecting the sandbox's own memory".to_string(),
+                backend: self.hypervisor_backend(),
+            });
+        }
+
+        let removes_execute = prot != MemoryRegionFlags::NONE
+            && !prot.contains(MemoryRegionFlags::EXECUTE)
+            && self.vm.get_mapped_regions().any(|region| {
+                region.flags.contains(MemoryRegionFlags::EXECUTE)
+                    && (region.guest_region.start as u64) < end
+                    && gpa < region.guest_region.end as u64
+            });
+        if removes_execute {
+            self.hypervisor_backend()
+                .require("execute protection", |c| c.execute_protection)?;
         }
+
+        self.vm
+            .protect_region(usize::try_from(gpa)?, usize::try_from(len)?, prot)
+            .map_err(|e| {
+                let e = HyperlightError::from(HyperlightVmError::ProtectRegion(e));
+                if e.is_poison_error() {
+                    self.poisoned = true;
+                }
+                e
+            })?;
+        self.snapshot = None;
+        Ok(())
+    }
+
+    /// Map the contents of a file into the guest at a particular address
+    ///
+    /// An optional `label` identifies this mapping in the PEB's
+    /// `FileMappingInfo` array (max 63 bytes, defaults to the file name).
+    ///
+    /// Returns the length of the mapping in bytes.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[instrument(err(Debug), skip(self, file_path, guest_base, label), parent = Span::current())]
+    pub fn map_file_cow(
+        &mut self,
+        file_path: &Path,
+        guest_base: u64,
+        label: Option<&str>,
+    ) -> Result<u64> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+
+        // Pre-check the file mapping limit before doing any expensive
+        // OS or VM work. The PEB count is the source of truth.
+        #[cfg(feature = "nanvix-unstable")]
+        let current_count = self
+            .mem_mgr
+            .shared_mem
+            .read::<u64>(self.mem_mgr.layout.get_file_mappings_size_offset())?
+            as usize;
+        #[cfg(feature = "nanvix-unstable")]
+        if current_count >= hyperlight_common::mem::MAX_FILE_MAPPINGS {
+            return Err(crate::HyperlightError::Error(format!(
+                "map_file_cow: file mapping limit reached ({} of {})",
+                current_count,
+                hyperlight_common::mem::MAX_FILE_MAPPINGS,
+            )));
+        }
+
+        // Phase 1: host-side OS work (open file, create mapping)
+        let mut prepared = prepare_file_cow(file_path, guest_base, label)?;
+
+        // Validate that the full mapped range doesn't overlap the
+        // sandbox's primary shared memory region.
+        let shared_size = self.mem_mgr.shared_mem.mem_size() as u64;
+        let base_addr = crate::mem::layout::SandboxMemoryLayout::BASE_ADDRESS as u64;
+        let shared_end = base_addr.checked_add(shared_size).ok_or_else(|| {
+            crate::HyperlightError::Error("shared memory end overflow".to_string())
+        })?;
+        let mapping_end = guest_base
+            .checked_add(prepared.size as u64)
+            .ok_or_else(|| {
+                crate::HyperlightError::Error(format!(
+                    "map_file_cow: guest address overflow: {:#x} + {:#x}",
+                    guest_base, prepared.size
+                ))
+            })?;
+        if guest_base < shared_end && mapping_end > base_addr {
+            return Err(crate::HyperlightError::Error(format!(
+                "map_file_cow: mapping [{:#x}..{:#x}) overlaps sandbox shared memory [{:#x}..{:#x})",
+                guest_base, mapping_end, base_addr, shared_end,
+            )));
+        }
+
+        // Phase 2: VM-side work (map into guest address space)
+        let region = prepared.to_memory_region()?;
+
+        unsafe { self.vm.map_region(&region) }
+            .map_err(HyperlightVmError::MapRegion)
+            .map_err(crate::HyperlightError::HyperlightVmError)?;
+
+        self.snapshot = None;
+
+        let size = prepared.size as u64;
+
+        // Mark consumed immediately after map_region succeeds.
+        // On Windows, WhpVm::map_memory copies the file mapping handle
+        // into its own `file_mappings` vec for cleanup on drop. If we
+        // deferred mark_consumed(), both PreparedFileMapping::drop and
+        // WhpVm::drop would release the same handle — a double-close.
+        // On Linux the hypervisor holds a reference to the host mmap;
+        // freeing it here would leave a dangling backing.
+        prepared.mark_consumed();
+
+        // Record the mapping metadata in the PEB. If this fails the VM
+        // still holds a valid mapping but the PEB won't list it — the
+        // limit was already pre-checked above so this should not fail
+        // in practice.
+        #[cfg(feature = "nanvix-unstable")]
+        self.mem_mgr
+            .write_file_mapping_entry(prepared.guest_base, size, &prepared.label)?;
+
+        Ok(size)
+    }
+
+    /// Calls a guest function with type-erased parameters and return values.
+    ///
+    /// This function is used for fuzz testing parameter and return type handling.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[cfg(feature = "fuzzing")]
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn call_type_erased_guest_function_by_name(
+        &mut self,
+        func_name: &str,
+        ret_type: ReturnType,
+        args: Vec<ParameterValue>,
+    ) -> Result<ReturnValue> {
+        self.call_dynamic(func_name, ret_type, args)
+    }
+
+    /// Calls a guest function with arguments encoded by `codec`, returning
+    /// its result encoded by `codec`.
+    ///
+    /// This lets tools and bridges that exchange arguments and results in
+    /// their own encoding call guest functions without converting them to
+    /// Rust types. The call itself is made as by [`call`](Self::call).
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::func::{Codec, FlatbufferCodec, ParameterValue, ReturnType};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let args = FlatbufferCodec.encode_parameters(&[ParameterValue::String("hi".into())])?;
+    /// let result = sandbox.call_encoded(&FlatbufferCodec, "Echo", ReturnType::String, &args)?;
+    /// println!("{:?}", FlatbufferCodec.decode_return_value(&result)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, codec, args), parent = Span::current())]
+    pub fn call_encoded(
+        &mut self,
+        codec: &impl Codec,
+        func_name: &str,
+        ret_type: ReturnType,
+        args: &[u8],
+    ) -> Result<Vec<u8>> {
+        let args = codec.decode_parameters(args)?;
+        let ret = self.call_dynamic(func_name, ret_type, args)?;
+        Ok(codec.encode_return_value(&ret)?)
+    }
+
+    /// Calls a guest function whose signature is only known at runtime.
+    ///
+    /// This has the same semantics as [`call`](Self::call), but takes the
+    /// arguments and expected return type as values rather than types.
+    pub(crate) fn call_dynamic(
+        &mut self,
+        func_name: &str,
+        ret_type: ReturnType,
+        args: Vec<ParameterValue>,
+    ) -> Result<ReturnValue> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+        maybe_time_and_emit_guest_call(func_name, || {
+            self.call_guest_function_by_name_no_reset(func_name, ret_type, args)
+        })
+    }
+
+    fn call_guest_function_by_name_no_reset(
+        &mut self,
+        function_name: &str,
+        return_type: ReturnType,
+        args: Vec<ParameterValue>,
+    ) -> Result<ReturnValue> {
+        self.call_guest_function_by_name_inner(
+            function_name,
+            return_type,
+            args,
+            None,
+            read_return_value,
+        )
+    }
+
+    /// If `partial_result` is provided and the call is cancelled by the host,
+    /// it is set to the last partial result published by the guest.
+    ///
+    /// `read_result` reads the guest's result once the call has completed.
+    pub(super) fn call_guest_function_by_name_inner<R>(
+        &mut self,
+        function_name: &str,
+        return_type: ReturnType,
+        args: Vec<ParameterValue>,
+        partial_result: Option<&mut Option<ReturnValue>>,
+        read_result: impl FnOnce(
+            &mut SandboxMemoryManager<HostSharedMemory>,
+        ) -> Result<std::result::Result<R, GuestError>>,
+    ) -> Result<R> {
+        let estimated_capacity = estimate_flatbuffer_capacity(function_name, &args);
+        let fc = FunctionCall::new(
+            function_name.to_string(),
+            Some(args),
+            FunctionCallType::Guest,
+            return_type,
+        )
+        .with_schema_generation(self.schema_generation);
+        self.call_guest_function_encoded_inner(
+            function_name,
+            estimated_capacity,
+            |builder| {
+                fc.encode(builder);
+            },
+            partial_result,
+            read_result,
+        )
+    }
+
+    /// Makes a guest call as
+    /// [`call_guest_function_by_name_inner`](Self::call_guest_function_by_name_inner)
+    /// does, with the `FunctionCall` that `encode` encodes into a builder of
+    /// `estimated_capacity` bytes.
+    pub(super) fn call_guest_function_encoded_inner<R>(
+        &mut self,
+        function_name: &str,
+        estimated_capacity: usize,
+        encode: impl FnOnce(&mut FlatBufferBuilder),
+        partial_result: Option<&mut Option<ReturnValue>>,
+        read_result: impl FnOnce(
+            &mut SandboxMemoryManager<HostSharedMemory>,
+        ) -> Result<std::result::Result<R, GuestError>>,
+    ) -> Result<R> {
+        self.call_guest_function_with_builder_inner(
+            function_name,
+            &mut FlatBufferBuilder::with_capacity(estimated_capacity),
+            encode,
+            partial_result,
+            read_result,
+        )
+    }
+
+    /// Makes a guest call as
+    /// [`call_guest_function_encoded_inner`](Self::call_guest_function_encoded_inner)
+    /// does, encoding the `FunctionCall` into `builder`, which is reset
+    /// first so that its allocation can be reused across calls.
+    pub(super) fn call_guest_function_with_builder_inner<R>(
+        &mut self,
+        function_name: &str,
+        builder: &mut FlatBufferBuilder<'_>,
+        encode: impl FnOnce(&mut FlatBufferBuilder),
+        partial_result: Option<&mut Option<ReturnValue>>,
+        read_result: impl FnOnce(
+            &mut SandboxMemoryManager<HostSharedMemory>,
+        ) -> Result<std::result::Result<R, GuestError>>,
+    ) -> Result<R> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // ===== KILL() TIMING POINT 1 =====
+        // Clear any stale cancellation from a previous guest function call or if kill() was called too early.
+        // Any kill() that completed (even partially) BEFORE this line has NO effect on this call.
+        self.vm.clear_cancel();
+        // A registry is shut down before the calls in progress are
+        // cancelled, so a call that starts after the cancellation sees it
+        if self
+            .registration
+            .as_ref()
+            .is_some_and(Registration::is_shut_down)
+        {
+            return Err(crate::HyperlightError::SandboxRegistryShutDown);
+        }
+        // A call that waits for the rate limit can still be cancelled by
+        // kill(), since the cancellation has already been cleared. The
+        // sandbox's housekeeping calls to built-in guest functions are made
+        // without the rate limit, see `without_call_rate_limit`
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire()?;
+        }
+
+        let start = Instant::now();
+        let mut dispatched = false;
+        let res = (|| {
+            builder.reset();
+            encode(builder);
+            self.mem_mgr
+                .write_guest_function_call(builder.finished_data())?;
+
+            dispatched = true;
+            let dispatch_res = self.vm.dispatch_call_from_host(
+                &mut self.mem_mgr,
+                &self.host_funcs,
+                #[cfg(gdb)]
+                self.dbg_mem_access_fn.clone(),
+            );
+
+            // Convert dispatch errors to HyperlightErrors to maintain backwards compatibility
+            // but first determine if sandbox should be poisoned
+            if let Err(e) = dispatch_res {
+                let (error, should_poison) = e.promote();
+                self.poisoned |= should_poison;
+                return Err(error);
+            }
+
+            let guest_result = read_result(&mut self.mem_mgr)?;
+            // Discard any partial results the guest published before its final result.
+            self.mem_mgr.clear_output_buffer();
+
+            match guest_result {
+                Ok(val) => Ok(val),
+                Err(guest_error) => {
+                    metrics::counter!(
+                        METRIC_GUEST_ERROR,
+                        METRIC_GUEST_ERROR_LABEL_CODE => (guest_error.code as u64).to_string()
+                    )
+                    .increment(1);
+
+                    Err(HyperlightError::GuestError(
+                        guest_error.code,
+                        guest_error.message,
+                    ))
+                }
+            }
+        })();
+
+        let duration = start.elapsed();
+        // Only the functions the guest resolved are recorded, so that calls
+        // to names the guest does not have cannot grow the histograms
+        // without bound
+        let resolved = dispatched
+            && !matches!(
+                &res,
+                Err(HyperlightError::GuestError(
+                    ErrorCode::GuestFunctionNotFound,
+                    _
+                ))
+            );
+        if resolved {
+            self.record_latency(function_name, duration);
+        }
+        #[cfg(feature = "otel")]
+        crate::otel::record_guest_call(function_name, duration, &res);
+        maybe_emit_guest_call_host_calls(function_name, self.vm.host_calls());
+
+        // Clear partial abort bytes so they don't leak across calls.
+        self.mem_mgr.abort_buffer.clear();
+
+        // In the happy path we do not need to clear io-buffers from the host because:
+        // - the serialized guest function call is zeroed out by the guest during deserialization, see call to `try_pop_shared_input_data_into::<FunctionCall>()`
+        // - the serialized guest function result is zeroed out by us (the host) during deserialization, see `get_guest_function_call_result`
+        // - any serialized host function call are zeroed out by us (the host) during deserialization, see `get_host_function_call`
+        // - any serialized host function result is zeroed out by the guest during deserialization, see `get_host_return_value`
+        if let Err(e) = &res {
+            // Salvage the guest's last partial result before the buffers are cleared.
+            if let (Some(partial_result), HyperlightError::ExecutionCanceledByHost()) =
+                (partial_result, e)
+            {
+                *partial_result = self.mem_mgr.take_partial_result();
+            }
+            self.mem_mgr.clear_io_buffers();
+
+            // Determine if we should poison the sandbox.
+            self.poisoned |= e.is_poison_error();
+        }
+
+        // Note: clear_call_active() is automatically called when _guard is dropped here
+
+        res
+    }
+
+    fn record_latency(&mut self, function_name: &str, duration: Duration) {
+        match self.latency.get_mut(function_name) {
+            Some(histogram) => histogram.record(duration),
+            None => {
+                let mut histogram = Histogram::default();
+                histogram.record(duration);
+                self.latency.insert(function_name.to_string(), histogram);
+            }
+        }
+    }
+
+    /// Returns the distribution of durations of the calls to the guest
+    /// function `func_name` made on this sandbox.
+    ///
+    /// Every call the guest resolved is recorded, including calls that
+    /// fail or are cancelled. Calls to functions the guest does not have,
+    /// and calls that fail before reaching the guest, are not, so the
+    /// number of histograms is bounded by the functions the guest
+    /// registered. The histogram is empty if the function has not been
+    /// called since the sandbox was created or [`reset_latency_histograms`](Self::reset_latency_histograms)
+    /// was last called. Histograms are not affected by [`restore`](Self::restore).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// for _ in 0..100 {
+    ///     sandbox.call::<i32>("PrintOutput", "Hello".to_string())?;
+    /// }
+    ///
+    /// let histogram = sandbox.latency_histogram("PrintOutput");
+    /// println!("p50: {:?}", histogram.percentile(50.0));
+    /// println!("p99: {:?}", histogram.percentile(99.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn latency_histogram(&self, func_name: &str) -> Histogram {
+        self.latency.get(func_name).cloned().unwrap_or_default()
+    }
+
+    /// Discards the call durations recorded for every guest function, see
+    /// [`latency_histogram`](Self::latency_histogram).
+    pub fn reset_latency_histograms(&mut self) {
+        self.latency.clear();
+    }
+
+    /// How long the guest took to initialise when the sandbox was created,
+    /// that is, to run its entrypoint up to and including `hyperlight_main`.
+    ///
+    /// A sandbox created with [`from_snapshot`](Self::from_snapshot) from a
+    /// snapshot of an initialised guest skips the initialisation, so this
+    /// is close to zero, which shows what starting from such a snapshot
+    /// saves over [`UninitializedSandbox::evolve`](crate::UninitializedSandbox::evolve).
+    /// Neither the creation of the VM nor the loading of the guest binary
+    /// is included.
+    pub fn init_duration(&self) -> Duration {
+        self.init_duration
+    }
+
+    /// Returns a handle for interrupting guest execution.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use std::thread;
+    /// # use std::time::Duration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// // Get interrupt handle before starting long-running operation
+    /// let interrupt_handle = sandbox.interrupt_handle();
+    ///
+    /// // Spawn thread to interrupt after timeout
+    /// let handle_clone = interrupt_handle.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(5));
+    ///     handle_clone.kill();
+    /// });
+    ///
+    /// // This call may be interrupted by the spawned thread
+    /// let result = sandbox.call_guest_function_by_name::<i32>("LongRunningFunction", ());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn interrupt_handle(&self) -> Arc<dyn InterruptHandle> {
+        self.vm.interrupt_handle()
+    }
+
+    /// Raises interrupt `vector` in the guest, which handles it through
+    /// its IDT and then carries on, unlike
+    /// [`InterruptHandle::kill`](crate::hypervisor::InterruptHandle::kill)
+    /// which cancels the guest call. This can be used to signal guests that
+    /// implement cooperative interrupt handlers, for example for preemption.
+    ///
+    /// Vectors `0x30..=0xFE` can be raised. Vectors below `0x20` are CPU
+    /// exceptions, `0x20..=0x2F` are where the guest remaps the legacy PIC
+    /// (the timer interrupt uses `0x20`) and `0xFF` is the spurious vector,
+    /// so any other vector fails with
+    /// [`InvalidInterruptVector`](crate::HyperlightError::InvalidInterruptVector).
+    ///
+    /// The sandbox is not running while this is called, so the interrupt
+    /// stays pending until the next guest call runs with interrupts
+    /// enabled (`sti`). Interrupts are not queued: raising the same vector
+    /// twice before it is delivered delivers it once. The guest
+    /// acknowledges an interrupt the same way as the timer interrupt, with
+    /// a non-specific EOI to the master PIC (`out 0x20, 0x20`). On KVM that
+    /// EOI never reaches the local APIC, so of several vectors raised
+    /// before a call that share their upper four bits, only the highest is
+    /// delivered during that call, and the others stay pending until
+    /// `raise_guest_interrupt` is next called.
+    ///
+    /// This needs the `hw-interrupts` feature, and fails with
+    /// [`Unsupported`](crate::HyperlightError::Unsupported) without it, see
+    /// [`HypervisorCapabilities::hardware_interrupts`](crate::hypervisor::HypervisorCapabilities::hardware_interrupts).
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn raise_guest_interrupt(&mut self, vector: u8) -> Result<()> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        self.hypervisor_backend()
+            .require("raising guest interrupts", |c| c.hardware_interrupts)?;
+
+        #[cfg(feature = "hw-interrupts")]
+        {
+            use crate::hypervisor::virtual_machine::x86_64::hw_interrupts::RAISABLE_VECTORS;
+
+            if !RAISABLE_VECTORS.contains(&vector) {
+                return Err(HyperlightError::InvalidInterruptVector(vector));
+            }
+            self.vm
+                .raise_interrupt(vector)
+                .map_err(HyperlightVmError::RaiseInterrupt)?;
+        }
+        #[cfg(not(feature = "hw-interrupts"))]
+        let _ = vector;
+        Ok(())
+    }
+
+    /// Returns the hypervisor backend this sandbox runs on.
+    ///
+    /// Use [`HypervisorBackend::capabilities`] to check which optional
+    /// features the backend supports before relying on them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let backend = sandbox.hypervisor_backend();
+    /// println!("running on {backend}");
+    /// if backend.capabilities().guest_debugging {
+    ///     println!("gdb is available");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hypervisor_backend(&self) -> HypervisorBackend {
+        self.vm.backend()
+    }
+
+    /// Returns the optional features supported by the hypervisor backend this
+    /// sandbox runs on, see [`HypervisorCapabilities`].
+    pub fn capabilities(&self) -> HypervisorCapabilities {
+        self.hypervisor_backend().capabilities()
+    }
+
+    /// Returns where the guest binary is loaded in guest memory: its load
+    /// base, size and entry point, and the address it was linked at.
+    ///
+    /// This can be used to translate guest addresses, such as those in a
+    /// backtrace or crash report, to addresses in the guest binary so that
+    /// they can be symbolized.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let image = sandbox.guest_image_info();
+    /// println!(
+    ///     "guest loaded at {:#x} ({} bytes), entry point {:#x}",
+    ///     image.load_base, image.size, image.entry_point
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn guest_image_info(&self) -> GuestImageInfo {
+        self.mem_mgr.layout.guest_image_info()
+    }
+
+    /// Runs guest code at `entry` with the registers `regs`, bypassing the
+    /// guest's function dispatch, until it executes `hlt`, and returns the
+    /// registers it halted with. This is meant for experimental guests and
+    /// microbenchmarks that do not use the function call ABI.
+    ///
+    /// `regs.rip` is ignored in favour of `entry`, which must be within the
+    /// guest image, see [`guest_image_info`](Self::guest_image_info). If
+    /// `regs.rsp` is 0, the code runs on the guest's stack. The code may
+    /// call host functions as usual, and can be cancelled through the
+    /// sandbox's [`InterruptHandle`].
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method returns [`HyperlightError::PoisonedSandbox`] if the
+    /// sandbox is poisoned, and poisons it if the code does not halt
+    /// cleanly, for example because it faults or is cancelled.
+    ///
+    /// # Safety
+    ///
+    /// The code runs with the guest's full privileges and none of the
+    /// guarantees of a guest function call: it can leave the guest's
+    /// memory and state inconsistent in ways the sandbox cannot detect,
+    /// so that later guest function calls misbehave. The caller must
+    /// ensure that `entry` is the start of code that expects `regs` and
+    /// ends with `hlt`, and should [`restore`](Self::restore) a snapshot
+    /// afterwards if the code may have changed guest state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::GuestRegisters;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// let snapshot = sandbox.snapshot()?;
+    ///
+    /// let entry = sandbox.guest_image_info().load_base + 0x1000;
+    /// let regs = GuestRegisters { rdi: 2, rsi: 3, ..Default::default() };
+    /// // SAFETY: the guest has code at `entry` that adds rdi and rsi into
+    /// // rax and halts
+    /// let regs = unsafe { sandbox.run_at(entry, regs)? };
+    /// println!("rax = {}", regs.rax);
+    /// sandbox.restore(snapshot)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(target_arch = "x86_64")]
+    #[instrument(err(Debug), skip(self, regs), parent = Span::current())]
+    pub unsafe fn run_at(&mut self, entry: u64, regs: GuestRegisters) -> Result<GuestRegisters> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        let image = self.guest_image_info();
+        if !image.contains(entry) {
+            return Err(new_error!(
+                "Entry point {:#x} is outside the guest image at {:#x}..{:#x}",
+                entry,
+                image.load_base,
+                image.load_base + image.size
+            ));
+        }
+        self.vm.clear_cancel();
+        // Whatever the code does, the sandbox no longer matches its last
+        // snapshot
+        self.snapshot = None;
+
+        let regs = CommonRegisters {
+            rip: entry,
+            ..regs.into()
+        };
+        let res = self.vm.run_at(
+            &regs,
+            &mut self.mem_mgr,
+            &self.host_funcs,
+            #[cfg(gdb)]
+            self.dbg_mem_access_fn.clone(),
+        );
+        self.mem_mgr.abort_buffer.clear();
+        match res {
+            Ok(regs) => Ok(regs.into()),
+            Err(e) => {
+                let (error, should_poison) = e.promote();
+                self.poisoned |= should_poison;
+                self.mem_mgr.clear_io_buffers();
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns the host's handle to the shared ring, or `None` if the sandbox
+    /// was created without one (see
+    /// [`SandboxConfiguration::set_shared_ring_size`](crate::sandbox::SandboxConfiguration::set_shared_ring_size)).
+    ///
+    /// The handle pushes messages the guest can pop and pops messages the
+    /// guest pushed. It can be moved to another thread to stream data while a
+    /// guest function is running, but only one host handle should be used at
+    /// a time. Restoring a snapshot empties the ring, and the handle must not
+    /// be used after restoring a snapshot with a different memory layout.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::SandboxConfiguration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut cfg = SandboxConfiguration::default();
+    /// cfg.set_shared_ring_size(0x4000);
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     Some(cfg)
+    /// )?.evolve()?;
+    ///
+    /// let ring = sandbox.shared_ring()?.expect("sandbox has a shared ring");
+    /// ring.push(b"hello")?;
+    /// sandbox.call::<()>("ConsumeRing", ())?;
+    /// while let Some(msg) = ring.pop()? {
+    ///     println!("guest sent {} bytes", msg.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shared_ring(&self) -> Result<Option<HostSharedRing>> {
+        self.mem_mgr.shared_ring()
+    }
+
+    /// Generate a crash dump of the current state of the VM underlying this sandbox.
+    ///
+    /// Creates an ELF core dump file that can be used for debugging. The dump
+    /// captures the current state of the sandbox including registers, memory regions,
+    /// and other execution context.
+    ///
+    /// The location of the core dump file is determined by the `HYPERLIGHT_CORE_DUMP_DIR`
+    /// environment variable. If not set, it defaults to the system's temporary directory.
+    ///
+    /// This is only available when the `crashdump` feature is enabled and then only if the sandbox
+    /// is also configured to allow core dumps (which is the default behavior).
+    ///
+    /// This can be useful for generating a crash dump from gdb when trying to debug issues in the
+    /// guest that dont cause crashes (e.g. a guest function that does not return)
+    ///
+    /// # Examples
+    ///
+    /// Attach to your running process with gdb and call this function:
+    ///
+    /// ```shell
+    /// sudo gdb -p <pid_of_your_process>
+    /// (gdb) info threads
+    /// # find the thread that is running the guest function you want to debug
+    /// (gdb) thread <thread_number>
+    /// # switch to the frame where you have access to your MultiUseSandbox instance
+    /// (gdb) backtrace
+    /// (gdb) frame <frame_number>
+    /// # get the pointer to your MultiUseSandbox instance
+    /// # Get the sandbox pointer
+    /// (gdb) print sandbox
+    /// # Call the crashdump function
+    /// call sandbox.generate_crashdump()
+    /// ```
+    /// The crashdump should be available in crash dump directory (see `HYPERLIGHT_CORE_DUMP_DIR` env var).
+    ///
+    #[cfg(crashdump)]
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn generate_crashdump(&mut self) -> Result<()> {
+        crate::hypervisor::crashdump::generate_crashdump(&self.vm, &mut self.mem_mgr, None)
+    }
+
+    /// Generate a crash dump of the current state of the VM, writing to `dir`.
+    ///
+    /// Like [`generate_crashdump`](Self::generate_crashdump), but the core dump
+    /// file is placed in `dir` instead of consulting the `HYPERLIGHT_CORE_DUMP_DIR`
+    /// environment variable.  This avoids the need for callers to use
+    /// `unsafe { std::env::set_var(...) }`.
+    #[cfg(crashdump)]
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn generate_crashdump_to_dir(&mut self, dir: impl Into<String>) -> Result<()> {
+        crate::hypervisor::crashdump::generate_crashdump(
+            &self.vm,
+            &mut self.mem_mgr,
+            Some(dir.into()),
+        )
+    }
+
+    /// Returns whether the sandbox is currently poisoned.
+    ///
+    /// A poisoned sandbox is in an inconsistent state due to the guest not running to completion.
+    /// All operations will be rejected until the sandbox is restored from a non-poisoned snapshot.
+    ///
+    /// ## Causes of Poisoning
+    ///
+    /// The sandbox becomes poisoned when guest execution is interrupted:
+    /// - **Panics/Aborts** - Guest code panics or calls `abort()`
+    /// - **Invalid Memory Access** - Read/write/execute violations  
+    /// - **Stack Overflow** - Guest exhausts stack space
+    /// - **Heap Exhaustion** - Guest runs out of heap memory
+    /// - **Forced Termination** - [`InterruptHandle::kill()`] called during execution
+    ///
+    /// ## Recovery
+    ///
+    /// To clear the poison state, use [`restore()`](Self::restore) with a snapshot
+    /// that was taken before the sandbox became poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// // Check if sandbox is poisoned
+    /// if sandbox.poisoned() {
+    ///     println!("Sandbox is poisoned and needs attention");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn poisoned(&self) -> bool {
+        self.poisoned
+    }
+}
+
+impl Callable for MultiUseSandbox {
+    fn call<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<Output> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        self.call(func_name, args)
+    }
+}
+
+impl std::fmt::Debug for MultiUseSandbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiUseSandbox").finish()
+    }
+}
+
+/// Reads the result of a completed guest call as a [`ReturnValue`].
+pub(super) fn read_return_value(
+    mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+) -> Result<std::result::Result<ReturnValue, GuestError>> {
+    Ok(mem_mgr.get_guest_function_call_result()?.into_inner())
+}
+
+/// Emit a warning for each memory-layout field in `caller` that
+/// disagrees with `snapshot`. Used by [`MultiUseSandbox::from_snapshot`]
+/// to surface ignored caller-supplied layout values, since those
+/// fields are always taken from the snapshot.
+
+fn warn_on_layout_override(
+    caller: &crate::sandbox::SandboxConfiguration,
+    snapshot: &crate::mem::layout::SandboxMemoryLayout,
+) {
+    let mismatches: &[(&str, u64, u64)] = &[
+        (
+            "input_data_size",
+            caller.get_input_data_size() as u64,
+            snapshot.input_data_size as u64,
+        ),
+        (
+            "output_data_size",
+            caller.get_output_data_size() as u64,
+            snapshot.output_data_size as u64,
+        ),
+        (
+            "heap_size",
+            caller.get_heap_size(),
+            snapshot.heap_size as u64,
+        ),
+        (
+            "stack_size",
+            caller.get_stack_size(),
+            snapshot.stack_size as u64,
+        ),
+        (
+            "scratch_size",
+            caller.get_scratch_size() as u64,
+            snapshot.get_scratch_size() as u64,
+        ),
+        (
+            "shared_ring_size",
+            caller.get_shared_ring_size() as u64,
+            snapshot.shared_ring_size as u64,
+        ),
+        (
+            "max_alloc_per_call",
+            caller.get_max_alloc_per_call(),
+            snapshot.max_alloc_per_call,
+        ),
+        (
+            "alloc_mode",
+            caller.get_guest_allocator() as u64,
+            snapshot.alloc_mode as u64,
+        ),
+        (
+            "huge_pages",
+            caller.get_huge_pages() as u64,
+            snapshot.huge_pages as u64,
+        ),
+        (
+            "detect_self_modifying_code",
+            caller.get_self_modifying_code_detection() as u64,
+            snapshot.detect_self_modifying_code as u64,
+        ),
+        (
+            "fp_exception_mask",
+            caller.get_fp_exception_mask().bits() as u64,
+            snapshot.fp_exception_mask.bits() as u64,
+        ),
+    ];
+    for (name, supplied, snap) in mismatches {
+        if supplied != snap {
+            tracing::warn!(
+                "from_snapshot ignoring caller-supplied {} ({}); using snapshot value ({})",
+                name,
+                supplied,
+                snap
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::thread;
+
+    use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+    use hyperlight_testing::sandbox_sizes::{LARGE_HEAP_SIZE, MEDIUM_HEAP_SIZE, SMALL_HEAP_SIZE};
+    use hyperlight_testing::{c_simple_guest_as_string, simple_guest_as_string};
+
+    use crate::mem::host_region::HostRegion;
+    use crate::mem::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
+    use crate::mem::shared_mem::{ExclusiveSharedMemory, GuestSharedMemory, SharedMemory as _};
+    use crate::mem::shared_mut_region::SharedMutRegion;
+    use crate::sandbox::{SandboxConfiguration, new_simple_guest_sandbox};
+    use crate::{GuestBinary, HyperlightError, MultiUseSandbox, Result, UninitializedSandbox};
+
+    #[test]
+    fn poison() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+        let snapshot = sbox.snapshot().unwrap();
+
+        // poison on purpose
+        let res = sbox
+            .call::<()>("guest_panic", "hello".to_string())
+            .unwrap_err();
+        assert!(
+            matches!(res, HyperlightError::GuestAborted(code, context) if code == ErrorCode::UnknownError as u8 && context.contains("hello"))
+        );
+        assert!(sbox.poisoned());
+
+        // guest calls should fail when poisoned
+        let res = sbox
+            .call::<()>("guest_panic", "hello2".to_string())
+            .unwrap_err();
+        assert!(matches!(res, HyperlightError::PoisonedSandbox));
+
+        // snapshot should fail when poisoned
+        if let Err(e) = sbox.snapshot() {
+            assert!(sbox.poisoned());
+            assert!(matches!(e, HyperlightError::PoisonedSandbox));
+        } else {
+            panic!("Snapshot should fail");
+        }
+
+        // map_region should fail when poisoned
+        {
+            let map_mem = allocate_guest_memory();
+            let guest_base = 0x0;
+            let region = region_for_memory(&map_mem, guest_base, MemoryRegionFlags::READ);
+            let res = unsafe { sbox.map_region(&region) }.unwrap_err();
+            assert!(matches!(res, HyperlightError::PoisonedSandbox));
+        }
+
+        // map_file_cow should fail when poisoned
+        {
+            let temp_file = std::env::temp_dir().join("test_poison_map_file.bin");
+            let res = sbox.map_file_cow(&temp_file, 0x0, None).unwrap_err();
+            assert!(matches!(res, HyperlightError::PoisonedSandbox));
+            std::fs::remove_file(&temp_file).ok(); // Clean up
+        }
+
+        // call_guest_function_by_name (deprecated) should fail when poisoned
+        #[allow(deprecated)]
+        let res = sbox
+            .call_guest_function_by_name::<String>("Echo", "test".to_string())
+            .unwrap_err();
+        assert!(matches!(res, HyperlightError::PoisonedSandbox));
+
+        // restore to non-poisoned snapshot should work and clear poison
+        sbox.restore(snapshot.clone()).unwrap();
+        assert!(!sbox.poisoned());
+
+        // guest calls should work again after restore
+        let res = sbox.call::<String>("Echo", "hello2".to_string()).unwrap();
+        assert_eq!(res, "hello2".to_string());
+        assert!(!sbox.poisoned());
+
+        // re-poison on purpose
+        let res = sbox
+            .call::<()>("guest_panic", "hello".to_string())
+            .unwrap_err();
+        assert!(
+            matches!(res, HyperlightError::GuestAborted(code, context) if code == ErrorCode::UnknownError as u8 && context.contains("hello"))
+        );
+        assert!(sbox.poisoned());
+
+        // restore to non-poisoned snapshot should work again
+        sbox.restore(snapshot.clone()).unwrap();
+        assert!(!sbox.poisoned());
+
+        // guest calls should work again
+        let res = sbox.call::<String>("Echo", "hello3".to_string()).unwrap();
+        assert_eq!(res, "hello3".to_string());
+        assert!(!sbox.poisoned());
+
+        // snapshot should work again
+        let _ = sbox.snapshot().unwrap();
+    }
+
+    /// Make sure input/output buffers are properly reset after guest call (with host call)
+    #[test]
+    fn host_func_error() {
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        sandbox
+            .register("HostError", || -> Result<()> {
+                Err(HyperlightError::Error("hi".to_string()))
+            })
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        // will exhaust io if leaky
+        for _ in 0..1000 {
+            let result = sandbox
+                .call::<i64>(
+                    "CallGivenParamlessHostFuncThatReturnsI64",
+                    "HostError".to_string(),
+                )
+                .unwrap_err();
+
+            assert!(
+                matches!(result, HyperlightError::GuestError(code, msg) if code == ErrorCode::HostFunctionError && msg == "hi"),
+            );
+        }
+    }
+
+    #[test]
+    fn call_host_func_expect_error() {
+        let path = simple_guest_as_string().unwrap();
+        let sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+        sandbox
+            .call::<()>("CallHostExpectError", "SomeUnknownHostFunc".to_string())
+            .unwrap();
+    }
+
+    /// Make sure input/output buffers are properly reset after guest call (with host call)
+    #[test]
+    fn io_buffer_reset() {
+        let mut cfg = SandboxConfiguration::default();
+        cfg.set_input_data_size(4096);
+        cfg.set_output_data_size(4096);
+        let path = simple_guest_as_string().unwrap();
+        let mut sandbox =
+            UninitializedSandbox::new(GuestBinary::FilePath(path), Some(cfg)).unwrap();
+        sandbox.register("HostAdd", |a: i32, b: i32| a + b).unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        // will exhaust io if leaky. Tests both success and error paths
+        for _ in 0..1000 {
+            let result = sandbox.call::<i32>("Add", (5i32, 10i32)).unwrap();
+            assert_eq!(result, 15);
+            let result = sandbox.call::<i32>("AddToStaticAndFail", ()).unwrap_err();
+            assert!(
+                matches!(result, HyperlightError::GuestError (code, msg ) if code == ErrorCode::GuestError && msg == "Crash on purpose")
+            );
+        }
+    }
+
+    #[test]
+    fn call_batch() {
+        use crate::sandbox::{BatchCall, BatchMode};
+
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        let failing_batch = || {
+            [
+                BatchCall::new::<i32>("AddToStatic", 5i32),
+                BatchCall::new::<i32>("AddToStatic", 5i32),
+                BatchCall::new::<i32>("AddToStaticAndFail", ()),
+                BatchCall::new::<i32>("AddToStatic", 5i32),
+            ]
+        };
+
+        // Calls before the failure keep their effects
+        let batch = sbox
+            .call_batch(failing_batch(), BatchMode::StopOnError)
+            .unwrap();
+        assert_eq!(batch.completed(), 2);
+        assert!(!batch.is_complete());
+        assert!(!batch.rolled_back);
+        assert!(matches!(batch.error, Some(HyperlightError::GuestError(..))));
+        let res: i32 = sbox.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 20);
+
+        // An atomic batch is rolled back entirely
+        let batch = sbox.call_batch(failing_batch(), BatchMode::Atomic).unwrap();
+        assert_eq!(batch.completed(), 2);
+        assert!(batch.rolled_back);
+        let res: i32 = sbox.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 20);
+
+        // A cancelled call rolls back an atomic batch and recovers the sandbox
+        let interrupt_handle = sbox.interrupt_handle();
+        let killer = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(100));
+            interrupt_handle.kill();
+        });
+        let batch = sbox
+            .call_batch(
+                [
+                    BatchCall::new::<i32>("AddToStatic", 1i32),
+                    BatchCall::new::<()>("Spin", ()),
+                ],
+                BatchMode::Atomic,
+            )
+            .unwrap();
+        killer.join().unwrap();
+        assert_eq!(batch.completed(), 1);
+        assert!(batch.rolled_back);
+        assert!(matches!(
+            batch.error,
+            Some(HyperlightError::ExecutionCanceledByHost())
+        ));
+        assert!(!sbox.poisoned());
+        let res: i32 = sbox.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 20);
+
+        // A batch that completes is not rolled back
+        let batch = sbox
+            .call_batch(
+                [
+                    BatchCall::new::<i32>("AddToStatic", 1i32),
+                    BatchCall::new::<i32>("GetStatic", ()),
+                ],
+                BatchMode::Atomic,
+            )
+            .unwrap();
+        assert!(batch.is_complete());
+        assert!(!batch.rolled_back);
+        assert_eq!(
+            batch.results.last(),
+            Some(&hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue::Int(21))
+        );
+    }
+
+    #[test]
+    fn on_guest_line() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let captured = lines.clone();
+        let guest_lines = sbox
+            .on_guest_line(move |line| captured.lock().unwrap().push(line.to_string()))
+            .unwrap();
+
+        // A partial line is kept until a later call completes it
+        let res: i32 = sbox.call("PrintOutput", "first\nsec".to_string()).unwrap();
+        assert_eq!(res, 9);
+        assert_eq!(*lines.lock().unwrap(), ["first"]);
+        sbox.call::<i32>("PrintOutput", "ond\nthird".to_string())
+            .unwrap();
+        assert_eq!(*lines.lock().unwrap(), ["first", "second"]);
+
+        guest_lines.finish().unwrap();
+        assert_eq!(*lines.lock().unwrap(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn guest_metrics() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        // The host functions are only registered once enabled
+        assert!(sbox.call::<()>("EmitMetrics", (1u64, 0.5f64)).is_err());
+
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+        let metrics = sbox.guest_metrics("simpleguest_").unwrap();
+        sbox.call::<()>("EmitMetrics", (2u64, 0.5f64)).unwrap();
+        sbox.call::<()>("EmitMetrics", (3u64, 0.25f64)).unwrap();
+
+        assert_eq!(
+            metrics.counters().unwrap().into_iter().collect::<Vec<_>>(),
+            [("simpleguest_cache_hits".to_string(), 5)]
+        );
+        assert_eq!(
+            metrics.gauges().unwrap().into_iter().collect::<Vec<_>>(),
+            [("simpleguest_hit_ratio".to_string(), 0.25)]
+        );
+    }
+
+    #[test]
+    fn call_into_reuses_buffer() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        let mut buf = Vec::with_capacity(1024);
+        let ptr = buf.as_ptr();
+        for len in [100, 10, 0, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            sbox.call_into::<Vec<u8>>("GetSizePrefixedBuffer", data.clone(), &mut buf)
+                .unwrap();
+            assert_eq!(buf, data);
+            // The buffer was large enough for the value, so it was not
+            // reallocated
+            assert_eq!(buf.as_ptr(), ptr);
+        }
+
+        let mut output = String::with_capacity(64);
+        let ptr = output.as_ptr();
+        sbox.call_reuse("Echo", "hello".to_string(), &mut output)
+            .unwrap();
+        assert_eq!(output, "hello");
+        assert_eq!(output.as_ptr(), ptr);
+
+        // A guest error leaves the buffer empty
+        let err = sbox
+            .call_into::<Vec<u8>>("AddToStaticAndFail", (), &mut buf)
+            .unwrap_err();
+        assert!(matches!(err, HyperlightError::GuestError(..)), "{err:?}");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn guest_events_overflow() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+        let events = sbox.guest_events(4).unwrap();
+
+        // The events that fit are kept in order, and the rest are dropped
+        let queued: u32 = sbox.call("EmitEvents", 6u32).unwrap();
+        assert_eq!(queued, 4);
+        let polled = events.poll_events().unwrap();
+        assert!(polled.overflowed());
+        assert_eq!(polled.dropped, 2);
+        assert_eq!(polled.high_water_mark, 4);
+        let payloads: Vec<_> = polled.events.iter().map(|e| e.payload.clone()).collect();
+        assert_eq!(
+            payloads,
+            (0u32..4)
+                .map(|i| i.to_le_bytes().to_vec())
+                .collect::<Vec<_>>()
+        );
+        assert!(polled.events.iter().all(|e| e.kind == "tick"));
+        assert_eq!(
+            polled.events.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+
+        // Polling resets the drop count and the high-water mark
+        let polled = events.poll_events().unwrap();
+        assert_eq!(polled, Default::default());
+
+        // Sequence numbers count the dropped events too
+        let queued: u32 = sbox.call("EmitEvents", 2u32).unwrap();
+        assert_eq!(queued, 2);
+        let polled = events.poll_events().unwrap();
+        assert!(!polled.overflowed());
+        assert_eq!(polled.high_water_mark, 2);
+        assert_eq!(
+            polled.events.iter().map(|e| e.sequence).collect::<Vec<_>>(),
+            [6, 7]
+        );
+    }
+
+    /// Tests that call_guest_function_by_name restores the state correctly
+    #[test]
+    fn test_call_guest_function_by_name() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        let snapshot = sbox.snapshot().unwrap();
+
+        let _ = sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let res: i32 = sbox.call("GetStatic", ()).unwrap();
+        assert_eq!(res, 5);
+
+        sbox.restore(snapshot).unwrap();
+        #[allow(deprecated)]
+        let _ = sbox
+            .call_guest_function_by_name::<i32>("AddToStatic", 5i32)
+            .unwrap();
+        #[allow(deprecated)]
+        let res: i32 = sbox.call_guest_function_by_name("GetStatic", ()).unwrap();
+        assert_eq!(res, 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::sync::{Arc, Barrier};
-    use std::thread;
+    #[test]
+    fn call_with_request_id() {
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().unwrap()),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
 
-    use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
-    use hyperlight_testing::sandbox_sizes::{LARGE_HEAP_SIZE, MEDIUM_HEAP_SIZE, SMALL_HEAP_SIZE};
-    use hyperlight_testing::simple_guest_as_string;
+        let id: Option<String> = sbox
+            .call_with_request_id("GetRequestId", (), "req-42")
+            .unwrap();
+        assert_eq!(id.as_deref(), Some("req-42"));
 
-    use crate::mem::memory_region::{MemoryRegion, MemoryRegionFlags, MemoryRegionType};
-    use crate::mem::shared_mem::{ExclusiveSharedMemory, GuestSharedMemory, SharedMemory as _};
-    use crate::sandbox::SandboxConfiguration;
-    use crate::{GuestBinary, HyperlightError, MultiUseSandbox, Result, UninitializedSandbox};
+        // The id only applies to the call it was given for
+        let id: Option<String> = sbox.call("GetRequestId", ()).unwrap();
+        assert_eq!(id, None);
+        assert!(
+            sbox.call_with_request_id::<()>("NoSuchFunction", (), "req-43")
+                .is_err()
+        );
+        let id: Option<String> = sbox.call("GetRequestId", ()).unwrap();
+        assert_eq!(id, None);
+    }
 
     #[test]
-    fn poison() {
+    fn call_encoded() {
+        use crate::func::{Codec, FlatbufferCodec, ParameterValue, ReturnType, ReturnValue};
+
+        /// Encodes `Int` values as little-endian bytes.
+        struct IntCodec;
+        impl Codec for IntCodec {
+            fn encode_parameters(&self, parameters: &[ParameterValue]) -> anyhow::Result<Vec<u8>> {
+                let mut bytes = Vec::new();
+                for parameter in parameters {
+                    let ParameterValue::Int(i) = parameter else {
+                        anyhow::bail!("unsupported parameter {parameter:?}");
+                    };
+                    bytes.extend_from_slice(&i.to_le_bytes());
+                }
+                Ok(bytes)
+            }
+            fn decode_parameters(&self, bytes: &[u8]) -> anyhow::Result<Vec<ParameterValue>> {
+                bytes
+                    .chunks(4)
+                    .map(|chunk| Ok(ParameterValue::Int(i32::from_le_bytes(chunk.try_into()?))))
+                    .collect()
+            }
+            fn encode_return_value(&self, value: &ReturnValue) -> anyhow::Result<Vec<u8>> {
+                let ReturnValue::Int(i) = value else {
+                    anyhow::bail!("unsupported return value {value:?}");
+                };
+                Ok(i.to_le_bytes().to_vec())
+            }
+            fn decode_return_value(&self, bytes: &[u8]) -> anyhow::Result<ReturnValue> {
+                Ok(ReturnValue::Int(i32::from_le_bytes(bytes.try_into()?)))
+            }
+        }
+
         let mut sbox: MultiUseSandbox = {
             let path = simple_guest_as_string().unwrap();
             let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
             u_sbox.evolve()
         }
         .unwrap();
-        let snapshot = sbox.snapshot().unwrap();
 
-        // poison on purpose
-        let res = sbox
-            .call::<()>("guest_panic", "hello".to_string())
-            .unwrap_err();
+        let args = FlatbufferCodec
+            .encode_parameters(&[ParameterValue::String("hello".to_string())])
+            .unwrap();
+        let ret = sbox
+            .call_encoded(&FlatbufferCodec, "Echo", ReturnType::String, &args)
+            .unwrap();
+        assert_eq!(
+            FlatbufferCodec.decode_return_value(&ret).unwrap(),
+            ReturnValue::String("hello".to_string())
+        );
+
+        let args = IntCodec
+            .encode_parameters(&[ParameterValue::Int(5)])
+            .unwrap();
+        let ret = sbox
+            .call_encoded(&IntCodec, "AddToStatic", ReturnType::Int, &args)
+            .unwrap();
+        assert_eq!(ret, 5i32.to_le_bytes());
+
+        // Arguments the codec cannot decode are rejected before the call
         assert!(
-            matches!(res, HyperlightError::GuestAborted(code, context) if code == ErrorCode::UnknownError as u8 && context.contains("hello"))
+            sbox.call_encoded(&IntCodec, "AddToStatic", ReturnType::Int, &[1, 2])
+                .is_err()
         );
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+    }
+
+    #[test]
+    fn reset_to_baseline() {
+        // Without a baseline there is nothing to reset to
+        let mut sbox = new_simple_guest_sandbox(None);
+        assert!(sbox.baseline.is_none());
+        assert!(matches!(
+            sbox.reset_to_baseline(),
+            Err(HyperlightError::NoMemorySnapshot)
+        ));
+
+        let mut config = SandboxConfiguration::default();
+        config.set_capture_baseline(true);
+        let mut sbox = new_simple_guest_sandbox(Some(config));
+
+        // The baseline is the sandbox's state before any call
+        let baseline = sbox.snapshot().unwrap();
+
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let snapshot = sbox.snapshot().unwrap();
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        sbox.restore(snapshot).unwrap();
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+
+        sbox.reset_to_baseline().unwrap();
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 0);
+        assert!(Arc::ptr_eq(&sbox.baseline.clone().unwrap(), &baseline));
+
+        // Resetting also recovers a poisoned sandbox
+        let res = sbox.call::<()>("guest_panic", "panic".to_string());
+        assert!(res.is_err());
         assert!(sbox.poisoned());
+        sbox.reset_to_baseline().unwrap();
+        assert!(!sbox.poisoned());
+        assert_eq!(sbox.call::<i32>("AddToStatic", 1i32).unwrap(), 1);
 
-        // guest calls should fail when poisoned
-        let res = sbox
-            .call::<()>("guest_panic", "hello2".to_string())
-            .unwrap_err();
-        assert!(matches!(res, HyperlightError::PoisonedSandbox));
+        // A sandbox created from a snapshot of an initialised guest uses
+        // that snapshot as its baseline
+        let mut sbox2 =
+            MultiUseSandbox::from_snapshot(baseline.clone(), crate::HostFunctions::default(), None)
+                .unwrap();
+        sbox2.call::<i32>("AddToStatic", 3i32).unwrap();
+        sbox2.reset_to_baseline().unwrap();
+        assert_eq!(sbox2.call::<i32>("GetStatic", ()).unwrap(), 0);
+        assert!(Arc::ptr_eq(&sbox2.baseline.clone().unwrap(), &baseline));
+    }
 
-        // snapshot should fail when poisoned
-        if let Err(e) = sbox.snapshot() {
-            assert!(sbox.poisoned());
-            assert!(matches!(e, HyperlightError::PoisonedSandbox));
-        } else {
-            panic!("Snapshot should fail");
+    #[test]
+    fn compact_snapshots() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
         }
+        .unwrap();
 
-        // map_region should fail when poisoned
-        {
-            let map_mem = allocate_guest_memory();
-            let guest_base = 0x0;
-            let region = region_for_memory(&map_mem, guest_base, MemoryRegionFlags::READ);
-            let res = unsafe { sbox.map_region(&region) }.unwrap_err();
-            assert!(matches!(res, HyperlightError::PoisonedSandbox));
+        // Nothing to compact before the sandbox has changed
+        assert_eq!(sbox.compact_snapshots().unwrap(), 0);
+
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let reclaimed = sbox.compact_snapshots().unwrap();
+        assert!(reclaimed > 0);
+        assert_eq!(sbox.compact_snapshots().unwrap(), 0);
+
+        // The baseline is now the compacted state
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        sbox.reset_to_baseline().unwrap();
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+
+        // A baseline that is still held elsewhere is not freed
+        let held = sbox.baseline.clone().unwrap();
+        sbox.call::<i32>("AddToStatic", 1i32).unwrap();
+        assert_eq!(sbox.compact_snapshots().unwrap(), 0);
+        sbox.restore(held).unwrap();
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+
+        let res = sbox.call::<()>("guest_panic", "panic".to_string());
+        assert!(res.is_err());
+        assert!(matches!(
+            sbox.compact_snapshots(),
+            Err(HyperlightError::PoisonedSandbox)
+        ));
+    }
+
+    #[test]
+    fn snapshot_regions() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
         }
+        .unwrap();
 
-        // map_file_cow should fail when poisoned
-        {
-            let temp_file = std::env::temp_dir().join("test_poison_map_file.bin");
-            let res = sbox.map_file_cow(&temp_file, 0x0, None).unwrap_err();
-            assert!(matches!(res, HyperlightError::PoisonedSandbox));
-            std::fs::remove_file(&temp_file).ok(); // Clean up
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let counter = sbox.call::<u64>("GetStaticAddress", ()).unwrap();
+        let partial = sbox
+            .snapshot_regions(&[counter..counter + 2, counter + 1..counter + 4])
+            .unwrap();
+        assert_eq!(partial.ranges().collect::<Vec<_>>(), [counter..counter + 4]);
+        assert_eq!(partial.size(), 4);
+
+        for _ in 0..3 {
+            assert_eq!(sbox.call::<i32>("AddToStatic", 10i32).unwrap(), 15);
+            sbox.restore_partial(&partial).unwrap();
+            assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
         }
 
-        // call_guest_function_by_name (deprecated) should fail when poisoned
-        #[allow(deprecated)]
-        let res = sbox
-            .call_guest_function_by_name::<String>("Echo", "test".to_string())
-            .unwrap_err();
-        assert!(matches!(res, HyperlightError::PoisonedSandbox));
+        // Empty and unmapped ranges are rejected
+        assert!(sbox.snapshot_regions(&[counter..counter]).is_err());
+        assert!(sbox.snapshot_regions(&[0..4096]).is_err());
 
-        // restore to non-poisoned snapshot should work and clear poison
-        sbox.restore(snapshot.clone()).unwrap();
-        assert!(!sbox.poisoned());
+        // After a full restore the counter's page is back to the snapshot
+        // contents, which differ from the partial snapshot's
+        let snapshot = sbox.snapshot().unwrap();
+        sbox.call::<i32>("AddToStatic", 1i32).unwrap();
+        let partial = sbox.snapshot_regions(&[counter..counter + 4]).unwrap();
+        sbox.restore(snapshot).unwrap();
+        assert!(sbox.restore_partial(&partial).is_err());
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+    }
 
-        // guest calls should work again after restore
-        let res = sbox.call::<String>("Echo", "hello2".to_string()).unwrap();
-        assert_eq!(res, "hello2".to_string());
-        assert!(!sbox.poisoned());
+    #[test]
+    fn guest_image_info() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
 
-        // re-poison on purpose
-        let res = sbox
-            .call::<()>("guest_panic", "hello".to_string())
-            .unwrap_err();
-        assert!(
-            matches!(res, HyperlightError::GuestAborted(code, context) if code == ErrorCode::UnknownError as u8 && context.contains("hello"))
+        let image = sbox.guest_image_info();
+        assert_eq!(
+            image.load_base,
+            sbox.mem_mgr.layout.get_guest_code_address() as u64
+        );
+        assert!(image.size > 0);
+        assert!(image.contains(image.entry_point));
+        assert!(!image.contains(image.load_base + image.size));
+        assert_eq!(
+            image.to_link_address(image.load_base),
+            Some(image.link_base)
+        );
+        assert_eq!(image.to_link_address(image.load_base + image.size), None);
+        assert_eq!(
+            image.slide(),
+            image.load_base.wrapping_sub(image.link_base) as i64
         );
-        assert!(sbox.poisoned());
 
-        // restore to non-poisoned snapshot should work again
+        // The image info is carried over by snapshots
+        let snapshot = sbox.snapshot().unwrap();
         sbox.restore(snapshot.clone()).unwrap();
-        assert!(!sbox.poisoned());
+        assert_eq!(sbox.guest_image_info(), image);
+        let sbox2 = MultiUseSandbox::from_snapshot(snapshot, crate::HostFunctions::default(), None)
+            .unwrap();
+        assert_eq!(sbox2.guest_image_info(), image);
+    }
 
-        // guest calls should work again
-        let res = sbox.call::<String>("Echo", "hello3".to_string()).unwrap();
-        assert_eq!(res, "hello3".to_string());
-        assert!(!sbox.poisoned());
+    #[test]
+    fn hypervisor_backend() {
+        use crate::hypervisor::virtual_machine::get_available_hypervisor;
 
-        // snapshot should work again
-        let _ = sbox.snapshot().unwrap();
+        let sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+
+        let expected = get_available_hypervisor().unwrap();
+        assert_eq!(sbox.hypervisor_backend(), expected.into());
+        assert_eq!(
+            sbox.hypervisor_backend().capabilities().guest_debugging,
+            cfg!(gdb)
+        );
     }
 
-    /// Make sure input/output buffers are properly reset after guest call (with host call)
     #[test]
-    fn host_func_error() {
-        let path = simple_guest_as_string().unwrap();
-        let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
-        sandbox
-            .register("HostError", || -> Result<()> {
-                Err(HyperlightError::Error("hi".to_string()))
-            })
-            .unwrap();
-        let mut sandbox = sandbox.evolve().unwrap();
+    fn test_snapshot_diff() {
+        use hyperlight_common::mem::PAGE_SIZE_USIZE;
 
-        // will exhaust io if leaky
-        for _ in 0..1000 {
-            let result = sandbox
-                .call::<i64>(
-                    "CallGivenParamlessHostFuncThatReturnsI64",
-                    "HostError".to_string(),
-                )
-                .unwrap_err();
+        use crate::sandbox::snapshot::MemoryDiffKind;
 
-            assert!(
-                matches!(result, HyperlightError::GuestError(code, msg) if code == ErrorCode::HostFunctionError && msg == "hi"),
-            );
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
         }
-    }
+        .unwrap();
 
-    #[test]
-    fn call_host_func_expect_error() {
-        let path = simple_guest_as_string().unwrap();
-        let sandbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
-        let mut sandbox = sandbox.evolve().unwrap();
-        sandbox
-            .call::<()>("CallHostExpectError", "SomeUnknownHostFunc".to_string())
-            .unwrap();
+        let before = sbox.snapshot().unwrap();
+        assert!(before.diff(&before).is_empty());
+        assert!(before.size() > 0);
+        assert_eq!(before.size() % PAGE_SIZE_USIZE, 0);
+
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let after = sbox.snapshot().unwrap();
+
+        let diffs = before.diff_with_contents(&after);
+        assert!(!diffs.is_empty());
+        for diff in &diffs {
+            assert_eq!(diff.len % PAGE_SIZE_USIZE, 0);
+            if diff.kind == MemoryDiffKind::Modified {
+                let (old, new) = (diff.before.as_ref().unwrap(), diff.after.as_ref().unwrap());
+                assert_eq!(old.len(), diff.len);
+                assert_eq!(new.len(), diff.len);
+                assert_ne!(old, new);
+            }
+        }
+        assert!(diffs.iter().any(|d| d.kind == MemoryDiffKind::Modified));
+
+        // The ranges are the same in both directions, without contents
+        // unless requested
+        let reverse = after.diff(&before);
+        assert_eq!(reverse.len(), diffs.len());
+        for (forward, reverse) in diffs.iter().zip(&reverse) {
+            assert_eq!(forward.guest_address, reverse.guest_address);
+            assert_eq!(forward.len, reverse.len);
+            assert!(reverse.before.is_none() && reverse.after.is_none());
+        }
     }
 
-    /// Make sure input/output buffers are properly reset after guest call (with host call)
     #[test]
-    fn io_buffer_reset() {
-        let mut cfg = SandboxConfiguration::default();
-        cfg.set_input_data_size(4096);
-        cfg.set_output_data_size(4096);
-        let path = simple_guest_as_string().unwrap();
-        let mut sandbox =
-            UninitializedSandbox::new(GuestBinary::FilePath(path), Some(cfg)).unwrap();
-        sandbox.register("HostAdd", |a: i32, b: i32| a + b).unwrap();
-        let mut sandbox = sandbox.evolve().unwrap();
+    fn test_latency_histogram() {
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
 
-        // will exhaust io if leaky. Tests both success and error paths
-        for _ in 0..1000 {
-            let result = sandbox.call::<i32>("Add", (5i32, 10i32)).unwrap();
-            assert_eq!(result, 15);
-            let result = sandbox.call::<i32>("AddToStaticAndFail", ()).unwrap_err();
-            assert!(
-                matches!(result, HyperlightError::GuestError (code, msg ) if code == ErrorCode::GuestError && msg == "Crash on purpose")
-            );
+        assert_eq!(sbox.latency_histogram("AddToStatic").count(), 0);
+
+        for _ in 0..10 {
+            sbox.call::<i32>("AddToStatic", 1i32).unwrap();
         }
+        sbox.call::<i32>("GetStatic", ()).unwrap();
+        sbox.call::<i32>("FunctionThatDoesNotExist", ())
+            .unwrap_err();
+
+        let histogram = sbox.latency_histogram("AddToStatic");
+        assert_eq!(histogram.count(), 10);
+        assert!(histogram.percentile(50.0).unwrap() <= histogram.percentile(99.0).unwrap());
+        assert!(histogram.percentile(99.0).unwrap() <= histogram.max().unwrap());
+        assert_eq!(sbox.latency_histogram("GetStatic").count(), 1);
+        // Calls to functions the guest does not have are not recorded
+        assert_eq!(
+            sbox.latency_histogram("FunctionThatDoesNotExist").count(),
+            0
+        );
+        assert!(!sbox.latency.contains_key("FunctionThatDoesNotExist"));
+
+        sbox.reset_latency_histograms();
+        assert_eq!(sbox.latency_histogram("AddToStatic").count(), 0);
+        assert_eq!(sbox.latency_histogram("GetStatic").count(), 0);
     }
 
-    /// Tests that call_guest_function_by_name restores the state correctly
     #[test]
-    fn test_call_guest_function_by_name() {
+    fn init_duration() {
         let mut sbox: MultiUseSandbox = {
             let path = simple_guest_as_string().unwrap();
             let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
             u_sbox.evolve()
         }
         .unwrap();
+        let evolved = sbox.init_duration();
+        assert!(evolved > std::time::Duration::ZERO);
 
+        // Calls and restores do not change it
         let snapshot = sbox.snapshot().unwrap();
+        sbox.call::<i32>("AddToStatic", 1i32).unwrap();
+        sbox.restore(snapshot.clone()).unwrap();
+        assert_eq!(sbox.init_duration(), evolved);
 
-        let _ = sbox.call::<i32>("AddToStatic", 5i32).unwrap();
-        let res: i32 = sbox.call("GetStatic", ()).unwrap();
-        assert_eq!(res, 5);
-
-        sbox.restore(snapshot).unwrap();
-        #[allow(deprecated)]
-        let _ = sbox
-            .call_guest_function_by_name::<i32>("AddToStatic", 5i32)
+        // Starting from a snapshot of the initialised guest skips the
+        // initialisation
+        let warm = MultiUseSandbox::from_snapshot(snapshot, crate::HostFunctions::default(), None)
             .unwrap();
-        #[allow(deprecated)]
-        let res: i32 = sbox.call_guest_function_by_name("GetStatic", ()).unwrap();
-        assert_eq!(res, 0);
+        assert!(warm.init_duration() < evolved, "{:?}", warm.init_duration());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn shared_memory_backing() {
+        use std::os::fd::AsRawFd;
+
+        let mut sbox: MultiUseSandbox = {
+            let path = simple_guest_as_string().unwrap();
+            let u_sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None).unwrap();
+            u_sbox.evolve()
+        }
+        .unwrap();
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let snapshot = sbox.snapshot().unwrap();
+
+        // The first sandbox fills the empty file, the second maps what it
+        // wrote
+        let backing = tempfile::tempfile().unwrap();
+        let mut config = SandboxConfiguration::default();
+        // SAFETY: `backing` outlives both sandboxes created below
+        unsafe { config.set_shared_memory_backing(backing.as_raw_fd()) };
+        let mut first = MultiUseSandbox::from_snapshot(
+            snapshot.clone(),
+            crate::HostFunctions::default(),
+            Some(config),
+        )
+        .unwrap();
+        assert!(backing.metadata().unwrap().len() > 0);
+        let mut second = MultiUseSandbox::from_snapshot(
+            snapshot.clone(),
+            crate::HostFunctions::default(),
+            Some(config),
+        )
+        .unwrap();
+
+        // Writes stay private to each sandbox
+        assert_eq!(first.call::<i32>("AddToStatic", 1i32).unwrap(), 6);
+        assert_eq!(second.call::<i32>("GetStatic", ()).unwrap(), 5);
+        let baseline = first.snapshot().unwrap();
+        first.call::<i32>("AddToStatic", 1i32).unwrap();
+        first.restore(baseline).unwrap();
+        assert_eq!(first.call::<i32>("GetStatic", ()).unwrap(), 6);
+
+        // A file that holds something else is rejected
+        let other = tempfile::tempfile().unwrap();
+        other.set_len(4096).unwrap();
+        let mut config = SandboxConfiguration::default();
+        // SAFETY: `other` outlives the sandbox creation below
+        unsafe { config.set_shared_memory_backing(other.as_raw_fd()) };
+        assert!(
+            MultiUseSandbox::from_snapshot(snapshot, crate::HostFunctions::default(), Some(config))
+                .is_err()
+        );
     }
 
     // Tests to ensure that many (1000) function calls can be made in a call context with a small stack (24K) and heap(20K).
@@ -1494,6 +3954,292 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn map_host_region() {
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+
+        let expected = b"hello world";
+        let guest_base = 0x1_0000_0000;
+        {
+            // The sandbox keeps the memory alive after the region is dropped
+            let region = HostRegion::from_slice(expected).unwrap();
+            sbox.map_host_region(&region, guest_base).unwrap();
+        }
+
+        let actual: Vec<u8> = sbox
+            .call(
+                "ReadMappedBuffer",
+                (guest_base, expected.len() as u64, true),
+            )
+            .unwrap();
+        assert_eq!(actual, expected);
+
+        let region = HostRegion::from_vec(expected.to_vec()).unwrap();
+        assert!(sbox.map_host_region(&region, guest_base + 1).is_err());
+    }
+
+    #[test]
+    fn multi_use_sandbox_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<MultiUseSandbox>();
+
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+        sbox.call::<i32>("AddToStatic", 5i32).unwrap();
+
+        // An idle sandbox keeps its state when it moves to another thread
+        let mut sbox = thread::spawn(move || {
+            sbox.call::<i32>("AddToStatic", 2i32).unwrap();
+            sbox
+        })
+        .join()
+        .unwrap();
+        assert_eq!(sbox.call::<i32>("GetStatic", ()).unwrap(), 7);
+    }
+
+    #[test]
+    fn map_code_region() {
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+
+        let code = [0x90, 0x90, 0x90, 0xC3]; // NOOP slide to RET
+        let guest_va = 0x1_0000_0000;
+        sbox.map_code_region(&code, guest_va).unwrap();
+
+        let succeed = sbox
+            .call::<bool>("ExecMappedBuffer", (guest_va, code.len() as u64))
+            .unwrap();
+        assert!(succeed);
+
+        let err = sbox
+            .call::<bool>("WriteMappedBuffer", (guest_va, code.len() as u64))
+            .unwrap_err();
+        assert!(
+            matches!(err, HyperlightError::MemoryAccessViolation(addr, ..) if addr == guest_va),
+            "{err:?}"
+        );
+
+        assert!(sbox.map_code_region(&code, guest_va + 1).is_err());
+        assert!(sbox.map_code_region(&[], guest_va + 0x10_0000).is_err());
+    }
+
+    #[test]
+    fn quiesce() {
+        // The pending and finished work of the guest
+        fn work(sbox: &mut MultiUseSandbox) -> [i32; 2] {
+            let bytes: Vec<u8> = sbox.call("GetWork", ()).unwrap();
+            [0, 1].map(|i| i32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()))
+        }
+
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().unwrap()),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+
+        sbox.call::<()>("StartWork", 5i32).unwrap();
+        assert_eq!(work(&mut sbox), [5, 0]);
+        // Taking a snapshot finishes the pending work first
+        let snapshot = sbox.snapshot().unwrap();
+        assert_eq!(work(&mut sbox), [0, 5]);
+
+        sbox.call::<()>("StartWork", 3i32).unwrap();
+        sbox.restore(snapshot).unwrap();
+        assert_eq!(work(&mut sbox), [0, 5]);
+        assert!(sbox.quiesce().unwrap());
+
+        // A guest without a handler is left alone
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(c_simple_guest_as_string().unwrap()),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+        assert!(!sbox.quiesce().unwrap());
+        sbox.call::<i32>("PrintOutput", "hello".to_string())
+            .unwrap();
+        sbox.snapshot().unwrap();
+        assert!(!sbox.poisoned());
+    }
+
+    #[test]
+    fn map_value() {
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+        struct Header {
+            magic: u32,
+            count: u32,
+        }
+
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+        let snapshot = sbox.snapshot().unwrap();
+
+        let guest_base = 0x1_0000_0000;
+        let header = sbox
+            .map_value(Header { magic: 1, count: 7 }, guest_base)
+            .unwrap();
+        assert_eq!(header.guest_address(), guest_base);
+
+        header.set(Header { magic: 2, count: 7 }).unwrap();
+        let actual: Vec<u8> = sbox
+            .call("ReadMappedBuffer", (guest_base, 8u64, true))
+            .unwrap();
+        assert_eq!(actual, bytemuck::bytes_of(&Header { magic: 2, count: 7 }));
+
+        // The guest overwrites the first byte
+        let written: bool = sbox.call("WriteMappedBuffer", (guest_base, 1u64)).unwrap();
+        assert!(written);
+        assert_eq!(
+            header.get().unwrap(),
+            Header {
+                magic: 0x42,
+                count: 7
+            }
+        );
+
+        // Restoring unmaps the value, but it can still be read
+        sbox.restore(snapshot).unwrap();
+        assert_eq!(
+            header.get().unwrap(),
+            Header {
+                magic: 0x42,
+                count: 7
+            }
+        );
+
+        assert!(sbox.map_value(0u64, guest_base + 1).is_err());
+        assert!(sbox.map_value((), guest_base).is_err());
+    }
+
+    #[test]
+    fn map_shared_mut_between_sandboxes() {
+        use std::sync::atomic::Ordering;
+
+        let region = SharedMutRegion::new(4096).unwrap();
+        let counter = region.atomic_u64(0).unwrap();
+        counter.store(100, Ordering::SeqCst);
+
+        // The same region at different guest addresses in each sandbox
+        let mut sandboxes = [
+            new_simple_guest_sandbox(None),
+            new_simple_guest_sandbox(None),
+        ];
+        let guest_bases = [0x1_0000_0000u64, 0x2_0000_0000u64];
+        let snapshot = sandboxes[0].snapshot().unwrap();
+        for (sbox, guest_base) in sandboxes.iter_mut().zip(guest_bases) {
+            sbox.map_shared_mut(&region, guest_base).unwrap();
+        }
+
+        // Both guests increment the counter at the same time
+        let times = 10_000u64;
+        std::thread::scope(|s| {
+            for (sbox, guest_base) in sandboxes.iter_mut().zip(guest_bases) {
+                s.spawn(move || {
+                    let last: u64 = sbox
+                        .call("AtomicIncrementMapped", (guest_base, times))
+                        .unwrap();
+                    assert!(last >= 100 + times && last <= 100 + 2 * times, "{last}");
+                });
+            }
+        });
+        assert_eq!(counter.load(Ordering::SeqCst), 100 + 2 * times);
+
+        // The host's writes are seen by the guests
+        region.write(8, b"shared").unwrap();
+        let actual: Vec<u8> = sandboxes[1]
+            .call("ReadMappedBuffer", (guest_bases[1] + 8, 6u64, false))
+            .unwrap();
+        assert_eq!(actual, b"shared");
+
+        // Restoring unmaps the region without rolling it back
+        sandboxes[0].restore(snapshot).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 100 + 2 * times);
+        let last: u64 = sandboxes[1]
+            .call("AtomicIncrementMapped", (guest_bases[1], 1u64))
+            .unwrap();
+        assert_eq!(last, 101 + 2 * times);
+
+        assert!(
+            sandboxes[0]
+                .map_shared_mut(&region, guest_bases[0] + 1)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn restore_checks_host_regions() {
+        let mut config = SandboxConfiguration::default();
+        config.set_capture_baseline(true);
+        let mut sbox = new_simple_guest_sandbox(Some(config));
+        let snapshot = sbox.snapshot().unwrap();
+
+        let guest_base = 0x1_0000_0000;
+        let region = HostRegion::from_slice(b"hello world").unwrap();
+        sbox.map_host_region(&region, guest_base).unwrap();
+        let _: Vec<u8> = sbox
+            .call("ReadMappedBuffer", (guest_base, 11u64, true))
+            .unwrap();
+        sbox.restore(snapshot.clone()).unwrap();
+
+        // Simulate a guest write on a hypervisor without read-only mappings
+        let region = HostRegion::from_slice(b"hello world").unwrap();
+        sbox.map_host_region(&region, guest_base).unwrap();
+        unsafe { region.base_ptr().write(b'j') };
+        let res = sbox.check_host_regions();
+        assert!(
+            matches!(res, Err(HyperlightError::HostRegionModified(base)) if base == guest_base),
+            "{res:?}"
+        );
+
+        // The restore succeeds, and the modification is reported once
+        // afterwards
+        sbox.restore(snapshot).unwrap();
+        let echoed: String = sbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+        let res = sbox.check_host_regions();
+        assert!(
+            matches!(res, Err(HyperlightError::HostRegionModified(base)) if base == guest_base),
+            "{res:?}"
+        );
+        sbox.check_host_regions().unwrap();
+
+        // Resetting to the baseline checks the regions it releases too
+        let region = HostRegion::from_slice(b"hello world").unwrap();
+        sbox.map_host_region(&region, guest_base).unwrap();
+        unsafe { region.base_ptr().write(b'j') };
+        sbox.reset_to_baseline().unwrap();
+        let res = sbox.check_host_regions();
+        assert!(
+            matches!(res, Err(HyperlightError::HostRegionModified(base)) if base == guest_base),
+            "{res:?}"
+        );
+    }
+
     // Makes sure MemoryRegionFlags::READ | MemoryRegionFlags::EXECUTE executable but not writable
     #[test]
     fn test_mmap_write_exec() {
@@ -1543,6 +4289,160 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_protect_region() {
+        use hyperlight_common::mem::PAGE_SIZE_USIZE;
+
+        use crate::hypervisor::hyperlight_vm::{HyperlightVmError, ProtectRegionError};
+
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+
+        let expected = [0xa5; 2 * PAGE_SIZE_USIZE];
+        let map_mem = page_aligned_memory(&expected);
+        let guest_base = 0x1_0000_0000; // Arbitrary guest base address
+        let second_page = (guest_base + PAGE_SIZE_USIZE) as u64;
+
+        unsafe {
+            sbox.map_region(&region_for_memory(
+                &map_mem,
+                guest_base,
+                MemoryRegionFlags::READ,
+            ))
+            .unwrap();
+        }
+        let _guard = map_mem.lock.try_read().unwrap();
+
+        sbox.protect_region(second_page, PAGE_SIZE_USIZE as u64, MemoryRegionFlags::NONE)
+            .unwrap();
+        assert_eq!(sbox.vm.get_mapped_regions().count(), 2);
+
+        // The first page is still readable, the second is not
+        let actual: Vec<u8> = sbox
+            .call("ReadMappedBuffer", (guest_base as u64, 16_u64, true))
+            .unwrap();
+        assert_eq!(actual, &expected[..16]);
+        let err = sbox
+            .call::<Vec<u8>>("ReadMappedBuffer", (second_page, 16_u64, true))
+            .unwrap_err();
+        match err {
+            HyperlightError::MemoryAccessViolation(addr, MemoryRegionFlags::READ, flags)
+                if addr == second_page && flags == MemoryRegionFlags::NONE => {}
+            e => panic!("Expected MemoryAccessViolation error, got {e:?}"),
+        };
+
+        // Invalid requests are rejected without poisoning the sandbox
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+        unsafe {
+            sbox.map_region(&region_for_memory(
+                &map_mem,
+                guest_base,
+                MemoryRegionFlags::READ,
+            ))
+            .unwrap();
+        }
+        for (gpa, len, prot) in [
+            (
+                second_page + 1,
+                PAGE_SIZE_USIZE as u64,
+                MemoryRegionFlags::NONE,
+            ),
+            (second_page, 0, MemoryRegionFlags::NONE),
+            (
+                second_page,
+                2 * PAGE_SIZE_USIZE as u64,
+                MemoryRegionFlags::NONE,
+            ),
+            (
+                second_page,
+                PAGE_SIZE_USIZE as u64,
+                MemoryRegionFlags::READ | MemoryRegionFlags::WRITE,
+            ),
+        ] {
+            let err = sbox.protect_region(gpa, len, prot).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    HyperlightError::HyperlightVmError(HyperlightVmError::ProtectRegion(
+                        ProtectRegionError::NotPageAligned { .. }
+                            | ProtectRegionError::NotMapped { .. }
+                            | ProtectRegionError::NotWritable { .. }
+                    ))
+                ),
+                "unexpected error for {gpa:#x}+{len:#x}: {err:?}"
+            );
+            assert!(!sbox.poisoned());
+        }
+        assert_eq!(sbox.vm.get_mapped_regions().count(), 1);
+
+        // The sandbox's own memory and scratch region cannot be reprotected
+        let scratch_base = hyperlight_common::layout::scratch_base_gpa(
+            crate::sandbox::SandboxConfiguration::DEFAULT_SCRATCH_SIZE,
+        );
+        for gpa in [
+            crate::mem::layout::SandboxMemoryLayout::BASE_ADDRESS as u64,
+            scratch_base,
+        ] {
+            match sbox.protect_region(gpa, PAGE_SIZE_USIZE as u64, MemoryRegionFlags::READ) {
+                Err(HyperlightError::Unsupported { backend, .. })
+                    if backend == sbox.hypervisor_backend() => {}
+                r => panic!("Expected Unsupported error for {gpa:#x}, got {r:?}"),
+            }
+            assert!(!sbox.poisoned());
+        }
+    }
+
+    #[test]
+    fn test_protect_region_execute_unsupported() {
+        use hyperlight_common::mem::PAGE_SIZE_USIZE;
+
+        let mut sbox = UninitializedSandbox::new(
+            GuestBinary::FilePath(simple_guest_as_string().expect("Guest Binary Missing")),
+            None,
+        )
+        .unwrap()
+        .evolve()
+        .unwrap();
+
+        let map_mem = page_aligned_memory(&[0xa5; PAGE_SIZE_USIZE]);
+        let guest_base = 0x1_0000_0000; // Arbitrary guest base address
+        unsafe {
+            sbox.map_region(&region_for_memory(
+                &map_mem,
+                guest_base,
+                MemoryRegionFlags::READ | MemoryRegionFlags::EXECUTE,
+            ))
+            .unwrap();
+        }
+
+        let res = sbox.protect_region(
+            guest_base as u64,
+            PAGE_SIZE_USIZE as u64,
+            MemoryRegionFlags::READ,
+        );
+        if sbox.capabilities().execute_protection {
+            res.unwrap();
+        } else {
+            match res {
+                Err(HyperlightError::Unsupported { feature, backend })
+                    if feature == "execute protection" && backend == sbox.hypervisor_backend() => {}
+                r => panic!("Expected Unsupported error, got {r:?}"),
+            }
+            assert!(!sbox.poisoned());
+        }
+    }
+
     fn page_aligned_memory(src: &[u8]) -> GuestSharedMemory {
         use hyperlight_common::mem::PAGE_SIZE_USIZE;
 
@@ -3443,4 +6343,38 @@ mod tests {
             assert!(msg.contains("Echo42"), "got: {}", msg);
         }
     }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn run_at() {
+        use super::GuestRegisters;
+
+        let path = simple_guest_as_string().unwrap();
+        let mut sbox = UninitializedSandbox::new(GuestBinary::FilePath(path), None)
+            .unwrap()
+            .evolve()
+            .unwrap();
+        let snapshot = sbox.snapshot().unwrap();
+
+        let entry: u64 = sbox.call("RawAddAddress", ()).unwrap();
+        let regs = GuestRegisters {
+            rdi: 2,
+            rsi: 3,
+            ..Default::default()
+        };
+        let regs = unsafe { sbox.run_at(entry, regs) }.unwrap();
+        assert_eq!(regs.rax, 5);
+        assert!(!sbox.poisoned());
+
+        let image = sbox.guest_image_info();
+        let outside = image.load_base + image.size;
+        assert!(unsafe { sbox.run_at(outside, GuestRegisters::default()) }.is_err());
+        assert!(!sbox.poisoned());
+
+        // The raw code did not touch guest state, but restore anyway as
+        // callers should
+        sbox.restore(snapshot).unwrap();
+        let echoed: String = sbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+    }
 }
@@ -0,0 +1,169 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::time::Duration;
+
+/// Number of buckets in a [`Histogram`]. Bucket `i` counts durations of
+/// less than `2^i` microseconds that did not fit in bucket `i - 1`, and the
+/// last bucket also counts everything longer.
+const NUM_BUCKETS: usize = 32;
+
+/// A histogram of guest function call durations, see
+/// [`MultiUseSandbox::latency_histogram`](crate::MultiUseSandbox::latency_histogram).
+///
+/// Durations are counted in fixed buckets whose upper bounds are powers of
+/// two microseconds, so recording a call is a single increment and
+/// percentiles are accurate to within a factor of two.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; NUM_BUCKETS],
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Histogram {
+    /// Record a single call that took `duration`.
+    pub(crate) fn record(&mut self, duration: Duration) {
+        self.buckets[bucket_index(duration)] += 1;
+        self.count += 1;
+        self.total = self.total.saturating_add(duration);
+        self.max = self.max.max(duration);
+    }
+
+    /// The number of calls recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The mean duration of the recorded calls, or `None` if no calls have
+    /// been recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).ok().filter(|&c| c > 0)?;
+        Some(self.total / count)
+    }
+
+    /// The longest recorded call, or `None` if no calls have been recorded.
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// An upper bound on the duration of the fastest `percentile`% of the
+    /// recorded calls, e.g. `percentile(99.0)` for the p99 latency.
+    ///
+    /// Returns `None` if no calls have been recorded. `percentile` is clamped
+    /// to `0.0..=100.0`, and the result never exceeds [`max`](Self::max).
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * self.count as f64).ceil() as u64;
+        let rank = rank.max(1);
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(bucket_upper_bound(i).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// The number of calls counted in each bucket, as pairs of the bucket's
+    /// exclusive upper bound and its count. The last bucket's bound is
+    /// [`Duration::MAX`].
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| (bucket_upper_bound(i), n))
+    }
+
+    /// Discard all recorded calls.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn bucket_index(duration: Duration) -> usize {
+    let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+    ((u64::BITS - micros.leading_zeros()) as usize).min(NUM_BUCKETS - 1)
+}
+
+fn bucket_upper_bound(index: usize) -> Duration {
+    if index == NUM_BUCKETS - 1 {
+        Duration::MAX
+    } else {
+        Duration::from_micros(1 << index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let h = Histogram::default();
+        assert_eq!(h.count(), 0);
+        assert_eq!(h.mean(), None);
+        assert_eq!(h.max(), None);
+        assert_eq!(h.percentile(50.0), None);
+        assert!(h.buckets().all(|(_, n)| n == 0));
+    }
+
+    #[test]
+    fn bucketing() {
+        assert_eq!(bucket_index(Duration::ZERO), 0);
+        assert_eq!(bucket_index(Duration::from_nanos(999)), 0);
+        assert_eq!(bucket_index(Duration::from_micros(1)), 1);
+        assert_eq!(bucket_index(Duration::from_micros(3)), 2);
+        assert_eq!(bucket_index(Duration::from_micros(4)), 3);
+        assert_eq!(bucket_index(Duration::from_secs(1)), 20);
+        assert_eq!(bucket_index(Duration::MAX), NUM_BUCKETS - 1);
+
+        for micros in [0, 1, 5, 100, 12345] {
+            let d = Duration::from_micros(micros);
+            assert!(d < bucket_upper_bound(bucket_index(d)));
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut h = Histogram::default();
+        for _ in 0..99 {
+            h.record(Duration::from_micros(10));
+        }
+        h.record(Duration::from_millis(50));
+
+        assert_eq!(h.count(), 100);
+        assert_eq!(h.max(), Some(Duration::from_millis(50)));
+        assert_eq!(h.percentile(50.0), Some(Duration::from_micros(16)));
+        assert_eq!(h.percentile(99.0), Some(Duration::from_micros(16)));
+        assert_eq!(h.percentile(100.0), Some(Duration::from_millis(50)));
+        assert_eq!(h.percentile(0.0), Some(Duration::from_micros(16)));
+        assert_eq!(
+            h.mean(),
+            Some((Duration::from_micros(10) * 99 + Duration::from_millis(50)) / 100)
+        );
+        assert_eq!(h.buckets().map(|(_, n)| n).sum::<u64>(), 100);
+
+        h.reset();
+        assert_eq!(h, Histogram::default());
+    }
+}
@@ -0,0 +1,391 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+
+use super::Callable;
+use crate::Result;
+use crate::func::{ParameterTuple, SupportedReturnType};
+
+/// The number of results a [`Memoized`] caches unless configured otherwise.
+pub const DEFAULT_MEMOIZED_CAPACITY: usize = 1024;
+
+/// Which cached result a [`Memoized`] discards when it is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Discard the result that was least recently returned.
+    #[default]
+    LeastRecentlyUsed,
+    /// Discard the result that was cached first, regardless of how often it
+    /// has been returned since.
+    FirstInFirstOut,
+}
+
+/// A cache key: the function name and the encoded return type and arguments.
+type Key = (String, Vec<u8>);
+
+struct Entry {
+    value: ReturnValue,
+    /// The position of this entry in the eviction order.
+    tick: u64,
+}
+
+/// Wraps a [`Callable`] and caches the results of calls to guest functions
+/// that have been marked as pure with [`mark_pure`](Self::mark_pure).
+///
+/// A call to a pure function with the same name, arguments and return type as
+/// a previous successful call returns the cached result without entering the
+/// guest. Calls to functions that are not marked as pure, and failed calls,
+/// are never cached.
+///
+/// Marking a function as pure is a promise that its result depends only on
+/// its arguments. The cache is not invalidated when the guest's state
+/// changes (for example when the wrapped sandbox is restored from a
+/// snapshot), so use [`invalidate`](Self::invalidate) or
+/// [`clear`](Self::clear) if that promise stops holding.
+///
+/// ```no_run
+/// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+/// # use hyperlight_host::sandbox::{Callable, EvictionPolicy, Memoized};
+/// # fn example() -> Result<()> {
+/// let sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?;
+/// let mut sandbox = Memoized::new(sandbox.evolve()?)
+///     .with_capacity(128)
+///     .with_eviction_policy(EvictionPolicy::FirstInFirstOut);
+/// sandbox.mark_pure("Hash");
+///
+/// let first: i32 = sandbox.call("Hash", "input".to_string())?;
+/// // Returned from the cache without entering the guest
+/// let second: i32 = sandbox.call("Hash", "input".to_string())?;
+/// assert_eq!(first, second);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Memoized<C: Callable> {
+    inner: C,
+    pure_functions: HashSet<String>,
+    capacity: usize,
+    eviction_policy: EvictionPolicy,
+    entries: HashMap<Key, Entry>,
+    /// The keys of all entries, in the order they will be evicted.
+    order: BTreeMap<u64, Key>,
+    next_tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl<C: Callable> Memoized<C> {
+    /// Wrap `inner`, caching up to [`DEFAULT_MEMOIZED_CAPACITY`] results with
+    /// the [`LeastRecentlyUsed`](EvictionPolicy::LeastRecentlyUsed) policy.
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            pure_functions: HashSet::new(),
+            capacity: DEFAULT_MEMOIZED_CAPACITY,
+            eviction_policy: EvictionPolicy::default(),
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Cache at most `capacity` results. A capacity of 0 disables caching.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.evict_to(capacity);
+        self
+    }
+
+    /// Use `eviction_policy` to choose which result to discard when the
+    /// cache is full.
+    pub fn with_eviction_policy(mut self, eviction_policy: EvictionPolicy) -> Self {
+        self.eviction_policy = eviction_policy;
+        self
+    }
+
+    /// Cache the results of calls to the guest function `func_name`.
+    pub fn mark_pure(&mut self, func_name: impl Into<String>) {
+        self.pure_functions.insert(func_name.into());
+    }
+
+    /// Stop caching the results of calls to `func_name`, and discard the
+    /// results already cached for it.
+    pub fn unmark_pure(&mut self, func_name: &str) {
+        self.pure_functions.remove(func_name);
+        self.invalidate(func_name);
+    }
+
+    /// Discard all cached results of calls to `func_name`.
+    pub fn invalidate(&mut self, func_name: &str) {
+        self.entries.retain(|(name, _), _| name != func_name);
+        self.order.retain(|_, (name, _)| name != func_name);
+    }
+
+    /// Discard all cached results.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// The number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of calls that were answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of calls to pure functions that had to enter the guest.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The wrapped [`Callable`].
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// The wrapped [`Callable`]. Calls made directly on it bypass the cache.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Unwrap the [`Callable`], discarding the cache.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn lookup(&mut self, key: &Key) -> Option<ReturnValue> {
+        let entry = self.entries.get_mut(key)?;
+        if self.eviction_policy == EvictionPolicy::LeastRecentlyUsed {
+            let key = self.order.remove(&entry.tick)?;
+            entry.tick = self.next_tick;
+            self.order.insert(self.next_tick, key);
+            self.next_tick += 1;
+        }
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: Key, value: ReturnValue) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.evict_to(self.capacity - 1);
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        self.order.insert(tick, key.clone());
+        self.entries.insert(key, Entry { value, tick });
+    }
+
+    fn evict_to(&mut self, len: usize) {
+        while self.entries.len() > len {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+        }
+    }
+}
+
+impl<C: Callable> Callable for Memoized<C> {
+    fn call<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        args: impl ParameterTuple,
+    ) -> Result<Output> {
+        if !self.pure_functions.contains(func_name) {
+            return self.inner.call(func_name, args);
+        }
+
+        let key = (
+            func_name.to_string(),
+            encode_key(Output::TYPE, args.clone()),
+        );
+        if let Some(value) = self.lookup(&key) {
+            self.hits += 1;
+            return Ok(Output::from_value(value)?);
+        }
+
+        self.misses += 1;
+        let output: Output = self.inner.call(func_name, args)?;
+        self.insert(key, output.clone().into_value());
+        Ok(output)
+    }
+}
+
+impl<C: Callable + std::fmt::Debug> std::fmt::Debug for Memoized<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Memoized")
+            .field("inner", &self.inner)
+            .field("capacity", &self.capacity)
+            .field("eviction_policy", &self.eviction_policy)
+            .field("len", &self.entries.len())
+            .finish()
+    }
+}
+
+/// Encode the return type and arguments of a call, so that two calls have
+/// the same encoding exactly when they have the same return type and
+/// arguments.
+fn encode_key(return_type: ReturnType, args: impl ParameterTuple) -> Vec<u8> {
+    let mut key = vec![return_type as u8];
+    for arg in args.into_value() {
+        encode_value(&mut key, arg);
+    }
+    key
+}
+
+fn encode_value(key: &mut Vec<u8>, arg: ParameterValue) {
+    match arg {
+        ParameterValue::Int(v) => encode_arg(key, 0, &v.to_le_bytes()),
+        ParameterValue::UInt(v) => encode_arg(key, 1, &v.to_le_bytes()),
+        ParameterValue::Long(v) => encode_arg(key, 2, &v.to_le_bytes()),
+        ParameterValue::ULong(v) => encode_arg(key, 3, &v.to_le_bytes()),
+        ParameterValue::Float(v) => encode_arg(key, 4, &v.to_bits().to_le_bytes()),
+        ParameterValue::Double(v) => encode_arg(key, 5, &v.to_bits().to_le_bytes()),
+        ParameterValue::String(v) => encode_arg(key, 6, v.as_bytes()),
+        ParameterValue::Bool(v) => encode_arg(key, 7, &[v as u8]),
+        ParameterValue::VecBytes(v) => encode_arg(key, 8, &v),
+        ParameterValue::Optional(None) => encode_arg(key, 9, &[]),
+        // The tag is followed by the encoding of the value
+        ParameterValue::Optional(Some(v)) => {
+            key.push(10);
+            encode_value(key, *v);
+        }
+        ParameterValue::VecFloat(v) => {
+            let bytes: Vec<u8> = v.iter().flat_map(|f| f.to_bits().to_le_bytes()).collect();
+            encode_arg(key, 11, &bytes)
+        }
+        ParameterValue::VecDouble(v) => {
+            let bytes: Vec<u8> = v.iter().flat_map(|d| d.to_bits().to_le_bytes()).collect();
+            encode_arg(key, 12, &bytes)
+        }
+        // The tag and number of entries are followed by each key and the
+        // encoding of its value
+        ParameterValue::Map(entries) => {
+            encode_arg(key, 13, &(entries.len() as u64).to_le_bytes());
+            for (name, value) in entries {
+                encode_arg(key, 14, name.as_bytes());
+                encode_value(key, value);
+            }
+        }
+    }
+}
+
+fn encode_arg(key: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    key.push(tag);
+    key.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    key.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the sum of its arguments and counts how often it was called.
+    #[derive(Default)]
+    struct Adder {
+        calls: usize,
+    }
+
+    impl Callable for Adder {
+        fn call<Output: SupportedReturnType>(
+            &mut self,
+            func_name: &str,
+            args: impl ParameterTuple,
+        ) -> Result<Output> {
+            self.calls += 1;
+            let sum = args
+                .into_value()
+                .into_iter()
+                .map(|arg| match arg {
+                    ParameterValue::Int(v) => Ok(v),
+                    _ => Err(crate::new_error!("{func_name}: unexpected argument")),
+                })
+                .sum::<Result<i32>>()?;
+            Ok(Output::from_value(ReturnValue::Int(sum))?)
+        }
+    }
+
+    #[test]
+    fn caches_only_pure_functions() {
+        let mut memoized = Memoized::new(Adder::default());
+        memoized.mark_pure("Add");
+
+        assert_eq!(memoized.call::<i32>("Add", (1, 2)).unwrap(), 3);
+        assert_eq!(memoized.call::<i32>("Add", (1, 2)).unwrap(), 3);
+        assert_eq!(memoized.call::<i32>("Add", (2, 1)).unwrap(), 3);
+        assert_eq!(memoized.inner().calls, 2);
+        assert_eq!((memoized.hits(), memoized.misses()), (1, 2));
+
+        memoized.call::<i32>("Other", (1, 2)).unwrap();
+        memoized.call::<i32>("Other", (1, 2)).unwrap();
+        assert_eq!(memoized.inner().calls, 4);
+        assert_eq!(memoized.len(), 2);
+
+        // Failed calls are not cached
+        memoized.mark_pure("Fail");
+        assert!(memoized.call::<i32>("Fail", (true,)).is_err());
+        assert!(memoized.call::<i32>("Fail", (true,)).is_err());
+        assert_eq!(memoized.inner().calls, 6);
+
+        memoized.unmark_pure("Add");
+        assert!(memoized.is_empty());
+        memoized.call::<i32>("Add", (1, 2)).unwrap();
+        assert_eq!(memoized.inner().calls, 7);
+    }
+
+    #[test]
+    fn eviction() {
+        let mut lru = Memoized::new(Adder::default()).with_capacity(2);
+        let mut fifo = Memoized::new(Adder::default())
+            .with_capacity(2)
+            .with_eviction_policy(EvictionPolicy::FirstInFirstOut);
+
+        for memoized in [&mut lru, &mut fifo] {
+            memoized.mark_pure("Add");
+            for args in [(1,), (2,), (1,), (3,), (1,)] {
+                memoized.call::<i32>("Add", args).unwrap();
+            }
+            assert_eq!(memoized.len(), 2);
+        }
+
+        // LRU keeps (1,) because it was used before (3,) was added, FIFO
+        // evicts it when (3,) is added and has to call the guest again.
+        assert_eq!(lru.inner().calls, 3);
+        assert_eq!(fifo.inner().calls, 4);
+
+        let mut disabled = Memoized::new(Adder::default()).with_capacity(0);
+        disabled.mark_pure("Add");
+        disabled.call::<i32>("Add", (1,)).unwrap();
+        disabled.call::<i32>("Add", (1,)).unwrap();
+        assert_eq!(disabled.inner().calls, 2);
+        assert!(disabled.is_empty());
+    }
+}
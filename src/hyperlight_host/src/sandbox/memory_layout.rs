@@ -0,0 +1,133 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The layout of a sandbox's memory, see [`MultiUseSandbox::memory_layout`].
+
+use crate::MultiUseSandbox;
+
+/// The sizes of a sandbox's memory regions, and how they are backed on the
+/// host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryLayout {
+    /// The size of the pages the host was asked to back the guest's
+    /// writable (scratch) memory with. This is the huge page size if huge
+    /// pages were requested with
+    /// [`SandboxConfiguration::set_huge_pages`](crate::sandbox::SandboxConfiguration::set_huge_pages)
+    /// and the host supports them, and the regular page size otherwise.
+    ///
+    /// Transparent huge pages are only allocated as the memory is touched,
+    /// and only while the kernel has contiguous memory to spare, so some of
+    /// the scratch memory may still be backed by regular pages. The other
+    /// regions, such as the snapshot memory, always use regular pages.
+    pub page_size: usize,
+    /// Whether huge pages were requested, whether or not they are used
+    pub huge_pages: bool,
+    /// The size of the guest's writable (scratch) memory
+    pub scratch_size: usize,
+    /// The size of the guest's snapshot memory, which holds its code,
+    /// heap and init data
+    pub snapshot_size: usize,
+    /// The size of the guest heap
+    pub heap_size: usize,
+    /// The maximum size of the guest's main stack, or 0 if it is only
+    /// bounded by the available scratch memory
+    pub stack_size: usize,
+    /// The size of the buffer the host writes guest call inputs to
+    pub input_data_size: usize,
+    /// The size of the buffer the guest writes call outputs to
+    pub output_data_size: usize,
+    /// The size of the shared ring, or 0 if the sandbox has none
+    pub shared_ring_size: usize,
+}
+
+impl MultiUseSandbox {
+    /// The layout of this sandbox's memory, including the size of the pages
+    /// requested for its scratch memory on the host.
+    ///
+    /// Restoring a snapshot with a different layout changes the layout of
+    /// the sandbox to the snapshot's.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::SandboxConfiguration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut config = SandboxConfiguration::default();
+    /// config.set_huge_pages(true);
+    /// let sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     Some(config)
+    /// )?.evolve()?;
+    ///
+    /// let layout = sandbox.memory_layout();
+    /// println!("scratch memory requested {} byte pages", layout.page_size);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn memory_layout(&self) -> MemoryLayout {
+        let layout = &self.mem_mgr.layout;
+        MemoryLayout {
+            page_size: self.mem_mgr.scratch_page_size,
+            huge_pages: layout.huge_pages,
+            scratch_size: layout.get_scratch_size(),
+            snapshot_size: layout.snapshot_size,
+            heap_size: layout.heap_size,
+            stack_size: layout.stack_size,
+            input_data_size: layout.input_data_size,
+            output_data_size: layout.output_data_size,
+            shared_ring_size: layout.shared_ring_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperlight_common::mem::PAGE_SIZE_USIZE;
+
+    use crate::mem::shared_mem::HUGE_PAGE_SIZE;
+    use crate::sandbox::SandboxConfiguration;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn regular_pages_by_default() {
+        let config = SandboxConfiguration::default();
+        let layout = new_simple_guest_sandbox(Some(config)).memory_layout();
+        assert!(!layout.huge_pages);
+        assert_eq!(layout.page_size, PAGE_SIZE_USIZE);
+        assert_eq!(layout.scratch_size, config.get_scratch_size());
+    }
+
+    #[test]
+    fn huge_pages() {
+        let mut config = SandboxConfiguration::default();
+        config.set_huge_pages(true);
+        let mut sandbox = new_simple_guest_sandbox(Some(config));
+        let layout = sandbox.memory_layout();
+        assert!(layout.huge_pages);
+        // Whether huge pages are used depends on the host
+        assert!(
+            [PAGE_SIZE_USIZE, HUGE_PAGE_SIZE].contains(&layout.page_size),
+            "{layout:?}"
+        );
+
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+        let snapshot = sandbox.snapshot().unwrap();
+        sandbox.restore(snapshot).unwrap();
+        assert_eq!(sandbox.memory_layout(), layout);
+    }
+}
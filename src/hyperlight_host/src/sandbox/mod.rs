@@ -14,22 +14,99 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+/// Statistics of the guest's heap allocator
+pub mod allocator_stats;
+/// Running batches of guest function calls
+pub mod batch;
+/// Passing guest functions borrowed byte slices
+pub mod borrowed_call;
+/// Validating calls to a guest function once
+pub mod call_schema;
+/// Guest calls that reuse their serialization buffers
+pub mod call_session;
+/// Host callbacks scoped to a single guest call
+pub(crate) mod callback;
 /// Configuration needed to establish a sandbox.
 pub mod config;
+/// Guest functions that suspend while they wait for the host
+pub mod continuation;
+/// Application-defined errors returned by guest functions
+pub mod domain_error;
+/// Counting the guest's exits to the host during a call
+pub mod exit_stats;
+/// Resetting a sandbox after every call
+pub mod fast_reset;
 /// Host-side file mapping preparation for `map_file_cow`.
 pub(crate) mod file_mapping;
+/// Releasing the hypervisor resources of idle sandboxes
+pub mod freeze;
+/// Listing the functions a guest has registered
+pub mod function_table;
+/// Walking the guest's call stack
+#[cfg(target_arch = "x86_64")]
+pub mod guest_backtrace;
+/// Events emitted by the guest
+pub mod guest_events;
+/// The compile-time features a guest was built with
+pub mod guest_features;
+/// Line-buffered capture of guest output
+pub mod guest_lines;
+/// Host functions that write to guest memory
+#[cfg(target_arch = "x86_64")]
+pub mod guest_memory;
+/// Metrics emitted by the guest
+pub mod guest_metrics;
+/// Guest functions that return a stream of chunks
+pub mod guest_stream;
+/// Listing the guest's threads of execution
+#[cfg(target_arch = "x86_64")]
+pub mod guest_threads;
+/// Sandboxes created on a background thread
+pub mod handle;
 /// Functionality for reading, but not modifying host functions
 pub(crate) mod host_funcs;
+/// Host-mediated calls from one sandbox's guest into another sandbox
+mod host_link;
+/// Host functions that stream their result to the guest
+pub mod host_stream;
+/// Reporting the progress of a guest's initialisation
+pub mod init_progress;
 /// Functionality for dealing with initialized sandboxes that can
 /// call 0 or more guest functions
 pub mod initialized_multi_use;
+/// Per-function guest call latency histograms
+pub mod latency;
+/// Caching of the results of pure guest functions
+pub mod memoized;
+/// The layout of a sandbox's memory
+pub mod memory_layout;
 pub(crate) mod outb;
+/// Limiting the rate of guest calls to a sandbox
+pub mod rate_limit;
+/// Waiting for the guest to signal that it is ready for calls
+pub mod readiness;
+/// Reentrant guest calls made from host functions
+pub mod reentrant;
+/// Enumerating and shutting down all the sandboxes in a registry
+pub mod registry;
+/// Which of a sandbox's memory pages are resident in host RAM
+pub mod residency;
+/// Passing a guest function a buffer assembled from several host buffers
+pub mod scatter_gather;
+/// Running the calls to many sandboxes on a few worker threads
+pub mod scheduler;
+/// Running a sandbox forward in several ways from the same state
+pub mod speculate;
+/// Comparing the states of two sandboxes
+pub mod state_equivalence;
 /// Functionality for creating uninitialized sandboxes, manipulating them,
 /// and converting them to initialized sandboxes.
 pub mod uninitialized;
 /// Functionality for properly converting `UninitializedSandbox`es to
 /// initialized `Sandbox`es.
 pub(crate) mod uninitialized_evolve;
+/// Arbitrary host data attached to a sandbox
+pub mod user_data;
 
 /// Representation of a snapshot of a `Sandbox`.
 pub mod snapshot;
@@ -41,17 +118,99 @@ mod callable;
 #[cfg(feature = "trace_guest")]
 pub(crate) mod trace;
 
+/// Re-export for the batch call types
+pub use batch::{BatchCall, BatchMode, BatchResult};
+/// Re-export for the `CallSchema` type
+pub use call_schema::CallSchema;
+/// Re-export for the `CallSession` type
+pub use call_session::CallSession;
 /// Trait used by the macros to paper over the differences between hyperlight and hyperlight-wasm
 pub use callable::Callable;
 /// Re-export for `SandboxConfiguration` type
 pub use config::SandboxConfiguration;
+/// Re-export for the continuation types
+pub use continuation::{ContinuationToken, Resumed};
+/// Re-export for the domain error types
+pub use domain_error::{DomainError, FromDomainError};
+/// Re-export for the exit statistics types
+pub use exit_stats::{ExitCounter, ExitReason, ExitStats};
+/// Re-export for the `FastResetCallable` type
+pub use fast_reset::FastResetCallable;
+/// Re-export for the `FrozenSandbox` type
+pub use freeze::FrozenSandbox;
+/// Re-export for the `FunctionSignature` type
+pub use function_table::FunctionSignature;
+/// Re-export for the guest event types
+pub use guest_events::{GuestEvent, GuestEvents, PolledEvents};
+/// Re-export for the `GuestLines` type
+pub use guest_lines::GuestLines;
+/// Re-export for the `GuestMemoryAccessor` type
+#[cfg(target_arch = "x86_64")]
+pub use guest_memory::GuestMemoryAccessor;
+/// Re-export for the `GuestMetrics` type
+pub use guest_metrics::GuestMetrics;
+/// Re-export for the `GuestStream` type
+pub use guest_stream::GuestStream;
+/// Re-export for the guest thread types
+#[cfg(target_arch = "x86_64")]
+pub use guest_threads::{GuestThreadInfo, GuestThreadState};
+/// Re-export for the `SandboxHandle` type
+pub use handle::SandboxHandle;
+/// Re-export for the `UnknownHostCallPolicy` type
+pub use host_funcs::UnknownHostCallPolicy;
+/// Re-export for the `HostLink` type
+pub use host_link::{CALL_SANDBOX_HOST_FUNCTION, HostLink};
+/// Re-export for the `RingWriter` type
+pub use host_stream::RingWriter;
+/// Re-export for the `GuestAllocatorStats` type
+pub use hyperlight_common::allocator_stats::GuestAllocatorStats;
+/// Re-export for the `FpExceptions` type
+pub use hyperlight_common::fp_exceptions::FpExceptions;
+/// Re-export for the `GuestFeatures` type
+pub use hyperlight_common::guest_features::GuestFeatures;
+/// Re-export for the `AllocMode` type
+pub use hyperlight_common::mem::AllocMode;
+/// Re-export for the `InitProgress` type
+pub use init_progress::InitProgress;
+/// Re-export for the `GuestRegisters` type
+#[cfg(target_arch = "x86_64")]
+pub use initialized_multi_use::GuestRegisters;
 /// Re-export for the `MultiUseSandbox` type
 pub use initialized_multi_use::{MultiUseSandbox, PtRootFinder};
+/// Re-export for the latency `Histogram` type
+pub use latency::Histogram;
+/// Re-export for the `Memoized` wrapper and its configuration
+pub use memoized::{EvictionPolicy, Memoized};
+/// Re-export for the `MemoryLayout` type
+pub use memory_layout::MemoryLayout;
+/// Re-export for the `RateLimitMode` type
+pub use rate_limit::RateLimitMode;
+/// Re-export for the `Readiness` type
+pub use readiness::Readiness;
+/// Re-export for the `SandboxRegistry` type
+pub use registry::{RegisteredSandbox, SandboxRegistry};
+/// Re-export for the `ResidencyStats` type
+pub use residency::ResidencyStats;
+/// Re-export for the `SandboxScheduler` type
+pub use scheduler::{SandboxScheduler, ScheduledCall};
+/// Re-export for the `StateDivergence` type
+pub use state_equivalence::StateDivergence;
 /// Re-export for `GuestBinary` type
 pub use uninitialized::GuestBinary;
 /// Re-export for `UninitializedSandbox` type
 pub use uninitialized::UninitializedSandbox;
 
+/// Creates an initialized sandbox running the simple guest, with `config`
+/// if one is given, for tests.
+#[cfg(test)]
+pub(crate) fn new_simple_guest_sandbox(config: Option<SandboxConfiguration>) -> MultiUseSandbox {
+    let path = hyperlight_testing::simple_guest_as_string().unwrap();
+    UninitializedSandbox::new(GuestBinary::FilePath(path), config)
+        .unwrap()
+        .evolve()
+        .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -16,14 +16,19 @@ limitations under the License.
 
 use std::sync::{Arc, Mutex};
 
-use hyperlight_common::flatbuffer_wrappers::function_types::{FunctionCallResult, ParameterValue};
+use hyperlight_common::builtin_functions::INVOKE_HOST_CALLBACK;
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    FunctionCallResult, ParameterValue, ReturnType, ReturnValue,
+};
 use hyperlight_common::flatbuffer_wrappers::guest_error::{ErrorCode, GuestError};
 use hyperlight_common::flatbuffer_wrappers::guest_log_data::GuestLogData;
 use hyperlight_common::flatbuffer_wrappers::guest_log_level::LogLevel;
-use hyperlight_common::outb::{Exception, OutBAction};
+use hyperlight_common::outb::{ABI_VERSION, Exception, OutBAction};
 use tracing::{Span, instrument};
 
-use super::host_funcs::FunctionRegistry;
+use super::callback;
+use super::host_funcs::{FunctionRegistry, UnknownHostCall, default_return_value};
+use super::host_stream;
 #[cfg(feature = "mem_profile")]
 use crate::hypervisor::regs::CommonRegisters;
 use crate::mem::mgr::SandboxMemoryManager;
@@ -34,6 +39,13 @@ use crate::sandbox::trace::MemTraceInfo;
 /// Errors that can occur when handling an outb operation from the guest.
 #[derive(Debug, thiserror::Error)]
 pub enum HandleOutbError {
+    #[error("Guest was built for ABI version {guest}, but the host uses version {host}")]
+    AbiMismatch {
+        /// The ABI version of the host
+        host: u32,
+        /// The ABI version the guest reported
+        guest: u32,
+    },
     #[error("Guest aborted: error code {code}, message: {message}")]
     GuestAborted {
         /// The error code from the guest
@@ -41,8 +53,41 @@ pub enum HandleOutbError {
         /// The error message from the guest
         message: String,
     },
+    #[error("Guest allocation of {requested} bytes exceeded the per-call limit of {limit} bytes")]
+    GuestAllocationLimitExceeded {
+        /// The size of the allocation that exceeded the limit
+        requested: u64,
+        /// The configured per-call allocation limit
+        limit: u64,
+    },
+    #[error("Guest floating-point exception at {rip:#x} (MXCSR {mxcsr:#x})")]
+    GuestFpException {
+        /// The guest's MXCSR register when the exception trapped
+        mxcsr: u32,
+        /// The address of the instruction that raised the exception
+        rip: u64,
+    },
+    #[error("Guest assertion failed: `{expression}` at {file}:{line}")]
+    GuestAssertionFailed {
+        /// The source text of the failed expression
+        expression: String,
+        /// The guest source file containing the assertion
+        file: String,
+        /// The line number of the assertion
+        line: u32,
+    },
+    #[error("Guest function call exceeded the limit of {limit} host function calls")]
+    HostCallLimitExceeded {
+        /// The configured per-call host call limit
+        limit: u64,
+    },
     #[error("Invalid outb port: {0}")]
     InvalidPort(String),
+    #[error("Guest executed the page at {address:#x} after writing to it")]
+    SelfModifyingCode {
+        /// The guest address that was executed
+        address: u64,
+    },
     #[error("Failed to read guest log data: {0}")]
     ReadLogData(String),
     #[error("Failed to read host function call: {0}")]
@@ -71,7 +116,9 @@ pub(super) fn outb_log(
     // We match on the level at runtime because tracing macros determine their
     // level at compile time. Guest file/line/module are passed as structured
     // fields (rather than tracing metadata) because they originate from the
-    // guest, not from this call site.
+    // guest, not from this call site. For the same reason the guest's own log
+    // target (e.g. the `target` passed to `hl_log_target` by C guests) is
+    // surfaced as `guest_target`: tracing targets must be static.
     //
     // Consumers using a `log` logger (without a tracing subscriber) still
     // receive these events thanks to the `tracing` crate's `log` feature,
@@ -80,6 +127,7 @@ pub(super) fn outb_log(
     let source_file = log_data.source_file.as_str();
     let line = log_data.line;
     let source = log_data.source.as_str();
+    let caller = log_data.caller.as_str();
     let message = log_data.message.as_str();
 
     match &log_data.level {
@@ -89,6 +137,7 @@ pub(super) fn outb_log(
                 guest_file = source_file,
                 guest_line = line,
                 guest_module = source,
+                guest_target = caller,
                 "{}",
                 message
             );
@@ -99,6 +148,7 @@ pub(super) fn outb_log(
                 guest_file = source_file,
                 guest_line = line,
                 guest_module = source,
+                guest_target = caller,
                 "{}",
                 message
             );
@@ -109,6 +159,7 @@ pub(super) fn outb_log(
                 guest_file = source_file,
                 guest_line = line,
                 guest_module = source,
+                guest_target = caller,
                 "{}",
                 message
             );
@@ -119,6 +170,7 @@ pub(super) fn outb_log(
                 guest_file = source_file,
                 guest_line = line,
                 guest_module = source,
+                guest_target = caller,
                 "{}",
                 message
             );
@@ -129,6 +181,7 @@ pub(super) fn outb_log(
                 guest_file = source_file,
                 guest_line = line,
                 guest_module = source,
+                guest_target = caller,
                 "{}",
                 message
             );
@@ -154,7 +207,21 @@ fn outb_abort(
         if b == ABORT_TERMINATOR {
             let guest_error_code = *buffer.first().unwrap_or(&0);
 
-            let result = {
+            let result = if guest_error_code == ErrorCode::GuestAssertionFailed as u8 {
+                Err(parse_assertion_failure(buffer.get(1..).unwrap_or_default()))
+            } else if guest_error_code == ErrorCode::GuestAllocationLimitExceeded as u8 {
+                Err(parse_allocation_limit_exceeded(
+                    buffer.get(1..).unwrap_or_default(),
+                ))
+            } else if guest_error_code == ErrorCode::GuestError as u8
+                && buffer.get(1) == Some(&(Exception::SIMDFloatingPointException as u8))
+            {
+                Err(parse_fp_exception(buffer.get(2..).unwrap_or_default()))
+            } else if guest_error_code == ErrorCode::SelfModifyingCode as u8 {
+                Err(parse_self_modifying_code(
+                    buffer.get(1..).unwrap_or_default(),
+                ))
+            } else {
                 let message = if let Some(&maybe_exception_code) = buffer.get(1) {
                     match Exception::try_from(maybe_exception_code) {
                         Ok(exception) => {
@@ -190,6 +257,152 @@ fn outb_abort(
     Ok(())
 }
 
+/// Parses the payload of an assertion failure abort, which the guest sends
+/// as `expression\0file\0line` with the line number in decimal.
+fn parse_assertion_failure(payload: &[u8]) -> HandleOutbError {
+    let mut parts = payload.splitn(3, |&b| b == 0);
+    let expression = String::from_utf8_lossy(parts.next().unwrap_or_default()).into_owned();
+    let file = String::from_utf8_lossy(parts.next().unwrap_or_default()).into_owned();
+    let line = std::str::from_utf8(parts.next().unwrap_or_default())
+        .ok()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+
+    HandleOutbError::GuestAssertionFailed {
+        expression,
+        file,
+        line,
+    }
+}
+
+/// Reads the host function call made by the guest, makes it with `call`
+/// and writes the result back for the guest.
+///
+/// `call` is given `mem_mgr` back so that the host function can make
+/// reentrant guest calls, see [`crate::sandbox::reentrant`], along with the
+/// type of the value the guest expects back.
+pub(crate) fn handle_host_function_call(
+    mem_mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+    call: impl FnOnce(
+        &mut SandboxMemoryManager<HostSharedMemory>,
+        &str,
+        Vec<ParameterValue>,
+        ReturnType,
+    ) -> Result<crate::Result<ReturnValue>, HandleOutbError>,
+) -> Result<(), HandleOutbError> {
+    let function_call = mem_mgr
+        .get_host_function_call()
+        .map_err(|e| HandleOutbError::ReadHostFunctionCall(e.to_string()))?;
+    let args: Vec<ParameterValue> = function_call.parameters.unwrap_or(vec![]);
+    let res = call(
+        mem_mgr,
+        &function_call.function_name,
+        args,
+        function_call.expected_return_type,
+    )?
+    .map_err(|e| GuestError::new(ErrorCode::HostFunctionError, e.to_string()));
+
+    let func_result = FunctionCallResult::new(res);
+
+    mem_mgr
+        .write_response_from_host_function_call(&func_result)
+        .map_err(|e| HandleOutbError::WriteHostFunctionResponse(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Calls the host function `name`, for which the guest expects a value of
+/// `return_type`.
+///
+/// The registry is only locked while the function is looked up, so the
+/// function itself can make a reentrant guest call which in turn calls
+/// host functions.
+pub(crate) fn call_host_function(
+    host_funcs: &Arc<Mutex<FunctionRegistry>>,
+    name: &str,
+    args: Vec<ParameterValue>,
+    return_type: ReturnType,
+) -> Result<crate::Result<ReturnValue>, HandleOutbError> {
+    let registry = host_funcs
+        .try_lock()
+        .map_err(|e| HandleOutbError::LockFailed(file!(), line!(), e.to_string()))?;
+    let Some(function) = registry.host_function(name) else {
+        if name == INVOKE_HOST_CALLBACK {
+            // The callback may call host functions itself
+            drop(registry);
+            return Ok(crate::metrics::maybe_time_and_emit_host_call(name, || {
+                callback::invoke(args)
+            }));
+        }
+        match registry.unknown_host_call(name) {
+            Some(UnknownHostCall::ReturnDefault) => {
+                tracing::warn!(
+                    "Guest called unregistered host function {}, returning a default {:?}",
+                    name,
+                    return_type
+                );
+                return Ok(Ok(default_return_value(return_type)));
+            }
+            Some(UnknownHostCall::Handler(handler)) => {
+                // The handler may call host functions itself
+                drop(registry);
+                return Ok(crate::metrics::maybe_time_and_emit_host_call(name, || {
+                    handler(name, args, return_type)
+                }));
+            }
+            None => {}
+        }
+        // Built-in functions and missing functions are handled by the registry
+        return Ok(registry.call_host_function(name, args));
+    };
+    drop(registry);
+
+    Ok(crate::metrics::maybe_time_and_emit_host_call(name, || {
+        function.call(args)
+    }))
+}
+
+/// Parses the payload of an allocation limit abort, which the guest sends
+/// as `requested\0limit` with both sizes in decimal.
+fn parse_allocation_limit_exceeded(payload: &[u8]) -> HandleOutbError {
+    let mut parts = payload.splitn(2, |&b| b == 0).map(|part| {
+        std::str::from_utf8(part)
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0)
+    });
+    HandleOutbError::GuestAllocationLimitExceeded {
+        requested: parts.next().unwrap_or(0),
+        limit: parts.next().unwrap_or(0),
+    }
+}
+
+/// Parses the payload of a SIMD floating-point exception abort, which the
+/// guest sends as `mxcsr\0rip` in decimal.
+fn parse_fp_exception(payload: &[u8]) -> HandleOutbError {
+    let mut parts = payload.splitn(2, |&b| b == 0).map(|part| {
+        std::str::from_utf8(part)
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0)
+    });
+    HandleOutbError::GuestFpException {
+        mxcsr: parts.next().unwrap_or(0) as u32,
+        rip: parts.next().unwrap_or(0),
+    }
+}
+
+/// Parses the payload of a self-modifying code abort, which the guest sends
+/// as the executed address in decimal.
+fn parse_self_modifying_code(payload: &[u8]) -> HandleOutbError {
+    HandleOutbError::SelfModifyingCode {
+        address: std::str::from_utf8(payload)
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(0),
+    }
+}
+
 /// Handles OutB operations from the guest.
 #[instrument(err(Debug), skip_all, parent = Span::current(), level= "Trace")]
 pub(crate) fn handle_outb(
@@ -206,24 +419,12 @@ pub(crate) fn handle_outb(
     {
         OutBAction::Log => outb_log(mem_mgr),
         OutBAction::CallFunction => {
-            let call = mem_mgr
-                .get_host_function_call()
-                .map_err(|e| HandleOutbError::ReadHostFunctionCall(e.to_string()))?;
-            let name = call.function_name.clone();
-            let args: Vec<ParameterValue> = call.parameters.unwrap_or(vec![]);
-            let res = host_funcs
-                .try_lock()
-                .map_err(|e| HandleOutbError::LockFailed(file!(), line!(), e.to_string()))?
-                .call_host_function(&name, args)
-                .map_err(|e| GuestError::new(ErrorCode::HostFunctionError, e.to_string()));
-
-            let func_result = FunctionCallResult::new(res);
-
-            mem_mgr
-                .write_response_from_host_function_call(&func_result)
-                .map_err(|e| HandleOutbError::WriteHostFunctionResponse(e.to_string()))?;
-
-            Ok(())
+            handle_host_function_call(mem_mgr, |mem_mgr, name, args, return_type| {
+                if let Some(res) = host_stream::call_builtin(host_funcs, mem_mgr, name, &args)? {
+                    return Ok(res);
+                }
+                call_host_function(host_funcs, name, args, return_type)
+            })
         }
         OutBAction::Abort => outb_abort(mem_mgr, data),
         OutBAction::DebugPrint => {
@@ -243,6 +444,15 @@ pub(crate) fn handle_outb(
         OutBAction::TraceMemoryAlloc => trace_info.handle_trace_mem_alloc(regs, mem_mgr),
         #[cfg(feature = "mem_profile")]
         OutBAction::TraceMemoryFree => trace_info.handle_trace_mem_free(regs, mem_mgr),
+        OutBAction::AbiVersion => {
+            if data != ABI_VERSION {
+                return Err(HandleOutbError::AbiMismatch {
+                    host: ABI_VERSION,
+                    guest: data,
+                });
+            }
+            Ok(())
+        }
     }
 }
 #[cfg(test)]
@@ -484,6 +694,7 @@ mod tests {
                         test_value_as_str(metadata_values_map, "level", expected_level);
                         test_value_as_str(event_values_map, "guest_file", "test source file");
                         test_value_as_str(event_values_map, "guest_module", "test source");
+                        test_value_as_str(event_values_map, "guest_target", "test caller");
                         test_value_as_str(metadata_values_map, "target", "hyperlight_guest");
                         count_matching_events += 1;
                     }
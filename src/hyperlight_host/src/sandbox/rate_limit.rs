@@ -0,0 +1,294 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Limiting the rate of guest calls to a sandbox, see
+//! [`MultiUseSandbox::with_call_rate_limit`].
+
+use std::time::{Duration, Instant};
+
+use crate::{HyperlightError, MultiUseSandbox, Result, new_error};
+
+/// What a sandbox does with a guest call that exceeds its rate limit, see
+/// [`MultiUseSandbox::with_call_rate_limit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Wait until the call is allowed, then make it
+    Block,
+    /// Fail the call with [`HyperlightError::RateLimited`]
+    Reject,
+}
+
+/// A token bucket that allows `per_sec` calls a second on average, and up
+/// to `burst` calls at once.
+#[derive(Debug)]
+pub(crate) struct CallRateLimiter {
+    per_sec: f64,
+    burst: f64,
+    mode: RateLimitMode,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl CallRateLimiter {
+    fn new(per_sec: f64, burst: u32, mode: RateLimitMode) -> Result<Self> {
+        if !per_sec.is_finite() || per_sec <= 0.0 {
+            return Err(new_error!(
+                "The call rate limit must be a positive number of calls per second, not {}",
+                per_sec
+            ));
+        }
+        if burst == 0 {
+            return Err(new_error!(
+                "The call rate limit must allow bursts of 1 call or more"
+            ));
+        }
+        Ok(Self {
+            per_sec,
+            burst: burst.into(),
+            mode,
+            // A sandbox starts with a full bucket
+            tokens: burst.into(),
+            refilled: Instant::now(),
+        })
+    }
+
+    /// Add the tokens earned since the bucket was last refilled.
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned = now.duration_since(self.refilled).as_secs_f64() * self.per_sec;
+        self.tokens = (self.tokens + earned).min(self.burst);
+        self.refilled = now;
+    }
+
+    /// Take a token for a call, waiting for one or failing with
+    /// [`HyperlightError::RateLimited`] if there is none, depending on the
+    /// mode.
+    pub(crate) fn acquire(&mut self) -> Result<()> {
+        self.refill();
+        if self.tokens < 1.0 {
+            let retry_after = Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec);
+            match self.mode {
+                RateLimitMode::Reject => return Err(HyperlightError::RateLimited { retry_after }),
+                RateLimitMode::Block => {
+                    std::thread::sleep(retry_after);
+                    self.refill();
+                }
+            }
+        }
+        // After blocking, rounding can leave the bucket a hair short of a
+        // token, which the next call makes up for
+        self.tokens -= 1.0;
+        Ok(())
+    }
+
+    fn tokens(&mut self) -> f64 {
+        self.refill();
+        self.tokens.max(0.0)
+    }
+}
+
+impl MultiUseSandbox {
+    /// Limits the rate of guest calls to this sandbox to `per_sec` a second
+    /// on average, allowing bursts of up to `burst` calls, for example to
+    /// stop a tenant from monopolising a host.
+    ///
+    /// The limit is a token bucket that holds up to `burst` tokens and
+    /// gains `per_sec` tokens a second. Each call that runs the guest's
+    /// own code takes a token: the calling methods, such as
+    /// [`call`](Self::call), and also resuming a continuation with
+    /// [`resume`](Self::resume) and pulling a stream's next chunk with
+    /// [`next_chunk`](Self::next_chunk). Only the sandbox's housekeeping
+    /// calls to built-in guest functions do not: quiescing the guest
+    /// before a snapshot, and reading its metadata with
+    /// [`guest_features`](Self::guest_features), the function table,
+    /// the call schema or the allocator stats. A call made when
+    /// the bucket is empty waits for the next token if
+    /// `mode` is [`RateLimitMode::Block`], and fails with
+    /// [`HyperlightError::RateLimited`], saying when the next token will
+    /// be available, if it is [`RateLimitMode::Reject`]. A rejected call
+    /// does not run the guest, so it does not poison the sandbox. The
+    /// bucket starts full, and setting a new limit replaces the old one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{HyperlightError, MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::rate_limit::RateLimitMode;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?
+    /// .with_call_rate_limit(10.0, 5, RateLimitMode::Reject)?;
+    ///
+    /// match sandbox.call::<String>("Echo", "hello".to_string()) {
+    ///     Err(HyperlightError::RateLimited { retry_after }) => {
+    ///         println!("too many calls, retry in {retry_after:?}")
+    ///     }
+    ///     res => println!("{}", res?),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_call_rate_limit(
+        mut self,
+        per_sec: f64,
+        burst: u32,
+        mode: RateLimitMode,
+    ) -> Result<Self> {
+        self.rate_limiter = Some(CallRateLimiter::new(per_sec, burst, mode)?);
+        Ok(self)
+    }
+
+    /// Removes the limit set with
+    /// [`with_call_rate_limit`](Self::with_call_rate_limit).
+    pub fn clear_call_rate_limit(&mut self) {
+        self.rate_limiter = None;
+    }
+
+    /// The number of calls that can be made right away without exceeding
+    /// the limit set with
+    /// [`with_call_rate_limit`](Self::with_call_rate_limit), including
+    /// the fraction of the next one earned so far, or `None` if the
+    /// sandbox has no limit.
+    pub fn call_rate_limit_tokens(&mut self) -> Option<f64> {
+        self.rate_limiter.as_mut().map(CallRateLimiter::tokens)
+    }
+
+    /// Runs `f`, which calls built-in guest functions on the sandbox's own
+    /// behalf, without those calls taking tokens from the rate limit.
+    pub(crate) fn without_call_rate_limit<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let rate_limiter = self.rate_limiter.take();
+        let res = f(self);
+        self.rate_limiter = rate_limiter;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimitMode;
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn reject() {
+        let mut sandbox = new_simple_guest_sandbox(None)
+            .with_call_rate_limit(1.0, 2, RateLimitMode::Reject)
+            .unwrap();
+        assert!(sandbox.call_rate_limit_tokens().unwrap() >= 2.0);
+        for _ in 0..2 {
+            let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+            assert_eq!(echoed, "hello");
+        }
+        let res = sandbox.call::<String>("Echo", "hello".to_string());
+        match res {
+            Err(HyperlightError::RateLimited { retry_after }) => {
+                assert!(retry_after <= Duration::from_secs(1), "{retry_after:?}")
+            }
+            res => panic!("Expected RateLimited, got {res:?}"),
+        }
+        assert!(!sandbox.poisoned());
+        assert!(sandbox.call_rate_limit_tokens().unwrap() < 1.0);
+
+        sandbox.clear_call_rate_limit();
+        assert!(sandbox.call_rate_limit_tokens().is_none());
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+    }
+
+    #[test]
+    fn block() {
+        let mut sandbox = new_simple_guest_sandbox(None)
+            .with_call_rate_limit(20.0, 1, RateLimitMode::Block)
+            .unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+            assert_eq!(echoed, "hello");
+        }
+        // The first call is made right away, and the others wait for a
+        // token each
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn built_in_calls_are_not_limited() {
+        let mut sandbox = new_simple_guest_sandbox(None)
+            .with_call_rate_limit(0.1, 1, RateLimitMode::Reject)
+            .unwrap();
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+        assert!(sandbox.call_rate_limit_tokens().unwrap() < 1.0);
+
+        sandbox.guest_features().unwrap();
+        sandbox.dump_guest_function_table().unwrap();
+        sandbox.snapshot().unwrap();
+        assert!(sandbox.call_rate_limit_tokens().unwrap() < 1.0);
+
+        // The limiter is still in place for the user's calls
+        assert!(matches!(
+            sandbox.call::<String>("Echo", "hello".to_string()),
+            Err(HyperlightError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn streams_and_continuations_are_limited() {
+        let mut sandbox = new_simple_guest_sandbox(None)
+            .with_call_rate_limit(0.1, 1, RateLimitMode::Reject)
+            .unwrap();
+        let stream = sandbox
+            .call_stream("StreamChunks", (b"abc".to_vec(), 1i32))
+            .unwrap();
+        assert!(matches!(
+            sandbox.next_chunk(&stream),
+            Err(HyperlightError::RateLimited { .. })
+        ));
+        assert!(!sandbox.poisoned());
+
+        let mut sandbox = new_simple_guest_sandbox(None)
+            .with_call_rate_limit(0.1, 1, RateLimitMode::Reject)
+            .unwrap();
+        let token = sandbox.call_suspending("AwaitConcat", 2i32).unwrap();
+        assert!(matches!(
+            sandbox.resume(token, b"a".to_vec()),
+            Err(HyperlightError::RateLimited { .. })
+        ));
+        assert!(!sandbox.poisoned());
+    }
+
+    #[test]
+    fn invalid_limits() {
+        assert!(
+            new_simple_guest_sandbox(None)
+                .with_call_rate_limit(0.0, 1, RateLimitMode::Block)
+                .is_err()
+        );
+        assert!(
+            new_simple_guest_sandbox(None)
+                .with_call_rate_limit(f64::NAN, 1, RateLimitMode::Block)
+                .is_err()
+        );
+        assert!(
+            new_simple_guest_sandbox(None)
+                .with_call_rate_limit(1.0, 0, RateLimitMode::Block)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Waiting for a guest to signal that it is ready for calls, see
+//! [`MultiUseSandbox::wait_until_ready`].
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use tracing::{Span, instrument};
+
+use crate::{HyperlightError, MultiUseSandbox, Result, new_error};
+
+/// Whether a guest has signalled that it is ready for calls, which can be
+/// waited for from any thread.
+///
+/// A guest signals that it is ready when it has finished the setup it
+/// does after it was initialised, which a Rust guest does with
+/// `hyperlight_guest_bin::host_comm::signal_ready` and a C guest with
+/// `hl_signal_ready`. Clones share the same state. Get one with
+/// [`MultiUseSandbox::readiness`].
+#[derive(Clone, Debug, Default)]
+pub struct Readiness {
+    ready: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl Readiness {
+    /// Record that the guest is ready, waking everything waiting for it.
+    pub(crate) fn signal(&self) -> Result<()> {
+        let (ready, signalled) = &*self.ready;
+        *ready
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))? = true;
+        signalled.notify_all();
+        Ok(())
+    }
+
+    /// Whether the guest has signalled that it is ready.
+    pub fn is_ready(&self) -> bool {
+        self.ready.0.lock().map(|ready| *ready).unwrap_or(false)
+    }
+
+    /// Wait up to `timeout` for the guest to signal that it is ready,
+    /// returning right away if it already has.
+    ///
+    /// Returns [`HyperlightError::SandboxNotReady`] if it has not when
+    /// `timeout` expires.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn wait_until_ready(&self, timeout: Duration) -> Result<()> {
+        let (ready, signalled) = &*self.ready;
+        let ready = ready
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        let (ready, _) = signalled
+            .wait_timeout_while(ready, timeout, |ready| !*ready)
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?;
+        if *ready {
+            Ok(())
+        } else {
+            Err(HyperlightError::SandboxNotReady(timeout))
+        }
+    }
+}
+
+impl MultiUseSandbox {
+    /// Whether the guest has signalled that it is ready for calls, see
+    /// [`Readiness`].
+    pub fn is_ready(&self) -> bool {
+        self.readiness.is_ready()
+    }
+
+    /// A handle for checking and waiting for the guest's readiness from
+    /// other threads, for example while this sandbox runs the guest's
+    /// warm-up on a worker thread.
+    pub fn readiness(&self) -> Readiness {
+        self.readiness.clone()
+    }
+
+    /// Waits up to `timeout` for the guest to signal that it is ready for
+    /// calls, returning right away if it already has.
+    ///
+    /// This is distinct from the guest's initialisation, which has always
+    /// completed by the time a [`MultiUseSandbox`] exists. It covers setup
+    /// that a guest does afterwards, such as warming caches in the guest
+    /// calls that follow its initialisation, so that callers do not race
+    /// it. The guest only runs during guest calls, so a signal that has not
+    /// been given yet can only come from a call on another thread. Wait for
+    /// it there with the handle returned by [`readiness`](Self::readiness).
+    ///
+    /// Readiness is host state: once the guest has signalled it, restoring
+    /// the sandbox to an earlier snapshot does not undo it.
+    ///
+    /// Returns [`HyperlightError::SandboxNotReady`] if the guest has not
+    /// signalled that it is ready when `timeout` expires.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// // The guest signals that it is ready part way through warming up
+    /// let readiness = sandbox.readiness();
+    /// let warm_up = std::thread::spawn(move || {
+    ///     sandbox.call::<()>("WarmUp", ())?;
+    ///     Ok::<_, hyperlight_host::HyperlightError>(sandbox)
+    /// });
+    /// readiness.wait_until_ready(Duration::from_secs(5))?;
+    /// // ... tell clients that the sandbox is ready ...
+    /// let sandbox = warm_up.join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn wait_until_ready(&self, timeout: Duration) -> Result<()> {
+        self.readiness.wait_until_ready(timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn wait_until_ready() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        assert!(!sandbox.is_ready());
+        assert!(matches!(
+            sandbox.wait_until_ready(Duration::from_millis(10)),
+            Err(HyperlightError::SandboxNotReady(_))
+        ));
+
+        let readiness = sandbox.readiness();
+        let waiter =
+            std::thread::spawn(move || readiness.wait_until_ready(Duration::from_secs(10)));
+        sandbox.call::<()>("SignalReady", ()).unwrap();
+        waiter.join().unwrap().unwrap();
+        assert!(sandbox.is_ready());
+        sandbox.wait_until_ready(Duration::ZERO).unwrap();
+    }
+}
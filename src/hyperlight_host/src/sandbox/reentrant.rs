@@ -0,0 +1,257 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Reentrant guest calls made from host functions.
+//!
+//! While a guest is blocked in a call to a host function, the
+//! [`MultiUseSandbox`](crate::MultiUseSandbox) that is running it is
+//! mutably borrowed, so the host function cannot call back into the
+//! guest through it. Instead, the host function uses [`call_guest`],
+//! which makes a nested call on the sandbox whose guest called it:
+//!
+//! ```no_run
+//! # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+//! # use hyperlight_host::func::Registerable;
+//! # use hyperlight_host::sandbox::reentrant;
+//! # fn example() -> Result<()> {
+//! let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?;
+//! sandbox.register_host_function("Transform", |input: String| -> Result<i32> {
+//!     // Ask the guest that called us to normalise the input first
+//!     let normalised: String = reentrant::call_guest("Normalise", input)?;
+//!     Ok(normalised.len() as i32)
+//! })?;
+//! let mut sandbox = sandbox.evolve()?;
+//! let len: i32 = sandbox.call("Process", "input".to_string())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # State model
+//!
+//! A nested call runs on the guest's stack, below the frame of the host
+//! function call it interrupted, and shares all other guest state with the
+//! outer call. When it returns, the vCPU state of the outer call is restored
+//! and the outer call continues as if the host function had returned
+//! normally.
+//!
+//! - A nested call that fails with a guest error is returned to the host
+//!   function, and the outer call is unaffected.
+//! - A nested call that does not run to completion (for example because the
+//!   guest aborted or accessed invalid memory) also fails the outer call,
+//!   and the sandbox is poisoned as it would be for any other call.
+//! - Cancelling the sandbox with
+//!   [`InterruptHandle::kill`](crate::hypervisor::InterruptHandle::kill)
+//!   cancels the nested call and every call it is nested in.
+//! - Nested calls do not take or invalidate snapshots, are not recorded in
+//!   the sandbox's latency histograms, and are not available while the
+//!   guest is initialising.
+//!
+//! # Depth limits
+//!
+//! Each nested call uses guest stack in addition to the stack already used
+//! by the calls it is nested in, so deeply nested calls can overflow the
+//! guest stack, which poisons the sandbox. Independently of the guest stack
+//! size, at most [`MAX_REENTRANT_DEPTH`] nested calls can be in progress on
+//! a thread at once; further calls fail with
+//! [`ReentrantCallDepthExceeded`](HyperlightError::ReentrantCallDepthExceeded).
+
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::metrics::maybe_time_and_emit_guest_call;
+use crate::{HyperlightError, Result};
+
+/// The maximum number of reentrant guest calls that can be in progress on a
+/// thread at once.
+pub const MAX_REENTRANT_DEPTH: usize = 16;
+
+/// Makes nested guest calls on, and writes to the memory of, the sandbox
+/// whose guest is calling a host function on this thread.
+pub(crate) trait ReentrantCall {
+    fn call(
+        &mut self,
+        function_name: &str,
+        return_type: ReturnType,
+        args: Vec<ParameterValue>,
+    ) -> Result<ReturnValue>;
+
+    /// Write `data` to the guest's memory at the guest virtual address
+    /// `gva`, see [`GuestMemoryAccessor`](crate::sandbox::guest_memory::GuestMemoryAccessor).
+    #[cfg(target_arch = "x86_64")]
+    fn write_guest_memory(&mut self, gva: u64, data: &[u8]) -> Result<()>;
+}
+
+thread_local! {
+    /// The innermost host function call in progress on this thread, or
+    /// `None` if there is none or a nested call is already using it.
+    static CURRENT: Cell<Option<NonNull<dyn ReentrantCall>>> = const { Cell::new(None) };
+    /// The number of nested guest calls in progress on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Restores the previous value of a thread local when dropped, so that it
+/// is restored even if a host function panics.
+struct Restore<T: Copy + 'static> {
+    key: &'static std::thread::LocalKey<Cell<T>>,
+    previous: T,
+}
+
+impl<T: Copy + 'static> Drop for Restore<T> {
+    fn drop(&mut self) {
+        self.key.set(self.previous);
+    }
+}
+
+/// Runs the host function `f`, allowing it to make nested guest calls
+/// through `scope`.
+pub(crate) fn enter<R>(scope: &mut dyn ReentrantCall, f: impl FnOnce() -> R) -> R {
+    // SAFETY: this only erases the lifetime of `scope`. The pointer is
+    // removed from `CURRENT` before this function returns, and `scope`
+    // is exclusively borrowed until then.
+    let scope = unsafe {
+        std::mem::transmute::<NonNull<dyn ReentrantCall + '_>, NonNull<dyn ReentrantCall>>(
+            NonNull::from(scope),
+        )
+    };
+    let _restore = Restore {
+        key: &CURRENT,
+        previous: CURRENT.replace(Some(scope)),
+    };
+    f()
+}
+
+/// Calls the guest function `func_name` on the sandbox whose guest called
+/// the host function that is currently running on this thread.
+///
+/// See the [module documentation](self) for the state model and limits of
+/// nested calls.
+///
+/// Returns [`ReentrantCallUnavailable`](HyperlightError::ReentrantCallUnavailable)
+/// if no host function called by a guest is running on this thread.
+pub fn call_guest<Output: SupportedReturnType>(
+    func_name: &str,
+    args: impl ParameterTuple,
+) -> Result<Output> {
+    let depth = DEPTH.get();
+    if depth >= MAX_REENTRANT_DEPTH {
+        return Err(HyperlightError::ReentrantCallDepthExceeded(
+            MAX_REENTRANT_DEPTH,
+        ));
+    }
+    // Take the scope so that it cannot be used again until this call
+    // returns; a host function called by the nested call gets its own.
+    let Some(mut scope) = CURRENT.take() else {
+        return Err(HyperlightError::ReentrantCallUnavailable);
+    };
+    let _restore_scope = Restore {
+        key: &CURRENT,
+        previous: Some(scope),
+    };
+    DEPTH.set(depth + 1);
+    let _restore_depth = Restore {
+        key: &DEPTH,
+        previous: depth,
+    };
+
+    maybe_time_and_emit_guest_call(func_name, || {
+        // SAFETY: `scope` was installed by `enter`, which has not returned
+        // since it is running the host function that made this call, and
+        // it was taken out of `CURRENT` above so this is the only reference.
+        let ret = unsafe { scope.as_mut() }.call(func_name, Output::TYPE, args.into_value())?;
+        Ok(Output::from_value(ret)?)
+    })
+}
+
+/// Writes `data` to the memory of the sandbox whose guest called the host
+/// function that is currently running on this thread.
+///
+/// Fails if no host function called by a guest is running on this thread,
+/// or while it is making a nested call.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn write_guest_memory(gva: u64, data: &[u8]) -> Result<()> {
+    let Some(mut scope) = CURRENT.take() else {
+        return Err(crate::new_error!(
+            "Guest memory can only be written by a host function while the guest is calling it"
+        ));
+    };
+    let _restore_scope = Restore {
+        key: &CURRENT,
+        previous: Some(scope),
+    };
+    // SAFETY: as for `call_guest`, `scope` was installed by `enter`, which
+    // has not returned, and was taken out of `CURRENT` above.
+    unsafe { scope.as_mut() }.write_guest_memory(gva, data)
+}
+
+/// Returns the number of reentrant guest calls in progress on this thread.
+pub fn depth() -> usize {
+    DEPTH.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl ReentrantCall for Echo {
+        fn call(
+            &mut self,
+            _function_name: &str,
+            _return_type: ReturnType,
+            mut args: Vec<ParameterValue>,
+        ) -> Result<ReturnValue> {
+            match args.pop() {
+                Some(ParameterValue::Int(i)) => Ok(ReturnValue::Int(i + depth() as i32)),
+                _ => Err(crate::new_error!("unexpected arguments")),
+            }
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        fn write_guest_memory(&mut self, _gva: u64, _data: &[u8]) -> Result<()> {
+            Err(crate::new_error!("no guest memory"))
+        }
+    }
+
+    #[test]
+    fn unavailable_outside_host_function() {
+        let res = call_guest::<i32>("Echo", (1,));
+        assert!(matches!(
+            res,
+            Err(HyperlightError::ReentrantCallUnavailable)
+        ));
+        assert_eq!(depth(), 0);
+    }
+
+    #[test]
+    fn calls_current_scope() {
+        let res = enter(&mut Echo, || call_guest::<i32>("Echo", (1,)));
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(depth(), 0);
+
+        // The scope is removed once the host function returns
+        let res = call_guest::<i32>("Echo", (1,));
+        assert!(matches!(
+            res,
+            Err(HyperlightError::ReentrantCallUnavailable)
+        ));
+    }
+}
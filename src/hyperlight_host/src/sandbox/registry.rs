@@ -0,0 +1,330 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Tracking live sandboxes so that they can be shut down together, see
+//! [`SandboxRegistry`].
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant};
+
+use tracing::{Span, instrument};
+
+use crate::hypervisor::InterruptHandle;
+use crate::{HyperlightError, MultiUseSandbox, Result, new_error};
+
+#[derive(Default)]
+struct Sandboxes {
+    interrupt_handles: BTreeMap<u64, Arc<dyn InterruptHandle>>,
+    /// The id of the last sandbox registered
+    last_id: u64,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    sandboxes: Mutex<Sandboxes>,
+    /// Notified whenever a sandbox leaves the registry
+    removed: Condvar,
+    shut_down: AtomicBool,
+}
+
+impl RegistryState {
+    fn lock(&self) -> Result<MutexGuard<'_, Sandboxes>> {
+        self.sandboxes
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))
+    }
+}
+
+/// Tracks the live sandboxes that have joined it with
+/// [`MultiUseSandbox::join_registry`], so that a host can interrupt them
+/// all, for example to shut down gracefully.
+///
+/// The registry only holds the [`InterruptHandle`] of each sandbox, never
+/// the sandbox itself, so it never keeps a sandbox alive. A sandbox leaves
+/// the registry when it is dropped.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use hyperlight_host::{GuestBinary, UninitializedSandbox, Result};
+/// # use hyperlight_host::sandbox::SandboxRegistry;
+/// # fn example() -> Result<()> {
+/// let registry = SandboxRegistry::new();
+///
+/// let mut sandbox = UninitializedSandbox::new(GuestBinary::FilePath("guest".into()), None)?
+///     .evolve()?;
+/// sandbox.join_registry(&registry)?;
+/// // ... hand the sandbox to a worker thread ...
+///
+/// // On shutdown, cancel all calls and wait for the workers to drop their
+/// // sandboxes
+/// let remaining = registry.shutdown_all(Duration::from_secs(5))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct SandboxRegistry {
+    state: Arc<RegistryState>,
+}
+
+/// A sandbox in a [`SandboxRegistry`], as seen by
+/// [`SandboxRegistry::for_each`].
+#[derive(Clone)]
+pub struct RegisteredSandbox {
+    id: u64,
+    interrupt_handle: Arc<dyn InterruptHandle>,
+}
+
+impl RegisteredSandbox {
+    /// The id the sandbox was given when it joined the registry.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The sandbox's interrupt handle, for example to cancel its current
+    /// call.
+    pub fn interrupt_handle(&self) -> &Arc<dyn InterruptHandle> {
+        &self.interrupt_handle
+    }
+}
+
+impl std::fmt::Debug for RegisteredSandbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisteredSandbox")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SandboxRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of sandboxes in the registry.
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.state.lock()?.interrupt_handles.len())
+    }
+
+    /// Whether the registry has no sandboxes.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Whether [`shutdown_all`](Self::shutdown_all) has been called.
+    pub fn is_shut_down(&self) -> bool {
+        self.state.shut_down.load(Ordering::Acquire)
+    }
+
+    /// Call `f` with each sandbox in the registry, in the order they joined.
+    ///
+    /// `f` sees the sandboxes that were in the registry when this was
+    /// called, and may use the registry itself.
+    pub fn for_each(&self, mut f: impl FnMut(&RegisteredSandbox)) -> Result<()> {
+        let sandboxes: Vec<_> = self
+            .state
+            .lock()?
+            .interrupt_handles
+            .iter()
+            .map(|(&id, handle)| RegisteredSandbox {
+                id,
+                interrupt_handle: handle.clone(),
+            })
+            .collect();
+        sandboxes.iter().for_each(&mut f);
+        Ok(())
+    }
+
+    /// Shut down every sandbox in the registry, and wait up to `timeout`
+    /// for them to be dropped.
+    ///
+    /// Any call in progress is cancelled as by [`InterruptHandle::kill`],
+    /// and every later call on a sandbox in the registry fails with
+    /// [`HyperlightError::SandboxRegistryShutDown`], as does joining the
+    /// registry. Since the registry does not own the sandboxes, it is up to
+    /// their owners to drop them once their calls fail.
+    ///
+    /// Returns the number of sandboxes still alive when `timeout` expired,
+    /// which is 0 if they were all dropped in time.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn shutdown_all(&self, timeout: Duration) -> Result<usize> {
+        // The flag is set before the calls are cancelled, so that a call
+        // that starts in between sees it.
+        self.state.shut_down.store(true, Ordering::Release);
+        self.for_each(|sandbox| {
+            sandbox.interrupt_handle.kill();
+        })?;
+
+        let deadline = Instant::now() + timeout;
+        let mut sandboxes = self.state.lock()?;
+        while !sandboxes.interrupt_handles.is_empty() {
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            sandboxes = self
+                .state
+                .removed
+                .wait_timeout(sandboxes, remaining)
+                .map_err(|e| new_error!("Error waiting at {}:{}: {}", file!(), line!(), e))?
+                .0;
+        }
+        Ok(sandboxes.interrupt_handles.len())
+    }
+}
+
+impl std::fmt::Debug for SandboxRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SandboxRegistry")
+            .field("shut_down", &self.is_shut_down())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A sandbox's membership of a [`SandboxRegistry`], which ends when it is
+/// dropped along with the sandbox.
+pub(crate) struct Registration {
+    state: Weak<RegistryState>,
+    id: u64,
+}
+
+impl Registration {
+    /// Whether the registry has been shut down. A registry that has been
+    /// dropped can no longer be shut down, so it never has been.
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.state
+            .upgrade()
+            .is_some_and(|state| state.shut_down.load(Ordering::Acquire))
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let Some(state) = self.state.upgrade() else {
+            return;
+        };
+        match state.sandboxes.lock() {
+            Ok(mut sandboxes) => {
+                sandboxes.interrupt_handles.remove(&self.id);
+            }
+            Err(e) => {
+                tracing::error!("Registration::drop: failed to lock the registry: {}", e);
+            }
+        }
+        state.removed.notify_all();
+    }
+}
+
+impl MultiUseSandbox {
+    /// Add this sandbox to `registry`, see [`SandboxRegistry`], and return
+    /// the id it was given there. The sandbox stays in the registry until
+    /// it is dropped.
+    ///
+    /// Fails if the sandbox is already in a registry, or with
+    /// [`HyperlightError::SandboxRegistryShutDown`] if `registry` has been
+    /// shut down.
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn join_registry(&mut self, registry: &SandboxRegistry) -> Result<u64> {
+        if self.registration.is_some() {
+            return Err(new_error!("The sandbox is already in a registry"));
+        }
+        let mut sandboxes = registry.state.lock()?;
+        // Checked under the lock, so that `shutdown_all` cannot miss a
+        // sandbox that joins while it runs
+        if registry.is_shut_down() {
+            return Err(HyperlightError::SandboxRegistryShutDown);
+        }
+        sandboxes.last_id += 1;
+        let id = sandboxes.last_id;
+        sandboxes
+            .interrupt_handles
+            .insert(id, self.interrupt_handle());
+        self.registration = Some(Registration {
+            state: Arc::downgrade(&registry.state),
+            id,
+        });
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::SandboxRegistry;
+    use crate::HyperlightError;
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn sandboxes_leave_when_dropped() {
+        let registry = SandboxRegistry::new();
+        let mut first = new_simple_guest_sandbox(None);
+        let mut second = new_simple_guest_sandbox(None);
+        let first_id = first.join_registry(&registry).unwrap();
+        let second_id = second.join_registry(&registry).unwrap();
+        assert!(first.join_registry(&registry).is_err());
+
+        let mut ids = Vec::new();
+        registry.for_each(|sandbox| ids.push(sandbox.id())).unwrap();
+        assert_eq!(ids, [first_id, second_id]);
+
+        drop(first);
+        assert_eq!(registry.len().unwrap(), 1);
+        drop(second);
+        assert!(registry.is_empty().unwrap());
+    }
+
+    #[test]
+    fn shutdown_all() {
+        let registry = SandboxRegistry::new();
+        let barrier = Arc::new(Barrier::new(2));
+
+        let mut sandbox = new_simple_guest_sandbox(None);
+        sandbox.join_registry(&registry).unwrap();
+        let worker = {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                // Runs until the registry cancels it
+                let res = sandbox.call::<()>("Spin", ());
+                assert!(
+                    matches!(res, Err(HyperlightError::ExecutionCanceledByHost())),
+                    "{res:?}"
+                );
+                // Later calls fail without entering the guest
+                let res = sandbox.call::<String>("Echo", "hi".to_string());
+                assert!(
+                    matches!(res, Err(HyperlightError::SandboxRegistryShutDown)),
+                    "{res:?}"
+                );
+            })
+        };
+
+        barrier.wait();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(registry.shutdown_all(Duration::from_secs(60)).unwrap(), 0);
+        worker.join().unwrap();
+
+        let mut sandbox = new_simple_guest_sandbox(None);
+        assert!(matches!(
+            sandbox.join_registry(&registry),
+            Err(HyperlightError::SandboxRegistryShutDown)
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Which of a sandbox's memory pages are resident in host RAM, see
+//! [`MultiUseSandbox::residency_stats`].
+
+use tracing::{Span, instrument};
+
+#[cfg(target_os = "linux")]
+use crate::mem::shared_mem::SharedMemory;
+use crate::{MultiUseSandbox, Result};
+
+/// How many of a sandbox's memory pages are resident in host RAM, see
+/// [`MultiUseSandbox::residency_stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResidencyStats {
+    /// The size of the host pages that are counted
+    pub page_size: usize,
+    /// The pages of the guest's snapshot memory, which holds its code,
+    /// heap and init data
+    pub snapshot_pages: usize,
+    /// The pages of the guest's snapshot memory that are resident
+    pub snapshot_resident_pages: usize,
+    /// The pages of the guest's writable (scratch) memory
+    pub scratch_pages: usize,
+    /// The pages of the guest's writable (scratch) memory that are
+    /// resident
+    pub scratch_resident_pages: usize,
+}
+
+impl ResidencyStats {
+    /// The pages of the guest's memory that are resident
+    pub fn resident_pages(&self) -> usize {
+        self.snapshot_resident_pages + self.scratch_resident_pages
+    }
+
+    /// The pages of the guest's memory that are not resident, because they
+    /// were never touched or the host reclaimed them
+    pub fn non_resident_pages(&self) -> usize {
+        self.snapshot_pages + self.scratch_pages - self.resident_pages()
+    }
+}
+
+impl MultiUseSandbox {
+    /// How many of the pages backing this sandbox's memory are currently
+    /// resident in host RAM, rather than never touched, swapped out or
+    /// reclaimed by the host.
+    ///
+    /// This is meant for monitoring memory pressure, for example to pick
+    /// sandboxes to evict or to decide whether prefaulting is worthwhile.
+    /// The counts are a snapshot that the guest and the host's memory
+    /// management change at any time. The snapshot memory may be shared
+    /// with other sandboxes created from the same snapshot, so its resident
+    /// pages are not necessarily used by this sandbox alone.
+    ///
+    /// Residency is queried with `mincore` on Linux. Elsewhere this returns
+    /// [`crate::HyperlightError::Unsupported`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let stats = sandbox.residency_stats()?;
+    /// println!(
+    ///     "{} KiB resident",
+    ///     stats.resident_pages() * stats.page_size / 1024
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn residency_stats(&self) -> Result<ResidencyStats> {
+        #[cfg(target_os = "linux")]
+        {
+            let page_size = page_size::get();
+            let snapshot = &self.mem_mgr.shared_mem;
+            let scratch = &self.mem_mgr.scratch_mem;
+            Ok(ResidencyStats {
+                page_size,
+                snapshot_pages: snapshot.mem_size().div_ceil(page_size),
+                snapshot_resident_pages: resident_pages(
+                    snapshot.base_ptr(),
+                    snapshot.mem_size(),
+                    page_size,
+                )?,
+                scratch_pages: scratch.mem_size().div_ceil(page_size),
+                scratch_resident_pages: resident_pages(
+                    scratch.base_ptr(),
+                    scratch.mem_size(),
+                    page_size,
+                )?,
+            })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(crate::HyperlightError::Unsupported {
+                feature: "page residency queries".to_string(),
+                backend: self.hypervisor_backend(),
+            })
+        }
+    }
+}
+
+/// The number of pages of the `size` bytes of host memory at `base` that
+/// are resident.
+#[cfg(target_os = "linux")]
+fn resident_pages(base: *mut u8, size: usize, page_size: usize) -> Result<usize> {
+    let mut residency = vec![0u8; size.div_ceil(page_size)];
+    // SAFETY: `base` is the page aligned start of a mapping of at least
+    // `size` bytes, and `residency` has a byte for each of its pages
+    let ret = unsafe { libc::mincore(base.cast(), size, residency.as_mut_ptr()) };
+    if ret != 0 {
+        return Err(crate::new_error!(
+            "mincore failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    // Only the lowest bit of each byte is defined
+    Ok(residency.iter().filter(|&&page| page & 1 != 0).count())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn residency_stats() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let echoed: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(echoed, "hello");
+
+        let stats = sandbox.residency_stats().unwrap();
+        assert_eq!(stats.page_size, page_size::get());
+        assert_eq!(
+            stats.scratch_pages * stats.page_size,
+            sandbox.memory_layout().scratch_size
+        );
+        // The guest has just run, so it has touched some of each
+        assert!(stats.snapshot_resident_pages > 0, "{stats:?}");
+        assert!(stats.scratch_resident_pages > 0, "{stats:?}");
+        assert!(stats.snapshot_resident_pages <= stats.snapshot_pages);
+        assert!(stats.scratch_resident_pages <= stats.scratch_pages);
+        assert_eq!(
+            stats.resident_pages() + stats.non_resident_pages(),
+            stats.snapshot_pages + stats.scratch_pages
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "linux"))]
+    fn residency_stats_unsupported() {
+        let sandbox = new_simple_guest_sandbox(None);
+        assert!(matches!(
+            sandbox.residency_stats(),
+            Err(crate::HyperlightError::Unsupported { .. })
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Passing a guest function a buffer assembled from several host buffers,
+//! see [`MultiUseSandbox::call_sg`].
+
+use std::io::IoSlice;
+
+use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
+use hyperlight_common::flatbuffer_wrappers::util::estimate_flatbuffer_capacity;
+use tracing::{Span, instrument};
+
+use super::initialized_multi_use::read_return_value;
+use crate::func::SupportedReturnType;
+use crate::metrics::maybe_time_and_emit_guest_call;
+use crate::{MultiUseSandbox, Result};
+
+/// The space a `Vec<u8>` parameter takes in an encoded call, besides its
+/// contents.
+const VEC_BYTES_PARAMETER_OVERHEAD: usize = 36;
+
+impl MultiUseSandbox {
+    /// Calls a guest function that takes a single `Vec<u8>`, passing it
+    /// the contents of `iovecs` one after the other.
+    ///
+    /// This is equivalent to concatenating the slices and passing the
+    /// result to [`call`](Self::call), but the slices are gathered straight
+    /// into the guest's input buffer, so the host does not need to
+    /// concatenate them first, for example to pass a header and a body
+    /// held in separate buffers. The bytes are always copied into the
+    /// guest's memory rather than mapped into it, so they must fit in the
+    /// input buffer like any other argument.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::IoSlice;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// let header = b"HEADER\n".to_vec();
+    /// let body = std::fs::read("body.bin")?;
+    /// let digest: Vec<u8> = sandbox.call_sg(
+    ///     "Digest",
+    ///     &[IoSlice::new(&header), IoSlice::new(&body)],
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip(self, iovecs), parent = Span::current())]
+    pub fn call_sg<Output: SupportedReturnType>(
+        &mut self,
+        func_name: &str,
+        iovecs: &[IoSlice<'_>],
+    ) -> Result<Output> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        // Reset snapshot since we are mutating the sandbox state
+        self.snapshot = None;
+
+        let slices: Vec<&[u8]> = iovecs.iter().map(|iovec| &**iovec).collect();
+        let len: usize = slices.iter().map(|slice| slice.len()).sum();
+        let estimated_capacity =
+            (estimate_flatbuffer_capacity(func_name, &[]) + VEC_BYTES_PARAMETER_OVERHEAD + len)
+                .next_power_of_two();
+        let fc = FunctionCall::new(
+            func_name.to_string(),
+            None,
+            FunctionCallType::Guest,
+            Output::TYPE,
+        )
+        .with_schema_generation(self.schema_generation);
+
+        maybe_time_and_emit_guest_call(func_name, || {
+            let ret = self.call_guest_function_encoded_inner(
+                func_name,
+                estimated_capacity,
+                |builder| {
+                    fc.encode_with_gathered_bytes(builder, &slices);
+                },
+                None,
+                read_return_value,
+            );
+            Ok(Output::from_value(ret?)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::IoSlice;
+
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn call_sg() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        let header = [1u8, 2, 3];
+        let body = vec![4u8; 1000];
+        let res: Vec<u8> = sandbox
+            .call_sg(
+                "ReverseBytes",
+                &[
+                    IoSlice::new(&header),
+                    IoSlice::new(&[]),
+                    IoSlice::new(&body),
+                ],
+            )
+            .unwrap();
+        let mut expected = [header.as_slice(), body.as_slice()].concat();
+        expected.reverse();
+        assert_eq!(res, expected);
+
+        let res: Vec<u8> = sandbox.call_sg("ReverseBytes", &[]).unwrap();
+        assert!(res.is_empty());
+
+        // The gathered bytes are the function's only parameter
+        assert!(
+            sandbox
+                .call_sg::<i32>("Add", &[IoSlice::new(&header)])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,594 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Running the calls to many sandboxes on a few threads, see
+//! [`SandboxScheduler`].
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use tracing::{Span, instrument};
+
+use super::continuation::{ContinuationToken, Resumed};
+use super::snapshot::Snapshot;
+use crate::func::{ParameterTuple, SupportedReturnType};
+use crate::{MultiUseSandbox, Result, new_error};
+
+/// A queued call, or the next slice of a cooperative call, which runs on
+/// the sandbox it was submitted to. It sends back the result once the call
+/// is complete, or returns the job that runs the call's next slice if the
+/// call yielded.
+struct Job(Box<dyn FnOnce(&mut MultiUseSandbox, Duration) -> Option<Job> + Send>);
+
+/// A sandbox owned by the scheduler and the calls queued for it.
+struct Slot {
+    /// The sandbox, or `None` while a worker is running one of its calls
+    sandbox: Option<MultiUseSandbox>,
+    /// The state the sandbox is restored to if a call poisons it: the one
+    /// it was given to the scheduler in, or the one before its latest call
+    /// if the scheduler takes a snapshot before each call
+    snapshot: Arc<Snapshot>,
+    pending: VecDeque<Job>,
+}
+
+struct State {
+    slots: Vec<Slot>,
+    /// The sandboxes that have calls queued and are not running, in the
+    /// order they get to run. A sandbox is in the queue at most once.
+    run_queue: VecDeque<usize>,
+    shut_down: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Notified whenever a sandbox is added to the run queue, and on
+    /// shutdown
+    work_ready: Condvar,
+    call_timeout: Duration,
+    /// Whether a snapshot of each sandbox is taken before each of its calls,
+    /// see [`SandboxScheduler::with_snapshot_before_each_call`]
+    snapshot_before_each_call: AtomicBool,
+}
+
+impl Shared {
+    fn lock(&self) -> Result<MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))
+    }
+}
+
+/// Runs the calls to a set of sandboxes on a fixed number of worker
+/// threads, so that many sandboxes can share a few threads.
+///
+/// Calls are queued per sandbox with [`submit`](Self::submit) and run in
+/// the order they were submitted to that sandbox. The workers take turns
+/// between the sandboxes that have calls queued: after running one call of
+/// a sandbox, a worker moves on to the next sandbox, so a sandbox with many
+/// queued calls does not hold up the others. A sandbox only ever runs one
+/// call at a time.
+///
+/// ## Time Slicing
+///
+/// The scheduler cannot preempt a guest call: a running call cannot be
+/// suspended by the host and resumed later, so a call submitted with
+/// [`submit`](Self::submit) holds its worker until it completes. Long
+/// calls can instead be time sliced cooperatively, by submitting them with
+/// [`submit_cooperative`](Self::submit_cooperative) to a guest function
+/// that suspends itself at points of its choosing, as for
+/// [`MultiUseSandbox::call_suspending`]. Each time it suspends, it yields
+/// its worker to the other sandboxes, and it is resumed on its sandbox's
+/// next turn.
+///
+/// To bound how long a guest that does not yield can hold a worker, each
+/// call, and each slice of a cooperative call, is also given a deadline of
+/// the scheduler's call timeout. A call that runs past it is cancelled as
+/// by [`MultiUseSandbox::call_with_timeout`] and fails with
+/// [`GuestCallTimedOut`](crate::HyperlightError::GuestCallTimedOut).
+///
+/// ## Poisoned Sandboxes
+///
+/// A snapshot of each sandbox is taken when it is given to the scheduler,
+/// and a sandbox that is poisoned by a call, because it timed out or for
+/// any other reason, is restored to it, so that it can run the calls
+/// queued after. This discards the changes of every call the sandbox ran
+/// before. With
+/// [`with_snapshot_before_each_call`](Self::with_snapshot_before_each_call)
+/// a snapshot is taken before each call instead, so that only the failed
+/// call's changes are discarded, at the cost of copying the memory each
+/// call changed.
+///
+/// Dropping the scheduler stops the workers once they have finished the
+/// calls they are running, fails the calls still queued, and drops the
+/// sandboxes. Use [`into_sandboxes`](Self::into_sandboxes) to keep them.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use hyperlight_host::{GuestBinary, MultiUseSandbox, UninitializedSandbox};
+/// # use hyperlight_host::sandbox::SandboxScheduler;
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let sandboxes = (0..16)
+///     .map(|_| {
+///         UninitializedSandbox::new(GuestBinary::FilePath("guest.bin".into()), None)?.evolve()
+///     })
+///     .collect::<Result<Vec<MultiUseSandbox>, _>>()?;
+/// let scheduler = SandboxScheduler::new(sandboxes, 2, Duration::from_millis(100))?;
+///
+/// let calls = (0..16)
+///     .map(|i| scheduler.submit::<String>(i, "Echo", format!("request {i}")))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// for call in calls {
+///     println!("{}", call.wait()?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SandboxScheduler {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// A call submitted to a [`SandboxScheduler`], whose result can be waited
+/// for.
+#[derive(Debug)]
+pub struct ScheduledCall<Output> {
+    receiver: Receiver<Result<Output>>,
+}
+
+impl<Output> ScheduledCall<Output> {
+    /// Wait for the call to complete, and return its result.
+    ///
+    /// This fails if the scheduler was dropped before the call completed.
+    pub fn wait(self) -> Result<Output> {
+        self.receiver
+            .recv()
+            .map_err(|_| new_error!("The scheduler was dropped before the call completed"))?
+    }
+}
+
+impl SandboxScheduler {
+    /// Create a scheduler that runs the calls to `sandboxes` on `workers`
+    /// threads, cancelling any call, or slice of a cooperative call, that
+    /// runs for longer than `call_timeout`.
+    ///
+    /// The sandboxes are identified by their index in `sandboxes`.
+    #[instrument(err(Debug), skip(sandboxes), parent = Span::current())]
+    pub fn new(
+        sandboxes: Vec<MultiUseSandbox>,
+        workers: usize,
+        call_timeout: Duration,
+    ) -> Result<Self> {
+        if workers == 0 {
+            return Err(new_error!("A scheduler needs at least 1 worker"));
+        }
+        let slots = sandboxes
+            .into_iter()
+            .map(|mut sandbox| {
+                Ok(Slot {
+                    snapshot: sandbox.snapshot()?,
+                    sandbox: Some(sandbox),
+                    pending: VecDeque::new(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                slots,
+                run_queue: VecDeque::new(),
+                shut_down: false,
+            }),
+            work_ready: Condvar::new(),
+            call_timeout,
+            snapshot_before_each_call: AtomicBool::new(false),
+        });
+        let mut scheduler = Self {
+            shared,
+            workers: Vec::with_capacity(workers),
+        };
+        for i in 0..workers {
+            let shared = scheduler.shared.clone();
+            let worker = thread::Builder::new()
+                .name(format!("hyperlight-scheduler-{i}"))
+                .spawn(move || run_worker(&shared))?;
+            scheduler.workers.push(worker);
+        }
+        Ok(scheduler)
+    }
+
+    /// Take a snapshot of each sandbox before each of its calls, so that a
+    /// sandbox poisoned by a call is restored to its state before that
+    /// call, rather than to the one it was given to the scheduler in.
+    ///
+    /// Taking the snapshot costs little when the previous call did not
+    /// change the sandbox, but otherwise copies the memory it changed.
+    pub fn with_snapshot_before_each_call(self) -> Self {
+        self.shared
+            .snapshot_before_each_call
+            .store(true, Ordering::Relaxed);
+        self
+    }
+
+    /// The number of sandboxes the scheduler runs calls on.
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.shared.lock()?.slots.len())
+    }
+
+    /// Whether the scheduler has no sandboxes.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Queue a call to the guest function `func_name` with `args` on the
+    /// sandbox at index `sandbox`, and return a handle to wait for its
+    /// result.
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn submit<Output: SupportedReturnType + Send + 'static>(
+        &self,
+        sandbox: usize,
+        func_name: &str,
+        args: impl ParameterTuple + Send + 'static,
+    ) -> Result<ScheduledCall<Output>> {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let func_name = func_name.to_string();
+        let job = Job(Box::new(move |sbox: &mut MultiUseSandbox, call_timeout| {
+            // If the call has been abandoned the result is dropped here
+            let _ = sender.send(sbox.call_with_timeout::<Output>(&func_name, args, call_timeout));
+            None
+        }));
+        self.enqueue(sandbox, job)?;
+        Ok(ScheduledCall { receiver })
+    }
+
+    /// Queue a cooperative call to the suspending guest function
+    /// `func_name` with `args` on the sandbox at index `sandbox`, and
+    /// return a handle to wait for its result.
+    ///
+    /// The guest function is called as by
+    /// [`MultiUseSandbox::call_suspending`], and yields to the other
+    /// sandboxes each time it suspends. On its sandbox's next turn, the
+    /// scheduler resumes it with an empty result, until it finishes. The
+    /// calls submitted to the same sandbox after it run once it has
+    /// finished.
+    #[instrument(err(Debug), skip(self, args), parent = Span::current())]
+    pub fn submit_cooperative(
+        &self,
+        sandbox: usize,
+        func_name: &str,
+        args: impl ParameterTuple + Send + 'static,
+    ) -> Result<ScheduledCall<Vec<u8>>> {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let func_name = func_name.to_string();
+        let job =
+            Job(Box::new(
+                move |sbox: &mut MultiUseSandbox, call_timeout| match sbox
+                    .call_suspending_with_timeout(&func_name, args, call_timeout)
+                {
+                    Ok(token) => Some(next_slice(token, sender)),
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        None
+                    }
+                },
+            ));
+        self.enqueue(sandbox, job)?;
+        Ok(ScheduledCall { receiver })
+    }
+
+    /// Queue `job` on the sandbox at index `sandbox`.
+    fn enqueue(&self, sandbox: usize, job: Job) -> Result<()> {
+        let mut state = self.shared.lock()?;
+        let slot = state
+            .slots
+            .get_mut(sandbox)
+            .ok_or_else(|| new_error!("The scheduler has no sandbox {}", sandbox))?;
+        // A sandbox that is running is queued again when its call
+        // completes, and one with calls pending is already queued
+        let idle = slot.sandbox.is_some() && slot.pending.is_empty();
+        slot.pending.push_back(job);
+        if idle {
+            state.run_queue.push_back(sandbox);
+            self.shared.work_ready.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Stop the scheduler once the calls that are running have completed,
+    /// and return its sandboxes in the order they were given to
+    /// [`new`](Self::new). The calls still queued fail.
+    #[instrument(err(Debug), skip(self), parent = Span::current())]
+    pub fn into_sandboxes(mut self) -> Result<Vec<MultiUseSandbox>> {
+        self.stop()?;
+        let mut state = self.shared.lock()?;
+        state
+            .slots
+            .iter_mut()
+            .map(|slot| {
+                slot.sandbox
+                    .take()
+                    .ok_or_else(|| new_error!("A scheduler worker lost its sandbox"))
+            })
+            .collect()
+    }
+
+    /// Stop the workers and wait for them to exit.
+    fn stop(&mut self) -> Result<()> {
+        {
+            let mut state = self.shared.lock()?;
+            state.shut_down = true;
+            state.run_queue.clear();
+            for slot in &mut state.slots {
+                slot.pending.clear();
+            }
+        }
+        self.shared.work_ready.notify_all();
+        for worker in self.workers.drain(..) {
+            worker
+                .join()
+                .map_err(|_| new_error!("A scheduler worker thread panicked"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SandboxScheduler {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            tracing::error!("Failed to stop the sandbox scheduler: {:?}", e);
+        }
+    }
+}
+
+impl std::fmt::Debug for SandboxScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SandboxScheduler")
+            .field("workers", &self.workers.len())
+            .field("call_timeout", &self.shared.call_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The job that resumes the suspended cooperative call of `token` for its
+/// next slice, and sends its result to `sender` once it has finished.
+fn next_slice(token: ContinuationToken, sender: SyncSender<Result<Vec<u8>>>) -> Job {
+    Job(Box::new(
+        move |sbox: &mut MultiUseSandbox, call_timeout| match sbox.resume_with_timeout(
+            token,
+            Vec::new(),
+            call_timeout,
+        ) {
+            Ok(Resumed::Suspended(token)) => Some(next_slice(token, sender)),
+            Ok(Resumed::Ready(result)) => {
+                let _ = sender.send(Ok(result));
+                None
+            }
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                None
+            }
+        },
+    ))
+}
+
+/// Run the next queued call of each sandbox in turn until the scheduler is
+/// stopped.
+fn run_worker(shared: &Shared) {
+    let Ok(mut state) = shared.lock() else {
+        return;
+    };
+    loop {
+        if state.shut_down {
+            return;
+        }
+        let Some(index) = state.run_queue.pop_front() else {
+            state = match shared.work_ready.wait(state) {
+                Ok(state) => state,
+                Err(_) => return,
+            };
+            continue;
+        };
+        let slot = &mut state.slots[index];
+        let Some(job) = slot.pending.pop_front() else {
+            continue;
+        };
+        let Some(mut sandbox) = slot.sandbox.take() else {
+            continue;
+        };
+        let mut snapshot = slot.snapshot.clone();
+        drop(state);
+
+        // Keep the state before this call to restore it if the call fails.
+        // If that cannot be captured, the state before an earlier call is
+        // the best there is.
+        if shared.snapshot_before_each_call.load(Ordering::Relaxed) {
+            match sandbox.snapshot() {
+                Ok(current) => snapshot = current,
+                Err(e) => tracing::error!("Failed to snapshot a scheduled sandbox: {:?}", e),
+            }
+        }
+        let next = (job.0)(&mut sandbox, shared.call_timeout);
+        if sandbox.poisoned()
+            && let Err(e) = sandbox.restore(snapshot.clone())
+        {
+            tracing::error!("Failed to restore a poisoned sandbox: {:?}", e);
+        }
+
+        state = match shared.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        let shut_down = state.shut_down;
+        let slot = &mut state.slots[index];
+        slot.sandbox = Some(sandbox);
+        slot.snapshot = snapshot;
+        if let Some(next) = next
+            && !shut_down
+        {
+            // A cooperative call that yielded carries on before the calls
+            // queued after it
+            slot.pending.push_front(next);
+        }
+        if !slot.pending.is_empty() {
+            // Other sandboxes that are waiting run first
+            state.run_queue.push_back(index);
+            shared.work_ready.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::TryRecvError;
+    use std::time::Duration;
+
+    use super::SandboxScheduler;
+    use crate::sandbox::new_simple_guest_sandbox;
+    use crate::{HyperlightError, MultiUseSandbox};
+
+    fn new_sandboxes(count: usize) -> Vec<MultiUseSandbox> {
+        (0..count).map(|_| new_simple_guest_sandbox(None)).collect()
+    }
+
+    #[test]
+    fn runs_calls_in_order_per_sandbox() {
+        let scheduler = SandboxScheduler::new(new_sandboxes(3), 2, Duration::from_secs(5)).unwrap();
+        assert_eq!(scheduler.len().unwrap(), 3);
+
+        let calls: Vec<_> = (0..30)
+            .map(|i| scheduler.submit::<i32>(i % 3, "AddToStatic", 1i32).unwrap())
+            .collect();
+        let results: Vec<i32> = calls.into_iter().map(|call| call.wait().unwrap()).collect();
+        // Each sandbox ran its calls one after the other
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result, i as i32 / 3 + 1);
+        }
+
+        assert!(scheduler.submit::<i32>(3, "AddToStatic", 1i32).is_err());
+        let sandboxes = scheduler.into_sandboxes().unwrap();
+        assert_eq!(sandboxes.len(), 3);
+    }
+
+    #[test]
+    fn timed_out_call_restores_initial_state() {
+        let scheduler =
+            SandboxScheduler::new(new_sandboxes(1), 1, Duration::from_millis(100)).unwrap();
+
+        let call = scheduler.submit::<i32>(0, "AddToStatic", 1i32).unwrap();
+        assert_eq!(call.wait().unwrap(), 1);
+        let res = scheduler.submit::<()>(0, "Spin", ()).unwrap().wait();
+        assert!(
+            matches!(res, Err(HyperlightError::GuestCallTimedOut { .. })),
+            "{res:?}"
+        );
+
+        // Without a snapshot before each call, the sandbox was restored to
+        // the state it was handed to the scheduler in
+        let call = scheduler.submit::<i32>(0, "GetStatic", ()).unwrap();
+        assert_eq!(call.wait().unwrap(), 0);
+    }
+
+    #[test]
+    fn timed_out_call_keeps_earlier_calls() {
+        let scheduler = SandboxScheduler::new(new_sandboxes(1), 1, Duration::from_millis(100))
+            .unwrap()
+            .with_snapshot_before_each_call();
+
+        for expected in 1..=3 {
+            let call = scheduler.submit::<i32>(0, "AddToStatic", 1i32).unwrap();
+            assert_eq!(call.wait().unwrap(), expected);
+        }
+        let res = scheduler.submit::<()>(0, "Spin", ()).unwrap().wait();
+        assert!(
+            matches!(res, Err(HyperlightError::GuestCallTimedOut { .. })),
+            "{res:?}"
+        );
+
+        // The sandbox was restored to its state before the call that timed
+        // out, not to the one it was handed to the scheduler in
+        let call = scheduler.submit::<i32>(0, "GetStatic", ()).unwrap();
+        assert_eq!(call.wait().unwrap(), 3);
+    }
+
+    #[test]
+    fn long_call_is_cancelled() {
+        let scheduler =
+            SandboxScheduler::new(new_sandboxes(2), 1, Duration::from_millis(100)).unwrap();
+
+        let spin = scheduler.submit::<()>(0, "Spin", ()).unwrap();
+        let echo = scheduler
+            .submit::<String>(1, "Echo", "hello".to_string())
+            .unwrap();
+        let res = spin.wait();
+        assert!(
+            matches!(res, Err(HyperlightError::GuestCallTimedOut { .. })),
+            "{res:?}"
+        );
+        assert_eq!(echo.wait().unwrap(), "hello");
+
+        // The cancelled call's sandbox was restored and runs later calls
+        let echo = scheduler
+            .submit::<String>(0, "Echo", "again".to_string())
+            .unwrap();
+        assert_eq!(echo.wait().unwrap(), "again");
+        let sandboxes = scheduler.into_sandboxes().unwrap();
+        assert!(!sandboxes[0].poisoned());
+    }
+
+    #[test]
+    fn cooperative_call_yields_to_other_sandboxes() {
+        let scheduler = SandboxScheduler::new(new_sandboxes(2), 1, Duration::from_secs(5)).unwrap();
+
+        let slices = scheduler
+            .submit_cooperative(0, "CountSlices", 10_000u32)
+            .unwrap();
+        let echo = scheduler
+            .submit::<String>(1, "Echo", "hello".to_string())
+            .unwrap();
+        assert_eq!(echo.wait().unwrap(), "hello");
+        // The other sandbox's call ran between two slices, long before the
+        // cooperative call finished
+        assert!(matches!(
+            slices.receiver.try_recv(),
+            Err(TryRecvError::Empty)
+        ));
+        assert_eq!(slices.wait().unwrap(), 10_000u32.to_le_bytes());
+
+        // The calls submitted to the same sandbox after a cooperative call
+        // run once it has finished
+        let slices = scheduler
+            .submit_cooperative(0, "CountSlices", 3u32)
+            .unwrap();
+        let add = scheduler.submit::<i32>(0, "AddToStatic", 1i32).unwrap();
+        assert_eq!(add.wait().unwrap(), 1);
+        assert_eq!(
+            slices.receiver.try_recv().unwrap().unwrap(),
+            3u32.to_le_bytes()
+        );
+
+        // A guest function that does not suspend cannot be called
+        // cooperatively
+        let res = scheduler
+            .submit_cooperative(1, "Echo", "hello".to_string())
+            .unwrap()
+            .wait();
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,216 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::collections::BTreeMap;
+
+use hyperlight_common::vmem::{self, PAGE_SIZE, TableReadOps};
+
+use super::{PTE_SIZE, Snapshot};
+use crate::mem::layout::SandboxMemoryLayout;
+
+/// How a range of guest memory differs between two snapshots, see
+/// [`Snapshot::diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryDiffKind {
+    /// The range is mapped in both snapshots, with different contents.
+    Modified,
+    /// The range is only mapped in the later snapshot.
+    Mapped,
+    /// The range is only mapped in the earlier snapshot.
+    Unmapped,
+}
+
+/// A range of guest virtual memory that differs between two snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryDiff {
+    /// The guest virtual address of the first page in the range.
+    pub guest_address: u64,
+    /// The length of the range in bytes, which is a multiple of the page
+    /// size.
+    pub len: usize,
+    /// How the range differs.
+    pub kind: MemoryDiffKind,
+    /// The contents of the range in the earlier snapshot, if requested
+    /// with [`Snapshot::diff_with_contents`] and the range was mapped in it.
+    pub before: Option<Vec<u8>>,
+    /// The contents of the range in the later snapshot, if requested with
+    /// [`Snapshot::diff_with_contents`] and the range is mapped in it.
+    pub after: Option<Vec<u8>>,
+}
+
+impl Snapshot {
+    /// Compare the guest memory of this snapshot with the later snapshot
+    /// `other`, returning the ranges of guest virtual memory that differ,
+    /// in address order.
+    ///
+    /// Memory is compared a page at a time, and adjacent pages that differ
+    /// in the same way are merged into a single range. Only memory captured
+    /// in the snapshots is compared: the scratch region, which holds the
+    /// guest's stack and page tables, and regions mapped from the host are
+    /// not.
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, Result};
+    /// # fn example(sandbox: &mut MultiUseSandbox) -> Result<()> {
+    /// let before = sandbox.snapshot()?;
+    /// sandbox.call::<i32>("SetStatic", ())?;
+    /// let after = sandbox.snapshot()?;
+    /// for diff in before.diff(&after) {
+    ///     println!("{:?} {:#x}..{:#x}", diff.kind, diff.guest_address, diff.guest_address + diff.len as u64);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &Snapshot) -> Vec<MemoryDiff> {
+        self.diff_impl(other, false)
+    }
+
+    /// Like [`diff`](Self::diff), but also returns the contents of each
+    /// range before and after.
+    pub fn diff_with_contents(&self, other: &Snapshot) -> Vec<MemoryDiff> {
+        self.diff_impl(other, true)
+    }
+
+    fn diff_impl(&self, other: &Snapshot, with_contents: bool) -> Vec<MemoryDiff> {
+        let before = self.guest_pages();
+        let after = other.guest_pages();
+
+        let mut addresses: Vec<u64> = before.keys().chain(after.keys()).copied().collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        let mut diffs: Vec<MemoryDiff> = Vec::new();
+        for address in addresses {
+            let old = before.get(&address).copied();
+            let new = after.get(&address).copied();
+            let kind = match (old, new) {
+                (Some(old), Some(new)) if old == new => continue,
+                (Some(_), Some(_)) => MemoryDiffKind::Modified,
+                (None, Some(_)) => MemoryDiffKind::Mapped,
+                (Some(_), None) => MemoryDiffKind::Unmapped,
+                (None, None) => continue,
+            };
+
+            let extends_last = diffs.last().is_some_and(|last| {
+                last.kind == kind && last.guest_address + last.len as u64 == address
+            });
+            if !extends_last {
+                diffs.push(MemoryDiff {
+                    guest_address: address,
+                    len: 0,
+                    kind,
+                    before: (with_contents && old.is_some()).then(Vec::new),
+                    after: (with_contents && new.is_some()).then(Vec::new),
+                });
+            }
+            // Just pushed if it did not already exist
+            #[allow(clippy::unwrap_used)]
+            let last = diffs.last_mut().unwrap();
+            last.len += PAGE_SIZE;
+            if let (Some(contents), Some(page)) = (last.before.as_mut(), old) {
+                contents.extend_from_slice(page);
+            }
+            if let (Some(contents), Some(page)) = (last.after.as_mut(), new) {
+                contents.extend_from_slice(page);
+            }
+        }
+        diffs
+    }
+
+    /// The contents of every page of guest virtual memory captured in this
+    /// snapshot, by guest virtual address.
+    fn guest_pages(&self) -> BTreeMap<u64, &[u8]> {
+        let memory = self.memory.as_slice();
+        let pt_offset = memory.len().saturating_sub(self.layout.get_pt_size());
+        let data_size = self.layout.snapshot_size.min(pt_offset);
+        let tables = SnapshotPageTables {
+            memory,
+            pt_offset,
+            pt_base_gpa: self.root_pt_gpa(),
+        };
+
+        let mut pages = BTreeMap::new();
+        let mappings =
+            unsafe { vmem::virt_to_phys(&tables, 0, hyperlight_common::layout::MAX_GVA as u64) };
+        for mapping in mappings {
+            for page in 0..mapping.len / PAGE_SIZE as u64 {
+                let offset = (mapping.phys_base + page * PAGE_SIZE as u64)
+                    .checked_sub(SandboxMemoryLayout::BASE_ADDRESS as u64)
+                    .and_then(|offset| usize::try_from(offset).ok());
+                // Pages outside the snapshot's data, such as the scratch
+                // region, are not captured in the snapshot.
+                let Some(contents) = offset
+                    .filter(|&offset| offset + PAGE_SIZE <= data_size)
+                    .map(|offset| &memory[offset..offset + PAGE_SIZE])
+                else {
+                    continue;
+                };
+                pages.insert(mapping.virt_base + page * PAGE_SIZE as u64, contents);
+            }
+        }
+        pages
+    }
+}
+
+/// Reads the page tables stored at the end of a snapshot's memory, which
+/// are addressed as if they had already been copied to the scratch region
+/// as they are when the snapshot is restored.
+struct SnapshotPageTables<'a> {
+    memory: &'a [u8],
+    /// The offset of the page tables in `memory`.
+    pt_offset: usize,
+    /// The guest physical address of the page tables once restored.
+    pt_base_gpa: u64,
+}
+
+impl TableReadOps for SnapshotPageTables<'_> {
+    type TableAddr = u64;
+    fn entry_addr(addr: u64, offset: u64) -> u64 {
+        addr + offset
+    }
+    unsafe fn read_entry(&self, addr: u64) -> vmem::PageTableEntry {
+        let pte_bytes = addr
+            .checked_sub(self.pt_base_gpa)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|offset| offset.checked_add(self.pt_offset))
+            .and_then(|offset| self.memory.get(offset..offset + PTE_SIZE));
+        let Some(pte_bytes) = pte_bytes else {
+            // Out of bounds entries are treated as not present, as in
+            // `SharedMemoryPageTableBuffer`.
+            return 0;
+        };
+        // The `get()` above ensures exactly PTE_SIZE bytes.
+        #[allow(clippy::unwrap_used)]
+        vmem::PageTableEntry::from_le_bytes(pte_bytes.try_into().unwrap())
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn to_phys(addr: u64) -> vmem::PhysAddr {
+        addr as vmem::PhysAddr
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn from_phys(addr: vmem::PhysAddr) -> u64 {
+        addr as u64
+    }
+    fn root_table(&self) -> u64 {
+        self.pt_base_gpa
+    }
+}
+
+impl<'a> AsRef<SnapshotPageTables<'a>> for SnapshotPageTables<'a> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
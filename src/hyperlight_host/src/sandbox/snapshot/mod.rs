@@ -34,6 +34,12 @@ use crate::mem::shared_mem::{ReadonlySharedMemory, SharedMemory};
 use crate::sandbox::SandboxConfiguration;
 use crate::sandbox::uninitialized::{GuestBinary, GuestEnvironment};
 
+mod diff;
+mod partial;
+
+pub use diff::{MemoryDiff, MemoryDiffKind};
+pub use partial::PartialSnapshot;
+
 const PTE_SIZE: usize = size_of::<vmem::PageTableEntry>();
 
 /// Presently, a snapshot can be of a preinitialised sandbox, which
@@ -280,6 +286,7 @@ impl Snapshot {
         let exe_info = match bin {
             GuestBinary::FilePath(bin_path_str) => ExeInfo::from_file(&bin_path_str)?,
             GuestBinary::Buffer(buffer) => ExeInfo::from_buf(buffer)?,
+            GuestBinary::MmapFile(bin_path_str) => ExeInfo::from_mapped_file(&bin_path_str)?,
         };
 
         // Check guest/host version compatibility.
@@ -307,6 +314,7 @@ impl Snapshot {
         let load_addr = layout.get_guest_code_address() as u64;
         let base_va = exe_info.base_va();
         let entrypoint_va: u64 = exe_info.entrypoint().into();
+        layout.set_code_image(base_va, entrypoint_va - base_va);
 
         let mut memory = vec![0; layout.get_memory_size()?];
 
@@ -558,12 +566,35 @@ impl Snapshot {
         self.snapshot_generation
     }
 
+    /// The number of bytes of guest memory held by this snapshot, which
+    /// is the memory that restoring a sandbox from it brings back.
+    pub fn size(&self) -> usize {
+        self.memory.mem_size()
+    }
+
     /// Return the main memory contents of the snapshot
     #[instrument(skip_all, parent = Span::current(), level= "Trace")]
     pub(crate) fn memory(&self) -> &ReadonlySharedMemory {
         &self.memory
     }
 
+    /// This snapshot, with its memory backed by `file` rather than
+    /// anonymous memory, see
+    /// [`SandboxConfiguration::set_shared_memory_backing`](crate::sandbox::SandboxConfiguration::set_shared_memory_backing).
+    #[cfg(target_os = "linux")]
+    pub(crate) fn backed_by_file(&self, file: &std::fs::File) -> Result<Self> {
+        Ok(Self {
+            layout: self.layout,
+            memory: self.memory.backed_by_file(file)?,
+            load_info: self.load_info.clone(),
+            stack_top_gva: self.stack_top_gva,
+            sregs: self.sregs.clone(),
+            entrypoint: self.entrypoint,
+            snapshot_generation: self.snapshot_generation,
+            host_functions: self.host_functions.clone(),
+        })
+    }
+
     /// Return a copy of the load info for the exe in the snapshot
     pub(crate) fn load_info(&self) -> LoadInfo {
         self.load_info.clone()
@@ -0,0 +1,272 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use hyperlight_common::layout::scratch_base_gva;
+use hyperlight_common::vmem::{self, MappingKind, PAGE_SIZE};
+
+use super::SharedMemoryPageTableBuffer;
+use crate::mem::layout::{BaseGpaRegion, SandboxMemoryLayout};
+use crate::mem::mgr::SandboxMemoryManager;
+use crate::mem::shared_mem::{HostSharedMemory, SharedMemory};
+use crate::{HyperlightError, Result, new_error};
+
+/// A copy of selected ranges of a sandbox's guest virtual memory, taken
+/// with [`MultiUseSandbox::snapshot_regions`](crate::MultiUseSandbox::snapshot_regions)
+/// and restored with
+/// [`MultiUseSandbox::restore_partial`](crate::MultiUseSandbox::restore_partial).
+///
+/// Unlike a [`Snapshot`](super::Snapshot), this does not capture the vCPU
+/// state, the page tables, or any memory outside its ranges.
+#[derive(Clone, Debug)]
+pub struct PartialSnapshot {
+    /// The captured ranges, sorted and non-overlapping, and their contents.
+    regions: Vec<(Range<u64>, Vec<u8>)>,
+    /// The pages outside the scratch region that the guest had already
+    /// written to when the snapshot was taken.
+    written_pages: BTreeSet<u64>,
+    /// The layout of the sandbox the snapshot was taken from.
+    layout: SandboxMemoryLayout,
+}
+
+impl PartialSnapshot {
+    /// The guest virtual address ranges captured in this snapshot, in
+    /// address order. Overlapping and adjacent ranges passed to
+    /// [`MultiUseSandbox::snapshot_regions`](crate::MultiUseSandbox::snapshot_regions)
+    /// are merged.
+    pub fn ranges(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.regions.iter().map(|(range, _)| range.clone())
+    }
+
+    /// The number of bytes of guest memory captured in this snapshot.
+    pub fn size(&self) -> usize {
+        self.regions
+            .iter()
+            .map(|(_, contents)| contents.len())
+            .sum()
+    }
+
+    /// Copy the contents of `ranges` of the guest virtual memory mapped by
+    /// the page tables at `root_pt`.
+    pub(crate) fn capture(
+        mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        root_pt: u64,
+        ranges: &[Range<u64>],
+    ) -> Result<Self> {
+        let ranges = merge_ranges(ranges)?;
+        let layout = mgr.layout;
+        mgr.shared_mem.with_contents(|snap| {
+            mgr.scratch_mem.with_contents(|scratch| {
+                let pt_buf = SharedMemoryPageTableBuffer::new(snap, scratch, layout, root_pt);
+                let mut regions = Vec::with_capacity(ranges.len());
+                for range in ranges {
+                    let mut contents = Vec::with_capacity((range.end - range.start) as usize);
+                    for_each_page(&pt_buf, layout, &range, |gva, page| {
+                        let (mem, offset) = match page {
+                            Backing::Snapshot(offset) => (snap, offset),
+                            Backing::Scratch(offset) => (scratch, offset),
+                        };
+                        let len = page_chunk_len(gva, range.end);
+                        let bytes = mem.get(offset..offset + len).ok_or_else(|| {
+                            new_error!("Guest address {:#x} is outside sandbox memory", gva)
+                        })?;
+                        contents.extend_from_slice(bytes);
+                        Ok(())
+                    })?;
+                    regions.push((range, contents));
+                }
+                Ok(Self {
+                    regions,
+                    written_pages: written_pages(&pt_buf, layout),
+                    layout,
+                })
+            })
+        })??
+    }
+
+    /// Write the captured contents back to guest memory, through the page
+    /// tables at `root_pt`. Nothing is written unless every range can be
+    /// restored.
+    pub(crate) fn restore_to(
+        &self,
+        mgr: &mut SandboxMemoryManager<HostSharedMemory>,
+        root_pt: u64,
+    ) -> Result<()> {
+        if !self.layout.is_compatible_with(&mgr.layout) {
+            return Err(HyperlightError::SnapshotLayoutMismatch);
+        }
+        let layout = mgr.layout;
+        // The scratch offset and contents of each write to make
+        let writes = mgr.shared_mem.with_contents(|snap| {
+            mgr.scratch_mem.with_contents(|scratch| {
+                let pt_buf = SharedMemoryPageTableBuffer::new(snap, scratch, layout, root_pt);
+                let mut writes = Vec::new();
+                for (range, contents) in &self.regions {
+                    for_each_page(&pt_buf, layout, range, |gva, page| {
+                        let len = page_chunk_len(gva, range.end);
+                        let start = (gva - range.start) as usize;
+                        let saved = &contents[start..start + len];
+                        match page {
+                            Backing::Scratch(offset) => writes.push((offset, saved)),
+                            // Pages that the guest has not written to since
+                            // the sandbox was last restored still hold the
+                            // snapshot's contents, which cannot be changed.
+                            Backing::Snapshot(offset) => {
+                                if snap.get(offset..offset + len) != Some(saved) {
+                                    return Err(new_error!(
+                                        "Guest address {:#x} is no longer writable by the guest and cannot be restored",
+                                        gva
+                                    ));
+                                }
+                            }
+                        }
+                        Ok(())
+                    })?;
+                }
+
+                let outside: Vec<u64> = written_pages(&pt_buf, layout)
+                    .difference(&self.written_pages)
+                    .copied()
+                    .filter(|&page| !self.overlaps(page..page + PAGE_SIZE as u64))
+                    .collect();
+                if let Some(first) = outside.first() {
+                    tracing::warn!(
+                        "Guest wrote to {} pages outside the ranges of the partial snapshot, starting at {:#x}, which restoring it does not undo",
+                        outside.len(),
+                        first
+                    );
+                }
+                Ok(writes)
+            })
+        })???;
+
+        for (offset, contents) in writes {
+            mgr.scratch_mem.copy_from_slice(contents, offset)?;
+        }
+        Ok(())
+    }
+
+    fn overlaps(&self, other: Range<u64>) -> bool {
+        self.regions
+            .iter()
+            .any(|(range, _)| range.start < other.end && other.start < range.end)
+    }
+}
+
+/// Sort `ranges` and merge the ones that overlap or are adjacent, checking
+/// that each is non-empty.
+fn merge_ranges(ranges: &[Range<u64>]) -> Result<Vec<Range<u64>>> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        if range.start >= range.end {
+            return Err(new_error!(
+                "Invalid guest memory range {:#x}..{:#x}",
+                range.start,
+                range.end
+            ));
+        }
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    Ok(merged)
+}
+
+/// The number of bytes from `gva` to the end of its page or `end`,
+/// whichever comes first.
+fn page_chunk_len(gva: u64, end: u64) -> usize {
+    let page_end = (gva & !(PAGE_SIZE as u64 - 1)) + PAGE_SIZE as u64;
+    (page_end.min(end) - gva) as usize
+}
+
+/// The sandbox memory backing a guest virtual address.
+enum Backing {
+    /// The snapshot memory at this offset, which the guest can only read.
+    Snapshot(usize),
+    /// The scratch memory at this offset.
+    Scratch(usize),
+}
+
+/// Call `f` with the start of each page-sized chunk of `range` and the
+/// memory backing it, failing if any part of `range` is not mapped to
+/// sandbox memory.
+fn for_each_page(
+    pt_buf: &SharedMemoryPageTableBuffer<'_>,
+    layout: SandboxMemoryLayout,
+    range: &Range<u64>,
+    mut f: impl FnMut(u64, Backing) -> Result<()>,
+) -> Result<()> {
+    let mut gva = range.start;
+    let mappings = unsafe { vmem::virt_to_phys(pt_buf, range.start, range.end - range.start) };
+    for mapping in mappings {
+        let mapping_end = mapping.virt_base + mapping.len;
+        if mapping.virt_base > gva || mapping_end <= gva {
+            break;
+        }
+        while gva < mapping_end && gva < range.end {
+            let gpa = mapping.phys_base + (gva - mapping.virt_base);
+            let page = match layout.resolve_gpa(gpa, &[]) {
+                Some(resolved) => match resolved.base {
+                    BaseGpaRegion::Snapshot(()) => Backing::Snapshot(resolved.offset),
+                    BaseGpaRegion::Scratch(()) => Backing::Scratch(resolved.offset),
+                    BaseGpaRegion::Mmap(_) => break,
+                },
+                None => break,
+            };
+            f(gva, page)?;
+            gva += page_chunk_len(gva, range.end) as u64;
+        }
+    }
+    if gva < range.end {
+        return Err(new_error!(
+            "Guest address {:#x} is not mapped to sandbox memory",
+            gva
+        ));
+    }
+    Ok(())
+}
+
+/// The pages below the scratch region that are mapped writable to scratch
+/// memory, which are those the guest has written to since the sandbox was
+/// last restored from a snapshot.
+fn written_pages(
+    pt_buf: &SharedMemoryPageTableBuffer<'_>,
+    layout: SandboxMemoryLayout,
+) -> BTreeSet<u64> {
+    let scratch_gva = scratch_base_gva(layout.scratch_size);
+    let mut pages = BTreeSet::new();
+    let mappings = unsafe { vmem::virt_to_phys(pt_buf, 0, scratch_gva) };
+    for mapping in mappings {
+        if !matches!(mapping.kind, MappingKind::Basic(basic) if basic.writable) {
+            continue;
+        }
+        for page in 0..mapping.len / PAGE_SIZE as u64 {
+            let offset = page * PAGE_SIZE as u64;
+            let in_scratch = layout
+                .resolve_gpa(mapping.phys_base + offset, &[])
+                .is_some_and(|resolved| matches!(resolved.base, BaseGpaRegion::Scratch(())));
+            if in_scratch && mapping.virt_base + offset < scratch_gva {
+                pages.insert(mapping.virt_base + offset);
+            }
+        }
+    }
+    pages
+}
@@ -0,0 +1,268 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Running a sandbox forward in several ways from the same state, see
+//! [`MultiUseSandbox::speculate`].
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tracing::{Span, instrument};
+
+use super::host_funcs::FunctionRegistry;
+use super::snapshot::Snapshot;
+use crate::sandbox::SandboxConfiguration;
+use crate::{MultiUseSandbox, Result, new_error};
+
+impl MultiUseSandbox {
+    /// Runs `run` once for each of `variants`, each time on a fork of the
+    /// sandbox in its current state, and returns the results in the order
+    /// of `variants`.
+    ///
+    /// The sandbox is snapshotted once, and each fork is created from that
+    /// snapshot, so its memory is shared copy-on-write rather than copied.
+    /// The variants are run in parallel on up to one thread per available
+    /// CPU, each of which creates a single fork and
+    /// [`restore`](Self::restore)s it to the snapshot between variants, so
+    /// no variant sees the effects of another, and a variant that poisons
+    /// its fork does not affect the rest. This sandbox itself is left in
+    /// the state it was in.
+    ///
+    /// Forks share this sandbox's host functions and configuration, except
+    /// that their memory is not backed by the file set with
+    /// `SandboxConfiguration::set_shared_memory_backing`. Each fork has
+    /// its own state for the calls its guest makes, such as the request id
+    /// of [`call_with_request_id`](Self::call_with_request_id) and its open
+    /// host stream, so forks can call host functions at the same time.
+    /// They do not have its user data, baseline or latency histograms, and
+    /// are not in its [`SandboxRegistry`](crate::sandbox::SandboxRegistry).
+    ///
+    /// This only returns an error if the sandbox could not be snapshotted;
+    /// a fork that could not be created is reported as the error of the
+    /// variant it was created for.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if the sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// sandbox.call::<()>("LoadModel", ())?;
+    ///
+    /// // Try every input against the loaded model
+    /// let scores = sandbox.speculate(["a", "b", "c"], |fork, input| {
+    ///     fork.call::<i32>("Score", input.to_string())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn speculate<Input, Output>(
+        &mut self,
+        variants: impl IntoIterator<Item = Input>,
+        run: impl Fn(&mut MultiUseSandbox, Input) -> Result<Output> + Sync,
+    ) -> Result<Vec<Result<Output>>>
+    where
+        Input: Send,
+        Output: Send,
+    {
+        if self.has_mapped_regions() {
+            return Err(new_error!(
+                "A sandbox with regions mapped into it cannot be forked"
+            ));
+        }
+        let checkpoint = self.snapshot()?;
+        let variants: Vec<Input> = variants.into_iter().collect();
+        let count = variants.len();
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(count);
+
+        #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+        let mut config = self.config;
+        #[cfg(target_os = "linux")]
+        config.clear_shared_memory_backing();
+        let fork = Fork {
+            checkpoint,
+            host_funcs: self.host_funcs.clone(),
+            config,
+        };
+        let queue = Mutex::new(variants.into_iter().enumerate());
+        let finished: Vec<Vec<(usize, Result<Output>)>> = thread::scope(|s| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| s.spawn(|| fork.run_variants(&queue, &run)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        });
+
+        let mut results: Vec<Option<Result<Output>>> = (0..count).map(|_| None).collect();
+        for (index, result) in finished.into_iter().flatten() {
+            results[index] = Some(result);
+        }
+        Ok(results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(new_error!("Variant was not run"))))
+            .collect())
+    }
+}
+
+/// What a speculation worker needs to create forks of a sandbox.
+struct Fork {
+    checkpoint: Arc<Snapshot>,
+    /// The sandbox's registry, which each fork gets a
+    /// [`fork`](FunctionRegistry::fork) of
+    host_funcs: Arc<Mutex<FunctionRegistry>>,
+    config: SandboxConfiguration,
+}
+
+impl Fork {
+    /// Runs variants taken from `queue` until it is empty, on a single
+    /// fork restored to the checkpoint before each one.
+    fn run_variants<Input, Output>(
+        &self,
+        queue: &Mutex<impl Iterator<Item = (usize, Input)>>,
+        run: &(impl Fn(&mut MultiUseSandbox, Input) -> Result<Output> + Sync),
+    ) -> Vec<(usize, Result<Output>)> {
+        let mut sandbox: Option<MultiUseSandbox> = None;
+        let mut results = Vec::new();
+        loop {
+            let Some((index, input)) = queue.lock().ok().and_then(|mut queue| queue.next()) else {
+                break;
+            };
+            let result = self
+                .ready(&mut sandbox)
+                .and_then(|sandbox| run(sandbox, input));
+            results.push((index, result));
+        }
+        results
+    }
+
+    /// Returns `sandbox` in the state of the checkpoint, creating it if
+    /// there is none yet.
+    fn ready<'a>(
+        &self,
+        sandbox: &'a mut Option<MultiUseSandbox>,
+    ) -> Result<&'a mut MultiUseSandbox> {
+        if let Some(existing) = sandbox {
+            if let Err(e) = existing.restore(self.checkpoint.clone()) {
+                // Start over with a new fork for the next variant
+                *sandbox = None;
+                return Err(e);
+            }
+        } else {
+            let host_funcs = self
+                .host_funcs
+                .lock()
+                .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+                .fork()?;
+            let mut fork = MultiUseSandbox::from_snapshot_with_registry(
+                self.checkpoint.clone(),
+                Arc::new(Mutex::new(host_funcs)),
+                self.config,
+            )?;
+            // The fork is in the state of the checkpoint
+            fork.snapshot = Some(self.checkpoint.clone());
+            *sandbox = Some(fork);
+        }
+        sandbox
+            .as_mut()
+            .ok_or_else(|| new_error!("Sandbox fork is missing"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sandbox::new_simple_guest_sandbox;
+
+    #[test]
+    fn speculate() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        sandbox.call::<i32>("AddToStatic", 5i32).unwrap();
+
+        let variants = [1, 2, -1, 3, 4, 5, 6, 7, 8];
+        let results = sandbox
+            .speculate(variants, |fork, n: i32| {
+                if n < 0 {
+                    // A failed variant does not affect the others
+                    return fork.call::<i32>("DoesNotExist", ());
+                }
+                fork.call::<i32>("AddToStatic", n)
+            })
+            .unwrap();
+
+        assert_eq!(results.len(), variants.len());
+        for (n, result) in variants.into_iter().zip(results) {
+            if n < 0 {
+                assert!(result.is_err());
+            } else {
+                // Every variant starts from the sandbox's state
+                assert_eq!(result.unwrap(), 5 + n);
+            }
+        }
+
+        // The sandbox itself is unchanged
+        assert_eq!(sandbox.call::<i32>("GetStatic", ()).unwrap(), 5);
+
+        let results = sandbox
+            .speculate(Vec::<i32>::new(), |fork, n| {
+                fork.call::<i32>("AddToStatic", n)
+            })
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn speculate_with_host_calls() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+
+        let variants: Vec<i32> = (0..16).collect();
+        let results = sandbox
+            .speculate(variants.clone(), |fork, n| {
+                // Calls HostPrint
+                let message = format!("variant {n}\n");
+                let printed = fork.call::<i32>("PrintOutput", message.clone())?;
+                assert_eq!(printed, message.len() as i32);
+                // Reads the request id from the fork's own registry
+                let request_id = format!("variant-{n}");
+                fork.call_with_request_id::<Option<String>>("GetRequestId", (), &request_id)
+            })
+            .unwrap();
+
+        for (n, result) in variants.into_iter().zip(results) {
+            assert_eq!(result.unwrap(), Some(format!("variant-{n}")));
+        }
+
+        // The sandbox's own registry is not affected by the forks
+        let id: Option<String> = sandbox.call("GetRequestId", ()).unwrap();
+        assert_eq!(id, None);
+    }
+}
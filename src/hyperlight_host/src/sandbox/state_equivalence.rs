@@ -0,0 +1,245 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Comparing the states of two sandboxes, see
+//! [`MultiUseSandbox::state_equivalent`].
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use tracing::{Span, instrument};
+
+use super::snapshot::{MemoryDiff, Snapshot};
+use crate::{MultiUseSandbox, Result};
+
+/// Where the states of two sandboxes diverge, see
+/// [`MultiUseSandbox::state_divergence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateDivergence {
+    /// The sandboxes have incompatible memory layouts, so their memory is
+    /// not compared.
+    Layout,
+    /// The vCPU state that the next guest call starts from differs: the
+    /// special registers, such as the page table root, the top of the
+    /// stack, or the entry point.
+    Registers,
+    /// These ranges of guest memory differ, in address order, with their
+    /// contents in this sandbox as `before` and in the other as `after`.
+    Memory(Vec<MemoryDiff>),
+}
+
+impl MultiUseSandbox {
+    /// Whether this sandbox and `other` are in equivalent states, so that
+    /// the same guest calls on them will behave the same.
+    ///
+    /// This is a shorthand for checking that
+    /// [`state_divergence`](Self::state_divergence) finds nothing, which
+    /// see for what is compared. It is meant for testing determinism, for
+    /// example that a sandbox restored from a snapshot matches one that
+    /// was initialised afresh and made the same calls.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if either sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{HostFunctions, MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    /// let mut restored =
+    ///     MultiUseSandbox::from_snapshot(sandbox.snapshot()?, HostFunctions::default(), None)?;
+    ///
+    /// sandbox.call::<i32>("AddToStatic", 5)?;
+    /// restored.call::<i32>("AddToStatic", 5)?;
+    /// assert!(sandbox.state_equivalent(&mut restored)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn state_equivalent(&mut self, other: &mut MultiUseSandbox) -> Result<bool> {
+        Ok(self.state_divergence(other)?.is_none())
+    }
+
+    /// Where the states of this sandbox and `other` diverge, or `None` if
+    /// they are equivalent.
+    ///
+    /// The sandboxes are compared as they would be captured in a
+    /// [`snapshot`](Self::snapshot): the guest memory captured in
+    /// snapshots, by guest virtual address, and the vCPU state that the
+    /// next guest call starts from. The general-purpose registers are not
+    /// compared, since every guest call sets them afresh, and neither are
+    /// the stack in the scratch region nor regions mapped from the host,
+    /// which snapshots do not capture. Bytes in the ranges set with
+    /// [`set_ignored_state_ranges`](Self::set_ignored_state_ranges) on
+    /// either sandbox are not compared either.
+    ///
+    /// This takes a snapshot of each sandbox that is not already captured
+    /// in one, without quiescing the guest as
+    /// [`snapshot`](Self::snapshot) does, so that the comparison does not
+    /// change the state it compares.
+    ///
+    /// ## Poisoned Sandbox
+    ///
+    /// This method will return [`crate::HyperlightError::PoisonedSandbox`] if either sandbox
+    /// is currently poisoned. Use [`restore()`](Self::restore) to recover from a poisoned state.
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn state_divergence(
+        &mut self,
+        other: &mut MultiUseSandbox,
+    ) -> Result<Option<StateDivergence>> {
+        let ours = self.current_state()?;
+        let theirs = other.current_state()?;
+
+        if !ours.layout().is_compatible_with(theirs.layout()) {
+            return Ok(Some(StateDivergence::Layout));
+        }
+        if ours.sregs() != theirs.sregs()
+            || ours.stack_top_gva() != theirs.stack_top_gva()
+            || ours.entrypoint() != theirs.entrypoint()
+        {
+            return Ok(Some(StateDivergence::Registers));
+        }
+
+        let mut ignored: Vec<Range<u64>> = self
+            .state_ignored_ranges
+            .iter()
+            .chain(&other.state_ignored_ranges)
+            .cloned()
+            .collect();
+        ignored.sort_unstable_by_key(|range| range.start);
+        let diffs: Vec<MemoryDiff> = ours
+            .diff_with_contents(&theirs)
+            .into_iter()
+            .filter(|diff| !is_ignored(diff, &ignored))
+            .collect();
+        Ok((!diffs.is_empty()).then_some(StateDivergence::Memory(diffs)))
+    }
+
+    /// Set the ranges of guest virtual memory that
+    /// [`state_divergence`](Self::state_divergence) and
+    /// [`state_equivalent`](Self::state_equivalent) ignore, replacing any
+    /// set before, such as a buffer holding a timestamp or a random seed
+    /// that is expected to differ between sandboxes.
+    pub fn set_ignored_state_ranges(&mut self, ranges: impl IntoIterator<Item = Range<u64>>) {
+        self.state_ignored_ranges = ranges.into_iter().collect();
+    }
+
+    /// A snapshot of the sandbox's current state, reusing the one it was
+    /// last captured in if it has not changed since.
+    fn current_state(&mut self) -> Result<Arc<Snapshot>> {
+        if self.poisoned {
+            return Err(crate::HyperlightError::PoisonedSandbox);
+        }
+        match &self.snapshot {
+            Some(snapshot) => Ok(snapshot.clone()),
+            None => self.take_snapshot(),
+        }
+    }
+}
+
+/// Whether every byte that differs in `diff` is in one of `ignored`, which
+/// is sorted by start address.
+fn is_ignored(diff: &MemoryDiff, ignored: &[Range<u64>]) -> bool {
+    match (&diff.before, &diff.after) {
+        (Some(before), Some(after)) => before
+            .iter()
+            .zip(after)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .all(|(offset, _)| {
+                let address = diff.guest_address + offset as u64;
+                covers(ignored, address..address + 1)
+            }),
+        // A range mapped in only one of the sandboxes differs throughout
+        _ => covers(
+            ignored,
+            diff.guest_address..diff.guest_address + diff.len as u64,
+        ),
+    }
+}
+
+/// Whether the union of `ranges`, which are sorted by start address,
+/// covers all of `range`.
+fn covers(ranges: &[Range<u64>], range: Range<u64>) -> bool {
+    let mut start = range.start;
+    for covering in ranges {
+        if start >= range.end || covering.start > start {
+            break;
+        }
+        start = start.max(covering.end);
+    }
+    start >= range.end
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::StateDivergence;
+    use crate::sandbox::new_simple_guest_sandbox;
+    use crate::{HostFunctions, MultiUseSandbox};
+
+    #[test]
+    fn state_equivalent() {
+        let mut sandbox = new_simple_guest_sandbox(None);
+        let mut restored = MultiUseSandbox::from_snapshot(
+            sandbox.snapshot().unwrap(),
+            HostFunctions::default(),
+            None,
+        )
+        .unwrap();
+        assert!(sandbox.state_equivalent(&mut restored).unwrap());
+
+        sandbox.call::<i32>("AddToStatic", 5i32).unwrap();
+        let Some(StateDivergence::Memory(diffs)) = sandbox.state_divergence(&mut restored).unwrap()
+        else {
+            panic!("expected the sandboxes' memory to diverge");
+        };
+        assert!(!diffs.is_empty());
+
+        // The same calls bring the sandboxes back in line
+        restored.call::<i32>("AddToStatic", 5i32).unwrap();
+        assert!(sandbox.state_equivalent(&mut restored).unwrap());
+
+        // Differences in ignored ranges do not count
+        sandbox.call::<i32>("AddToStatic", 1i32).unwrap();
+        let Some(StateDivergence::Memory(diffs)) = sandbox.state_divergence(&mut restored).unwrap()
+        else {
+            panic!("expected the sandboxes' memory to diverge");
+        };
+        restored.set_ignored_state_ranges(
+            diffs
+                .iter()
+                .map(|diff| diff.guest_address..diff.guest_address + diff.len as u64),
+        );
+        assert!(sandbox.state_equivalent(&mut restored).unwrap());
+    }
+
+    #[test]
+    fn covers() {
+        let ranges = [0x10..0x20, 0x18..0x30, 0x40..0x50];
+        assert!(super::covers(&ranges, 0x10..0x30));
+        assert!(super::covers(&ranges, 0x1f..0x20));
+        assert!(!super::covers(&ranges, 0x10..0x31));
+        assert!(!super::covers(&ranges, 0x28..0x48));
+        assert!(!super::covers(&[], 0x10..0x11));
+    }
+}
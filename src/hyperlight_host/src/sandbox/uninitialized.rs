@@ -14,6 +14,8 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fmt::Debug;
 use std::option::Option;
 use std::path::Path;
@@ -22,7 +24,11 @@ use std::sync::{Arc, Mutex};
 use tracing::{Span, instrument};
 use tracing_core::LevelFilter;
 
-use super::host_funcs::FunctionRegistry;
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+
+use super::host_funcs::{FunctionRegistry, UnknownHostCallPolicy};
 use super::snapshot::Snapshot;
 use super::uninitialized_evolve::evolve_impl_multi_use;
 use crate::func::host_functions::{HostFunction, register_host_function};
@@ -201,24 +207,36 @@ pub enum GuestBinary<'a> {
     Buffer(&'a [u8]),
     /// A path to the GuestBinary
     FilePath(String),
+    /// A path to the GuestBinary, which is mapped rather than read into
+    /// memory when the sandbox is created.
+    ///
+    /// Pages of the file are only read from disk as they are needed, so
+    /// parts of a large binary that are never loaded, such as its debug
+    /// information, cost neither time nor memory. The segments that are
+    /// loaded are copied into the sandbox, so the guest can never write to
+    /// the file, even through writable segments, and the mapping is
+    /// released once the sandbox has been created. The file must not be
+    /// modified or truncated until then.
+    MmapFile(String),
 }
 impl<'a> GuestBinary<'a> {
     /// If the guest binary is identified by a file, canonicalise the path
     ///
-    /// For [`GuestBinary::FilePath`], this resolves the path to its canonical
-    /// form. For [`GuestBinary::Buffer`], this method is a no-op.
+    /// For [`GuestBinary::FilePath`] and [`GuestBinary::MmapFile`], this
+    /// resolves the path to its canonical form. For [`GuestBinary::Buffer`],
+    /// this method is a no-op.
     /// TODO: Maybe we should make the GuestEnvironment or
     ///       GuestBinary constructors crate-private and turn this
     ///       into an invariant on one of those types.
     pub fn canonicalize(&mut self) -> Result<()> {
-        if let GuestBinary::FilePath(p) = self {
+        if let GuestBinary::FilePath(p) | GuestBinary::MmapFile(p) = self {
             let canon = Path::new(&p)
                 .canonicalize()
                 .map_err(|e| new_error!("GuestBinary not found: '{}': {}", p, e))?
                 .into_os_string()
                 .into_string()
                 .map_err(|e| new_error!("Error converting OsString to String: {:?}", e))?;
-            *self = GuestBinary::FilePath(canon)
+            *p = canon
         }
         Ok(())
     }
@@ -407,7 +425,7 @@ impl UninitializedSandbox {
         let env = env.into();
         #[cfg(crashdump)]
         let binary_path = match &env.guest_binary {
-            GuestBinary::FilePath(path) => Some(path.clone()),
+            GuestBinary::FilePath(path) | GuestBinary::MmapFile(path) => Some(path.clone()),
             GuestBinary::Buffer(_) => None,
         };
         let snapshot = Snapshot::from_env(env, cfg)?;
@@ -429,6 +447,25 @@ impl UninitializedSandbox {
         evolve_impl_multi_use(self)
     }
 
+    /// Checks that the guest binary can be evolved into a sandbox, without
+    /// creating the virtual machine or running any guest code.
+    ///
+    /// [`new`](Self::new) has already parsed the guest binary, checked its
+    /// hyperlight-guest-bin version and laid out memory for the configuration.
+    /// This additionally checks that the memory layout fits within the
+    /// allocated memory and that the guest's entrypoint lies within its
+    /// loaded code, returning [`GuestBinaryValidationFailed`] describing the
+    /// first problem found.
+    ///
+    /// Validation has no side effects, so the sandbox can still be evolved
+    /// afterwards. This is cheap enough to run in CI on every guest binary.
+    ///
+    /// [`GuestBinaryValidationFailed`]: crate::HyperlightError::GuestBinaryValidationFailed
+    #[instrument(err(Debug), skip_all, parent = Span::current())]
+    pub fn validate(&self) -> Result<()> {
+        self.mgr.validate()
+    }
+
     /// Map the contents of a file into the guest at a particular address.
     ///
     /// The file mapping is prepared immediately (host-side OS work) but
@@ -534,6 +571,169 @@ impl UninitializedSandbox {
         register_host_function(host_func, self, name.as_ref())
     }
 
+    /// Makes guest calls to `alias` call the guest function `target` instead.
+    ///
+    /// Aliases are resolved by the guest before it looks up the function to
+    /// call, so a guest API can be evolved (e.g. `"Echo"` to `"Echo_v2"`)
+    /// without changing its callers. The guest fetches the alias table once
+    /// while it is initialised, so aliases must be added before
+    /// [`evolve`](Self::evolve). Aliases are not resolved recursively.
+    pub fn alias_guest_function(
+        &mut self,
+        alias: impl Into<String>,
+        target: impl Into<String>,
+    ) -> Result<()> {
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .alias_guest_function(alias.into(), target.into());
+        Ok(())
+    }
+
+    /// Sets the environment variables the guest can read, replacing any set
+    /// before.
+    ///
+    /// The guest reads a variable with the built-in host function
+    /// `hl_getenv`, and enumerates them with `hl_guest_env_names`, which
+    /// `hyperlight_guest_bin::host_comm::getenv` and
+    /// `hyperlight_guest_bin::host_comm::guest_env_names` wrap. This lets
+    /// the same guest binary be configured per sandbox. The environment is
+    /// fixed once the sandbox is evolved, so a guest reads the same values
+    /// on every call, including after a snapshot is restored. It is set
+    /// here rather than on [`SandboxConfiguration`], which only holds
+    /// plain values.
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?;
+    /// sandbox.set_guest_env(HashMap::from([("MODE".to_string(), "strict".to_string())]))?;
+    /// let sandbox: MultiUseSandbox = sandbox.evolve()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_guest_env(&mut self, env: HashMap<String, String>) -> Result<()> {
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .set_guest_env(env);
+        Ok(())
+    }
+
+    /// Sets the command-line arguments the guest can read, in order,
+    /// replacing any set before. By convention the first is the name of
+    /// the program.
+    ///
+    /// The guest reads them with the built-in host function `hl_args`,
+    /// which `hyperlight_guest_bin::host_comm::guest_args` wraps, as argc
+    /// and argv would be in a POSIX program. Arguments need not be UTF-8:
+    /// each is passed to the guest as the bytes of its [`OsString`], which
+    /// on Unix are its raw bytes. Like the environment set with
+    /// [`set_guest_env`](Self::set_guest_env), the arguments are fixed once
+    /// the sandbox is evolved, and are set here rather than on
+    /// [`SandboxConfiguration`], which only holds plain values.
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?;
+    /// sandbox.set_guest_args(["grep", "-n", "needle"])?;
+    /// let sandbox: MultiUseSandbox = sandbox.evolve()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_guest_args<A: Into<OsString>>(
+        &mut self,
+        args: impl IntoIterator<Item = A>,
+    ) -> Result<()> {
+        let args = args.into_iter().map(Into::into).collect();
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .set_guest_args(args);
+        Ok(())
+    }
+
+    /// Sets what happens when the guest calls a host function that is not
+    /// registered, replacing any handler set with
+    /// [`set_unknown_host_call_handler`](Self::set_unknown_host_call_handler).
+    ///
+    /// By default the guest gets an error. With
+    /// [`UnknownHostCallPolicy::ReturnDefault`] it gets the zero value of
+    /// the type it expects instead, which lets a guest that probes for
+    /// optional host functions run against hosts that lack them. Built-in
+    /// host functions are not affected.
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_host::sandbox::UnknownHostCallPolicy;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?;
+    /// sandbox.set_unknown_host_call_policy(UnknownHostCallPolicy::ReturnDefault)?;
+    /// let sandbox: MultiUseSandbox = sandbox.evolve()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_unknown_host_call_policy(&mut self, policy: UnknownHostCallPolicy) -> Result<()> {
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .set_unknown_host_call_policy(policy);
+        Ok(())
+    }
+
+    /// Sets a host function that is called in place of any host function
+    /// the guest calls that is not registered, taking precedence over the
+    /// [`UnknownHostCallPolicy`].
+    ///
+    /// `handler` is given the name of the function the guest called, its
+    /// arguments and the type of the value the guest expects, which the
+    /// value it returns must match. An error it returns is passed to the
+    /// guest. Built-in host functions are not affected.
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # use hyperlight_common::flatbuffer_wrappers::function_types::{ReturnType, ReturnValue};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?;
+    /// sandbox.set_unknown_host_call_handler(|name, _args, return_type| {
+    ///     println!("guest called missing host function {name}");
+    ///     match return_type {
+    ///         ReturnType::Bool => Ok(ReturnValue::Bool(false)),
+    ///         _ => Err(hyperlight_host::HyperlightError::HostFunctionNotFound(name.to_string())),
+    ///     }
+    /// })?;
+    /// let sandbox: MultiUseSandbox = sandbox.evolve()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_unknown_host_call_handler(
+        &mut self,
+        handler: impl Fn(&str, Vec<ParameterValue>, ReturnType) -> Result<ReturnValue>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Result<()> {
+        self.host_funcs
+            .try_lock()
+            .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+            .set_unknown_host_call_handler(Arc::new(handler));
+        Ok(())
+    }
+
     /// Registers the special "HostPrint" function for guest printing.
     ///
     /// This overrides the default behavior of writing to stdout.
@@ -590,9 +790,10 @@ mod tests {
     use std::{fs, thread};
 
     use crossbeam_queue::ArrayQueue;
-    use hyperlight_common::flatbuffer_wrappers::function_types::{ParameterValue, ReturnValue};
+    use hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue;
     use hyperlight_testing::simple_guest_as_string;
 
+    use crate::func::params;
     use crate::sandbox::SandboxConfiguration;
     use crate::sandbox::uninitialized::{GuestBinary, GuestEnvironment};
     use crate::{MultiUseSandbox, Result, UninitializedSandbox, new_error};
@@ -622,6 +823,13 @@ mod tests {
         let sandbox = UninitializedSandbox::new(GuestBinary::FilePath(binary_path.clone()), None);
         assert!(sandbox.is_ok());
 
+        // Guest Binary mapped from path
+
+        let sandbox = UninitializedSandbox::new(GuestBinary::MmapFile(binary_path.clone()), None);
+        let mut sandbox = sandbox.unwrap().evolve().unwrap();
+        let res: String = sandbox.call("Echo", "mapped".to_string()).unwrap();
+        assert_eq!(res, "mapped");
+
         // Guest Binary does not exist at path
 
         let mut binary_path_does_not_exist = binary_path.clone();
@@ -695,7 +903,7 @@ mod tests {
 
             let res = host_funcs
                 .unwrap()
-                .call_host_function("test0", vec![ParameterValue::Int(1)])
+                .call_host_function("test0", params![1i32])
                 .unwrap();
 
             assert_eq!(res, ReturnValue::Int(2));
@@ -720,10 +928,7 @@ mod tests {
 
             let res = host_funcs
                 .unwrap()
-                .call_host_function(
-                    "test1",
-                    vec![ParameterValue::Int(1), ParameterValue::Int(2)],
-                )
+                .call_host_function("test1", params![1i32, 2i32])
                 .unwrap();
 
             assert_eq!(res, ReturnValue::Int(3));
@@ -1288,6 +1493,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate() {
+        use crate::HyperlightError;
+        use crate::sandbox::snapshot::NextAction;
+
+        let binary_path = simple_guest_as_string().unwrap();
+        let sbox =
+            UninitializedSandbox::new(GuestBinary::FilePath(binary_path.clone()), None).unwrap();
+        sbox.validate().unwrap();
+        // Validation leaves the sandbox usable
+        sbox.evolve().unwrap();
+
+        let mut sbox = UninitializedSandbox::new(GuestBinary::FilePath(binary_path), None).unwrap();
+        sbox.mgr.entrypoint = NextAction::Initialise(0);
+        let res = sbox.validate();
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestBinaryValidationFailed(msg)) if msg.contains("entrypoint")),
+            "unexpected result: {res:?}"
+        );
+    }
+
     #[test]
     fn test_invalid_path() {
         let invalid_path = "some/path/that/does/not/exist";
@@ -1504,10 +1730,7 @@ mod tests {
                 .expect("Failed to lock host funcs");
 
             let result = host_funcs
-                .call_host_function(
-                    "CustomAdd",
-                    vec![ParameterValue::Int(10), ParameterValue::Int(20)],
-                )
+                .call_host_function("CustomAdd", params![10i32, 20i32])
                 .expect("Failed to call CustomAdd");
 
             assert_eq!(result, ReturnValue::Int(30));
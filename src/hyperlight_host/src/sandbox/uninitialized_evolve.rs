@@ -15,6 +15,7 @@ limitations under the License.
 */
 #[cfg(gdb)]
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use rand::RngExt;
 use tracing::{Span, instrument};
@@ -22,7 +23,7 @@ use tracing::{Span, instrument};
 use super::SandboxConfiguration;
 #[cfg(any(crashdump, gdb))]
 use super::uninitialized::SandboxRuntimeConfig;
-use crate::hypervisor::hyperlight_vm::{HyperlightVm, HyperlightVmError};
+use crate::hypervisor::hyperlight_vm::{HyperlightVm, HyperlightVmError, InitializeError};
 use crate::mem::exe::LoadInfo;
 use crate::mem::mgr::SandboxMemoryManager;
 use crate::mem::ptr::RawPtr;
@@ -33,11 +34,12 @@ use crate::sandbox::config::DebugInfo;
 use crate::sandbox::trace::MemTraceInfo;
 #[cfg(target_os = "linux")]
 use crate::signal_handlers::setup_signal_handlers;
-use crate::{MultiUseSandbox, Result, UninitializedSandbox};
+use crate::{MultiUseSandbox, Result, UninitializedSandbox, new_error};
 
 #[instrument(err(Debug), skip_all, parent = Span::current(), level = "Trace")]
 pub(super) fn evolve_impl_multi_use(u_sbox: UninitializedSandbox) -> Result<MultiUseSandbox> {
     let (mut hshm, gshm) = u_sbox.mgr.build()?;
+    hshm.max_result_bytes = u_sbox.config.get_max_result_bytes();
 
     // Publish the HostSharedMemory for scratch so any pre-existing
     // GuestCounter can begin issuing volatile writes.
@@ -68,6 +70,17 @@ pub(super) fn evolve_impl_multi_use(u_sbox: UninitializedSandbox) -> Result<Mult
         u_sbox.load_info,
     )?;
 
+    // Let an `evolve_with_progress` callback cancel the initialisation
+    if let Some(reporter) = u_sbox
+        .host_funcs
+        .lock()
+        .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+        .init_progress
+        .as_mut()
+    {
+        reporter.set_interrupt_handle(vm.interrupt_handle());
+    }
+
     let seed = {
         let mut rng = rand::rng();
         rng.random::<u64>()
@@ -118,6 +131,14 @@ pub(super) fn evolve_impl_multi_use(u_sbox: UninitializedSandbox) -> Result<Mult
         hshm.write_file_mapping_entry(prepared.guest_base, prepared.size as u64, &prepared.label)?;
     }
 
+    let has_aliases = u_sbox
+        .host_funcs
+        .lock()
+        .map_err(|e| new_error!("Error locking at {}:{}: {}", file!(), line!(), e))?
+        .has_guest_function_aliases();
+    hshm.set_guest_function_aliases(has_aliases)?;
+
+    let init_start = Instant::now();
     vm.initialise(
         peb_addr,
         seed,
@@ -128,18 +149,23 @@ pub(super) fn evolve_impl_multi_use(u_sbox: UninitializedSandbox) -> Result<Mult
         #[cfg(gdb)]
         dbg_mem_access_hdl,
     )
-    .map_err(HyperlightVmError::Initialize)?;
+    .map_err(InitializeError::promote)?;
+    let init_duration = init_start.elapsed();
 
     #[cfg(gdb)]
     let dbg_mem_wrapper = Arc::new(Mutex::new(hshm.clone()));
 
-    Ok(MultiUseSandbox::from_uninit(
+    let mut sbox = MultiUseSandbox::from_uninit(
         u_sbox.host_funcs,
         hshm,
         vm,
+        u_sbox.config,
+        init_duration,
         #[cfg(gdb)]
         dbg_mem_wrapper,
-    ))
+    );
+    sbox.capture_baseline(None)?;
+    Ok(sbox)
 }
 
 pub(crate) fn set_up_hypervisor_partition(
@@ -0,0 +1,144 @@
+/*
+Copyright 2025  The Hyperlight Authors.
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Arbitrary host data attached to a sandbox, see
+//! [`MultiUseSandbox::set_user_data`].
+
+use std::any::Any;
+
+use crate::MultiUseSandbox;
+
+impl MultiUseSandbox {
+    /// Attach `value` to this sandbox, replacing any value attached before,
+    /// for example to keep track of the request or tenant it serves without
+    /// a separate map.
+    ///
+    /// The value belongs to the host and is never visible to the guest. It
+    /// is kept across guest calls, snapshots and restores, and is only
+    /// dropped when it is replaced, taken, or the sandbox is dropped, or by
+    /// [`reset_to_baseline`](Self::reset_to_baseline) if
+    /// [`clear_user_data_on_reset`](Self::clear_user_data_on_reset) was
+    /// enabled.
+    ///
+    /// `T` must be [`Send`] because the sandbox is, so the value moves
+    /// between threads with it. It need not be [`Sync`]: the sandbox is not,
+    /// so the value is only ever accessed by the thread that owns the
+    /// sandbox.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hyperlight_host::{MultiUseSandbox, UninitializedSandbox, GuestBinary};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// struct Tenant {
+    ///     id: u64,
+    /// }
+    ///
+    /// let mut sandbox: MultiUseSandbox = UninitializedSandbox::new(
+    ///     GuestBinary::FilePath("guest.bin".into()),
+    ///     None
+    /// )?.evolve()?;
+    ///
+    /// sandbox.set_user_data(Tenant { id: 42 });
+    /// // ... later, wherever the sandbox ends up ...
+    /// if let Some(tenant) = sandbox.user_data::<Tenant>() {
+    ///     println!("sandbox serves tenant {}", tenant.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_user_data<T: Any + Send>(&mut self, value: T) {
+        self.user_data = Some(Box::new(value));
+    }
+
+    /// The value attached with [`set_user_data`](Self::set_user_data), or
+    /// `None` if there is none or it is not a `T`.
+    pub fn user_data<T: Any + Send>(&self) -> Option<&T> {
+        self.user_data.as_ref()?.downcast_ref()
+    }
+
+    /// The value attached with [`set_user_data`](Self::set_user_data), for
+    /// modification, or `None` if there is none or it is not a `T`.
+    pub fn user_data_mut<T: Any + Send>(&mut self) -> Option<&mut T> {
+        self.user_data.as_mut()?.downcast_mut()
+    }
+
+    /// Detach and return the value attached with
+    /// [`set_user_data`](Self::set_user_data), or return `None` and leave it
+    /// attached if there is none or it is not a `T`.
+    pub fn take_user_data<T: Any + Send>(&mut self) -> Option<T> {
+        if !self.user_data.as_ref()?.is::<T>() {
+            return None;
+        }
+        let value = self.user_data.take()?.downcast().ok()?;
+        Some(*value)
+    }
+
+    /// Whether [`reset_to_baseline`](Self::reset_to_baseline) also drops the
+    /// value attached with [`set_user_data`](Self::set_user_data), so that
+    /// data about a request cannot outlive it. This is off by default.
+    pub fn clear_user_data_on_reset(&mut self, clear: bool) {
+        self.clear_user_data_on_reset = clear;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sandbox::{SandboxConfiguration, new_simple_guest_sandbox};
+
+    #[derive(Debug, PartialEq)]
+    struct Tenant(u64);
+
+    #[test]
+    fn user_data() {
+        let mut config = SandboxConfiguration::default();
+        config.set_capture_baseline(true);
+        let mut sandbox = new_simple_guest_sandbox(Some(config));
+        assert_eq!(sandbox.user_data::<Tenant>(), None);
+
+        sandbox.set_user_data(Tenant(1));
+        assert_eq!(sandbox.user_data::<u64>(), None);
+        sandbox.user_data_mut::<Tenant>().unwrap().0 += 1;
+
+        // Kept across calls, snapshots and resets
+        let snapshot = sandbox.snapshot().unwrap();
+        let _: i32 = sandbox.call("AddToStatic", 5i32).unwrap();
+        sandbox.restore(snapshot).unwrap();
+        sandbox.reset_to_baseline().unwrap();
+        assert_eq!(sandbox.user_data::<Tenant>(), Some(&Tenant(2)));
+
+        assert_eq!(sandbox.take_user_data::<u64>(), None);
+        assert_eq!(sandbox.take_user_data::<Tenant>(), Some(Tenant(2)));
+        assert_eq!(sandbox.user_data::<Tenant>(), None);
+    }
+
+    #[test]
+    fn clear_user_data_on_reset() {
+        let mut config = SandboxConfiguration::default();
+        config.set_capture_baseline(true);
+        let mut sandbox = new_simple_guest_sandbox(Some(config));
+        sandbox.clear_user_data_on_reset(true);
+        sandbox.set_user_data(Tenant(1));
+
+        let snapshot = sandbox.snapshot().unwrap();
+        sandbox.restore(snapshot).unwrap();
+        assert_eq!(sandbox.user_data::<Tenant>(), Some(&Tenant(1)));
+
+        sandbox.reset_to_baseline().unwrap();
+        assert_eq!(sandbox.user_data::<Tenant>(), None);
+    }
+}
@@ -18,11 +18,14 @@ use std::sync::{Arc, Barrier};
 use std::thread;
 use std::time::Duration;
 
+use hyperlight_common::flatbuffer_wrappers::function_types::ReturnValue;
 use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
 use hyperlight_common::log_level::GuestLogFilter;
-use hyperlight_host::sandbox::SandboxConfiguration;
-use hyperlight_host::{HyperlightError, MultiUseSandbox};
+use hyperlight_common::outb::{ABI_VERSION, OutBAction};
+use hyperlight_host::sandbox::{AllocMode, DomainError, FpExceptions, SandboxConfiguration};
+use hyperlight_host::{GuestBinary, HyperlightError, MultiUseSandbox, UninitializedSandbox};
 use hyperlight_testing::simplelogger::{LOGGER, SimpleLogger};
+use hyperlight_testing::tracing_subscriber::TracingSubscriber;
 use serial_test::serial;
 use tracing_core::LevelFilter;
 
@@ -379,6 +382,99 @@ fn interrupt_spamming_host_call() {
     });
 }
 
+#[test]
+fn call_with_timeout() {
+    with_rust_sandbox(|mut sbox| {
+        let res = sbox
+            .call_with_timeout::<()>("Spin", (), Duration::from_millis(100))
+            .unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::GuestCallTimedOut {
+                    partial_result: None,
+                    ..
+                }
+            ),
+            "unexpected error: {res:?}"
+        );
+        assert!(sbox.poisoned());
+    });
+
+    // A call that completes in time is unaffected by the timeout
+    with_rust_sandbox(|mut sbox| {
+        let res: String = sbox
+            .call_with_timeout("Echo", "hello".to_string(), Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(res, "hello");
+    });
+}
+
+#[test]
+fn call_with_timeout_partial_result() {
+    with_rust_sandbox(|mut sbox| {
+        let res = sbox
+            .call_with_timeout_partial::<String>(
+                "PublishPartialResult",
+                ("partial".to_string(), "final".to_string(), true),
+                Duration::from_millis(100),
+            )
+            .unwrap_err();
+        match res {
+            HyperlightError::GuestCallTimedOut {
+                partial_result: Some(ReturnValue::String(partial)),
+                ..
+            } => assert_eq!(partial, "partial"),
+            e => panic!("unexpected error: {e:?}"),
+        }
+    });
+
+    // Partial results are discarded when the guest function completes
+    with_rust_sandbox(|mut sbox| {
+        for _ in 0..3 {
+            let res: String = sbox
+                .call_with_timeout_partial(
+                    "PublishPartialResult",
+                    ("partial".to_string(), "final".to_string(), false),
+                    Duration::from_secs(10),
+                )
+                .unwrap();
+            assert_eq!(res, "final");
+        }
+    });
+
+    // Each partial result replaces the one before it, so publishing many
+    // more than fit in the output buffer at once is fine
+    with_rust_sandbox(|mut sbox| {
+        let count = 10_000u32;
+        let res = sbox
+            .call_with_timeout_partial::<u32>(
+                "PublishPartialResults",
+                (count, true),
+                Duration::from_millis(500),
+            )
+            .unwrap_err();
+        match res {
+            HyperlightError::GuestCallTimedOut {
+                partial_result: Some(ReturnValue::UInt(partial)),
+                ..
+            } => assert_eq!(partial, count - 1),
+            e => panic!("unexpected error: {e:?}"),
+        }
+    });
+    with_rust_sandbox(|mut sbox| {
+        let count = 10_000u32;
+        let res: u32 = sbox
+            .call_with_timeout_partial(
+                "PublishPartialResults",
+                (count, false),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        assert_eq!(res, count);
+    });
+}
+
 #[test]
 fn print_four_args_c_guest() {
     with_c_sandbox(|mut sbox1| {
@@ -390,6 +486,67 @@ fn print_four_args_c_guest() {
     });
 }
 
+// Checks that `hl_param_bytes_copy` copies byte parameters and rejects
+// anything else.
+#[test]
+fn param_bytes_copy_c_guest() {
+    // The `PARAM_ERROR_*` values of `hyperlight_guest_capi`
+    const INDEX_OUT_OF_RANGE: i32 = -1;
+    const NOT_BYTES: i32 = -2;
+    const BUFFER_TOO_SMALL: i32 = -3;
+
+    with_c_sandbox(|mut sbox1| {
+        let data = vec![1u8, 2, 3, 4, 5, 6];
+        let mut copy = |index: i32, dst_len: i32| {
+            sbox1
+                .call::<i32>("CopyBytesParam", (data.clone(), index, dst_len))
+                .unwrap()
+        };
+        assert_eq!(copy(0, 6), 6);
+        assert_eq!(copy(0, 64), 6);
+        assert_eq!(copy(0, 5), BUFFER_TOO_SMALL);
+        // The second parameter is an `Int`
+        assert_eq!(copy(1, 64), NOT_BYTES);
+        assert_eq!(copy(3, 64), INDEX_OUT_OF_RANGE);
+    });
+}
+
+// Checks that a C guest can log under its own target through `hl_log_target`.
+#[test]
+fn log_message_with_target_c_guest() {
+    let subscriber = TracingSubscriber::new(tracing::Level::WARN);
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        with_c_uninit_sandbox(|mut sbox| {
+            sbox.set_max_guest_log_level(LevelFilter::WARN);
+            let mut sbox1 = sbox.evolve().unwrap();
+            subscriber.clear();
+
+            let res = sbox1.call::<i32>(
+                "LogMessageWithTarget",
+                (
+                    "c_guest::net".to_string(),
+                    "hello from the network stack".to_string(),
+                    log::Level::Warn as i64,
+                ),
+            );
+            assert!(matches!(&res, Ok(-1)), "unexpected result: {res:?}");
+
+            subscriber.test_trace_records(|_, events| {
+                let guest_events: Vec<_> = events
+                    .iter()
+                    .map(|event| &event["event"])
+                    .filter(|event| event["metadata"]["target"] == "hyperlight_guest")
+                    .collect();
+                assert_eq!(guest_events.len(), 1, "unexpected events: {events:?}");
+                let event = guest_events[0];
+                assert_eq!(event["metadata"]["level"], "WARN");
+                assert_eq!(event["message"], "hello from the network stack");
+                assert_eq!(event["guest_target"], "c_guest::net");
+            });
+        });
+    });
+}
+
 // Checks that guest can abort with a specific code.
 #[test]
 fn guest_abort() {
@@ -482,6 +639,37 @@ fn guest_abort_c_guest() {
     });
 }
 
+// Checks that a failed HL_ASSERT in a c guest is reported with its expression and location.
+#[test]
+fn guest_assertion_failed_c_guest() {
+    with_c_sandbox(|mut sbox1| {
+        let res = sbox1.call::<i32>("GuestAssert", 42_i32);
+        assert!(matches!(&res, Ok(42)), "unexpected result: {res:?}");
+
+        let res = sbox1.call::<i32>("GuestAssert", 7_i32).unwrap_err();
+        assert!(
+            matches!(&res, HyperlightError::GuestAssertionFailed { expression, file, line } if expression == "value == 42" && file.ends_with("main.c") && *line > 0),
+            "unexpected error: {res:?}"
+        );
+        assert!(sbox1.poisoned());
+    });
+}
+
+// Checks that a panic handler installed by a c guest chooses the code the guest aborts with.
+#[test]
+fn guest_panic_handler_c_guest() {
+    with_c_sandbox(|mut sbox1| {
+        let res = sbox1
+            .call::<i32>("GuestPanicWithHandler", 42_i32)
+            .unwrap_err();
+        assert!(
+            matches!(&res, HyperlightError::GuestAborted(code, message) if *code == 42 && message.contains("Failed to convert CStr")),
+            "unexpected error: {res:?}"
+        );
+        assert!(sbox1.poisoned());
+    });
+}
+
 #[test]
 fn guest_panic() {
     // this test is rust-specific
@@ -578,6 +766,43 @@ fn guest_outb_with_invalid_port_poisons_sandbox() {
     });
 }
 
+/// Test that a guest reporting a different ABI version is rejected, and
+/// that the sandbox is poisoned.
+#[test]
+fn guest_abi_version_mismatch() {
+    with_rust_sandbox(|mut sbox| {
+        let res = sbox.call::<()>(
+            "OutbWithPort",
+            (OutBAction::AbiVersion as u32, ABI_VERSION + 1),
+        );
+        assert!(
+            matches!(
+                res,
+                Err(HyperlightError::AbiMismatch { host, guest })
+                    if host == ABI_VERSION && guest == ABI_VERSION + 1
+            ),
+            "unexpected result: {res:?}"
+        );
+        assert!(sbox.poisoned());
+    });
+}
+
+/// Test that a guest which initialises without reporting its ABI version
+/// is rejected.
+#[cfg_attr(feature = "hw-interrupts", ignore)]
+#[test]
+fn guest_abi_version_missing() {
+    let path = hyperlight_testing::dummy_guest_as_string().unwrap();
+    let res = UninitializedSandbox::new(GuestBinary::FilePath(path), None)
+        .unwrap()
+        .evolve();
+    assert!(
+        matches!(res, Err(HyperlightError::AbiVersionMissing { host }) if host == ABI_VERSION),
+        "unexpected result: {:?}",
+        res.err()
+    );
+}
+
 #[test]
 fn corrupt_output_size_prefix_rejected() {
     with_rust_sandbox(|mut sbox| {
@@ -638,6 +863,273 @@ fn guest_panic_no_alloc() {
     });
 }
 
+#[test]
+fn guest_allocation_limit() {
+    const LIMIT: u64 = 0x4000;
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_heap_size(0x100000);
+    cfg.set_max_alloc_per_call(LIMIT);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        let snapshot = sbox.snapshot().unwrap();
+
+        // Allocations within the limit succeed
+        let res: i32 = sbox.call("CallMalloc", (LIMIT / 2) as i32).unwrap();
+        assert_eq!(res, (LIMIT / 2) as i32);
+
+        // A runaway allocator is stopped at the limit rather than running
+        // the heap dry
+        let res = sbox.call::<()>("ExhaustHeap", ()).unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::GuestAllocationLimitExceeded {
+                    requested: 1,
+                    limit: LIMIT
+                }
+            ),
+            "unexpected error: {res:?}"
+        );
+        assert!(sbox.poisoned());
+
+        sbox.restore(snapshot.clone()).unwrap();
+        let res = sbox
+            .call::<i32>("CallMalloc", (LIMIT * 2) as i32)
+            .unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::GuestAllocationLimitExceeded { requested, limit: LIMIT } if *requested == LIMIT * 2
+            ),
+            "unexpected error: {res:?}"
+        );
+
+        // Each call gets a fresh budget
+        sbox.restore(snapshot).unwrap();
+        for _ in 0..4 {
+            sbox.call::<i32>("CallMalloc", (LIMIT / 2) as i32).unwrap();
+        }
+    });
+}
+
+#[test]
+fn self_modifying_code_detection() {
+    // Without detection, the guest can rewrite and run its own code
+    let mut address = 0;
+    with_rust_sandbox(|mut sbox| {
+        address = sbox.call("RunRewrittenCode", ()).unwrap();
+        let again: u64 = sbox.call("RunRewrittenCode", ()).unwrap();
+        assert_eq!(again, address);
+    });
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_self_modifying_code_detection(true);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        let snapshot = sbox.snapshot().unwrap();
+
+        // Calls that only write data are unaffected
+        sbox.call::<i32>("AddToStatic", 1).unwrap();
+
+        let res = sbox.call::<u64>("RunRewrittenCode", ()).unwrap_err();
+        let HyperlightError::SelfModifyingCode { address: executed } = res else {
+            panic!("unexpected error: {res:?}");
+        };
+        // The guest faults on the first instruction it fetches from the
+        // written page, which is either the rewritten function itself or
+        // the code following the write, if that shares its page
+        assert_eq!(executed & !0xfff, address & !0xfff);
+        assert!(sbox.poisoned());
+
+        sbox.restore(snapshot).unwrap();
+        sbox.call::<i32>("AddToStatic", 1).unwrap();
+    });
+}
+
+#[test]
+fn fp_exception_mask() {
+    // By default, floating-point exceptions produce IEEE 754 results
+    with_rust_sandbox(|mut sbox| {
+        let res: f64 = sbox.call("DivideDouble", (1.0_f64, 0.0_f64)).unwrap();
+        assert_eq!(res, f64::INFINITY);
+        let res: f64 = sbox.call("DivideDouble", (0.0_f64, 0.0_f64)).unwrap();
+        assert!(res.is_nan());
+    });
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_fp_exception_mask(FpExceptions::all() - FpExceptions::DIVIDE_BY_ZERO);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        let snapshot = sbox.snapshot().unwrap();
+
+        let res: f64 = sbox.call("DivideDouble", (1.0_f64, 4.0_f64)).unwrap();
+        assert_eq!(res, 0.25);
+        // An invalid operation is still masked
+        let res: f64 = sbox.call("DivideDouble", (0.0_f64, 0.0_f64)).unwrap();
+        assert!(res.is_nan());
+
+        let res = sbox
+            .call::<f64>("DivideDouble", (1.0_f64, 0.0_f64))
+            .unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::GuestFpException { exceptions, mxcsr, .. }
+                    if *exceptions == FpExceptions::DIVIDE_BY_ZERO
+                        && FpExceptions::masked_in(*mxcsr) == FpExceptions::all() - FpExceptions::DIVIDE_BY_ZERO
+            ),
+            "unexpected error: {res:?}"
+        );
+        assert!(sbox.poisoned());
+
+        // The exception still traps after the sandbox is restored
+        sbox.restore(snapshot).unwrap();
+        let res = sbox
+            .call::<f64>("DivideDouble", (1.0_f64, 0.0_f64))
+            .unwrap_err();
+        assert!(
+            matches!(res, HyperlightError::GuestFpException { .. }),
+            "unexpected error: {res:?}"
+        );
+    });
+}
+
+#[test]
+fn guest_bump_allocator() {
+    const HEAP_SIZE: u64 = 0x100000;
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_heap_size(HEAP_SIZE);
+    cfg.set_guest_allocator(AllocMode::Bump);
+    cfg.set_capture_baseline(true);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        // Freeing the last allocation reclaims it, so calls that free what
+        // they allocate can allocate more than the heap in total
+        for _ in 0..16 {
+            let res: i32 = sbox.call("CallMalloc", (HEAP_SIZE / 8) as i32).unwrap();
+            assert_eq!(res, (HEAP_SIZE / 8) as i32);
+        }
+
+        let res = sbox.call::<()>("ExhaustHeap", ()).unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::GuestAborted(_, msg) if msg.contains("memory allocation of ")
+            ),
+            "unexpected error: {res:?}"
+        );
+
+        // Resetting the sandbox resets the bump pointer with the heap
+        sbox.reset_to_baseline().unwrap();
+        let res: i32 = sbox.call("CallMalloc", (HEAP_SIZE / 2) as i32).unwrap();
+        assert_eq!(res, (HEAP_SIZE / 2) as i32);
+    });
+}
+
+#[test]
+fn host_call_limit() {
+    const LIMIT: u64 = 3;
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_max_host_calls_per_call(LIMIT);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        let snapshot = sbox.snapshot().unwrap();
+
+        // Each call can make up to the limit of host calls
+        for _ in 0..2 {
+            let res: i32 = sbox
+                .call_with_callback("SumCallback", LIMIT as i32, |i: i32| i)
+                .unwrap();
+            assert_eq!(res, 3);
+        }
+
+        // The call that makes one host call too many is stopped
+        let mut calls = 0;
+        let res = sbox
+            .call_with_callback::<i32, _, _>("SumCallback", LIMIT as i32 + 1, |i: i32| {
+                calls += 1;
+                i
+            })
+            .unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::HostCallLimitExceeded { limit: LIMIT }
+            ),
+            "unexpected error: {res:?}"
+        );
+        assert_eq!(calls, LIMIT);
+        assert!(sbox.poisoned());
+
+        sbox.restore(snapshot).unwrap();
+        sbox.call::<i32>("PrintOutput", "Hello".to_string())
+            .unwrap();
+    });
+}
+
+#[test]
+fn result_size_limit() {
+    const LIMIT: u64 = 4096;
+
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_max_result_bytes(LIMIT);
+    with_rust_sandbox_cfg(cfg, |mut sbox| {
+        let res: Vec<u8> = sbox.call("ReturnBuffer", 1024_u32).unwrap();
+        assert_eq!(res, vec![0xAB; 1024]);
+
+        // The encoded result is larger than the buffer it carries
+        let res = sbox.call::<Vec<u8>>("ReturnBuffer", 8192_u32).unwrap_err();
+        assert!(
+            matches!(
+                &res,
+                HyperlightError::ResultTooLarge { size, limit: 4096 } if *size > 8192
+            ),
+            "unexpected error: {res:?}"
+        );
+        let mut buf = Vec::new();
+        let res = sbox.call_into::<Vec<u8>>("ReturnBuffer", 8192_u32, &mut buf);
+        assert!(
+            matches!(&res, Err(HyperlightError::ResultTooLarge { .. })),
+            "unexpected result: {res:?}"
+        );
+
+        // The guest completed the calls, so the sandbox is still usable
+        assert!(!sbox.poisoned());
+        let res: Vec<u8> = sbox.call("ReturnBuffer", 16_u32).unwrap();
+        assert_eq!(res, vec![0xAB; 16]);
+    });
+}
+
+#[test]
+fn call_status_c_guest() {
+    with_c_sandbox(|mut sbox1| {
+        assert_eq!(sbox1.call_status("CheckEven", 4_i32).unwrap(), 0);
+        assert_eq!(sbox1.call_status("CheckEven", 3_i32).unwrap(), 1);
+
+        // A failing call is an error rather than a status
+        let res = sbox1.call_status("NoSuchFunction", ()).unwrap_err();
+        assert!(
+            matches!(&res, HyperlightError::GuestError(code, _) if *code == ErrorCode::GuestFunctionNotFound),
+            "unexpected error: {res:?}"
+        );
+    });
+}
+
+#[test]
+fn domain_error_c_guest() {
+    with_c_sandbox(|mut sbox| {
+        let res = sbox.call_or_domain_error::<i32, DomainError>("CheckedDivide", (7_i32, 2_i32));
+        assert_eq!(res.unwrap(), Ok(3));
+        let res = sbox.call_or_domain_error::<i32, DomainError>("CheckedDivide", (7_i32, 0_i32));
+        assert_eq!(
+            res.unwrap(),
+            Err(DomainError::new(0, Some("division by zero".to_string())))
+        );
+        let res =
+            sbox.call_or_domain_error::<i32, DomainError>("CheckedDivide", (i32::MIN, -1_i32));
+        assert_eq!(res.unwrap(), Err(DomainError::new(1, None)));
+        assert!(!sbox.poisoned());
+    });
+}
+
 // Tests libc alloca
 #[test]
 fn dynamic_stack_allocate_c_guest() {
@@ -697,6 +1189,47 @@ fn guard_page_check_2() {
     });
 }
 
+#[test]
+fn configured_stack_size() {
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_stack_size(0x8000);
+    with_rust_sandbox_cfg(cfg, |mut sbox1| {
+        // Fits within the configured stack
+        sbox1.call::<i32>("StackOverflow", 1_i32).unwrap();
+
+        // Needs roughly 64KiB of stack, which is more than was configured but
+        // would fit in the default scratch region
+        let res = sbox1.call::<i32>("StackOverflow", 8_i32).unwrap_err();
+        assert!(
+            matches!(&res, HyperlightError::GuestAborted(code, msg) if *code == ErrorCode::GuestError as u8 && msg.contains("PageFault")),
+            "unexpected error: {res:?}"
+        );
+    });
+}
+
+#[test]
+fn shared_ring_round_trip() {
+    let mut cfg = SandboxConfiguration::default();
+    cfg.set_shared_ring_size(0x4000);
+    with_rust_sandbox_cfg(cfg, |mut sbox1| {
+        let ring = sbox1.shared_ring().unwrap().unwrap();
+        assert_eq!(ring.pop().unwrap(), None);
+
+        ring.push(b"first").unwrap();
+        ring.push(b"second").unwrap();
+        let echoed: u32 = sbox1.call("EchoSharedRing", ()).unwrap();
+        assert_eq!(echoed, 2);
+
+        assert_eq!(ring.pop().unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(ring.pop().unwrap().as_deref(), Some(&b"second"[..]));
+        assert_eq!(ring.pop().unwrap(), None);
+    });
+
+    with_rust_sandbox(|sbox1| {
+        assert!(sbox1.shared_ring().unwrap().is_none());
+    });
+}
+
 #[test]
 fn execute_on_heap() {
     with_rust_sandbox(|mut sbox1| {
@@ -828,6 +1361,51 @@ fn log_test_messages(levelfilter: Option<tracing_core::LevelFilter>) {
     }
 }
 
+// Check that debug builds of the C API log a warning for an FfiVec that a guest function leaks.
+// This test is ignored as it sets a logger, see `log_message`.
+// It can be run explicitly with `cargo test --test integration_test leaked_ffi_vec_is_logged_c_guest -- --ignored`
+#[test]
+#[ignore]
+#[cfg(debug_assertions)]
+fn leaked_ffi_vec_is_logged_c_guest() {
+    SimpleLogger::initialize_test_logger();
+    with_c_uninit_sandbox(|mut sbox| {
+        sbox.set_max_guest_log_level(LevelFilter::WARN);
+        let mut sbox1 = sbox.evolve().unwrap();
+
+        LOGGER.clear_log_calls();
+        let res = sbox1.call::<i32>("LeakResult", 42).unwrap();
+        assert_eq!(res, 42);
+
+        LOGGER.test_log_records(|calls| {
+            let leaks: Vec<_> = calls
+                .iter()
+                .filter(|call| call.args.contains("leaked an FfiVec"))
+                .collect();
+            assert_eq!(leaks.len(), 1, "unexpected log records: {calls:?}");
+            assert_eq!(leaks[0].level, log::Level::Warn);
+            assert!(
+                leaks[0].args.contains("Guest function LeakResult"),
+                "unexpected message: {}",
+                leaks[0].args
+            );
+        });
+
+        // Results that are returned are freed, so nothing is reported for them
+        sbox1
+            .call::<i32>("CopyBytesParam", (vec![1u8], 0, 1))
+            .unwrap();
+        LOGGER.test_log_records(|calls| {
+            assert!(
+                !calls
+                    .iter()
+                    .any(|call| call.args.contains("leaked an FfiVec")),
+                "unexpected log records: {calls:?}"
+            );
+        });
+    });
+}
+
 /// Tests whether host is able to return Bool as return type
 /// or not
 #[test]
@@ -1872,3 +2450,40 @@ fn hw_timer_interrupts() {
         );
     });
 }
+
+/// Test that an interrupt raised by the host is delivered to the guest's
+/// handler once the next guest call enables interrupts, and that the guest
+/// carries on running afterwards.
+#[test]
+#[cfg(feature = "hw-interrupts")]
+fn raise_guest_interrupt() {
+    with_rust_sandbox(|mut sbox| {
+        sbox.raise_guest_interrupt(0x40).unwrap();
+        let count: i32 = sbox
+            .call("CountRaisedInterrupts", (0x40_i32, 100_000_000_i32))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // Nothing is pending once the interrupt has been delivered
+        let count: i32 = sbox
+            .call("CountRaisedInterrupts", (0x40_i32, 1_000_000_i32))
+            .unwrap();
+        assert_eq!(count, 0);
+
+        // Exception, PIC and spurious vectors cannot be raised
+        for vector in [0x0e, 0x20, 0x2f, 0xff] {
+            let err = sbox.raise_guest_interrupt(vector).unwrap_err();
+            assert!(matches!(err, HyperlightError::InvalidInterruptVector(v) if v == vector));
+        }
+        assert!(!sbox.poisoned());
+    });
+}
+
+#[test]
+#[cfg(not(feature = "hw-interrupts"))]
+fn raise_guest_interrupt_unsupported() {
+    with_rust_sandbox(|mut sbox| {
+        let err = sbox.raise_guest_interrupt(0x40).unwrap_err();
+        assert!(matches!(err, HyperlightError::Unsupported { .. }));
+    });
+}
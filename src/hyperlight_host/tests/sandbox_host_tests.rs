@@ -14,10 +14,15 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 use core::f64;
+use std::collections::HashMap;
 use std::sync::mpsc::channel;
 use std::sync::{Arc, Mutex};
 
-use hyperlight_host::sandbox::SandboxConfiguration;
+use hyperlight_common::flatbuffer_wrappers::function_types::{
+    ParameterValue, ReturnType, ReturnValue,
+};
+use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_host::sandbox::{HostLink, SandboxConfiguration, UnknownHostCallPolicy, reentrant};
 use hyperlight_host::{
     GuestBinary, HyperlightError, MultiUseSandbox, Result, UninitializedSandbox, new_error,
 };
@@ -25,8 +30,8 @@ use hyperlight_testing::simple_guest_as_string;
 
 pub mod common; // pub to disable dead_code warning
 use crate::common::{
-    with_all_sandboxes, with_all_sandboxes_cfg, with_all_sandboxes_with_writer,
-    with_all_uninit_sandboxes,
+    new_rust_sandbox, new_rust_uninit_sandbox, with_all_sandboxes, with_all_sandboxes_cfg,
+    with_all_sandboxes_with_writer, with_all_uninit_sandboxes, with_c_uninit_sandbox,
 };
 
 #[test]
@@ -209,6 +214,143 @@ fn incorrect_parameter_num() {
     });
 }
 
+#[test]
+fn default_parameter_values() {
+    with_all_sandboxes(|mut sandbox| {
+        let res: i32 = sandbox.call("AddWithDefault", (1_i32, 2_i32)).unwrap();
+        assert_eq!(res, 3);
+        let res: i32 = sandbox.call("AddWithDefault", 1_i32).unwrap();
+        assert_eq!(res, 11);
+
+        let res = sandbox.call::<i32>("AddWithDefault", ());
+        assert!(matches!(
+            res.unwrap_err(),
+            HyperlightError::GuestError(
+                hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode::GuestFunctionIncorrecNoOfParameters,
+                msg
+            ) if msg == "Called function AddWithDefault with 0 parameters but it takes 1 to 2."
+        ));
+    });
+}
+
+#[test]
+fn optional_parameter_values() {
+    with_all_sandboxes(|mut sandbox| {
+        let res: i32 = sandbox.call("AddOptional", (1_i32, Some(2_i32))).unwrap();
+        assert_eq!(res, 3);
+        let res: i32 = sandbox.call("AddOptional", (1_i32, None::<i32>)).unwrap();
+        assert_eq!(res, 1);
+
+        let res = sandbox.call::<i32>("AddOptional", (1_i32, 2_i32));
+        assert!(matches!(
+            res.unwrap_err(),
+            HyperlightError::GuestError(ErrorCode::GuestFunctionParameterTypeMismatch, _)
+        ));
+    });
+}
+
+#[test]
+fn map_parameter_values() {
+    with_all_sandboxes(|mut sandbox| {
+        let map = vec![
+            (
+                "name".to_string(),
+                ParameterValue::String("widget".to_string()),
+            ),
+            ("count".to_string(), ParameterValue::Int(3)),
+            ("count".to_string(), ParameterValue::Int(4)),
+            (
+                "nested".to_string(),
+                ParameterValue::Map(vec![("count".to_string(), ParameterValue::Int(5))]),
+            ),
+        ];
+        let res: i32 = sandbox
+            .call("LookupMapInt", (map.clone(), "count".to_string()))
+            .unwrap();
+        assert_eq!(res, 3);
+        let res: i32 = sandbox
+            .call("LookupMapInt", (map.clone(), "name".to_string()))
+            .unwrap();
+        assert_eq!(res, -1);
+        let res: i32 = sandbox
+            .call("LookupMapInt", (Vec::new(), "count".to_string()))
+            .unwrap();
+        assert_eq!(res, -1);
+
+        let res = sandbox.call::<i32>("LookupMapInt", ("count".to_string(), "count".to_string()));
+        assert!(matches!(
+            res.unwrap_err(),
+            HyperlightError::GuestError(ErrorCode::GuestFunctionParameterTypeMismatch, _)
+        ));
+    });
+}
+
+#[test]
+fn multi_buffer_return_value() {
+    with_all_sandboxes(|mut sandbox| {
+        let res: Vec<Vec<u8>> = sandbox
+            .call("SplitBuffer", (b"result+log".to_vec(), 6i32))
+            .unwrap();
+        assert_eq!(res, [b"result".to_vec(), b"+log".to_vec()]);
+
+        // Empty buffers are kept, so the count is always preserved
+        let res: Vec<Vec<u8>> = sandbox
+            .call("SplitBuffer", (b"all".to_vec(), 3i32))
+            .unwrap();
+        assert_eq!(res, [b"all".to_vec(), Vec::new()]);
+        let res: Vec<Vec<u8>> = sandbox
+            .call("SplitBuffer", (Vec::<u8>::new(), 0i32))
+            .unwrap();
+        assert_eq!(res, [Vec::<u8>::new(), Vec::new()]);
+    });
+}
+
+#[test]
+fn status_with_payload_return_value() {
+    with_all_sandboxes(|mut sandbox| {
+        let res = sandbox
+            .call_status_payload("EchoStatusWithPayload", (-5i32, b"payload".to_vec()))
+            .unwrap();
+        assert_eq!(res, (-5, b"payload".to_vec()));
+
+        // An empty payload is still a payload
+        let res = sandbox
+            .call_status_payload("EchoStatusWithPayload", (i32::MAX, Vec::<u8>::new()))
+            .unwrap();
+        assert_eq!(res, (i32::MAX, Vec::new()));
+    });
+}
+
+#[test]
+fn optional_return_value() {
+    let mut sandbox = new_rust_sandbox();
+    let res: Option<u32> = sandbox.call("FindByte", (vec![1u8, 2, 3], 3_u32)).unwrap();
+    assert_eq!(res, Some(2));
+    let res: Option<u32> = sandbox.call("FindByte", (vec![1u8, 2, 3], 4_u32)).unwrap();
+    assert_eq!(res, None);
+}
+
+#[test]
+fn float_array_round_trip() {
+    let mut sandbox = new_rust_sandbox();
+    let floats = vec![1.5f32, -0.0, f32::NAN, f32::INFINITY, f32::MIN_POSITIVE];
+    let res: Vec<f32> = sandbox.call("EchoFloats", floats.clone()).unwrap();
+    assert_eq!(
+        res.iter().map(|f| f.to_bits()).collect::<Vec<_>>(),
+        floats.iter().map(|f| f.to_bits()).collect::<Vec<_>>()
+    );
+
+    let doubles = vec![1.5f64, -0.0, f64::NAN, f64::NEG_INFINITY, f64::MAX];
+    let res: Vec<f64> = sandbox.call("EchoDoubles", doubles.clone()).unwrap();
+    assert_eq!(
+        res.iter().map(|d| d.to_bits()).collect::<Vec<_>>(),
+        doubles.iter().map(|d| d.to_bits()).collect::<Vec<_>>()
+    );
+
+    let res: Vec<f64> = sandbox.call("EchoDoubles", Vec::<f64>::new()).unwrap();
+    assert!(res.is_empty());
+}
+
 #[test]
 fn small_scratch_sandbox() {
     let mut cfg = SandboxConfiguration::default();
@@ -375,3 +517,339 @@ fn host_function_error() {
         }
     });
 }
+
+#[test]
+fn guest_handles_host_function_result() {
+    with_all_uninit_sandboxes(|mut sandbox| {
+        sandbox
+            .register("HostMethod1", |msg: String| -> Result<i32> {
+                if msg == "fail" {
+                    Err(new_error!("Host function error!"))
+                } else {
+                    Ok(msg.len() as i32)
+                }
+            })
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        let res: String = sandbox
+            .call("CallHostMethod1WithResult", "hello".to_string())
+            .unwrap();
+        assert_eq!(res, "Ok: 5");
+
+        // The host function failing is reported to the guest, which
+        // carries on without aborting or poisoning the sandbox
+        let res: String = sandbox
+            .call("CallHostMethod1WithResult", "fail".to_string())
+            .unwrap();
+        assert!(
+            res.contains("Host function error!"),
+            "unexpected result: {res}"
+        );
+        assert!(!sandbox.poisoned());
+    });
+}
+
+#[test]
+fn reentrant_guest_call() {
+    let mut sandbox = new_rust_uninit_sandbox();
+    sandbox
+        .register("HostMethod1", |msg: String| -> Result<i32> {
+            // Nested guest errors are returned to the host function
+            let res = reentrant::call_guest::<i32>("AddToStaticAndFail", ());
+            assert!(matches!(res, Err(HyperlightError::GuestError(..))));
+            reentrant::call_guest::<i32>("AddToStatic", msg.len() as i32)
+        })
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+
+    let res: String = sandbox
+        .call("CallHostMethod1WithResult", "hello".to_string())
+        .unwrap();
+    assert_eq!(res, "Ok: 15");
+
+    // The nested calls share the guest's state with the outer call
+    let res: i32 = sandbox.call("GetStatic", ()).unwrap();
+    assert_eq!(res, 15);
+    assert!(!sandbox.poisoned());
+
+    let res = reentrant::call_guest::<i32>("GetStatic", ());
+    assert!(matches!(
+        res,
+        Err(HyperlightError::ReentrantCallUnavailable)
+    ));
+}
+
+#[test]
+fn reentrant_guest_call_depth_limit() {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let max_depth = Arc::new(Mutex::new(0));
+
+    let mut sandbox = new_rust_uninit_sandbox();
+    let (errors_clone, max_depth_clone) = (errors.clone(), max_depth.clone());
+    sandbox
+        .register("HostMethod1", move |msg: String| -> Result<i32> {
+            let mut max_depth = max_depth_clone.lock().unwrap();
+            *max_depth = (*max_depth).max(reentrant::depth());
+            drop(max_depth);
+
+            // Recurse until the depth limit is reached
+            match reentrant::call_guest::<String>("CallHostMethod1WithResult", msg) {
+                Ok(res) => Ok(res.len() as i32),
+                Err(e) => {
+                    errors_clone.lock().unwrap().push(e.to_string());
+                    Err(e)
+                }
+            }
+        })
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+
+    sandbox
+        .call::<String>("CallHostMethod1WithResult", "hello".to_string())
+        .unwrap();
+    assert!(!sandbox.poisoned());
+
+    assert_eq!(*max_depth.lock().unwrap(), reentrant::MAX_REENTRANT_DEPTH);
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0].contains("maximum nesting depth"),
+        "unexpected error: {}",
+        errors[0]
+    );
+}
+
+#[test]
+fn linked_sandbox_call() {
+    let link = HostLink::new();
+    link.register_sandbox("echo", new_rust_sandbox()).unwrap();
+
+    with_c_uninit_sandbox(|mut sandbox| {
+        link.register_with(&mut sandbox).unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        let res: String = sandbox
+            .call("CallLinkedEcho", ("echo".to_string(), "hello".to_string()))
+            .unwrap();
+        assert_eq!(res, "hello");
+
+        let res = sandbox.call::<String>(
+            "CallLinkedEcho",
+            ("missing".to_string(), "hello".to_string()),
+        );
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(_, msg)) if msg.contains("missing")),
+            "unexpected result: {res:?}"
+        );
+    });
+}
+
+#[test]
+fn guest_verifies_host_function_signature() {
+    let mut sandbox = new_rust_uninit_sandbox();
+    sandbox.register("HostAdd", |a: i32, b: i32| a + b).unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+
+    let res: String = sandbox
+        .call("GetHostFunctionSignature", "HostAdd".to_string())
+        .unwrap();
+    assert_eq!(res, "[Int, Int] -> Int");
+
+    let res = sandbox.call::<String>("GetHostFunctionSignature", "Missing".to_string());
+    assert!(
+        matches!(&res, Err(HyperlightError::GuestError(ErrorCode::HostFunctionError, msg)) if msg.contains("Missing")),
+        "unexpected result: {res:?}"
+    );
+
+    let res = sandbox.call::<i32>("CallHostAddWithWrongTypes", ());
+    assert!(
+        matches!(&res, Err(HyperlightError::GuestError(ErrorCode::GuestFunctionParameterTypeMismatch, msg)) if msg.contains("parameter index 1 of host function HostAdd")),
+        "unexpected result: {res:?}"
+    );
+}
+
+#[test]
+fn guest_function_alias() {
+    with_all_uninit_sandboxes(|mut sandbox| {
+        sandbox.alias_guest_function("EchoAlias", "Echo").unwrap();
+        sandbox
+            .alias_guest_function("AliasToMissing", "FunctionDoesntExist")
+            .unwrap();
+        let mut sandbox = sandbox.evolve().unwrap();
+
+        let res: String = sandbox.call("EchoAlias", "hello".to_string()).unwrap();
+        assert_eq!(res, "hello");
+
+        // The aliased function can still be called by its own name
+        let res: String = sandbox.call("Echo", "hello".to_string()).unwrap();
+        assert_eq!(res, "hello");
+
+        // Errors name the function the alias resolved to
+        let res = sandbox.call::<i32>("AliasToMissing", ());
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestError(ErrorCode::GuestFunctionNotFound, msg)) if msg.contains("FunctionDoesntExist")),
+            "unexpected result: {res:?}"
+        );
+    });
+}
+
+#[test]
+fn malformed_guest_function_aliases_fail_init() {
+    with_all_uninit_sandboxes(|mut sandbox| {
+        sandbox
+            .register("GuestFunctionAliases", || {
+                Ok(b"AliasWithoutTarget\0".to_vec())
+            })
+            .unwrap();
+
+        let res = sandbox.evolve();
+        assert!(
+            matches!(&res, Err(HyperlightError::GuestAborted(_, msg)) if msg.contains("AliasWithoutTarget")),
+            "unexpected result: {:?}",
+            res.err()
+        );
+    });
+}
+
+#[test]
+fn guest_env() {
+    let mut sandbox = new_rust_uninit_sandbox();
+    sandbox
+        .set_guest_env(HashMap::from([
+            ("MODE".to_string(), "strict".to_string()),
+            ("LEVEL".to_string(), "3".to_string()),
+        ]))
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+
+    let mode: Option<String> = sandbox.call("GetEnv", "MODE".to_string()).unwrap();
+    assert_eq!(mode.as_deref(), Some("strict"));
+    let missing: Option<String> = sandbox.call("GetEnv", "MISSING".to_string()).unwrap();
+    assert_eq!(missing, None);
+    let names: String = sandbox.call("GetEnvNames", ()).unwrap();
+    assert_eq!(names, "LEVEL,MODE");
+
+    // A sandbox without an environment has no variables
+    let mut sandbox = new_rust_sandbox();
+    let mode: Option<String> = sandbox.call("GetEnv", "MODE".to_string()).unwrap();
+    assert_eq!(mode, None);
+    let names: String = sandbox.call("GetEnvNames", ()).unwrap();
+    assert_eq!(names, "");
+}
+
+#[test]
+fn guest_args() {
+    let mut sandbox = new_rust_uninit_sandbox();
+    #[cfg(unix)]
+    let not_utf8 = {
+        use std::os::unix::ffi::OsStringExt;
+        std::ffi::OsString::from_vec(vec![b'x', 0xff, 0, b'y'])
+    };
+    #[cfg(not(unix))]
+    let not_utf8 = std::ffi::OsString::from("x\u{0}y");
+    let expected = not_utf8.as_encoded_bytes().to_vec();
+    sandbox
+        .set_guest_args([
+            std::ffi::OsString::from("prog"),
+            std::ffi::OsString::new(),
+            not_utf8,
+        ])
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+
+    let argc: i32 = sandbox.call("GetArgc", ()).unwrap();
+    assert_eq!(argc, 3);
+    let arg: Option<Vec<u8>> = sandbox.call("GetArg", 0i32).unwrap();
+    assert_eq!(arg.as_deref(), Some(&b"prog"[..]));
+    let arg: Option<Vec<u8>> = sandbox.call("GetArg", 1i32).unwrap();
+    assert_eq!(arg.as_deref(), Some(&b""[..]));
+    let arg: Option<Vec<u8>> = sandbox.call("GetArg", 2i32).unwrap();
+    assert_eq!(arg, Some(expected));
+
+    // A sandbox without arguments has none
+    let mut sandbox = new_rust_sandbox();
+    let argc: i32 = sandbox.call("GetArgc", ()).unwrap();
+    assert_eq!(argc, 0);
+}
+
+#[test]
+fn unknown_host_call_policy() {
+    // By default the guest gets an error
+    let mut sandbox = new_rust_sandbox();
+    sandbox
+        .call::<()>("CallHostExpectError", "Missing".to_string())
+        .unwrap();
+
+    let mut sandbox = new_rust_uninit_sandbox();
+    sandbox
+        .set_unknown_host_call_policy(UnknownHostCallPolicy::ReturnDefault)
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+    let res: i64 = sandbox
+        .call(
+            "CallGivenParamlessHostFuncThatReturnsI64",
+            "Missing".to_string(),
+        )
+        .unwrap();
+    assert_eq!(res, 0);
+
+    let mut sandbox = new_rust_uninit_sandbox();
+    let called = Arc::new(Mutex::new(Vec::new()));
+    let called_clone = called.clone();
+    sandbox
+        .set_unknown_host_call_handler(move |name, _, return_type| {
+            called_clone.lock().unwrap().push(name.to_string());
+            assert_eq!(return_type, ReturnType::Long);
+            Ok(ReturnValue::Long(42))
+        })
+        .unwrap();
+    let mut sandbox = sandbox.evolve().unwrap();
+    let res: i64 = sandbox
+        .call(
+            "CallGivenParamlessHostFuncThatReturnsI64",
+            "Missing".to_string(),
+        )
+        .unwrap();
+    assert_eq!(res, 42);
+    // Built-in host functions are still answered by the sandbox
+    let names: String = sandbox.call("GetEnvNames", ()).unwrap();
+    assert_eq!(names, "");
+    assert_eq!(*called.lock().unwrap(), ["Missing"]);
+}
+
+#[test]
+fn guest_monotonic_clock() {
+    let mut sandbox = new_rust_sandbox();
+    let start = std::time::Instant::now();
+    let elapsed: u64 = sandbox.call("TimeSpin", 10_000_000u64).unwrap();
+    assert!(elapsed > 0);
+    assert!(elapsed <= start.elapsed().as_nanos() as u64);
+}
+
+#[test]
+fn call_with_callback() {
+    let mut sandbox = new_rust_sandbox();
+    let mut seen = Vec::new();
+    let res: i32 = sandbox
+        .call_with_callback("SumCallback", 4, |i: i32| {
+            seen.push(i);
+            i * i
+        })
+        .unwrap();
+    assert_eq!(res, 14);
+    assert_eq!(seen, vec![0, 1, 2, 3]);
+
+    // Errors returned by the callback are reported to the guest
+    let res = sandbox.call_with_callback::<i32, _, _>("SumCallback", 4, |i: i32| {
+        if i == 2 {
+            Err(new_error!("callback failed"))
+        } else {
+            Ok(i)
+        }
+    });
+    assert!(
+        matches!(&res, Err(HyperlightError::GuestError(ErrorCode::HostFunctionError, msg)) if msg.contains("callback failed")),
+        "unexpected result: {res:?}"
+    );
+}
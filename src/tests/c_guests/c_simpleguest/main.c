@@ -34,6 +34,8 @@ hl_Vec *set_byte_array_to_zero(const hl_FunctionCall* params) {
   return hl_flatbuffer_result_from_Bytes(x, input.len);
 }
 
+int add_with_default(int a, int b) { return a + b; }
+
 int print_output(const char *message) {
   int res = printf_f("%s", message);
   return res;
@@ -218,6 +220,27 @@ hl_Vec *get_size_prefixed_buffer(const hl_FunctionCall* params) {
   return hl_flatbuffer_result_from_Bytes(input.data, input.len);
 }
 
+// Copies parameter `index` into a buffer of `dst_len` bytes with
+// hl_param_bytes_copy, and returns what it returned
+hl_Vec *copy_bytes_param(const hl_FunctionCall* params) {
+  hl_Vec input = params->parameters[0].value.VecBytes;
+  int32_t index = params->parameters[1].value.Int;
+  int32_t dst_len = params->parameters[2].value.Int;
+  uint8_t *buffer = malloc(dst_len > 0 ? dst_len : 1);
+  intptr_t copied = hl_param_bytes_copy(params, index, buffer, dst_len);
+  HL_ASSERT(copied < 0 || memcmp(buffer, input.data, copied) == 0);
+  free(buffer);
+  return hl_flatbuffer_result_from_Int((int32_t)copied);
+}
+
+// Builds a result holding `value` and drops it instead of returning it, so
+// that debug builds of the C API report the leak
+hl_Vec *leak_result(const hl_FunctionCall* params) {
+  int32_t value = params->parameters[0].value.Int;
+  hl_flatbuffer_result_from_Int(value);
+  return hl_flatbuffer_result_from_Int(value);
+}
+
 int guest_abort_with_code(int32_t code) {
   hl_abort_with_code(code);
   return -1;
@@ -228,6 +251,26 @@ int guest_abort_with_msg(int32_t code, const char *message) {
   return -1;
 }
 
+int guest_assert(int32_t value) {
+  HL_ASSERT(value == 42);
+  return value;
+}
+
+static uint8_t panic_code = 0;
+
+uint8_t panic_handler(const char *message) {
+  (void)message;
+  return panic_code;
+}
+
+int guest_panic_with_handler(int32_t code) {
+  panic_code = (uint8_t)code;
+  hl_set_panic_handler(panic_handler);
+  // hl_set_error panics on a message that is not valid UTF-8
+  hl_set_error(hl_ErrorCode_GuestError, "\xff");
+  return -1;
+}
+
 int execute_on_stack(void) {
   uint8_t hlt = 0xF4;
   ((void (*)()) & hlt)();
@@ -239,12 +282,108 @@ int log_message(const char *message, int64_t level) {
   return -1;
 }
 
+int log_message_with_target(const char *target, const char *message, int64_t level) {
+  LOG_TARGET((hl_Level)level, target, message);
+  return -1;
+}
+
 hl_Vec *twenty_four_k_in_eight_k_out(const hl_FunctionCall* params) {
   hl_Vec input = params->parameters[0].value.VecBytes;
   assert(input.len == 24 * 1024);
   return hl_flatbuffer_result_from_Bytes(input.data, 8 * 1024);
 }
 
+hl_Vec *call_linked_echo(const hl_FunctionCall *params) {
+  const char *sandbox_name = params->parameters[0].value.String;
+  hl_Parameter param = {.tag = hl_ParameterType_String,
+                        .value = {.String = params->parameters[1].value.String}};
+  const hl_FunctionCall call = {.function_name = "Echo",
+                                .parameters = &param,
+                                .parameters_len = 1,
+                                .return_type = hl_ReturnType_String};
+  return hl_call_sandbox(sandbox_name, &call);
+}
+
+hl_Vec *add_optional(const hl_FunctionCall *params) {
+  int32_t a = params->parameters[0].value.Int;
+  int32_t b = hl_param_is_present(params, 1) ? params->parameters[1].value.Int : 0;
+  HL_ASSERT(!hl_param_is_present(params, 2));
+  return hl_flatbuffer_result_from_Int(a + b);
+}
+
+// Returns the first Int entry of the map for the key, or -1
+hl_Vec *lookup_map_int(const hl_FunctionCall *params) {
+  const hl_Parameter *map = &params->parameters[0];
+  const char *key = params->parameters[1].value.String;
+  HL_ASSERT(hl_param_map_len(map) >= 0);
+  const hl_Parameter *value = hl_param_map_get(map, key);
+  if (value == NULL || value->tag != hl_ParameterType_Int) {
+    return hl_flatbuffer_result_from_Int(-1);
+  }
+  return hl_flatbuffer_result_from_Int(value->value.Int);
+}
+
+// Returns the bytes before index `at` and the bytes from it as two buffers
+hl_Vec *split_buffer(const hl_FunctionCall *params) {
+  hl_Vec data = params->parameters[0].value.VecBytes;
+  int32_t at = params->parameters[1].value.Int;
+  size_t split = at < 0 ? 0 : (size_t)at > data.len ? data.len : (size_t)at;
+  const uint8_t *buffers[] = {data.data, data.data + split};
+  size_t lens[] = {split, data.len - split};
+  return hl_flatbuffer_result_from_buffers(buffers, lens, 2);
+}
+
+// Returns the status and payload it is passed
+hl_Vec *echo_status_with_payload(const hl_FunctionCall *params) {
+  int32_t code = params->parameters[0].value.Int;
+  hl_Vec data = params->parameters[1].value.VecBytes;
+  return hl_flatbuffer_result_from_status_payload(code, data.data, data.len);
+}
+
+// Returns status 1 early for odd values, and 0 otherwise
+hl_Vec *check_even(const hl_FunctionCall *params) {
+  if (params->parameters[0].value.Int % 2 != 0) {
+    return hl_return_status(1);
+  }
+  return hl_return_status(0);
+}
+
+// Divides the first Int by the second, failing with a domain error on
+// division by zero (0) or overflow (1)
+hl_Vec *checked_divide(const hl_FunctionCall *params) {
+  int32_t dividend = params->parameters[0].value.Int;
+  int32_t divisor = params->parameters[1].value.Int;
+  if (divisor == 0) {
+    return hl_return_domain_error(0, "division by zero");
+  }
+  if (dividend == INT32_MIN && divisor == -1) {
+    return hl_return_domain_error(1, NULL);
+  }
+  return hl_flatbuffer_result_from_Int(dividend / divisor);
+}
+
+static char host_result_message[256];
+
+const char *call_host_method_with_result(const char *from_host) {
+  hl_Parameter params = {.tag = hl_ParameterType_String,
+                         .value = {.String = from_host}};
+  const hl_FunctionCall host_call = {.function_name = "HostMethod1",
+                                     .parameters = &params,
+                                     .parameters_len = 1,
+                                     .return_type = hl_ReturnType_Int};
+  hl_HostResult *result = hl_call_host_function_with_result(&host_call);
+  if (hl_host_result_is_err(result)) {
+    // The message is freed along with the result, so copy it first
+    strncpy(host_result_message, hl_host_result_error(result),
+            sizeof(host_result_message) - 1);
+  } else {
+    snprintf(host_result_message, sizeof(host_result_message), "Ok: %d",
+             hl_host_result_as_Int(result));
+  }
+  hl_free_host_result(result);
+  return host_result_message;
+}
+
 int guest_function(const char *from_host) {
   char guest_message[256] = "Hello from GuestFunction1, ";
   int len = strlen(from_host);
@@ -337,9 +476,12 @@ HYPERLIGHT_WRAP_FUNCTION(guest_fn_checks_if_host_returns_double_value, Double, 2
 HYPERLIGHT_WRAP_FUNCTION(guest_fn_checks_if_host_returns_string_value, String, 0)
 HYPERLIGHT_WRAP_FUNCTION(guest_fn_checks_if_host_returns_bool_value, Bool, 2, Int, Int)
 HYPERLIGHT_WRAP_FUNCTION(echo, String, 1, String)
+HYPERLIGHT_WRAP_FUNCTION(add_with_default, Int, 2, Int, Int)
+static hl_Parameter add_with_default_defaults[] = {{.tag = hl_ParameterType_Int, .value = {.Int = 10}}};
 // HYPERLIGHT_WRAP_FUNCTION(set_byte_array_to_zero, 1, VecBytes) is not valid for functions that return VecBytes
 HYPERLIGHT_WRAP_FUNCTION(guest_function, Int, 1, String)
 HYPERLIGHT_WRAP_FUNCTION(print_output, Int, 1, String)
+HYPERLIGHT_WRAP_FUNCTION(call_host_method_with_result, String, 1, String)
 HYPERLIGHT_WRAP_FUNCTION(stack_allocate, Int, 1, Int)
 HYPERLIGHT_WRAP_FUNCTION(stack_overflow, Int, 1, Int)
 HYPERLIGHT_WRAP_FUNCTION(buffer_overrun, Int, 1, String)
@@ -363,8 +505,11 @@ HYPERLIGHT_WRAP_FUNCTION(set_static, Int, 0)
 // HYPERLIGHT_WRAP_FUNCTION(get_size_prefixed_buffer, Int, 1, VecBytes) is not valid for functions that return VecBytes
 HYPERLIGHT_WRAP_FUNCTION(guest_abort_with_msg, Int, 2, Int, String)
 HYPERLIGHT_WRAP_FUNCTION(guest_abort_with_code, Int, 1, Int)
+HYPERLIGHT_WRAP_FUNCTION(guest_assert, Int, 1, Int)
+HYPERLIGHT_WRAP_FUNCTION(guest_panic_with_handler, Int, 1, Int)
 HYPERLIGHT_WRAP_FUNCTION(execute_on_stack, Int, 0)
 HYPERLIGHT_WRAP_FUNCTION(log_message, Int, 2, String, Long)
+HYPERLIGHT_WRAP_FUNCTION(log_message_with_target, Int, 3, String, String, Long)
 // HYPERLIGHT_WRAP_FUNCTION(twenty_four_k_in_eight_k_out, VecBytes, 1, VecBytes) is not valid for functions that return VecBytes
 
 void hyperlight_main(void)
@@ -379,6 +524,7 @@ void hyperlight_main(void)
     hl_register_function_definition("SetByteArrayToZero", set_byte_array_to_zero, 1, (hl_ParameterType[]){hl_ParameterType_VecBytes}, hl_ReturnType_VecBytes);
     HYPERLIGHT_REGISTER_FUNCTION("GuestMethod1", guest_function);
     HYPERLIGHT_REGISTER_FUNCTION("PrintOutput", print_output);
+    HYPERLIGHT_REGISTER_FUNCTION("CallHostMethod1WithResult", call_host_method_with_result);
     HYPERLIGHT_REGISTER_FUNCTION("StackAllocate", stack_allocate);
     HYPERLIGHT_REGISTER_FUNCTION("StackOverflow", stack_overflow);
     HYPERLIGHT_REGISTER_FUNCTION("BufferOverrun", buffer_overrun);
@@ -402,13 +548,31 @@ void hyperlight_main(void)
     // HYPERLIGHT_REGISTER_FUNCTION macro does not work for functions that return VecBytes,
     // so we use hl_register_function_definition directly
     hl_register_function_definition("GetSizePrefixedBuffer", get_size_prefixed_buffer, 1, (hl_ParameterType[]){hl_ParameterType_VecBytes}, hl_ReturnType_VecBytes);
+    hl_register_function_definition("CopyBytesParam", copy_bytes_param, 3, (hl_ParameterType[]){hl_ParameterType_VecBytes, hl_ParameterType_Int, hl_ParameterType_Int}, hl_ReturnType_Int);
+    hl_register_function_definition("LeakResult", leak_result, 1, (hl_ParameterType[]){hl_ParameterType_Int}, hl_ReturnType_Int);
     HYPERLIGHT_REGISTER_FUNCTION("GuestAbortWithCode", guest_abort_with_code);
     HYPERLIGHT_REGISTER_FUNCTION("GuestAbortWithMessage", guest_abort_with_msg);
+    HYPERLIGHT_REGISTER_FUNCTION("GuestAssert", guest_assert);
+    HYPERLIGHT_REGISTER_FUNCTION("GuestPanicWithHandler", guest_panic_with_handler);
     HYPERLIGHT_REGISTER_FUNCTION("ExecuteOnStack", execute_on_stack);
     HYPERLIGHT_REGISTER_FUNCTION("LogMessage", log_message);
+    HYPERLIGHT_REGISTER_FUNCTION("LogMessageWithTarget", log_message_with_target);
     // HYPERLIGHT_REGISTER_FUNCTION macro does not work for functions that return VecBytes,
     // so we use hl_register_function_definition directly
     hl_register_function_definition("24K_in_8K_out", twenty_four_k_in_eight_k_out, 1, (hl_ParameterType[]){hl_ParameterType_VecBytes}, hl_ReturnType_VecBytes);
+    hl_register_function_definition("CallLinkedEcho", call_linked_echo, 2, (hl_ParameterType[]){hl_ParameterType_String, hl_ParameterType_String}, hl_ReturnType_String);
+    HL_ASSERT(HYPERLIGHT_REGISTER_FUNCTION_WITH_DEFAULTS("AddWithDefault", add_with_default, 1, add_with_default_defaults));
+    hl_register_function_definition("AddOptional", add_optional, 2, (hl_ParameterType[]){hl_ParameterType_Int, hl_ParameterType_Optional}, hl_ReturnType_Int);
+    hl_register_function_definition("LookupMapInt", lookup_map_int, 2, (hl_ParameterType[]){hl_ParameterType_Map, hl_ParameterType_String}, hl_ReturnType_Int);
+    hl_register_function_definition("CheckEven", check_even, 1, (hl_ParameterType[]){hl_ParameterType_Int}, hl_ReturnType_Int);
+    hl_register_function_definition("CheckedDivide", checked_divide, 2, (hl_ParameterType[]){hl_ParameterType_Int, hl_ParameterType_Int}, hl_ReturnType_Int);
+    hl_register_function_definition("SplitBuffer", split_buffer, 2, (hl_ParameterType[]){hl_ParameterType_VecBytes, hl_ParameterType_Int}, hl_ReturnType_MultiBuffer);
+    hl_register_function_definition("EchoStatusWithPayload", echo_status_with_payload, 2, (hl_ParameterType[]){hl_ParameterType_Int, hl_ParameterType_VecBytes}, hl_ReturnType_StatusWithPayload);
+
+    // Registering a name twice is rejected and leaves the first registration in place
+    uintptr_t registered = hl_registration_count();
+    HL_ASSERT(HYPERLIGHT_REGISTER_FUNCTION("Echo", print_output) == hl_ErrorCode_DuplicateGuestFunctionName);
+    HL_ASSERT(hl_registration_count() == registered);
 }
 
 // This dispatch function is only used when the host dispatches a guest function
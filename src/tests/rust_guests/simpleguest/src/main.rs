@@ -32,27 +32,35 @@ use alloc::{format, vec};
 use core::alloc::Layout;
 use core::ffi::c_char;
 use core::hint::black_box;
-use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use core::sync::atomic::{AtomicI32, AtomicU32, AtomicU64, Ordering};
 
 use hyperlight_common::flatbuffer_wrappers::function_call::{FunctionCall, FunctionCallType};
 use hyperlight_common::flatbuffer_wrappers::function_types::{
     ParameterType, ParameterValue, ReturnType, ReturnValue,
 };
-use hyperlight_common::flatbuffer_wrappers::guest_error::ErrorCode;
+use hyperlight_common::flatbuffer_wrappers::guest_error::{DomainError, ErrorCode};
 use hyperlight_common::flatbuffer_wrappers::guest_log_level::LogLevel;
 use hyperlight_common::flatbuffer_wrappers::util::get_flatbuffer_result;
 use hyperlight_common::log_level::GuestLogFilter;
 use hyperlight_common::vmem::{BasicMapping, MappingKind};
 use hyperlight_guest::error::{HyperlightGuestError, Result};
 use hyperlight_guest::exit::{abort_with_code, abort_with_code_and_message};
+use hyperlight_guest_bin::continuation::{Poll, suspend};
 use hyperlight_guest_bin::exception::arch::{Context, ExceptionInfo};
+use hyperlight_guest_bin::guest_features::{GuestFeatures, declare_application_features};
 use hyperlight_guest_bin::guest_function::definition::{GuestFunc, GuestFunctionDefinition};
 use hyperlight_guest_bin::guest_function::register::register_function;
+use hyperlight_guest_bin::guest_stream::open_stream;
 use hyperlight_guest_bin::host_comm::{
-    call_host_function, call_host_function_without_returning_result, get_host_return_value_raw,
-    print_output_with_host_print, read_n_bytes_from_user_memory,
+    call_host_callback, call_host_function, call_host_function_with_result,
+    call_host_function_without_returning_result, current_request_id, emit_counter, emit_event,
+    emit_gauge, get_host_return_value_raw, getenv, guest_args, guest_env_names,
+    host_function_signature, monotonic_ns, print_output_with_host_print, publish_partial_result,
+    read_n_bytes_from_user_memory, report_init_progress, signal_ready, verify_host_function_call,
 };
+use hyperlight_guest_bin::host_stream::HostStream;
 use hyperlight_guest_bin::memory::malloc;
+use hyperlight_guest_bin::shared_ring::shared_ring;
 use hyperlight_guest_bin::{GUEST_HANDLE, guest_function, guest_logger, host_function};
 // `log` is intentionally kept here: the LogMessage guest function exercises the
 // guest-side `log` crate path to verify that guests using `log` are still supported.
@@ -83,6 +91,11 @@ fn echo_double(value: f64) -> f64 {
     value
 }
 
+#[guest_function("DivideDouble")]
+fn divide_double(a: f64, b: f64) -> f64 {
+    core::hint::black_box(a) / core::hint::black_box(b)
+}
+
 // Test exception handler that validates stack layout and records invocation
 // It is designed to interact with the trigger_int3 breakpoint exception function below
 fn test_exception_handler(
@@ -204,6 +217,12 @@ fn print_using_printf(msg: String) -> i32 {
     print_output(msg)
 }
 
+#[guest_function("ReverseBytes")]
+fn reverse_bytes(mut data: Vec<u8>) -> Vec<u8> {
+    data.reverse();
+    data
+}
+
 #[guest_function("SetByteArrayToZero")]
 fn set_byte_array_to_zero(mut vec: Vec<u8>) -> Vec<u8> {
     vec.fill(0);
@@ -368,6 +387,15 @@ fn exhaust_heap() {
     panic!("function should have panicked before due to OOM")
 }
 
+#[guest_function("TouchPages")]
+fn touch_pages(pages: u64) -> u64 {
+    let mut buffer = vec![0u8; pages as usize * 4096];
+    for page in buffer.chunks_mut(4096) {
+        page[0] = 1;
+    }
+    black_box(buffer).len() as u64 / 4096
+}
+
 #[guest_function("MallocAndFree")]
 fn malloc_and_free(size: i32) -> i32 {
     let alloc_length = size.min(MAX_BUFFER_SIZE as i32);
@@ -387,6 +415,11 @@ fn get_size_prefixed_buffer(data: Vec<u8>) -> Vec<u8> {
     data
 }
 
+#[guest_function("ReturnBuffer")]
+fn return_buffer(size: u32) -> Vec<u8> {
+    vec![0xAB; size as usize]
+}
+
 #[expect(
     clippy::empty_loop,
     reason = "This function is used to keep the CPU busy"
@@ -398,6 +431,69 @@ fn spin() {
     }
 }
 
+/// Publishes `partial` as a partial result, then either returns `final_result`
+/// or spins forever so the host has to cancel the call.
+#[guest_function("PublishPartialResult")]
+fn publish_partial(partial: String, final_result: String, spin: bool) -> Result<String> {
+    publish_partial_result(partial)?;
+    while black_box(spin) {
+        // Keep the CPU busy until the host cancels the call
+    }
+    Ok(final_result)
+}
+
+/// Publishes each number below `count` as a partial result, then either
+/// returns `count` or spins forever so the host has to cancel the call.
+#[guest_function("PublishPartialResults")]
+fn publish_partials(count: u32, spin: bool) -> Result<u32> {
+    for i in 0..count {
+        publish_partial_result(i)?;
+    }
+    while black_box(spin) {
+        // Keep the CPU busy until the host cancels the call
+    }
+    Ok(count)
+}
+
+/// Pops every message the host pushed onto the shared ring and pushes it
+/// back, returning the number of messages echoed.
+#[guest_function("EchoSharedRing")]
+fn echo_shared_ring() -> Result<u32> {
+    let ring = shared_ring().ok_or_else(|| {
+        HyperlightGuestError::new(ErrorCode::GuestError, "No shared ring".to_string())
+    })?;
+    let mut echoed = 0;
+    while let Some(message) = ring
+        .pop()
+        .map_err(|e| HyperlightGuestError::new(ErrorCode::GuestError, e.to_string()))?
+    {
+        ring.push(&message)
+            .map_err(|e| HyperlightGuestError::new(ErrorCode::GuestError, e.to_string()))?;
+        echoed += 1;
+    }
+    Ok(echoed)
+}
+
+/// Reads the stream of the streaming host function `function_name`,
+/// called with `len`, stopping after `max_parts` parts if that is not zero.
+/// Returns a hash of what was read.
+#[guest_function("HashHostStream")]
+fn hash_host_stream(function_name: String, len: u64, max_parts: u32) -> Result<u64> {
+    let mut stream = HostStream::open(&function_name, (len,))?;
+    let mut hash = 0u64;
+    let mut parts = 0;
+    while let Some(part) = stream.read()? {
+        hash = part.iter().fold(hash, |hash, &b| {
+            hash.wrapping_mul(31).wrapping_add(b as u64)
+        });
+        parts += 1;
+        if parts == max_parts {
+            break;
+        }
+    }
+    Ok(hash)
+}
+
 /// Spins the CPU for approximately the specified number of milliseconds
 #[guest_function("SpinForMs")]
 fn spin_for_ms(milliseconds: u32) -> u64 {
@@ -527,8 +623,26 @@ core::arch::global_asm!(
     counter = sym TIMER_IRQ_COUNT,
 );
 
+/// Counter incremented by the handler for interrupts raised by the host.
+static RAISED_IRQ_COUNT: AtomicU32 = AtomicU32::new(0);
+
+// Handler for interrupts raised with `MultiUseSandbox::raise_guest_interrupt`,
+// identical to the timer handler but with its own counter.
+core::arch::global_asm!(
+    ".globl _raised_irq_handler",
+    "_raised_irq_handler:",
+    "push rax",
+    "lock inc dword ptr [rip + {counter}]",
+    "mov al, 0x20",
+    "out 0x20, al",
+    "pop rax",
+    "iretq",
+    counter = sym RAISED_IRQ_COUNT,
+);
+
 unsafe extern "C" {
     fn _timer_irq_handler();
+    fn _raised_irq_handler();
 }
 
 /// IDT pointer structure for SIDT/LIDT instructions.
@@ -538,6 +652,78 @@ struct IdtPtr {
     base: u64,
 }
 
+/// Install a 16-byte interrupt gate for `vector` pointing to `handler_addr`
+/// in the current IDT. Returns `false` if the IDT is too small.
+fn install_idt_entry(vector: usize, handler_addr: u64) -> bool {
+    // Read current IDT base via SIDT
+    let mut idtr = IdtPtr { limit: 0, base: 0 };
+    unsafe {
+        core::arch::asm!(
+            "sidt [{}]",
+            in(reg) &mut idtr as *mut IdtPtr,
+            options(nostack, preserves_flags)
+        );
+    }
+
+    // Ensure the IDT is large enough for the 16-byte entry.
+    let required_end = (vector + 1) * 16; // byte just past the entry
+    if (idtr.limit as usize + 1) < required_end {
+        return false;
+    }
+
+    let entry_ptr = (idtr.base as usize + vector * 16) as *mut u8;
+    unsafe {
+        // offset_low (bits 0-15 of handler)
+        core::ptr::write_volatile(entry_ptr as *mut u16, handler_addr as u16);
+        // selector: 0x08 = kernel code segment
+        core::ptr::write_volatile(entry_ptr.add(2) as *mut u16, 0x08);
+        // IST=0, reserved=0
+        core::ptr::write_volatile(entry_ptr.add(4), 0);
+        // type_attr: 0x8E = interrupt gate, present, DPL=0
+        core::ptr::write_volatile(entry_ptr.add(5), 0x8E);
+        // offset_mid (bits 16-31)
+        core::ptr::write_volatile(entry_ptr.add(6) as *mut u16, (handler_addr >> 16) as u16);
+        // offset_high (bits 32-63)
+        core::ptr::write_volatile(entry_ptr.add(8) as *mut u32, (handler_addr >> 32) as u32);
+        // reserved
+        core::ptr::write_volatile(entry_ptr.add(12) as *mut u32, 0);
+    }
+
+    // Ensure the IDT writes are visible before enabling interrupts.
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+    true
+}
+
+/// Test delivery of an interrupt raised by the host.
+///
+/// Installs `_raised_irq_handler` for `vector`, enables interrupts and
+/// busy-waits for up to `max_spin` iterations for the interrupt, which the
+/// host raised before this call. Returns the number of interrupts received,
+/// or -1 if the handler could not be installed.
+#[guest_function("CountRaisedInterrupts")]
+fn count_raised_interrupts(vector: i32, max_spin: i32) -> i32 {
+    RAISED_IRQ_COUNT.store(0, Ordering::SeqCst);
+
+    if !install_idt_entry(vector as usize, _raised_irq_handler as *const () as u64) {
+        return -1;
+    }
+
+    unsafe {
+        core::arch::asm!("sti", options(nomem, nostack));
+    }
+    for _ in 0..max_spin as u32 {
+        if RAISED_IRQ_COUNT.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        core::hint::spin_loop();
+    }
+    unsafe {
+        core::arch::asm!("cli", options(nomem, nostack));
+    }
+
+    RAISED_IRQ_COUNT.load(Ordering::SeqCst) as i32
+}
+
 /// Test hardware timer interrupt delivery.
 ///
 /// This function:
@@ -576,48 +762,10 @@ fn test_timer_interrupts(period_us: i32, max_spin: i32) -> i32 {
     }
 
     // 2) Install IDT entry for vector 0x20 (timer interrupt)
-    let handler_addr = _timer_irq_handler as *const () as u64;
-
-    // Read current IDT base via SIDT
-    let mut idtr = IdtPtr { limit: 0, base: 0 };
-    unsafe {
-        core::arch::asm!(
-            "sidt [{}]",
-            in(reg) &mut idtr as *mut IdtPtr,
-            options(nostack, preserves_flags)
-        );
-    }
-
-    // Vector 0x20 needs bytes at offset 0x200..0x210 (16-byte entry).
-    // Ensure the IDT is large enough.
-    const VECTOR: usize = 0x20;
-    let required_end = (VECTOR + 1) * 16; // byte just past the entry
-    if (idtr.limit as usize + 1) < required_end {
+    if !install_idt_entry(0x20, _timer_irq_handler as *const () as u64) {
         return -1; // IDT too small
     }
 
-    // Write a 16-byte IDT entry at vector 0x20 (offset = 0x20 * 16 = 0x200)
-    let entry_ptr = (idtr.base as usize + VECTOR * 16) as *mut u8;
-    unsafe {
-        // offset_low (bits 0-15 of handler)
-        core::ptr::write_volatile(entry_ptr as *mut u16, handler_addr as u16);
-        // selector: 0x08 = kernel code segment
-        core::ptr::write_volatile(entry_ptr.add(2) as *mut u16, 0x08);
-        // IST=0, reserved=0
-        core::ptr::write_volatile(entry_ptr.add(4), 0);
-        // type_attr: 0x8E = interrupt gate, present, DPL=0
-        core::ptr::write_volatile(entry_ptr.add(5), 0x8E);
-        // offset_mid (bits 16-31)
-        core::ptr::write_volatile(entry_ptr.add(6) as *mut u16, (handler_addr >> 16) as u16);
-        // offset_high (bits 32-63)
-        core::ptr::write_volatile(entry_ptr.add(8) as *mut u32, (handler_addr >> 32) as u32);
-        // reserved
-        core::ptr::write_volatile(entry_ptr.add(12) as *mut u32, 0);
-    }
-
-    // Ensure the IDT writes are visible before enabling interrupts.
-    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
-
     // 3) Program PIT channel 0 as rate generator (mode 2).
     //    Divisor = period_us * 1_193_182 / 1_000_000 (PIT oscillator is 1.193182 MHz).
     //    On KVM the in-kernel PIT handles these IO writes directly.
@@ -681,6 +829,185 @@ fn get_static() -> i32 {
     unsafe { COUNTER }
 }
 
+/// Work started by `StartWork` that has not been finished yet.
+static PENDING_WORK: AtomicI32 = AtomicI32::new(0);
+/// Work finished when the host quiesced the guest.
+static FINISHED_WORK: AtomicI32 = AtomicI32::new(0);
+
+#[guest_function("StartWork")]
+fn start_work(amount: i32) {
+    PENDING_WORK.fetch_add(amount, Ordering::SeqCst);
+}
+
+// Called by the host before it takes a snapshot
+#[guest_function("__hyperlight_quiesce")]
+fn quiesce() {
+    let pending = PENDING_WORK.swap(0, Ordering::SeqCst);
+    FINISHED_WORK.fetch_add(pending, Ordering::SeqCst);
+}
+
+#[guest_function("GetWork")]
+fn get_work() -> Vec<u8> {
+    [
+        PENDING_WORK.load(Ordering::SeqCst),
+        FINISHED_WORK.load(Ordering::SeqCst),
+    ]
+    .iter()
+    .flat_map(|work| work.to_le_bytes())
+    .collect()
+}
+
+#[guest_function("GetStaticAddress")]
+fn get_static_address() -> u64 {
+    core::ptr::addr_of!(COUNTER) as u64
+}
+
+#[guest_function("AddOptional")]
+fn add_optional(a: i32, b: Option<i32>) -> i32 {
+    a + b.unwrap_or(0)
+}
+
+#[guest_function("LookupMapInt")]
+fn lookup_map_int(map: Vec<(String, ParameterValue)>, key: String) -> i32 {
+    map.into_iter()
+        .find(|(name, _)| *name == key)
+        .and_then(|(_, value)| i32::try_from(value).ok())
+        .unwrap_or(-1)
+}
+
+#[guest_function("FindByte")]
+fn find_byte(haystack: Vec<u8>, needle: u32) -> Option<u32> {
+    haystack
+        .iter()
+        .position(|&b| u32::from(b) == needle)
+        .map(|i| i as u32)
+}
+
+#[guest_function("EchoFloats")]
+fn echo_floats(values: Vec<f32>) -> Vec<f32> {
+    values
+}
+
+#[guest_function("EchoDoubles")]
+fn echo_doubles(values: Vec<f64>) -> Vec<f64> {
+    values
+}
+
+#[guest_function("SplitBuffer")]
+fn split_buffer(mut data: Vec<u8>, at: i32) -> Vec<Vec<u8>> {
+    let rest = data.split_off((at.max(0) as usize).min(data.len()));
+    vec![data, rest]
+}
+
+#[guest_function("SignalReady")]
+fn signal_ready_now() -> Result<()> {
+    signal_ready()
+}
+
+#[guest_function("EchoStatusWithPayload")]
+fn echo_status_with_payload(code: i32, data: Vec<u8>) -> (i32, Vec<u8>) {
+    (code, data)
+}
+
+#[guest_function("GetRequestId")]
+fn get_request_id() -> Result<Option<String>> {
+    current_request_id()
+}
+
+#[guest_function("GetEnv")]
+fn get_env(name: String) -> Result<Option<String>> {
+    getenv(&name)
+}
+
+#[guest_function("GetArgc")]
+fn get_argc() -> Result<i32> {
+    Ok(guest_args()?.len() as i32)
+}
+
+#[guest_function("GetArg")]
+fn get_arg(index: i32) -> Result<Option<Vec<u8>>> {
+    Ok(guest_args()?.into_iter().nth(index as usize))
+}
+
+#[guest_function("GetEnvNames")]
+fn get_env_names() -> Result<String> {
+    Ok(guest_env_names()?.join(","))
+}
+
+#[guest_function("TimeSpin")]
+fn time_spin(iterations: u64) -> Result<u64> {
+    let start = monotonic_ns()?;
+    for i in 0..iterations {
+        core::hint::black_box(i);
+    }
+    Ok(monotonic_ns()? - start)
+}
+
+#[guest_function("StreamChunks")]
+fn stream_chunks(data: Vec<u8>, chunk_size: i32) -> u64 {
+    let chunks: Vec<Vec<u8>> = data
+        .chunks(chunk_size.max(1) as usize)
+        .map(<[u8]>::to_vec)
+        .collect();
+    open_stream(chunks.into_iter())
+}
+
+/// Concatenates the results of `count` suspensions, waiting for each from
+/// the host in turn.
+#[guest_function("AwaitConcat")]
+fn await_concat(count: i32) -> u64 {
+    suspend(move |first| Ok(concat_rest(first, count - 1)))
+}
+
+fn concat_rest(acc: Vec<u8>, remaining: i32) -> Poll {
+    if remaining <= 0 {
+        return Poll::Ready(acc);
+    }
+    Poll::Pending(Box::new(move |next: Vec<u8>| {
+        if next.is_empty() {
+            return Err(HyperlightGuestError::new(
+                ErrorCode::GuestError,
+                "AwaitConcat was resumed with nothing".to_string(),
+            ));
+        }
+        let mut acc = acc;
+        acc.extend_from_slice(&next);
+        Ok(concat_rest(acc, remaining - 1))
+    }))
+}
+
+/// Suspends `slices` times, or once if `slices` is 0, ignoring what it is
+/// resumed with, and returns the number of times it was resumed.
+#[guest_function("CountSlices")]
+fn count_slices(slices: u32) -> u64 {
+    suspend(move |_| Ok(count_rest(1, slices)))
+}
+
+fn count_rest(resumed: u32, slices: u32) -> Poll {
+    if resumed >= slices {
+        return Poll::Ready(resumed.to_le_bytes().to_vec());
+    }
+    Poll::Pending(Box::new(move |_| Ok(count_rest(resumed + 1, slices))))
+}
+
+// Raw code for `MultiUseSandbox::run_at`, outside of the function call ABI:
+// adds rdi and rsi into rax and halts.
+core::arch::global_asm!(
+    ".globl _raw_add",
+    "_raw_add:",
+    "lea rax, [rdi + rsi]",
+    "hlt",
+);
+
+unsafe extern "C" {
+    fn _raw_add();
+}
+
+#[guest_function("RawAddAddress")]
+fn raw_add_address() -> u64 {
+    _raw_add as *const () as u64
+}
+
 #[guest_function("AddToStaticAndFail")]
 fn add_to_static_and_fail() -> Result<i32> {
     unsafe { COUNTER += 10 };
@@ -727,6 +1054,58 @@ fn add(a: i32, b: i32) -> Result<i32> {
     host_add(a, b)
 }
 
+#[guest_function("GetHostFunctionSignature")]
+fn get_host_function_signature(name: String) -> Result<String> {
+    let definition = host_function_signature(&name)?;
+    Ok(format!(
+        "{:?} -> {:?}",
+        definition.parameter_types.unwrap_or_default(),
+        definition.return_type
+    ))
+}
+
+#[inline(never)]
+extern "C" fn rewritten_code() -> u64 {
+    42
+}
+
+/// Writes to the guest's own code and then runs it, returning the address
+/// of the code that was written.
+#[guest_function("RunRewrittenCode")]
+fn run_rewritten_code() -> u64 {
+    let code = rewritten_code as *mut u8;
+    // Writing the first byte back unchanged is enough for the page to have
+    // been written to
+    unsafe { code.write_volatile(code.read_volatile()) };
+    let f = core::hint::black_box(rewritten_code as extern "C" fn() -> u64);
+    assert_eq!(f(), 42);
+    code as u64
+}
+
+#[guest_function("FillFromHost")]
+fn fill_from_host(len: i32) -> Result<Vec<u8>> {
+    let mut buffer = vec![0u8; len as usize];
+    call_host_function::<()>(
+        "FillBuffer",
+        Some(vec![
+            ParameterValue::ULong(buffer.as_mut_ptr() as u64),
+            ParameterValue::Int(len),
+        ]),
+        ReturnType::Void,
+    )?;
+    Ok(buffer)
+}
+
+#[guest_function("CallHostAddWithWrongTypes")]
+fn call_host_add_with_wrong_types() -> Result<i32> {
+    let parameters = vec![
+        ParameterValue::Int(1),
+        ParameterValue::String("2".to_string()),
+    ];
+    verify_host_function_call("HostAdd", &parameters, ReturnType::Int)?;
+    call_host_function::<i32>("HostAdd", Some(parameters), ReturnType::Int)
+}
+
 // Does nothing, but used for testing large parameters
 #[guest_function("LargeParameters")]
 fn large_parameters(v: Vec<u8>, s: String) {
@@ -775,6 +1154,33 @@ fn read_mapped_buffer(base: u64, len: u64, do_map: bool) -> Vec<u8> {
     data.to_vec()
 }
 
+/// Atomically adds 1 to the counter at `base`, `times` times, mapping
+/// the page it is on first, and returns the counter's final value as this
+/// guest saw it.
+#[guest_function("AtomicIncrementMapped")]
+fn atomic_increment_mapped(base: u64, times: u64) -> u64 {
+    unsafe {
+        hyperlight_guest_bin::paging::map_region(
+            base,
+            base as _,
+            4096,
+            MappingKind::Basic(BasicMapping {
+                readable: true,
+                writable: true,
+                executable: false,
+            }),
+        );
+        hyperlight_guest_bin::paging::barrier::first_valid_same_ctx();
+    }
+
+    let counter = unsafe { core::sync::atomic::AtomicU64::from_ptr(base as *mut u64) };
+    let mut last = 0;
+    for _ in 0..times {
+        last = counter.fetch_add(1, core::sync::atomic::Ordering::SeqCst) + 1;
+    }
+    last
+}
+
 #[guest_function("CheckMapped")]
 fn check_mapped_buffer(base: u64) -> bool {
     hyperlight_guest_bin::paging::virt_to_phys(base)
@@ -860,6 +1266,7 @@ fn call_host_expect_error(hostfuncname: String) -> Result<()> {
 #[hyperlight_guest_bin::main]
 #[instrument(skip_all, parent = Span::current(), level= "Trace")]
 fn main() {
+    report_init_progress(0, 2, "registering functions").expect("progress should be reported");
     let print_output_def = GuestFunctionDefinition::<GuestFunc>::new(
         "PrintOutputWithHostPrint".to_string(),
         Vec::from(&[ParameterType::String]),
@@ -867,6 +1274,21 @@ fn main() {
         print_output_with_host_print,
     );
     register_function(print_output_def);
+    report_init_progress(1, 2, "registering functions").expect("progress should be reported");
+
+    let add_with_default_def = GuestFunctionDefinition::<GuestFunc>::from_fn(
+        "AddWithDefault".to_string(),
+        add_with_default,
+    )
+    .with_defaults(vec![ParameterValue::Int(10)])
+    .expect("default values should match the parameter types");
+    register_function(add_with_default_def);
+    declare_application_features(GuestFeatures::application(0));
+    report_init_progress(2, 2, "ready").expect("progress should be reported");
+}
+
+fn add_with_default(a: i32, b: i32) -> i32 {
+    a + b
 }
 
 #[host_function("HostMethod")]
@@ -887,6 +1309,20 @@ fn guest_function1(message: String) -> Result<i32> {
     host_method_1(message)
 }
 
+#[guest_function("CallHostMethod1WithResult")]
+fn call_host_method_1_with_result(message: String) -> Result<String> {
+    let result = call_host_function_with_result(
+        "HostMethod1",
+        Some(vec![ParameterValue::String(message)]),
+        ReturnType::Int,
+    )?;
+    Ok(match result {
+        Ok(ReturnValue::Int(value)) => format!("Ok: {value}"),
+        Ok(value) => format!("Unexpected return value: {value:?}"),
+        Err(e) => e.message,
+    })
+}
+
 #[guest_function("GuestMethod2")]
 fn guest_function2(message: String) -> Result<i32> {
     let message = format!("Hello from GuestFunction2, {}", message);
@@ -943,6 +1379,35 @@ fn call_host_spin() -> Result<()> {
     host_spin()
 }
 
+#[guest_function("SumCallback")]
+fn sum_callback(count: i32, callback: u64) -> Result<i32> {
+    let mut sum = 0;
+    for i in 0..count {
+        sum += call_host_callback::<i32>(callback, i)?;
+    }
+    Ok(sum)
+}
+
+#[guest_function("EmitMetrics")]
+fn emit_metrics(hits: u64, hit_ratio: f64) -> Result<()> {
+    emit_counter("cache_hits", hits)?;
+    emit_gauge("hit_ratio", hit_ratio)?;
+    Ok(())
+}
+
+/// Emits `count` "tick" events whose payload is their index, and returns
+/// how many of them the host queued.
+#[guest_function("EmitEvents")]
+fn emit_events(count: u32) -> Result<u32> {
+    let mut queued = 0;
+    for i in 0..count {
+        if emit_event("tick", &i.to_le_bytes())? {
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
 #[guest_function("HostCallLoop")]
 fn host_call_loop(host_func_name: String) -> Result<Vec<u8>> {
     loop {
@@ -977,6 +1442,18 @@ fn fuzz_guest_trace(max_depth: u32, msg: String) -> u32 {
     fuzz_traced_function(0, max_depth, &msg)
 }
 
+// Divides `dividend` by `divisor`, failing with a domain error on division
+// by zero (0) or overflow (1)
+#[guest_function("CheckedDivide")]
+fn checked_divide(dividend: i32, divisor: i32) -> Result<i32> {
+    if divisor == 0 {
+        return Err(DomainError::new(0, Some("division by zero".to_string())).into());
+    }
+    Ok(dividend
+        .checked_div(divisor)
+        .ok_or(DomainError::new(1, None))?)
+}
+
 #[guest_function("CorruptOutputSizePrefix")]
 fn corrupt_output_size_prefix() -> i32 {
     unsafe {
@@ -1064,6 +1541,19 @@ fn fuzz_host_function(func: FunctionCall) -> Result<Vec<u8>> {
             ReturnValue::Bool(bool) => Ok(get_flatbuffer_result(bool)),
             ReturnValue::Void(()) => Ok(get_flatbuffer_result(())),
             ReturnValue::VecBytes(byte) => Ok(get_flatbuffer_result(byte.as_slice())),
+            ReturnValue::VecFloat(v) => Ok(get_flatbuffer_result(v.as_slice())),
+            ReturnValue::VecDouble(v) => Ok(get_flatbuffer_result(v.as_slice())),
+            ReturnValue::MultiBuffer(v) => {
+                let buffers: Vec<&[u8]> = v.iter().map(Vec::as_slice).collect();
+                Ok(get_flatbuffer_result(buffers.as_slice()))
+            }
+            ReturnValue::StatusWithPayload { code, data } => {
+                Ok(get_flatbuffer_result((code, data.as_slice())))
+            }
+            ReturnValue::Optional(_) => Err(HyperlightGuestError::new(
+                ErrorCode::UnsupportedParameterType,
+                "Optional host return values are not fuzzed".to_string(),
+            )),
         },
         Err(e) => Err(e),
     }